@@ -0,0 +1,108 @@
+#!/usr/bin/env python
+
+# A performance sanity check to run before submitting a change to a scheme. It runs a small
+# matrix of map benchmarks and compares the schemes against each other, printing PASS or FAIL for
+# each comparison. It exits with 1 if any benchmark or comparison fails.
+
+import subprocess
+import os, argparse, csv
+from collections import defaultdict
+
+RESULTS_PATH = os.path.join(os.path.dirname(os.path.realpath(__file__)), "results")
+BIN_PATH = os.path.join(os.path.dirname(os.path.realpath(__file__)), "..", "..", "target", "release")
+
+mms = ['nr', 'ebr', 'pebr', 'hp', 'hp-pp', 'hp-brcu', 'hp-rcu', 'nbr', 'vbr',
+       'circ-ebr', 'circ-hp', 'cdrc-ebr', 'cdrc-hp']
+
+cpu_count = os.cpu_count() or 2
+
+PROFILES = {
+    # A matrix for CI-sized machines, which takes a few minutes.
+    'quick': {
+        'dss': ['hash-map', 'nm-tree'],
+        'ts': sorted({1, max(1, cpu_count // 2)}),
+        'i': 3,
+        'g': 1,
+        'kr': 100000,
+    },
+}
+
+parser = argparse.ArgumentParser()
+parser.add_argument("-p", "--profile", dest="profile", choices=PROFILES.keys(), default="quick",
+                    help="the matrix of benchmarks to run")
+parser.add_argument("-s", "--slack", dest="slack", type=float, default=0.2,
+                    help="the fraction of the throughput of NR by which another scheme may exceed it")
+args = parser.parse_args()
+profile = PROFILES[args.profile]
+
+subprocess.run(['cargo', 'build', '--release'])
+
+os.makedirs(RESULTS_PATH, exist_ok=True)
+output = os.path.join(RESULTS_PATH, f'{args.profile}.csv')
+if os.path.exists(output):
+    os.remove(output)
+
+cmds = []
+for ds in profile['dss']:
+    for mm in mms:
+        for t in profile['ts']:
+            cmds.append([os.path.join(BIN_PATH, mm),
+                         '-i', str(profile['i']),
+                         '-d', ds,
+                         '-g', str(profile['g']),
+                         '-t', str(t),
+                         '-r', str(profile['kr']),
+                         '-o', output])
+
+print('number of configurations: ', len(cmds))
+print('estimated time: ', int(len(cmds) * profile['i'] * 1.5) // 60, ' min')
+
+failed = []
+for i, cmd in enumerate(cmds):
+    print("bench {}/{}: '{}'".format(i + 1, len(cmds), ' '.join(cmd)))
+    try:
+        if subprocess.run(cmd, timeout=profile['i'] * 10 + 30).returncode != 0:
+            failed.append(' '.join(cmd))
+    except subprocess.TimeoutExpired:
+        print("timeout")
+        failed.append(' '.join(cmd))
+
+# (ds, threads) -> mm -> throughput
+throughputs = defaultdict(dict)
+if os.path.exists(output):
+    with open(output) as f:
+        for row in csv.DictReader(f):
+            throughputs[(row['ds'], int(row['threads']))][row['mm']] = int(row['throughput'])
+
+checks = []
+
+def check(passed, text):
+    checks.append(passed)
+    print(f"{'PASS' if passed else 'FAIL'}: {text}")
+
+print("\n====sanity====")
+check(len(throughputs) > 0, "the benchmarks recorded their results")
+for cmd in failed:
+    check(False, f"'{cmd}' did not finish")
+for (ds, t), results in sorted(throughputs.items()):
+    for mm, throughput in sorted(results.items()):
+        check(throughput > 0, f"{mm} makes progress on {ds} with {t} threads ({throughput} ops/s)")
+    # NR never reclaims, so no scheme should be faster than it beyond the noise.
+    nr = results.get('nr')
+    if nr is None:
+        continue
+    for mm, throughput in sorted(results.items()):
+        if mm == 'nr':
+            continue
+        check(throughput <= nr * (1 + args.slack),
+              f"nr ({nr} ops/s) >= {mm} ({throughput} ops/s) on {ds} with {t} threads")
+for ds in profile['dss']:
+    lo, hi = profile['ts'][0], profile['ts'][-1]
+    nr_lo = throughputs[(ds, lo)].get('nr')
+    nr_hi = throughputs[(ds, hi)].get('nr')
+    if lo == hi or nr_lo is None or nr_hi is None:
+        continue
+    check(nr_hi >= nr_lo, f"nr scales on {ds} from {lo} ({nr_lo} ops/s) to {hi} threads ({nr_hi} ops/s)")
+
+print(f"\n{checks.count(True)} passed, {checks.count(False)} failed")
+exit(0 if all(checks) else 1)
@@ -0,0 +1,69 @@
+"""GDB pretty-printers for the tagged pointers of EBR and PEBR.
+
+The forks of crossbeam-epoch store a tag in the low bits of the address of `Atomic<T>`,
+`Shared<T>`, and `Owned<T>`, which GDB otherwise prints as a plain integer. These printers split
+the word into the address and the tag, and expand a non-null pointer to its target.
+
+The script is embedded in the debug info of the crate, so GDB loads it with a binary that is in
+its `auto-load safe-path`. Otherwise, load it with `source debugger/smr_benchmark_gdb.py`.
+"""
+
+import re
+
+import gdb
+import gdb.printing
+
+TAGGED_POINTER = re.compile(r"^(crossbeam_\w+)::(\w+::)*(Atomic|Shared|Owned)<(.+)>$")
+
+
+def first_scalar(value):
+    """Returns the integer of the innermost field, e.g., of `AtomicUsize` or `AtomicPtr`."""
+    while value.type.strip_typedefs().code == gdb.TYPE_CODE_STRUCT:
+        fields = value.type.strip_typedefs().fields()
+        if not fields:
+            raise gdb.GdbError("no scalar field in {}".format(value.type))
+        value = value[fields[0]]
+    return int(value.cast(gdb.lookup_type("usize")))
+
+
+def target_type(value):
+    """Returns the type of the pointee, which is the (only) generic parameter of the pointer."""
+    try:
+        return value.type.strip_typedefs().template_argument(0)
+    except (RuntimeError, gdb.error):
+        match = TAGGED_POINTER.match(value.type.strip_typedefs().name)
+        return gdb.lookup_type(match.group(4))
+
+
+class TaggedPointerPrinter:
+    def __init__(self, value):
+        self.value = value
+        self.target = target_type(value)
+        data = value["data"]
+        raw = first_scalar(data)
+        align = getattr(self.target, "alignof", 8) or 8
+        self.tag = raw & (align - 1)
+        self.address = raw & ~(align - 1)
+
+    def to_string(self):
+        if self.address == 0:
+            text = "null"
+        else:
+            text = "0x{:x}".format(self.address)
+        if self.tag != 0:
+            text += " [tag {}]".format(self.tag)
+        return text
+
+    def children(self):
+        if self.address != 0:
+            pointer = gdb.Value(self.address).cast(self.target.pointer())
+            yield "*", pointer.dereference()
+
+
+def build_printer():
+    printer = gdb.printing.RegexpCollectionPrettyPrinter("smr_benchmark")
+    printer.add_printer("tagged pointer", TAGGED_POINTER.pattern, TaggedPointerPrinter)
+    return printer
+
+
+gdb.printing.register_pretty_printer(gdb.current_objfile(), build_printer(), replace=True)
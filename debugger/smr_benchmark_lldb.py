@@ -0,0 +1,78 @@
+"""LLDB formatters for the tagged pointers of EBR and PEBR.
+
+The forks of crossbeam-epoch store a tag in the low bits of the address of `Atomic<T>`,
+`Shared<T>`, and `Owned<T>`. These formatters split the word into the address and the tag, and
+expand a non-null pointer to its target.
+
+Load them with `command script import debugger/smr_benchmark_lldb.py`.
+"""
+
+import lldb
+
+TAGGED_POINTER = r"^crossbeam_[a-z_]+::([a-z_]+::)*(Atomic|Shared|Owned)<.+>$"
+
+
+def first_scalar(value):
+    """Returns the integer of the innermost field, e.g., of `AtomicUsize` or `AtomicPtr`."""
+    while value.GetNumChildren() > 0 and not value.GetType().IsPointerType():
+        value = value.GetChildAtIndex(0)
+    return value.GetValueAsUnsigned()
+
+
+def decompose(value):
+    target = value.GetType().GetTemplateArgumentType(0)
+    align = 8
+    if target.IsValid():
+        # LLDB does not expose the alignment, so the largest power of two dividing the size
+        # approximates it.
+        size = target.GetByteSize()
+        if size > 0:
+            align = size & -size
+    raw = first_scalar(value.GetChildMemberWithName("data"))
+    return target, raw & ~(align - 1), raw & (align - 1)
+
+
+def tagged_pointer_summary(value, _dict):
+    _, address, tag = decompose(value.GetNonSyntheticValue())
+    text = "null" if address == 0 else "0x{:x}".format(address)
+    if tag != 0:
+        text += " [tag {}]".format(tag)
+    return text
+
+
+class TaggedPointerProvider:
+    def __init__(self, value, _dict):
+        self.value = value
+        self.update()
+
+    def update(self):
+        self.target, self.address, self.tag = decompose(self.value)
+        return False
+
+    def num_children(self):
+        return 1 if self.address != 0 and self.target.IsValid() else 0
+
+    def get_child_index(self, name):
+        return 0 if name == "*" else -1
+
+    def get_child_at_index(self, index):
+        if index != 0 or self.num_children() == 0:
+            return None
+        return self.value.CreateValueFromAddress("*", self.address, self.target)
+
+    def has_children(self):
+        return self.num_children() > 0
+
+
+def __lldb_init_module(debugger, _dict):
+    debugger.HandleCommand(
+        'type summary add -x "{}" -F {}.tagged_pointer_summary -w smr_benchmark'.format(
+            TAGGED_POINTER, __name__
+        )
+    )
+    debugger.HandleCommand(
+        'type synthetic add -x "{}" -l {}.TaggedPointerProvider -w smr_benchmark'.format(
+            TAGGED_POINTER, __name__
+        )
+    )
+    debugger.HandleCommand("type category enable smr_benchmark")
@@ -1,7 +1,10 @@
 #![feature(cfg_sanitize)]
+pub mod slow_memory;
 mod smr;
 mod smr_common;
+pub mod stats;
 mod strong;
+pub mod timeline;
 mod utils;
 mod weak;
 
@@ -0,0 +1,61 @@
+//! A delay after each load of a [`Snapshot`](crate::Snapshot), to emulate a slower memory.
+//!
+//! A far memory, such as a CXL expander, makes each dereference of a node slower, while the
+//! cost of the reference counts and of the reclamation stays the same. With the `slow-memory`
+//! feature, a load of a snapshot spins for the delay installed with [`set_delay`] after it has
+//! loaded its pointer. The delay is calibrated into a number of spins of a volatile loop once, so
+//! that it does not read the clock on each load. Without the feature, the delay compiles to
+//! nothing.
+
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// The number of spins of a delay.
+static SPINS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of spins that a calibration times.
+const CALIBRATION_SPINS: usize = 1 << 24;
+
+/// Spins `spins` times of a loop that the compiler cannot elide.
+#[inline(never)]
+fn spin(spins: usize) {
+    let mut i = 0usize;
+    while unsafe { ptr::read_volatile(&i) } < spins {
+        unsafe { ptr::write_volatile(&mut i, i + 1) };
+    }
+}
+
+/// Calibrates the number of spins that take `delay`, and installs it as the delay of each load
+/// of a snapshot. Returns the number of spins.
+pub fn set_delay(delay: Duration) -> usize {
+    let start = Instant::now();
+    spin(CALIBRATION_SPINS);
+    let elapsed = start.elapsed().as_nanos().max(1);
+    let spins = (delay.as_nanos() * CALIBRATION_SPINS as u128 / elapsed) as usize;
+    SPINS.store(spins, Ordering::Relaxed);
+    spins
+}
+
+#[inline(always)]
+pub(crate) fn delay() {
+    #[cfg(feature = "slow-memory")]
+    {
+        let spins = SPINS.load(Ordering::Relaxed);
+        if spins > 0 {
+            spin(spins);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::set_delay;
+    use std::time::Duration;
+
+    #[test]
+    fn calibrate() {
+        assert!(set_delay(Duration::from_millis(10)) > 0);
+        assert_eq!(set_delay(Duration::ZERO), 0);
+    }
+}
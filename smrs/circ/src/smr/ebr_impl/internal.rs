@@ -52,6 +52,7 @@ use super::epoch::{AtomicEpoch, Epoch};
 use super::guard::{unprotected, Guard};
 use super::sync::list::{Entry, IsElement, IterError, List};
 use super::sync::queue::Queue;
+use crate::timeline::{self, Event};
 
 #[allow(missing_docs)]
 pub static GLOBAL_GARBAGE_COUNT: AtomicUsize = AtomicUsize::new(0);
@@ -181,6 +182,7 @@ impl Global {
     pub(crate) fn push_bag(&self, bag: &mut Bag, guard: &Guard) {
         GLOBAL_GARBAGE_COUNT.fetch_add(bag.0.len(), Ordering::AcqRel);
         let bag = mem::replace(bag, Bag::new());
+        crate::stats::record_flush();
 
         atomic::fence(Ordering::SeqCst);
 
@@ -215,8 +217,11 @@ impl Global {
             ) {
                 None => break,
                 Some(sealed_bag) => {
-                    GLOBAL_GARBAGE_COUNT.fetch_sub(sealed_bag.bag.0.len(), Ordering::AcqRel);
+                    let len = sealed_bag.bag.0.len();
+                    GLOBAL_GARBAGE_COUNT.fetch_sub(len, Ordering::AcqRel);
+                    timeline::emit(Event::BatchStart, len as u64);
                     drop(sealed_bag);
+                    timeline::emit(Event::BatchEnd, len as u64);
                 }
             }
         }
@@ -268,6 +273,7 @@ impl Global {
         // advanced two steps ahead of it.
         let new_epoch = global_epoch.successor();
         self.epoch.store(new_epoch, Ordering::Release);
+        timeline::emit(Event::EpochFlip, new_epoch.value() as u64);
         new_epoch
     }
 
@@ -378,6 +384,7 @@ impl Local {
             deferred = d;
             self.schedule_collection();
         }
+        crate::stats::record_defer(bag.0.len());
         self.incr_advance(guard);
     }
 
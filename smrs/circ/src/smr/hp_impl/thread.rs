@@ -5,6 +5,7 @@ use std::cell::{Cell, RefCell};
 use super::domain::Domain;
 use super::hazard::ThreadRecord;
 use super::retire::{Pile, Retired};
+use crate::timeline::{self, Event};
 
 pub static COUNTS_BETWEEN_FLUSH: AtomicUsize = AtomicUsize::new(64);
 
@@ -56,7 +57,8 @@ impl Thread {
         self.domain()
             .num_garbages
             .fetch_add(self.retired.borrow().len(), Ordering::AcqRel);
-        self.domain().retireds.push(self.retired.take())
+        self.domain().retireds.push(self.retired.take());
+        crate::stats::record_flush();
     }
 
     // NOTE: T: Send not required because we reclaim only locally.
@@ -70,9 +72,10 @@ impl Thread {
     where
         F: FnOnce(),
     {
-        self.retired
-            .borrow_mut()
-            .push(Retired::new(ptr as *mut _, f));
+        let mut retired = self.retired.borrow_mut();
+        retired.push(Retired::new(ptr as *mut _, f));
+        crate::stats::record_defer(retired.len());
+        drop(retired);
         let count = self.count.get().wrapping_add(1);
         self.count.set(count);
         if count % counts_between_flush() == 0 {
@@ -112,6 +115,7 @@ impl Thread {
         if retireds.is_empty() {
             return;
         }
+        timeline::emit(Event::ScanStart, retireds_len as u64);
 
         membarrier::heavy();
 
@@ -130,6 +134,7 @@ impl Thread {
         self.domain()
             .num_garbages
             .fetch_sub(retireds_len - not_freed.len(), Ordering::AcqRel);
+        timeline::emit(Event::ScanEnd, (retireds_len - not_freed.len()) as u64);
         self.domain().retireds.push(not_freed);
     }
 }
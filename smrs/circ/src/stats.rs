@@ -0,0 +1,66 @@
+//! Per-thread statistics of the deferred tasks.
+//!
+//! A deferred decrement of a reference count (and the destruction or deallocation that follows
+//! it) first waits in a queue of the thread that deferred it: the local bag for EBR, and the local
+//! retired list for HP. The queue is handed over to the global collection once it is full, so its
+//! length tells how much work a thread is holding back. It is tracked with thread-local counters
+//! only, and does not add any shared writes.
+
+use std::cell::Cell;
+
+/// The statistics of the deferred tasks of the current thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeferredStats {
+    /// The number of tasks in the local queue.
+    pub len: usize,
+    /// The largest `len` since the thread started or since [`reset_local_peak`].
+    pub peak: usize,
+    /// The total number of tasks that the thread has deferred.
+    pub deferred: u64,
+}
+
+thread_local! {
+    static LOCAL: Cell<DeferredStats> = const {
+        Cell::new(DeferredStats {
+            len: 0,
+            peak: 0,
+            deferred: 0,
+        })
+    };
+}
+
+/// Returns the statistics of the deferred tasks of the current thread.
+pub fn local_deferred_stats() -> DeferredStats {
+    LOCAL.with(Cell::get)
+}
+
+/// Restarts tracking the peak length of the local queue from its current length.
+pub fn reset_local_peak() {
+    LOCAL.with(|local| {
+        let mut stats = local.get();
+        stats.peak = stats.len;
+        local.set(stats);
+    });
+}
+
+/// Records that a task is deferred and the local queue has `len` tasks now.
+#[inline]
+pub(crate) fn record_defer(len: usize) {
+    LOCAL.with(|local| {
+        let mut stats = local.get();
+        stats.len = len;
+        stats.peak = stats.peak.max(len);
+        stats.deferred += 1;
+        local.set(stats);
+    });
+}
+
+/// Records that the local queue is handed over to the global collection.
+#[inline]
+pub(crate) fn record_flush() {
+    LOCAL.with(|local| {
+        let mut stats = local.get();
+        stats.len = 0;
+        local.set(stats);
+    });
+}
@@ -8,7 +8,7 @@ use std::{
 use atomic::Atomic;
 use static_assertions::const_assert;
 
-use crate::{Acquired, AtomicWeak, Cs, Pointer, RcInner, Tagged, TaggedCnt, Weak};
+use crate::{slow_memory, Acquired, AtomicWeak, Cs, Pointer, RcInner, Tagged, TaggedCnt, Weak};
 
 pub trait GraphNode<C: Cs + ?Sized> {
     const UNIQUE_OUTDEGREE: bool;
@@ -525,6 +525,7 @@ impl<T: GraphNode<C>, C: Cs> Snapshot<T, C> {
     #[inline]
     pub fn load(&mut self, from: &AtomicRc<T, C>, cs: &C) {
         cs.acquire(|order| from.load(order), &mut self.acquired);
+        slow_memory::delay();
     }
 
     #[inline]
@@ -608,6 +609,7 @@ impl<T, C: Cs> Snapshot<T, C> {
     pub fn load_from_weak(&mut self, from: &AtomicWeak<T, C>, cs: &C) -> bool {
         loop {
             let ptr = cs.acquire(|order| from.load(order), &mut self.acquired);
+            slow_memory::delay();
 
             if ptr.is_null() || unsafe { C::non_zero(ptr.deref()) } {
                 return true;
@@ -0,0 +1,42 @@
+//! Hooks on the reclamations of the schemes, for the timelines of the benchmark.
+//!
+//! With the `timeline` feature, the schemes call the hook installed with [`set_hook`] at the
+//! events of their reclamations: HP when a reclamation starts and ends its scan of the hazard
+//! pointers, and EBR when it advances the global epoch and when it starts and ends destroying a
+//! bag of the global queue. Without the feature, the events compile to nothing.
+
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A reclamation of HP starts scanning the hazard pointers, with the number of retired
+    /// objects.
+    ScanStart,
+    /// A reclamation of HP has scanned the hazard pointers and destroyed the unprotected objects,
+    /// with the number of destroyed objects.
+    ScanEnd,
+    /// EBR has advanced the global epoch, with the new epoch. Two threads that try to advance at
+    /// once may both report the same epoch.
+    EpochFlip,
+    /// EBR starts destroying an expired bag, with the number of its objects.
+    BatchStart,
+    /// EBR has destroyed an expired bag, with the number of its objects.
+    BatchEnd,
+}
+
+static HOOK: OnceLock<fn(Event, u64)> = OnceLock::new();
+
+/// Installs the hook of the events. Only the first hook is installed.
+pub fn set_hook(hook: fn(Event, u64)) {
+    let _ = HOOK.set(hook);
+}
+
+#[inline(always)]
+pub(crate) fn emit(event: Event, value: u64) {
+    #[cfg(feature = "timeline")]
+    if let Some(hook) = HOOK.get() {
+        hook(event, value);
+    }
+    #[cfg(not(feature = "timeline"))]
+    let _ = (event, value);
+}
@@ -385,6 +385,16 @@ impl CsGuard {
         compiler_fence(Ordering::SeqCst);
         result
     }
+
+    /// Restarts this critical section from its beginning, as if a reclaimer had neutralized the
+    /// current thread at this point.
+    ///
+    /// It lets a test run the rollback of a section at a chosen point, instead of waiting for a
+    /// signal to arrive there. It must not be called in a `mask` section.
+    #[inline]
+    pub fn restart(&self) -> ! {
+        self.rb.restart()
+    }
 }
 
 impl Handle for CsGuard {}
@@ -2,6 +2,7 @@ use core::marker::PhantomData;
 use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
 use core::{mem, ptr};
 
+use crate::interpose::{self, Point};
 use crate::thread::Thread;
 use crate::untagged;
 use crate::DEFAULT_THREAD;
@@ -41,6 +42,7 @@ impl<'domain> HazardPointer<'domain> {
     /// Protect the given address.
     pub fn protect_raw<T>(&mut self, ptr: *mut T) {
         self.slot().store(ptr as *mut u8, Ordering::Release);
+        interpose::delay(Point::Protect);
     }
 
     /// Release the protection awarded by this hazard pointer, if any.
@@ -0,0 +1,262 @@
+//! Faults injected at the boundaries of the scheme, to study how sensitive a structure is to the
+//! promptness of the reclamation.
+//!
+//! With the `interpose` feature, each protection and each retirement draws from a thread-local
+//! generator whether it is interposed on, with the probability installed with [`install`]: an
+//! interposed protection spins for its delay after it announces its pointer and before it
+//! validates it, an interposed retirement spins for its delay before it retires its pointer, and
+//! an interposed reclamation, i.e., the scan that a retirement triggers periodically, is skipped,
+//! so that its garbage waits for the next scan. The structures are not changed, and see only a
+//! slower or lazier scheme. Without the feature, the hooks compile to nothing.
+
+use std::cell::Cell;
+use std::fmt;
+use std::ptr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// A boundary of the scheme that faults are injected at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Point {
+    /// The announcement of a hazard pointer, which is delayed before its validation.
+    Protect,
+    /// A retirement, which is delayed.
+    Retire,
+    /// The reclamation that a retirement triggers, which is skipped.
+    Reclaim,
+}
+
+impl Point {
+    const ALL: [Point; 3] = [Point::Protect, Point::Retire, Point::Reclaim];
+
+    fn name(self) -> &'static str {
+        match self {
+            Point::Protect => "protect",
+            Point::Retire => "retire",
+            Point::Reclaim => "reclaim",
+        }
+    }
+}
+
+/// The faults injected at a point: each event of the point is interposed on with `probability`,
+/// and spins for `delay` if it is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Injection {
+    pub point: Point,
+    pub probability: f64,
+    pub delay: Duration,
+}
+
+impl FromStr for Injection {
+    type Err = String;
+
+    /// Parses `<point>=<probability>[:<delay in ns>]`, e.g., `protect=0.01:1000`. A skipped
+    /// reclamation has no delay.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (point, rest) = s
+            .split_once('=')
+            .ok_or_else(|| format!("{s}: expected <point>=<probability>[:<delay ns>]"))?;
+        let point = Point::ALL
+            .into_iter()
+            .find(|p| p.name() == point)
+            .ok_or_else(|| format!("{point}: expected protect, retire or reclaim"))?;
+        let (probability, delay) = match rest.split_once(':') {
+            Some((probability, delay)) => (probability, Some(delay)),
+            None => (rest, None),
+        };
+        let probability = probability
+            .parse::<f64>()
+            .ok()
+            .filter(|p| (0.0..=1.0).contains(p))
+            .ok_or_else(|| format!("{probability}: expected a probability between 0 and 1"))?;
+        let delay = match (point, delay) {
+            (Point::Reclaim, Some(_)) => return Err("a skipped reclamation has no delay".into()),
+            (Point::Reclaim, None) => Duration::ZERO,
+            (_, None) => return Err(format!("{s}: expected a delay in ns")),
+            (_, Some(delay)) => Duration::from_nanos(
+                delay
+                    .parse::<u64>()
+                    .map_err(|_| format!("{delay}: expected a delay in ns"))?,
+            ),
+        };
+        Ok(Self {
+            point,
+            probability,
+            delay,
+        })
+    }
+}
+
+impl fmt::Display for Injection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.point.name(), self.probability)?;
+        if self.point != Point::Reclaim {
+            write!(f, ":{}", self.delay.as_nanos())?;
+        }
+        Ok(())
+    }
+}
+
+/// The number of the events of each point that were interposed on since [`install`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub delayed_protects: usize,
+    pub delayed_retires: usize,
+    pub skipped_reclaims: usize,
+}
+
+/// The probability of each point, as a threshold of a uniform `u64`.
+static THRESHOLDS: [AtomicU64; 3] = [const { AtomicU64::new(0) }; 3];
+/// The spins of the delay of each point.
+static SPINS: [AtomicUsize; 3] = [const { AtomicUsize::new(0) }; 3];
+static COUNTS: [AtomicUsize; 3] = [const { AtomicUsize::new(0) }; 3];
+
+/// The number of spins that a calibration times.
+const CALIBRATION_SPINS: usize = 1 << 24;
+
+thread_local! {
+    // 0 until the first draw of the thread, which seeds it.
+    static STATE: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Spins `spins` times of a loop that the compiler cannot elide.
+#[inline(never)]
+fn spin(spins: usize) {
+    let mut i = 0usize;
+    while unsafe { ptr::read_volatile(&i) } < spins {
+        unsafe { ptr::write_volatile(&mut i, i + 1) };
+    }
+}
+
+/// Installs `injections`, replacing the previous ones, and resets the counts. The delays are
+/// calibrated into numbers of spins of a volatile loop once, so that an interposed event does not
+/// read the clock. Returns the number of spins of each injection.
+pub fn install(injections: &[Injection]) -> Vec<usize> {
+    for point in 0..Point::ALL.len() {
+        THRESHOLDS[point].store(0, Ordering::Relaxed);
+        SPINS[point].store(0, Ordering::Relaxed);
+        COUNTS[point].store(0, Ordering::Relaxed);
+    }
+    let start = Instant::now();
+    spin(CALIBRATION_SPINS);
+    let elapsed = start.elapsed().as_nanos().max(1);
+    injections
+        .iter()
+        .map(|injection| {
+            let point = injection.point as usize;
+            let spins = (injection.delay.as_nanos() * CALIBRATION_SPINS as u128 / elapsed) as usize;
+            let threshold = if injection.probability >= 1.0 {
+                u64::MAX
+            } else {
+                (injection.probability * u64::MAX as f64) as u64
+            };
+            THRESHOLDS[point].store(threshold, Ordering::Relaxed);
+            SPINS[point].store(spins, Ordering::Relaxed);
+            spins
+        })
+        .collect()
+}
+
+/// Returns the events that were interposed on since [`install`].
+pub fn counts() -> Counts {
+    let count = |point: Point| COUNTS[point as usize].load(Ordering::Relaxed);
+    Counts {
+        delayed_protects: count(Point::Protect),
+        delayed_retires: count(Point::Retire),
+        skipped_reclaims: count(Point::Reclaim),
+    }
+}
+
+/// Whether an event of `point` is interposed on, counting it if it is.
+#[inline]
+#[cfg_attr(not(feature = "interpose"), allow(dead_code))]
+fn draw(point: Point) -> bool {
+    let threshold = THRESHOLDS[point as usize].load(Ordering::Relaxed);
+    if threshold == 0 {
+        return false;
+    }
+    let sample = STATE.with(|state| {
+        let mut x = state.get();
+        if x == 0 {
+            // The address of the slot tells the threads apart.
+            x = (state as *const Cell<u64> as u64) | 1;
+        }
+        // xorshift64
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        x
+    });
+    let hit = sample <= threshold;
+    if hit {
+        COUNTS[point as usize].fetch_add(1, Ordering::Relaxed);
+    }
+    hit
+}
+
+/// Delays an event of `point` if it is interposed on.
+#[inline(always)]
+pub(crate) fn delay(point: Point) {
+    #[cfg(feature = "interpose")]
+    if draw(point) {
+        spin(SPINS[point as usize].load(Ordering::Relaxed));
+    }
+    #[cfg(not(feature = "interpose"))]
+    let _ = point;
+}
+
+/// Whether the reclamation that a retirement triggers is skipped.
+#[inline(always)]
+pub(crate) fn skip_reclamation() -> bool {
+    #[cfg(feature = "interpose")]
+    return draw(Point::Reclaim);
+    #[cfg(not(feature = "interpose"))]
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{counts, draw, install, Injection, Point};
+    use std::time::Duration;
+
+    #[test]
+    fn parse() {
+        let injection = "protect=0.25:1000".parse::<Injection>().unwrap();
+        assert_eq!(
+            injection,
+            Injection {
+                point: Point::Protect,
+                probability: 0.25,
+                delay: Duration::from_nanos(1000),
+            }
+        );
+        assert_eq!(injection.to_string(), "protect=0.25:1000");
+        assert_eq!(
+            "reclaim=1".parse::<Injection>().unwrap().to_string(),
+            "reclaim=1"
+        );
+        assert!("retire=0.5".parse::<Injection>().is_err());
+        assert!("reclaim=0.5:10".parse::<Injection>().is_err());
+        assert!("protect=2:10".parse::<Injection>().is_err());
+        assert!("scan=0.5".parse::<Injection>().is_err());
+    }
+
+    #[test]
+    fn draws() {
+        install(&[
+            "retire=1:0".parse().unwrap(),
+            "reclaim=0.5".parse().unwrap(),
+        ]);
+        assert!(!draw(Point::Protect));
+        assert!((0..100).all(|_| draw(Point::Retire)));
+        let skipped = (0..10000).filter(|_| draw(Point::Reclaim)).count();
+        assert!((4000..6000).contains(&skipped));
+        assert_eq!(counts().delayed_retires, 100);
+        assert_eq!(counts().skipped_reclaims, skipped);
+        install(&[]);
+        assert_eq!(counts(), Default::default());
+        assert!(!draw(Point::Retire));
+    }
+}
@@ -2,9 +2,12 @@
 
 mod domain;
 mod hazard;
+pub mod interpose;
+pub mod reclaimer;
 mod retire;
 mod tag;
 mod thread;
+pub mod timeline;
 
 pub use hazard::HazardPointer;
 pub use hazard::ProtectError;
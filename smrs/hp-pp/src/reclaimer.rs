@@ -0,0 +1,122 @@
+//! Dedicated reclaimer threads, which take the scans of the retired pointers off the threads that
+//! retire them.
+//!
+//! With [`set_dedicated`], a retirement still flushes the bag of its thread into the domain, but
+//! never scans the hazard pointers to free the garbage itself: the scans are left to the threads
+//! that run a [`Reclaimer`], which scan the garbage of the domain as soon as there is some, and
+//! sleep while there is none. A skipped reclamation of `interpose` is not drawn either, since
+//! there is no reclamation to skip.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::domain::Domain;
+use crate::thread::Thread;
+
+static DEDICATED: AtomicBool = AtomicBool::new(false);
+
+/// How long a reclaimer sleeps when the domain has no garbage.
+const IDLE_PERIOD: Duration = Duration::from_micros(50);
+
+/// Makes the retirements leave the reclamation to the reclaimers if `dedicated`, or reclaim
+/// themselves as usual otherwise.
+pub fn set_dedicated(dedicated: bool) {
+    DEDICATED.store(dedicated, Ordering::Relaxed);
+}
+
+/// Whether the retirements leave the reclamation to the reclaimers.
+#[inline]
+pub(crate) fn dedicated() -> bool {
+    DEDICATED.load(Ordering::Relaxed)
+}
+
+/// What a reclaimer did while it ran.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The scans of the garbage of the domain, which skip the times it had none.
+    pub scans: u64,
+    pub freed: u64,
+    /// The time spent in the scans.
+    pub busy: Duration,
+    pub elapsed: Duration,
+}
+
+impl Stats {
+    /// The fraction of the time that the reclaimer spent in the scans.
+    pub fn utilization(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+        self.busy.as_secs_f64() / self.elapsed.as_secs_f64()
+    }
+}
+
+/// A reclaimer, which registers with the domain as a thread with no hazard pointers of its own.
+pub struct Reclaimer<'domain> {
+    thread: Thread<'domain>,
+    stats: Stats,
+}
+
+impl<'domain> Reclaimer<'domain> {
+    pub fn new(domain: &'domain Domain) -> Self {
+        Self {
+            thread: Thread::new(domain),
+            stats: Stats::default(),
+        }
+    }
+
+    /// Scans the garbage of the domain once, and returns whether there was any.
+    pub fn step(&mut self) -> bool {
+        let start = Instant::now();
+        let Some(freed) = self.thread.do_reclamation() else {
+            return false;
+        };
+        self.stats.scans += 1;
+        self.stats.freed += freed as u64;
+        self.stats.busy += start.elapsed();
+        true
+    }
+
+    /// Scans the garbage of the domain for as long as `running` returns true, and returns what
+    /// the reclaimer did.
+    pub fn run<F: FnMut() -> bool>(mut self, mut running: F) -> Stats {
+        let start = Instant::now();
+        while running() {
+            if !self.step() {
+                thread::sleep(IDLE_PERIOD);
+            }
+        }
+        self.stats.elapsed = start.elapsed();
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{set_dedicated, Reclaimer};
+    use crate::domain::Domain;
+    use crate::thread::{counts_between_collect, Thread};
+
+    #[test]
+    fn takes_over_the_reclamation() {
+        let domain = Domain::new();
+        set_dedicated(true);
+        let mut thread = Thread::new(&domain);
+        let retired = 4 * counts_between_collect();
+        for i in 0..retired {
+            unsafe { thread.retire(Box::into_raw(Box::new(i))) };
+        }
+        // Every bag was flushed, and none was scanned.
+        assert_eq!(domain.num_garbages(), retired);
+
+        let mut reclaimer = Reclaimer::new(&domain);
+        assert!(reclaimer.step());
+        assert_eq!(domain.num_garbages(), 0);
+        assert!(!reclaimer.step());
+        let stats = reclaimer.run(|| false);
+        assert_eq!(stats.scans, 1);
+        assert_eq!(stats.freed, retired as u64);
+        set_dedicated(false);
+    }
+}
@@ -5,7 +5,10 @@ use std::collections::VecDeque;
 use crate::domain::Domain;
 use crate::domain::EpochBarrier;
 use crate::hazard::ThreadRecord;
+use crate::interpose::{self, Point};
+use crate::reclaimer;
 use crate::retire::{Retired, Unlinked};
+use crate::timeline::{self, Event};
 use crate::HazardPointer;
 use crate::{Invalidate, Unlink};
 
@@ -80,6 +83,7 @@ impl<'domain> Thread<'domain> {
     // NOTE: T: Send not required because we reclaim only locally.
     #[inline]
     pub unsafe fn retire<T>(&mut self, ptr: *mut T) {
+        interpose::delay(Point::Retire);
         self.retired.push(Retired::new(ptr));
         let count = self.count.wrapping_add(1);
         self.count = count;
@@ -87,7 +91,10 @@ impl<'domain> Thread<'domain> {
             self.flush_retireds();
         }
         // TODO: collecting right after pushing is kinda weird
-        if count % counts_between_collect() == 0 {
+        if count % counts_between_collect() == 0
+            && !reclaimer::dedicated()
+            && !interpose::skip_reclamation()
+        {
             self.do_reclamation();
         }
     }
@@ -132,7 +139,10 @@ impl<'domain> Thread<'domain> {
         if count % counts_between_flush() == 0 {
             self.flush_retireds();
         }
-        if count % counts_between_collect() == 0 {
+        if count % counts_between_collect() == 0
+            && !reclaimer::dedicated()
+            && !interpose::skip_reclamation()
+        {
             self.do_reclamation();
         }
     }
@@ -159,13 +169,16 @@ impl<'domain> Thread<'domain> {
         self.retired.append(&mut invalidateds);
     }
 
+    /// Frees the retired pointers of the domain that are not protected, and returns how many it
+    /// freed, or `None` if the domain had none.
     #[inline]
-    pub(crate) fn do_reclamation(&mut self) {
+    pub(crate) fn do_reclamation(&mut self) -> Option<usize> {
         let retireds = self.domain.retireds.pop_all();
         let retireds_len = retireds.len();
         if retireds.is_empty() {
-            return;
+            return None;
         }
+        timeline::emit(Event::ScanStart, retireds_len as u64);
 
         self.domain.barrier.barrier();
 
@@ -184,10 +197,11 @@ impl<'domain> Thread<'domain> {
                 }
             })
             .collect();
-        self.domain
-            .num_garbages
-            .fetch_sub(retireds_len - not_freed.len(), Ordering::AcqRel);
+        let freed = retireds_len - not_freed.len();
+        self.domain.num_garbages.fetch_sub(freed, Ordering::AcqRel);
+        timeline::emit(Event::ScanEnd, freed as u64);
         self.domain.retireds.push(not_freed);
+        Some(freed)
     }
 }
 
@@ -0,0 +1,33 @@
+//! Hooks on the reclamations of the domain, for the timelines of the benchmark.
+//!
+//! With the `timeline` feature, a reclamation calls the hook installed with [`set_hook`] when it
+//! starts and ends its scan of the hazard pointers. Without the feature, the events compile to
+//! nothing.
+
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A reclamation starts scanning the hazard pointers, with the number of retired blocks.
+    ScanStart,
+    /// A reclamation has scanned the hazard pointers and freed the unprotected blocks, with the
+    /// number of freed blocks.
+    ScanEnd,
+}
+
+static HOOK: OnceLock<fn(Event, u64)> = OnceLock::new();
+
+/// Installs the hook of the events. Only the first hook is installed.
+pub fn set_hook(hook: fn(Event, u64)) {
+    let _ = HOOK.set(hook);
+}
+
+#[inline(always)]
+pub(crate) fn emit(event: Event, value: u64) {
+    #[cfg(feature = "timeline")]
+    if let Some(hook) = HOOK.get() {
+        hook(event, value);
+    }
+    #[cfg(not(feature = "timeline"))]
+    let _ = (event, value);
+}
@@ -1,3 +1,6 @@
+pub mod validation;
+
+use std::cell::Cell;
 use std::{
     cell::RefCell, collections::VecDeque, fmt::Display, marker::PhantomData, mem::align_of,
     ops::Deref, ptr::null_mut, sync::atomic::AtomicU64,
@@ -7,6 +10,7 @@ use atomic::{Atomic, Ordering};
 use crossbeam_queue::SegQueue;
 use crossbeam_utils::CachePadded;
 use portable_atomic::{compiler_fence, AtomicU128, AtomicUsize};
+use validation::Event;
 
 static ENTRIES_PER_BAG: AtomicUsize = AtomicUsize::new(128);
 pub const INIT_BAGS_PER_LOCAL: usize = 1;
@@ -44,6 +48,8 @@ impl<T> Deref for Inner<T> {
 
 pub struct Global<T: Default> {
     epoch: CachePadded<AtomicU64>,
+    /// The guards that hold the epoch with `validation::Policy::Fallback`.
+    stalls: CachePadded<AtomicUsize>,
     avail: SegQueue<*mut Bag<Inner<T>>>,
 }
 
@@ -59,6 +65,7 @@ impl<T: Default> Global<T> {
         }
         Self {
             epoch: CachePadded::new(AtomicU64::new(1)),
+            stalls: CachePadded::new(AtomicUsize::new(0)),
             avail,
         }
     }
@@ -68,6 +75,9 @@ impl<T: Default> Global<T> {
     }
 
     pub fn advance(&self, expected: u64) -> Result<u64, u64> {
+        if self.stalls.load(Ordering::SeqCst) > 0 {
+            return Err(expected);
+        }
         match self.epoch.compare_exchange(
             expected,
             expected + 1,
@@ -133,6 +143,8 @@ pub struct Local<T: Default> {
     global: *const Global<T>,
     avail: RefCell<VecDeque<*mut Bag<Inner<T>>>>,
     retired: RefCell<VecDeque<*mut Bag<Inner<T>>>>,
+    /// The validation failures of the guards of this thread in a row.
+    streak: Cell<u32>,
 }
 
 impl<T: Default> Local<T> {
@@ -151,6 +163,7 @@ impl<T: Default> Local<T> {
             global,
             avail: RefCell::new(avail),
             retired: RefCell::new(retired),
+            streak: Cell::new(0),
         }
     }
 
@@ -207,9 +220,16 @@ impl<T: Default> Local<T> {
     }
 
     pub fn guard(&self) -> Guard<T> {
+        let stalling = validation::falls_back(self.streak.get());
+        if stalling {
+            validation::count(Event::Fallback);
+            self.global().stalls.fetch_add(1, Ordering::SeqCst);
+        }
         Guard {
             local: self,
             epoch: self.global().epoch(),
+            failed: Cell::new(false),
+            stalling,
         }
     }
 }
@@ -234,6 +254,10 @@ impl<T: Default> Drop for Local<T> {
 pub struct Guard<T: Default> {
     local: *const Local<T>,
     epoch: u64,
+    /// Whether a validation failed since the guard was taken or refreshed.
+    failed: Cell<bool>,
+    /// Whether the guard holds the epoch.
+    stalling: bool,
 }
 
 impl<T: Default> Guard<T> {
@@ -247,6 +271,7 @@ impl<T: Default> Guard<T> {
 
     pub fn refresh(&mut self) {
         self.epoch = self.global().epoch();
+        self.failed.set(false);
     }
 
     pub fn allocate<'g, F>(&'g self, mut init: F) -> Result<Shared<'g, T>, ()>
@@ -259,6 +284,7 @@ impl<T: Default> Guard<T> {
         // If the retire epoch is (greater than or) equal to the current epoch,
         // try advance the global epoch.
         if self.epoch <= slot_ref.retire.load(Ordering::SeqCst) {
+            validation::count(Event::Allocation);
             self.local().return_avail(ptr);
             let _ = self.global().advance(self.epoch);
             return Err(());
@@ -311,11 +337,26 @@ impl<T: Default> Guard<T> {
         if self.epoch == self.global().epoch() {
             Ok(())
         } else {
+            validation::count(Event::Failure);
+            self.failed.set(true);
+            let streak = &self.local().streak;
+            streak.set(streak.get().saturating_add(1));
             Err(())
         }
     }
 }
 
+impl<T: Default> Drop for Guard<T> {
+    fn drop(&mut self) {
+        if !self.failed.get() {
+            self.local().streak.set(0);
+        }
+        if self.stalling {
+            self.global().stalls.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
 pub struct Shared<'g, T> {
     ptr: *mut Inner<T>,
     birth: u64,
@@ -451,6 +492,7 @@ impl<T: Default> MutAtomic<T> {
         _: &Guard<T>,
     ) -> CompareExchangeError<T> {
         if !owner.validate_birth_epoch() {
+            validation::count(Event::Reallocation);
             return CompareExchangeError::Reallocated;
         }
         let curr = compose_u128(owner.birth.max(current.birth), current.as_raw());
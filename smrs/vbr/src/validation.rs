@@ -0,0 +1,198 @@
+//! The validation failures of the scheme, and what a thread does when it keeps failing.
+//!
+//! An operation of VBR reads without protecting anything, and validates its reads against the
+//! epoch of its guard: a read fails once the global epoch has advanced since the guard was taken,
+//! and the structure restarts the operation with a new guard. The epoch advances whenever an
+//! allocation finds its slot retired in the current epoch, so under a high churn a long traversal
+//! may fail again and again, which no structure counts on its own. Every failed validation is
+//! counted here instead, with the allocations that found their slot retired in the epoch of their
+//! guard and the CASes whose owner was reallocated.
+//!
+//! With [`Policy::Fallback`], a thread whose last validations failed in a row takes its next guard
+//! on the slow path: the guard holds the epoch, i.e., no allocation advances it until the guard is
+//! dropped, so that the next attempt of the operation does not fail its validations, unless an
+//! advance was already underway. The allocations of the other threads fail and retry meanwhile.
+
+use std::cell::Cell;
+use std::fmt;
+use std::ops::Sub;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// What a thread does after its validations fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Policy {
+    /// Restarts the operation with a new guard, which is what the structures do.
+    Retry,
+    /// Restarts the operation with a new guard, which holds the epoch after the given number of
+    /// validation failures in a row.
+    Fallback(u32),
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    /// Parses `retry` or `fallback:<failures>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "retry" {
+            return Ok(Policy::Retry);
+        }
+        let failures = s
+            .strip_prefix("fallback:")
+            .ok_or_else(|| format!("{s}: expected retry or fallback:<failures>"))?
+            .parse::<u32>()
+            .ok()
+            .filter(|&failures| failures > 0)
+            .ok_or_else(|| format!("{s}: expected a positive number of failures"))?;
+        Ok(Policy::Fallback(failures))
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Policy::Retry => write!(f, "retry"),
+            Policy::Fallback(failures) => write!(f, "fallback:{failures}"),
+        }
+    }
+}
+
+/// The failures in a row after which a guard holds the epoch, or 0 with [`Policy::Retry`].
+static FALLBACK_AFTER: AtomicU32 = AtomicU32::new(0);
+
+/// Makes the threads follow `policy` from now on.
+pub fn set_policy(policy: Policy) {
+    let after = match policy {
+        Policy::Retry => 0,
+        Policy::Fallback(failures) => failures,
+    };
+    FALLBACK_AFTER.store(after, Ordering::Relaxed);
+}
+
+/// Whether a guard taken after `streak` validation failures in a row holds the epoch.
+#[inline]
+pub(crate) fn falls_back(streak: u32) -> bool {
+    let after = FALLBACK_AFTER.load(Ordering::Relaxed);
+    after > 0 && streak >= after
+}
+
+/// The failures of the threads, which are kept in a counter of each thread and added to the
+/// totals when it exits, so that counting does not add contention to a retry storm.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The reads that found the epoch advanced since their guard was taken.
+    pub failures: u64,
+    /// The allocations that found their slot retired in the epoch of their guard.
+    pub allocations: u64,
+    /// The CASes whose owner was reallocated.
+    pub reallocations: u64,
+    /// The guards that held the epoch with [`Policy::Fallback`].
+    pub fallbacks: u64,
+}
+
+impl Sub for Stats {
+    type Output = Stats;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Stats {
+            failures: self.failures - rhs.failures,
+            allocations: self.allocations - rhs.allocations,
+            reallocations: self.reallocations - rhs.reallocations,
+            fallbacks: self.fallbacks - rhs.fallbacks,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Event {
+    Failure,
+    Allocation,
+    Reallocation,
+    Fallback,
+}
+
+static TOTALS: [AtomicU64; 4] = [const { AtomicU64::new(0) }; 4];
+
+#[derive(Default)]
+struct LocalCounts([Cell<u64>; 4]);
+
+impl Drop for LocalCounts {
+    fn drop(&mut self) {
+        for (total, count) in TOTALS.iter().zip(self.0.iter()) {
+            total.fetch_add(count.get(), Ordering::Relaxed);
+        }
+    }
+}
+
+thread_local! {
+    static LOCAL: LocalCounts = LocalCounts::default();
+}
+
+/// Counts an event of the calling thread.
+#[inline]
+pub(crate) fn count(event: Event) {
+    let index = event as usize;
+    if LOCAL
+        .try_with(|local| local.0[index].set(local.0[index].get() + 1))
+        .is_err()
+    {
+        // The thread is exiting.
+        TOTALS[index].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns the failures since the start of the process, including those of the calling thread,
+/// but not those of the other running threads.
+pub fn stats() -> Stats {
+    let mut counts = [0; 4];
+    for (count, total) in counts.iter_mut().zip(TOTALS.iter()) {
+        *count = total.load(Ordering::Relaxed);
+    }
+    let _ = LOCAL.try_with(|local| {
+        for (count, local) in counts.iter_mut().zip(local.0.iter()) {
+            *count += local.get();
+        }
+    });
+    Stats {
+        failures: counts[Event::Failure as usize],
+        allocations: counts[Event::Allocation as usize],
+        reallocations: counts[Event::Reallocation as usize],
+        fallbacks: counts[Event::Fallback as usize],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count, falls_back, set_policy, stats, Event, Policy};
+    use std::thread;
+
+    #[test]
+    fn parse() {
+        assert_eq!("retry".parse::<Policy>().unwrap(), Policy::Retry);
+        let policy = "fallback:8".parse::<Policy>().unwrap();
+        assert_eq!(policy, Policy::Fallback(8));
+        assert_eq!(policy.to_string(), "fallback:8");
+        assert!("fallback:0".parse::<Policy>().is_err());
+        assert!("fallback".parse::<Policy>().is_err());
+
+        set_policy(policy);
+        assert!(!falls_back(7) && falls_back(8));
+        set_policy(Policy::Retry);
+        assert!(!falls_back(u32::MAX));
+    }
+
+    #[test]
+    fn counts_of_exited_threads() {
+        let before = stats();
+        thread::spawn(|| {
+            count(Event::Failure);
+            count(Event::Failure);
+            count(Event::Fallback);
+        })
+        .join()
+        .unwrap();
+        let after = stats() - before;
+        // Other tests may count failures concurrently.
+        assert!(after.failures >= 2 && after.fallbacks >= 1);
+    }
+}
@@ -0,0 +1,241 @@
+//! The placement of the benchmark threads on the CPUs with `--pin`.
+//!
+//! Without `--pin`, the scheduler places the threads and may migrate them during a run, so two runs
+//! of the same configuration may differ in how many threads share a core, a socket, or a NUMA
+//! node, and a scheme with a shared state (e.g., a global epoch) may vary with the placement more
+//! than with the scheme. With `--pin`, each thread is pinned before it warms up and starts, so that
+//! the memory that it first touches during the run is allocated on its node:
+//!
+//! - `compact` fills the CPUs of a core, then the cores of a socket, before the next socket, so that
+//!   the threads share as much of the caches as they can.
+//! - `scatter` deals the threads over the sockets, and over the cores of each socket, before placing
+//!   two of them on the SMT siblings of a core.
+//! - `numa:<nodes>` deals the threads over the given NUMA nodes, and leaves the scheduler to place
+//!   each thread among the CPUs of its node.
+//!
+//! Thread `i` takes the `i`-th slot of the placement, wrapping around if there are more threads
+//! than slots.
+
+use crate::topology::{cpu_list, topology, Topology};
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+/// How the threads are placed with `--pin`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pin {
+    Compact,
+    Scatter,
+    /// The ids of the NUMA nodes, in an increasing order.
+    Numa(Vec<usize>),
+}
+
+impl FromStr for Pin {
+    type Err = String;
+
+    /// Parses `compact`, `scatter`, or `numa:<nodes>` with a list of nodes, e.g., `numa:0,2-3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compact" => Ok(Pin::Compact),
+            "scatter" => Ok(Pin::Scatter),
+            _ => {
+                let nodes = s
+                    .strip_prefix("numa:")
+                    .ok_or_else(|| format!("{s}: expected compact, scatter or numa:<nodes>"))?;
+                Ok(Pin::Numa(parse_list(nodes)?))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pin::Compact => write!(f, "compact"),
+            Pin::Scatter => write!(f, "scatter"),
+            Pin::Numa(nodes) => write!(f, "numa:{}", cpu_list(nodes)),
+        }
+    }
+}
+
+/// Parses a list of ranges, e.g., `0-3,8-11`, as sysfs formats them, into its ids in an increasing
+/// order.
+pub fn parse_list(list: &str) -> Result<Vec<usize>, String> {
+    let mut ids = Vec::new();
+    for range in list.trim().split(',') {
+        let id = |id: &str| {
+            id.parse::<usize>()
+                .map_err(|e| format!("invalid id {id} in {list}: {e}"))
+        };
+        match range.split_once('-') {
+            Some((first, last)) => ids.extend(id(first)?..=id(last)?),
+            None => ids.push(id(range)?),
+        }
+    }
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// The NUMA nodes of the machine with their CPUs, from sysfs, in the order of the node ids. Every
+/// CPU is put on node 0 if they are not available.
+pub fn numa_nodes() -> Vec<(usize, Vec<usize>)> {
+    let mut nodes = Vec::new();
+    let entries = fs::read_dir("/sys/devices/system/node")
+        .into_iter()
+        .flatten();
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(node) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix("node"))
+            .and_then(|id| id.parse::<usize>().ok())
+        else {
+            continue;
+        };
+        // A node without CPUs, e.g., of a memory expander, cannot run a thread.
+        let Some(cpus) = fs::read_to_string(entry.path().join("cpulist"))
+            .ok()
+            .and_then(|list| parse_list(&list).ok())
+        else {
+            continue;
+        };
+        nodes.push((node, cpus));
+    }
+    if nodes.is_empty() {
+        nodes.push((0, topology().available_cpus(None, false)));
+    }
+    nodes.sort();
+    nodes
+}
+
+/// The CPUs that each thread is pinned to, in the order of the threads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    slots: Vec<Vec<usize>>,
+}
+
+impl Placement {
+    /// Places the threads on `cpus` of `topology` as `pin` does, where `nodes` are the NUMA nodes
+    /// with their CPUs.
+    pub fn new(
+        pin: &Pin,
+        topology: &Topology,
+        cpus: &[usize],
+        nodes: &[(usize, Vec<usize>)],
+    ) -> Result<Self, String> {
+        let core_of = |cpu: usize| topology.core_of(cpu).unwrap_or((0, cpu));
+        let slots: Vec<Vec<usize>> = match pin {
+            Pin::Compact => {
+                let mut cpus = cpus.to_vec();
+                cpus.sort_by_key(|&cpu| (core_of(cpu), cpu));
+                cpus.into_iter().map(|cpu| vec![cpu]).collect()
+            }
+            Pin::Scatter => {
+                // The CPUs of each socket, the first CPU of each core before its siblings.
+                let mut sockets: Vec<(usize, Vec<(usize, usize, usize)>)> = Vec::new();
+                for &cpu in cpus {
+                    let (socket, core) = core_of(cpu);
+                    let sibling = cpus
+                        .iter()
+                        .filter(|&&other| other < cpu && core_of(other) == (socket, core))
+                        .count();
+                    match sockets.iter_mut().find(|(id, _)| *id == socket) {
+                        Some((_, cpus)) => cpus.push((sibling, core, cpu)),
+                        None => sockets.push((socket, vec![(sibling, core, cpu)])),
+                    }
+                }
+                sockets.sort();
+                for (_, cpus) in sockets.iter_mut() {
+                    cpus.sort();
+                }
+                let rounds = sockets.iter().map(|(_, cpus)| cpus.len()).max();
+                (0..rounds.unwrap_or(0))
+                    .flat_map(|i| sockets.iter().filter_map(move |(_, cpus)| cpus.get(i)))
+                    .map(|&(_, _, cpu)| vec![cpu])
+                    .collect()
+            }
+            Pin::Numa(ids) => ids
+                .iter()
+                .map(|id| {
+                    let (_, node) = nodes
+                        .iter()
+                        .find(|(node, _)| node == id)
+                        .ok_or_else(|| format!("There is no NUMA node {id}"))?;
+                    let node = node
+                        .iter()
+                        .copied()
+                        .filter(|cpu| cpus.contains(cpu))
+                        .collect::<Vec<_>>();
+                    if node.is_empty() {
+                        return Err(format!("NUMA node {id} has none of the available CPUs"));
+                    }
+                    Ok(node)
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        if slots.is_empty() {
+            return Err("There are no CPUs to pin the threads to".to_string());
+        }
+        Ok(Self { slots })
+    }
+
+    /// The CPUs that the thread of `index` is pinned to.
+    pub fn cpus(&self, index: usize) -> &[usize] {
+        &self.slots[index % self.slots.len()]
+    }
+
+    /// Pins the calling thread, which is the thread of `index`, to its CPUs.
+    pub fn pin(&self, index: usize) {
+        topology().restrict_to_cpus(self.cpus(index));
+    }
+
+    /// The CPUs of the first `threads` threads, separated by spaces.
+    pub fn describe(&self, threads: usize) -> String {
+        (0..threads)
+            .map(|index| cpu_list(self.cpus(index)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_list, Pin, Placement};
+    use crate::topology::Topology;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_list("0-2,8,8\n").unwrap(), [0, 1, 2, 8]);
+        assert!(parse_list("0-a").is_err());
+        assert_eq!("compact".parse::<Pin>().unwrap(), Pin::Compact);
+        let pin = "numa:2,0-1".parse::<Pin>().unwrap();
+        assert_eq!(pin, Pin::Numa(vec![0, 1, 2]));
+        assert_eq!(pin.to_string(), "numa:0-2");
+        assert!("numa:".parse::<Pin>().is_err());
+        assert!("spread".parse::<Pin>().is_err());
+    }
+
+    #[test]
+    fn placements() {
+        // Two sockets of two cores with two CPUs each, where the siblings are numbered apart, as
+        // in the test of the topology, with a NUMA node per socket.
+        let cores = (0..8).map(|cpu| (cpu, ((cpu / 2) % 2, cpu % 2))).collect();
+        let topology = Topology::from_cores(cores);
+        let nodes = [(0, vec![0, 1, 4, 5]), (1, vec![2, 3, 6, 7])];
+        let cpus = (0..8).collect::<Vec<_>>();
+        let place = |pin: &str, cpus: &[usize]| {
+            Placement::new(&pin.parse().unwrap(), &topology, cpus, &nodes)
+        };
+
+        let compact = place("compact", &cpus).unwrap();
+        assert_eq!(compact.describe(8), "0 4 1 5 2 6 3 7");
+        let scatter = place("scatter", &cpus).unwrap();
+        assert_eq!(scatter.describe(9), "0 2 1 3 4 6 5 7 0");
+        let numa = place("numa:1", &cpus).unwrap();
+        assert_eq!(numa.describe(2), "2-3,6-7 2-3,6-7");
+        assert_eq!(place("numa:0-1", &[0, 2]).unwrap().describe(2), "0 2");
+        assert!(place("numa:2", &cpus).is_err());
+        assert!(place("numa:1", &[0, 1]).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! Counts of the nodes of the structures that are allocated and not freed yet.
+//!
+//! The memory of a run is sampled from the statistics of jemalloc, which also count the caches of
+//! the allocator, the bags and the retired lists of the schemes, and every other allocation of the
+//! process, so the memory of a run only approximates what the structure holds. With the
+//! `alloc-tags` feature, `--alloc-curve` registers the layouts of the nodes of the structure on the
+//! scheme, with the header of the scheme, from the table of `layout` as tags, and the global
+//! allocator counts the allocations and the frees of each tag. The outstanding nodes of a tag are
+//! then counted whether they are reachable, retired, or waiting in a bag, and the auxiliary thread
+//! samples them into a curve.
+//!
+//! The allocator sees a layout and not a type, so a tag also counts the other allocations of the
+//! same size and alignment, of which the benchmark threads make few, and two node types of the same
+//! layout share the tag of the first one, with a warning. The nodes from the slab of `--slab` do
+//! not go through the allocator, and are not counted.
+
+use crate::log_event;
+use csv::Writer;
+use std::alloc::Layout;
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// The number of tags that may be registered. The later registrations are ignored.
+const MAX_TAGS: usize = 8;
+
+struct Tag {
+    size: AtomicUsize,
+    align: AtomicUsize,
+    /// The allocations minus the frees, which is negative while the frees of the blocks that were
+    /// allocated before the registration outnumber the allocations.
+    live: AtomicIsize,
+}
+
+static TAGS: [Tag; MAX_TAGS] = [const {
+    Tag {
+        size: AtomicUsize::new(0),
+        align: AtomicUsize::new(0),
+        live: AtomicIsize::new(0),
+    }
+}; MAX_TAGS];
+
+/// The number of the registered tags, which are the first ones of `TAGS`.
+static REGISTERED: AtomicUsize = AtomicUsize::new(0);
+
+/// The names of the registered tags, which the allocator does not read.
+static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Registers `layout` as the tag `name`, unless it is registered already, in which case the tag
+/// that it is registered as counts the nodes of `name` too.
+pub fn register(name: &'static str, layout: Layout) {
+    let mut names = NAMES.lock().unwrap();
+    if let Some(index) = TAGS[..names.len()]
+        .iter()
+        .position(|tag| tag.matches(layout))
+    {
+        log_event!(
+            Warn,
+            Setup,
+            "a node shares the layout of a registered tag, which counts both",
+            tag = names[index],
+            node = name,
+            size = layout.size(),
+            align = layout.align(),
+        );
+        return;
+    }
+    if names.len() == MAX_TAGS {
+        return;
+    }
+    let tag = &TAGS[names.len()];
+    tag.size.store(layout.size(), Ordering::Relaxed);
+    tag.align.store(layout.align(), Ordering::Relaxed);
+    names.push(name);
+    REGISTERED.store(names.len(), Ordering::Release);
+}
+
+impl Tag {
+    #[inline]
+    fn matches(&self, layout: Layout) -> bool {
+        self.size.load(Ordering::Relaxed) == layout.size()
+            && self.align.load(Ordering::Relaxed) == layout.align()
+    }
+}
+
+#[inline]
+fn lookup(layout: Layout) -> Option<&'static Tag> {
+    TAGS[..REGISTERED.load(Ordering::Acquire)]
+        .iter()
+        .find(|tag| tag.matches(layout))
+}
+
+/// Counts an allocation of the global allocator.
+#[inline]
+pub fn allocated(layout: Layout) {
+    if let Some(tag) = lookup(layout) {
+        tag.live.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts a free of the global allocator.
+#[inline]
+pub fn freed(layout: Layout) {
+    if let Some(tag) = lookup(layout) {
+        tag.live.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The outstanding nodes of a tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagCount {
+    pub name: &'static str,
+    pub nodes: usize,
+    pub bytes: usize,
+}
+
+/// The outstanding nodes of each registered tag, in the order of the registrations.
+pub fn snapshot() -> Vec<TagCount> {
+    let names = NAMES.lock().unwrap();
+    names
+        .iter()
+        .zip(&TAGS)
+        .map(|(&name, tag)| {
+            let nodes = tag.live.load(Ordering::Relaxed).max(0) as usize;
+            TagCount {
+                name,
+                nodes,
+                bytes: nodes * tag.size.load(Ordering::Relaxed),
+            }
+        })
+        .collect()
+}
+
+/// The peak and the average of the bytes of the outstanding nodes of all tags.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NodeBytes {
+    pub peak: usize,
+    pub avg: usize,
+}
+
+/// The outstanding nodes of each tag over a run, as the auxiliary thread samples them.
+#[derive(Default)]
+pub struct AllocCurve {
+    start: OnceLock<Instant>,
+    samples: Mutex<Vec<(Duration, Vec<TagCount>)>>,
+}
+
+impl AllocCurve {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the start of the measurement, which is the origin of the times of the curve.
+    pub fn start(&self) {
+        let _ = self.start.set(Instant::now());
+    }
+
+    /// Samples the outstanding nodes of each tag.
+    pub fn sample(&self) {
+        let elapsed = self.start.get().map_or(Duration::ZERO, Instant::elapsed);
+        let counts = snapshot();
+        self.samples.lock().unwrap().push((elapsed, counts));
+    }
+
+    /// The peak and the average over the samples of the bytes of the nodes of all tags.
+    pub fn summary(&self) -> NodeBytes {
+        let samples = self.samples.lock().unwrap();
+        let totals = samples
+            .iter()
+            .map(|(_, counts)| counts.iter().map(|count| count.bytes).sum::<usize>());
+        NodeBytes {
+            peak: totals.clone().max().unwrap_or(0),
+            avg: totals.sum::<usize>() / samples.len().max(1),
+        }
+    }
+
+    /// Writes the curve to a CSV file at `path`, with a row of the time since the start of the
+    /// measurement in milliseconds, the tag, and its nodes and their bytes for each tag of each
+    /// sample, and returns the number of the samples.
+    pub fn dump(&self, path: &str) -> usize {
+        let samples = self.samples.lock().unwrap();
+        let mut writer = Writer::from_path(path).unwrap();
+        writer
+            .write_record(["time_ms", "tag", "nodes", "bytes"])
+            .unwrap();
+        for (elapsed, counts) in samples.iter() {
+            for count in counts {
+                writer
+                    .write_record([
+                        elapsed.as_millis().to_string(),
+                        count.name.to_string(),
+                        count.nodes.to_string(),
+                        count.bytes.to_string(),
+                    ])
+                    .unwrap();
+            }
+        }
+        writer.flush().unwrap();
+        samples.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocated, freed, register, snapshot, AllocCurve};
+    use std::alloc::Layout;
+    use std::fs;
+
+    #[test]
+    fn counts_registered_layouts() {
+        // A layout that no other test allocates.
+        let layout = Layout::from_size_align(192, 64).unwrap();
+        register("test node", layout);
+        register("test node", layout);
+        // Another node of the same layout is counted by the tag of the first one.
+        register("other node", layout);
+        assert!(snapshot().iter().all(|c| c.name != "other node"));
+        let count = || {
+            let counts = snapshot();
+            assert_eq!(counts.iter().filter(|c| c.name == "test node").count(), 1);
+            counts.into_iter().find(|c| c.name == "test node").unwrap()
+        };
+
+        let curve = AllocCurve::new();
+        curve.start();
+        for _ in 0..10 {
+            allocated(layout);
+        }
+        freed(layout);
+        // Another layout of the same size is not counted.
+        allocated(Layout::from_size_align(192, 8).unwrap());
+        curve.sample();
+        assert_eq!((count().nodes, count().bytes), (9, 9 * 192));
+        for _ in 0..9 {
+            freed(layout);
+        }
+        curve.sample();
+        assert_eq!(count().nodes, 0);
+
+        let summary = curve.summary();
+        assert!(summary.peak >= 9 * 192);
+        let path = std::env::temp_dir().join(format!("alloc-curve-{}.csv", std::process::id()));
+        assert_eq!(curve.dump(path.to_str().unwrap()), 2);
+        let csv = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(csv.lines().any(|row| row.ends_with(",test node,9,1728")));
+        assert!(csv.lines().any(|row| row.ends_with(",test node,0,0")));
+    }
+}
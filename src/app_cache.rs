@@ -0,0 +1,194 @@
+//! The workload of `app-cache`, which simulates a web cache in front of a slower store.
+//!
+//! Each thread replays a trace of requests for the objects of a catalog. A set of hot objects
+//! receives a given share of the requests with Zipfian popularity, and the rest of the requests
+//! are spread uniformly over the whole catalog, so that most of them are for objects that are
+//! requested too rarely to stay cached. A request that misses the cache admits its object, and the
+//! admission into a full cache evicts another object with CLOCK. Every so often, a contiguous range
+//! of the catalog is invalidated at once, as when the pages of a site are purged after a
+//! deployment, so that the cache removes many objects in a burst.
+//!
+//! The eviction is kept out of the maps, so that the same cache runs on the hash map of every
+//! scheme: the ring of the clock holds the keys of the objects rather than pointers to them, and
+//! the map holds the slot of each object in the ring as its value.
+
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// The popularity of the objects of a catalog.
+pub struct Popularity {
+    keys: usize,
+    hot_keys: usize,
+    /// The fraction of the requests that are for the hot objects.
+    hot_share: f64,
+    /// The cumulative probabilities of the hot objects, from the most popular.
+    cdf: Vec<f64>,
+    catalog: Uniform<usize>,
+}
+
+impl Popularity {
+    /// A catalog of `keys` objects, `hot_keys` of which receive `hot_share` of the requests, the
+    /// `i`-th most popular of them in proportion to `1 / i^zipf`.
+    pub fn new(keys: usize, hot_keys: usize, hot_share: f64, zipf: f64) -> Self {
+        assert!(keys > 0, "The catalog must not be empty");
+        assert!(
+            hot_keys <= keys,
+            "There cannot be more hot objects than objects"
+        );
+        assert!(
+            (0.0..=1.0).contains(&hot_share),
+            "The share of the hot objects must be between 0 and 1"
+        );
+        assert!(zipf >= 0.0, "The Zipfian exponent must not be negative");
+        let mut cdf = Vec::with_capacity(hot_keys);
+        let mut sum = 0.0;
+        for rank in 1..=hot_keys {
+            sum += 1.0 / (rank as f64).powf(zipf);
+            cdf.push(sum);
+        }
+        for p in cdf.iter_mut() {
+            *p /= sum;
+        }
+        Self {
+            keys,
+            hot_keys,
+            hot_share: if hot_keys > 0 { hot_share } else { 0.0 },
+            cdf,
+            catalog: Uniform::from(0..keys),
+        }
+    }
+
+    pub fn keys(&self) -> usize {
+        self.keys
+    }
+
+    /// The key of the hot object of `rank`, 0 being the most popular. The hot objects are spread
+    /// evenly over the catalog, so that an invalidated range holds as many of them as any other.
+    pub fn hot_key(&self, rank: usize) -> usize {
+        rank * (self.keys / self.hot_keys)
+    }
+
+    /// The object of a request.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        if self.hot_share > 0.0 && rng.gen::<f64>() < self.hot_share {
+            let u = rng.gen::<f64>();
+            let rank = self.cdf.partition_point(|&p| p <= u);
+            self.hot_key(rank.min(self.hot_keys - 1))
+        } else {
+            self.catalog.sample(rng)
+        }
+    }
+
+    /// A trace of `len` requests, which is the same for the same `seed`.
+    pub fn trace(&self, len: usize, seed: u64) -> Vec<usize> {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        (0..len).map(|_| self.sample(rng)).collect()
+    }
+}
+
+/// A slot of the ring of the clock.
+#[derive(Default)]
+struct Slot {
+    /// The key of the object of the slot plus one, or 0 for an empty slot.
+    key: AtomicUsize,
+    referenced: AtomicBool,
+}
+
+/// The ring of the clock of a cache, which picks the objects to evict.
+///
+/// Like `ClockCache`, the eviction is approximate under concurrency: an object that is admitted
+/// again while its old slot is being reused may be evicted early, and the cache may hold a few
+/// more objects than its capacity while admissions are in flight.
+pub struct Clock {
+    slots: Box<[Slot]>,
+    hand: AtomicUsize,
+}
+
+impl Clock {
+    /// Creates the ring of a cache of `capacity` objects.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "The capacity of a cache must be positive");
+        Self {
+            slots: (0..capacity).map(|_| Slot::default()).collect(),
+            hand: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Marks the object of `slot` as referenced, for a hit.
+    #[inline]
+    pub fn touch(&self, slot: usize) {
+        let referenced = &self.slots[slot].referenced;
+        // Avoids writing to the slot of a hot object on every hit.
+        if !referenced.load(Ordering::Relaxed) {
+            referenced.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Advances the hand to an unreferenced slot and puts `key` in it, and returns the slot and
+    /// the key that it held, which is to be evicted.
+    pub fn claim(&self, key: usize) -> (usize, Option<usize>) {
+        loop {
+            let index = self.hand.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+            let slot = &self.slots[index];
+            if slot.referenced.swap(false, Ordering::Relaxed) {
+                // Gives the object of the slot a second chance.
+                continue;
+            }
+            let old = slot.key.swap(key + 1, Ordering::AcqRel);
+            return (index, old.checked_sub(1));
+        }
+    }
+
+    /// Empties `slot` if it still holds `key`, whose admission failed.
+    pub fn release(&self, slot: usize, key: usize) {
+        let slot = &self.slots[slot];
+        let _ = slot
+            .key
+            .compare_exchange(key + 1, 0, Ordering::AcqRel, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clock, Popularity};
+
+    #[test]
+    fn popularity() {
+        let popularity = Popularity::new(1000, 10, 0.9, 1.0);
+        let trace = popularity.trace(100_000, 1);
+        assert_eq!(trace, popularity.trace(100_000, 1));
+        assert!(trace.iter().all(|&key| key < 1000));
+        let hot = trace.iter().filter(|&&key| key % 100 == 0).count();
+        // 90% for the hot objects, and 1% of the rest that fall on them.
+        assert!((88_000..92_000).contains(&hot), "{hot}");
+        let first = trace.iter().filter(|&&key| key == 0).count();
+        let second = trace.iter().filter(|&&key| key == 100).count();
+        assert!(first > second * 3 / 2, "{first} {second}");
+
+        let uniform = Popularity::new(1000, 0, 0.9, 1.0);
+        assert!(uniform.trace(1000, 2).iter().all(|&key| key < 1000));
+    }
+
+    #[test]
+    fn clock() {
+        let clock = Clock::new(3);
+        assert_eq!(clock.claim(10), (0, None));
+        assert_eq!(clock.claim(11), (1, None));
+        assert_eq!(clock.claim(12), (2, None));
+        clock.touch(0);
+        // The referenced object gets a second chance.
+        assert_eq!(clock.claim(13), (1, Some(11)));
+        assert_eq!(clock.claim(14), (2, Some(12)));
+        assert_eq!(clock.claim(15), (0, Some(10)));
+        clock.release(1, 13);
+        clock.release(2, 12);
+        assert_eq!(clock.claim(16), (1, None));
+        assert_eq!(clock.claim(17), (2, Some(14)));
+    }
+}
@@ -0,0 +1,251 @@
+//! A local database of baseline results, which tracks the performance of each configuration across
+//! runs.
+//!
+//! With `--baseline <dir>`, the row of a run is summarized into a file of the directory that is
+//! named after a hash of the configured columns of the row (the columns that `compare` does not
+//! count as measured, including the scheme), so that a later run of the same configuration finds
+//! it whatever else was run in between. The first run of a configuration stores its summary as the
+//! baseline, and each later run prints the deltas of its metrics against it and flags a regression
+//! past the threshold. A run with `--baseline-update` replaces the stored summary, e.g., after a
+//! change that is meant to change the performance.
+//!
+//! The hash is FNV-1a, which does not change across Rust versions, and the configured columns are
+//! stored with the summary, so that a collision is detected instead of compared.
+
+use crate::compare::MEASURED;
+use std::fmt;
+use std::fs::{self, create_dir_all};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// The summarized metrics, and whether a higher value is better.
+const METRICS: [(&str, bool); 3] = [
+    ("throughput", true),
+    ("peak_mem", false),
+    ("avg_mem", false),
+];
+
+pub struct Baseline {
+    dir: PathBuf,
+    /// The change of a metric in the wrong direction past which it is a regression (%).
+    threshold: f64,
+    update: bool,
+}
+
+/// The change of a metric of a run from its baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delta {
+    pub metric: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    /// The change relative to the baseline (%).
+    pub percent: f64,
+    pub regression: bool,
+}
+
+impl fmt::Display for Delta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:+.2}% ({} -> {})",
+            self.metric, self.percent, self.baseline, self.current
+        )?;
+        if self.regression {
+            write!(f, " REGRESSION")?;
+        }
+        Ok(())
+    }
+}
+
+/// The FNV-1a hash of the configured columns.
+fn fnv1a(configured: &[(&str, &str)]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for (column, value) in configured {
+        for byte in column
+            .bytes()
+            .chain([b'='])
+            .chain(value.bytes())
+            .chain([b'\n'])
+        {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+impl Baseline {
+    pub fn open<P: AsRef<Path>>(dir: P, threshold: f64, update: bool) -> Self {
+        let dir = dir.as_ref().to_path_buf();
+        create_dir_all(&dir).unwrap_or_else(|e| panic!("Failed to create {}: {e}", dir.display()));
+        Self {
+            dir,
+            threshold,
+            update,
+        }
+    }
+
+    /// Compares the row `record` of the columns `header` with the baseline of its configuration,
+    /// and prints and returns the deltas of the metrics. The row is stored as the baseline if there
+    /// is none yet or with `--baseline-update`, and no delta is returned then.
+    pub fn check<T: AsRef<str>>(&self, header: &[&str], record: &[T]) -> Vec<Delta> {
+        let row = header
+            .iter()
+            .zip(record)
+            .map(|(column, value)| (*column, value.as_ref()))
+            .collect::<Vec<_>>();
+        let configured = row
+            .iter()
+            .filter(|(column, _)| !MEASURED.contains(column))
+            .copied()
+            .collect::<Vec<_>>();
+        let summary = METRICS
+            .iter()
+            .map(|(metric, _)| {
+                let value = row.iter().find(|(column, _)| column == metric);
+                (*metric, value.map_or("", |(_, value)| *value))
+            })
+            .collect::<Vec<_>>();
+        let path = self.dir.join(format!("{:016x}.csv", fnv1a(&configured)));
+
+        let stored = match fs::read(&path) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => panic!("Failed to read {}: {e}", path.display()),
+        };
+        let stored = stored.filter(|_| !self.update).map(|contents| {
+            let mut reader = csv::Reader::from_reader(contents.as_slice());
+            let columns = reader.headers().unwrap().clone();
+            let values = reader.records().next().unwrap().unwrap();
+            columns
+                .iter()
+                .zip(values.iter())
+                .map(|(column, value)| (column.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        });
+        let Some(stored) = stored else {
+            self.store(&path, &configured, &summary);
+            println!("baseline: stored in {}", path.display());
+            return Vec::new();
+        };
+        let (stored_configured, stored_summary) = stored.split_at(stored.len() - METRICS.len());
+        if stored_configured
+            .iter()
+            .map(|(column, value)| (column.as_str(), value.as_str()))
+            .ne(configured.iter().copied())
+        {
+            println!(
+                "baseline: {} is of another configuration with the same hash, not compared",
+                path.display()
+            );
+            return Vec::new();
+        }
+
+        let deltas = METRICS
+            .iter()
+            .zip(stored_summary)
+            .zip(&summary)
+            .filter_map(
+                |(((metric, higher_is_better), (_, baseline)), (_, current))| {
+                    let baseline = baseline.parse::<f64>().ok()?;
+                    let current = current.parse::<f64>().ok()?;
+                    let percent = if baseline != 0.0 {
+                        (current - baseline) / baseline * 100.0
+                    } else {
+                        0.0
+                    };
+                    let worse = if *higher_is_better { -percent } else { percent };
+                    Some(Delta {
+                        metric,
+                        baseline,
+                        current,
+                        percent,
+                        regression: worse > self.threshold,
+                    })
+                },
+            )
+            .collect::<Vec<_>>();
+        println!(
+            "baseline ({}): {}",
+            path.display(),
+            deltas
+                .iter()
+                .map(|delta| delta.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if deltas.iter().any(|delta| delta.regression) {
+            println!(
+                "baseline: regression past {}% against {}",
+                self.threshold,
+                path.display()
+            );
+        }
+        deltas
+    }
+
+    /// Replaces the baseline at `path` with the configured columns and the summary of a run.
+    fn store(&self, path: &Path, configured: &[(&str, &str)], summary: &[(&str, &str)]) {
+        let mut contents = Vec::new();
+        let mut writer = csv::Writer::from_writer(&mut contents);
+        let columns = configured.iter().chain(summary);
+        writer
+            .write_record(columns.clone().map(|(column, _)| column))
+            .unwrap();
+        writer
+            .write_record(columns.map(|(_, value)| value))
+            .unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        fs::write(path, contents)
+            .unwrap_or_else(|e| panic!("Failed to write {}: {e}", path.display()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Baseline;
+    use std::fs;
+
+    #[test]
+    fn tracks_each_configuration() {
+        let dir = std::env::temp_dir().join(format!("baseline-{}", std::process::id()));
+        let header = [
+            "ds",
+            "mm",
+            "threads",
+            "throughput",
+            "peak_mem",
+            "avg_mem",
+            "reps",
+        ];
+        let baseline = Baseline::open(&dir, 5.0, false);
+
+        // The first runs of two configurations are stored.
+        assert!(baseline
+            .check(&header, &["hash-map", "ebr", "4", "1000", "100", "50", "1"])
+            .is_empty());
+        assert!(baseline
+            .check(&header, &["hash-map", "hp", "4", "800", "100", "50", "1"])
+            .is_empty());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        // A slower run of the first one with more repetitions is a regression of the throughput.
+        let deltas = baseline.check(&header, &["hash-map", "ebr", "4", "900", "102", "50", "3"]);
+        assert_eq!(deltas.len(), 3);
+        assert_eq!(deltas[0].metric, "throughput");
+        assert!((deltas[0].percent + 10.0).abs() < 1e-9);
+        assert!(deltas[0].regression);
+        assert!(!deltas[1].regression && !deltas[2].regression);
+
+        // An update replaces the baseline, which the next run is compared with.
+        let updating = Baseline::open(&dir, 5.0, true);
+        assert!(updating
+            .check(&header, &["hash-map", "ebr", "4", "900", "100", "50", "1"])
+            .is_empty());
+        let deltas = baseline.check(&header, &["hash-map", "ebr", "4", "890", "150", "50", "1"]);
+        assert!(!deltas[0].regression);
+        assert!(deltas[1].regression);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,639 @@
+extern crate clap;
+extern crate csv;
+
+extern crate crossbeam_ebr;
+extern crate smr_benchmark;
+
+use clap::{value_parser, Arg, ArgMatches, Command, ValueEnum};
+use crossbeam_utils::thread::scope;
+use rand::prelude::*;
+use std::cmp::max;
+use std::io::{stdout, Write};
+use std::sync::atomic::Ordering;
+use std::sync::{mpsc, Arc, Barrier};
+use std::time::{Duration, Instant};
+
+use smr_benchmark::app_cache::{Clock, Popularity};
+use smr_benchmark::ds_impl::{ebr, hp, hp_pp, nr};
+use smr_benchmark::results::ResultsFile;
+use smr_benchmark::MemSampler;
+
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+#[allow(non_camel_case_types)]
+pub enum MM {
+    NR,
+    EBR,
+    HP,
+    HP_PP,
+}
+
+struct Config {
+    mm: MM,
+    threads: usize,
+    capacity: usize,
+    popularity: Popularity,
+    hot_keys: usize,
+    hot_share: u32,
+    zipf: f64,
+    trace_len: usize,
+    invalidate_period: Option<Duration>,
+    invalidate_keys: usize,
+    aux_thread: usize,
+    aux_thread_period: Duration,
+    sampling: bool,
+    interval: u64,
+    duration: Duration,
+    mem_sampler: MemSampler,
+}
+
+/// The requests that a thread served during a run.
+#[derive(Default)]
+struct Served {
+    requests: u64,
+    hits: u64,
+    evictions: u64,
+}
+
+/// The invalidations of a run.
+#[derive(Default)]
+struct Invalidated {
+    purges: u64,
+    removed: u64,
+}
+
+fn main() {
+    let matches = Command::new("smr_benchmark")
+        .arg(
+            Arg::new("memory manager")
+                .short('m')
+                .value_parser(value_parser!(MM))
+                .required(true)
+                .ignore_case(true)
+                .help("Memeory manager(s)"),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .value_parser(value_parser!(usize))
+                .required(true)
+                .help("Numbers of threads which serve requests."),
+        )
+        .arg(
+            Arg::new("keys")
+                .short('r')
+                .value_parser(value_parser!(usize))
+                .help("The number of objects in the catalog: [0..KEYS]")
+                .default_value("1000000"),
+        )
+        .arg(
+            Arg::new("capacity")
+                .long("capacity")
+                .value_parser(value_parser!(usize))
+                .help("The number of objects that the cache holds.")
+                .default_value("100000"),
+        )
+        .arg(
+            Arg::new("hot keys")
+                .long("hot-keys")
+                .value_parser(value_parser!(usize))
+                .help("The number of hot objects, which are spread evenly over the catalog.")
+                .default_value("10000"),
+        )
+        .arg(
+            Arg::new("hot share")
+                .long("hot-share")
+                .value_parser(value_parser!(u32).range(0..=100))
+                .help(
+                    "The percentage of the requests for the hot objects. The other requests are \
+                     for uniformly random objects of the catalog.",
+                )
+                .default_value("80"),
+        )
+        .arg(
+            Arg::new("zipf")
+                .long("zipf")
+                .value_parser(value_parser!(f64))
+                .help(
+                    "The Zipfian exponent of the popularity of the hot objects. 0 makes them \
+                     equally popular.",
+                )
+                .default_value("0.99"),
+        )
+        .arg(
+            Arg::new("trace len")
+                .long("trace-len")
+                .value_parser(value_parser!(usize))
+                .help(
+                    "The number of requests in the trace of each thread, which the thread \
+                     replays from the start when it reaches its end.",
+                )
+                .default_value("1048576"),
+        )
+        .arg(
+            Arg::new("invalidate period")
+                .long("invalidate-period")
+                .value_parser(value_parser!(u64))
+                .help(
+                    "The period in milliseconds of the bulk invalidations, each of which removes \
+                     a contiguous range of the catalog from the cache. 0 for no invalidation.",
+                )
+                .default_value("100"),
+        )
+        .arg(
+            Arg::new("invalidate keys")
+                .long("invalidate-keys")
+                .value_parser(value_parser!(usize))
+                .help("The number of objects in the range of a bulk invalidation.")
+                .default_value("10000"),
+        )
+        .arg(
+            Arg::new("interval")
+                .short('i')
+                .value_parser(value_parser!(u64))
+                .help("Time interval in seconds to run the benchmark")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .help("Output CSV filename. Appends the data if the file already exists."),
+        )
+        .get_matches();
+
+    let (config, output) = setup(matches);
+    bench(&config, output.as_ref());
+}
+
+fn setup(m: ArgMatches) -> (Config, Option<ResultsFile>) {
+    let mm = m.get_one::<MM>("memory manager").copied().unwrap();
+    let threads = m.get_one::<usize>("threads").copied().unwrap();
+    let keys = m.get_one::<usize>("keys").copied().unwrap();
+    let capacity = m.get_one::<usize>("capacity").copied().unwrap();
+    let hot_keys = m.get_one::<usize>("hot keys").copied().unwrap();
+    let hot_share = m.get_one::<u32>("hot share").copied().unwrap();
+    let zipf = m.get_one::<f64>("zipf").copied().unwrap();
+    let trace_len = m.get_one::<usize>("trace len").copied().unwrap();
+    let invalidate_period = m.get_one::<u64>("invalidate period").copied().unwrap();
+    let invalidate_keys = m.get_one::<usize>("invalidate keys").copied().unwrap();
+    let interval = m.get_one::<u64>("interval").copied().unwrap();
+    let sampling = cfg!(all(not(feature = "sanitize"), target_os = "linux"));
+    let duration = Duration::from_secs(interval);
+
+    assert!(
+        threads >= 1,
+        "The number of threads must be greater than zero!"
+    );
+    assert!(capacity >= 1, "The capacity must be greater than zero!");
+    assert!(
+        keys > capacity,
+        "The catalog must be larger than the cache, or nothing is ever evicted!"
+    );
+    assert!(trace_len >= 1, "The traces must not be empty!");
+    assert!(
+        invalidate_keys <= keys,
+        "An invalidation cannot cover more objects than the catalog!"
+    );
+
+    let output = m.get_one::<String>("output").map(|output_name| {
+        // NOTE: `append` on `bench`
+        ResultsFile::open(
+            output_name,
+            &[
+                "mm",
+                "threads",
+                "keys",
+                "capacity",
+                "hot_keys",
+                "hot_share",
+                "zipf",
+                "invalidate_period_ms",
+                "invalidate_keys",
+                "throughput",
+                "hit_rate",
+                "evictions",
+                "invalidated",
+                "peak_mem",
+                "avg_mem",
+                "peak_garb",
+                "avg_garb",
+                "interval",
+            ],
+        )
+    });
+    let config = Config {
+        mm,
+        threads,
+        capacity,
+        popularity: Popularity::new(keys, hot_keys, hot_share as f64 / 100.0, zipf),
+        hot_keys,
+        hot_share,
+        zipf,
+        trace_len,
+        invalidate_period: (invalidate_period > 0)
+            .then(|| Duration::from_millis(invalidate_period)),
+        invalidate_keys,
+        aux_thread: if sampling { 1 } else { 0 },
+        aux_thread_period: Duration::from_millis(1),
+        sampling,
+        interval,
+        duration,
+        mem_sampler: MemSampler::new(),
+    };
+    (config, output)
+}
+
+fn name<T: ValueEnum>(value: &T) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+fn bench(config: &Config, output: Option<&ResultsFile>) {
+    println!(
+        "{}: {} threads, {} objects, capacity {}, {}% of the requests for {} hot objects",
+        name(&config.mm),
+        config.threads,
+        config.popularity.keys(),
+        config.capacity,
+        config.hot_share,
+        config.hot_keys,
+    );
+    let result = match config.mm {
+        MM::NR => run(config, &Nr(nr::HashMap::with_capacity(config.capacity))),
+        MM::EBR => run(
+            config,
+            &Ebr::new(ebr::HashMap::with_capacity(config.capacity)),
+        ),
+        MM::HP => run(config, &Hp(hp::HashMap::with_capacity(config.capacity))),
+        MM::HP_PP => run(config, &Hp(hp_pp::HashMap::with_capacity(config.capacity))),
+    };
+    let requests_per_sec = result.served.requests / config.interval;
+    let hit_rate = result.served.hits as f64 / result.served.requests.max(1) as f64;
+
+    if let Some(output) = output {
+        output.append([
+            name(&config.mm),
+            config.threads.to_string(),
+            config.popularity.keys().to_string(),
+            config.capacity.to_string(),
+            config.hot_keys.to_string(),
+            config.hot_share.to_string(),
+            config.zipf.to_string(),
+            config
+                .invalidate_period
+                .map_or(0, |period| period.as_millis())
+                .to_string(),
+            config.invalidate_keys.to_string(),
+            requests_per_sec.to_string(),
+            format!("{hit_rate:.4}"),
+            result.served.evictions.to_string(),
+            result.invalidated.removed.to_string(),
+            result.peak_mem.to_string(),
+            result.avg_mem.to_string(),
+            result.peak_garb.to_string(),
+            result.avg_garb.to_string(),
+            config.interval.to_string(),
+        ]);
+    }
+    println!(
+        "requests/s: {}, hit rate: {:.2}%, evictions: {}",
+        requests_per_sec,
+        hit_rate * 100.0,
+        result.served.evictions
+    );
+    println!(
+        "invalidated: {} objects in {} purges",
+        result.invalidated.removed, result.invalidated.purges
+    );
+    println!(
+        "peak mem: {}, avg_mem: {}, peak garb: {}, avg garb: {}",
+        result.peak_mem, result.avg_mem, result.peak_garb, result.avg_garb
+    );
+}
+
+/// The hash map of one of the schemes, which holds the slot of each cached object in the clock.
+trait Scheme: Sync {
+    /// The state of a thread on the scheme.
+    type Local;
+
+    fn local(&self) -> Self::Local;
+
+    /// Ends the critical section of the thread between two of its operations.
+    fn quiesce(&self, _local: &mut Self::Local) {}
+
+    fn get(&self, local: &mut Self::Local, key: usize) -> Option<usize>;
+
+    fn insert(&self, local: &mut Self::Local, key: usize, slot: usize) -> bool;
+
+    fn remove(&self, local: &mut Self::Local, key: usize) -> bool;
+
+    /// The retired nodes of the scheme that are not freed yet.
+    fn garbages(&self) -> usize;
+}
+
+struct Nr(nr::HashMap<usize, usize>);
+
+impl Scheme for Nr {
+    type Local = ();
+
+    fn local(&self) {}
+
+    fn get(&self, _: &mut (), key: usize) -> Option<usize> {
+        use nr::concurrent_map::{ConcurrentMap, OutputHolder};
+        self.0.get(&key).map(|slot| *slot.output())
+    }
+
+    fn insert(&self, _: &mut (), key: usize, slot: usize) -> bool {
+        use nr::ConcurrentMap;
+        self.0.insert(key, slot)
+    }
+
+    fn remove(&self, _: &mut (), key: usize) -> bool {
+        use nr::ConcurrentMap;
+        self.0.remove(&key).is_some()
+    }
+
+    fn garbages(&self) -> usize {
+        0
+    }
+}
+
+struct Ebr {
+    map: ebr::HashMap<usize, usize>,
+    collector: crossbeam_ebr::Collector,
+}
+
+impl Ebr {
+    fn new(map: ebr::HashMap<usize, usize>) -> Self {
+        Self {
+            map,
+            collector: crossbeam_ebr::Collector::new(),
+        }
+    }
+}
+
+/// A pinned thread of `Ebr`, whose guard is dropped before its handle.
+struct Pinned {
+    guard: crossbeam_ebr::Guard,
+    _handle: crossbeam_ebr::LocalHandle,
+}
+
+impl Scheme for Ebr {
+    type Local = Pinned;
+
+    fn local(&self) -> Pinned {
+        let handle = self.collector.register();
+        Pinned {
+            guard: handle.pin(),
+            _handle: handle,
+        }
+    }
+
+    fn quiesce(&self, local: &mut Pinned) {
+        local.guard.repin();
+    }
+
+    fn get(&self, local: &mut Pinned, key: usize) -> Option<usize> {
+        use ebr::concurrent_map::{ConcurrentMap, OutputHolder};
+        self.map.get(&key, &local.guard).map(|slot| *slot.output())
+    }
+
+    fn insert(&self, local: &mut Pinned, key: usize, slot: usize) -> bool {
+        use ebr::ConcurrentMap;
+        self.map.insert(key, slot, &local.guard)
+    }
+
+    fn remove(&self, local: &mut Pinned, key: usize) -> bool {
+        use ebr::ConcurrentMap;
+        self.map.remove(&key, &local.guard).is_some()
+    }
+
+    fn garbages(&self) -> usize {
+        crossbeam_ebr::GLOBAL_GARBAGE_COUNT.load(Ordering::Acquire)
+    }
+}
+
+struct Hp<M>(M);
+
+impl<M: hp::ConcurrentMap<usize, usize> + Sync> Scheme for Hp<M> {
+    type Local = M::Handle<'static>;
+
+    fn local(&self) -> Self::Local {
+        M::handle()
+    }
+
+    fn get(&self, local: &mut Self::Local, key: usize) -> Option<usize> {
+        use hp::concurrent_map::OutputHolder;
+        self.0.get(local, &key).map(|slot| *slot.output())
+    }
+
+    fn insert(&self, local: &mut Self::Local, key: usize, slot: usize) -> bool {
+        self.0.insert(local, key, slot)
+    }
+
+    fn remove(&self, local: &mut Self::Local, key: usize) -> bool {
+        self.0.remove(local, &key).is_some()
+    }
+
+    fn garbages(&self) -> usize {
+        hp_pp::DEFAULT_DOMAIN.num_garbages()
+    }
+}
+
+/// Admits the object of `key` after a miss, and returns whether it evicted another object.
+fn admit<S: Scheme>(cache: &S, local: &mut S::Local, clock: &Clock, key: usize) -> bool {
+    let (slot, old) = clock.claim(key);
+    if !cache.insert(local, key, slot) {
+        // Another thread admitted the object in the meantime.
+        clock.release(slot, key);
+        return false;
+    }
+    // The old object may have been invalidated, or admitted again into another slot.
+    match old {
+        Some(old) if cache.get(local, old) == Some(slot) => cache.remove(local, old),
+        _ => false,
+    }
+}
+
+/// Serves the requests of `trace` from the release of `barrier` for the duration of the run.
+fn serve<S: Scheme>(
+    config: &Config,
+    cache: &S,
+    clock: &Clock,
+    trace: &[usize],
+    barrier: &Barrier,
+) -> Served {
+    let mut served = Served::default();
+    let mut local = cache.local();
+    barrier.wait();
+    let start = Instant::now();
+
+    for &key in trace.iter().cycle() {
+        if start.elapsed() >= config.duration {
+            break;
+        }
+        if let Some(slot) = cache.get(&mut local, key) {
+            clock.touch(slot);
+            served.hits += 1;
+        } else if admit(cache, &mut local, clock, key) {
+            served.evictions += 1;
+        }
+        served.requests += 1;
+        cache.quiesce(&mut local);
+    }
+    served
+}
+
+/// Removes a random range of the catalog from the cache once every period of the invalidations.
+fn invalidate<S: Scheme>(
+    config: &Config,
+    cache: &S,
+    period: Duration,
+    barrier: &Barrier,
+) -> Invalidated {
+    let mut invalidated = Invalidated::default();
+    let keys = config.popularity.keys();
+    let rng = &mut rand::thread_rng();
+    let mut local = cache.local();
+    barrier.wait();
+    let start = Instant::now();
+
+    let mut next_purge = start + period;
+    while start.elapsed() < config.duration {
+        if Instant::now() >= next_purge {
+            let first = rng.gen_range(0..keys);
+            for key in (first..first + config.invalidate_keys).map(|key| key % keys) {
+                if cache.remove(&mut local, key) {
+                    invalidated.removed += 1;
+                }
+                cache.quiesce(&mut local);
+            }
+            invalidated.purges += 1;
+            next_purge += period;
+        }
+        std::thread::sleep(config.aux_thread_period);
+    }
+    invalidated
+}
+
+/// The requests and the invalidations of a run, and the memory usage of the process during it.
+struct Run {
+    served: Served,
+    invalidated: Invalidated,
+    peak_mem: usize,
+    avg_mem: usize,
+    peak_garb: usize,
+    avg_garb: usize,
+}
+
+/// Fills the cache, and runs the threads on it.
+fn run<S: Scheme>(config: &Config, cache: &S) -> Run {
+    let clock = &Clock::new(config.capacity);
+    {
+        let mut local = cache.local();
+        let rng = &mut rand::thread_rng();
+        let mut objects = 0;
+        while objects < config.capacity {
+            let key = config.popularity.sample(rng);
+            if cache.get(&mut local, key).is_none() {
+                admit(cache, &mut local, clock, key);
+                objects += 1;
+            }
+            cache.quiesce(&mut local);
+        }
+    }
+    let traces = (0..config.threads)
+        .map(|thread| config.popularity.trace(config.trace_len, thread as u64))
+        .collect::<Vec<_>>();
+    print!("prefilled... ");
+    stdout().flush().unwrap();
+
+    let invalidator = config.invalidate_period.map_or(0, |_| 1);
+    let barrier = &Arc::new(Barrier::new(
+        config.threads + invalidator + config.aux_thread,
+    ));
+    let (served_sender, served_receiver) = mpsc::channel();
+    let (invalidated_sender, invalidated_receiver) = mpsc::channel();
+    let (mem_sender, mem_receiver) = mpsc::channel();
+
+    scope(|s| {
+        // sampling thread
+        if config.aux_thread > 0 {
+            let mem_sender = mem_sender.clone();
+            s.spawn(move |_| {
+                let mut samples = 0usize;
+                let mut acc = 0usize;
+                let mut peak = 0usize;
+                let mut garb_acc = 0usize;
+                let mut garb_peak = 0usize;
+                barrier.clone().wait();
+
+                let start = Instant::now();
+                let mut next_sampling = start + Duration::from_millis(1);
+                while start.elapsed() < config.duration {
+                    let now = Instant::now();
+                    if now > next_sampling {
+                        let allocated = config.mem_sampler.sample();
+                        samples += 1;
+
+                        acc += allocated;
+                        peak = max(peak, allocated);
+
+                        let garbages = cache.garbages();
+                        garb_acc += garbages;
+                        garb_peak = max(garb_peak, garbages);
+
+                        next_sampling = now + Duration::from_millis(1);
+                    }
+                    std::thread::sleep(config.aux_thread_period);
+                }
+
+                if config.sampling {
+                    mem_sender
+                        .send((peak, acc / samples, garb_peak, garb_acc / samples))
+                        .unwrap();
+                } else {
+                    mem_sender.send((0, 0, 0, 0)).unwrap();
+                }
+            });
+        } else {
+            mem_sender.send((0, 0, 0, 0)).unwrap();
+        }
+
+        if let Some(period) = config.invalidate_period {
+            let invalidated_sender = invalidated_sender.clone();
+            s.spawn(move |_| {
+                let invalidated = invalidate(config, cache, period, barrier);
+                invalidated_sender.send(invalidated).unwrap();
+            });
+        } else {
+            invalidated_sender.send(Invalidated::default()).unwrap();
+        }
+
+        for trace in &traces {
+            let served_sender = served_sender.clone();
+            s.spawn(move |_| {
+                let served = serve(config, cache, clock, trace, barrier);
+                served_sender.send(served).unwrap();
+            });
+        }
+    })
+    .unwrap();
+    println!("end");
+
+    let mut served = Served::default();
+    for _ in 0..config.threads {
+        let local = served_receiver.recv().unwrap();
+        served.requests += local.requests;
+        served.hits += local.hits;
+        served.evictions += local.evictions;
+    }
+    let invalidated = invalidated_receiver.recv().unwrap();
+    let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
+    Run {
+        served,
+        invalidated,
+        peak_mem,
+        avg_mem,
+        peak_garb,
+        avg_garb,
+    }
+}
@@ -5,14 +5,20 @@ use std::cmp::max;
 use std::io::{stdout, Write};
 use std::path::Path;
 use std::sync::atomic::Ordering;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, Perf, DS};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchWriter, Config, Op, Perf, ThreadResults, TreeShape, DS,
+};
+use smr_benchmark::drain_cdrc_ebr;
 use smr_benchmark::ds_impl::cdrc::{
-    BonsaiTreeMap, ConcurrentMap, HHSList, HList, HMList, HashMap, NMTreeMap, SkipList,
+    BonsaiTreeMap, ConcurrentMap, HHSList, HList, HMList, HashMap, NMTreeMap, OutputHolder,
+    SkipList,
 };
+use smr_benchmark::log_event;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -27,7 +33,7 @@ fn main() {
 
 fn bench(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
+    let perf = config.measure(|| match config.ds {
         DS::HList => bench_map::<HList<usize, usize, CsEBR>>(config, PrefillStrategy::Decreasing),
         DS::HMList => bench_map::<HMList<usize, usize, CsEBR>>(config, PrefillStrategy::Decreasing),
         DS::HHSList => {
@@ -48,7 +54,7 @@ fn bench(config: &Config, output: BenchWriter) {
             bench_map::<BonsaiTreeMap<usize, usize, CsEBR>>(config, PrefillStrategy::Decreasing)
         }
         _ => panic!("Unsupported(or unimplemented) data structure for CDRC"),
-    };
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
 }
@@ -68,48 +74,60 @@ impl PrefillStrategy {
         map: &M,
     ) {
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                let cs = unsafe { &Cs::unprotected() };
+                let output = &mut M::empty_output();
+                for key in config.shaped_prefill::<usize>() {
+                    let value = key;
+                    map.insert(key, value, output, cs);
+                }
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            // Safety: We assume that the insert operation does not retire
-                            // any elements. Note that this assumption may not hold for all
-                            // data structures (e.g., Bonsai tree).
-                            let cs = unsafe { &Cs::unprotected() };
-                            let output = &mut M::empty_output();
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key;
-                                map.insert(key, value, output, cs);
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        // Safety: We assume that the insert operation does not retire
+                        // any elements. Note that this assumption may not hold for all
+                        // data structures (e.g., Bonsai tree).
+                        let cs = unsafe { &Cs::unprotected() };
+                        let output = &mut M::empty_output();
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let key = config.prefill_dist.sample(rng);
+                            let value = key;
+                            map.insert(key, value, output, cs);
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
                 let cs = unsafe { &Cs::unprotected() };
                 let output = &mut M::empty_output();
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
+                for key in config.decreasing_prefill::<usize>() {
                     let value = key;
                     map.insert(key, value, output, cs);
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        let output = &mut M::empty_output();
+        config.age(|op, key| {
+            if op == Op::Insert {
+                map.insert(key, key, output, &CsEBR::new())
+            } else {
+                map.remove(&key, output, &CsEBR::new())
+            }
+        });
     }
 }
 
@@ -123,9 +141,12 @@ fn bench_map<M: ConcurrentMap<usize, usize, CsEBR> + Send + Sync>(
     });
     let map = &M::new();
     strategy.prefill(config, map);
+    config.run_hygiene();
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
 
     scope(|s| {
@@ -138,7 +159,7 @@ fn bench_map<M: ConcurrentMap<usize, usize, CsEBR> + Send + Sync>(
                 let mut peak = 0usize;
                 let mut garb_acc = 0usize;
                 let mut garb_peak = 0usize;
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
                 let mut next_sampling = start + config.sampling_period;
@@ -172,48 +193,75 @@ fn bench_map<M: ConcurrentMap<usize, usize, CsEBR> + Send + Sync>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
                 let mut rng = &mut rand::thread_rng();
-                barrier.clone().wait();
+                let output = &mut M::empty_output();
+                config.start(gate, result, |key| {
+                    map.get(&key, output, &CsEBR::new());
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
                 let start = Instant::now();
 
-                let output = &mut M::empty_output();
                 let mut cs = CsEBR::new();
-                while start.elapsed() < config.duration {
+                while start.elapsed() < lifetime {
+                    let op_start = result.op_start(config, config.timed(critical));
                     let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
+                    conflicts.enter(result.index, key);
+                    let op = config.sample_op(critical, &mut rng);
+                    match op {
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(key, key, output, &cs);
+                        }
                         Op::Get => {
-                            map.get(&key, output, &cs);
+                            shadow.run(Op::Get, key, || {
+                                map.get(&key, output, &cs).then(|| *output.output())
+                            });
                         }
                         Op::Insert => {
                             let value = key;
-                            map.insert(key, value, output, &cs);
+                            shadow.run(Op::Insert, key, || {
+                                map.insert(key, value, output, &cs).then_some(value)
+                            });
                         }
                         Op::Remove => {
-                            map.remove(&key, output, &cs);
+                            shadow.run(Op::Remove, key, || {
+                                map.remove(&key, output, &cs).then(|| *output.output())
+                            });
                         }
                     }
+                    conflicts.exit(result.index);
                     ops += 1;
+                    result.record_op(config, critical, Some(op), op_start);
                     cs.clear();
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
                     cs.eager_reclaim();
                 }
 
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
             });
         }
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene_with(drain_cdrc_ebr);
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
@@ -2,17 +2,28 @@ use circ::{ebr_impl, set_counts_between_flush_ebr, Cs, CsEBR};
 use crossbeam_utils::thread::scope;
 use rand::prelude::*;
 use std::cmp::max;
+use std::collections::VecDeque;
 use std::io::{stdout, Write};
 use std::path::Path;
-use std::sync::atomic::Ordering;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, Perf, DS};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchWriter, Config, Mode, Op, Perf, ThreadResults, TreeShape, DS,
+};
+use smr_benchmark::drain_circ_ebr;
+use smr_benchmark::ds_impl::circ_ebr::extendible_hash_map::{
+    DIRECTORY_DOUBLINGS, DIRECTORY_HALVINGS, SEGMENT_MERGES, SEGMENT_SPLITS,
+};
+use smr_benchmark::ds_impl::circ_ebr::swap_map::take_swap_stats;
 use smr_benchmark::ds_impl::circ_ebr::{
-    BonsaiTreeMap, ConcurrentMap, HHSList, HList, HMList, HashMap, NMTreeMap, SkipList,
+    BonsaiTreeMap, ConcurrentMap, ExtendibleHashMap, HHSList, HList, HMList, HashMap, KvHashMap,
+    NMTreeMap, OutputHolder, SkipList, SwapMap,
 };
+use smr_benchmark::log_event;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -27,24 +38,201 @@ fn main() {
 
 fn bench(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
-        DS::HList => bench_map::<HList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HMList => bench_map::<HMList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HHSList => bench_map::<HHSList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HashMap => bench_map::<HashMap<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::NMTree => bench_map::<NMTreeMap<usize, usize>>(config, PrefillStrategy::Random),
-        DS::SkipList => bench_map::<SkipList<usize, usize>>(config, PrefillStrategy::Decreasing),
+    if let Some(delay) = config.memory_delay {
+        let spins = circ::slow_memory::set_delay(delay);
+        println!("memory delay: {delay:?} per snapshot load ({spins} spins)");
+    }
+    let perf = config.measure(|| match config.ds {
+        _ if config.swap_period.is_some() => bench_swap(config),
+        DS::HList => bench_map::<HList<usize, usize>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            Some(HList::range as fn(_, _, _, _, _)),
+            None,
+        ),
+        DS::HMList => bench_map::<HMList<usize, usize>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            Some(HMList::range as fn(_, _, _, _, _)),
+            None,
+        ),
+        DS::HHSList => bench_map::<HHSList<usize, usize>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            (config.mode == Mode::Merge).then_some(HHSList::merge_from as fn(_, _, _) -> _),
+            None,
+            Some(HHSList::range as fn(_, _, _, _, _)),
+            None,
+        ),
+        DS::HashMap => bench_map::<HashMap<usize, usize>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            Some(HashMap::range as fn(_, _, _, _, _)),
+            None,
+        ),
+        DS::ExtHashMap => bench_map::<ExtendibleHashMap<usize, usize>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        DS::KvHashMap => bench_map::<KvHashMap<usize, usize>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        DS::NMTree => bench_map::<NMTreeMap<usize, usize>>(
+            config,
+            PrefillStrategy::Random,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        DS::SkipList => bench_map::<SkipList<usize, usize>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
         DS::BonsaiTree => {
             // Note: Using the `Random` strategy with the Bonsai tree is unsafe
             // because it involves multiple threads with unprotected guards.
             // It is safe for many other data structures that don't retire elements
             // during insertion, but this is not the case for the Bonsai tree.
-            bench_map::<BonsaiTreeMap<usize, usize>>(config, PrefillStrategy::Decreasing)
+            bench_map::<BonsaiTreeMap<usize, usize>>(
+                config,
+                PrefillStrategy::Decreasing,
+                Some(query_history),
+                None,
+                Some(clone_range),
+                None,
+                None,
+            )
         }
         _ => panic!("Unsupported(or unimplemented) data structure for CIRC"),
-    };
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
+    if config.ds == DS::ExtHashMap {
+        println!(
+            "segment splits: {}, merges: {}, directory doublings: {}, halvings: {}",
+            SEGMENT_SPLITS.load(Ordering::Relaxed),
+            SEGMENT_MERGES.load(Ordering::Relaxed),
+            DIRECTORY_DOUBLINGS.load(Ordering::Relaxed),
+            DIRECTORY_HALVINGS.load(Ordering::Relaxed)
+        );
+    }
+}
+
+/// Runs the map benchmark on a `SwapMap` of the data structure with `--swap-period`, which an
+/// extra thread rebuilds and swaps as a whole.
+fn bench_swap(config: &Config) -> Perf {
+    match config.ds {
+        DS::HList => bench_map::<SwapMap<HList<usize, usize>>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        DS::HMList => bench_map::<SwapMap<HMList<usize, usize>>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        DS::HHSList => bench_map::<SwapMap<HHSList<usize, usize>>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        DS::HashMap => bench_map::<SwapMap<HashMap<usize, usize>>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        DS::ExtHashMap => bench_map::<SwapMap<ExtendibleHashMap<usize, usize>>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        DS::KvHashMap => bench_map::<SwapMap<KvHashMap<usize, usize>>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        DS::NMTree => bench_map::<SwapMap<NMTreeMap<usize, usize>>>(
+            config,
+            PrefillStrategy::Random,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        DS::SkipList => bench_map::<SwapMap<SkipList<usize, usize>>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        DS::BonsaiTree => bench_map::<SwapMap<BonsaiTreeMap<usize, usize>>>(
+            config,
+            PrefillStrategy::Decreasing,
+            None,
+            None,
+            None,
+            None,
+            Some(rebuild),
+        ),
+        _ => panic!("Unsupported(or unimplemented) data structure for CIRC"),
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -60,47 +248,61 @@ impl PrefillStrategy {
         // Some data structures (e.g., Bonsai tree, Elim AB-Tree) need SMR's retirement
         // functionality even during insertions.
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                for key in config.shaped_prefill::<usize>() {
+                    let value = key;
+                    map.insert(key, value, &CsEBR::new());
+                }
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key;
-                                map.insert(key, value, &CsEBR::new());
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let key = config.prefill_dist.sample(rng);
+                            let value = key;
+                            map.insert(key, value, &CsEBR::new());
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
+                for key in config.decreasing_prefill::<usize>() {
                     let value = key;
                     map.insert(key, value, &CsEBR::new());
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        config.age(|op, key| {
+            if op == Op::Insert {
+                map.insert(key, key, &CsEBR::new())
+            } else {
+                map.remove(&key, &CsEBR::new()).is_some()
+            }
+        });
     }
 }
 
 fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
     config: &Config,
     strategy: PrefillStrategy,
+    history: Option<fn(&Config, &M) -> u64>,
+    merge: Option<fn(&M, &M, &CsEBR) -> usize>,
+    clone_range: Option<fn(&M, &usize, &usize, &CsEBR) -> usize>,
+    range: Option<fn(&M, &usize, &usize, &mut Vec<usize>, &CsEBR)>,
+    rebuild: Option<fn(&Config, PrefillStrategy, &M) -> u64>,
 ) -> Perf {
     // Note: It tries a collection after two bag flushes.
     match config.bag_size {
@@ -109,10 +311,27 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
     }
     let map = &M::new();
     strategy.prefill(config, map);
+    config.run_hygiene();
+    let timeline = &config.reclaim_timeline();
+    let curve = &config.alloc_curve();
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let history = history.filter(|_| config.history > 0);
+    let gate = &StartGate::new(
+        config.threads
+            + config.aux_thread
+            + history.is_some() as usize
+            + rebuild.is_some() as usize,
+    );
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
+    let (history_sender, history_receiver) = mpsc::channel();
+    let (rebuild_sender, rebuild_receiver) = mpsc::channel();
+    let clone_range = clone_range.zip(config.clone_range);
+    let cloned = &AtomicUsize::new(0);
+    let range = range.zip(config.range_query);
+    let scanned = &AtomicUsize::new(0);
 
     scope(|s| {
         // sampling & interference thread
@@ -124,12 +343,25 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                 let mut peak = 0usize;
                 let mut garb_acc = 0usize;
                 let mut garb_peak = 0usize;
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
+                if let Some(timeline) = timeline {
+                    timeline.start();
+                }
+                if let Some(curve) = curve {
+                    curve.start();
+                }
+                let mut next_timeline = start + config.reclaim_timeline_period;
                 let mut next_sampling = start + config.sampling_period;
                 while start.elapsed() < config.duration {
                     let now = Instant::now();
+                    if let Some(timeline) = timeline {
+                        if now > next_timeline {
+                            timeline.sample();
+                            next_timeline = now + config.reclaim_timeline_period;
+                        }
+                    }
                     if now > next_sampling {
                         let allocated = config.mem_sampler.sample();
                         samples += 1;
@@ -140,6 +372,9 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                         let garb = ebr_impl::GLOBAL_GARBAGE_COUNT.load(Ordering::Acquire);
                         garb_acc += garb;
                         garb_peak = max(garb_peak, garb);
+                        if let Some(curve) = curve {
+                            curve.sample();
+                        }
 
                         next_sampling = now + config.sampling_period;
                     }
@@ -158,46 +393,164 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        // version history thread
+        if let Some(history) = history {
+            let history_sender = history_sender.clone();
+            s.spawn(move |_| {
+                gate.wait();
+                history_sender.send(history(config, map)).unwrap();
+            });
+        }
+
+        // map rebuilding thread
+        if let Some(rebuild) = rebuild {
+            let rebuild_sender = rebuild_sender.clone();
+            s.spawn(move |_| {
+                gate.wait();
+                rebuild_sender.send(rebuild(config, strategy, map)).unwrap();
+            });
+        }
+
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
+                // With `--mode merge`, inserted keys are collected in a list of the thread first.
+                let mut batch = merge.map(|merge| (merge, M::new(), 0));
                 let mut rng = &mut rand::thread_rng();
-                barrier.clone().wait();
+                let mut thread_cloned = 0;
+                let mut thread_scanned = 0;
+                let mut scan = Vec::new();
+                config.start(gate, result, |key| {
+                    map.get(&key, &CsEBR::new());
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
                 let start = Instant::now();
 
                 let mut cs = CsEBR::new();
-                while start.elapsed() < config.duration {
+                while start.elapsed() < lifetime {
+                    let op_start = result.op_start(config, config.timed(critical));
                     let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
-                        Op::Get => {
-                            map.get(&key, &cs);
-                        }
-                        Op::Insert => {
-                            let value = key;
-                            map.insert(key, value, &cs);
-                        }
-                        Op::Remove => {
-                            map.remove(&key, &cs);
+                    conflicts.enter(result.index, key);
+                    let op = if let Some((clone_range, keys)) =
+                        clone_range.filter(|_| rng.gen_ratio(config.clone_range_rate, 100))
+                    {
+                        thread_cloned += clone_range(map, &key, &(key + keys - 1), &cs);
+                        None
+                    } else if let Some((range, keys)) =
+                        range.filter(|_| rng.gen_ratio(config.range_query_rate, 100))
+                    {
+                        scan.clear();
+                        range(map, &key, &(key + keys - 1), &mut scan, &cs);
+                        thread_scanned += scan.len();
+                        None
+                    } else if config.update_rate > 0 && rng.gen_ratio(config.update_rate, 100) {
+                        let value = key;
+                        map.update(key, value, &cs);
+                        None
+                    } else {
+                        let op = config.sample_op(critical, &mut rng);
+                        match op {
+                            _ if config.get_or_insert(critical, rng) => {
+                                map.get_or_insert(key, key, &cs);
+                            }
+                            Op::Get => {
+                                shadow
+                                    .run(Op::Get, key, || map.get(&key, &cs).map(|v| *v.output()));
+                            }
+                            Op::Insert => {
+                                let value = key;
+                                if let Some((merge, batch, batched)) = batch.as_mut() {
+                                    batch.insert(key, value, &cs);
+                                    *batched += 1;
+                                    if *batched == config.merge_batch {
+                                        merge(map, batch, &cs);
+                                        *batched = 0;
+                                    }
+                                } else {
+                                    shadow.run(Op::Insert, key, || {
+                                        map.insert(key, value, &cs).then_some(value)
+                                    });
+                                }
+                            }
+                            Op::Remove => {
+                                shadow.run(Op::Remove, key, || {
+                                    map.remove(&key, &cs).map(|v| *v.output())
+                                });
+                            }
                         }
-                    }
+                        Some(op)
+                    };
+                    conflicts.exit(result.index);
                     ops += 1;
+                    if let Some(timeline) = timeline {
+                        timeline.publish(result.index, ops);
+                    }
+                    result.record_op(config, critical, op, op_start);
                     cs.clear();
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
                 }
 
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops);
+                cloned.fetch_add(thread_cloned, Ordering::Relaxed);
+                scanned.fetch_add(thread_scanned, Ordering::Relaxed);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
+                let deferred = circ::stats::local_deferred_stats();
+                result.deferred_len = deferred.len;
+                result.deferred_peak = deferred.peak;
             });
         }
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene_with(drain_circ_ebr);
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
+    if history.is_some() {
+        println!(
+            "lookups in past versions: {}",
+            history_receiver.recv().unwrap()
+        );
+    }
+    if rebuild.is_some() {
+        println!(
+            "map swaps: {}, replaced versions: {}",
+            rebuild_receiver.recv().unwrap(),
+            take_swap_stats()
+        );
+    }
+    if clone_range.is_some() {
+        println!(
+            "keys copied by the range clones: {}",
+            cloned.load(Ordering::Relaxed)
+        );
+    }
+    if range.is_some() {
+        println!(
+            "keys returned by the range queries: {}",
+            scanned.load(Ordering::Relaxed)
+        );
+    }
+    results.print_deferred();
+    if let Some(timeline) = timeline {
+        let path = config.reclaim_timeline.as_ref().unwrap();
+        let (written, overwritten) = timeline.dump(path);
+        println!("reclamation timeline: {written} events, {overwritten} overwritten");
+    }
+    if let Some(curve) = curve {
+        config.record_alloc_curve(curve);
     }
-    let ops_per_sec = ops / config.interval;
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
@@ -207,3 +560,71 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
         avg_garb,
     }
 }
+
+/// Records a version of the Bonsai tree every `--history-period`, retaining the last `--history`
+/// ones, and looks up random keys in random retained versions meanwhile.
+///
+/// Returns the number of lookups.
+fn query_history(config: &Config, map: &BonsaiTreeMap<usize, usize>) -> u64 {
+    let rng = &mut rand::thread_rng();
+    let mut versions = VecDeque::with_capacity(config.history);
+    let mut lookups = 0;
+    let mut cs = CsEBR::new();
+    let start = Instant::now();
+    let mut next_record = start;
+    while start.elapsed() < config.duration {
+        let now = Instant::now();
+        if now >= next_record {
+            if versions.len() == config.history {
+                versions.pop_front();
+            }
+            versions.push_back(map.version(&cs));
+            next_record = now + config.history_period;
+        }
+        let version = &versions[rng.gen_range(0..versions.len())];
+        let key = config.key_dist.sample(rng);
+        BonsaiTreeMap::get_in(version, &key, &cs);
+        lookups += 1;
+        cs.clear();
+    }
+    lookups
+}
+
+/// Copies the keys in `lo..=hi` out of the Bonsai tree with `--clone-range`.
+///
+/// Returns the number of the copied keys.
+fn clone_range(map: &BonsaiTreeMap<usize, usize>, lo: &usize, hi: &usize, cs: &CsEBR) -> usize {
+    map.clone_range(lo, hi, cs).len()
+}
+
+/// Rebuilds the map from scratch every `--swap-period` with the keys of a prefill, and publishes
+/// the new map in place of the current one, whose readers go on with it until they are done.
+///
+/// Returns the number of swaps.
+fn rebuild<M: ConcurrentMap<usize, usize>>(
+    config: &Config,
+    strategy: PrefillStrategy,
+    map: &SwapMap<M>,
+) -> u64 {
+    let rng = &mut rand::thread_rng();
+    let mut swaps = 0;
+    let start = Instant::now();
+    loop {
+        std::thread::sleep(config.swap_period.unwrap());
+        if start.elapsed() >= config.duration {
+            return swaps;
+        }
+        let fresh = M::new();
+        let keys: Box<dyn Iterator<Item = usize> + '_> = match strategy {
+            PrefillStrategy::Random => {
+                Box::new((0..config.prefill).map(|_| config.prefill_dist.sample(rng)))
+            }
+            PrefillStrategy::Decreasing => config.decreasing_prefill::<usize>(),
+        };
+        for key in keys {
+            fresh.insert(key, key, &CsEBR::new());
+        }
+        map.publish(fresh, &CsEBR::new());
+        swaps += 1;
+    }
+}
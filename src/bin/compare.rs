@@ -0,0 +1,145 @@
+extern crate clap;
+extern crate smr_benchmark;
+
+use clap::{value_parser, Arg, Command};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fs::File;
+
+use smr_benchmark::compare::{read_groups, varying_columns, Comparison, Groups};
+
+fn main() {
+    let matches = Command::new("compare")
+        .arg(
+            Arg::new("results")
+                .num_args(1..=2)
+                .required(true)
+                .help("The CSV files of the two sides, or a single file with both `-s` schemes"),
+        )
+        .arg(
+            Arg::new("schemes")
+                .short('s')
+                .long("schemes")
+                .num_args(2)
+                .help("The schemes of the two sides, e.g., `-s ebr hp`"),
+        )
+        .arg(
+            Arg::new("metric")
+                .long("metric")
+                .help("The column that is compared")
+                .default_value("throughput"),
+        )
+        .arg(
+            Arg::new("alpha")
+                .long("alpha")
+                .value_parser(value_parser!(f64))
+                .help("The significance level of the tests")
+                .default_value("0.05"),
+        )
+        .arg(
+            Arg::new("resamples")
+                .long("resamples")
+                .value_parser(value_parser!(usize))
+                .help("The number of bootstrap resamples of each configuration")
+                .default_value("10000"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_parser(value_parser!(u64))
+                .help("The seed of the bootstrap resamples")
+                .default_value("0"),
+        )
+        .get_matches();
+
+    let files = matches
+        .get_many::<String>("results")
+        .unwrap()
+        .collect::<Vec<_>>();
+    let schemes = matches
+        .get_many::<String>("schemes")
+        .map(|schemes| schemes.map(String::as_str).collect::<Vec<_>>());
+    let metric = matches.get_one::<String>("metric").unwrap();
+    let alpha = matches.get_one::<f64>("alpha").copied().unwrap();
+    let resamples = matches.get_one::<usize>("resamples").copied().unwrap();
+    let seed = matches.get_one::<u64>("seed").copied().unwrap();
+
+    assert!(
+        files.len() == 2 || schemes.is_some(),
+        "Two schemes must be given with `-s` to compare them within one file!"
+    );
+    assert!(
+        alpha > 0.0 && alpha < 1.0,
+        "The significance level must be between 0 and 1!"
+    );
+
+    let side = |i: usize| {
+        let path = files[i.min(files.len() - 1)];
+        let scheme = schemes.as_ref().map(|schemes| schemes[i]);
+        let file = File::open(path).unwrap_or_else(|e| panic!("Cannot open {path}: {e}"));
+        let groups =
+            read_groups(file, metric, scheme).unwrap_or_else(|e| panic!("Cannot read {path}: {e}"));
+        let label = match scheme {
+            Some(scheme) if files.len() == 2 => format!("{scheme} in {path}"),
+            Some(scheme) => scheme.to_string(),
+            None => path.to_string(),
+        };
+        (label, groups)
+    };
+    let (label_a, a) = side(0);
+    let (label_b, b) = side(1);
+    report(&label_a, &a, &label_b, &b, metric, alpha, resamples, seed);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn report(
+    label_a: &str,
+    a: &Groups,
+    label_b: &str,
+    b: &Groups,
+    metric: &str,
+    alpha: f64,
+    resamples: usize,
+    seed: u64,
+) {
+    let rng = &mut StdRng::seed_from_u64(seed);
+    let shared = a
+        .iter()
+        .filter_map(|(key, runs)| b.get(key).map(|other| (key, runs, other)))
+        .collect::<Vec<_>>();
+    let columns = varying_columns(shared.iter().map(|(key, _, _)| *key));
+
+    println!("{label_a} vs {label_b}: {metric}, alpha = {alpha}");
+    let (mut significant, mut not_significant, mut too_few) = (0, 0, 0);
+    for (key, runs_a, runs_b) in &shared {
+        let name = key
+            .iter()
+            .filter(|(column, _)| columns.contains(column))
+            .map(|(column, value)| format!("{column}={value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        // A single run has no spread to test a difference against.
+        if runs_a.len() < 2 || runs_b.len() < 2 {
+            println!(
+                "{name}: too few runs ({} vs {})",
+                runs_a.len(),
+                runs_b.len()
+            );
+            too_few += 1;
+            continue;
+        }
+        let comparison = Comparison::new(runs_a, runs_b, alpha, resamples, rng);
+        println!("{name}: {comparison}");
+        if comparison.significant {
+            significant += 1;
+        } else {
+            not_significant += 1;
+        }
+    }
+    println!(
+        "{} configurations: {significant} significant, {not_significant} not significant, \
+         {too_few} with too few runs, {} on one side only",
+        shared.len(),
+        a.len() + b.len() - 2 * shared.len()
+    );
+}
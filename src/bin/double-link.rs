@@ -7,16 +7,14 @@ extern crate smr_benchmark;
 use circ::Cs;
 use clap::{value_parser, Arg, ArgMatches, Command, ValueEnum};
 use crossbeam_utils::thread::scope;
-use csv::Writer;
 use rand::distributions::Uniform;
 use rand::prelude::*;
 use std::cmp::max;
-use std::fs::{create_dir_all, File, OpenOptions};
-use std::path::Path;
 use std::sync::atomic::{compiler_fence, Ordering};
 use std::sync::{mpsc, Arc, Barrier};
 use std::time::{Duration, Instant};
 
+use smr_benchmark::results::ResultsFile;
 use smr_benchmark::{ds_impl, MemSampler};
 
 #[derive(PartialEq, Debug, ValueEnum, Clone)]
@@ -76,11 +74,11 @@ fn main() {
         )
         .get_matches();
 
-    let (config, mut output) = setup(matches);
-    bench(&config, output.as_mut());
+    let (config, output) = setup(matches);
+    bench(&config, output.as_ref());
 }
 
-fn setup(m: ArgMatches) -> (Config, Option<Writer<File>>) {
+fn setup(m: ArgMatches) -> (Config, Option<ResultsFile>) {
     let mm = m.get_one::<MM>("memory manager").cloned().unwrap();
     let threads = m.get_one::<usize>("threads").copied().unwrap();
     let interval = m.get_one::<u64>("interval").copied().unwrap();
@@ -93,34 +91,18 @@ fn setup(m: ArgMatches) -> (Config, Option<Writer<File>>) {
     );
 
     let output = m.get_one::<String>("output").map(|output_name| {
-        let output_path = Path::new(output_name);
-        let dir = output_path.parent().unwrap();
-        create_dir_all(dir).unwrap();
-        match OpenOptions::new().read(true).append(true).open(output_path) {
-            Ok(f) => csv::Writer::from_writer(f),
-            Err(_) => {
-                let f = OpenOptions::new()
-                    .read(true)
-                    .write(true)
-                    .create(true)
-                    .open(output_path)
-                    .unwrap();
-                let mut output = csv::Writer::from_writer(f);
-                // NOTE: `write_record` on `bench`
-                output
-                    .write_record([
-                        "mm",
-                        "threads",
-                        "throughput",
-                        "peak_mem",
-                        "avg_mem",
-                        "interval",
-                    ])
-                    .unwrap();
-                output.flush().unwrap();
-                output
-            }
-        }
+        // NOTE: `append` on `bench`
+        ResultsFile::open(
+            output_name,
+            &[
+                "mm",
+                "threads",
+                "throughput",
+                "peak_mem",
+                "avg_mem",
+                "interval",
+            ],
+        )
     });
     let mem_sampler = MemSampler::new();
     let config = Config {
@@ -137,7 +119,7 @@ fn setup(m: ArgMatches) -> (Config, Option<Writer<File>>) {
     (config, output)
 }
 
-fn bench(config: &Config, output: Option<&mut Writer<File>>) {
+fn bench(config: &Config, output: Option<&ResultsFile>) {
     println!(
         "{}: {} threads",
         config.mm.to_possible_value().unwrap().get_name(),
@@ -155,22 +137,19 @@ fn bench(config: &Config, output: Option<&mut Writer<File>>) {
         MM::CIRC_HP => bench_queue_circ_hp(config),
     };
     if let Some(output) = output {
-        output
-            .write_record(&[
-                config
-                    .mm
-                    .to_possible_value()
-                    .unwrap()
-                    .get_name()
-                    .to_string(),
-                config.threads.to_string(),
-                ops_per_sec.to_string(),
-                peak_mem.to_string(),
-                avg_mem.to_string(),
-                config.interval.to_string(),
-            ])
-            .unwrap();
-        output.flush().unwrap();
+        output.append([
+            config
+                .mm
+                .to_possible_value()
+                .unwrap()
+                .get_name()
+                .to_string(),
+            config.threads.to_string(),
+            ops_per_sec.to_string(),
+            peak_mem.to_string(),
+            avg_mem.to_string(),
+            config.interval.to_string(),
+        ]);
     }
     println!(
         "ops/s: {}, peak mem: {}, avg_mem: {}",
@@ -2,19 +2,42 @@ use crossbeam_utils::thread::scope;
 use rand::prelude::*;
 use std::cmp::max;
 use std::io::{stdout, Write};
+use std::iter;
+use std::marker::PhantomData;
 use std::mem::ManuallyDrop;
 use std::path::Path;
-use std::sync::atomic::Ordering;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 use typenum::{Unsigned, U1, U4};
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, OpsPerCs, Perf, DS};
+use smr_benchmark::config::harness::{self, Scheme};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchKey, BenchWriter, CompositeKey, Config, KeyType, Mode, Op, OpsPerCs, Perf,
+    StealReport, ThreadResult, ThreadResults, TreeShape, DS,
+};
+use smr_benchmark::drain_ebr;
+use smr_benchmark::ds_impl::deque::{self, Steal};
+use smr_benchmark::ds_impl::ebr::concurrent_map::OutputHolder;
+use smr_benchmark::ds_impl::ebr::extendible_hash_map::{
+    DIRECTORY_DOUBLINGS, DIRECTORY_HALVINGS, SEGMENT_MERGES, SEGMENT_SPLITS,
+};
+use smr_benchmark::ds_impl::ebr::list::{
+    harris_skipped_unlinks, set_harris_unlink_threshold, set_hhs_eventual_reads, HHS_STALE_HITS,
+};
+#[cfg(feature = "persistent")]
+use smr_benchmark::ds_impl::ebr::PersistentHashMap;
 use smr_benchmark::ds_impl::ebr::{
-    BonsaiTreeMap, ConcurrentMap, EFRBTree, ElimABTree, HHSList, HList, HMList, HashMap, NMTreeMap,
-    SkipList,
+    BonsaiTreeMap, ChaseLevDeque, ClockCache, ConcurrentCounter, ConcurrentDeque, ConcurrentMap,
+    ConcurrentSet, EFRBTree, ElimABTree, ExtendibleHashMap, HHSList, HHSListSet, HList, HListSet,
+    HMList, HMListSet, HashMap, HashMapCounter, Log, MarkerSkipList, MovePair, NMTreeMap, Side,
+    SkipList, SkipListSet, WaitFreeTreap,
 };
+use smr_benchmark::epoch_dump::EpochTimeline;
+use smr_benchmark::log_event;
+use smr_benchmark::signals;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -30,24 +53,136 @@ fn main() {
     }
 }
 
+/// The number of `get`s on the map over all repetitions, for the stale hit rate.
+static MAP_GETS: AtomicU64 = AtomicU64::new(0);
+
 fn bench<N: Unsigned>(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
-        DS::HList => bench_map::<HList<usize, usize>, N>(config, PrefillStrategy::Decreasing),
-        DS::HMList => bench_map::<HMList<usize, usize>, N>(config, PrefillStrategy::Decreasing),
-        DS::HHSList => bench_map::<HHSList<usize, usize>, N>(config, PrefillStrategy::Decreasing),
-        DS::HashMap => bench_map::<HashMap<usize, usize>, N>(config, PrefillStrategy::Decreasing),
-        DS::NMTree => bench_map::<NMTreeMap<usize, usize>, N>(config, PrefillStrategy::Random),
-        DS::BonsaiTree => {
+    set_harris_unlink_threshold(config.harris_unlink_threshold);
+    set_hhs_eventual_reads(config.hhs_eventual_reads);
+    let mut perf = config.measure(|| match config.mode {
+        Mode::Map => match config.ds {
+            DS::HList => {
+                bench_map::<usize, HList<usize, usize>, N>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HMList => {
+                bench_map::<usize, HMList<usize, usize>, N>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HHSList => {
+                bench_map::<usize, HHSList<usize, usize>, N>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HashMap => {
+                bench_map::<usize, HashMap<usize, usize>, N>(config, PrefillStrategy::Decreasing)
+            }
+            #[cfg(feature = "persistent")]
+            DS::PersistentHashMap => bench_map::<usize, PersistentHashMap<usize, usize>, N>(
+                config,
+                PrefillStrategy::Decreasing,
+            ),
+            DS::ExtHashMap => bench_map::<usize, ExtendibleHashMap<usize, usize>, N>(
+                config,
+                PrefillStrategy::Decreasing,
+            ),
+            DS::NMTree => match config.key_type {
+                KeyType::Usize => {
+                    bench_map::<usize, NMTreeMap<usize, usize>, N>(config, PrefillStrategy::Random)
+                }
+                KeyType::U128 => {
+                    bench_map::<u128, NMTreeMap<u128, usize>, N>(config, PrefillStrategy::Random)
+                }
+                KeyType::Composite => bench_map::<CompositeKey, NMTreeMap<CompositeKey, usize>, N>(
+                    config,
+                    PrefillStrategy::Random,
+                ),
+            },
             // For Bonsai Tree, it would be faster to use a single thread to prefill.
-            bench_map::<BonsaiTreeMap<usize, usize>, N>(config, PrefillStrategy::Decreasing)
-        }
-        DS::EFRBTree => bench_map::<EFRBTree<usize, usize>, N>(config, PrefillStrategy::Random),
-        DS::SkipList => bench_map::<SkipList<usize, usize>, N>(config, PrefillStrategy::Decreasing),
-        DS::ElimAbTree => bench_map::<ElimABTree<usize, usize>, N>(config, PrefillStrategy::Random),
-    };
+            DS::BonsaiTree => match config.key_type {
+                KeyType::Usize => bench_map::<usize, BonsaiTreeMap<usize, usize>, N>(
+                    config,
+                    PrefillStrategy::Decreasing,
+                ),
+                KeyType::U128 => bench_map::<u128, BonsaiTreeMap<u128, usize>, N>(
+                    config,
+                    PrefillStrategy::Decreasing,
+                ),
+                KeyType::Composite => bench_map::<
+                    CompositeKey,
+                    BonsaiTreeMap<CompositeKey, usize>,
+                    N,
+                >(config, PrefillStrategy::Decreasing),
+            },
+            DS::EFRBTree => {
+                bench_map::<usize, EFRBTree<usize, usize>, N>(config, PrefillStrategy::Random)
+            }
+            DS::SkipList => {
+                bench_map::<usize, SkipList<usize, usize>, N>(config, PrefillStrategy::Decreasing)
+            }
+            DS::MarkerSkipList => bench_map::<usize, MarkerSkipList<usize, usize>, N>(
+                config,
+                PrefillStrategy::Decreasing,
+            ),
+            DS::ElimAbTree => {
+                bench_map::<usize, ElimABTree<usize, usize>, N>(config, PrefillStrategy::Random)
+            }
+            DS::WfTreap => {
+                bench_map::<usize, WaitFreeTreap<usize, usize>, N>(config, PrefillStrategy::Random)
+            }
+            _ => panic!("Unsupported(or unimplemented) data structure for EBR"),
+        },
+        Mode::Set => match config.ds {
+            DS::HList => bench_set::<HListSet<usize>, N>(config),
+            DS::HMList => bench_set::<HMListSet<usize>, N>(config),
+            DS::HHSList => bench_set::<HHSListSet<usize>, N>(config),
+            DS::SkipList => bench_set::<SkipListSet<usize>, N>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the set mode"),
+        },
+        Mode::Move => match config.ds {
+            DS::HashMap => bench_move::<N>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the move mode"),
+        },
+        Mode::Merge => match config.ds {
+            DS::HHSList => bench_merge::<N>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the merge mode"),
+        },
+        Mode::Cache => match config.ds {
+            DS::HashMap => bench_cache::<N>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the cache mode"),
+        },
+        Mode::Counter => match config.ds {
+            DS::HashMap => bench_counter::<HashMapCounter<usize>, N>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the counter mode"),
+        },
+        Mode::Log => bench_log::<N>(config),
+        Mode::Deque => match config.ds {
+            DS::ChaseLev => bench_deque::<ChaseLevDeque<usize>, N>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the deque mode"),
+        },
+    });
+    if config.hhs_eventual_reads {
+        let stale_hits = HHS_STALE_HITS.load(Ordering::Relaxed);
+        let gets = MAP_GETS.load(Ordering::Relaxed);
+        perf.stale_hit_rate = Some(stale_hits as f64 / gets.max(1) as f64);
+        println!("stale hits: {stale_hits} of {gets} gets");
+    }
     output.write_record(config, &perf);
     println!("{}", perf);
+    if config.harris_unlink_threshold > 1 {
+        let (chains, nodes) = harris_skipped_unlinks();
+        println!(
+            "skipped unlinks per repetition: {} chains, {} nodes",
+            chains / perf.reps,
+            nodes / perf.reps
+        );
+    }
+    if config.ds == DS::ExtHashMap {
+        println!(
+            "segment splits: {}, merges: {}, directory doublings: {}, halvings: {}",
+            SEGMENT_SPLITS.load(Ordering::Relaxed),
+            SEGMENT_MERGES.load(Ordering::Relaxed),
+            DIRECTORY_DOUBLINGS.load(Ordering::Relaxed),
+            DIRECTORY_HALVINGS.load(Ordering::Relaxed)
+        );
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -59,53 +194,72 @@ pub enum PrefillStrategy {
 }
 
 impl PrefillStrategy {
-    fn prefill<M: ConcurrentMap<usize, usize> + Send + Sync>(self, config: &Config, map: &M) {
+    fn prefill<K: BenchKey, M: ConcurrentMap<K, usize> + Send + Sync>(
+        self,
+        config: &Config,
+        map: &M,
+    ) {
         // Some data structures (e.g., Bonsai tree, Elim AB-Tree) need SMR's retirement
         // functionality even during insertions.
         let collector = &crossbeam_ebr::Collector::new();
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                let handle = collector.register();
+                for index in config.shaped_prefill::<K>() {
+                    let key = K::from_index(index);
+                    let value = index;
+                    map.insert(key, value, &handle.pin());
+                }
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            let handle = collector.register();
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key;
-                                map.insert(key, value, &handle.pin());
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        let handle = collector.register();
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let index = config.prefill_dist.sample(rng);
+                            let key = K::from_index(index);
+                            let value = index;
+                            map.insert(key, value, &handle.pin());
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
                 for _ in 0..config.prefill {}
             }
             PrefillStrategy::Decreasing => {
                 let handle = collector.register();
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
-                    let value = key;
+                for index in config.decreasing_prefill::<K>() {
+                    let key = K::from_index(index);
+                    let value = index;
                     map.insert(key, value, &handle.pin());
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        let handle = collector.register();
+        config.age(|op, index| {
+            let key = K::from_index(index);
+            if op == Op::Insert {
+                map.insert(key, index, &handle.pin())
+            } else {
+                map.remove(&key, &handle.pin()).is_some()
+            }
+        });
     }
 }
 
-fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
+fn bench_map<K: BenchKey, M: ConcurrentMap<K, usize> + Send + Sync, N: Unsigned>(
     config: &Config,
     strategy: PrefillStrategy,
 ) -> Perf {
@@ -114,14 +268,24 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
         BagSize::Small => crossbeam_ebr::set_bag_capacity(512),
         BagSize::Large => crossbeam_ebr::set_bag_capacity(4096),
     }
-    let map = &M::new();
+    let map = &if config.slab {
+        M::with_slab()
+    } else {
+        M::new()
+    };
     strategy.prefill(config, map);
+    config.run_hygiene();
 
     let collector = &crossbeam_ebr::Collector::new();
+    let dump = &config.epoch_dump();
+    let curve = &config.alloc_curve();
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
+    let scanned = &AtomicUsize::new(0);
 
     scope(|s| {
         // sampling & interference thread
@@ -134,19 +298,45 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
                 let mut garb_acc = 0usize;
                 let mut garb_peak = 0usize;
                 let handle = collector.register();
-                barrier.clone().wait();
+                // The auxiliary thread takes the last slot of the timeline.
+                let mut timeline = dump.as_ref().map(|dump| {
+                    let names = (0..config.threads)
+                        .map(|t| format!("t{t}"))
+                        .chain(iter::once("aux".to_string()))
+                        .collect::<Vec<_>>();
+                    EpochTimeline::create(dump, config.epoch_dump.as_ref().unwrap(), &names)
+                });
+                gate.wait();
 
                 let start = Instant::now();
+                if let Some(curve) = curve {
+                    curve.start();
+                }
                 // Immediately drop if no non-coop else keep it and repin periodically.
                 let mut guard = ManuallyDrop::new(handle.pin());
                 if config.non_coop == 0 {
                     unsafe { ManuallyDrop::drop(&mut guard) };
+                } else if let Some(dump) = dump {
+                    dump.pin(config.threads, 0);
                 }
                 let mut next_sampling = start + config.sampling_period;
                 let mut next_repin = start + config.non_coop_period;
-                while start.elapsed() < config.duration {
+                let mut next_dump = start + config.epoch_dump_period;
+                // The run is extended by the pauses with `--signals`.
+                let paused = signals::paused_time();
+                while start.elapsed()
+                    < config.duration + signals::paused_time().saturating_sub(paused)
+                {
                     let now = Instant::now();
-                    if now > next_sampling {
+                    if let Some(timeline) = timeline.as_mut() {
+                        if now > next_dump {
+                            let garbages =
+                                crossbeam_ebr::GLOBAL_GARBAGE_COUNT.load(Ordering::Acquire);
+                            timeline.sample(now - start, garbages);
+                            next_dump = now + config.epoch_dump_period;
+                        }
+                    }
+                    if now > next_sampling && !signals::paused() {
                         let allocated = config.mem_sampler.sample();
                         samples += 1;
 
@@ -156,11 +346,17 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
                         let garbages = crossbeam_ebr::GLOBAL_GARBAGE_COUNT.load(Ordering::Acquire);
                         garb_acc += garbages;
                         garb_peak = max(garb_peak, garbages);
+                        if let Some(curve) = curve {
+                            curve.sample();
+                        }
 
                         next_sampling = now + config.sampling_period;
                     }
                     if now > next_repin {
                         (*guard).repin();
+                        if let Some(dump) = dump {
+                            dump.pin(config.threads, 0);
+                        }
                         next_repin = now + config.non_coop_period;
                     }
                     std::thread::sleep(config.aux_thread_period);
@@ -169,6 +365,9 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
                 if config.non_coop > 0 {
                     unsafe { ManuallyDrop::drop(&mut guard) };
                 }
+                if let Some(timeline) = timeline {
+                    timeline.finish();
+                }
 
                 if config.sampling {
                     mem_sender
@@ -182,50 +381,189 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut gets: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
+                let mut cache = config.stale_cache::<usize>();
+                let mut trace = config.trace_cursor(result.index);
+                let mut batch = Vec::with_capacity(config.multi_get);
+                let mut batched: u64 = 0;
                 let mut rng = &mut rand::thread_rng();
-                let handle = collector.register();
-                barrier.clone().wait();
+                let mut thread_scanned = 0;
+                let registration = config.registers_early().then(|| collector.register());
+                config.start(gate, result, |index| {
+                    if let Some(handle) = &registration {
+                        map.get(&K::from_index(index), &handle.pin());
+                    }
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
+                let handle = registration.unwrap_or_else(|| collector.register());
                 let start = Instant::now();
 
                 let mut guard = handle.pin();
-                while start.elapsed() < config.duration {
-                    let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
+                if let Some(dump) = dump {
+                    dump.pin(result.index, ops);
+                }
+                // The first thread removes the ranges.
+                let mut next_range_removal = config
+                    .range_remove
+                    .filter(|_| result.index == 0)
+                    .map(|_| start + config.range_remove_period);
+                while start.elapsed() < lifetime + result.paused {
+                    if let Some(next) = next_range_removal.as_mut() {
+                        let now = Instant::now();
+                        if now >= *next {
+                            let lo = config.key_dist.sample(rng);
+                            let hi = lo + config.range_remove.unwrap();
+                            let removed =
+                                map.remove_range(&K::from_index(lo), &K::from_index(hi), &guard);
+                            result.range_removals += 1;
+                            result.range_removed += removed as u64;
+                            result.slowest_range_removal =
+                                result.slowest_range_removal.max(now.elapsed());
+                            *next = now + config.range_remove_period;
+                        }
+                    }
+                    let op_start = result.op_start(
+                        config,
+                        config.timed(critical) || config.range_remove.is_some(),
+                    );
+                    let (op, index) = match trace.as_mut() {
+                        Some(trace) => trace.next_op(),
+                        None => (
+                            config.sample_op(critical, &mut rng),
+                            config.key_dist.sample(rng),
+                        ),
+                    };
+                    let key = K::from_index(index);
+                    conflicts.enter(result.index, index);
+                    // A partial scan takes the place of the sampled operation.
+                    let scan = config
+                        .partial_scan
+                        .filter(|_| rng.gen_ratio(config.partial_scan_rate, 100));
+                    match op {
+                        _ if scan.is_some() => {
+                            thread_scanned += map.partial_scan(&key, scan.unwrap(), &guard);
+                        }
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(key, index, &guard);
+                        }
+                        Op::Get if config.multi_get > 1 => {
+                            batch.clear();
+                            batch.push(key);
+                            batch.extend(
+                                (1..config.multi_get)
+                                    .map(|_| K::from_index(config.key_dist.sample(rng))),
+                            );
+                            map.multi_get(&batch, &guard);
+                            gets += batch.len() as u64;
+                            // Each key of the batch counts as an operation.
+                            batched += batch.len() as u64 - 1;
+                        }
                         Op::Get => {
-                            map.get(&key, &guard);
+                            if let Some(cache) = cache.as_mut() {
+                                let now = Instant::now();
+                                if cache.get(index, now).is_none() {
+                                    let value = map.get(&key, &guard).map(|v| *v.output());
+                                    cache.put(index, value, now);
+                                    gets += 1;
+                                }
+                            } else {
+                                shadow
+                                    .run(op, index, || map.get(&key, &guard).map(|v| *v.output()));
+                                gets += 1;
+                            }
                         }
                         Op::Insert => {
-                            let value = key;
-                            map.insert(key, value, &guard);
+                            let value = index;
+                            shadow.run(op, index, || {
+                                map.insert(key, value, &guard).then_some(value)
+                            });
+                            if let Some(cache) = cache.as_mut() {
+                                cache.invalidate(index);
+                            }
+                        }
+                        Op::Remove if config.extract_min => {
+                            map.extract_min(&guard);
                         }
                         Op::Remove => {
-                            map.remove(&key, &guard);
+                            shadow.run(op, index, || map.remove(&key, &guard).map(|v| *v.output()));
+                            if let Some(cache) = cache.as_mut() {
+                                cache.invalidate(index);
+                            }
                         }
                     }
+                    conflicts.exit(result.index);
                     ops += 1;
+                    result.record_op(config, critical, scan.is_none().then_some(op), op_start);
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
+                    if config.paused(result, ops + batched) {
+                        // A paused thread must not hold back the epoch.
+                        drop(guard);
+                        if let Some(dump) = dump {
+                            dump.unpin(result.index);
+                        }
+                        config.wait_while_paused(result);
+                        guard = handle.pin();
+                        if let Some(dump) = dump {
+                            dump.pin(result.index, ops);
+                        }
+                    }
                     if ops % N::to_u64() == 0 {
                         drop(guard);
                         guard = handle.pin();
+                        if let Some(dump) = dump {
+                            dump.pin(result.index, ops);
+                        }
                     }
                 }
+                drop(guard);
+                if let Some(dump) = dump {
+                    dump.unpin(result.index);
+                }
 
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops + batched);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
+                scanned.fetch_add(thread_scanned, Ordering::Relaxed);
+                result.hits = cache.map_or(0, |cache| cache.hits());
+                result.gets = gets;
             });
         }
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene_with(|| drain_ebr(collector));
+    MAP_GETS.fetch_add(results.gets(), Ordering::Relaxed);
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
+    if config.stale_ttl.is_some() {
+        println!("gets served from the stale cache: {}", results.hits());
+    }
+    if config.partial_scan.is_some() {
+        println!(
+            "elements iterated by the partial scans: {}",
+            scanned.load(Ordering::Relaxed)
+        );
+    }
+    if config.range_remove.is_some() {
+        results.print_range_removals();
     }
-    let ops_per_sec = ops / config.interval;
+    if let Some(curve) = curve {
+        config.record_alloc_curve(curve);
+    }
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
@@ -235,3 +573,469 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
         avg_garb,
     }
 }
+
+fn prefill_set<S: ConcurrentSet<usize> + Send + Sync>(config: &Config, set: &S) {
+    // Lists and skip lists are prefilled with a single thread in a decreasing order.
+    let collector = &crossbeam_ebr::Collector::new();
+    let handle = collector.register();
+    for key in config.decreasing_prefill::<usize>() {
+        set.insert(key, &handle.pin());
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+}
+
+fn bench_set<S: ConcurrentSet<usize> + Send + Sync, N: Unsigned>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => crossbeam_ebr::set_bag_capacity(512),
+        BagSize::Large => crossbeam_ebr::set_bag_capacity(4096),
+    }
+    let set = &S::new();
+    prefill_set(config, set);
+    config.run_hygiene();
+
+    let collector = &crossbeam_ebr::Collector::new();
+    let (results, perf) = harness::run(config, &Ebr(collector), |_| {
+        let mut pinned = Pinned::<N>::new(collector);
+        move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+            let guard = pinned.guard();
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    set.contains(&key, guard);
+                }
+                Op::Insert => {
+                    set.insert(key, guard);
+                }
+                Op::Remove => {
+                    set.remove(&key, guard);
+                }
+            }
+            pinned.tick();
+            true
+        }
+    });
+    config.run_hygiene_with(|| drain_ebr(collector));
+
+    results.print_sockets();
+    perf
+}
+
+fn prefill_counter<C: ConcurrentCounter<usize> + Send + Sync>(config: &Config, counter: &C) {
+    // Each prefilled key has a count of one.
+    let collector = &crossbeam_ebr::Collector::new();
+    let handle = collector.register();
+    for key in config.decreasing_prefill::<usize>() {
+        counter.increment(key, &handle.pin());
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+}
+
+/// Benchmarks a map from keys to atomic counters as a multiset. A get reads the count of its
+/// key, an insert increments it, and a remove decrements it without removing the key.
+fn bench_counter<C: ConcurrentCounter<usize> + Send + Sync, N: Unsigned>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => crossbeam_ebr::set_bag_capacity(512),
+        BagSize::Large => crossbeam_ebr::set_bag_capacity(4096),
+    }
+    let counter = &C::new();
+    prefill_counter(config, counter);
+    config.run_hygiene();
+
+    let collector = &crossbeam_ebr::Collector::new();
+    let (results, perf) = harness::run(config, &Ebr(collector), |_| {
+        let mut pinned = Pinned::<N>::new(collector);
+        move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+            let guard = pinned.guard();
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    counter.count(&key, guard);
+                }
+                Op::Insert => {
+                    counter.increment(key, guard);
+                }
+                Op::Remove => {
+                    counter.decrement(&key, guard);
+                }
+            }
+            pinned.tick();
+            true
+        }
+    });
+    config.run_hygiene_with(|| drain_ebr(collector));
+
+    results.print_sockets();
+    perf
+}
+
+type MoveMap = MovePair<usize, usize, HashMap<usize, usize>>;
+
+/// Prefills the pair of maps, putting each key in a random one of them, and returns which keys
+/// have been inserted.
+fn prefill_move(config: &Config, pair: &MoveMap) -> Vec<bool> {
+    let collector = &crossbeam_ebr::Collector::new();
+    let handle = collector.register();
+    let rng = &mut rand::thread_rng();
+    let mut prefilled = vec![false; config.key_range];
+    for _ in 0..config.prefill {
+        let key = config.prefill_dist.sample(rng);
+        let side = if rng.gen() { Side::A } else { Side::B };
+        if !prefilled[key] {
+            prefilled[key] = pair.insert(side, key, key, &handle.pin());
+        }
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+    prefilled
+}
+
+/// Benchmarks moving keys between two hash maps. A get looks a key up in both maps, an insert
+/// moves it from the first map to the second one, and a remove moves it back.
+fn bench_move<N: Unsigned>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => crossbeam_ebr::set_bag_capacity(512),
+        BagSize::Large => crossbeam_ebr::set_bag_capacity(4096),
+    }
+    let pair = &MoveMap::new(config.threads);
+    let prefilled = prefill_move(config, pair);
+    config.run_hygiene();
+
+    let collector = &crossbeam_ebr::Collector::new();
+    let (results, perf) = harness::run(config, &Ebr(collector), |tid| {
+        let mut pinned = Pinned::<N>::new(collector);
+        move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+            let guard = pinned.guard();
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    pair.get(&key, guard);
+                }
+                Op::Insert => {
+                    pair.move_key(tid, &key, Side::A, guard);
+                }
+                Op::Remove => {
+                    pair.move_key(tid, &key, Side::B, guard);
+                }
+            }
+            pinned.tick();
+            true
+        }
+    });
+
+    // Every move either fails or keeps its key, so each prefilled key is in exactly one map.
+    {
+        let handle = collector.register();
+        let guard = &handle.pin();
+        for (key, &prefilled) in prefilled.iter().enumerate() {
+            assert_eq!(
+                pair.copies(&key, guard),
+                prefilled as usize,
+                "key {key} is lost or duplicated by the moves"
+            );
+        }
+    }
+    println!("validated the moves of {} keys", config.key_range);
+    log_event!(
+        Info,
+        Validate,
+        "validated the moves",
+        keys = config.key_range
+    );
+    config.run_hygiene_with(|| drain_ebr(collector));
+
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks merging lists. An insert adds a key to a list of the thread, which is merged into
+/// the shared list every `config.merge_batch` insertions.
+fn bench_merge<N: Unsigned>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => crossbeam_ebr::set_bag_capacity(512),
+        BagSize::Large => crossbeam_ebr::set_bag_capacity(4096),
+    }
+    let map = &HHSList::<usize, usize>::new();
+    PrefillStrategy::Decreasing.prefill(config, map);
+    config.run_hygiene();
+
+    let collector = &crossbeam_ebr::Collector::new();
+    let (results, perf) = harness::run(config, &Ebr(collector), |_| {
+        let mut pinned = Pinned::<N>::new(collector);
+        let batch = HHSList::new();
+        let mut batched = 0;
+        move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+            let guard = pinned.guard();
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    map.get(&key, guard);
+                }
+                Op::Insert => {
+                    batch.insert(key, key, guard);
+                    batched += 1;
+                    if batched == config.merge_batch {
+                        map.merge_from(&batch, guard);
+                        batched = 0;
+                    }
+                }
+                Op::Remove => {
+                    map.remove(&key, guard);
+                }
+            }
+            pinned.tick();
+            true
+        }
+    });
+    config.run_hygiene_with(|| drain_ebr(collector));
+
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks a hash map of bounded size as a read-through cache. A get that misses inserts its
+/// key, and an insert into the full map evicts another key.
+fn bench_cache<N: Unsigned>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => crossbeam_ebr::set_bag_capacity(512),
+        BagSize::Large => crossbeam_ebr::set_bag_capacity(4096),
+    }
+    let cache = &ClockCache::with_capacity(config.cache_capacity);
+    {
+        let collector = &crossbeam_ebr::Collector::new();
+        let handle = collector.register();
+        for key in config.decreasing_prefill::<usize>() {
+            cache.insert(key, key, &handle.pin());
+        }
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+    config.run_hygiene();
+
+    let collector = &crossbeam_ebr::Collector::new();
+    let (results, perf) = harness::run(config, &Ebr(collector), |_| {
+        let mut pinned = Pinned::<N>::new(collector);
+        move |rng: &mut ThreadRng, result: &mut ThreadResult| {
+            let guard = pinned.guard();
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    result.gets += 1;
+                    if cache.get(&key, guard).is_some() {
+                        result.hits += 1;
+                    } else {
+                        cache.insert(key, key, guard);
+                    }
+                }
+                Op::Insert => {
+                    cache.insert(key, key, guard);
+                }
+                Op::Remove => {
+                    cache.remove(&key, guard);
+                }
+            }
+            pinned.tick();
+            true
+        }
+    });
+    config.run_hygiene_with(|| drain_ebr(collector));
+
+    results.print_hit_rate();
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks an append log that an extra thread truncates, as a write-ahead log is once a
+/// checkpoint lets it drop its old segments, which retires the truncated entries in one burst of
+/// a single thread. A get reads a random entry that is not truncated, and an insert or a remove
+/// appends an entry.
+fn bench_log<N: Unsigned>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => crossbeam_ebr::set_bag_capacity(512),
+        BagSize::Large => crossbeam_ebr::set_bag_capacity(4096),
+    }
+    let log = &Log::new();
+    {
+        let collector = &crossbeam_ebr::Collector::new();
+        let handle = collector.register();
+        for key in 0..config.log_retain {
+            log.append(key, &handle.pin());
+        }
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", entries = config.log_retain);
+    stdout().flush().unwrap();
+    config.run_hygiene();
+
+    let collector = &crossbeam_ebr::Collector::new();
+    let truncate = || {
+        let handle = collector.register();
+        move || {
+            let retain = config.log_retain as u64;
+            let mut truncations = 0u64;
+            let mut truncated = 0u64;
+            let start = Instant::now();
+            while start.elapsed() < config.duration {
+                let guard = &handle.pin();
+                let last = log.last(guard);
+                if (last + 1).saturating_sub(log.first()) >= 2 * retain {
+                    truncated += log.truncate(last - retain, guard) as u64;
+                    truncations += 1;
+                } else {
+                    std::hint::spin_loop();
+                }
+            }
+            (truncations, truncated)
+        }
+    };
+    let (results, perf, (truncations, truncated)) =
+        harness::run_with_side(config, &Ebr(collector), truncate, |_| {
+            let mut pinned = Pinned::<N>::new(collector);
+            move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+                let guard = pinned.guard();
+                match Op::OPS[config.op_dist.sample(rng)] {
+                    Op::Get => {
+                        let index = rng.gen_range(log.first()..=log.last(guard));
+                        log.get(index, guard);
+                    }
+                    Op::Insert | Op::Remove => {
+                        log.append(config.key_dist.sample(rng), guard);
+                    }
+                }
+                pinned.tick();
+                true
+            }
+        });
+    config.run_hygiene_with(|| drain_ebr(collector));
+
+    println!("log truncations: {truncations}, truncated entries: {truncated}");
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks a work-stealing deque whose owner, an extra thread, pushes bursts of
+/// `--deque-burst` items and pops each of them back while the threads steal from it, as a worker
+/// of a work-stealing scheduler spawns and runs its tasks. The deque grows and shrinks with the
+/// bursts, which retires its buffers. An operation is a successful steal.
+fn bench_deque<D: ConcurrentDeque<usize> + Send + Sync, N: Unsigned>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => crossbeam_ebr::set_bag_capacity(512),
+        BagSize::Large => crossbeam_ebr::set_bag_capacity(4096),
+    }
+    let deque = &D::new();
+    deque::take_buffer_stats();
+    config.run_hygiene();
+
+    let collector = &crossbeam_ebr::Collector::new();
+    let owner = || {
+        let handle = collector.register();
+        move || {
+            let mut pushed = 0u64;
+            let mut popped = 0u64;
+            let start = Instant::now();
+            while start.elapsed() < config.duration {
+                let guard = &handle.pin();
+                for item in 0..config.deque_burst {
+                    unsafe { deque.push(item, guard) };
+                }
+                pushed += config.deque_burst as u64;
+                while unsafe { deque.pop(guard) }.is_some() {
+                    popped += 1;
+                }
+            }
+            (pushed, popped)
+        }
+    };
+    let (results, perf, (pushed, popped)) =
+        harness::run_with_side(config, &Ebr(collector), owner, |_| {
+            let mut pinned = Pinned::<N>::new(collector);
+            move |_: &mut ThreadRng, result: &mut ThreadResult| {
+                let stolen = match deque.steal(pinned.guard()) {
+                    Steal::Success(_) => true,
+                    Steal::Retry => {
+                        result.steals_lost += 1;
+                        false
+                    }
+                    Steal::Empty => false,
+                };
+                result.steal_attempts += 1;
+                // Repins by the attempts, as the deque may stay empty for a while.
+                pinned.tick();
+                stolen
+            }
+        });
+    config.run_hygiene_with(|| drain_ebr(collector));
+
+    println!("owner pushes: {pushed}, owner pops: {popped}");
+    let (attempts, lost) = results.steals();
+    let buffers = deque::take_buffer_stats();
+    config.record_steals(&StealReport {
+        attempts,
+        stolen: pushed - popped,
+        lost,
+        retired: buffers.retired,
+        reclaimed: buffers.reclaimed,
+    });
+    results.print_sockets();
+    perf
+}
+
+/// The sampling & interference thread of the benchmarks other than the map one, on a collector.
+struct Ebr<'a>(&'a crossbeam_ebr::Collector);
+
+impl Scheme for Ebr<'_> {
+    /// The guard that the thread holds with `--non-coop`, and the handle that it is pinned on.
+    type Aux = (Option<crossbeam_ebr::Guard>, crossbeam_ebr::LocalHandle);
+
+    fn aux(&self, config: &Config) -> Self::Aux {
+        let handle = self.0.register();
+        ((config.non_coop > 0).then(|| handle.pin()), handle)
+    }
+
+    fn repin(&self, (guard, _): &mut Self::Aux) {
+        if let Some(guard) = guard {
+            guard.repin();
+        }
+    }
+
+    fn garbage(&self) -> usize {
+        crossbeam_ebr::GLOBAL_GARBAGE_COUNT.load(Ordering::Acquire)
+    }
+}
+
+/// The guard of a benchmark thread, which is repinned every `N` operations.
+struct Pinned<N> {
+    guard: crossbeam_ebr::Guard,
+    _handle: crossbeam_ebr::LocalHandle,
+    ops: u64,
+    _ops_per_cs: PhantomData<N>,
+}
+
+impl<N: Unsigned> Pinned<N> {
+    fn new(collector: &crossbeam_ebr::Collector) -> Self {
+        let handle = collector.register();
+        Self {
+            guard: handle.pin(),
+            _handle: handle,
+            ops: 0,
+            _ops_per_cs: PhantomData,
+        }
+    }
+
+    fn guard(&self) -> &crossbeam_ebr::Guard {
+        &self.guard
+    }
+
+    /// Counts an operation, and repins the guard after every `N` of them.
+    fn tick(&mut self) {
+        self.ops += 1;
+        if self.ops % N::to_u64() == 0 {
+            self.guard.repin();
+        }
+    }
+}
@@ -4,14 +4,19 @@ use rand::prelude::*;
 use std::cmp::max;
 use std::io::{stdout, Write};
 use std::path::Path;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, Perf, DS};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchWriter, Config, Op, Perf, ThreadResults, TreeShape, DS,
+};
+use smr_benchmark::ds_impl::hp_brcu::concurrent_map::OutputHolder;
 use smr_benchmark::ds_impl::hp_brcu::{
     BonsaiTreeMap, ConcurrentMap, ElimABTree, HHSList, HList, HMList, HashMap, NMTreeMap, SkipList,
 };
+use smr_benchmark::log_event;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -26,7 +31,7 @@ fn main() {
 
 fn bench(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
+    let perf = config.measure(|| match config.ds {
         DS::HList => bench_map::<HList<usize, usize>>(config, PrefillStrategy::Decreasing),
         DS::HMList => bench_map::<HMList<usize, usize>>(config, PrefillStrategy::Decreasing),
         DS::HHSList => bench_map::<HHSList<usize, usize>>(config, PrefillStrategy::Decreasing),
@@ -38,7 +43,7 @@ fn bench(config: &Config, output: BenchWriter) {
         }
         DS::ElimAbTree => bench_map::<ElimABTree<usize, usize>>(config, PrefillStrategy::Random),
         _ => panic!("Unsupported(or unimplemented) data structure for HP-BRCU"),
-    };
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
 }
@@ -54,41 +59,46 @@ pub enum PrefillStrategy {
 impl PrefillStrategy {
     fn prefill<M: ConcurrentMap<usize, usize> + Send + Sync>(self, config: &Config, map: &M) {
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                hp_brcu::THREAD.with(|handle| {
+                    let handle = &mut **handle.borrow_mut();
+                    let output = &mut M::empty_output(handle);
+                    for key in config.shaped_prefill::<usize>() {
+                        let value = key.clone();
+                        map.insert(key, value, output, handle);
+                    }
+                });
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            hp_brcu::THREAD.with(|handle| {
-                                let handle = &mut **handle.borrow_mut();
-                                let output = &mut M::empty_output(handle);
-                                let rng = &mut rand::thread_rng();
-                                let count = config.prefill / threads
-                                    + if t < config.prefill % threads { 1 } else { 0 };
-                                for _ in 0..count {
-                                    let key = config.key_dist.sample(rng);
-                                    let value = key.clone();
-                                    map.insert(key, value, output, handle);
-                                }
-                            });
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        hp_brcu::THREAD.with(|handle| {
+                            let handle = &mut **handle.borrow_mut();
+                            let output = &mut M::empty_output(handle);
+                            let rng = &mut rand::thread_rng();
+                            for _ in units {
+                                let key = config.prefill_dist.sample(rng);
+                                let value = key.clone();
+                                map.insert(key, value, output, handle);
+                            }
                         });
-                    }
-                })
-                .unwrap();
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
                 hp_brcu::THREAD.with(|handle| {
                     let handle = &mut **handle.borrow_mut();
                     let output = &mut M::empty_output(handle);
-                    let rng = &mut rand::thread_rng();
-                    let mut keys = Vec::with_capacity(config.prefill);
-                    for _ in 0..config.prefill {
-                        keys.push(config.key_dist.sample(rng));
-                    }
-                    keys.sort_by(|a, b| b.cmp(a));
-                    for key in keys.drain(..) {
+                    for key in config.decreasing_prefill::<usize>() {
                         let value = key.clone();
                         map.insert(key, value, output, handle);
                     }
@@ -96,7 +106,19 @@ impl PrefillStrategy {
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        hp_brcu::THREAD.with(|handle| {
+            let handle = &mut **handle.borrow_mut();
+            let output = &mut M::empty_output(handle);
+            config.age(|op, key| {
+                if op == Op::Insert {
+                    map.insert(key, key, output, handle)
+                } else {
+                    map.remove(&key, output, handle)
+                }
+            });
+        });
     }
 }
 
@@ -111,9 +133,12 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
     }
     let map = &M::new();
     strategy.prefill(config, map);
+    config.run_hygiene();
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
 
     scope(|s| {
@@ -126,7 +151,7 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                 let mut peak = 0usize;
                 let mut garb_acc = 0usize;
                 let mut garb_peak = 0usize;
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
                 let mut next_sampling = start + config.sampling_period;
@@ -160,47 +185,74 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 THREAD.with(|th| {
                     let handle = &mut **th.borrow_mut();
                     let mut ops: u64 = 0;
+                    let mut thrasher = config.cache_thrasher();
                     let mut rng = &mut rand::thread_rng();
                     let output = &mut M::empty_output(handle);
-                    barrier.clone().wait();
+                    config.start(gate, result, |key| {
+                        map.get(&key, output, handle);
+                    });
+                    let lifetime = config.arrive(result);
+                    let critical = config.critical(result.index);
                     let start = Instant::now();
 
-                    while start.elapsed() < config.duration {
+                    while start.elapsed() < lifetime {
+                        let op_start = result.op_start(config, config.timed(critical));
                         let key = config.key_dist.sample(rng);
-                        match Op::OPS[config.op_dist.sample(&mut rng)] {
+                        conflicts.enter(result.index, key);
+                        let op = config.sample_op(critical, &mut rng);
+                        match op {
+                            _ if config.get_or_insert(critical, rng) => {
+                                map.get_or_insert(key, key, output, handle);
+                            }
                             Op::Get => {
-                                map.get(&key, output, handle);
+                                shadow.run(Op::Get, key, || {
+                                    map.get(&key, output, handle).then(|| *output.output())
+                                });
                             }
                             Op::Insert => {
                                 let value = key.clone();
-                                map.insert(key, value, output, handle);
+                                shadow.run(Op::Insert, key, || {
+                                    map.insert(key, value, output, handle).then_some(value)
+                                });
                             }
                             Op::Remove => {
-                                map.remove(&key, output, handle);
+                                shadow.run(Op::Remove, key, || {
+                                    map.remove(&key, output, handle).then(|| *output.output())
+                                });
                             }
                         }
+                        conflicts.exit(result.index);
                         ops += 1;
+                        result.record_op(config, critical, Some(op), op_start);
+                        if let Some(thrasher) = thrasher.as_mut() {
+                            thrasher.tick();
+                        }
                     }
-                    ops_sender.send(ops).unwrap();
+                    result.record(start, ops);
+                    result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
                 })
             });
         }
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene();
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
@@ -5,15 +5,20 @@ use rand::prelude::*;
 use std::cmp::max;
 use std::io::{stdout, Write};
 use std::path::Path;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, Perf, DS};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchWriter, Config, Op, Perf, ThreadResults, TreeShape, DS,
+};
+use smr_benchmark::ds_impl::hp::concurrent_map::OutputHolder;
 use smr_benchmark::ds_impl::{
     hp::ConcurrentMap,
     hp_pp::{BonsaiTreeMap, EFRBTree, HHSList, HList, HMList, HashMap, NMTreeMap, SkipList},
 };
+use smr_benchmark::log_event;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -28,7 +33,11 @@ fn main() {
 
 fn bench(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
+    if !config.injections.is_empty() {
+        let spins = hp_pp::interpose::install(&config.injections);
+        println!("injected delays: {spins:?} spins");
+    }
+    let perf = config.measure(|| match config.ds {
         DS::HList => bench_map::<HList<usize, usize>>(config, PrefillStrategy::Decreasing),
         DS::HMList => bench_map::<HMList<usize, usize>>(config, PrefillStrategy::Decreasing),
         DS::HHSList => bench_map::<HHSList<usize, usize>>(config, PrefillStrategy::Decreasing),
@@ -40,9 +49,16 @@ fn bench(config: &Config, output: BenchWriter) {
             bench_map::<BonsaiTreeMap<usize, usize>>(config, PrefillStrategy::Decreasing)
         }
         _ => panic!("Unsupported(or unimplemented) data structure for HP++"),
-    };
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
+    if !config.injections.is_empty() {
+        let counts = hp_pp::interpose::counts();
+        println!(
+            "delayed protections: {}, delayed retirements: {}, skipped reclamations: {}",
+            counts.delayed_protects, counts.delayed_retires, counts.skipped_reclaims
+        );
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -56,43 +72,54 @@ pub enum PrefillStrategy {
 impl PrefillStrategy {
     fn prefill<M: ConcurrentMap<usize, usize> + Send + Sync>(self, config: &Config, map: &M) {
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                let mut handle = M::handle();
+                for key in config.shaped_prefill::<usize>() {
+                    let value = key.clone();
+                    map.insert(&mut handle, key, value);
+                }
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            let mut handle = M::handle();
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key.clone();
-                                map.insert(&mut handle, key, value);
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        let mut handle = M::handle();
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let key = config.prefill_dist.sample(rng);
+                            let value = key.clone();
+                            map.insert(&mut handle, key, value);
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
                 let mut handle = M::handle();
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
+                for key in config.decreasing_prefill::<usize>() {
                     let value = key.clone();
                     map.insert(&mut handle, key, value);
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        let mut handle = M::handle();
+        config.age(|op, key| {
+            if op == Op::Insert {
+                map.insert(&mut handle, key, key)
+            } else {
+                map.remove(&mut handle, &key).is_some()
+            }
+        });
     }
 }
 
@@ -107,12 +134,21 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
     }
     let map = &M::new();
     strategy.prefill(config, map);
+    config.run_hygiene();
+    let timeline = &config.reclaim_timeline();
+    let curve = &config.alloc_curve();
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread + config.reclaimers);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
 
-    scope(|s| {
+    let reclaimers = scope(|s| {
+        let reclaimers = (0..config.reclaimers)
+            .map(|_| s.spawn(move |_| config.run_reclaimer(gate)))
+            .collect::<Vec<_>>();
+
         // sampling & interference thread
         if config.aux_thread > 0 {
             let mem_sender = mem_sender.clone();
@@ -122,12 +158,25 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                 let mut peak = 0usize;
                 let mut garb_acc = 0usize;
                 let mut garb_peak = 0usize;
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
+                if let Some(timeline) = timeline {
+                    timeline.start();
+                }
+                if let Some(curve) = curve {
+                    curve.start();
+                }
+                let mut next_timeline = start + config.reclaim_timeline_period;
                 let mut next_sampling = start + config.sampling_period;
                 while start.elapsed() < config.duration {
                     let now = Instant::now();
+                    if let Some(timeline) = timeline {
+                        if now > next_timeline {
+                            timeline.sample();
+                            next_timeline = now + config.reclaim_timeline_period;
+                        }
+                    }
                     if now > next_sampling {
                         let allocated = config.mem_sampler.sample();
                         samples += 1;
@@ -138,6 +187,9 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                         let garbages = DEFAULT_DOMAIN.num_garbages();
                         garb_acc += garbages;
                         garb_peak = max(garb_peak, garbages);
+                        if let Some(curve) = curve {
+                            curve.sample();
+                        }
 
                         next_sampling = now + config.sampling_period;
                     }
@@ -156,45 +208,92 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
                 let mut rng = &mut rand::thread_rng();
-                let mut map_handle = M::handle();
-                barrier.clone().wait();
+                let mut registration = config.registers_early().then(M::handle);
+                config.start(gate, result, |key| {
+                    if let Some(map_handle) = registration.as_mut() {
+                        map.get(map_handle, &key);
+                    }
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
+                let mut map_handle = registration.unwrap_or_else(M::handle);
                 let start = Instant::now();
 
-                while start.elapsed() < config.duration {
+                while start.elapsed() < lifetime {
+                    let op_start = result.op_start(config, config.timed(critical));
                     let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
+                    conflicts.enter(result.index, key);
+                    let op = config.sample_op(critical, &mut rng);
+                    match op {
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(&mut map_handle, key, key);
+                        }
                         Op::Get => {
-                            map.get(&mut map_handle, &key);
+                            shadow.run(Op::Get, key, || {
+                                map.get(&mut map_handle, &key).map(|v| *v.output())
+                            });
                         }
                         Op::Insert => {
                             let value = key.clone();
-                            map.insert(&mut map_handle, key, value);
+                            shadow.run(Op::Insert, key, || {
+                                map.insert(&mut map_handle, key, value).then_some(value)
+                            });
                         }
                         Op::Remove => {
-                            map.remove(&mut map_handle, &key);
+                            shadow.run(Op::Remove, key, || {
+                                map.remove(&mut map_handle, &key).map(|v| *v.output())
+                            });
                         }
                     }
+                    conflicts.exit(result.index);
                     ops += 1;
+                    if let Some(timeline) = timeline {
+                        timeline.publish(result.index, ops);
+                    }
+                    result.record_op(config, critical, Some(op), op_start);
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
                 }
 
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
             });
         }
+
+        reclaimers
+            .into_iter()
+            .map(|reclaimer| reclaimer.join().unwrap())
+            .collect::<Vec<_>>()
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene_with(hp_pp::do_reclamation);
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
+    if let Some(timeline) = timeline {
+        let path = config.reclaim_timeline.as_ref().unwrap();
+        let (written, overwritten) = timeline.dump(path);
+        println!("reclamation timeline: {written} events, {overwritten} overwritten");
+    }
+    if let Some(curve) = curve {
+        config.record_alloc_curve(curve);
     }
-    let ops_per_sec = ops / config.interval;
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+    config.record_reclaimers(&reclaimers);
+
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
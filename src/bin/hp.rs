@@ -5,15 +5,30 @@ use rand::prelude::*;
 use std::cmp::max;
 use std::io::{stdout, Write};
 use std::path::Path;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, Perf, DS};
+use smr_benchmark::config::harness::{self, Scheme};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchKey, BenchWriter, CompositeKey, Config, KeyType, Mode, Op, Perf,
+    StealReport, ThreadResult, ThreadResults, TreeShape, DS,
+};
+use smr_benchmark::ds_impl::deque::{self, Steal};
+use smr_benchmark::ds_impl::hp::clock_cache::Handle as CacheHandle;
+use smr_benchmark::ds_impl::hp::concurrent_map::OutputHolder;
+use smr_benchmark::ds_impl::hp::log::Handle as LogHandle;
+#[cfg(feature = "persistent")]
+use smr_benchmark::ds_impl::hp::PersistentHashMap;
 use smr_benchmark::ds_impl::hp::{
-    BonsaiTreeMap, ConcurrentMap, EFRBTree, ElimABTree, HHSList, HList, HMList, HashMap, NMTreeMap,
-    SkipList,
+    BonsaiTreeMap, ChaseLevDeque, ClockCache, ConcurrentCounter, ConcurrentDeque, ConcurrentMap,
+    ConcurrentSet, EFRBTree, ElimABTree, HHSList, HHSListSet, HList, HListSet, HMList, HMListSet,
+    HashMap, HashMapCounter, Log, MarkerSkipList, NMTreeMap, SkipList, SkipListSet,
 };
+use smr_benchmark::log_event;
+use smr_benchmark::signals;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -28,21 +43,104 @@ fn main() {
 
 fn bench(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
-        DS::HList => bench_map::<HList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HHSList => bench_map::<HHSList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HMList => bench_map::<HMList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HashMap => bench_map::<HashMap<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::EFRBTree => bench_map::<EFRBTree<usize, usize>>(config, PrefillStrategy::Random),
-        DS::SkipList => bench_map::<SkipList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::BonsaiTree => {
-            bench_map::<BonsaiTreeMap<usize, usize>>(config, PrefillStrategy::Decreasing)
-        }
-        DS::NMTree => bench_map::<NMTreeMap<usize, usize>>(config, PrefillStrategy::Random),
-        DS::ElimAbTree => bench_map::<ElimABTree<usize, usize>>(config, PrefillStrategy::Random),
-    };
+    if !config.injections.is_empty() {
+        let spins = hp_pp::interpose::install(&config.injections);
+        println!("injected delays: {spins:?} spins");
+    }
+    let perf = config.measure(|| match config.mode {
+        Mode::Map => match config.ds {
+            DS::HList => {
+                bench_map::<usize, HList<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HHSList => {
+                bench_map::<usize, HHSList<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HMList => {
+                bench_map::<usize, HMList<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HashMap => {
+                bench_map::<usize, HashMap<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            #[cfg(feature = "persistent")]
+            DS::PersistentHashMap => bench_map::<usize, PersistentHashMap<usize, usize>>(
+                config,
+                PrefillStrategy::Decreasing,
+            ),
+            DS::EFRBTree => {
+                bench_map::<usize, EFRBTree<usize, usize>>(config, PrefillStrategy::Random)
+            }
+            DS::SkipList => {
+                bench_map::<usize, SkipList<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::MarkerSkipList => bench_map::<usize, MarkerSkipList<usize, usize>>(
+                config,
+                PrefillStrategy::Decreasing,
+            ),
+            DS::BonsaiTree => {
+                match config.key_type {
+                    KeyType::Usize => bench_map::<usize, BonsaiTreeMap<usize, usize>>(
+                        config,
+                        PrefillStrategy::Decreasing,
+                    ),
+                    KeyType::U128 => bench_map::<u128, BonsaiTreeMap<u128, usize>>(
+                        config,
+                        PrefillStrategy::Decreasing,
+                    ),
+                    KeyType::Composite => bench_map::<
+                        CompositeKey,
+                        BonsaiTreeMap<CompositeKey, usize>,
+                    >(config, PrefillStrategy::Decreasing),
+                }
+            }
+            DS::NMTree => match config.key_type {
+                KeyType::Usize => {
+                    bench_map::<usize, NMTreeMap<usize, usize>>(config, PrefillStrategy::Random)
+                }
+                KeyType::U128 => {
+                    bench_map::<u128, NMTreeMap<u128, usize>>(config, PrefillStrategy::Random)
+                }
+                KeyType::Composite => bench_map::<CompositeKey, NMTreeMap<CompositeKey, usize>>(
+                    config,
+                    PrefillStrategy::Random,
+                ),
+            },
+            DS::ElimAbTree => {
+                bench_map::<usize, ElimABTree<usize, usize>>(config, PrefillStrategy::Random)
+            }
+            _ => panic!("Unsupported(or unimplemented) data structure for HP"),
+        },
+        Mode::Set => match config.ds {
+            DS::HList => bench_set::<HListSet<usize>>(config),
+            DS::HMList => bench_set::<HMListSet<usize>>(config),
+            DS::HHSList => bench_set::<HHSListSet<usize>>(config),
+            DS::SkipList => bench_set::<SkipListSet<usize>>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the set mode"),
+        },
+        Mode::Move => panic!("Unsupported(or unimplemented) SMR for the move mode"),
+        Mode::Merge => panic!("Unsupported(or unimplemented) SMR for the merge mode"),
+        Mode::Cache => match config.ds {
+            DS::HashMap => bench_cache(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the cache mode"),
+        },
+        Mode::Counter => match config.ds {
+            DS::HashMap => bench_counter::<HashMapCounter<usize>>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the counter mode"),
+        },
+        Mode::Log => bench_log(config),
+        Mode::Deque => match config.ds {
+            DS::ChaseLev => bench_deque::<ChaseLevDeque<usize>>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the deque mode"),
+        },
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
+    if !config.injections.is_empty() {
+        let counts = hp_pp::interpose::counts();
+        println!(
+            "delayed protections: {}, delayed retirements: {}, skipped reclamations: {}",
+            counts.delayed_protects, counts.delayed_retires, counts.skipped_reclaims
+        );
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -54,49 +152,68 @@ pub enum PrefillStrategy {
 }
 
 impl PrefillStrategy {
-    fn prefill<M: ConcurrentMap<usize, usize> + Send + Sync>(self, config: &Config, map: &M) {
+    fn prefill<K: BenchKey, M: ConcurrentMap<K, usize> + Send + Sync>(
+        self,
+        config: &Config,
+        map: &M,
+    ) {
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                let mut handle = M::handle();
+                for index in config.shaped_prefill::<K>() {
+                    let key = K::from_index(index);
+                    let value = index;
+                    map.insert(&mut handle, key, value);
+                }
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            let mut handle = M::handle();
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key;
-                                map.insert(&mut handle, key, value);
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        let mut handle = M::handle();
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let index = config.prefill_dist.sample(rng);
+                            let key = K::from_index(index);
+                            let value = index;
+                            map.insert(&mut handle, key, value);
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
                 let mut handle = M::handle();
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
-                    let value = key;
+                for index in config.decreasing_prefill::<K>() {
+                    let key = K::from_index(index);
+                    let value = index;
                     map.insert(&mut handle, key, value);
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        let mut handle = M::handle();
+        config.age(|op, index| {
+            let key = K::from_index(index);
+            if op == Op::Insert {
+                map.insert(&mut handle, key, index)
+            } else {
+                map.remove(&mut handle, &key).is_some()
+            }
+        });
     }
 }
 
-fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
+fn bench_map<K: BenchKey, M: ConcurrentMap<K, usize> + Send + Sync>(
     config: &Config,
     strategy: PrefillStrategy,
 ) -> Perf {
@@ -107,12 +224,22 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
     }
     let map = &M::new();
     strategy.prefill(config, map);
+    config.run_hygiene();
+    let timeline = &config.reclaim_timeline();
+    let curve = &config.alloc_curve();
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread + config.reclaimers);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
+    let scanned = &AtomicUsize::new(0);
+
+    let reclaimers = scope(|s| {
+        let reclaimers = (0..config.reclaimers)
+            .map(|_| s.spawn(move |_| config.run_reclaimer(gate)))
+            .collect::<Vec<_>>();
 
-    scope(|s| {
         // sampling & interference thread
         if config.aux_thread > 0 {
             let mem_sender = mem_sender.clone();
@@ -122,13 +249,30 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                 let mut peak = 0usize;
                 let mut garb_acc = 0usize;
                 let mut garb_peak = 0usize;
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
+                if let Some(timeline) = timeline {
+                    timeline.start();
+                }
+                if let Some(curve) = curve {
+                    curve.start();
+                }
+                let mut next_timeline = start + config.reclaim_timeline_period;
                 let mut next_sampling = start + config.sampling_period;
-                while start.elapsed() < config.duration {
+                // The run is extended by the pauses with `--signals`.
+                let paused = signals::paused_time();
+                while start.elapsed()
+                    < config.duration + signals::paused_time().saturating_sub(paused)
+                {
                     let now = Instant::now();
-                    if now > next_sampling {
+                    if let Some(timeline) = timeline {
+                        if now > next_timeline {
+                            timeline.sample();
+                            next_timeline = now + config.reclaim_timeline_period;
+                        }
+                    }
+                    if now > next_sampling && !signals::paused() {
                         let allocated = config.mem_sampler.sample();
                         samples += 1;
 
@@ -138,6 +282,9 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                         let garbages = DEFAULT_DOMAIN.num_garbages();
                         garb_acc += garbages;
                         garb_peak = max(garb_peak, garbages);
+                        if let Some(curve) = curve {
+                            curve.sample();
+                        }
 
                         next_sampling = now + config.sampling_period;
                     }
@@ -156,45 +303,176 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
+                let mut cache = config.stale_cache::<usize>();
+                let mut trace = config.trace_cursor(result.index);
+                let mut batch = Vec::with_capacity(config.multi_get);
                 let mut rng = &mut rand::thread_rng();
-                let mut map_handle = M::handle();
-                barrier.clone().wait();
+                let mut thread_scanned = 0;
+                let mut registration = config.registers_early().then(M::handle);
+                config.start(gate, result, |index| {
+                    if let Some(map_handle) = registration.as_mut() {
+                        map.get(map_handle, &K::from_index(index));
+                    }
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
+                let mut map_handle = registration.unwrap_or_else(M::handle);
                 let start = Instant::now();
 
-                while start.elapsed() < config.duration {
-                    let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
+                // The first thread removes the ranges.
+                let mut next_range_removal = config
+                    .range_remove
+                    .filter(|_| result.index == 0)
+                    .map(|_| start + config.range_remove_period);
+                while start.elapsed() < lifetime + result.paused {
+                    if let Some(next) = next_range_removal.as_mut() {
+                        let now = Instant::now();
+                        if now >= *next {
+                            let lo = config.key_dist.sample(rng);
+                            let hi = lo + config.range_remove.unwrap();
+                            let removed = map.remove_range(
+                                &mut map_handle,
+                                &K::from_index(lo),
+                                &K::from_index(hi),
+                            );
+                            result.range_removals += 1;
+                            result.range_removed += removed as u64;
+                            result.slowest_range_removal =
+                                result.slowest_range_removal.max(now.elapsed());
+                            *next = now + config.range_remove_period;
+                        }
+                    }
+                    let op_start = result.op_start(
+                        config,
+                        config.timed(critical) || config.range_remove.is_some(),
+                    );
+                    let (op, index) = match trace.as_mut() {
+                        Some(trace) => trace.next_op(),
+                        None => (
+                            config.sample_op(critical, &mut rng),
+                            config.key_dist.sample(rng),
+                        ),
+                    };
+                    let key = K::from_index(index);
+                    conflicts.enter(result.index, index);
+                    // A partial scan takes the place of the sampled operation.
+                    let scan = config
+                        .partial_scan
+                        .filter(|_| rng.gen_ratio(config.partial_scan_rate, 100));
+                    match op {
+                        _ if scan.is_some() => {
+                            thread_scanned +=
+                                map.partial_scan(&mut map_handle, &key, scan.unwrap());
+                        }
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(&mut map_handle, key, index);
+                        }
+                        Op::Get if config.multi_get > 1 => {
+                            batch.clear();
+                            batch.push(key);
+                            batch.extend(
+                                (1..config.multi_get)
+                                    .map(|_| K::from_index(config.key_dist.sample(rng))),
+                            );
+                            map.multi_get(&mut map_handle, &batch);
+                            // Each key of the batch counts as an operation.
+                            ops += batch.len() as u64 - 1;
+                        }
                         Op::Get => {
-                            map.get(&mut map_handle, &key);
+                            if let Some(cache) = cache.as_mut() {
+                                let now = Instant::now();
+                                if cache.get(index, now).is_none() {
+                                    let value = map.get(&mut map_handle, &key).map(|v| *v.output());
+                                    cache.put(index, value, now);
+                                }
+                            } else {
+                                shadow.run(op, index, || {
+                                    map.get(&mut map_handle, &key).map(|v| *v.output())
+                                });
+                            }
                         }
                         Op::Insert => {
-                            let value = key;
-                            map.insert(&mut map_handle, key, value);
+                            let value = index;
+                            shadow.run(op, index, || {
+                                map.insert(&mut map_handle, key, value).then_some(value)
+                            });
+                            if let Some(cache) = cache.as_mut() {
+                                cache.invalidate(index);
+                            }
                         }
                         Op::Remove => {
-                            map.remove(&mut map_handle, &key);
+                            shadow.run(op, index, || {
+                                map.remove(&mut map_handle, &key).map(|v| *v.output())
+                            });
+                            if let Some(cache) = cache.as_mut() {
+                                cache.invalidate(index);
+                            }
                         }
                     }
+                    conflicts.exit(result.index);
                     ops += 1;
+                    if let Some(timeline) = timeline {
+                        timeline.publish(result.index, ops);
+                    }
+                    result.record_op(config, critical, scan.is_none().then_some(op), op_start);
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
+                    if config.paused(result, ops) {
+                        config.wait_while_paused(result);
+                    }
                 }
 
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
+                scanned.fetch_add(thread_scanned, Ordering::Relaxed);
+                result.hits = cache.map_or(0, |cache| cache.hits());
             });
         }
+
+        reclaimers
+            .into_iter()
+            .map(|reclaimer| reclaimer.join().unwrap())
+            .collect::<Vec<_>>()
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene_with(hp_pp::do_reclamation);
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
+    if config.stale_ttl.is_some() {
+        println!("gets served from the stale cache: {}", results.hits());
     }
-    let ops_per_sec = ops / config.interval;
+    if config.partial_scan.is_some() {
+        println!(
+            "elements iterated by the partial scans: {}",
+            scanned.load(Ordering::Relaxed)
+        );
+    }
+    if config.range_remove.is_some() {
+        results.print_range_removals();
+    }
+    if let Some(timeline) = timeline {
+        let path = config.reclaim_timeline.as_ref().unwrap();
+        let (written, overwritten) = timeline.dump(path);
+        println!("reclamation timeline: {written} events, {overwritten} overwritten");
+    }
+    if let Some(curve) = curve {
+        config.record_alloc_curve(curve);
+    }
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+    config.record_reclaimers(&reclaimers);
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
@@ -204,3 +482,281 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
         avg_garb,
     }
 }
+
+fn prefill_set<S: ConcurrentSet<usize> + Send + Sync>(config: &Config, set: &S) {
+    // Lists and skip lists are prefilled with a single thread in a decreasing order.
+    let mut handle = S::handle();
+    for key in config.decreasing_prefill::<usize>() {
+        set.insert(&mut handle, key);
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+}
+
+fn bench_set<S: ConcurrentSet<usize> + Send + Sync>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => set_counts_between_flush(512),
+        BagSize::Large => set_counts_between_flush(4096),
+    }
+    let set = &S::new();
+    prefill_set(config, set);
+    config.run_hygiene();
+
+    let (results, perf) = harness::run(config, &Hp, |_| {
+        let mut handle = S::handle();
+        move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    set.contains(&mut handle, &key);
+                }
+                Op::Insert => {
+                    set.insert(&mut handle, key);
+                }
+                Op::Remove => {
+                    set.remove(&mut handle, &key);
+                }
+            }
+            true
+        }
+    });
+    config.run_hygiene_with(hp_pp::do_reclamation);
+
+    results.print_sockets();
+    perf
+}
+
+fn prefill_counter<C: ConcurrentCounter<usize> + Send + Sync>(config: &Config, counter: &C) {
+    // Each prefilled key has a count of one.
+    let mut handle = C::handle();
+    for key in config.decreasing_prefill::<usize>() {
+        counter.increment(&mut handle, key);
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+}
+
+/// Benchmarks a map from keys to atomic counters as a multiset. A get reads the count of its
+/// key, an insert increments it, and a remove decrements it without removing the key.
+fn bench_counter<C: ConcurrentCounter<usize> + Send + Sync>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => set_counts_between_flush(512),
+        BagSize::Large => set_counts_between_flush(4096),
+    }
+    let counter = &C::new();
+    prefill_counter(config, counter);
+    config.run_hygiene();
+
+    let (results, perf) = harness::run(config, &Hp, |_| {
+        let mut handle = C::handle();
+        move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    counter.count(&mut handle, &key);
+                }
+                Op::Insert => {
+                    counter.increment(&mut handle, key);
+                }
+                Op::Remove => {
+                    counter.decrement(&mut handle, &key);
+                }
+            }
+            true
+        }
+    });
+    config.run_hygiene_with(hp_pp::do_reclamation);
+
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks a hash map of bounded size as a read-through cache. A get that misses inserts its
+/// key, and an insert into the full map evicts another key.
+fn bench_cache(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => set_counts_between_flush(512),
+        BagSize::Large => set_counts_between_flush(4096),
+    }
+    let cache = &ClockCache::with_capacity(config.cache_capacity);
+    {
+        let mut handle = CacheHandle::default();
+        for key in config.decreasing_prefill::<usize>() {
+            cache.insert(key, key, &mut handle);
+        }
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+    config.run_hygiene();
+
+    let (results, perf) = harness::run(config, &Hp, |_| {
+        let mut handle = CacheHandle::default();
+        move |rng: &mut ThreadRng, result: &mut ThreadResult| {
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    result.gets += 1;
+                    if cache.get(&key, &mut handle).is_some() {
+                        result.hits += 1;
+                    } else {
+                        cache.insert(key, key, &mut handle);
+                    }
+                }
+                Op::Insert => {
+                    cache.insert(key, key, &mut handle);
+                }
+                Op::Remove => {
+                    cache.remove(&key, &mut handle);
+                }
+            }
+            true
+        }
+    });
+    config.run_hygiene_with(hp_pp::do_reclamation);
+
+    results.print_hit_rate();
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks an append log that an extra thread truncates, as a write-ahead log is once a
+/// checkpoint lets it drop its old segments, which retires the truncated entries in one burst of
+/// a single thread. A get reads a random entry that is not truncated, and an insert or a remove
+/// appends an entry.
+fn bench_log(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => set_counts_between_flush(512),
+        BagSize::Large => set_counts_between_flush(4096),
+    }
+    let log = &Log::new();
+    {
+        let handle = &mut LogHandle::default();
+        for key in 0..config.log_retain {
+            log.append(key, handle);
+        }
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", entries = config.log_retain);
+    stdout().flush().unwrap();
+    config.run_hygiene();
+
+    let truncate = || {
+        let mut handle = LogHandle::default();
+        move || {
+            let retain = config.log_retain as u64;
+            let mut truncations = 0u64;
+            let mut truncated = 0u64;
+            let start = Instant::now();
+            while start.elapsed() < config.duration {
+                let last = log.last(&mut handle);
+                if (last + 1).saturating_sub(log.first()) >= 2 * retain {
+                    truncated += log.truncate(last - retain, &mut handle) as u64;
+                    truncations += 1;
+                } else {
+                    std::hint::spin_loop();
+                }
+            }
+            (truncations, truncated)
+        }
+    };
+    let (results, perf, (truncations, truncated)) =
+        harness::run_with_side(config, &Hp, truncate, |_| {
+            let mut handle = LogHandle::default();
+            move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+                match Op::OPS[config.op_dist.sample(rng)] {
+                    Op::Get => {
+                        let index = rng.gen_range(log.first()..=log.last(&mut handle));
+                        log.get(index, &mut handle);
+                    }
+                    Op::Insert | Op::Remove => {
+                        log.append(config.key_dist.sample(rng), &mut handle);
+                    }
+                }
+                true
+            }
+        });
+    config.run_hygiene_with(hp_pp::do_reclamation);
+
+    println!("log truncations: {truncations}, truncated entries: {truncated}");
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks a work-stealing deque whose owner, an extra thread, pushes bursts of
+/// `--deque-burst` items and pops each of them back while the threads steal from it, as a worker
+/// of a work-stealing scheduler spawns and runs its tasks. The deque grows and shrinks with the
+/// bursts, which retires its buffers. An operation is a successful steal.
+fn bench_deque<D: ConcurrentDeque<usize> + Send + Sync>(config: &Config) -> Perf {
+    match config.bag_size {
+        BagSize::Small => set_counts_between_flush(512),
+        BagSize::Large => set_counts_between_flush(4096),
+    }
+    let deque = &D::new();
+    deque::take_buffer_stats();
+    config.run_hygiene();
+
+    let owner = || {
+        let mut handle = D::handle();
+        move || {
+            let mut pushed = 0u64;
+            let mut popped = 0u64;
+            let start = Instant::now();
+            while start.elapsed() < config.duration {
+                for item in 0..config.deque_burst {
+                    unsafe { deque.push(&mut handle, item) };
+                }
+                pushed += config.deque_burst as u64;
+                while unsafe { deque.pop(&mut handle) }.is_some() {
+                    popped += 1;
+                }
+            }
+            (pushed, popped)
+        }
+    };
+    let (results, perf, (pushed, popped)) = harness::run_with_side(config, &Hp, owner, |_| {
+        let mut handle = D::handle();
+        move |_: &mut ThreadRng, result: &mut ThreadResult| {
+            let stolen = match deque.steal(&mut handle) {
+                Steal::Success(_) => true,
+                Steal::Retry => {
+                    result.steals_lost += 1;
+                    false
+                }
+                Steal::Empty => false,
+            };
+            result.steal_attempts += 1;
+            stolen
+        }
+    });
+    config.run_hygiene_with(hp_pp::do_reclamation);
+
+    println!("owner pushes: {pushed}, owner pops: {popped}");
+    let (attempts, lost) = results.steals();
+    let buffers = deque::take_buffer_stats();
+    config.record_steals(&StealReport {
+        attempts,
+        stolen: pushed - popped,
+        lost,
+        retired: buffers.retired,
+        reclaimed: buffers.reclaimed,
+    });
+    results.print_sockets();
+    perf
+}
+
+/// The sampling & interference thread of the benchmarks other than the map one, on the default
+/// domain.
+struct Hp;
+
+impl Scheme for Hp {
+    type Aux = ();
+
+    fn aux(&self, _config: &Config) -> Self::Aux {}
+
+    fn garbage(&self) -> usize {
+        DEFAULT_DOMAIN.num_garbages()
+    }
+}
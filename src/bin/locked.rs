@@ -0,0 +1,173 @@
+use crossbeam_utils::thread::scope;
+use rand::prelude::*;
+use std::cmp::max;
+use std::io::{stdout, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Instant;
+
+use smr_benchmark::config::map::{
+    setup, BenchKey, BenchWriter, Config, Mode, Op, Perf, ThreadResults, DS,
+};
+use smr_benchmark::ds_impl::locked::concurrent_map::OutputHolder;
+use smr_benchmark::ds_impl::locked::{BravoBTreeMap, ConcurrentMap};
+use smr_benchmark::log_event;
+use smr_benchmark::start::StartGate;
+
+fn main() {
+    let (config, output) = setup(
+        Path::new(file!())
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string())
+            .unwrap(),
+    );
+    bench(&config, output)
+}
+
+fn bench(config: &Config, output: BenchWriter) {
+    println!("{}", config);
+    let perf = config.measure(|| match config.mode {
+        Mode::Map => match config.ds {
+            DS::BravoTree => bench_map::<usize, BravoBTreeMap<usize, usize>>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the locked baselines"),
+        },
+        _ => panic!("Unsupported(or unimplemented) mode for the locked baselines"),
+    });
+    output.write_record(config, &perf);
+    println!("{}", perf);
+}
+
+fn prefill<K: BenchKey, M: ConcurrentMap<K, usize> + Send + Sync>(config: &Config, map: &M) {
+    // The lock serializes the insertions, so a single thread prefills in a decreasing order.
+    for index in config.decreasing_prefill::<K>() {
+        let key = K::from_index(index);
+        let value = index;
+        map.insert(key, value);
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+    config.age(|op, index| {
+        let key = K::from_index(index);
+        if op == Op::Insert {
+            map.insert(key, index)
+        } else {
+            map.remove(&key).is_some()
+        }
+    });
+}
+
+fn bench_map<K: BenchKey, M: ConcurrentMap<K, usize> + Send + Sync>(config: &Config) -> Perf {
+    let map = &M::new();
+    prefill(config, map);
+    config.run_hygiene();
+
+    let gate = &StartGate::new(config.threads + config.aux_thread);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
+    let (mem_sender, mem_receiver) = mpsc::channel();
+
+    scope(|s| {
+        if config.aux_thread > 0 {
+            let mem_sender = mem_sender.clone();
+            s.spawn(move |_| {
+                assert!(config.sampling);
+                let mut samples = 0usize;
+                let mut acc = 0usize;
+                let mut peak = 0usize;
+                gate.wait();
+
+                let start = Instant::now();
+                let mut next_sampling = start + config.sampling_period;
+                while start.elapsed() < config.duration {
+                    let now = Instant::now();
+                    if now > next_sampling {
+                        let allocated = config.mem_sampler.sample();
+                        samples += 1;
+
+                        acc += allocated;
+                        peak = max(peak, allocated);
+
+                        next_sampling = now + config.sampling_period;
+                    }
+                    std::thread::sleep(config.aux_thread_period);
+                }
+                mem_sender.send((peak, acc / samples, 0, 0)).unwrap();
+            });
+        } else {
+            mem_sender.send((0, 0, 0, 0)).unwrap();
+        }
+
+        for result in results.slots() {
+            s.spawn(move |_| {
+                let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
+                let mut rng = &mut rand::thread_rng();
+                config.start(gate, result, |index| {
+                    map.get(&K::from_index(index));
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
+                let start = Instant::now();
+
+                while start.elapsed() < lifetime {
+                    let op_start = result.op_start(config, config.timed(critical));
+                    let index = config.key_dist.sample(rng);
+                    let key = K::from_index(index);
+                    conflicts.enter(result.index, index);
+                    let op = config.sample_op(critical, &mut rng);
+                    match op {
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(key, index);
+                        }
+                        Op::Get => {
+                            shadow.run(Op::Get, index, || map.get(&key).map(|v| *v.output()));
+                        }
+                        Op::Insert => {
+                            let value = index;
+                            shadow.run(Op::Insert, index, || {
+                                map.insert(key, value).then_some(value)
+                            });
+                        }
+                        Op::Remove => {
+                            shadow.run(Op::Remove, index, || map.remove(&key).map(|v| *v.output()));
+                        }
+                    }
+                    conflicts.exit(result.index);
+                    ops += 1;
+                    result.record_op(config, critical, Some(op), op_start);
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
+                }
+
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
+            });
+        }
+    })
+    .unwrap();
+    println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene();
+
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+
+    let ops_per_sec = results.ops_per_sec();
+    let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
+    Perf {
+        ops_per_sec,
+        peak_mem,
+        avg_mem,
+        peak_garb,
+        avg_garb,
+    }
+}
@@ -9,16 +9,16 @@ extern crate smr_benchmark;
 
 use clap::{value_parser, Arg, ArgMatches, Command, ValueEnum};
 use crossbeam_utils::thread::scope;
-use csv::Writer;
 use rand::distributions::Uniform;
 use rand::prelude::*;
+use smr_benchmark::clock::{ClockReport, ThreadClock};
 use smr_benchmark::ds_impl;
 use smr_benchmark::ds_impl::cdrc::OutputHolder;
+use smr_benchmark::reclamation;
+use smr_benchmark::results::ResultsFile;
 use std::cmp::max;
 use std::fmt;
-use std::fs::{create_dir_all, File, OpenOptions};
 use std::io::{stdout, Write};
-use std::path::Path;
 use std::sync::atomic::Ordering;
 use std::sync::{mpsc, Arc, Barrier};
 use std::time::{Duration, Instant};
@@ -81,8 +81,8 @@ struct Config {
 
     key_dist: Uniform<usize>,
     prefill: usize,
-    interval: u64,
     duration: Duration,
+    reclaim_bound: Option<Duration>,
 
     mem_sampler: MemSampler,
 }
@@ -168,6 +168,15 @@ fn main() {
                 )
                 .default_value("1"),
         )
+        .arg(
+            Arg::new("reclaim bound")
+                .long("reclaim-bound")
+                .value_parser(value_parser!(u64))
+                .help(
+                    "Checks that each node that a writer removes is freed within this many \
+                     milliseconds after its removal, and fails the run otherwise.",
+                ),
+        )
         .arg(
             Arg::new("output")
                 .short('o')
@@ -175,11 +184,11 @@ fn main() {
         )
         .get_matches();
 
-    let (config, mut output) = setup(matches);
-    bench::<U1>(&config, output.as_mut());
+    let (config, output) = setup(matches);
+    bench::<U1>(&config, output.as_ref());
 }
 
-fn setup(m: ArgMatches) -> (Config, Option<Writer<File>>) {
+fn setup(m: ArgMatches) -> (Config, Option<ResultsFile>) {
     let mm = m.get_one::<MM>("memory manager").cloned().unwrap();
     let writers = m.get_one::<usize>("writers").copied().unwrap();
     let readers = m.get_one::<usize>("readers").copied().unwrap();
@@ -190,48 +199,39 @@ fn setup(m: ArgMatches) -> (Config, Option<Writer<File>>) {
     let sampling_period = m.get_one::<u64>("sampling period").copied().unwrap();
     let sampling = sampling_period > 0 && cfg!(all(not(feature = "sanitize"), target_os = "linux"));
     let duration = Duration::from_secs(interval);
+    let reclaim_bound = m
+        .get_one::<u64>("reclaim bound")
+        .map(|&bound| Duration::from_millis(bound));
 
     assert!(
         readers >= 1,
         "The number of readers must be greater than zero!"
     );
+    assert!(
+        reclaim_bound.is_none() || !matches!(mm, MM::PEBR | MM::VBR),
+        "The reclamation bound is not supported for {mm:?}, whose pop returns a copy of the value"
+    );
 
     let output = m.get_one::<String>("output").map(|output_name| {
-        let output_path = Path::new(output_name);
-        let dir = output_path.parent().unwrap();
-        create_dir_all(dir).unwrap();
-        match OpenOptions::new()
-            .read(true)
-            .write(true)
-            .append(true)
-            .open(&output_name)
-        {
-            Ok(f) => csv::Writer::from_writer(f),
-            Err(_) => {
-                let f = OpenOptions::new()
-                    .read(true)
-                    .write(true)
-                    .create(true)
-                    .open(&output_name)
-                    .unwrap();
-                let mut output = csv::Writer::from_writer(f);
-                // NOTE: `write_record` on `bench`
-                output
-                    .write_record(&[
-                        "mm",
-                        "sampling_period",
-                        "throughput",
-                        "peak_mem",
-                        "avg_mem",
-                        "peak_garb",
-                        "avg_garb",
-                        "key_range",
-                    ])
-                    .unwrap();
-                output.flush().unwrap();
-                output
-            }
-        }
+        // NOTE: `append` on `bench`
+        ResultsFile::open(
+            output_name,
+            &[
+                "mm",
+                "sampling_period",
+                "throughput",
+                "peak_mem",
+                "avg_mem",
+                "peak_garb",
+                "avg_garb",
+                "key_range",
+                "max_drift_us",
+                "skewed_periods",
+                "reclaim_bound_ms",
+                "max_reclaim_latency_us",
+                "reclaim_violations",
+            ],
+        )
     });
     let mem_sampler = MemSampler::new();
     let config = Config {
@@ -246,22 +246,25 @@ fn setup(m: ArgMatches) -> (Config, Option<Writer<File>>) {
 
         key_dist,
         prefill,
-        interval,
         duration,
+        reclaim_bound,
 
         mem_sampler,
     };
+    if let Some(bound) = reclaim_bound {
+        reclamation::enable(bound);
+    }
     (config, output)
 }
 
-fn bench<N: Unsigned>(config: &Config, output: Option<&mut Writer<File>>) {
+fn bench<N: Unsigned>(config: &Config, output: Option<&ResultsFile>) {
     println!(
         "{}: {} writers, {} readers",
         config.mm.to_possible_value().unwrap().get_name(),
         config.writers,
         config.readers
     );
-    let (ops_per_sec, peak_mem, avg_mem, peak_garb, avg_garb) = match config.mm {
+    let (clock, peak_mem, avg_mem, peak_garb, avg_garb) = match config.mm {
         MM::NR => bench_map_nr(config, PrefillStrategy::Decreasing),
         MM::EBR => bench_map_ebr::<N>(config, PrefillStrategy::Decreasing),
         MM::PEBR => bench_map_pebr::<N>(config, PrefillStrategy::Decreasing),
@@ -274,30 +277,50 @@ fn bench<N: Unsigned>(config: &Config, output: Option<&mut Writer<File>>) {
         MM::NBR_LARGE => bench_map_nbr(config, PrefillStrategy::Decreasing, &NBR_LARGE_CAP, 2),
         MM::VBR => bench_map_vbr(config, PrefillStrategy::Decreasing),
     };
+    let ops_per_sec = clock.ops_per_sec as u64;
+    let reclaims = config.reclaim_bound.map(|_| reclamation::report());
     if let Some(output) = output {
-        output
-            .write_record(&[
-                config
-                    .mm
-                    .to_possible_value()
-                    .unwrap()
-                    .get_name()
-                    .to_string(),
-                config.sampling_period.as_millis().to_string(),
-                ops_per_sec.to_string(),
-                peak_mem.to_string(),
-                avg_mem.to_string(),
-                peak_garb.to_string(),
-                avg_garb.to_string(),
-                (config.prefill * 2).to_string(),
-            ])
-            .unwrap();
-        output.flush().unwrap();
+        output.append([
+            config
+                .mm
+                .to_possible_value()
+                .unwrap()
+                .get_name()
+                .to_string(),
+            config.sampling_period.as_millis().to_string(),
+            ops_per_sec.to_string(),
+            peak_mem.to_string(),
+            avg_mem.to_string(),
+            peak_garb.to_string(),
+            avg_garb.to_string(),
+            (config.prefill * 2).to_string(),
+            clock.max_drift.as_micros().to_string(),
+            clock.skewed.to_string(),
+            reclaims
+                .as_ref()
+                .map_or(String::new(), |r| r.bound.as_millis().to_string()),
+            reclaims
+                .as_ref()
+                .map_or(String::new(), |r| r.max_latency.as_micros().to_string()),
+            reclaims
+                .as_ref()
+                .map_or(String::new(), |r| r.violations().to_string()),
+        ]);
     }
     println!(
         "ops/s: {}, peak mem: {}, avg_mem: {}, peak garb: {}, avg garb: {}",
         ops_per_sec, peak_mem, avg_mem, peak_garb, avg_garb
     );
+    println!("{clock}");
+    if let Some(reclaims) = reclaims {
+        println!("{reclaims}");
+        assert_eq!(
+            reclaims.violations(),
+            0,
+            "Some removed nodes were not freed within {:?}",
+            reclaims.bound
+        );
+    }
 }
 
 #[allow(unused)]
@@ -534,7 +557,10 @@ impl PrefillStrategy {
     }
 }
 
-fn bench_map_nr(config: &Config, strategy: PrefillStrategy) -> (u64, usize, usize, usize, usize) {
+fn bench_map_nr(
+    config: &Config,
+    strategy: PrefillStrategy,
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::ebr::ConcurrentMap;
     let map = &ds_impl::ebr::HHSList::new();
     strategy.prefill_ebr(config, map);
@@ -596,6 +622,7 @@ fn bench_map_nr(config: &Config, strategy: PrefillStrategy) -> (u64, usize, usiz
                         assert!(map.insert(key, value, unsafe { crossbeam_ebr::leaking() }));
                     } else {
                         let (key, value) = map.pop(unsafe { crossbeam_ebr::leaking() }).unwrap();
+                        reclamation::retired(value);
                         acquired = Some((key.clone(), value.clone()));
                     }
                 }
@@ -609,35 +636,30 @@ fn bench_map_nr(config: &Config, strategy: PrefillStrategy) -> (u64, usize, usiz
                 let mut ops: u64 = 0;
                 let rng = &mut rand::thread_rng();
                 barrier.clone().wait();
-                let start = Instant::now();
+                let mut clock = ThreadClock::start();
 
-                while start.elapsed() < config.duration {
+                while clock.running(ops, config.duration) {
                     let key = config.key_dist.sample(rng);
                     let _ = map.get(&key, unsafe { crossbeam_ebr::leaking() });
                     ops += 1;
                 }
 
-                ops_sender.send(ops).unwrap();
+                ops_sender.send(clock.finish(ops)).unwrap();
             });
         }
     })
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
 
 fn bench_map_ebr<N: Unsigned>(
     config: &Config,
     strategy: PrefillStrategy,
-) -> (u64, usize, usize, usize, usize) {
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::ebr::ConcurrentMap;
     let map = &ds_impl::ebr::HHSList::new();
     strategy.prefill_ebr(config, map);
@@ -709,6 +731,7 @@ fn bench_map_ebr<N: Unsigned>(
                         assert!(map.insert(key, value, &guard));
                     } else {
                         let (key, value) = map.pop(&guard).unwrap();
+                        reclamation::retired(value);
                         acquired = Some((key.clone(), value.clone()));
                     }
                     ops += 1;
@@ -728,10 +751,10 @@ fn bench_map_ebr<N: Unsigned>(
                 let rng = &mut rand::thread_rng();
                 let handle = collector.register();
                 barrier.clone().wait();
-                let start = Instant::now();
+                let mut clock = ThreadClock::start();
 
                 let mut guard = handle.pin();
-                while start.elapsed() < config.duration {
+                while clock.running(ops, config.duration) {
                     let key = config.key_dist.sample(rng);
                     let _ = map.get(&key, &guard);
                     ops += 1;
@@ -741,27 +764,22 @@ fn bench_map_ebr<N: Unsigned>(
                     }
                 }
 
-                ops_sender.send(ops).unwrap();
+                ops_sender.send(clock.finish(ops)).unwrap();
             });
         }
     })
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
 
 fn bench_map_pebr<N: Unsigned>(
     config: &Config,
     strategy: PrefillStrategy,
-) -> (u64, usize, usize, usize, usize) {
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::pebr::ConcurrentMap;
     let map = &ds_impl::pebr::HHSList::new();
     strategy.prefill_pebr(config, map);
@@ -854,10 +872,10 @@ fn bench_map_pebr<N: Unsigned>(
                 let handle = collector.register();
                 let mut map_handle = ds_impl::pebr::HHSList::handle(&handle.pin());
                 barrier.clone().wait();
-                let start = Instant::now();
+                let mut clock = ThreadClock::start();
 
                 let mut guard = handle.pin();
-                while start.elapsed() < config.duration {
+                while clock.running(ops, config.duration) {
                     let key = config.key_dist.sample(rng);
                     let _ = map.get(&mut map_handle, &key, &mut guard);
                     ops += 1;
@@ -867,24 +885,22 @@ fn bench_map_pebr<N: Unsigned>(
                     }
                 }
 
-                ops_sender.send(ops).unwrap();
+                ops_sender.send(clock.finish(ops)).unwrap();
             });
         }
     })
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
 
-fn bench_map_hp(config: &Config, strategy: PrefillStrategy) -> (u64, usize, usize, usize, usize) {
+fn bench_map_hp(
+    config: &Config,
+    strategy: PrefillStrategy,
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::hp::ConcurrentMap;
     let map = &ds_impl::hp::HMList::new();
     strategy.prefill_hp(config, map);
@@ -952,6 +968,7 @@ fn bench_map_hp(config: &Config, strategy: PrefillStrategy) -> (u64, usize, usiz
                         assert!(map.insert(&mut map_handle, key, value));
                     } else {
                         let (key, value) = map.pop(&mut map_handle).unwrap();
+                        reclamation::retired(value);
                         acquired = Some((key.clone(), value.clone()));
                     }
                 }
@@ -966,35 +983,30 @@ fn bench_map_hp(config: &Config, strategy: PrefillStrategy) -> (u64, usize, usiz
                 let rng = &mut rand::thread_rng();
                 let mut map_handle = ds_impl::hp::HMList::<usize, usize>::handle();
                 barrier.clone().wait();
-                let start = Instant::now();
+                let mut clock = ThreadClock::start();
 
-                while start.elapsed() < config.duration {
+                while clock.running(ops, config.duration) {
                     let key = config.key_dist.sample(rng);
                     let _ = map.get(&mut map_handle, &key);
                     ops += 1;
                 }
 
-                ops_sender.send(ops).unwrap();
+                ops_sender.send(clock.finish(ops)).unwrap();
             });
         }
     })
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
 
 fn bench_map_hp_pp(
     config: &Config,
     strategy: PrefillStrategy,
-) -> (u64, usize, usize, usize, usize) {
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::hp::ConcurrentMap;
     let map = &ds_impl::hp_pp::HHSList::new();
     strategy.prefill_hp(config, map);
@@ -1062,6 +1074,7 @@ fn bench_map_hp_pp(
                         assert!(map.insert(&mut map_handle, key, value))
                     } else {
                         let (key, value) = map.pop(&mut map_handle).unwrap();
+                        reclamation::retired(value);
                         acquired = Some((key.clone(), value.clone()));
                     }
                 }
@@ -1076,35 +1089,30 @@ fn bench_map_hp_pp(
                 let rng = &mut rand::thread_rng();
                 let mut map_handle = ds_impl::hp_pp::HHSList::<usize, usize>::handle();
                 barrier.clone().wait();
-                let start = Instant::now();
+                let mut clock = ThreadClock::start();
 
-                while start.elapsed() < config.duration {
+                while clock.running(ops, config.duration) {
                     let key = config.key_dist.sample(rng);
                     let _ = map.get(&mut map_handle, &key);
                     ops += 1;
                 }
 
-                ops_sender.send(ops).unwrap();
+                ops_sender.send(clock.finish(ops)).unwrap();
             });
         }
     })
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
 
 fn bench_map_cdrc<C: cdrc::Cs, N: Unsigned>(
     config: &Config,
     strategy: PrefillStrategy,
-) -> (u64, usize, usize, usize, usize) {
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::cdrc::ConcurrentMap;
     let map = &ds_impl::cdrc::HHSList::new();
     strategy.prefill_cdrc(config, map);
@@ -1173,6 +1181,7 @@ fn bench_map_cdrc<C: cdrc::Cs, N: Unsigned>(
                         assert!(map.insert(value, value, output, &cs));
                     } else {
                         assert!(map.pop(output, &cs));
+                        reclamation::retired(output.output());
                         acquired = Some(output.output().clone());
                     }
                     ops += 1;
@@ -1190,11 +1199,11 @@ fn bench_map_cdrc<C: cdrc::Cs, N: Unsigned>(
                 let mut ops: u64 = 0;
                 let rng = &mut rand::thread_rng();
                 barrier.clone().wait();
-                let start = Instant::now();
+                let mut clock = ThreadClock::start();
 
                 let output = &mut ds_impl::cdrc::HHSList::empty_output();
                 let mut cs = C::new();
-                while start.elapsed() < config.duration {
+                while clock.running(ops, config.duration) {
                     let key = config.key_dist.sample(rng);
                     let _ = map.get(&key, output, &cs);
                     ops += 1;
@@ -1203,27 +1212,22 @@ fn bench_map_cdrc<C: cdrc::Cs, N: Unsigned>(
                     }
                 }
 
-                ops_sender.send(ops).unwrap();
+                ops_sender.send(clock.finish(ops)).unwrap();
             });
         }
     })
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
 
 fn bench_map_hp_brcu<const ROLLBACK: bool>(
     config: &Config,
     strategy: PrefillStrategy,
-) -> (u64, usize, usize, usize, usize) {
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::hp_brcu::concurrent_map::OutputHolder;
     use ds_impl::hp_brcu::ConcurrentMap;
 
@@ -1299,6 +1303,7 @@ fn bench_map_hp_brcu<const ROLLBACK: bool>(
                             assert!(map.insert(value, value, output, handle))
                         } else {
                             assert!(map.pop(output, handle));
+                            reclamation::retired(output.output());
                             acquired = Some(output.output().clone());
                         }
                     }
@@ -1319,15 +1324,15 @@ fn bench_map_hp_brcu<const ROLLBACK: bool>(
                     let mut ops: u64 = 0;
                     let rng = &mut rand::thread_rng();
                     barrier.clone().wait();
-                    let start = Instant::now();
+                    let mut clock = ThreadClock::start();
 
-                    while start.elapsed() < config.duration {
+                    while clock.running(ops, config.duration) {
                         let key = config.key_dist.sample(rng);
                         let _ = map.get(&key, output, handle);
                         ops += 1;
                     }
 
-                    ops_sender.send(ops).unwrap();
+                    ops_sender.send(clock.finish(ops)).unwrap();
                 });
             });
         }
@@ -1335,14 +1340,9 @@ fn bench_map_hp_brcu<const ROLLBACK: bool>(
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
 
 fn bench_map_nbr(
@@ -1350,7 +1350,7 @@ fn bench_map_nbr(
     strategy: PrefillStrategy,
     nbr_config: &NBRConfig,
     max_hazptrs: usize,
-) -> (u64, usize, usize, usize, usize) {
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::nbr::ConcurrentMap;
     let map = &ds_impl::nbr::HHSList::new();
     strategy.prefill_nbr(config, map, max_hazptrs);
@@ -1426,6 +1426,7 @@ fn bench_map_nbr(
                         assert!(map.insert(key, value, &mut handle, &guard));
                     } else {
                         let (key, value) = map.pop(&mut handle, &guard).unwrap();
+                        reclamation::retired(value);
                         acquired = Some((key.clone(), value.clone()));
                     }
                 }
@@ -1441,32 +1442,30 @@ fn bench_map_nbr(
                 let mut ops: u64 = 0;
                 let rng = &mut rand::thread_rng();
                 barrier.clone().wait();
-                let start = Instant::now();
+                let mut clock = ThreadClock::start();
 
-                while start.elapsed() < config.duration {
+                while clock.running(ops, config.duration) {
                     let key = config.key_dist.sample(rng);
                     let _ = map.get(&key, &mut handle, &guard);
                     ops += 1;
                 }
 
-                ops_sender.send(ops).unwrap();
+                ops_sender.send(clock.finish(ops)).unwrap();
             });
         }
     })
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
 
-fn bench_map_vbr(config: &Config, strategy: PrefillStrategy) -> (u64, usize, usize, usize, usize) {
+fn bench_map_vbr(
+    config: &Config,
+    strategy: PrefillStrategy,
+) -> (ClockReport, usize, usize, usize, usize) {
     use ds_impl::vbr::ConcurrentMap;
     let global = &ds_impl::vbr::HHSList::global(config.prefill);
     let local = &ds_impl::vbr::HHSList::local(global);
@@ -1550,27 +1549,22 @@ fn bench_map_vbr(config: &Config, strategy: PrefillStrategy) -> (u64, usize, usi
                 let mut ops: u64 = 0;
                 let rng = &mut rand::thread_rng();
                 barrier.clone().wait();
-                let start = Instant::now();
+                let mut clock = ThreadClock::start();
 
-                while start.elapsed() < config.duration {
+                while clock.running(ops, config.duration) {
                     let key = config.key_dist.sample(rng);
                     let _ = map.get(&key, local);
                     ops += 1;
                 }
 
-                ops_sender.send(ops).unwrap();
+                ops_sender.send(clock.finish(ops)).unwrap();
             });
         }
     })
     .unwrap();
     println!("end");
 
-    let mut ops = 0;
-    for _ in 0..config.readers {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    let clock = ClockReport::sum(ops_receiver.iter().take(config.readers));
     let (peak_mem, avg_mem, garb_peak, garb_avg) = mem_receiver.recv().unwrap();
-    (ops_per_sec, peak_mem, avg_mem, garb_peak, garb_avg)
+    (clock, peak_mem, avg_mem, garb_peak, garb_avg)
 }
@@ -0,0 +1,510 @@
+extern crate clap;
+extern crate csv;
+
+extern crate crossbeam_ebr;
+extern crate smr_benchmark;
+
+use clap::{value_parser, Arg, ArgMatches, Command, ValueEnum};
+use crossbeam_utils::thread::scope;
+use crossbeam_utils::CachePadded;
+use rand::distributions::Uniform;
+use rand::prelude::*;
+use std::cmp::max;
+use std::io::{stdout, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Barrier};
+use std::time::{Duration, Instant};
+
+use smr_benchmark::ds_impl::migration::Migration;
+use smr_benchmark::ds_impl::{ebr, hp};
+use smr_benchmark::results::ResultsFile;
+use smr_benchmark::MemSampler;
+
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum MM {
+    EBR,
+    HP,
+}
+
+struct Config {
+    mm: MM,
+    threads: usize,
+    buckets: usize,
+    migrate_after: Duration,
+    aux_thread: usize,
+    aux_thread_period: Duration,
+    sampling: bool,
+    interval: u64,
+    duration: Duration,
+    mem_sampler: MemSampler,
+    range: usize,
+    key_dist: Uniform<usize>,
+}
+
+fn main() {
+    let matches = Command::new("smr_benchmark")
+        .arg(
+            Arg::new("memory manager")
+                .short('m')
+                .value_parser(value_parser!(MM))
+                .required(true)
+                .ignore_case(true)
+                .help("Memeory manager(s)"),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .value_parser(value_parser!(usize))
+                .required(true)
+                .help("Numbers of threads which perform operations on the map."),
+        )
+        .arg(
+            Arg::new("range")
+                .short('r')
+                .value_parser(value_parser!(usize))
+                .help("Key range: [0..RANGE], half of which is prefilled")
+                .default_value("100000"),
+        )
+        .arg(
+            Arg::new("buckets")
+                .long("buckets")
+                .value_parser(value_parser!(usize))
+                .help("The number of buckets of the table before the migration.")
+                .default_value("16384"),
+        )
+        .arg(
+            Arg::new("migrate after")
+                .long("migrate-after")
+                .value_parser(value_parser!(u64))
+                .help(
+                    "The time in milliseconds after the start at which the map is migrated into \
+                     a table with twice the buckets.",
+                )
+                .default_value("2000"),
+        )
+        .arg(
+            Arg::new("interval")
+                .short('i')
+                .value_parser(value_parser!(u64))
+                .help("Time interval in seconds to run the benchmark")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .help("Output CSV filename. Appends the data if the file already exists."),
+        )
+        .get_matches();
+
+    let (config, output) = setup(matches);
+    bench(&config, output.as_ref());
+}
+
+fn setup(m: ArgMatches) -> (Config, Option<ResultsFile>) {
+    let mm = m.get_one::<MM>("memory manager").copied().unwrap();
+    let threads = m.get_one::<usize>("threads").copied().unwrap();
+    let range = m.get_one::<usize>("range").copied().unwrap();
+    let buckets = m.get_one::<usize>("buckets").copied().unwrap();
+    let migrate_after = m.get_one::<u64>("migrate after").copied().unwrap();
+    let interval = m.get_one::<u64>("interval").copied().unwrap();
+    let sampling = cfg!(all(not(feature = "sanitize"), target_os = "linux"));
+    let duration = Duration::from_secs(interval);
+    let migrate_after = Duration::from_millis(migrate_after);
+
+    assert!(
+        threads >= 1,
+        "The number of threads must be greater than zero!"
+    );
+    assert!(range >= 2, "The key range must hold at least two keys!");
+    assert!(buckets >= 1, "The table must have a bucket!");
+    assert!(
+        !migrate_after.is_zero() && migrate_after < duration,
+        "The migration must start within the run, after some operations!"
+    );
+
+    let output = m.get_one::<String>("output").map(|output_name| {
+        // NOTE: `append` on `bench`
+        ResultsFile::open(
+            output_name,
+            &[
+                "mm",
+                "threads",
+                "key_range",
+                "buckets",
+                "migrate_after_ms",
+                "throughput",
+                "throughput_before",
+                "throughput_during",
+                "dip",
+                "migration_us",
+                "copied",
+                "helped",
+                "peak_mem",
+                "peak_mem_migration",
+                "avg_mem",
+                "interval",
+            ],
+        )
+    });
+    let config = Config {
+        mm,
+        threads,
+        buckets,
+        migrate_after,
+        aux_thread: if sampling { 1 } else { 0 },
+        aux_thread_period: Duration::from_millis(1),
+        sampling,
+        interval,
+        duration,
+        mem_sampler: MemSampler::new(),
+        range,
+        key_dist: Uniform::from(0..range),
+    };
+    (config, output)
+}
+
+fn name<T: ValueEnum>(value: &T) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+fn bench(config: &Config, output: Option<&ResultsFile>) {
+    println!(
+        "{}: {} threads, {} buckets, migrated after {:?}",
+        name(&config.mm),
+        config.threads,
+        config.buckets,
+        config.migrate_after,
+    );
+    let result = match config.mm {
+        MM::EBR => run(
+            config,
+            &Ebr::new(ebr::MigratingHashMap::with_buckets(config.buckets)),
+        ),
+        MM::HP => run(
+            config,
+            &Hp(hp::MigratingHashMap::with_buckets(config.buckets)),
+        ),
+    };
+    let ops_per_sec = result.ops / config.interval;
+    let migrated = &result.migrated;
+    let before = migrated.before as f64 / migrated.began.as_secs_f64();
+    let during = migrated.during as f64 / migrated.took.as_secs_f64();
+    let dip = 1.0 - during / before;
+
+    if let Some(output) = output {
+        output.append([
+            name(&config.mm),
+            config.threads.to_string(),
+            config.range.to_string(),
+            config.buckets.to_string(),
+            config.migrate_after.as_millis().to_string(),
+            ops_per_sec.to_string(),
+            format!("{before:.0}"),
+            format!("{during:.0}"),
+            format!("{dip:.4}"),
+            migrated.took.as_micros().to_string(),
+            migrated.migration.copied.to_string(),
+            migrated.migration.helped.to_string(),
+            result.peak_mem.to_string(),
+            result.peak_mem_migration.to_string(),
+            result.avg_mem.to_string(),
+            config.interval.to_string(),
+        ]);
+    }
+    println!(
+        "ops/s: {}, before the migration: {:.0}, during the migration: {:.0} (dip: {:.2}%)",
+        ops_per_sec,
+        before,
+        during,
+        dip * 100.0
+    );
+    println!(
+        "migration: {} buckets in {:?}, {} keys copied and retired with the old table, {} buckets \
+         migrated by the updates",
+        migrated.migration.buckets,
+        migrated.took,
+        migrated.migration.copied,
+        migrated.migration.helped
+    );
+    println!(
+        "peak mem: {}, from the migration: {}, avg_mem: {}",
+        result.peak_mem, result.peak_mem_migration, result.avg_mem
+    );
+}
+
+/// The migrating hash map of one of the schemes.
+trait Scheme: Sync {
+    /// The state of a thread on the scheme.
+    type Local;
+
+    fn local(&self) -> Self::Local;
+
+    /// Ends the critical section of the thread between two of its operations.
+    fn quiesce(&self, _local: &mut Self::Local) {}
+
+    fn get(&self, local: &mut Self::Local, key: usize) -> bool;
+
+    fn insert(&self, local: &mut Self::Local, key: usize) -> bool;
+
+    fn remove(&self, local: &mut Self::Local, key: usize) -> bool;
+
+    fn migrate(&self, local: &mut Self::Local) -> Migration;
+}
+
+struct Ebr {
+    map: ebr::MigratingHashMap<usize, usize>,
+    collector: crossbeam_ebr::Collector,
+}
+
+impl Ebr {
+    fn new(map: ebr::MigratingHashMap<usize, usize>) -> Self {
+        Self {
+            map,
+            collector: crossbeam_ebr::Collector::new(),
+        }
+    }
+}
+
+/// A pinned thread of `Ebr`, whose guard is dropped before its handle.
+struct Pinned {
+    guard: crossbeam_ebr::Guard,
+    _handle: crossbeam_ebr::LocalHandle,
+}
+
+impl Scheme for Ebr {
+    type Local = Pinned;
+
+    fn local(&self) -> Pinned {
+        let handle = self.collector.register();
+        Pinned {
+            guard: handle.pin(),
+            _handle: handle,
+        }
+    }
+
+    fn quiesce(&self, local: &mut Pinned) {
+        local.guard.repin();
+    }
+
+    fn get(&self, local: &mut Pinned, key: usize) -> bool {
+        self.map.get(&key, &local.guard).is_some()
+    }
+
+    fn insert(&self, local: &mut Pinned, key: usize) -> bool {
+        self.map.insert(key, key, &local.guard)
+    }
+
+    fn remove(&self, local: &mut Pinned, key: usize) -> bool {
+        self.map.remove(&key, &local.guard).is_some()
+    }
+
+    fn migrate(&self, local: &mut Pinned) -> Migration {
+        self.map.migrate(&local.guard)
+    }
+}
+
+struct Hp(hp::MigratingHashMap<usize, usize>);
+
+impl Scheme for Hp {
+    type Local = hp::migrating_hash_map::Handle<'static>;
+
+    fn local(&self) -> Self::Local {
+        Default::default()
+    }
+
+    fn get(&self, local: &mut Self::Local, key: usize) -> bool {
+        use hp::ConcurrentMap;
+        self.0.get(local, &key).is_some()
+    }
+
+    fn insert(&self, local: &mut Self::Local, key: usize) -> bool {
+        use hp::ConcurrentMap;
+        self.0.insert(local, key, key)
+    }
+
+    fn remove(&self, local: &mut Self::Local, key: usize) -> bool {
+        use hp::ConcurrentMap;
+        self.0.remove(local, &key).is_some()
+    }
+
+    fn migrate(&self, local: &mut Self::Local) -> Migration {
+        self.0.migrate(local)
+    }
+}
+
+/// The migration of a run, and the operations of the threads around it.
+struct Migrated {
+    migration: Migration,
+    /// The time from the start at which the migration began.
+    began: Duration,
+    took: Duration,
+    /// The operations before and during the migration.
+    before: u64,
+    during: u64,
+}
+
+/// The operations and the migration of a run, and the memory usage of the process during it.
+struct Run {
+    ops: u64,
+    migrated: Migrated,
+    peak_mem: usize,
+    peak_mem_migration: usize,
+    avg_mem: usize,
+}
+
+/// Runs the operations of a thread from the release of `barrier` for the duration of the run, and
+/// publishes their number in `ops` after each of them.
+fn operate<S: Scheme>(config: &Config, map: &S, ops: &AtomicU64, barrier: &Barrier) -> u64 {
+    let mut local = map.local();
+    let rng = &mut rand::thread_rng();
+    let mut done = 0;
+    barrier.wait();
+    let start = Instant::now();
+
+    while start.elapsed() < config.duration {
+        let key = config.key_dist.sample(rng);
+        // Half of the operations are gets, and the others are split evenly between the updates.
+        match rng.gen_range(0..4) {
+            0 => map.insert(&mut local, key),
+            1 => map.remove(&mut local, key),
+            _ => map.get(&mut local, key),
+        };
+        done += 1;
+        ops.store(done, Ordering::Relaxed);
+        map.quiesce(&mut local);
+    }
+    done
+}
+
+/// Migrates the map once, `config.migrate_after` after the release of `barrier`.
+fn migrate<S: Scheme>(
+    config: &Config,
+    map: &S,
+    ops: &[CachePadded<AtomicU64>],
+    migrating: &AtomicBool,
+    barrier: &Barrier,
+) -> Migrated {
+    let total = || {
+        ops.iter()
+            .map(|ops| ops.load(Ordering::Relaxed))
+            .sum::<u64>()
+    };
+    barrier.wait();
+    let start = Instant::now();
+
+    std::thread::sleep(config.migrate_after);
+    // A pinned thread of `Ebr` would hold back the reclamation while it sleeps.
+    let mut local = map.local();
+    let before = total();
+    let began = start.elapsed();
+    migrating.store(true, Ordering::Relaxed);
+    let migration = map.migrate(&mut local);
+    let took = start.elapsed() - began;
+    let during = total() - before;
+    Migrated {
+        migration,
+        began,
+        took,
+        before,
+        during,
+    }
+}
+
+/// Prefills the map, and runs the threads and the migration on it.
+fn run<S: Scheme>(config: &Config, map: &S) -> Run {
+    {
+        let mut local = map.local();
+        let rng = &mut rand::thread_rng();
+        let mut keys = 0;
+        while keys < config.range / 2 {
+            if map.insert(&mut local, config.key_dist.sample(rng)) {
+                keys += 1;
+            }
+            map.quiesce(&mut local);
+        }
+    }
+    print!("prefilled... ");
+    stdout().flush().unwrap();
+
+    let ops = &(0..config.threads)
+        .map(|_| CachePadded::new(AtomicU64::new(0)))
+        .collect::<Vec<_>>();
+    let migrating = &AtomicBool::new(false);
+    let barrier = &Arc::new(Barrier::new(config.threads + 1 + config.aux_thread));
+    let (ops_sender, ops_receiver) = mpsc::channel();
+    let (migrated_sender, migrated_receiver) = mpsc::channel();
+    let (mem_sender, mem_receiver) = mpsc::channel();
+
+    scope(|s| {
+        // sampling thread
+        if config.aux_thread > 0 {
+            let mem_sender = mem_sender.clone();
+            s.spawn(move |_| {
+                let mut samples = 0usize;
+                let mut acc = 0usize;
+                let mut peak = 0usize;
+                let mut peak_migration = 0usize;
+                barrier.clone().wait();
+
+                let start = Instant::now();
+                let mut next_sampling = start + Duration::from_millis(1);
+                while start.elapsed() < config.duration {
+                    let now = Instant::now();
+                    if now > next_sampling {
+                        let allocated = config.mem_sampler.sample();
+                        samples += 1;
+
+                        acc += allocated;
+                        if migrating.load(Ordering::Relaxed) {
+                            peak_migration = max(peak_migration, allocated);
+                        } else {
+                            peak = max(peak, allocated);
+                        }
+
+                        next_sampling = now + Duration::from_millis(1);
+                    }
+                    std::thread::sleep(config.aux_thread_period);
+                }
+
+                if config.sampling {
+                    mem_sender
+                        .send((peak, peak_migration, acc / samples))
+                        .unwrap();
+                } else {
+                    mem_sender.send((0, 0, 0)).unwrap();
+                }
+            });
+        } else {
+            mem_sender.send((0, 0, 0)).unwrap();
+        }
+
+        s.spawn(move |_| {
+            let migrated = migrate(config, map, ops, migrating, barrier);
+            migrated_sender.send(migrated).unwrap();
+        });
+
+        for ops in ops.iter() {
+            let ops_sender = ops_sender.clone();
+            s.spawn(move |_| {
+                let done = operate(config, map, ops, barrier);
+                ops_sender.send(done).unwrap();
+            });
+        }
+    })
+    .unwrap();
+    println!("end");
+
+    let mut ops = 0;
+    for _ in 0..config.threads {
+        ops += ops_receiver.recv().unwrap();
+    }
+    let migrated = migrated_receiver.recv().unwrap();
+    let (peak_mem, peak_mem_migration, avg_mem) = mem_receiver.recv().unwrap();
+    Run {
+        ops,
+        migrated,
+        peak_mem,
+        peak_mem_migration,
+        avg_mem,
+    }
+}
@@ -3,12 +3,16 @@ use rand::prelude::*;
 use std::cmp::max;
 use std::io::{stdout, Write};
 use std::path::Path;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, Perf, DS};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchWriter, Config, Op, Perf, ThreadResults, TreeShape, DS,
+};
 use smr_benchmark::ds_impl::nbr::{ConcurrentMap, HHSList, HList, HashMap, NMTreeMap};
+use smr_benchmark::log_event;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -23,13 +27,13 @@ fn main() {
 
 fn bench(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
+    let perf = config.measure(|| match config.ds {
         DS::HList => bench_map::<HList<usize, usize>>(config, PrefillStrategy::Decreasing, 2),
         DS::HHSList => bench_map::<HHSList<usize, usize>>(config, PrefillStrategy::Decreasing, 2),
         DS::HashMap => bench_map::<HashMap<usize, usize>>(config, PrefillStrategy::Decreasing, 2),
         DS::NMTree => bench_map::<NMTreeMap<usize, usize>>(config, PrefillStrategy::Random, 4),
         _ => panic!("Unsupported(or unimplemented) data structure for NBR"),
-    };
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
 }
@@ -57,6 +61,16 @@ impl PrefillStrategy {
         max_hazptrs: usize,
     ) {
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                let collector = &nbr::Collector::new(1, 256, 32, max_hazptrs);
+                let mut guard = collector.register();
+                let mut handle = M::handle(&mut guard);
+                for key in config.shaped_prefill::<usize>() {
+                    let value = key.clone();
+                    map.insert(key, value, &mut handle, &guard);
+                }
+            }
             PrefillStrategy::Random => {
                 let (bag_cap_pow2, lowatermark) = extract_nbr_params(config);
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
@@ -64,42 +78,47 @@ impl PrefillStrategy {
                     &nbr::Collector::new(threads, bag_cap_pow2, lowatermark, max_hazptrs);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            let mut guard = collector.register();
-                            let mut handle = M::handle(&mut guard);
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key.clone();
-                                map.insert(key, value, &mut handle, &guard);
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        let mut guard = collector.register();
+                        let mut handle = M::handle(&mut guard);
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let key = config.prefill_dist.sample(rng);
+                            let value = key.clone();
+                            map.insert(key, value, &mut handle, &guard);
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
                 let collector = &nbr::Collector::new(1, 256, 32, max_hazptrs);
                 let mut guard = collector.register();
                 let mut handle = M::handle(&mut guard);
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
+                for key in config.decreasing_prefill::<usize>() {
                     let value = key.clone();
                     map.insert(key, value, &mut handle, &guard);
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        let collector = &nbr::Collector::new(1, 256, 32, max_hazptrs);
+        let mut guard = collector.register();
+        let mut handle = M::handle(&mut guard);
+        config.age(|op, key| {
+            if op == Op::Insert {
+                map.insert(key, key, &mut handle, &guard)
+            } else {
+                map.remove(&key, &mut handle, &guard).is_some()
+            }
+        });
     }
 }
 
@@ -111,11 +130,14 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
     let (bag_cap_pow2, lowatermark) = extract_nbr_params(config);
     let map = &M::new();
     strategy.prefill(config, map, max_hazptrs);
+    config.run_hygiene();
 
     let collector = &nbr::Collector::new(config.threads, bag_cap_pow2, lowatermark, max_hazptrs);
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
 
     scope(|s| {
@@ -128,7 +150,7 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                 let mut peak = 0usize;
                 let mut garb_acc = 0usize;
                 let mut garb_peak = 0usize;
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
                 let mut next_sampling = start + config.sampling_period;
@@ -162,46 +184,79 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
                 let mut rng = &mut rand::thread_rng();
-                let mut guard = collector.register();
-                let mut handle = M::handle(&mut guard);
-                barrier.clone().wait();
+                let register = || {
+                    let mut guard = collector.register();
+                    let handle = M::handle(&mut guard);
+                    (guard, handle)
+                };
+                let mut registration = config.registers_early().then(register);
+                config.start(gate, result, |key| {
+                    if let Some((guard, handle)) = registration.as_mut() {
+                        map.get(&key, handle, guard);
+                    }
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
+                let (mut guard, mut handle) = registration.unwrap_or_else(register);
                 let start = Instant::now();
 
-                while start.elapsed() < config.duration {
+                while start.elapsed() < lifetime {
+                    let op_start = result.op_start(config, config.timed(critical));
                     let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
+                    conflicts.enter(result.index, key);
+                    let op = config.sample_op(critical, &mut rng);
+                    match op {
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(key, key, &mut handle, &guard);
+                        }
                         Op::Get => {
-                            map.get(&key, &mut handle, &guard);
+                            shadow
+                                .run(Op::Get, key, || map.get(&key, &mut handle, &guard).copied());
                         }
                         Op::Insert => {
                             let value = key.clone();
-                            map.insert(key, value, &mut handle, &guard);
+                            shadow.run(Op::Insert, key, || {
+                                map.insert(key, value, &mut handle, &guard).then_some(value)
+                            });
                         }
                         Op::Remove => {
-                            map.remove(&key, &mut handle, &guard);
+                            shadow.run(Op::Remove, key, || {
+                                map.remove(&key, &mut handle, &guard).copied()
+                            });
                         }
                     }
+                    conflicts.exit(result.index);
                     ops += 1;
+                    result.record_op(config, critical, Some(op), op_start);
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
                 }
 
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
             });
         }
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene();
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
@@ -3,15 +3,24 @@ use rand::prelude::*;
 use std::cmp::max;
 use std::io::{stdout, Write};
 use std::path::Path;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 
-use smr_benchmark::config::map::{setup, BenchWriter, Config, Op, Perf, DS};
+use smr_benchmark::config::harness::{self, NoReclamation};
+use smr_benchmark::config::map::{
+    setup, BenchKey, BenchWriter, CompositeKey, Config, KeyType, Mode, Op, Perf, ThreadResult,
+    ThreadResults, TreeShape, DS,
+};
+use smr_benchmark::ds_impl::nr::concurrent_map::OutputHolder;
 use smr_benchmark::ds_impl::nr::{
-    BonsaiTreeMap, ConcurrentMap, EFRBTree, ElimABTree, HHSList, HList, HMList, HashMap, NMTreeMap,
-    SkipList,
+    BonsaiTreeMap, ClockCache, ConcurrentCounter, ConcurrentMap, ConcurrentSet, EFRBTree,
+    ElimABTree, HHSList, HHSListSet, HList, HListSet, HMList, HMListSet, HashMap, HashMapCounter,
+    Log, NMTreeMap, SkipList, SkipListSet,
 };
+use smr_benchmark::log_event;
+use smr_benchmark::signals;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -26,19 +35,79 @@ fn main() {
 
 fn bench(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
-        DS::HList => bench_map::<HList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HMList => bench_map::<HMList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HHSList => bench_map::<HHSList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::HashMap => bench_map::<HashMap<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::NMTree => bench_map::<NMTreeMap<usize, usize>>(config, PrefillStrategy::Random),
-        DS::SkipList => bench_map::<SkipList<usize, usize>>(config, PrefillStrategy::Decreasing),
-        DS::BonsaiTree => {
-            bench_map::<BonsaiTreeMap<usize, usize>>(config, PrefillStrategy::Decreasing)
-        }
-        DS::EFRBTree => bench_map::<EFRBTree<usize, usize>>(config, PrefillStrategy::Random),
-        DS::ElimAbTree => bench_map::<ElimABTree<usize, usize>>(config, PrefillStrategy::Random),
-    };
+    let perf = config.measure(|| match config.mode {
+        Mode::Map => match config.ds {
+            DS::HList => {
+                bench_map::<usize, HList<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HMList => {
+                bench_map::<usize, HMList<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HHSList => {
+                bench_map::<usize, HHSList<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::HashMap => {
+                bench_map::<usize, HashMap<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::NMTree => match config.key_type {
+                KeyType::Usize => {
+                    bench_map::<usize, NMTreeMap<usize, usize>>(config, PrefillStrategy::Random)
+                }
+                KeyType::U128 => {
+                    bench_map::<u128, NMTreeMap<u128, usize>>(config, PrefillStrategy::Random)
+                }
+                KeyType::Composite => bench_map::<CompositeKey, NMTreeMap<CompositeKey, usize>>(
+                    config,
+                    PrefillStrategy::Random,
+                ),
+            },
+            DS::SkipList => {
+                bench_map::<usize, SkipList<usize, usize>>(config, PrefillStrategy::Decreasing)
+            }
+            DS::BonsaiTree => {
+                match config.key_type {
+                    KeyType::Usize => bench_map::<usize, BonsaiTreeMap<usize, usize>>(
+                        config,
+                        PrefillStrategy::Decreasing,
+                    ),
+                    KeyType::U128 => bench_map::<u128, BonsaiTreeMap<u128, usize>>(
+                        config,
+                        PrefillStrategy::Decreasing,
+                    ),
+                    KeyType::Composite => bench_map::<
+                        CompositeKey,
+                        BonsaiTreeMap<CompositeKey, usize>,
+                    >(config, PrefillStrategy::Decreasing),
+                }
+            }
+            DS::EFRBTree => {
+                bench_map::<usize, EFRBTree<usize, usize>>(config, PrefillStrategy::Random)
+            }
+            DS::ElimAbTree => {
+                bench_map::<usize, ElimABTree<usize, usize>>(config, PrefillStrategy::Random)
+            }
+            _ => panic!("Unsupported(or unimplemented) data structure for NR"),
+        },
+        Mode::Set => match config.ds {
+            DS::HList => bench_set::<HListSet<usize>>(config),
+            DS::HMList => bench_set::<HMListSet<usize>>(config),
+            DS::HHSList => bench_set::<HHSListSet<usize>>(config),
+            DS::SkipList => bench_set::<SkipListSet<usize>>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the set mode"),
+        },
+        Mode::Move => panic!("Unsupported(or unimplemented) SMR for the move mode"),
+        Mode::Merge => panic!("Unsupported(or unimplemented) SMR for the merge mode"),
+        Mode::Cache => match config.ds {
+            DS::HashMap => bench_cache(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the cache mode"),
+        },
+        Mode::Counter => match config.ds {
+            DS::HashMap => bench_counter::<HashMapCounter<usize>>(config),
+            _ => panic!("Unsupported(or unimplemented) data structure for the counter mode"),
+        },
+        Mode::Log => bench_log(config),
+        Mode::Deque => panic!("Unsupported(or unimplemented) SMR for the deque mode"),
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
 }
@@ -52,55 +121,76 @@ pub enum PrefillStrategy {
 }
 
 impl PrefillStrategy {
-    fn prefill<M: ConcurrentMap<usize, usize> + Send + Sync>(self, config: &Config, map: &M) {
+    fn prefill<K: BenchKey, M: ConcurrentMap<K, usize> + Send + Sync>(
+        self,
+        config: &Config,
+        map: &M,
+    ) {
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                for index in config.shaped_prefill::<K>() {
+                    let key = K::from_index(index);
+                    let value = index;
+                    map.insert(key, value);
+                }
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key;
-                                map.insert(key, value);
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let index = config.prefill_dist.sample(rng);
+                            let key = K::from_index(index);
+                            let value = index;
+                            map.insert(key, value);
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
-                    let value = key;
+                for index in config.decreasing_prefill::<K>() {
+                    let key = K::from_index(index);
+                    let value = index;
                     map.insert(key, value);
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        config.age(|op, index| {
+            let key = K::from_index(index);
+            if op == Op::Insert {
+                map.insert(key, index)
+            } else {
+                map.remove(&key).is_some()
+            }
+        });
     }
 }
 
-fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
+fn bench_map<K: BenchKey, M: ConcurrentMap<K, usize> + Send + Sync>(
     config: &Config,
     strategy: PrefillStrategy,
 ) -> Perf {
     let map = &M::new();
     strategy.prefill(config, map);
+    config.run_hygiene();
+    let curve = &config.alloc_curve();
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
 
     scope(|s| {
@@ -111,18 +201,28 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                 let mut samples = 0usize;
                 let mut acc = 0usize;
                 let mut peak = 0usize;
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
+                if let Some(curve) = curve {
+                    curve.start();
+                }
                 let mut next_sampling = start + config.sampling_period;
-                while start.elapsed() < config.duration {
+                // The run is extended by the pauses with `--signals`.
+                let paused = signals::paused_time();
+                while start.elapsed()
+                    < config.duration + signals::paused_time().saturating_sub(paused)
+                {
                     let now = Instant::now();
-                    if now > next_sampling {
+                    if now > next_sampling && !signals::paused() {
                         let allocated = config.mem_sampler.sample();
                         samples += 1;
 
                         acc += allocated;
                         peak = max(peak, allocated);
+                        if let Some(curve) = curve {
+                            curve.sample();
+                        }
 
                         next_sampling = now + config.sampling_period;
                     }
@@ -134,44 +234,99 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
+                let mut cache = config.stale_cache::<usize>();
+                let mut trace = config.trace_cursor(result.index);
                 let mut rng = &mut rand::thread_rng();
-                barrier.clone().wait();
+                config.start(gate, result, |index| {
+                    map.get(&K::from_index(index));
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
                 let start = Instant::now();
 
-                while start.elapsed() < config.duration {
-                    let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
+                while start.elapsed() < lifetime + result.paused {
+                    let op_start = result.op_start(config, config.timed(critical));
+                    let (op, index) = match trace.as_mut() {
+                        Some(trace) => trace.next_op(),
+                        None => (
+                            config.sample_op(critical, &mut rng),
+                            config.key_dist.sample(rng),
+                        ),
+                    };
+                    let key = K::from_index(index);
+                    conflicts.enter(result.index, index);
+                    match op {
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(key, index);
+                        }
                         Op::Get => {
-                            map.get(&key);
+                            if let Some(cache) = cache.as_mut() {
+                                let now = Instant::now();
+                                if cache.get(index, now).is_none() {
+                                    let value = map.get(&key).map(|v| *v.output());
+                                    cache.put(index, value, now);
+                                }
+                            } else {
+                                shadow.run(op, index, || map.get(&key).map(|v| *v.output()));
+                            }
                         }
                         Op::Insert => {
-                            let value = key;
-                            map.insert(key, value);
+                            let value = index;
+                            shadow.run(op, index, || map.insert(key, value).then_some(value));
+                            if let Some(cache) = cache.as_mut() {
+                                cache.invalidate(index);
+                            }
+                        }
+                        Op::Remove if config.extract_min => {
+                            map.extract_min();
                         }
                         Op::Remove => {
-                            map.remove(&key);
+                            shadow.run(op, index, || map.remove(&key).map(|v| *v.output()));
+                            if let Some(cache) = cache.as_mut() {
+                                cache.invalidate(index);
+                            }
                         }
                     }
+                    conflicts.exit(result.index);
                     ops += 1;
+                    result.record_op(config, critical, Some(op), op_start);
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
+                    if config.paused(result, ops) {
+                        config.wait_while_paused(result);
+                    }
                 }
 
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
+                result.hits = cache.map_or(0, |cache| cache.hits());
             });
         }
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene();
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
+    if config.stale_ttl.is_some() {
+        println!("gets served from the stale cache: {}", results.hits());
+    }
+    if let Some(curve) = curve {
+        config.record_alloc_curve(curve);
     }
-    let ops_per_sec = ops / config.interval;
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
@@ -181,3 +336,175 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
         avg_garb,
     }
 }
+
+fn prefill_set<S: ConcurrentSet<usize> + Send + Sync>(config: &Config, set: &S) {
+    // Lists and skip lists are prefilled with a single thread in a decreasing order.
+    for key in config.decreasing_prefill::<usize>() {
+        set.insert(key);
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+}
+
+fn bench_set<S: ConcurrentSet<usize> + Send + Sync>(config: &Config) -> Perf {
+    let set = &S::new();
+    prefill_set(config, set);
+    config.run_hygiene();
+
+    let (results, perf) = harness::run(config, &NoReclamation, |_| {
+        move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    set.contains(&key);
+                }
+                Op::Insert => {
+                    set.insert(key);
+                }
+                Op::Remove => {
+                    set.remove(&key);
+                }
+            }
+            true
+        }
+    });
+    config.run_hygiene();
+
+    results.print_sockets();
+    perf
+}
+
+fn prefill_counter<C: ConcurrentCounter<usize> + Send + Sync>(config: &Config, counter: &C) {
+    // Each prefilled key has a count of one.
+    for key in config.decreasing_prefill::<usize>() {
+        counter.increment(key);
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+}
+
+/// Benchmarks a map from keys to atomic counters as a multiset. A get reads the count of its
+/// key, an insert increments it, and a remove decrements it without removing the key.
+fn bench_counter<C: ConcurrentCounter<usize> + Send + Sync>(config: &Config) -> Perf {
+    let counter = &C::new();
+    prefill_counter(config, counter);
+    config.run_hygiene();
+
+    let (results, perf) = harness::run(config, &NoReclamation, |_| {
+        move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    counter.count(&key);
+                }
+                Op::Insert => {
+                    counter.increment(key);
+                }
+                Op::Remove => {
+                    counter.decrement(&key);
+                }
+            }
+            true
+        }
+    });
+    config.run_hygiene();
+
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks a hash map of bounded size as a read-through cache. A get that misses inserts its
+/// key, and an insert into the full map evicts another key.
+fn bench_cache(config: &Config) -> Perf {
+    let cache = &ClockCache::with_capacity(config.cache_capacity);
+    for key in config.decreasing_prefill::<usize>() {
+        cache.insert(key, key);
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+    stdout().flush().unwrap();
+    config.run_hygiene();
+
+    let (results, perf) = harness::run(config, &NoReclamation, |_| {
+        move |rng: &mut ThreadRng, result: &mut ThreadResult| {
+            let key = config.key_dist.sample(rng);
+            match Op::OPS[config.op_dist.sample(rng)] {
+                Op::Get => {
+                    result.gets += 1;
+                    if cache.get(&key).is_some() {
+                        result.hits += 1;
+                    } else {
+                        cache.insert(key, key);
+                    }
+                }
+                Op::Insert => {
+                    cache.insert(key, key);
+                }
+                Op::Remove => {
+                    cache.remove(&key);
+                }
+            }
+            true
+        }
+    });
+    config.run_hygiene();
+
+    results.print_hit_rate();
+    results.print_sockets();
+    perf
+}
+
+/// Benchmarks an append log that an extra thread truncates, as a write-ahead log is once a
+/// checkpoint lets it drop its old segments. A get reads a random entry that is not truncated, and
+/// an insert or a remove appends an entry.
+fn bench_log(config: &Config) -> Perf {
+    let log = &Log::new();
+    for key in 0..config.log_retain {
+        log.append(key);
+    }
+    print!("prefilled... ");
+    log_event!(Info, Prefill, "prefilled", entries = config.log_retain);
+    stdout().flush().unwrap();
+    config.run_hygiene();
+
+    let truncate = || {
+        move || {
+            let retain = config.log_retain as u64;
+            let mut truncations = 0u64;
+            let mut truncated = 0u64;
+            let start = Instant::now();
+            while start.elapsed() < config.duration {
+                let last = log.last();
+                if (last + 1).saturating_sub(log.first()) >= 2 * retain {
+                    truncated += log.truncate(last - retain) as u64;
+                    truncations += 1;
+                } else {
+                    std::hint::spin_loop();
+                }
+            }
+            (truncations, truncated)
+        }
+    };
+    let (results, perf, (truncations, truncated)) =
+        harness::run_with_side(config, &NoReclamation, truncate, |_| {
+            move |rng: &mut ThreadRng, _: &mut ThreadResult| {
+                match Op::OPS[config.op_dist.sample(rng)] {
+                    Op::Get => {
+                        let index = rng.gen_range(log.first()..=log.last());
+                        log.get(index);
+                    }
+                    Op::Insert | Op::Remove => {
+                        log.append(config.key_dist.sample(rng));
+                    }
+                }
+                true
+            }
+        });
+    config.run_hygiene();
+
+    println!("log truncations: {truncations}, truncated entries: {truncated}");
+    results.print_sockets();
+    perf
+}
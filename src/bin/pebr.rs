@@ -5,16 +5,22 @@ use std::io::{stdout, Write};
 use std::mem::ManuallyDrop;
 use std::path::Path;
 use std::sync::atomic::Ordering;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 use typenum::{Unsigned, U1, U4};
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, OpsPerCs, Perf, DS};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchWriter, Config, Op, OpsPerCs, Perf, ThreadResults, TreeShape, DS,
+};
+use smr_benchmark::drain_pebr;
+use smr_benchmark::ds_impl::pebr::concurrent_map::OutputHolder;
 use smr_benchmark::ds_impl::pebr::{
     BonsaiTreeMap, ConcurrentMap, EFRBTree, ElimABTree, HHSList, HList, HMList, HashMap, NMTreeMap,
     SkipList,
 };
+use smr_benchmark::log_event;
+use smr_benchmark::start::StartGate;
 
 fn main() {
     let (config, output) = setup(
@@ -32,7 +38,7 @@ fn main() {
 
 fn bench<N: Unsigned>(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
+    let perf = config.measure(|| match config.ds {
         DS::HList => bench_map::<HList<usize, usize>, N>(config, PrefillStrategy::Decreasing),
         DS::HMList => bench_map::<HMList<usize, usize>, N>(config, PrefillStrategy::Decreasing),
         DS::HHSList => bench_map::<HHSList<usize, usize>, N>(config, PrefillStrategy::Decreasing),
@@ -45,7 +51,8 @@ fn bench<N: Unsigned>(config: &Config, output: BenchWriter) {
         DS::EFRBTree => bench_map::<EFRBTree<usize, usize>, N>(config, PrefillStrategy::Random),
         DS::SkipList => bench_map::<SkipList<usize, usize>, N>(config, PrefillStrategy::Decreasing),
         DS::ElimAbTree => bench_map::<ElimABTree<usize, usize>, N>(config, PrefillStrategy::Random),
-    };
+        _ => panic!("Unsupported(or unimplemented) data structure for PEBR"),
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
 }
@@ -64,47 +71,62 @@ impl PrefillStrategy {
         // functionality even during insertions.
         let collector = &crossbeam_pebr::Collector::new();
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                let handle = collector.register();
+                let guard = &mut handle.pin();
+                let mut handle = M::handle(guard);
+                for key in config.shaped_prefill::<usize>() {
+                    let value = key.clone();
+                    map.insert(&mut handle, key, value, guard);
+                }
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            let handle = collector.register();
-                            let guard = &mut handle.pin();
-                            let mut handle = M::handle(guard);
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key.clone();
-                                map.insert(&mut handle, key, value, guard);
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        let handle = collector.register();
+                        let guard = &mut handle.pin();
+                        let mut handle = M::handle(guard);
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let key = config.prefill_dist.sample(rng);
+                            let value = key.clone();
+                            map.insert(&mut handle, key, value, guard);
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
                 let handle = collector.register();
                 let guard = &mut handle.pin();
                 let mut handle = M::handle(guard);
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
+                for key in config.decreasing_prefill::<usize>() {
                     let value = key.clone();
                     map.insert(&mut handle, key, value, guard);
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        let handle = collector.register();
+        let mut map_handle = M::handle(&handle.pin());
+        config.age(|op, key| {
+            let guard = &mut handle.pin();
+            if op == Op::Insert {
+                map.insert(&mut map_handle, key, key, guard)
+            } else {
+                map.remove(&mut map_handle, &key, guard).is_some()
+            }
+        });
     }
 }
 
@@ -119,11 +141,14 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
     }
     let map = &M::new();
     strategy.prefill(config, map);
+    config.run_hygiene();
 
     let collector = &crossbeam_pebr::Collector::new();
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
 
     scope(|s| {
@@ -137,7 +162,7 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
                 let mut garb_acc = 0usize;
                 let mut garb_peak = 0usize;
                 let handle = collector.register();
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
                 // Immediately drop if no non-coop else keep it and repin periodically.
@@ -185,51 +210,88 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync, N: Unsigned>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
                 let mut rng = &mut rand::thread_rng();
-                let handle = collector.register();
-                let mut map_handle = M::handle(&handle.pin());
-                barrier.clone().wait();
+                let register = || {
+                    let handle = collector.register();
+                    let map_handle = M::handle(&handle.pin());
+                    (handle, map_handle)
+                };
+                let mut registration = config.registers_early().then(register);
+                config.start(gate, result, |key| {
+                    if let Some((handle, map_handle)) = registration.as_mut() {
+                        map.get(map_handle, &key, &mut handle.pin());
+                    }
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
+                let (handle, mut map_handle) = registration.unwrap_or_else(register);
                 let start = Instant::now();
 
                 let mut guard = handle.pin();
-                while start.elapsed() < config.duration {
+                while start.elapsed() < lifetime {
+                    let op_start = result.op_start(config, config.timed(critical));
                     let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
+                    conflicts.enter(result.index, key);
+                    let op = config.sample_op(critical, &mut rng);
+                    match op {
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(&mut map_handle, key, key, &mut guard);
+                        }
                         Op::Get => {
-                            map.get(&mut map_handle, &key, &mut guard);
+                            shadow.run(Op::Get, key, || {
+                                map.get(&mut map_handle, &key, &mut guard)
+                                    .map(|v| *v.output())
+                            });
                         }
                         Op::Insert => {
                             let value = key.clone();
-                            map.insert(&mut map_handle, key, value, &mut guard);
+                            shadow.run(Op::Insert, key, || {
+                                map.insert(&mut map_handle, key, value, &mut guard)
+                                    .then_some(value)
+                            });
                         }
                         Op::Remove => {
-                            map.remove(&mut map_handle, &key, &mut guard);
+                            shadow.run(Op::Remove, key, || {
+                                map.remove(&mut map_handle, &key, &mut guard)
+                                    .map(|v| *v.output())
+                            });
                         }
                     }
+                    conflicts.exit(result.index);
                     ops += 1;
+                    result.record_op(config, critical, Some(op), op_start);
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
                     if ops % N::to_u64() == 0 {
                         M::clear(&mut map_handle);
                         guard.repin();
                     }
                 }
 
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
             });
         }
     })
     .unwrap();
     println!("end");
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.run_hygiene_with(|| drain_pebr(collector));
 
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
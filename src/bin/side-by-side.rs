@@ -0,0 +1,509 @@
+extern crate clap;
+extern crate csv;
+
+extern crate crossbeam_ebr;
+extern crate smr_benchmark;
+
+use clap::{value_parser, Arg, ArgMatches, Command, ValueEnum};
+use crossbeam_utils::thread::scope;
+use rand::distributions::{Uniform, WeightedIndex};
+use rand::prelude::*;
+use std::cmp::max;
+use std::io::{stdout, Write};
+use std::sync::{mpsc, Arc, Barrier};
+use std::time::{Duration, Instant};
+
+use smr_benchmark::config::map::Op;
+use smr_benchmark::ds_impl::{ebr, hp, nr};
+use smr_benchmark::results::ResultsFile;
+use smr_benchmark::MemSampler;
+
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum MM {
+    NR,
+    EBR,
+    HP,
+}
+
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum DS {
+    HMList,
+    HashMap,
+    NMTree,
+    SkipList,
+}
+
+struct Config {
+    ds: DS,
+    mms: [MM; 2],
+    threads: usize,
+    get_rate: u8,
+    range: usize,
+    aux_thread: usize,
+    aux_thread_period: Duration,
+    sampling: bool,
+    interval: u64,
+    duration: Duration,
+    mem_sampler: MemSampler,
+    prefill: usize,
+    key_dist: Uniform<usize>,
+    op_dist: WeightedIndex<i32>,
+}
+
+/// The throughput of each instance and the memory usage of the process during a run.
+struct Run {
+    ops_per_sec: Vec<u64>,
+    peak_mem: usize,
+    avg_mem: usize,
+}
+
+fn main() {
+    let matches = Command::new("smr_benchmark")
+        .arg(
+            Arg::new("memory managers")
+                .short('m')
+                .value_parser(value_parser!(MM))
+                .num_args(2)
+                .required(true)
+                .ignore_case(true)
+                .help("The memory managers of the two instances, e.g., `-m ebr hp`"),
+        )
+        .arg(
+            Arg::new("data structure")
+                .short('d')
+                .value_parser(value_parser!(DS))
+                .required(true)
+                .ignore_case(true)
+                .help("Data structure of both instances"),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .value_parser(value_parser!(usize))
+                .required(true)
+                .help("Numbers of threads which perform operations on each instance."),
+        )
+        .arg(
+            Arg::new("get rate")
+                .short('g')
+                .value_parser(value_parser!(u8).range(0..4))
+                .help(
+                    "The proportion of `get`(read) operations. \
+                     0: 0%, 1: 50%, 2: 90%, 3: 100%",
+                )
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("range")
+                .short('r')
+                .value_parser(value_parser!(usize))
+                .help("Key range of each instance: [0..RANGE]")
+                .default_value("100000"),
+        )
+        .arg(
+            Arg::new("interval")
+                .short('i')
+                .value_parser(value_parser!(u64))
+                .help("Time interval in seconds to run each phase of the benchmark")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .help("Output CSV filename. Appends the data if the file already exists."),
+        )
+        .get_matches();
+
+    let (config, output) = setup(matches);
+    bench(&config, output.as_ref());
+}
+
+fn setup(m: ArgMatches) -> (Config, Option<ResultsFile>) {
+    let mms = m.get_many::<MM>("memory managers").unwrap();
+    let mms: [MM; 2] = mms.copied().collect::<Vec<_>>().try_into().unwrap();
+    let ds = m.get_one::<DS>("data structure").copied().unwrap();
+    let threads = m.get_one::<usize>("threads").copied().unwrap();
+    let get_rate = m.get_one::<u8>("get rate").copied().unwrap();
+    let range = m.get_one::<usize>("range").copied().unwrap();
+    let interval = m.get_one::<u64>("interval").copied().unwrap();
+    let sampling = cfg!(all(not(feature = "sanitize"), target_os = "linux"));
+    let duration = Duration::from_secs(interval);
+
+    assert!(
+        threads >= 1,
+        "The number of threads must be greater than zero!"
+    );
+    assert!(range >= 2, "The key range must be at least two!");
+
+    let op_weights = match get_rate {
+        0 => &[0, 1, 1],
+        1 => &[2, 1, 1],
+        2 => &[18, 1, 1],
+        _ => &[1, 0, 0],
+    };
+
+    let output = m.get_one::<String>("output").map(|output_name| {
+        // NOTE: `append` on `bench`
+        ResultsFile::open(
+            output_name,
+            &[
+                "ds",
+                "mm_a",
+                "mm_b",
+                "threads",
+                "get_rate",
+                "key_range",
+                "solo_a",
+                "solo_b",
+                "together_a",
+                "together_b",
+                "interference_a",
+                "interference_b",
+                "peak_mem",
+                "avg_mem",
+                "interval",
+            ],
+        )
+    });
+    let config = Config {
+        ds,
+        mms,
+        threads,
+        get_rate,
+        range,
+        aux_thread: if sampling { 1 } else { 0 },
+        aux_thread_period: Duration::from_millis(1),
+        sampling,
+        interval,
+        duration,
+        mem_sampler: MemSampler::new(),
+        prefill: range / 2,
+        key_dist: Uniform::from(0..range),
+        op_dist: WeightedIndex::new(op_weights).unwrap(),
+    };
+    (config, output)
+}
+
+fn name<T: ValueEnum>(value: &T) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+/// The drop of the throughput of an instance next to the other, relative to its throughput alone.
+fn interference(solo: u64, together: u64) -> f64 {
+    if solo == 0 {
+        return 0.0;
+    }
+    1.0 - together as f64 / solo as f64
+}
+
+fn bench(config: &Config, output: Option<&ResultsFile>) {
+    let [a, b] = config.mms;
+    println!(
+        "{} next to {}: {}, {} threads each",
+        name(&a),
+        name(&b),
+        name(&config.ds),
+        config.threads,
+    );
+
+    // Each instance alone on its own pool, and then both at once on disjoint pools. Every run
+    // starts from fresh instances, so that the garbage of a run does not count towards the next.
+    let solo_a = run(config, &[instance(a, config.ds)]).ops_per_sec[0];
+    let solo_b = run(config, &[instance(b, config.ds)]).ops_per_sec[0];
+    let together = run(config, &[instance(a, config.ds), instance(b, config.ds)]);
+    let (together_a, together_b) = (together.ops_per_sec[0], together.ops_per_sec[1]);
+    let interference_a = interference(solo_a, together_a);
+    let interference_b = interference(solo_b, together_b);
+
+    if let Some(output) = output {
+        output.append([
+            name(&config.ds),
+            name(&a),
+            name(&b),
+            config.threads.to_string(),
+            config.get_rate.to_string(),
+            config.range.to_string(),
+            solo_a.to_string(),
+            solo_b.to_string(),
+            together_a.to_string(),
+            together_b.to_string(),
+            format!("{interference_a:.4}"),
+            format!("{interference_b:.4}"),
+            together.peak_mem.to_string(),
+            together.avg_mem.to_string(),
+            config.interval.to_string(),
+        ]);
+    }
+    println!(
+        "{}: solo ops/s: {}, together ops/s: {}, interference: {:.2}%",
+        name(&a),
+        solo_a,
+        together_a,
+        interference_a * 100.0
+    );
+    println!(
+        "{}: solo ops/s: {}, together ops/s: {}, interference: {:.2}%",
+        name(&b),
+        solo_b,
+        together_b,
+        interference_b * 100.0
+    );
+    println!(
+        "together: peak mem: {}, avg_mem: {}",
+        together.peak_mem, together.avg_mem
+    );
+}
+
+impl Config {
+    fn next_op(&self, rng: &mut ThreadRng) -> (Op, usize) {
+        (Op::OPS[self.op_dist.sample(rng)], self.key_dist.sample(rng))
+    }
+}
+
+/// A map on one of the schemes, which runs the workload of the threads of its pool.
+trait Instance: Sync {
+    /// Inserts the initial keys from a single thread.
+    fn prefill(&self, config: &Config);
+
+    /// Runs the workload on a thread of the pool of the instance, from the release of `barrier`
+    /// for the duration of the run, and returns its number of operations.
+    fn work(&self, config: &Config, barrier: &Barrier) -> u64;
+}
+
+struct Nr<M>(M);
+
+impl<M: nr::ConcurrentMap<usize, usize>> Nr<M> {
+    fn new() -> Self {
+        Self(M::new())
+    }
+}
+
+impl<M: nr::ConcurrentMap<usize, usize> + Send + Sync> Instance for Nr<M> {
+    fn prefill(&self, config: &Config) {
+        let rng = &mut rand::thread_rng();
+        let mut keys = 0;
+        while keys < config.prefill {
+            if self.0.insert(config.key_dist.sample(rng), 0) {
+                keys += 1;
+            }
+        }
+    }
+
+    fn work(&self, config: &Config, barrier: &Barrier) -> u64 {
+        let mut ops: u64 = 0;
+        let rng = &mut rand::thread_rng();
+        barrier.wait();
+        let start = Instant::now();
+
+        while start.elapsed() < config.duration {
+            let (op, key) = config.next_op(rng);
+            match op {
+                Op::Get => {
+                    self.0.get(&key);
+                }
+                Op::Insert => {
+                    self.0.insert(key, key);
+                }
+                Op::Remove => {
+                    self.0.remove(&key);
+                }
+            }
+            ops += 1;
+        }
+        ops
+    }
+}
+
+struct Ebr<M> {
+    map: M,
+    collector: crossbeam_ebr::Collector,
+}
+
+impl<M: ebr::ConcurrentMap<usize, usize>> Ebr<M> {
+    fn new() -> Self {
+        Self {
+            map: M::new(),
+            collector: crossbeam_ebr::Collector::new(),
+        }
+    }
+}
+
+impl<M: ebr::ConcurrentMap<usize, usize> + Send + Sync> Instance for Ebr<M> {
+    fn prefill(&self, config: &Config) {
+        let handle = self.collector.register();
+        let rng = &mut rand::thread_rng();
+        let mut keys = 0;
+        while keys < config.prefill {
+            let guard = handle.pin();
+            if self.map.insert(config.key_dist.sample(rng), 0, &guard) {
+                keys += 1;
+            }
+        }
+    }
+
+    fn work(&self, config: &Config, barrier: &Barrier) -> u64 {
+        let mut ops: u64 = 0;
+        let rng = &mut rand::thread_rng();
+        let handle = self.collector.register();
+        barrier.wait();
+        let start = Instant::now();
+
+        let mut guard = handle.pin();
+        while start.elapsed() < config.duration {
+            let (op, key) = config.next_op(rng);
+            match op {
+                Op::Get => {
+                    self.map.get(&key, &guard);
+                }
+                Op::Insert => {
+                    self.map.insert(key, key, &guard);
+                }
+                Op::Remove => {
+                    self.map.remove(&key, &guard);
+                }
+            }
+            ops += 1;
+            drop(guard);
+            guard = handle.pin();
+        }
+        ops
+    }
+}
+
+struct Hp<M>(M);
+
+impl<M: hp::ConcurrentMap<usize, usize>> Hp<M> {
+    fn new() -> Self {
+        Self(M::new())
+    }
+}
+
+impl<M: hp::ConcurrentMap<usize, usize> + Send + Sync> Instance for Hp<M> {
+    fn prefill(&self, config: &Config) {
+        let mut handle = M::handle();
+        let rng = &mut rand::thread_rng();
+        let mut keys = 0;
+        while keys < config.prefill {
+            if self.0.insert(&mut handle, config.key_dist.sample(rng), 0) {
+                keys += 1;
+            }
+        }
+    }
+
+    fn work(&self, config: &Config, barrier: &Barrier) -> u64 {
+        let mut ops: u64 = 0;
+        let rng = &mut rand::thread_rng();
+        let mut handle = M::handle();
+        barrier.wait();
+        let start = Instant::now();
+
+        while start.elapsed() < config.duration {
+            let (op, key) = config.next_op(rng);
+            match op {
+                Op::Get => {
+                    self.0.get(&mut handle, &key);
+                }
+                Op::Insert => {
+                    self.0.insert(&mut handle, key, key);
+                }
+                Op::Remove => {
+                    self.0.remove(&mut handle, &key);
+                }
+            }
+            ops += 1;
+        }
+        ops
+    }
+}
+
+fn instance(mm: MM, ds: DS) -> Box<dyn Instance> {
+    match (mm, ds) {
+        (MM::NR, DS::HMList) => Box::new(Nr::<nr::HMList<usize, usize>>::new()),
+        (MM::NR, DS::HashMap) => Box::new(Nr::<nr::HashMap<usize, usize>>::new()),
+        (MM::NR, DS::NMTree) => Box::new(Nr::<nr::NMTreeMap<usize, usize>>::new()),
+        (MM::NR, DS::SkipList) => Box::new(Nr::<nr::SkipList<usize, usize>>::new()),
+        (MM::EBR, DS::HMList) => Box::new(Ebr::<ebr::HMList<usize, usize>>::new()),
+        (MM::EBR, DS::HashMap) => Box::new(Ebr::<ebr::HashMap<usize, usize>>::new()),
+        (MM::EBR, DS::NMTree) => Box::new(Ebr::<ebr::NMTreeMap<usize, usize>>::new()),
+        (MM::EBR, DS::SkipList) => Box::new(Ebr::<ebr::SkipList<usize, usize>>::new()),
+        (MM::HP, DS::HMList) => Box::new(Hp::<hp::HMList<usize, usize>>::new()),
+        (MM::HP, DS::HashMap) => Box::new(Hp::<hp::HashMap<usize, usize>>::new()),
+        (MM::HP, DS::NMTree) => Box::new(Hp::<hp::NMTreeMap<usize, usize>>::new()),
+        (MM::HP, DS::SkipList) => Box::new(Hp::<hp::SkipList<usize, usize>>::new()),
+    }
+}
+
+/// Prefills the instances, and runs each of them on a pool of its own, all at once.
+fn run(config: &Config, instances: &[Box<dyn Instance>]) -> Run {
+    for instance in instances {
+        instance.prefill(config);
+    }
+    print!("prefilled... ");
+    stdout().flush().unwrap();
+
+    let threads = instances.len() * config.threads;
+    let barrier = &Arc::new(Barrier::new(threads + config.aux_thread));
+    let (ops_sender, ops_receiver) = mpsc::channel();
+    let (mem_sender, mem_receiver) = mpsc::channel();
+
+    scope(|s| {
+        // sampling & interference thread
+        if config.aux_thread > 0 {
+            let mem_sender = mem_sender.clone();
+            s.spawn(move |_| {
+                let mut samples = 0usize;
+                let mut acc = 0usize;
+                let mut peak = 0usize;
+                barrier.clone().wait();
+
+                let start = Instant::now();
+                let mut next_sampling = start + Duration::from_millis(1);
+                while start.elapsed() < config.duration {
+                    let now = Instant::now();
+                    if now > next_sampling {
+                        let allocated = config.mem_sampler.sample();
+                        samples += 1;
+
+                        acc += allocated;
+                        peak = max(peak, allocated);
+
+                        next_sampling = now + Duration::from_millis(1);
+                    }
+                    std::thread::sleep(config.aux_thread_period);
+                }
+
+                if config.sampling {
+                    mem_sender.send((peak, acc / samples)).unwrap();
+                } else {
+                    mem_sender.send((0, 0)).unwrap();
+                }
+            });
+        } else {
+            mem_sender.send((0, 0)).unwrap();
+        }
+
+        for (index, instance) in instances.iter().enumerate() {
+            for _ in 0..config.threads {
+                let ops_sender = ops_sender.clone();
+                s.spawn(move |_| {
+                    let ops = instance.work(config, barrier);
+                    ops_sender.send((index, ops)).unwrap();
+                });
+            }
+        }
+    })
+    .unwrap();
+    println!("end");
+
+    let mut ops = vec![0; instances.len()];
+    for _ in 0..threads {
+        let (index, local_ops) = ops_receiver.recv().unwrap();
+        ops[index] += local_ops;
+    }
+    let (peak_mem, avg_mem) = mem_receiver.recv().unwrap();
+    Run {
+        ops_per_sec: ops.into_iter().map(|ops| ops / config.interval).collect(),
+        peak_mem,
+        avg_mem,
+    }
+}
@@ -0,0 +1,363 @@
+extern crate clap;
+extern crate csv;
+
+extern crate crossbeam_ebr;
+extern crate smr_benchmark;
+
+use circ::Cs;
+use clap::{value_parser, Arg, ArgMatches, Command, ValueEnum};
+use crossbeam_utils::thread::{scope, Scope};
+use rand::distributions::Uniform;
+use rand::prelude::*;
+use std::cmp::max;
+use std::sync::atomic::{compiler_fence, Ordering};
+use std::sync::{mpsc, Arc, Barrier};
+use std::time::{Duration, Instant};
+
+use smr_benchmark::ds_impl::{circ_hp, ebr, hp, hp_pp};
+use smr_benchmark::results::ResultsFile;
+use smr_benchmark::MemSampler;
+
+#[derive(PartialEq, Debug, ValueEnum, Clone)]
+#[allow(non_camel_case_types)]
+pub enum MM {
+    EBR,
+    HP,
+    HP_PP,
+    CIRC_HP,
+}
+
+#[derive(PartialEq, Debug, ValueEnum, Clone)]
+pub enum DS {
+    Treiber,
+    Elimination,
+}
+
+struct Config {
+    mm: MM,
+    ds: DS,
+    threads: usize,
+    aux_thread: usize,
+    aux_thread_period: Duration,
+    sampling: bool,
+    interval: u64,
+    duration: Duration,
+    mem_sampler: MemSampler,
+    key_dist: Uniform<usize>,
+}
+
+fn main() {
+    let matches = Command::new("smr_benchmark")
+        .arg(
+            Arg::new("memory manager")
+                .short('m')
+                .value_parser(value_parser!(MM))
+                .required(true)
+                .ignore_case(true)
+                .help("Memeory manager(s)"),
+        )
+        .arg(
+            Arg::new("data structure")
+                .short('d')
+                .value_parser(value_parser!(DS))
+                .ignore_case(true)
+                .help("Stack, which backs off to an elimination array for `elimination`")
+                .default_value("treiber"),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .value_parser(value_parser!(usize))
+                .required(true)
+                .help("Numbers of threads which perform push and pop."),
+        )
+        .arg(
+            Arg::new("interval")
+                .short('i')
+                .value_parser(value_parser!(u64))
+                .help("Time interval in seconds to run the benchmark")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .help("Output CSV filename. Appends the data if the file already exists."),
+        )
+        .get_matches();
+
+    let (config, output) = setup(matches);
+    bench(&config, output.as_ref());
+}
+
+fn setup(m: ArgMatches) -> (Config, Option<ResultsFile>) {
+    let mm = m.get_one::<MM>("memory manager").cloned().unwrap();
+    let ds = m.get_one::<DS>("data structure").cloned().unwrap();
+    let threads = m.get_one::<usize>("threads").copied().unwrap();
+    let interval = m.get_one::<u64>("interval").copied().unwrap();
+    let sampling = cfg!(all(not(feature = "sanitize"), target_os = "linux"));
+    let duration = Duration::from_secs(interval);
+
+    assert!(
+        threads >= 1,
+        "The number of threads must be greater than zero!"
+    );
+
+    let output = m.get_one::<String>("output").map(|output_name| {
+        // NOTE: `append` on `bench`
+        ResultsFile::open(
+            output_name,
+            &[
+                "ds",
+                "mm",
+                "threads",
+                "throughput",
+                "peak_mem",
+                "avg_mem",
+                "interval",
+            ],
+        )
+    });
+    let mem_sampler = MemSampler::new();
+    let config = Config {
+        mm,
+        ds,
+        threads,
+        aux_thread: if sampling { 1 } else { 0 },
+        aux_thread_period: Duration::from_millis(1),
+        sampling,
+        interval,
+        duration,
+        mem_sampler,
+        key_dist: Uniform::from(0..100000),
+    };
+    (config, output)
+}
+
+fn bench(config: &Config, output: Option<&ResultsFile>) {
+    println!(
+        "{} {}: {} threads",
+        config.ds.to_possible_value().unwrap().get_name(),
+        config.mm.to_possible_value().unwrap().get_name(),
+        config.threads,
+    );
+    let (ops_per_sec, peak_mem, avg_mem) = match (&config.mm, &config.ds) {
+        (MM::EBR, DS::Treiber) => bench_stack_ebr::<ebr::TreiberStack<String>>(config),
+        (MM::EBR, DS::Elimination) => bench_stack_ebr::<ebr::EliminationStack<String>>(config),
+        (MM::HP, DS::Treiber) => bench_stack_hp::<hp::TreiberStack<String>>(config),
+        (MM::HP, DS::Elimination) => bench_stack_hp::<hp::EliminationStack<String>>(config),
+        (MM::HP_PP, DS::Treiber) => bench_stack_hp::<hp_pp::TreiberStack<String>>(config),
+        (MM::HP_PP, DS::Elimination) => bench_stack_hp::<hp_pp::EliminationStack<String>>(config),
+        (MM::CIRC_HP, DS::Treiber) => bench_stack_circ_hp::<circ_hp::TreiberStack<String>>(config),
+        (MM::CIRC_HP, DS::Elimination) => {
+            bench_stack_circ_hp::<circ_hp::EliminationStack<String>>(config)
+        }
+    };
+    if let Some(output) = output {
+        output.append([
+            config
+                .ds
+                .to_possible_value()
+                .unwrap()
+                .get_name()
+                .to_string(),
+            config
+                .mm
+                .to_possible_value()
+                .unwrap()
+                .get_name()
+                .to_string(),
+            config.threads.to_string(),
+            ops_per_sec.to_string(),
+            peak_mem.to_string(),
+            avg_mem.to_string(),
+            config.interval.to_string(),
+        ]);
+    }
+    println!(
+        "ops/s: {}, peak mem: {}, avg_mem: {}",
+        ops_per_sec, peak_mem, avg_mem
+    );
+}
+
+/// Spawns the thread that samples the memory usage during the run, if sampling is enabled, and
+/// sends the peak and the average usage to `mem_sender` at the end.
+fn spawn_sampler<'s>(
+    s: &Scope<'s>,
+    config: &'s Config,
+    barrier: &'s Arc<Barrier>,
+    mem_sender: mpsc::Sender<(usize, usize)>,
+) {
+    if config.aux_thread == 0 {
+        mem_sender.send((0, 0)).unwrap();
+        return;
+    }
+    s.spawn(move |_| {
+        let mut samples = 0usize;
+        let mut acc = 0usize;
+        let mut peak = 0usize;
+        barrier.clone().wait();
+
+        let start = Instant::now();
+        let mut next_sampling = start + Duration::from_millis(1);
+        while start.elapsed() < config.duration {
+            let now = Instant::now();
+            if now > next_sampling {
+                let allocated = config.mem_sampler.sample();
+                samples += 1;
+
+                acc += allocated;
+                peak = max(peak, allocated);
+
+                next_sampling = now + Duration::from_millis(1);
+            }
+            std::thread::sleep(config.aux_thread_period);
+        }
+
+        if config.sampling {
+            mem_sender.send((peak, acc / samples)).unwrap();
+        } else {
+            mem_sender.send((0, 0)).unwrap();
+        }
+    });
+}
+
+fn collect(
+    config: &Config,
+    ops_receiver: mpsc::Receiver<u64>,
+    mem_receiver: mpsc::Receiver<(usize, usize)>,
+) -> (u64, usize, usize) {
+    let mut ops = 0;
+    for _ in 0..config.threads {
+        let local_ops = ops_receiver.recv().unwrap();
+        ops += local_ops;
+    }
+    let ops_per_sec = ops / config.interval;
+    let (peak_mem, avg_mem) = mem_receiver.recv().unwrap();
+    (ops_per_sec, peak_mem, avg_mem)
+}
+
+fn bench_stack_ebr<S: ebr::ConcurrentStack<String> + Send + Sync>(
+    config: &Config,
+) -> (u64, usize, usize) {
+    let stack = &S::new();
+    let collector = &crossbeam_ebr::Collector::new();
+
+    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
+    let (ops_sender, ops_receiver) = mpsc::channel();
+    let (mem_sender, mem_receiver) = mpsc::channel();
+
+    scope(|s| {
+        spawn_sampler(s, config, barrier, mem_sender);
+
+        for _ in 0..config.threads {
+            let ops_sender = ops_sender.clone();
+            s.spawn(move |_| {
+                let mut ops: u64 = 0;
+                let rng = &mut rand::thread_rng();
+                let handle = collector.register();
+                barrier.clone().wait();
+                let start = Instant::now();
+
+                let mut guard = handle.pin();
+                while start.elapsed() < config.duration {
+                    let item = config.key_dist.sample(rng).to_string();
+                    stack.push(item, &guard);
+                    compiler_fence(Ordering::SeqCst);
+                    stack.pop(&guard).unwrap();
+                    compiler_fence(Ordering::SeqCst);
+
+                    ops += 1;
+                    drop(guard);
+                    guard = handle.pin();
+                }
+                ops_sender.send(ops).unwrap();
+            });
+        }
+    })
+    .unwrap();
+    println!("end");
+
+    collect(config, ops_receiver, mem_receiver)
+}
+
+fn bench_stack_hp<S: hp::ConcurrentStack<String> + Send + Sync>(
+    config: &Config,
+) -> (u64, usize, usize) {
+    let stack = &S::new();
+
+    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
+    let (ops_sender, ops_receiver) = mpsc::channel();
+    let (mem_sender, mem_receiver) = mpsc::channel();
+
+    scope(|s| {
+        spawn_sampler(s, config, barrier, mem_sender);
+
+        for _ in 0..config.threads {
+            let ops_sender = ops_sender.clone();
+            s.spawn(move |_| {
+                let mut ops: u64 = 0;
+                let rng = &mut rand::thread_rng();
+                let mut handle = S::handle();
+                barrier.clone().wait();
+                let start = Instant::now();
+
+                while start.elapsed() < config.duration {
+                    let item = config.key_dist.sample(rng).to_string();
+                    stack.push(&mut handle, item);
+                    compiler_fence(Ordering::SeqCst);
+                    stack.pop(&mut handle).unwrap();
+                    compiler_fence(Ordering::SeqCst);
+
+                    ops += 1;
+                }
+                ops_sender.send(ops).unwrap();
+            });
+        }
+    })
+    .unwrap();
+    println!("end");
+
+    collect(config, ops_receiver, mem_receiver)
+}
+
+fn bench_stack_circ_hp<S: circ_hp::ConcurrentStack<String> + Send + Sync>(
+    config: &Config,
+) -> (u64, usize, usize) {
+    let stack = &S::new();
+
+    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
+    let (ops_sender, ops_receiver) = mpsc::channel();
+    let (mem_sender, mem_receiver) = mpsc::channel();
+
+    scope(|s| {
+        spawn_sampler(s, config, barrier, mem_sender);
+
+        for _ in 0..config.threads {
+            let ops_sender = ops_sender.clone();
+            s.spawn(move |_| {
+                let mut ops: u64 = 0;
+                let rng = &mut rand::thread_rng();
+                let holder = &mut S::Holder::default();
+                barrier.clone().wait();
+                let start = Instant::now();
+
+                let mut cs = circ::CsHP::new();
+                while start.elapsed() < config.duration {
+                    let item = config.key_dist.sample(rng).to_string();
+                    stack.push(item, holder, &cs);
+                    compiler_fence(Ordering::SeqCst);
+                    stack.pop(holder, &cs).unwrap();
+                    compiler_fence(Ordering::SeqCst);
+
+                    ops += 1;
+                    cs.clear();
+                }
+                ops_sender.send(ops).unwrap();
+            });
+        }
+    })
+    .unwrap();
+    println!("end");
+
+    collect(config, ops_receiver, mem_receiver)
+}
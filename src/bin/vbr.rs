@@ -3,14 +3,19 @@ use rand::prelude::*;
 use std::cmp::max;
 use std::io::{stdout, Write};
 use std::path::Path;
-use std::sync::{mpsc, Arc, Barrier};
+use std::sync::mpsc;
 use std::thread::available_parallelism;
 use std::time::Instant;
 
-use smr_benchmark::config::map::{setup, BagSize, BenchWriter, Config, Op, Perf, DS};
+use smr_benchmark::config::map::{
+    setup, BagSize, BenchWriter, Config, Op, Perf, ThreadResults, TreeShape, DS,
+};
 use smr_benchmark::ds_impl::vbr::{
     ConcurrentMap, ElimABTree, HHSList, HList, HMList, HashMap, NMTreeMap, SkipList,
 };
+use smr_benchmark::log_event;
+use smr_benchmark::start::StartGate;
+use vbr::validation;
 
 fn main() {
     let (config, output) = setup(
@@ -25,7 +30,7 @@ fn main() {
 
 fn bench(config: &Config, output: BenchWriter) {
     println!("{}", config);
-    let perf = match config.ds {
+    let perf = config.measure(|| match config.ds {
         DS::HList => bench_map::<HList<usize, usize>>(config, PrefillStrategy::Decreasing),
         DS::HMList => bench_map::<HMList<usize, usize>>(config, PrefillStrategy::Decreasing),
         DS::HHSList => bench_map::<HHSList<usize, usize>>(config, PrefillStrategy::Decreasing),
@@ -34,7 +39,7 @@ fn bench(config: &Config, output: BenchWriter) {
         DS::SkipList => bench_map::<SkipList<usize, usize>>(config, PrefillStrategy::Decreasing),
         DS::ElimAbTree => bench_map::<ElimABTree<usize, usize>>(config, PrefillStrategy::Random),
         _ => panic!("Unsupported(or unimplemented) data structure for VBR"),
-    };
+    });
     output.write_record(config, &perf);
     println!("{}", perf);
 }
@@ -55,43 +60,54 @@ impl PrefillStrategy {
         global: &M::Global,
     ) {
         match self {
+            // A tree of a given shape is prefilled in its order, with a single thread.
+            PrefillStrategy::Random if config.tree_shape != TreeShape::Random => {
+                let local = &M::local(global);
+                for key in config.shaped_prefill::<usize>() {
+                    let value = key.clone();
+                    map.insert(key, value, local);
+                }
+            }
             PrefillStrategy::Random => {
                 let threads = available_parallelism().map(|v| v.get()).unwrap_or(1);
                 print!("prefilling with {threads} threads... ");
                 stdout().flush().unwrap();
-                scope(|s| {
-                    for t in 0..threads {
-                        s.spawn(move |_| {
-                            let local = &M::local(global);
-                            let rng = &mut rand::thread_rng();
-                            let count = config.prefill / threads
-                                + if t < config.prefill % threads { 1 } else { 0 };
-                            for _ in 0..count {
-                                let key = config.key_dist.sample(rng);
-                                let value = key.clone();
-                                map.insert(key, value, local);
-                            }
-                        });
-                    }
-                })
-                .unwrap();
+                let finished = config
+                    .prefill_schedule
+                    .run(threads, config.prefill, |units| {
+                        let local = &M::local(global);
+                        let rng = &mut rand::thread_rng();
+                        for _ in units {
+                            let key = config.prefill_dist.sample(rng);
+                            let value = key.clone();
+                            map.insert(key, value, local);
+                        }
+                    });
+                print!(
+                    "threads finished in {:?} to {:?}... ",
+                    finished[0],
+                    finished[threads - 1]
+                );
             }
             PrefillStrategy::Decreasing => {
                 let local = &M::local(global);
-                let rng = &mut rand::thread_rng();
-                let mut keys = Vec::with_capacity(config.prefill);
-                for _ in 0..config.prefill {
-                    keys.push(config.key_dist.sample(rng));
-                }
-                keys.sort_by(|a, b| b.cmp(a));
-                for key in keys.drain(..) {
+                for key in config.decreasing_prefill::<usize>() {
                     let value = key.clone();
                     map.insert(key, value, local);
                 }
             }
         }
         print!("prefilled... ");
+        log_event!(Info, Prefill, "prefilled", keys = config.prefill);
         stdout().flush().unwrap();
+        let local = &M::local(global);
+        config.age(|op, key| {
+            if op == Op::Insert {
+                map.insert(key, key, local)
+            } else {
+                map.remove(&key, local).is_some()
+            }
+        });
     }
 }
 
@@ -105,6 +121,7 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
     let local = &M::local(global);
     let map = &M::new(local);
     strategy.prefill(config, map, global);
+    config.run_hygiene();
 
     // Adjust the bag size after prefilling.
     match config.bag_size {
@@ -112,9 +129,12 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
         BagSize::Large => vbr::set_bag_capacity(4096),
     }
 
-    let barrier = &Arc::new(Barrier::new(config.threads + config.aux_thread));
-    let (ops_sender, ops_receiver) = mpsc::channel();
+    let gate = &StartGate::new(config.threads + config.aux_thread);
+    let mut results = ThreadResults::new(config.threads);
+    let shadow = &config.shadow();
+    let conflicts = &config.conflicts();
     let (mem_sender, mem_receiver) = mpsc::channel();
+    let validations = validation::stats();
 
     scope(|s| {
         // sampling & interference thread
@@ -126,7 +146,7 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
                 let mut peak = 0usize;
                 let garb_acc = 0usize;
                 let garb_peak = 0usize;
-                barrier.clone().wait();
+                gate.wait();
 
                 let start = Instant::now();
                 let mut next_sampling = start + config.sampling_period;
@@ -156,44 +176,71 @@ fn bench_map<M: ConcurrentMap<usize, usize> + Send + Sync>(
             mem_sender.send((0, 0, 0, 0)).unwrap();
         }
 
-        for _ in 0..config.threads {
-            let ops_sender = ops_sender.clone();
+        for result in results.slots() {
             s.spawn(move |_| {
                 let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
                 let mut rng = &mut rand::thread_rng();
-                let local = &M::local(global);
-                barrier.clone().wait();
+                let registration = config.registers_early().then(|| M::local(global));
+                config.start(gate, result, |key| {
+                    if let Some(local) = &registration {
+                        map.get(&key, local);
+                    }
+                });
+                let lifetime = config.arrive(result);
+                let critical = config.critical(result.index);
+                let local = &registration.unwrap_or_else(|| M::local(global));
                 let start = Instant::now();
 
-                while start.elapsed() < config.duration {
+                while start.elapsed() < lifetime {
+                    let op_start = result.op_start(config, config.timed(critical));
                     let key = config.key_dist.sample(rng);
-                    match Op::OPS[config.op_dist.sample(&mut rng)] {
+                    conflicts.enter(result.index, key);
+                    let op = config.sample_op(critical, &mut rng);
+                    match op {
+                        _ if config.get_or_insert(critical, rng) => {
+                            map.get_or_insert(key, key, local);
+                        }
                         Op::Get => {
-                            map.get(&key, local);
+                            shadow.run(Op::Get, key, || map.get(&key, local));
                         }
                         Op::Insert => {
                             let value = key.clone();
-                            map.insert(key, value, local);
+                            shadow.run(Op::Insert, key, || {
+                                map.insert(key, value, local).then_some(value)
+                            });
                         }
                         Op::Remove => {
-                            map.remove(&key, local);
+                            shadow.run(Op::Remove, key, || map.remove(&key, local));
                         }
                     }
+                    conflicts.exit(result.index);
                     ops += 1;
+                    result.record_op(config, critical, Some(op), op_start);
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
                 }
-                ops_sender.send(ops).unwrap();
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
             });
         }
     })
     .unwrap();
     println!("end");
-
-    let mut ops = 0;
-    for _ in 0..config.threads {
-        let local_ops = ops_receiver.recv().unwrap();
-        ops += local_ops;
-    }
-    let ops_per_sec = ops / config.interval;
+    shadow.report();
+    config.record_conflicts(conflicts);
+    config.record_validations(validation::stats() - validations, results.ops());
+    config.run_hygiene();
+
+    results.print_sockets();
+    results.record_start_skew();
+    results.record_overdue(config);
+    results.record_classes(config);
+    results.record_op_latencies(config);
+    results.record_threads(config);
+
+    let ops_per_sec = results.ops_per_sec();
     let (peak_mem, avg_mem, peak_garb, avg_garb) = mem_receiver.recv().unwrap();
     Perf {
         ops_per_sec,
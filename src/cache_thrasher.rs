@@ -0,0 +1,79 @@
+//! A per-thread buffer that evicts the data structure from the caches.
+//!
+//! A benchmark thread with a thrasher streams through its buffer once every given number of
+//! operations, so that the following operations find the structure cold in the caches. This models
+//! an application that touches much more memory than the structure between its operations, where
+//! the cache misses of pointer chasing dominate and the overhead of the protection shrinks
+//! relatively. The time spent on streaming is excluded from the throughput.
+//!
+//! The buffer is allocated from the system allocator directly, so that it is not included in the
+//! memory usage sampled from the global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const CACHE_LINE: usize = 64;
+
+pub struct CacheThrasher {
+    buf: *mut u8,
+    layout: Layout,
+    period: u64,
+    countdown: u64,
+    elapsed: Duration,
+}
+
+impl CacheThrasher {
+    /// Creates a thrasher with a buffer of `size` bytes, streaming once every `period` operations.
+    pub fn new(size: usize, period: u64) -> Self {
+        let layout = Layout::from_size_align(size.max(CACHE_LINE), CACHE_LINE).unwrap();
+        let buf = unsafe { System.alloc_zeroed(layout) };
+        assert!(
+            !buf.is_null(),
+            "failed to allocate the cache thrashing buffer"
+        );
+        Self {
+            buf,
+            layout,
+            period,
+            countdown: period,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Counts an operation, and streams through the buffer if `period` operations have passed.
+    #[inline]
+    pub fn tick(&mut self) {
+        self.countdown -= 1;
+        if self.countdown == 0 {
+            self.countdown = self.period;
+            self.thrash();
+        }
+    }
+
+    #[cold]
+    fn thrash(&mut self) {
+        let start = Instant::now();
+        // Writes a byte of each cache line, so that the lines of the structure are evicted even
+        // from the caches that prefer to keep clean lines.
+        for offset in (0..self.layout.size()).step_by(CACHE_LINE) {
+            unsafe {
+                let byte = self.buf.add(offset);
+                byte.write_volatile(byte.read_volatile().wrapping_add(1));
+            }
+        }
+        black_box(self.buf);
+        self.elapsed += start.elapsed();
+    }
+
+    /// The total time spent on streaming through the buffer.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl Drop for CacheThrasher {
+    fn drop(&mut self) {
+        unsafe { System.dealloc(self.buf, self.layout) };
+    }
+}
@@ -0,0 +1,181 @@
+//! A clock of a benchmark thread that tolerates the clock skews of some virtual machines.
+//!
+//! On some VMs, the monotonic clock of the guest jumps when the host migrates or deschedules a
+//! vCPU, or when the guest steers it towards the clock of the host, so that an hours-long run
+//! reports a throughput that no machine can reach. Each thread keeps its own clock, and
+//! resynchronizes it once every period of `Instant` against the raw monotonic clock of the kernel,
+//! which the time synchronization of the guest does not adjust. A period whose two elapsed times
+//! differ by more than `TOLERANCE` of the period is skewed, and neither its operations nor its
+//! time count towards the throughput. The differences of all periods are reported as the drift.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The period of the resynchronizations.
+pub const RESYNC_PERIOD: Duration = Duration::from_secs(1);
+
+/// The fraction of a period by which the two clocks may disagree before the period is skewed.
+const TOLERANCE: f64 = 0.01;
+
+/// The raw monotonic time, which is the time of `Instant` on the platforms without one.
+fn raw_now(fallback: Instant, origin: Instant) -> Duration {
+    #[cfg(target_os = "linux")]
+    {
+        let _ = (fallback, origin);
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        let ret = unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_RAW, &mut ts) };
+        assert_eq!(ret, 0, "Failed to read CLOCK_MONOTONIC_RAW");
+        Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        fallback - origin
+    }
+}
+
+pub struct ThreadClock {
+    start: Instant,
+    /// The times of both clocks at the last resynchronization.
+    synced: Instant,
+    synced_raw: Duration,
+    /// The number of operations at the last resynchronization.
+    synced_ops: u64,
+    report: ClockReport,
+}
+
+impl ThreadClock {
+    /// Starts the clock of the calling thread.
+    pub fn start() -> Self {
+        let start = Instant::now();
+        Self {
+            start,
+            synced: start,
+            synced_raw: raw_now(start, start),
+            synced_ops: 0,
+            report: ClockReport::default(),
+        }
+    }
+
+    /// Returns whether the thread, which has run `ops` operations, is still within `duration`, and
+    /// resynchronizes the clock if a period has passed.
+    #[inline]
+    pub fn running(&mut self, ops: u64, duration: Duration) -> bool {
+        let now = Instant::now();
+        if now - self.synced >= RESYNC_PERIOD {
+            self.sync(now, ops);
+        }
+        now - self.start < duration
+    }
+
+    #[cold]
+    fn sync(&mut self, now: Instant, ops: u64) {
+        let raw = raw_now(now, self.start);
+        self.account(now - self.synced, raw.saturating_sub(self.synced_raw), ops);
+        self.synced = now;
+        self.synced_raw = raw;
+        self.synced_ops = ops;
+    }
+
+    /// Accounts for a period in which `ops` operations have run, which took `elapsed` on `Instant`
+    /// and `raw` on the raw clock.
+    fn account(&mut self, elapsed: Duration, raw: Duration, ops: u64) {
+        let drift = elapsed.abs_diff(raw);
+        let report = &mut self.report;
+        report.periods += 1;
+        report.drift += drift;
+        report.max_drift = report.max_drift.max(drift);
+        if drift.as_secs_f64() > elapsed.max(raw).as_secs_f64() * TOLERANCE {
+            report.skewed += 1;
+        } else {
+            report.ops += ops - self.synced_ops;
+            report.time += elapsed;
+        }
+    }
+
+    /// Stops the clock after `ops` operations, and returns what it has measured.
+    pub fn finish(mut self, ops: u64) -> ClockReport {
+        self.sync(Instant::now(), ops);
+        let mut report = self.report;
+        if !report.time.is_zero() {
+            report.ops_per_sec = report.ops as f64 / report.time.as_secs_f64();
+        }
+        report
+    }
+}
+
+/// The measurement of one or more thread clocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ClockReport {
+    /// The operations and the time of the periods that were not skewed.
+    pub ops: u64,
+    pub time: Duration,
+    /// The throughput over the periods that were not skewed, summed over the threads.
+    pub ops_per_sec: f64,
+    pub periods: usize,
+    pub skewed: usize,
+    /// The total and the largest difference between the clocks in a period.
+    pub drift: Duration,
+    pub max_drift: Duration,
+}
+
+impl ClockReport {
+    /// Adds up the reports of the threads.
+    pub fn sum<I: IntoIterator<Item = ClockReport>>(reports: I) -> Self {
+        reports
+            .into_iter()
+            .fold(Self::default(), |sum, report| Self {
+                ops: sum.ops + report.ops,
+                time: sum.time + report.time,
+                ops_per_sec: sum.ops_per_sec + report.ops_per_sec,
+                periods: sum.periods + report.periods,
+                skewed: sum.skewed + report.skewed,
+                drift: sum.drift + report.drift,
+                max_drift: sum.max_drift.max(report.max_drift),
+            })
+    }
+}
+
+impl fmt::Display for ClockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "clock drift: {:?} in total, {:?} at most per period, {} of {} periods skewed",
+            self.drift, self.max_drift, self.skewed, self.periods
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClockReport, ThreadClock};
+    use std::time::Duration;
+
+    #[test]
+    fn skewed_periods_are_excluded() {
+        let mut clock = ThreadClock::start();
+        let second = Duration::from_secs(1);
+        clock.account(second, second + Duration::from_micros(100), 1000);
+        clock.synced_ops = 1000;
+        // The clock jumped by a second in this period.
+        clock.account(2 * second, second, 5000);
+        clock.synced_ops = 5000;
+        clock.account(second, second, 6000);
+        let report = clock.report;
+        assert_eq!(report.ops, 2000);
+        assert_eq!(report.time, 2 * second);
+        assert_eq!((report.skewed, report.periods), (1, 3));
+        assert_eq!(report.max_drift, second);
+
+        let threads = [1000.0, 500.0].map(|ops_per_sec| ClockReport {
+            ops_per_sec,
+            ..report
+        });
+        let sum = ClockReport::sum(threads);
+        assert_eq!(sum.ops_per_sec, 1500.0);
+        assert_eq!(sum.skewed, 2);
+        assert_eq!(sum.max_drift, second);
+    }
+}
@@ -0,0 +1,379 @@
+//! Significance tests of the differences between two sets of results.
+//!
+//! A result file has a row for each run of a configuration, and a configuration that is run
+//! several times, e.g., by a repeated sweep, has several rows. The runs of each configuration on
+//! one side are compared with the runs of the same configuration on the other side, where a side
+//! is a result file, a scheme within a result file, or both. A configuration is identified by the
+//! columns of its row that are not measured, e.g., the data structure, the number of threads and
+//! the key range, except for the scheme.
+//!
+//! Each configuration is tested with the Mann–Whitney U test, which does not assume that the
+//! runs are normally distributed, and the ratio of the medians of the two sides gets a bootstrap
+//! confidence interval. A difference is significant if the p-value of the test is below `alpha`
+//! and the interval of the ratio excludes 1.
+//!
+//! Both the wide rows of the `-o` files of the benchmarks and the long rows of the sweeps
+//! (`point,...,ds,mm,metric,value`) are read.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+
+/// The columns of a wide row that are measured rather than configured.
+pub const MEASURED: [&str; 41] = [
+    "throughput",
+    "peak_mem",
+    "avg_mem",
+    "peak_garb",
+    "avg_garb",
+    "reps",
+    "ci",
+    "stale_hit_rate",
+    "leaked_frees",
+    "critical_ops_per_sec",
+    "critical_p99_us",
+    "background_ops_per_sec",
+    "avg_mhz",
+    "freq_warnings",
+    "max_start_skew_us",
+    "avg_start_skew_us",
+    "overdue_ops",
+    "flushes",
+    "fences",
+    "reclaimer_util",
+    "conflict_rate",
+    "get_p50_ns",
+    "get_p90_ns",
+    "get_p99_ns",
+    "get_p999_ns",
+    "insert_p50_ns",
+    "insert_p90_ns",
+    "insert_p99_ns",
+    "insert_p999_ns",
+    "remove_p50_ns",
+    "remove_p90_ns",
+    "remove_p99_ns",
+    "remove_p999_ns",
+    "steal_success_rate",
+    "reclaimed_buffers",
+    "peak_node_bytes",
+    "avg_node_bytes",
+    "peak_rss",
+    "avg_rss",
+    "validation_failures",
+    "validation_fallbacks",
+];
+
+/// The largest number of runs of a side for which the exact distribution of U is used.
+const EXACT_RUNS: usize = 30;
+
+/// The configured columns of a configuration and their values, without the scheme.
+pub type Key = Vec<(String, String)>;
+
+/// The runs of each configuration of a side, as the values of the metric.
+pub type Groups = BTreeMap<Key, Vec<f64>>;
+
+/// Reads the values of `metric` of the runs of `scheme`, or of every scheme, from a result file,
+/// and groups them by configuration.
+pub fn read_groups<R: io::Read>(
+    reader: R,
+    metric: &str,
+    scheme: Option<&str>,
+) -> Result<Groups, csv::Error> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let columns = reader.headers()?.clone();
+    let long = columns.iter().any(|column| column == "metric");
+    let mut groups = Groups::new();
+    for row in reader.records() {
+        let row = row?;
+        let field = |name: &str| {
+            columns
+                .iter()
+                .position(|column| column == name)
+                .and_then(|column| row.get(column))
+        };
+        if scheme.is_some_and(|scheme| field("mm") != Some(scheme)) {
+            continue;
+        }
+        let value = if long {
+            if field("metric") != Some(metric) {
+                continue;
+            }
+            field("value")
+        } else {
+            field(metric)
+        };
+        // A run that does not record the metric, e.g., a column of another mode, is not a run of
+        // the comparison.
+        let Some(value) = value.and_then(|value| value.parse::<f64>().ok()) else {
+            continue;
+        };
+        let key = columns
+            .iter()
+            .zip(row.iter())
+            .filter(|(column, _)| {
+                let skipped = if long {
+                    ["point", "mm", "metric", "value"].as_slice()
+                } else {
+                    ["mm"].as_slice()
+                };
+                !skipped.contains(column) && !(!long && MEASURED.contains(column))
+            })
+            .map(|(column, value)| (column.to_string(), value.to_string()))
+            .collect();
+        groups.entry(key).or_default().push(value);
+    }
+    Ok(groups)
+}
+
+/// The outcome of the test of a configuration.
+pub struct Comparison {
+    pub runs: (usize, usize),
+    pub medians: (f64, f64),
+    /// The median of the second side over the median of the first one.
+    pub ratio: f64,
+    /// The 95% bootstrap confidence interval of `ratio`.
+    pub ci: (f64, f64),
+    /// The two-sided p-value of the Mann–Whitney U test.
+    pub p: f64,
+    pub significant: bool,
+}
+
+impl Comparison {
+    /// Tests the runs `a` of a configuration on the first side against its runs `b` on the second
+    /// side, with `resamples` bootstrap resamples.
+    pub fn new(a: &[f64], b: &[f64], alpha: f64, resamples: usize, rng: &mut StdRng) -> Self {
+        assert!(!a.is_empty() && !b.is_empty());
+        let medians = (median(a), median(b));
+        let ratio = medians.1 / medians.0;
+        let ci = bootstrap_ratio(a, b, resamples, rng);
+        let p = mann_whitney(a, b);
+        Self {
+            runs: (a.len(), b.len()),
+            medians,
+            ratio,
+            ci,
+            p,
+            significant: p < alpha && (ci.0 > 1.0 || ci.1 < 1.0),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.0} vs {:.0} ({} vs {} runs): x{:.3} [{:.3}, {:.3}], p = {:.4}, {}",
+            self.medians.0,
+            self.medians.1,
+            self.runs.0,
+            self.runs.1,
+            self.ratio,
+            self.ci.0,
+            self.ci.1,
+            self.p,
+            if self.significant {
+                "significant"
+            } else {
+                "NOT significant"
+            }
+        )
+    }
+}
+
+/// The columns of `keys` that take more than one value, and `ds`, which tell the configurations
+/// apart when they are printed.
+pub fn varying_columns<'k>(keys: impl Iterator<Item = &'k Key>) -> BTreeSet<String> {
+    let mut values = BTreeMap::<&str, BTreeSet<&str>>::new();
+    for key in keys {
+        for (column, value) in key {
+            values.entry(column).or_default().insert(value);
+        }
+    }
+    values
+        .into_iter()
+        .filter(|(column, values)| *column == "ds" || values.len() > 1)
+        .map(|(column, _)| column.to_string())
+        .collect()
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// The 2.5th and 97.5th percentiles of the ratio of the medians of resamples of `b` and `a`.
+fn bootstrap_ratio(a: &[f64], b: &[f64], resamples: usize, rng: &mut StdRng) -> (f64, f64) {
+    let mut resample = |values: &[f64]| {
+        let picked = (0..values.len())
+            .map(|_| values[rng.gen_range(0..values.len())])
+            .collect::<Vec<_>>();
+        median(&picked)
+    };
+    let mut ratios = (0..resamples.max(1))
+        .map(|_| {
+            let a = resample(a);
+            resample(b) / a
+        })
+        .collect::<Vec<_>>();
+    ratios.sort_by(f64::total_cmp);
+    let percentile = |p: f64| ratios[((ratios.len() - 1) as f64 * p).round() as usize];
+    (percentile(0.025), percentile(0.975))
+}
+
+/// The two-sided p-value of the Mann–Whitney U test of `a` and `b`. It is exact for small samples
+/// without ties, and from the normal approximation with the correction for ties otherwise.
+fn mann_whitney(a: &[f64], b: &[f64]) -> f64 {
+    let (n1, n2) = (a.len(), b.len());
+    let n = n1 + n2;
+    let mut all = a
+        .iter()
+        .map(|&v| (v, true))
+        .chain(b.iter().map(|&v| (v, false)))
+        .collect::<Vec<_>>();
+    all.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // The ranks from 1, with the average rank for a run of ties.
+    let mut rank_sum = 0.0;
+    let mut tie_term = 0.0;
+    let mut i = 0;
+    while i < n {
+        let mut j = i;
+        while j + 1 < n && all[j + 1].0 == all[i].0 {
+            j += 1;
+        }
+        let rank = (i + j + 2) as f64 / 2.0;
+        rank_sum += rank * all[i..=j].iter().filter(|(_, first)| *first).count() as f64;
+        let t = (j - i + 1) as f64;
+        tie_term += t * t * t - t;
+        i = j + 1;
+    }
+    let u = rank_sum - (n1 * (n1 + 1)) as f64 / 2.0;
+
+    if tie_term == 0.0 && n1 <= EXACT_RUNS && n2 <= EXACT_RUNS {
+        let counts = u_counts(n1, n2);
+        let total = counts.iter().sum::<f64>();
+        let u = u as usize;
+        let lower = counts[..=u].iter().sum::<f64>() / total;
+        let upper = counts[u..].iter().sum::<f64>() / total;
+        return (2.0 * lower.min(upper)).min(1.0);
+    }
+
+    let mean = (n1 * n2) as f64 / 2.0;
+    let var = (n1 * n2) as f64 / 12.0 * ((n + 1) as f64 - tie_term / (n * (n - 1)) as f64);
+    if var == 0.0 {
+        return 1.0;
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / var.sqrt();
+    (2.0 * (1.0 - normal_cdf(z))).min(1.0)
+}
+
+/// The number of the orders of `n1` and `n2` distinct runs for each value of U.
+fn u_counts(n1: usize, n2: usize) -> Vec<f64> {
+    // `counts[i][j][u]`, built up from the orders of fewer runs: the largest of `i` and `j` runs
+    // is either one of the `i`, which is above the `j` others, or one of the `j`.
+    let mut counts = vec![vec![vec![0.0; n1 * n2 + 1]; n2 + 1]; n1 + 1];
+    for i in 0..=n1 {
+        for j in 0..=n2 {
+            if i == 0 || j == 0 {
+                counts[i][j][0] = 1.0;
+                continue;
+            }
+            for u in 0..=i * j {
+                let above = if u >= j { counts[i - 1][j][u - j] } else { 0.0 };
+                counts[i][j][u] = above + counts[i][j - 1][u];
+            }
+        }
+    }
+    counts.swap_remove(n1).swap_remove(n2)
+}
+
+/// The CDF of the standard normal distribution, after Abramowitz and Stegun (7.1.26).
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / 2f64.sqrt();
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        (1.0 + erf) / 2.0
+    } else {
+        (1.0 - erf) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mann_whitney, read_groups, varying_columns, Comparison};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn exact_p_value() {
+        // One of the 70 orders of 4 and 4 runs is as extreme on each side.
+        let p = mann_whitney(&[1.0, 2.0, 3.0, 4.0], &[5.0, 6.0, 7.0, 8.0]);
+        assert!((p - 2.0 / 70.0).abs() < 1e-9);
+        let p = mann_whitney(&[1.0, 4.0, 5.0, 8.0], &[2.0, 3.0, 6.0, 7.0]);
+        assert!((p - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ties() {
+        assert_eq!(mann_whitney(&[5.0; 6], &[5.0; 6]), 1.0);
+        let p = mann_whitney(&[1.0, 1.0, 2.0, 2.0, 3.0], &[7.0, 7.0, 8.0, 9.0, 9.0]);
+        assert!(p < 0.05);
+    }
+
+    #[test]
+    fn significance() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        let a = [100.0, 102.0, 98.0, 101.0, 99.0];
+        let faster = [120.0, 118.0, 122.0, 121.0, 119.0];
+        let same = [101.0, 99.0, 100.0, 103.0, 97.0];
+        let comparison = Comparison::new(&a, &faster, 0.05, 1000, rng);
+        assert!(comparison.significant);
+        assert!((comparison.ratio - 1.2).abs() < 1e-9);
+        assert!(comparison.ci.0 > 1.0);
+        assert!(!Comparison::new(&a, &same, 0.05, 1000, rng).significant);
+    }
+
+    #[test]
+    fn wide_and_long_rows() {
+        let wide = "ds,mm,threads,throughput,peak_mem\n\
+                    hash-map,ebr,1,100,5\n\
+                    hash-map,hp,1,90,4\n\
+                    hash-map,ebr,1,102,5\n\
+                    hash-map,ebr,2,200,5\n";
+        let groups = read_groups(wide.as_bytes(), "throughput", Some("ebr")).unwrap();
+        assert_eq!(groups.len(), 2);
+        let key = vec![
+            ("ds".to_string(), "hash-map".to_string()),
+            ("threads".to_string(), "1".to_string()),
+        ];
+        assert_eq!(groups[&key], [100.0, 102.0]);
+        assert_eq!(
+            varying_columns(groups.keys())
+                .into_iter()
+                .collect::<Vec<_>>(),
+            ["ds", "threads"]
+        );
+
+        let long = "point,threads,ds,mm,metric,value\n\
+                    0,1,hash-map,ebr,throughput,100\n\
+                    0,1,hash-map,ebr,peak_mem,5\n\
+                    0,1,hash-map,hp,throughput,90\n\
+                    3,1,hash-map,ebr,throughput,102\n";
+        let groups = read_groups(long.as_bytes(), "throughput", Some("ebr")).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[&key], [100.0, 102.0]);
+    }
+}
@@ -0,0 +1,208 @@
+//! The threads of the benchmarks of the structures other than the maps.
+//!
+//! Each benchmark thread sets up its state, e.g., registers with the scheme, starts with the others
+//! at a [`StartGate`] as a map benchmark thread does, warming its caches with `--warm-up`
+//! unrecorded operations of the structure, and runs them until the end of the run. Meanwhile, the sampling &
+//! interference thread samples the memory and the garbage of the scheme, holding a guard of the
+//! scheme with `--non-coop`. A benchmark gives the operations of its structure as a closure per
+//! thread, and its scheme as a [`Scheme`]. It may also run a side thread that drives the structure
+//! from outside, e.g., the owner of a work-stealing deque.
+
+use super::map::{Config, Perf, ThreadResult, ThreadResults};
+use crate::start::StartGate;
+use crossbeam_utils::thread::scope;
+use rand::rngs::ThreadRng;
+use std::cmp::max;
+use std::time::Instant;
+
+/// What the sampling & interference thread does with a scheme.
+pub trait Scheme: Sync {
+    /// The state of the sampling & interference thread, e.g., the guard that it holds with
+    /// `--non-coop`.
+    type Aux;
+
+    /// Sets up the sampling & interference thread at the start of the run.
+    fn aux(&self, config: &Config) -> Self::Aux;
+
+    /// Repins the guard of `aux`, if it holds one, every `--non-coop-period`.
+    fn repin(&self, _aux: &mut Self::Aux) {}
+
+    /// The retired objects that are not freed yet, or 0 if the scheme does not count them.
+    fn garbage(&self) -> usize {
+        0
+    }
+}
+
+/// A scheme that never frees, i.e., `nr`.
+pub struct NoReclamation;
+
+impl Scheme for NoReclamation {
+    type Aux = ();
+
+    fn aux(&self, _config: &Config) -> Self::Aux {}
+}
+
+/// Runs the benchmark threads for `config.duration` with the sampling & interference thread of
+/// `scheme`, and returns their results and the performance of the run.
+///
+/// `worker` sets up the thread of an index before the start, and returns its operation, which
+/// runs an operation of the thread and returns whether it counts toward the throughput.
+pub fn run<S, W, F>(config: &Config, scheme: &S, worker: W) -> (ThreadResults, Perf)
+where
+    S: Scheme,
+    W: Fn(usize) -> F + Sync,
+    F: FnMut(&mut ThreadRng, &mut ThreadResult) -> bool,
+{
+    let (results, perf, _) = run_threads(config, scheme, None::<fn() -> fn()>, worker);
+    (results, perf)
+}
+
+/// Runs the benchmark threads as [`run`] does, and a side thread, which `side` sets up before
+/// the start. The side thread runs the closure that `side` returns, which runs until the end of
+/// the run, and its result is returned with those of the benchmark threads.
+pub fn run_with_side<S, D, G, T, W, F>(
+    config: &Config,
+    scheme: &S,
+    side: D,
+    worker: W,
+) -> (ThreadResults, Perf, T)
+where
+    S: Scheme,
+    D: FnOnce() -> G + Send,
+    G: FnOnce() -> T,
+    T: Send,
+    W: Fn(usize) -> F + Sync,
+    F: FnMut(&mut ThreadRng, &mut ThreadResult) -> bool,
+{
+    let (results, perf, side) = run_threads(config, scheme, Some(side), worker);
+    (results, perf, side.unwrap())
+}
+
+fn run_threads<S, D, G, T, W, F>(
+    config: &Config,
+    scheme: &S,
+    side: Option<D>,
+    worker: W,
+) -> (ThreadResults, Perf, Option<T>)
+where
+    S: Scheme,
+    D: FnOnce() -> G + Send,
+    G: FnOnce() -> T,
+    T: Send,
+    W: Fn(usize) -> F + Sync,
+    F: FnMut(&mut ThreadRng, &mut ThreadResult) -> bool,
+{
+    let gate = &StartGate::new(config.threads + config.aux_thread + side.is_some() as usize);
+    let worker = &worker;
+    let mut results = ThreadResults::new(config.threads);
+
+    let (mem, side) = scope(|s| {
+        // sampling & interference thread
+        let mem = (config.aux_thread > 0).then(|| s.spawn(move |_| sample(config, scheme, gate)));
+
+        let side = side.map(|side| {
+            s.spawn(move |_| {
+                let side = side();
+                gate.wait();
+                side()
+            })
+        });
+
+        for result in results.slots() {
+            s.spawn(move |_| {
+                let mut ops: u64 = 0;
+                let mut thrasher = config.cache_thrasher();
+                let rng = &mut rand::thread_rng();
+                let mut op = worker(result.index);
+                // The warm-up operations are not recorded in the result of the thread.
+                let warm_up = &mut ThreadResult {
+                    index: result.index,
+                    ..Default::default()
+                };
+                config.start(gate, result, |_| {
+                    op(rng, warm_up);
+                });
+                result.arrived();
+                let start = Instant::now();
+
+                while start.elapsed() < config.duration {
+                    if op(rng, result) {
+                        ops += 1;
+                    }
+                    if let Some(thrasher) = thrasher.as_mut() {
+                        thrasher.tick();
+                    }
+                }
+
+                result.record(start, ops);
+                result.thrash_time = thrasher.map_or(Default::default(), |t| t.elapsed());
+            });
+        }
+
+        (
+            mem.map(|mem| mem.join().unwrap()),
+            side.map(|side| side.join().unwrap()),
+        )
+    })
+    .unwrap();
+    println!("end");
+    results.record_start_skew();
+
+    let (peak_mem, avg_mem, peak_garb, avg_garb) = mem.unwrap_or_default();
+    let perf = Perf {
+        ops_per_sec: results.ops_per_sec(),
+        peak_mem,
+        avg_mem,
+        peak_garb,
+        avg_garb,
+    };
+    (results, perf, side)
+}
+
+/// Samples the memory and the garbage of `scheme` every `--sampling-period` until the end of the
+/// run, and returns their peaks and averages, or zeros without `--sampling`.
+fn sample<S: Scheme>(
+    config: &Config,
+    scheme: &S,
+    gate: &StartGate,
+) -> (usize, usize, usize, usize) {
+    let mut samples = 0usize;
+    let mut acc = 0usize;
+    let mut peak = 0usize;
+    let mut garb_acc = 0usize;
+    let mut garb_peak = 0usize;
+    gate.wait();
+
+    let start = Instant::now();
+    let mut aux = scheme.aux(config);
+    let mut next_sampling = start + config.sampling_period;
+    let mut next_repin = start + config.non_coop_period;
+    while start.elapsed() < config.duration {
+        let now = Instant::now();
+        if now > next_sampling {
+            let allocated = config.mem_sampler.sample();
+            samples += 1;
+
+            acc += allocated;
+            peak = max(peak, allocated);
+
+            let garbages = scheme.garbage();
+            garb_acc += garbages;
+            garb_peak = max(garb_peak, garbages);
+
+            next_sampling = now + config.sampling_period;
+        }
+        if now > next_repin {
+            scheme.repin(&mut aux);
+            next_repin = now + config.non_coop_period;
+        }
+        std::thread::sleep(config.aux_thread_period);
+    }
+    drop(aux);
+
+    if config.sampling && samples > 0 {
+        (peak, acc / samples, garb_peak, garb_acc / samples)
+    } else {
+        (0, 0, 0, 0)
+    }
+}
@@ -1,11 +1,53 @@
-use crate::MemSampler;
+use super::sweep::{self, Sweep};
+use crate::affinity::{numa_nodes, Pin, Placement};
+use crate::alloc_tags::{self, AllocCurve, NodeBytes};
+use crate::baseline::Baseline;
+use crate::cache_thrasher::CacheThrasher;
+use crate::conflicts::{ConflictRate, Conflicts};
+use crate::cpu_freq::{FreqReport, FreqSampler};
+use crate::ds_impl::cas;
+use crate::ds_impl::helping;
+use crate::ds_impl::nr::immediate_free;
+use crate::ds_impl::persist::{self, Persists};
+use crate::ds_impl::retry;
+use crate::epoch_dump::EpochDump;
+use crate::huge_pages::{self, HugePages};
+use crate::latency::Histogram;
+use crate::layout::node_layouts;
+use crate::leak::{self, Frees};
+use crate::limits::max_threads;
+use crate::log::LogLevels;
+use crate::log_event;
+use crate::reclaim_timeline::ReclaimTimeline;
+use crate::results::{self, ResultsFile};
+use crate::rss::Rss;
+use crate::shadow::Shadow;
+use crate::signals;
+use crate::stale_cache::StaleCache;
+use crate::start::StartGate;
+use crate::topology::{cpu_list, topology};
+use crate::trace::{Trace, TraceCursor, TraceFormat, TraceSplit};
+use crate::{release_allocator_caches, MemSampler};
 use clap::{value_parser, Arg, ArgAction, Command, ValueEnum};
-use csv::Writer;
-use rand::distributions::{Uniform, WeightedIndex};
+use crossbeam_utils::thread::scope;
+use crossbeam_utils::CachePadded;
+use hp_pp::interpose::Injection;
+use hp_pp::reclaimer::{self, Reclaimer};
+use rand::distributions::{Distribution, Uniform, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::alloc::Layout;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
 use std::fmt;
-use std::fs::{create_dir_all, File, OpenOptions};
-use std::path::Path;
-use std::time::Duration;
+use std::io::{stdout, Write};
+use std::iter;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use vbr::validation::{self, Policy};
 
 #[derive(PartialEq, Debug, ValueEnum, Clone)]
 pub enum DS {
@@ -13,11 +55,94 @@ pub enum DS {
     HMList,
     HHSList,
     HashMap,
+    PersistentHashMap,
+    ExtHashMap,
+    KvHashMap,
     NMTree,
     BonsaiTree,
     EFRBTree,
     SkipList,
+    MarkerSkipList,
     ElimAbTree,
+    WfTreap,
+    BravoTree,
+    Log,
+    ChaseLev,
+}
+
+/// Whether a benchmark drives a structure as a map or as a key-only set, moves keys between two
+/// maps, inserts keys by merging batches of them, uses a map of bounded size as a cache, counts
+/// the occurrences of keys, appends to a log that a single thread truncates, or steals from a
+/// work-stealing deque that a single thread pushes to.
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum Mode {
+    Map,
+    Set,
+    Move,
+    Merge,
+    Cache,
+    Counter,
+    Log,
+    Deque,
+}
+
+/// The type of the keys in map benchmarks.
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum KeyType {
+    Usize,
+    U128,
+    Composite,
+}
+
+/// A key type that a map benchmark can use instead of `usize`.
+pub trait BenchKey: Ord + Clone + Copy + Default + fmt::Debug + Send + Sync + 'static {
+    /// Maps a sampled key index to a key. Distinct indices give distinct keys, and the order of
+    /// the keys need not follow the order of the indices.
+    fn from_index(index: usize) -> Self;
+
+    /// Whether the order of the keys follows the order of their indices.
+    const INDEX_ORDERED: bool = true;
+}
+
+impl BenchKey for usize {
+    #[inline(always)]
+    fn from_index(index: usize) -> Self {
+        index
+    }
+}
+
+impl BenchKey for u128 {
+    #[inline(always)]
+    fn from_index(index: usize) -> Self {
+        // Puts the index on both halves so that comparing two keys inspects both words.
+        ((index as u128) << 64) | index as u128
+    }
+}
+
+/// A string-prefixed key, whose comparisons first scan a long prefix shared by many keys.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default, Debug)]
+pub struct CompositeKey {
+    prefix: &'static str,
+    id: u64,
+}
+
+const COMPOSITE_KEY_PREFIXES: [&str; 4] = [
+    "smr-benchmark/composite-key/prefix/0",
+    "smr-benchmark/composite-key/prefix/1",
+    "smr-benchmark/composite-key/prefix/2",
+    "smr-benchmark/composite-key/prefix/3",
+];
+
+impl BenchKey for CompositeKey {
+    const INDEX_ORDERED: bool = false;
+
+    #[inline(always)]
+    fn from_index(index: usize) -> Self {
+        Self {
+            prefix: COMPOSITE_KEY_PREFIXES[index % COMPOSITE_KEY_PREFIXES.len()],
+            id: (index / COMPOSITE_KEY_PREFIXES.len()) as u64,
+        }
+    }
 }
 
 pub enum OpsPerCs {
@@ -34,6 +159,190 @@ impl fmt::Display for OpsPerCs {
     }
 }
 
+/// How a single-threaded prefill generates its keys.
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum KeyUniverse {
+    /// Samples `prefill` indices with replacement into a vector, and sorts it.
+    Materialized,
+    /// Generates the indices in a decreasing order on the fly, in constant memory. Each index in
+    /// the range is included independently with the probability of `prefill / range`.
+    Arithmetic,
+}
+
+/// Generates the indices of `KeyUniverse::Arithmetic` for `prefill` keys in `0..range`, in a
+/// decreasing order.
+fn arithmetic_prefill(prefill: usize, range: usize) -> impl Iterator<Item = usize> {
+    let mut rng = rand::thread_rng();
+    let ln_q = (1.0 - prefill as f64 / range as f64).ln();
+    // Without keys, `ln_q` is 0, and the skips would saturate to 0 instead of the whole range.
+    let mut end = if prefill == 0 { 0 } else { range };
+    iter::from_fn(move || {
+        // The number of indices skipped before the next included one is geometric.
+        let u: f64 = rng.gen();
+        let skip = ((1.0 - u).ln() / ln_q) as usize;
+        end = end.checked_sub(skip.saturating_add(1))?;
+        Some(end)
+    })
+}
+
+/// The distribution of the keys of the operations, given with `--distribution`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Skew {
+    Uniform,
+    /// The probability of the key of rank `i` (from 1) is proportional to `1 / i^theta`.
+    Zipf(f64),
+}
+
+impl FromStr for Skew {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("uniform") {
+            return Ok(Skew::Uniform);
+        }
+        let theta = s
+            .strip_prefix("zipf:")
+            .ok_or_else(|| format!("expected uniform or zipf:<theta>, got {s}"))?
+            .parse::<f64>()
+            .map_err(|e| format!("invalid theta in {s}: {e}"))?;
+        if !(theta > 0.0 && theta.is_finite()) {
+            return Err(format!("theta must be positive, got {theta}"));
+        }
+        Ok(Skew::Zipf(theta))
+    }
+}
+
+impl fmt::Display for Skew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Skew::Uniform => write!(f, "uniform"),
+            Skew::Zipf(theta) => write!(f, "zipf:{theta}"),
+        }
+    }
+}
+
+/// Samples the key indices of the operations.
+pub enum KeyDist {
+    Uniform(Uniform<usize>),
+    Zipf {
+        /// The cumulative probabilities of the ranks, computed once and shared by the threads.
+        cdf: Box<[f64]>,
+        /// The index of each rank. The ranks are shuffled over the range, so that the hot keys
+        /// are not all at the head of a list or in a corner of a tree.
+        indices: Box<[usize]>,
+    },
+}
+
+impl KeyDist {
+    pub fn new(skew: Skew, key_range: usize) -> Self {
+        let Skew::Zipf(theta) = skew else {
+            return KeyDist::Uniform(Uniform::from(0..key_range));
+        };
+        let mut sum = 0.0;
+        let mut cdf = (1..=key_range)
+            .map(|rank| {
+                sum += (rank as f64).powf(-theta);
+                sum
+            })
+            .collect::<Box<[f64]>>();
+        cdf.iter_mut().for_each(|p| *p /= sum);
+        let mut indices = (0..key_range).collect::<Box<[usize]>>();
+        indices.shuffle(&mut StdRng::seed_from_u64(0));
+        KeyDist::Zipf { cdf, indices }
+    }
+}
+
+impl Distribution<usize> for KeyDist {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        match self {
+            KeyDist::Uniform(uniform) => uniform.sample(rng),
+            KeyDist::Zipf { cdf, indices } => {
+                let u: f64 = rng.gen();
+                let rank = cdf.partition_point(|&p| p < u).min(cdf.len() - 1);
+                indices[rank]
+            }
+        }
+    }
+}
+
+/// The order in which the keys of a tree are inserted before the run.
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum TreeShape {
+    /// Inserts random keys with multiple threads.
+    Random,
+    /// Inserts the median of the keys first, and then the medians of the keys on either side of
+    /// the inserted ones, level by level. The external BST is balanced when the run starts.
+    Balanced,
+    /// Inserts the keys in a decreasing order with a single thread, which degenerates an
+    /// unbalanced tree into a path.
+    Sorted,
+}
+
+/// How the work of a multi-threaded phase (e.g., prefilling) is distributed over the threads.
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum Schedule {
+    /// Each thread runs an equal share of the work, decided before the phase starts.
+    Static,
+    /// The threads repeatedly take a chunk of the remaining work from a shared counter.
+    Dynamic,
+}
+
+/// The number of units of work that a thread takes at once with [`Schedule::Dynamic`].
+const SCHEDULE_CHUNK: usize = 64;
+
+impl Schedule {
+    /// Runs `total` units of work on `threads` threads.
+    ///
+    /// Each thread calls `work` once, with an iterator over the indices of the units assigned to
+    /// it. Returns the times at which the threads finished since the phase started, in an
+    /// increasing order.
+    pub fn run<F>(self, threads: usize, total: usize, work: F) -> Vec<Duration>
+    where
+        F: Fn(&mut dyn Iterator<Item = usize>) + Sync,
+    {
+        let work = &work;
+        let next = &AtomicUsize::new(0);
+        let start = Instant::now();
+        let mut finished = scope(|s| {
+            let handles = (0..threads)
+                .map(|t| {
+                    s.spawn(move |_| {
+                        match self {
+                            Schedule::Static => {
+                                let share = total / threads;
+                                let begin = t * share + t.min(total % threads);
+                                let count = share + if t < total % threads { 1 } else { 0 };
+                                work(&mut (begin..begin + count));
+                            }
+                            Schedule::Dynamic => {
+                                let mut chunk = 0..0;
+                                work(&mut iter::from_fn(|| {
+                                    if chunk.is_empty() {
+                                        let begin =
+                                            next.fetch_add(SCHEDULE_CHUNK, Ordering::Relaxed);
+                                        chunk =
+                                            begin.min(total)..(begin + SCHEDULE_CHUNK).min(total);
+                                    }
+                                    chunk.next()
+                                }));
+                            }
+                        }
+                        start.elapsed()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+        finished.sort();
+        finished
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum BagSize {
     Small,
@@ -49,7 +358,7 @@ impl fmt::Display for BagSize {
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum Op {
     Get,
     Insert,
@@ -71,6 +380,8 @@ pub enum GetRate {
 pub struct Config {
     pub ds: DS,
     pub mm: String,
+    pub mode: Mode,
+    pub key_type: KeyType,
     pub threads: usize,
     pub bag_size: BagSize,
 
@@ -83,22 +394,860 @@ pub struct Config {
 
     pub get_rate: GetRate,
     pub op_dist: WeightedIndex<i32>,
-    pub key_dist: Uniform<usize>,
+    pub key_dist: KeyDist,
+    pub skew: Skew,
+    /// The distribution of the keys of the prefill and the aging, which is uniform whatever
+    /// `--distribution` is, so that the structure has the same size.
+    pub prefill_dist: Uniform<usize>,
     pub prefill: usize,
+    pub prefill_schedule: Schedule,
+    /// The number of operations of the churn that ages the structure after the prefill with
+    /// `--age`.
+    pub age: u64,
+    /// The number of unrecorded `get`s, or operations of a structure other than a map, that each
+    /// benchmark thread runs with `--warm-up` before the threads start at once.
+    pub warm_up: usize,
     pub key_range: usize,
+    pub key_universe: KeyUniverse,
+    pub tree_shape: TreeShape,
     pub interval: u64,
     pub duration: Duration,
     pub ops_per_cs: OpsPerCs,
+    pub hygiene: bool,
+    pub harris_unlink_threshold: usize,
+    pub hhs_eventual_reads: bool,
+    pub stale_ttl: Option<Duration>,
+    pub stale_cache_size: usize,
+    /// The number of keys that a `get` looks up in a batch with `--multi-get`.
+    pub multi_get: usize,
+    /// The number of keys of the ranges that the first thread removes with `--range-remove`, once
+    /// per `range_remove_period`.
+    pub range_remove: Option<usize>,
+    pub range_remove_period: Duration,
+    /// The percentage of the operations that replace the value of a present key with
+    /// `--update-rate`.
+    pub update_rate: u32,
+    /// The percentage of the operations that get a key or insert it if it is absent with
+    /// `--get-or-insert-rate`.
+    pub get_or_insert_rate: u32,
+    /// Whether the removes extract the smallest key instead of a random one with `--extract-min`.
+    pub extract_min: bool,
+    /// The number of latency-critical threads with `--critical`, which only read and measure the
+    /// latency of each of their operations, while the other threads run the workload.
+    pub critical: usize,
+    /// The target of the 99th percentile latency of the latency-critical threads.
+    pub p99_target: Option<Duration>,
+    /// The deadline past which an operation counts as overdue with `--op-deadline`.
+    pub op_deadline: Option<Duration>,
+    /// Whether every thread records the latencies of its operations by type with
+    /// `--op-latencies`.
+    pub op_latencies: bool,
+    /// The period of the operations of each thread that are checked for a conflict on their key
+    /// with `--conflict-sample`.
+    pub conflict_sample: Option<u64>,
+    /// The instrumentation of the operations samples one in this many of them with `--sample`,
+    /// i.e., the timed operations and the conflict checks.
+    pub sample: u64,
+    /// The trace that the threads replay with `--trace`, instead of the get rate and the key range.
+    pub trace: Option<Trace>,
+    pub trace_path: Option<String>,
+    pub target_ci: Option<f64>,
+    pub max_reps: usize,
+    pub history: usize,
+    pub history_period: Duration,
+    /// The number of keys of the ranges that `--clone-range` copies out of the Bonsai tree, in
+    /// `clone_range_rate` percent of the operations.
+    pub clone_range: Option<usize>,
+    pub clone_range_rate: u32,
+    /// The number of keys of the ranges that `--range-query` scans out of the lists and the hash
+    /// map, in `range_query_rate` percent of the operations.
+    pub range_query: Option<usize>,
+    pub range_query_rate: u32,
+    /// The number of elements that `--partial-scan` iterates from a key of `hhs-list`, in
+    /// `partial_scan_rate` percent of the operations.
+    pub partial_scan: Option<usize>,
+    pub partial_scan_rate: u32,
+    /// The period to rebuild the whole map and swap it in place of the current one with
+    /// `--swap-period`.
+    pub swap_period: Option<Duration>,
+    /// The delay after each load of a snapshot of CIRC with `--memory-delay`.
+    pub memory_delay: Option<Duration>,
+    /// The faults injected at the boundaries of `hp_pp` with `--inject`.
+    pub injections: Vec<Injection>,
+    /// The number of dedicated threads that scan and free the garbage of `hp_pp` with
+    /// `--reclaimers`, instead of the benchmark threads that retire it.
+    pub reclaimers: usize,
+    /// What a thread of `vbr` does after its validations fail, with `--validation-policy`.
+    pub validation_policy: Policy,
+    /// The size of the per-thread cache thrashing buffer in bytes, or 0 to disable it.
+    pub thrash_cache: usize,
+    pub thrash_period: u64,
+    /// The socket that the threads are restricted to.
+    pub socket: Option<usize>,
+    /// The CPUs that the number of threads was detected from with `-t auto`.
+    pub auto_threads: Option<AutoThreads>,
+    /// How the threads are pinned to the CPUs with `--pin`, and the CPUs of each thread.
+    pub pin: Option<(Pin, Placement)>,
+    /// Whether the memory is backed by transparent huge pages.
+    pub huge_pages: HugePages,
+    /// Whether the frees are skipped and counted with `--leak`.
+    pub leak: bool,
+    /// Whether `nr` frees the nodes of the lists once they are unlinked with `--immediate-free`.
+    pub immediate_free: bool,
+    /// Whether the persistent structures execute their write-backs and fences with `--pmem-flush`.
+    pub pmem_flush: bool,
+    /// Whether `SIGUSR1` pauses and resumes the recording and `SIGUSR2` dumps the progress with
+    /// `--signals`.
+    pub signals: bool,
+    /// Whether every operation is checked against a reference map with `--shadow-check`.
+    pub shadow_check: bool,
+    /// The number of keys that a thread collects before merging them with `--mode merge`.
+    pub merge_batch: usize,
+    /// The number of keys that the map holds with `--mode cache`.
+    pub cache_capacity: usize,
+    /// The number of entries that the truncator keeps in the log with `--mode log`.
+    pub log_retain: usize,
+    /// The number of items that the owner pushes before it pops them back with `--mode deque`.
+    pub deque_burst: usize,
+    /// Whether the structure allocates its nodes from a slab of its own.
+    pub slab: bool,
+    /// The percentage of the measurement window that each thread runs for with
+    /// `--thread-lifetime`. The start offsets of the threads are staggered over the window.
+    pub thread_lifetime: Option<u64>,
+    /// The file to write the timeline of the epochs to with `--epoch-dump`.
+    pub epoch_dump: Option<String>,
+    pub epoch_dump_period: Duration,
+    /// The file to write the timeline of the reclamations to with `--reclaim-timeline`.
+    pub reclaim_timeline: Option<String>,
+    pub reclaim_timeline_period: Duration,
+    /// The file to write the outstanding nodes of each node type to with `--alloc-curve`.
+    pub alloc_curve: Option<String>,
+    /// The CSV file to append the measurements of each thread to with `--thread-sidecar`.
+    pub thread_sidecar: Option<ResultsFile>,
 
     pub mem_sampler: MemSampler,
 }
 
+impl Config {
+    /// Releases the allocator caches between the phases of a run, if `--hygiene` is given.
+    pub fn run_hygiene(&self) {
+        self.run_hygiene_with(|| {});
+    }
+
+    /// Drains the reclamation scheme with `drain` and then releases the allocator caches,
+    /// if `--hygiene` is given.
+    ///
+    /// Also advises the heap that has grown in the last phase with `--thp always`.
+    pub fn run_hygiene_with<F: FnOnce()>(&self, drain: F) {
+        if self.hygiene {
+            drain();
+            release_allocator_caches();
+        }
+        huge_pages::advise_heap();
+    }
+
+    /// Creates the cursor of the thread `index` in the trace if `--trace` is given.
+    pub fn trace_cursor(&self, index: usize) -> Option<TraceCursor<'_>> {
+        self.trace.as_ref().map(|trace| trace.cursor(index))
+    }
+
+    /// Creates a per-thread cache of `get` results if `--stale-ttl` is given.
+    pub fn stale_cache<V: Clone>(&self) -> Option<StaleCache<V>> {
+        self.stale_ttl
+            .map(|ttl| StaleCache::new(self.stale_cache_size, ttl))
+    }
+
+    /// Ages the structure after the prefill with the `--age` operations of `churn`, which runs an
+    /// insert or a remove of the key of an index and returns whether it succeeded.
+    ///
+    /// The churn alternates between removing random keys until one is removed and inserting random
+    /// keys until one is inserted, so that the structure keeps the size of the prefill while its
+    /// nodes are replaced at random positions, and the scheme retires and reclaims them.
+    pub fn age<F: FnMut(Op, usize) -> bool>(&self, mut churn: F) {
+        if self.age == 0 {
+            return;
+        }
+        let mut rng = rand::thread_rng();
+        let mut op = Op::Remove;
+        for _ in 0..self.age {
+            if churn(op, self.prefill_dist.sample(&mut rng)) {
+                op = if op == Op::Remove {
+                    Op::Insert
+                } else {
+                    Op::Remove
+                };
+            }
+        }
+        print!("aged... ");
+        stdout().flush().unwrap();
+        log_event!(Info, Prefill, "aged", ops = self.age);
+    }
+
+    /// Returns the key indices for a single thread to prefill, in the decreasing order of their
+    /// keys.
+    ///
+    /// With `--key-universe arithmetic`, the indices are generated without materializing them if
+    /// the order of the keys follows that of the indices.
+    pub fn decreasing_prefill<K: BenchKey>(&self) -> Box<dyn Iterator<Item = usize>> {
+        if self.key_universe == KeyUniverse::Arithmetic && K::INDEX_ORDERED {
+            return Box::new(arithmetic_prefill(self.prefill, self.key_range));
+        }
+        let mut rng = rand::thread_rng();
+        let mut keys = Vec::with_capacity(self.prefill);
+        for _ in 0..self.prefill {
+            keys.push(self.prefill_dist.sample(&mut rng));
+        }
+        // Sorts by the keys, whose order may differ from that of the indices.
+        keys.sort_by_key(|index| Reverse(K::from_index(*index)));
+        Box::new(keys.into_iter())
+    }
+
+    /// Returns the key indices for a single thread to prefill a tree in the order of
+    /// `--tree-shape`, which must not be `random`.
+    pub fn shaped_prefill<K: BenchKey>(&self) -> Box<dyn Iterator<Item = usize>> {
+        match self.tree_shape {
+            TreeShape::Random => panic!("A random tree is prefilled by multiple threads"),
+            TreeShape::Sorted => self.decreasing_prefill::<K>(),
+            TreeShape::Balanced => {
+                let mut keys = self.decreasing_prefill::<K>().collect::<Vec<_>>();
+                // The materialized keys are sampled with replacement.
+                keys.dedup();
+                let mut order = Vec::with_capacity(keys.len());
+                let mut ranges = VecDeque::from([(0, keys.len())]);
+                while let Some((begin, end)) = ranges.pop_front() {
+                    if begin == end {
+                        continue;
+                    }
+                    let mid = begin + (end - begin) / 2;
+                    order.push(keys[mid]);
+                    ranges.push_back((begin, mid));
+                    ranges.push_back((mid + 1, end));
+                }
+                Box::new(order.into_iter())
+            }
+        }
+    }
+
+    /// Creates the shadow of the structure of a run, which checks the operations only if
+    /// `--shadow-check` is given.
+    pub fn shadow(&self) -> Shadow {
+        Shadow::new(self.shadow_check)
+    }
+
+    /// Creates the announcements of the operations of a run, which are sampled for conflicts only
+    /// if `--conflict-sample` is given, and `--sample` times less often with it.
+    pub fn conflicts(&self) -> Conflicts {
+        Conflicts::new(
+            self.threads,
+            self.conflict_sample.map(|period| period * self.sample),
+        )
+    }
+
+    /// Prints the conflict rate of a run and its hottest keys, and adds it to the totals of the
+    /// repetitions.
+    pub fn record_conflicts(&self, conflicts: &Conflicts) {
+        if !conflicts.enabled() {
+            return;
+        }
+        let rate = conflicts.rate();
+        let hottest = conflicts
+            .hottest(HOTTEST_KEYS)
+            .into_iter()
+            .map(|(index, conflicts)| format!("{index} ({conflicts})"))
+            .collect::<Vec<_>>();
+        println!("conflicts: {rate}");
+        if !hottest.is_empty() {
+            println!("keys with the most conflicts: {}", hottest.join(", "));
+        }
+
+        let mut totals = CONFLICTS.lock().unwrap();
+        totals.sampled += rate.sampled;
+        totals.conflicting += rate.conflicting;
+    }
+
+    /// Runs a dedicated reclaimer of `--reclaimers` on the default domain of `hp_pp` for the
+    /// window of a run, which it starts at `gate` with the benchmark threads, and returns what it
+    /// did. The window is extended by the pauses with `--signals`.
+    pub fn run_reclaimer(&self, gate: &StartGate) -> reclaimer::Stats {
+        let reclaimer = Reclaimer::new(&hp_pp::DEFAULT_DOMAIN);
+        gate.wait();
+        let start = Instant::now();
+        let paused = signals::paused_time();
+        reclaimer
+            .run(|| start.elapsed() < self.duration + signals::paused_time().saturating_sub(paused))
+    }
+
+    /// Prints the validation failures of a run of `vbr` with the fallbacks of
+    /// `--validation-policy`, and adds them to the totals of the repetitions.
+    pub fn record_validations(&self, stats: validation::Stats, ops: u64) {
+        let report = ValidationReport { stats, ops };
+        println!("validation: {report}");
+        let mut totals = VALIDATIONS.lock().unwrap();
+        totals.stats.failures += stats.failures;
+        totals.stats.allocations += stats.allocations;
+        totals.stats.reallocations += stats.reallocations;
+        totals.stats.fallbacks += stats.fallbacks;
+        totals.ops += ops;
+    }
+
+    /// Prints what the dedicated reclaimers of a run did, apart from the throughput of the
+    /// benchmark threads, and adds it to the totals of the repetitions.
+    pub fn record_reclaimers(&self, stats: &[reclaimer::Stats]) {
+        if stats.is_empty() {
+            return;
+        }
+        let mut run = reclaimer::Stats::default();
+        for stats in stats {
+            run.scans += stats.scans;
+            run.freed += stats.freed;
+            run.busy += stats.busy;
+            run.elapsed += stats.elapsed;
+        }
+        println!(
+            "reclaimers: {}",
+            ReclaimerReport {
+                threads: stats.len(),
+                utilization: run.utilization(),
+                scans: run.scans,
+                freed: run.freed,
+            }
+        );
+
+        let mut totals = RECLAIMERS.lock().unwrap();
+        totals.scans += run.scans;
+        totals.freed += run.freed;
+        totals.busy += run.busy;
+        totals.elapsed += run.elapsed;
+    }
+
+    /// Prints the steals of a run of the deque mode and the buffers that it retired and reclaimed,
+    /// and adds them to the totals of the repetitions.
+    pub fn record_steals(&self, report: &StealReport) {
+        println!("steals: {report}");
+        let mut totals = STEALS.lock().unwrap();
+        totals.attempts += report.attempts;
+        totals.stolen += report.stolen;
+        totals.lost += report.lost;
+        totals.retired += report.retired;
+        totals.reclaimed += report.reclaimed;
+    }
+
+    /// Creates the curve of the outstanding nodes of each node type if `--alloc-curve` is given.
+    pub fn alloc_curve(&self) -> Option<AllocCurve> {
+        self.alloc_curve.as_ref().map(|_| AllocCurve::new())
+    }
+
+    /// Writes the curve of the outstanding nodes of a run to the `--alloc-curve` file, prints the
+    /// peak and the average of their bytes, and adds them to the totals of the repetitions.
+    pub fn record_alloc_curve(&self, curve: &AllocCurve) {
+        let path = self.alloc_curve.as_ref().unwrap();
+        let samples = curve.dump(path);
+        let bytes = curve.summary();
+        println!(
+            "node allocations: {samples} samples, peak {} bytes, avg {} bytes",
+            bytes.peak, bytes.avg
+        );
+        let mut totals = NODE_BYTES.lock().unwrap();
+        totals.peak = totals.peak.max(bytes.peak);
+        totals.avg += bytes.avg;
+    }
+
+    /// Creates a per-thread cache thrasher if `--thrash-cache` is given.
+    pub fn cache_thrasher(&self) -> Option<CacheThrasher> {
+        (self.thrash_cache > 0).then(|| CacheThrasher::new(self.thrash_cache, self.thrash_period))
+    }
+
+    /// Creates the shared state of the epoch timeline if `--epoch-dump` is given, with a slot for
+    /// each benchmark thread and one for the auxiliary thread.
+    pub fn epoch_dump(&self) -> Option<EpochDump> {
+        self.epoch_dump
+            .as_ref()
+            .map(|_| EpochDump::new(self.threads + 1))
+    }
+
+    /// Creates the timeline of the reclamations if `--reclaim-timeline` is given, with a slot
+    /// for the operations of each benchmark thread.
+    pub fn reclaim_timeline(&self) -> Option<ReclaimTimeline> {
+        self.reclaim_timeline
+            .as_ref()
+            .map(|_| ReclaimTimeline::new(self.threads))
+    }
+
+    /// The injections of `--inject`, separated by spaces, or an empty string without any.
+    pub fn injections_spec(&self) -> String {
+        self.injections
+            .iter()
+            .map(Injection::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Pins the thread of `result` with `--pin`, registers it at `gate`, warms its caches with the
+    /// `--warm-up` `get`s of `get` once every thread has registered, and waits until every thread
+    /// has warmed up to start with the others at once.
+    pub fn start<F: FnMut(usize)>(&self, gate: &StartGate, result: &mut ThreadResult, mut get: F) {
+        if let Some((_, placement)) = &self.pin {
+            placement.pin(result.index);
+        }
+        gate.register();
+        let mut rng = rand::thread_rng();
+        for _ in 0..self.warm_up {
+            get(self.key_dist.sample(&mut rng));
+        }
+        result.start_skew = gate.start();
+    }
+
+    /// Whether the threads register with the scheme before they start, so that the start waits
+    /// for every registration. With `--thread-lifetime`, a thread registers only when it arrives.
+    pub fn registers_early(&self) -> bool {
+        self.thread_lifetime.is_none()
+    }
+
+    /// Waits until the thread of `result` arrives, and returns how long it runs for.
+    ///
+    /// Without `--thread-lifetime`, every thread arrives at once and runs for the whole window.
+    /// Otherwise, the first thread arrives at once, the last one departs at the end of the window,
+    /// and the others arrive at even intervals in between.
+    pub fn arrive(&self, result: &mut ThreadResult) -> Duration {
+        let lifetime = match self.thread_lifetime {
+            None => self.duration,
+            Some(percentage) => {
+                let lifetime = self.duration * percentage as u32 / 100;
+                let offset = (self.duration - lifetime) * result.index as u32
+                    / (self.threads - 1).max(1) as u32;
+                std::thread::sleep(offset);
+                result.window = Some(self.duration);
+                lifetime
+            }
+        };
+        result.arrived();
+        log_event!(
+            Debug,
+            Run,
+            "arrived",
+            thread = result.index,
+            lifetime_ms = lifetime.as_millis()
+        );
+        lifetime
+    }
+
+    /// Whether the thread of `index` is latency-critical with `--critical`.
+    pub fn critical(&self, index: usize) -> bool {
+        index < self.critical
+    }
+
+    /// Whether a thread measures the latency of each of its operations, which a latency-critical
+    /// thread does, and every thread with `--op-deadline` or `--op-latencies`.
+    pub fn timed(&self, critical: bool) -> bool {
+        critical || self.op_deadline.is_some() || self.op_latencies
+    }
+
+    /// Publishes that the thread of `result` has run `ops` operations for `SIGUSR2`, and returns
+    /// whether the recording is paused with `--signals`.
+    #[inline]
+    pub fn paused(&self, result: &ThreadResult, ops: u64) -> bool {
+        if !self.signals {
+            return false;
+        }
+        signals::publish(result.index, ops);
+        signals::paused()
+    }
+
+    /// Waits until the recording is resumed, and extends the run of the thread by the wait.
+    pub fn wait_while_paused(&self, result: &mut ThreadResult) {
+        log_event!(Debug, Run, "paused", thread = result.index);
+        result.paused += signals::wait_while_paused();
+        log_event!(Debug, Run, "resumed", thread = result.index);
+    }
+
+    /// Samples whether the next operation of a thread is a get-or-insert with
+    /// `--get-or-insert-rate`, which it never is on a latency-critical thread.
+    #[inline]
+    pub fn get_or_insert<R: Rng>(&self, critical: bool, rng: &mut R) -> bool {
+        !critical && self.get_or_insert_rate > 0 && rng.gen_ratio(self.get_or_insert_rate, 100)
+    }
+
+    /// Samples the next operation of a thread, which is always a `get` on a latency-critical one.
+    pub fn sample_op<R: Rng>(&self, critical: bool, rng: &mut R) -> Op {
+        if critical {
+            Op::Get
+        } else {
+            Op::OPS[self.op_dist.sample(rng)]
+        }
+    }
+}
+
+/// The minimum number of repetitions before `--target-ci` may stop the measurement.
+const MIN_REPS: usize = 3;
+
+impl Config {
+    /// Runs `run`, repeating it until the 95% confidence interval of the throughput becomes
+    /// narrower than `--target-ci` (or `--max-reps` is reached) if it is given, and then reports
+    /// what the repetitions recorded:
+    ///
+    /// - the frequencies of the CPUs that the threads may run on, sampled throughout, with what
+    ///   may have distorted them;
+    /// - the start skews of the threads, if the benchmark starts them with a `StartGate`;
+    /// - the retries of the structure by cause in the measured windows, if it counts them;
+    /// - the failed CAS of the lists and `nm-tree` in the measured windows, by whether the link or
+    ///   only its tag changed;
+    /// - the failed inserts of `nm-tree` in the measured windows, by whether they helped a remove;
+    /// - the write-backs and fences of a persistent structure;
+    /// - the conflicts of the operations with `--conflict-sample`;
+    /// - what the dedicated reclaimers did with `--reclaimers`;
+    /// - the validation failures of `vbr`;
+    /// - the node allocations with `--alloc-curve`, and the resident memory with `--sampling`;
+    /// - the steals of the deque mode;
+    /// - the frees that the benchmark threads skipped with `--leak`;
+    /// - the throughput and latencies of the thread classes with `--critical`;
+    /// - the operations past `--op-deadline`, and the latencies with `--op-latencies`.
+    pub fn measure<F: FnMut() -> Perf>(&self, run: F) -> Measurement {
+        let freq = FreqSampler::start(topology().available_cpus(self.socket, false));
+        let mut measurement = self.repeat(run);
+        measurement.freq = freq.finish();
+        if let Some(freq) = &measurement.freq {
+            println!("cpu frequency: {freq}");
+            if !freq.warnings.is_empty() {
+                log_event!(
+                    Warn,
+                    Run,
+                    "the frequency scaling may distort the throughput",
+                    warnings = freq.warnings.join(", ")
+                );
+            }
+        }
+        let skews = START_SKEWS.lock().unwrap();
+        if skews.reps > 0 {
+            let report = StartSkew {
+                max: skews.max,
+                avg: skews.sum / skews.reps,
+            };
+            if measurement.reps > 1 {
+                println!("start skew over {} repetitions: {report}", skews.reps);
+            }
+            measurement.start_skew = Some(report);
+        }
+        drop(skews);
+        let retries = retry::measured_retries() / measurement.reps;
+        if retries.total() > 0 {
+            println!("retries over {} repetitions: {retries}", measurement.reps);
+        }
+        let failures = cas::measured_cas_failures() / measurement.reps;
+        if failures.total() > 0 {
+            println!(
+                "cas failures over {} repetitions: {failures}",
+                measurement.reps
+            );
+        }
+        let conflicts = helping::measured_insert_conflicts() / measurement.reps;
+        if conflicts.total() > 0 {
+            println!(
+                "insert conflicts over {} repetitions: {conflicts}",
+                measurement.reps
+            );
+        }
+        let persists = persist::persists();
+        if persists.flushes + persists.fences > 0 {
+            let reps = measurement.reps;
+            println!("persistence: {persists} over {reps} repetitions");
+            measurement.persists = Some(Persists {
+                flushes: persists.flushes / reps,
+                fences: persists.fences / reps,
+            });
+        }
+        if self.conflict_sample.is_some() {
+            let totals = CONFLICTS.lock().unwrap();
+            let reps = measurement.reps as u64;
+            let rate = ConflictRate {
+                sampled: totals.sampled / reps,
+                conflicting: totals.conflicting / reps,
+            };
+            if reps > 1 {
+                println!("conflicts over {reps} repetitions: {rate}");
+            }
+            measurement.conflicts = Some(rate);
+        }
+        if self.reclaimers > 0 {
+            let totals = RECLAIMERS.lock().unwrap();
+            let reps = measurement.reps as u64;
+            let report = ReclaimerReport {
+                threads: self.reclaimers,
+                utilization: totals.utilization(),
+                scans: totals.scans / reps,
+                freed: totals.freed / reps,
+            };
+            if reps > 1 {
+                println!("reclaimers over {reps} repetitions: {report}");
+            }
+            measurement.reclaimers = Some(report);
+        }
+        if self.mm == "vbr" {
+            let totals = VALIDATIONS.lock().unwrap();
+            let reps = measurement.reps as u64;
+            let report = ValidationReport {
+                stats: validation::Stats {
+                    failures: totals.stats.failures / reps,
+                    allocations: totals.stats.allocations / reps,
+                    reallocations: totals.stats.reallocations / reps,
+                    fallbacks: totals.stats.fallbacks / reps,
+                },
+                ops: totals.ops / reps,
+            };
+            if reps > 1 {
+                println!("validation over {reps} repetitions: {report}");
+            }
+            measurement.validations = Some(report);
+        }
+        if self.alloc_curve.is_some() {
+            let totals = NODE_BYTES.lock().unwrap();
+            let bytes = NodeBytes {
+                peak: totals.peak,
+                avg: totals.avg / measurement.reps,
+            };
+            if measurement.reps > 1 {
+                println!(
+                    "node allocations over {} repetitions: peak {} bytes, avg {} bytes",
+                    measurement.reps, bytes.peak, bytes.avg
+                );
+            }
+            measurement.node_bytes = Some(bytes);
+        }
+        if self.sampling {
+            measurement.rss = self.mem_sampler.rss();
+            if let Some(rss) = measurement.rss {
+                println!(
+                    "rss: peak {}, avg {}",
+                    readable_bytes(rss.peak),
+                    readable_bytes(rss.avg)
+                );
+            }
+        }
+        if self.mode == Mode::Deque {
+            let totals = STEALS.lock().unwrap();
+            let reps = measurement.reps as u64;
+            let report = StealReport {
+                attempts: totals.attempts / reps,
+                stolen: totals.stolen / reps,
+                lost: totals.lost / reps,
+                retired: totals.retired / reps,
+                reclaimed: totals.reclaimed / reps,
+            };
+            if reps > 1 {
+                println!("steals over {reps} repetitions: {report}");
+            }
+            measurement.steals = Some(report);
+        }
+        if self.leak {
+            let frees = leak::recorded_frees();
+            let reps = measurement.reps;
+            measurement.leaked = Some(Frees {
+                count: frees.count / reps,
+                bytes: frees.bytes / reps,
+            });
+            println!("leaked: {frees} over {reps} repetitions");
+        }
+        if self.critical > 0 {
+            let classes = CLASSES.lock().unwrap();
+            let reps = measurement.reps as u64;
+            let report = ClassReport {
+                critical_ops_per_sec: classes.critical_ops_per_sec / reps,
+                background_ops_per_sec: classes.background_ops_per_sec / reps,
+                p50: classes.latencies.percentile(0.5),
+                p99: classes.latencies.percentile(0.99),
+                p999: classes.latencies.percentile(0.999),
+            };
+            println!("latency-critical over {reps} repetitions: {report}");
+            if let Some(target) = self.p99_target {
+                let met = if report.p99 <= target {
+                    "met"
+                } else {
+                    "missed"
+                };
+                println!("p99 target of {target:?}: {met}");
+            }
+            measurement.classes = Some(report);
+        }
+        if let Some(deadline) = self.op_deadline {
+            let totals = OVERDUE.lock().unwrap();
+            let reps = measurement.reps as u64;
+            let report = OverdueReport {
+                deadline,
+                overdue: totals.overdue / reps,
+                ops: totals.ops / reps,
+                slowest: totals.slowest,
+            };
+            if reps > 1 {
+                println!("overdue over {reps} repetitions: {report}");
+            }
+            measurement.overdue = Some(report);
+        }
+        if self.op_latencies {
+            let totals = OP_LATENCIES.lock().unwrap();
+            let report = totals.each_ref().map(OpLatencies::of);
+            println!(
+                "op latencies over {} repetitions: {}",
+                measurement.reps,
+                op_latencies_summary(&report)
+            );
+            measurement.op_latencies = Some(report);
+        }
+        measurement
+    }
+
+    fn repeat<F: FnMut() -> Perf>(&self, mut run: F) -> Measurement {
+        let mut run = |rep: usize| {
+            log_event!(Info, Run, "started", rep = rep);
+            let perf = run();
+            log_event!(
+                Info,
+                Run,
+                "finished",
+                rep = rep,
+                ops_per_sec = perf.ops_per_sec,
+                peak_mem = perf.peak_mem
+            );
+            perf
+        };
+        let Some(target_ci) = self.target_ci else {
+            return Measurement::new(&[run(1)]);
+        };
+        let mut perfs = vec![];
+        loop {
+            perfs.push(run(perfs.len() + 1));
+            let measurement = Measurement::new(&perfs);
+            if perfs.len() >= MIN_REPS && measurement.ci.unwrap() <= target_ci {
+                return measurement;
+            }
+            if perfs.len() >= self.max_reps {
+                if let Some(ci) = measurement.ci.filter(|&ci| ci > target_ci) {
+                    log_event!(
+                        Warn,
+                        Run,
+                        "the confidence interval is wider than the target",
+                        reps = perfs.len(),
+                        ci = ci,
+                        target_ci = target_ci
+                    );
+                }
+                return measurement;
+            }
+            println!("{}", measurement);
+        }
+    }
+}
+
 impl fmt::Display for Config {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{}: {} threads, n{}, c{}, g{}, {} bag",
+            "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}: {} threads, n{}, c{}, g{}, {} bag",
             self.ds.to_possible_value().unwrap().get_name(),
+            match self.mode {
+                Mode::Map => "",
+                Mode::Set => " (set)",
+                Mode::Move => " (move)",
+                Mode::Merge => " (merge)",
+                Mode::Cache => " (cache)",
+                Mode::Counter => " (counter)",
+                Mode::Log => " (log)",
+                Mode::Deque => " (deque)",
+            },
+            match self.key_type {
+                KeyType::Usize => "",
+                KeyType::U128 => " (u128 keys)",
+                KeyType::Composite => " (composite keys)",
+            },
+            if self.slab { " (slab)" } else { "" },
+            match self.tree_shape {
+                TreeShape::Random => "",
+                TreeShape::Balanced => " (balanced)",
+                TreeShape::Sorted => " (sorted)",
+            },
+            if self.trace.is_some() { " (trace)" } else { "" },
+            match self.skew {
+                Skew::Uniform => String::new(),
+                Skew::Zipf(theta) => format!(" (zipf {theta})"),
+            },
+            if self.multi_get > 1 {
+                format!(" (multi-get {})", self.multi_get)
+            } else {
+                String::new()
+            },
+            self.range_remove
+                .map(|keys| format!(" (range-remove {keys})"))
+                .unwrap_or_default(),
+            if self.leak { " (leak)" } else { "" },
+            if self.immediate_free {
+                " (immediate free)"
+            } else {
+                ""
+            },
+            if self.update_rate > 0 {
+                format!(" (update {}%)", self.update_rate)
+            } else {
+                String::new()
+            },
+            if self.get_or_insert_rate > 0 {
+                format!(" (get-or-insert {}%)", self.get_or_insert_rate)
+            } else {
+                String::new()
+            },
+            if self.extract_min {
+                " (extract-min)"
+            } else {
+                ""
+            },
+            self.clone_range
+                .map(|keys| format!(" (clone-range {keys} at {}%)", self.clone_range_rate))
+                .unwrap_or_default(),
+            self.range_query
+                .map(|keys| format!(" (range-query {keys} at {}%)", self.range_query_rate))
+                .unwrap_or_default(),
+            self.partial_scan
+                .map(|items| format!(" (partial-scan {items} at {}%)", self.partial_scan_rate))
+                .unwrap_or_default(),
+            self.swap_period
+                .map(|period| format!(" (swap every {period:?})"))
+                .unwrap_or_default(),
+            self.memory_delay
+                .map(|delay| format!(" (memory delay {delay:?})"))
+                .unwrap_or_default(),
+            if self.injections.is_empty() {
+                String::new()
+            } else {
+                format!(" (inject {})", self.injections_spec())
+            },
+            if self.reclaimers > 0 {
+                format!(" ({} reclaimers)", self.reclaimers)
+            } else {
+                String::new()
+            },
+            if self.age > 0 {
+                format!(" (aged {})", self.age)
+            } else {
+                String::new()
+            },
+            if self.critical > 0 {
+                format!(" (critical {})", self.critical)
+            } else {
+                String::new()
+            },
+            self.op_deadline
+                .map(|deadline| format!(" (deadline {deadline:?})"))
+                .unwrap_or_default(),
+            if self.op_latencies {
+                " (op latencies)"
+            } else {
+                ""
+            },
+            self.conflict_sample
+                .map(|period| format!(" (conflicts 1/{period})"))
+                .unwrap_or_default(),
+            if self.sample > 1 {
+                format!(" (sample 1/{})", self.sample)
+            } else {
+                String::new()
+            },
             self.threads,
             self.non_coop,
             self.ops_per_cs,
@@ -109,7 +1258,8 @@ impl fmt::Display for Config {
 }
 
 pub struct BenchWriter {
-    output: Option<Writer<File>>,
+    output: Option<ResultsFile>,
+    baseline: Option<Baseline>,
 }
 
 #[derive(Clone)]
@@ -135,69 +1285,1258 @@ impl fmt::Display for Perf {
     }
 }
 
-fn readable_bytes(num: usize) -> String {
-    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
-    for (i, unit) in UNITS.iter().enumerate() {
-        if num / 2usize.pow(i as u32 * 10) < 1000 {
-            return format!("{:.3} {}", num as f64 / 2f64.powf(i as f64 * 10.0), unit);
+/// The measurement of one benchmark thread.
+#[derive(Default)]
+pub struct ThreadResult {
+    pub ops: u64,
+    /// The number of `get`s served from the stale cache, or that find their key in the cache mode.
+    pub hits: u64,
+    /// The number of `get`s, for the cache mode and `--hhs-eventual-reads`.
+    pub gets: u64,
+    /// The number of steals in the deque mode, and of those that lost the race for an item.
+    pub steal_attempts: u64,
+    pub steals_lost: u64,
+    pub start: Option<Instant>,
+    pub stop: Option<Instant>,
+    /// The length of the thread's local queue of deferred tasks at the end, for the schemes that
+    /// report it.
+    pub deferred_len: usize,
+    /// The peak length of the thread's local queue of deferred tasks.
+    pub deferred_peak: usize,
+    /// The time spent on thrashing the caches, which is excluded from the throughput.
+    pub thrash_time: Duration,
+    /// The socket that the thread was running on when it stopped.
+    pub socket: Option<usize>,
+    /// The index of the thread among the benchmark threads.
+    pub index: usize,
+    /// The measurement window, if the thread runs for only a part of it.
+    pub window: Option<Duration>,
+    /// The latency of the slowest operation of the thread, which is measured with
+    /// `--range-remove` and `--op-deadline`.
+    pub slowest: Duration,
+    /// The number of range removals of the thread, the keys that they removed, and the latency
+    /// of the slowest one.
+    pub range_removals: u64,
+    pub range_removed: u64,
+    pub slowest_range_removal: Duration,
+    /// The frees that the thread had skipped with `--leak` when it arrived.
+    pub arrival_frees: Frees,
+    /// The latencies of the operations of a latency-critical thread with `--critical`.
+    pub latencies: Histogram,
+    /// How long after the release of the threads the thread observed it.
+    pub start_skew: Duration,
+    /// The number of operations that took longer than `--op-deadline`.
+    pub overdue: u64,
+    /// The time that the thread waited while the recording was paused with `--signals`, which is
+    /// excluded from the throughput.
+    pub paused: Duration,
+    /// The operations of the thread by their sampled type, in the order of `Op::OPS`.
+    pub op_counts: [u64; 3],
+    /// The time spent in the operations of each type, for a thread that times its operations.
+    pub op_time: [Duration; 3],
+    /// The latencies of the operations of each type with `--op-latencies`, in the order of
+    /// `Op::OPS`.
+    pub op_latencies: [Histogram; 3],
+    /// The operations that the thread has run since its last timed one, with `--sample`.
+    since_sample: u64,
+}
+
+impl ThreadResult {
+    /// Records that the thread has run `ops` operations since `start`, until now.
+    pub fn record(&mut self, start: Instant, ops: u64) {
+        self.ops = ops;
+        self.start = Some(start);
+        self.stop = Some(Instant::now());
+        self.socket = topology().current_socket();
+        if leak::enabled() {
+            leak::record(leak::local_frees() - self.arrival_frees);
+        }
+        retry::depart();
+        cas::depart();
+        helping::depart();
+    }
+
+    /// Marks the start of the measured window of the thread, whose skipped frees and counts of the
+    /// structures are recorded when it stops.
+    pub fn arrived(&mut self) {
+        self.arrival_frees = leak::local_frees();
+        retry::arrive();
+        cas::arrive();
+        helping::arrive();
+    }
+
+    /// The start of the next operation if the thread times it, i.e., if `timed` and it is the
+    /// `--sample`-th operation since the last timed one.
+    #[inline]
+    pub fn op_start(&mut self, config: &Config, timed: bool) -> Option<Instant> {
+        if !timed {
+            return None;
+        }
+        self.since_sample += 1;
+        if self.since_sample < config.sample {
+            return None;
+        }
+        self.since_sample = 0;
+        Some(Instant::now())
+    }
+
+    /// Counts an operation of the sampled type `op`, or of another kind such as a range query if
+    /// it is `None`, and records its latency if the thread times its operations.
+    #[inline]
+    pub fn record_op(
+        &mut self,
+        config: &Config,
+        critical: bool,
+        op: Option<Op>,
+        op_start: Option<Instant>,
+    ) {
+        if let Some(op) = op {
+            self.op_counts[op as usize] += 1;
+        }
+        if let Some(op_start) = op_start {
+            let latency = op_start.elapsed();
+            if let Some(op) = op {
+                self.op_time[op as usize] += latency;
+                if config.op_latencies {
+                    self.op_latencies[op as usize].record(latency);
+                }
+            }
+            self.record_latency(config, critical, latency);
+        }
+    }
+
+    /// Records the latency of an operation, into the latencies of a latency-critical thread, and
+    /// as overdue if it is past `--op-deadline`. The operation has completed either way.
+    pub fn record_latency(&mut self, config: &Config, critical: bool, latency: Duration) {
+        self.slowest = self.slowest.max(latency);
+        if critical {
+            self.latencies.record(latency);
+        }
+        if config
+            .op_deadline
+            .is_some_and(|deadline| latency > deadline)
+        {
+            self.overdue += 1;
+        }
+    }
+
+    fn ops_per_sec(&self) -> f64 {
+        let start = self.start.expect("a thread did not record its result");
+        let stop = self.stop.unwrap();
+        let ops_per_sec =
+            self.ops as f64 / (stop - start - self.thrash_time - self.paused).as_secs_f64();
+        // A thread that runs for a part of the window contributes to the throughput of the whole
+        // window in proportion.
+        match self.window {
+            Some(window) => {
+                ops_per_sec * (stop - start - self.paused).as_secs_f64() / window.as_secs_f64()
+            }
+            None => ops_per_sec,
         }
     }
-    format!(
-        "{:.3} {}",
-        num as f64 / 2f64.powf((UNITS.len() - 1) as f64 * 10.0),
-        UNITS.last().unwrap()
-    )
 }
 
-impl BenchWriter {
-    pub fn write_record(self, config: &Config, perf: &Perf) {
-        if let Some(mut output) = self.output {
-            output
-                .write_record(&[
-                    // chrono::Local::now().to_rfc3339(),
-                    config
-                        .ds
-                        .to_possible_value()
-                        .unwrap()
-                        .get_name()
-                        .to_string(),
-                    config.mm.clone(),
-                    config.threads.to_string(),
-                    config.bag_size.to_string(),
-                    config.sampling_period.as_millis().to_string(),
-                    config.non_coop.to_string(),
-                    (config.get_rate as u8).to_string(),
-                    config.ops_per_cs.to_string(),
-                    perf.ops_per_sec.to_string(),
-                    perf.peak_mem.to_string(),
-                    perf.avg_mem.to_string(),
-                    perf.peak_garb.to_string(),
-                    perf.avg_garb.to_string(),
-                    config.key_range.to_string(),
-                    config.interval.to_string(),
-                ])
-                .unwrap();
-            output.flush().unwrap();
+/// The measurements of the benchmark threads, in one cacheline-padded slot per thread.
+///
+/// Each thread writes only to its own slot, and the main thread aggregates the slots after
+/// joining the threads, so that recording the results does not contend on shared state.
+pub struct ThreadResults {
+    slots: Box<[CachePadded<ThreadResult>]>,
+}
+
+impl ThreadResults {
+    pub fn new(threads: usize) -> Self {
+        Self {
+            slots: (0..threads)
+                .map(|index| {
+                    CachePadded::new(ThreadResult {
+                        index,
+                        ..Default::default()
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// The slots to hand out to the threads, one each.
+    pub fn slots(&mut self) -> impl Iterator<Item = &mut ThreadResult> {
+        self.slots.iter_mut().map(|slot| &mut **slot)
+    }
+
+    /// The sum of the throughputs of the threads, each over the time between its own start and
+    /// stop timestamps.
+    ///
+    /// Dividing the total number of operations by the nominal interval would overcount the
+    /// threads that stop late, which skews the result when there are many threads.
+    pub fn ops_per_sec(&self) -> u64 {
+        self.slots
+            .iter()
+            .map(|slot| slot.ops_per_sec())
+            .sum::<f64>() as u64
+    }
+
+    /// Prints the throughput of the threads on each socket, if the machine has more than one.
+    pub fn print_sockets(&self) {
+        let topology = topology();
+        if topology.sockets().count() < 2 {
+            return;
+        }
+        let per_socket = topology
+            .sockets()
+            .map(|socket| {
+                let slots = self.slots.iter().filter(|slot| slot.socket == Some(socket));
+                let threads = slots.clone().count();
+                let ops_per_sec = slots.map(|slot| slot.ops_per_sec()).sum::<f64>() as u64;
+                format!("socket {socket}: {threads} threads, {ops_per_sec} ops/s")
+            })
+            .collect::<Vec<_>>();
+        println!("per socket: {}", per_socket.join(", "));
+    }
+
+    /// The total number of `get`s served from the stale caches.
+    pub fn hits(&self) -> u64 {
+        self.slots.iter().map(|slot| slot.hits).sum()
+    }
+
+    /// The total number of operations of the threads.
+    pub fn ops(&self) -> u64 {
+        self.slots.iter().map(|slot| slot.ops).sum()
+    }
+
+    /// The total number of `get`s, for the benchmarks that count them.
+    pub fn gets(&self) -> u64 {
+        self.slots.iter().map(|slot| slot.gets).sum()
+    }
+
+    /// The total numbers of steals, and of those that lost the race for an item, in the deque
+    /// mode.
+    pub fn steals(&self) -> (u64, u64) {
+        let attempts = self.slots.iter().map(|slot| slot.steal_attempts).sum();
+        let lost = self.slots.iter().map(|slot| slot.steals_lost).sum();
+        (attempts, lost)
+    }
+
+    /// Prints the ratio of the `get`s that hit over the threads.
+    pub fn print_hit_rate(&self) {
+        let gets = self.gets();
+        println!(
+            "cache hit rate: {:.2}% ({} of {} gets)",
+            100.0 * self.hits() as f64 / gets.max(1) as f64,
+            self.hits(),
+            gets
+        );
+    }
+
+    /// Prints the range removals and the latency of the slowest other operation over the threads.
+    pub fn print_range_removals(&self) {
+        let removals: u64 = self.slots.iter().map(|slot| slot.range_removals).sum();
+        let removed: u64 = self.slots.iter().map(|slot| slot.range_removed).sum();
+        let slowest_removal = self
+            .slots
+            .iter()
+            .map(|slot| slot.slowest_range_removal)
+            .max();
+        let slowest = self.slots.iter().map(|slot| slot.slowest).max();
+        println!(
+            "range removals: {} removing {} keys, slowest {:?}; slowest other operation: {:?}",
+            removals,
+            removed,
+            slowest_removal.unwrap_or_default(),
+            slowest.unwrap_or_default(),
+        );
+    }
+
+    /// Prints the statistics of the local queues of deferred tasks over the threads.
+    pub fn print_deferred(&self) {
+        let threads = self.slots.len().max(1);
+        let max_peak = self.slots.iter().map(|slot| slot.deferred_peak).max();
+        let peaks: usize = self.slots.iter().map(|slot| slot.deferred_peak).sum();
+        let lens: usize = self.slots.iter().map(|slot| slot.deferred_len).sum();
+        println!(
+            "deferred tasks per thread: max peak {}, avg peak {}, avg at the end {}",
+            max_peak.unwrap_or(0),
+            peaks / threads,
+            lens / threads,
+        );
+    }
+
+    /// Prints the maximum and the average of the start skews of the threads, and adds them to
+    /// the totals of the repetitions.
+    pub fn record_start_skew(&self) {
+        let skews = self.slots.iter().map(|slot| slot.start_skew);
+        let report = StartSkew {
+            max: skews.clone().max().unwrap_or_default(),
+            avg: skews.sum::<Duration>() / self.slots.len().max(1) as u32,
+        };
+        println!("start skew: {report}");
+
+        let mut totals = START_SKEWS.lock().unwrap();
+        totals.max = totals.max.max(report.max);
+        totals.sum += report.avg;
+        totals.reps += 1;
+    }
+
+    /// Prints the operations of the threads that were overdue with `--op-deadline`, and adds them
+    /// to the totals of the repetitions.
+    pub fn record_overdue(&self, config: &Config) {
+        let Some(deadline) = config.op_deadline else {
+            return;
+        };
+        // Only the timed operations of `--sample` are checked against the deadline.
+        let report = OverdueReport {
+            deadline,
+            overdue: self.slots.iter().map(|slot| slot.overdue).sum::<u64>() * config.sample,
+            ops: self.slots.iter().map(|slot| slot.ops).sum(),
+            slowest: self
+                .slots
+                .iter()
+                .map(|slot| slot.slowest)
+                .max()
+                .unwrap_or_default(),
+        };
+        println!("overdue: {report}");
+
+        let mut totals = OVERDUE.lock().unwrap();
+        totals.overdue += report.overdue;
+        totals.ops += report.ops;
+        totals.slowest = totals.slowest.max(report.slowest);
+    }
+
+    /// Appends a row for each thread to `--thread-sidecar`, with its operations by type and its
+    /// timing relative to the first thread that started, so that the fairness and the skew of a
+    /// run can be analyzed offline.
+    pub fn record_threads(&self, config: &Config) {
+        let Some(sidecar) = config.thread_sidecar.as_ref() else {
+            return;
+        };
+        let run = SIDECAR_RUNS.fetch_add(1, Ordering::Relaxed);
+        let first_start = self.slots.iter().filter_map(|slot| slot.start).min();
+        let micros = |duration: Duration| duration.as_micros().to_string();
+        sidecar.append_all(self.slots.iter().map(|slot| {
+            let start = slot.start.expect("a thread did not record its result");
+            let critical = config.critical(slot.index);
+            let timed = config.timed(critical);
+            // The times and the overdue operations are scaled up from those of `--sample`.
+            let op_time = |op: Op| {
+                timed
+                    .then(|| {
+                        (slot.op_time[op as usize].as_micros() * config.sample as u128).to_string()
+                    })
+                    .unwrap_or_default()
+            };
+            [
+                config
+                    .ds
+                    .to_possible_value()
+                    .unwrap()
+                    .get_name()
+                    .to_string(),
+                config.mm.clone(),
+                config.threads.to_string(),
+                (config.get_rate as u8).to_string(),
+                config.key_range.to_string(),
+                run.to_string(),
+                slot.index.to_string(),
+                critical.to_string(),
+                slot.socket
+                    .map(|socket| socket.to_string())
+                    .unwrap_or_default(),
+                micros(start - first_start.unwrap()),
+                micros(slot.stop.unwrap() - start),
+                micros(slot.paused),
+                micros(slot.thrash_time),
+                slot.ops.to_string(),
+                slot.op_counts[Op::Get as usize].to_string(),
+                slot.op_counts[Op::Insert as usize].to_string(),
+                slot.op_counts[Op::Remove as usize].to_string(),
+                slot.ops
+                    .saturating_sub(slot.op_counts.iter().sum())
+                    .to_string(),
+                op_time(Op::Get),
+                op_time(Op::Insert),
+                op_time(Op::Remove),
+                micros(slot.slowest),
+                (slot.overdue * config.sample).to_string(),
+            ]
+        }));
+        println!(
+            "thread sidecar: appended {} threads of run {run}",
+            self.slots.len()
+        );
+    }
+
+    /// Prints the throughput and the latencies of the latency-critical threads and the throughput
+    /// of the others apart, and adds them to the totals of the repetitions, with `--critical`.
+    pub fn record_classes(&self, config: &Config) {
+        if config.critical == 0 {
+            return;
+        }
+        let (critical, background): (Vec<_>, Vec<_>) = self
+            .slots
+            .iter()
+            .partition(|slot| config.critical(slot.index));
+        let mut latencies = Histogram::default();
+        for slot in &critical {
+            latencies.merge(&slot.latencies);
+        }
+        let report = ClassReport {
+            critical_ops_per_sec: critical.iter().map(|slot| slot.ops_per_sec()).sum::<f64>()
+                as u64,
+            background_ops_per_sec: background
+                .iter()
+                .map(|slot| slot.ops_per_sec())
+                .sum::<f64>() as u64,
+            p50: latencies.percentile(0.5),
+            p99: latencies.percentile(0.99),
+            p999: latencies.percentile(0.999),
+        };
+        println!("latency-critical: {report}");
+
+        let mut classes = CLASSES.lock().unwrap();
+        classes.critical_ops_per_sec += report.critical_ops_per_sec;
+        classes.background_ops_per_sec += report.background_ops_per_sec;
+        classes.latencies.merge(&latencies);
+    }
+
+    /// Merges the latencies of the threads by operation type, prints their percentiles, and adds
+    /// them to the totals of the repetitions, with `--op-latencies`.
+    pub fn record_op_latencies(&self, config: &Config) {
+        if !config.op_latencies {
+            return;
+        }
+        let mut latencies: [Histogram; 3] = Default::default();
+        for slot in self.slots.iter() {
+            for (merged, latencies) in latencies.iter_mut().zip(&slot.op_latencies) {
+                merged.merge(latencies);
+            }
+        }
+        let report = latencies.each_ref().map(OpLatencies::of);
+        println!("op latencies: {}", op_latencies_summary(&report));
+
+        let mut totals = OP_LATENCIES.lock().unwrap();
+        for (total, latencies) in totals.iter_mut().zip(&latencies) {
+            total.merge(latencies);
         }
     }
 }
 
-pub fn setup(mm: String) -> (Config, BenchWriter) {
-    let m = Command::new(mm.clone())
-        .arg(
-            Arg::new("data structure")
-                .short('d')
-                .value_parser(value_parser!(DS))
-                .required(true)
-                .ignore_case(true)
+/// The sums of the overdue operations and of all the operations over the repetitions, and the
+/// slowest operation.
+struct OverdueTotals {
+    overdue: u64,
+    ops: u64,
+    slowest: Duration,
+}
+
+static OVERDUE: Mutex<OverdueTotals> = Mutex::new(OverdueTotals {
+    overdue: 0,
+    ops: 0,
+    slowest: Duration::ZERO,
+});
+
+/// The operations that took longer than `--op-deadline`, out of all the operations.
+#[derive(Clone, Copy)]
+pub struct OverdueReport {
+    pub deadline: Duration,
+    pub overdue: u64,
+    pub ops: u64,
+    pub slowest: Duration,
+}
+
+impl fmt::Display for OverdueReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} operations ({:.4}%) past {:?}, slowest {:?}",
+            self.overdue,
+            self.ops,
+            100.0 * self.overdue as f64 / self.ops.max(1) as f64,
+            self.deadline,
+            self.slowest
+        )
+    }
+}
+
+/// The columns of `--thread-sidecar`, one row per thread of a run. `others` counts the operations
+/// of other kinds, such as the range queries. The times are in microseconds, and the times of the
+/// operations by type are empty for a thread that does not time its operations.
+const THREAD_SIDECAR_HEADER: [&str; 23] = [
+    "ds",
+    "mm",
+    "threads",
+    "get_rate",
+    "key_range",
+    "run",
+    "thread",
+    "critical",
+    "socket",
+    "start_offset_us",
+    "elapsed_us",
+    "paused_us",
+    "thrash_us",
+    "ops",
+    "gets",
+    "inserts",
+    "removes",
+    "others",
+    "get_time_us",
+    "insert_time_us",
+    "remove_time_us",
+    "slowest_us",
+    "overdue",
+];
+
+/// The number of runs of the process whose threads were appended to `--thread-sidecar`.
+static SIDECAR_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of the keys with the most conflicts that are printed after a run.
+const HOTTEST_KEYS: usize = 5;
+
+/// The sums of the sampled and the conflicting operations over the repetitions.
+static CONFLICTS: Mutex<ConflictRate> = Mutex::new(ConflictRate {
+    sampled: 0,
+    conflicting: 0,
+});
+
+/// The sums of what the dedicated reclaimers did over the repetitions.
+static RECLAIMERS: Mutex<reclaimer::Stats> = Mutex::new(reclaimer::Stats {
+    scans: 0,
+    freed: 0,
+    busy: Duration::ZERO,
+    elapsed: Duration::ZERO,
+});
+
+/// What the dedicated reclaimers did with `--reclaimers`: the fraction of their time that they
+/// spent in the scans, and the scans and the frees of all of them.
+#[derive(Clone, Copy)]
+pub struct ReclaimerReport {
+    pub threads: usize,
+    pub utilization: f64,
+    pub scans: u64,
+    pub freed: u64,
+}
+
+impl fmt::Display for ReclaimerReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} threads {:.2}% busy, {} scans, {} frees",
+            self.threads,
+            100.0 * self.utilization,
+            self.scans,
+            self.freed
+        )
+    }
+}
+
+/// The highest peak and the sum of the averages of the bytes of the outstanding nodes over the
+/// repetitions.
+static NODE_BYTES: Mutex<NodeBytes> = Mutex::new(NodeBytes { peak: 0, avg: 0 });
+
+/// The sums of the steals and the buffers of the deque mode over the repetitions.
+static VALIDATIONS: Mutex<ValidationReport> = Mutex::new(ValidationReport {
+    stats: validation::Stats {
+        failures: 0,
+        allocations: 0,
+        reallocations: 0,
+        fallbacks: 0,
+    },
+    ops: 0,
+});
+
+/// The validation failures of `vbr` and the fallbacks of `--validation-policy`, with the
+/// operations of the benchmark threads.
+#[derive(Clone, Copy)]
+pub struct ValidationReport {
+    pub stats: validation::Stats,
+    pub ops: u64,
+}
+
+impl ValidationReport {
+    /// The failed validations per operation, which restart the operation at least once each.
+    pub fn failures_per_op(&self) -> f64 {
+        self.stats.failures as f64 / self.ops.max(1) as f64
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} failed reads ({:.4} per operation), {} failed allocations, {} reallocated CAS \
+             owners, {} fallbacks",
+            self.stats.failures,
+            self.failures_per_op(),
+            self.stats.allocations,
+            self.stats.reallocations,
+            self.stats.fallbacks
+        )
+    }
+}
+
+static STEALS: Mutex<StealReport> = Mutex::new(StealReport {
+    attempts: 0,
+    stolen: 0,
+    lost: 0,
+    retired: 0,
+    reclaimed: 0,
+});
+
+/// The steals of the stealers of the deque mode, of which the `lost` ones found an item but lost
+/// the race for it, and the buffers that the owner retired as it resized the deque and that were
+/// reclaimed.
+#[derive(Clone, Copy)]
+pub struct StealReport {
+    pub attempts: u64,
+    pub stolen: u64,
+    pub lost: u64,
+    pub retired: u64,
+    pub reclaimed: u64,
+}
+
+impl StealReport {
+    /// The fraction of the steals that found an item and took it.
+    pub fn success_rate(&self) -> f64 {
+        if self.stolen + self.lost == 0 {
+            return 0.0;
+        }
+        self.stolen as f64 / (self.stolen + self.lost) as f64
+    }
+}
+
+impl fmt::Display for StealReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} attempts, {} stolen, {} lost races ({:.2}% succeeded), {} retired buffers, {} \
+             reclaimed",
+            self.attempts,
+            self.stolen,
+            self.lost,
+            100.0 * self.success_rate(),
+            self.retired,
+            self.reclaimed
+        )
+    }
+}
+
+/// The largest start skew of a thread and the sum of the average start skews over the
+/// repetitions.
+struct StartSkewTotals {
+    max: Duration,
+    sum: Duration,
+    reps: u32,
+}
+
+static START_SKEWS: Mutex<StartSkewTotals> = Mutex::new(StartSkewTotals {
+    max: Duration::ZERO,
+    sum: Duration::ZERO,
+    reps: 0,
+});
+
+/// The largest and the average start skews of the threads.
+#[derive(Clone, Copy)]
+pub struct StartSkew {
+    pub max: Duration,
+    pub avg: Duration,
+}
+
+impl fmt::Display for StartSkew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "max {:?}, avg {:?}", self.max, self.avg)
+    }
+}
+
+/// The sums of the throughputs of the thread classes and their latencies over the repetitions.
+struct ClassTotals {
+    critical_ops_per_sec: u64,
+    background_ops_per_sec: u64,
+    latencies: Histogram,
+}
+
+static CLASSES: Mutex<ClassTotals> = Mutex::new(ClassTotals {
+    critical_ops_per_sec: 0,
+    background_ops_per_sec: 0,
+    latencies: Histogram::new(),
+});
+
+/// The throughput and the latencies of the latency-critical threads, and the throughput of the
+/// background threads with `--critical`.
+#[derive(Clone, Copy)]
+pub struct ClassReport {
+    pub critical_ops_per_sec: u64,
+    pub background_ops_per_sec: u64,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub p999: Duration,
+}
+
+impl fmt::Display for ClassReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ops/s: {}, p50: {:?}, p99: {:?}, p99.9: {:?}; background ops/s: {}",
+            self.critical_ops_per_sec, self.p50, self.p99, self.p999, self.background_ops_per_sec
+        )
+    }
+}
+
+/// The latencies of the operations of each type over the repetitions with `--op-latencies`.
+static OP_LATENCIES: Mutex<[Histogram; 3]> =
+    Mutex::new([Histogram::new(), Histogram::new(), Histogram::new()]);
+
+/// The percentiles of the latencies of the operations of a type.
+#[derive(Clone, Copy)]
+pub struct OpLatencies {
+    pub ops: u64,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub p999: Duration,
+}
+
+impl OpLatencies {
+    fn of(latencies: &Histogram) -> Self {
+        Self {
+            ops: latencies.count(),
+            p50: latencies.percentile(0.5),
+            p90: latencies.percentile(0.9),
+            p99: latencies.percentile(0.99),
+            p999: latencies.percentile(0.999),
+        }
+    }
+}
+
+impl fmt::Display for OpLatencies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ops, p50: {:?}, p90: {:?}, p99: {:?}, p99.9: {:?}",
+            self.ops, self.p50, self.p90, self.p99, self.p999
+        )
+    }
+}
+
+/// The latencies of the types of operations that ran, in the order of `Op::OPS`.
+fn op_latencies_summary(report: &[OpLatencies; 3]) -> String {
+    Op::OPS
+        .iter()
+        .zip(report)
+        .filter(|(_, latencies)| latencies.ops > 0)
+        .map(|(op, latencies)| format!("{op:?} ({latencies})"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The aggregated result of the repetitions of a benchmark.
+pub struct Measurement {
+    /// The mean throughput and average memory usage, and the highest peaks over the repetitions.
+    pub perf: Perf,
+    /// The half-width of the 95% confidence interval of the throughput, relative to its mean (%).
+    /// `None` if the benchmark ran only once.
+    pub ci: Option<f64>,
+    pub reps: usize,
+    /// The ratio of the `get`s that returned a logically removed value with
+    /// `--hhs-eventual-reads`, over all repetitions.
+    pub stale_hit_rate: Option<f64>,
+    /// The frees that the benchmark threads skipped per repetition with `--leak`.
+    pub leaked: Option<Frees>,
+    /// The results of the thread classes over the repetitions with `--critical`.
+    pub classes: Option<ClassReport>,
+    /// The frequencies of the CPUs over the repetitions, if the platform exposes them.
+    pub freq: Option<FreqReport>,
+    /// The largest start skew over the repetitions and the average of their average start skews,
+    /// for the benchmarks that start their threads with a `StartGate`.
+    pub start_skew: Option<StartSkew>,
+    /// The operations past `--op-deadline` per repetition.
+    pub overdue: Option<OverdueReport>,
+    /// The write-backs and fences of a persistent structure per repetition, with its prefill.
+    pub persists: Option<Persists>,
+    /// What the dedicated reclaimers did per repetition with `--reclaimers`.
+    pub reclaimers: Option<ReclaimerReport>,
+    /// The validation failures and the fallbacks per repetition on `vbr`.
+    pub validations: Option<ValidationReport>,
+    /// The sampled and the conflicting operations per repetition with `--conflict-sample`.
+    pub conflicts: Option<ConflictRate>,
+    /// The percentiles of the latencies of each type of operations over the repetitions with
+    /// `--op-latencies`, in the order of `Op::OPS`.
+    pub op_latencies: Option<[OpLatencies; 3]>,
+    /// The steals and the buffers of the deque per repetition with `--mode deque`.
+    pub steals: Option<StealReport>,
+    /// The highest peak and the average of the bytes of the outstanding nodes over the repetitions
+    /// with `--alloc-curve`.
+    pub node_bytes: Option<NodeBytes>,
+    /// The highest peak and the average of the resident set size over the samples of the
+    /// repetitions.
+    pub rss: Option<Rss>,
+}
+
+impl Measurement {
+    fn new(perfs: &[Perf]) -> Self {
+        let reps = perfs.len();
+        let mean = |f: fn(&Perf) -> usize| perfs.iter().map(f).sum::<usize>() / reps;
+        let peak = |f: fn(&Perf) -> usize| perfs.iter().map(f).max().unwrap();
+        let perf = Perf {
+            ops_per_sec: perfs.iter().map(|p| p.ops_per_sec).sum::<u64>() / reps as u64,
+            peak_mem: peak(|p| p.peak_mem),
+            avg_mem: mean(|p| p.avg_mem),
+            peak_garb: peak(|p| p.peak_garb),
+            avg_garb: mean(|p| p.avg_garb),
+        };
+
+        let ci = (reps > 1).then(|| {
+            let avg = perfs.iter().map(|p| p.ops_per_sec as f64).sum::<f64>() / reps as f64;
+            let var = perfs
+                .iter()
+                .map(|p| (p.ops_per_sec as f64 - avg).powi(2))
+                .sum::<f64>()
+                / (reps - 1) as f64;
+            let half_width = student_t_95(reps - 1) * (var / reps as f64).sqrt();
+            if avg > 0.0 {
+                half_width / avg * 100.0
+            } else {
+                0.0
+            }
+        });
+
+        Self {
+            perf,
+            ci,
+            reps,
+            stale_hit_rate: None,
+            leaked: None,
+            classes: None,
+            freq: None,
+            start_skew: None,
+            overdue: None,
+            persists: None,
+            reclaimers: None,
+            validations: None,
+            conflicts: None,
+            op_latencies: None,
+            steals: None,
+            node_bytes: None,
+            rss: None,
+        }
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.perf)?;
+        if let Some(ci) = self.ci {
+            write!(f, ", ci: ±{:.2}%, reps: {}", ci, self.reps)?;
+        }
+        Ok(())
+    }
+}
+
+/// The two-sided 95% critical value of Student's t-distribution with `df` degrees of freedom.
+fn student_t_95(df: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    TABLE.get(df - 1).copied().unwrap_or(1.960)
+}
+
+fn readable_bytes(num: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    for (i, unit) in UNITS.iter().enumerate() {
+        if num / 2usize.pow(i as u32 * 10) < 1000 {
+            return format!("{:.3} {}", num as f64 / 2f64.powf(i as f64 * 10.0), unit);
+        }
+    }
+    format!(
+        "{:.3} {}",
+        num as f64 / 2f64.powf((UNITS.len() - 1) as f64 * 10.0),
+        UNITS.last().unwrap()
+    )
+}
+
+/// The columns of the `-o` file, one row per benchmark, which `--baseline` summarizes too.
+const RESULTS_HEADER: [&str; 79] = [
+    // "timestamp",
+    "ds",
+    "mm",
+    "threads",
+    "bag_size",
+    "sampling_period",
+    "non_coop",
+    "get_rate",
+    "ops_per_cs",
+    "throughput",
+    "peak_mem",
+    "avg_mem",
+    "peak_garb",
+    "avg_garb",
+    "key_range",
+    "interval",
+    "mode",
+    "key_type",
+    "reps",
+    "ci",
+    "tree_shape",
+    "thp",
+    "stale_hit_rate",
+    "trace",
+    "multi_get",
+    "range_remove",
+    "auto_threads",
+    "leaked_frees",
+    "update_rate",
+    "extract_min",
+    "age",
+    "critical",
+    "critical_ops_per_sec",
+    "critical_p99_us",
+    "background_ops_per_sec",
+    "clone_range",
+    "avg_mhz",
+    "freq_warnings",
+    "max_start_skew_us",
+    "avg_start_skew_us",
+    "swap_period_ms",
+    "log_retain",
+    "op_deadline_us",
+    "overdue_ops",
+    "memory_delay_ns",
+    "inject",
+    "flushes",
+    "fences",
+    "get_or_insert_rate",
+    "reclaimers",
+    "reclaimer_util",
+    "conflict_sample",
+    "conflict_rate",
+    "range_query",
+    "distribution",
+    "get_p50_ns",
+    "get_p90_ns",
+    "get_p99_ns",
+    "get_p999_ns",
+    "insert_p50_ns",
+    "insert_p90_ns",
+    "insert_p99_ns",
+    "insert_p999_ns",
+    "remove_p50_ns",
+    "remove_p90_ns",
+    "remove_p99_ns",
+    "remove_p999_ns",
+    "partial_scan",
+    "deque_burst",
+    "steal_success_rate",
+    "reclaimed_buffers",
+    "peak_node_bytes",
+    "avg_node_bytes",
+    "peak_rss",
+    "avg_rss",
+    "sample",
+    "validation_policy",
+    "validation_failures",
+    "validation_fallbacks",
+    "pin",
+];
+
+impl BenchWriter {
+    pub fn write_record(self, config: &Config, measurement: &Measurement) {
+        let perf = &measurement.perf;
+        let record = [
+            // chrono::Local::now().to_rfc3339(),
+            config
+                .ds
+                .to_possible_value()
+                .unwrap()
+                .get_name()
+                .to_string(),
+            config.mm.clone(),
+            config.threads.to_string(),
+            config.bag_size.to_string(),
+            config.sampling_period.as_millis().to_string(),
+            config.non_coop.to_string(),
+            (config.get_rate as u8).to_string(),
+            config.ops_per_cs.to_string(),
+            perf.ops_per_sec.to_string(),
+            perf.peak_mem.to_string(),
+            perf.avg_mem.to_string(),
+            perf.peak_garb.to_string(),
+            perf.avg_garb.to_string(),
+            config.key_range.to_string(),
+            config.interval.to_string(),
+            config
+                .mode
+                .to_possible_value()
+                .unwrap()
+                .get_name()
+                .to_string(),
+            config
+                .key_type
+                .to_possible_value()
+                .unwrap()
+                .get_name()
+                .to_string(),
+            measurement.reps.to_string(),
+            measurement.ci.map(|ci| ci.to_string()).unwrap_or_default(),
+            config
+                .tree_shape
+                .to_possible_value()
+                .unwrap()
+                .get_name()
+                .to_string(),
+            config.huge_pages.describe(),
+            measurement
+                .stale_hit_rate
+                .map(|rate| rate.to_string())
+                .unwrap_or_default(),
+            config.trace_path.clone().unwrap_or_default(),
+            config.multi_get.to_string(),
+            config
+                .range_remove
+                .map(|keys| keys.to_string())
+                .unwrap_or_default(),
+            config
+                .auto_threads
+                .as_ref()
+                .map(|auto| {
+                    let kind = if auto.physical { "physical" } else { "logical" };
+                    format!("{kind} {}", cpu_list(&auto.cpus))
+                })
+                .unwrap_or_default(),
+            measurement
+                .leaked
+                .map(|frees| frees.count.to_string())
+                .unwrap_or_default(),
+            config.update_rate.to_string(),
+            config.extract_min.to_string(),
+            config.age.to_string(),
+            config.critical.to_string(),
+            measurement
+                .classes
+                .map(|c| c.critical_ops_per_sec.to_string())
+                .unwrap_or_default(),
+            measurement
+                .classes
+                .map(|c| c.p99.as_micros().to_string())
+                .unwrap_or_default(),
+            measurement
+                .classes
+                .map(|c| c.background_ops_per_sec.to_string())
+                .unwrap_or_default(),
+            config
+                .clone_range
+                .map(|keys| keys.to_string())
+                .unwrap_or_default(),
+            measurement
+                .freq
+                .as_ref()
+                .map(|freq| freq.avg_mhz.to_string())
+                .unwrap_or_default(),
+            measurement
+                .freq
+                .as_ref()
+                .map(|freq| freq.warnings.join("; "))
+                .unwrap_or_default(),
+            measurement
+                .start_skew
+                .map(|skew| skew.max.as_micros().to_string())
+                .unwrap_or_default(),
+            measurement
+                .start_skew
+                .map(|skew| skew.avg.as_micros().to_string())
+                .unwrap_or_default(),
+            config
+                .swap_period
+                .map(|period| period.as_millis().to_string())
+                .unwrap_or_default(),
+            if config.mode == Mode::Log {
+                config.log_retain.to_string()
+            } else {
+                String::new()
+            },
+            config
+                .op_deadline
+                .map(|deadline| deadline.as_micros().to_string())
+                .unwrap_or_default(),
+            measurement
+                .overdue
+                .map(|overdue| overdue.overdue.to_string())
+                .unwrap_or_default(),
+            config
+                .memory_delay
+                .map(|delay| delay.as_nanos().to_string())
+                .unwrap_or_default(),
+            config.injections_spec(),
+            measurement
+                .persists
+                .map(|persists| persists.flushes.to_string())
+                .unwrap_or_default(),
+            measurement
+                .persists
+                .map(|persists| persists.fences.to_string())
+                .unwrap_or_default(),
+            config.get_or_insert_rate.to_string(),
+            config.reclaimers.to_string(),
+            measurement
+                .reclaimers
+                .map(|reclaimers| format!("{:.4}", reclaimers.utilization))
+                .unwrap_or_default(),
+            config
+                .conflict_sample
+                .map(|period| period.to_string())
+                .unwrap_or_default(),
+            measurement
+                .conflicts
+                .map(|conflicts| format!("{:.6}", conflicts.rate()))
+                .unwrap_or_default(),
+            config
+                .range_query
+                .map(|keys| keys.to_string())
+                .unwrap_or_default(),
+            config.skew.to_string(),
+        ]
+        .into_iter()
+        .chain(Op::OPS.iter().flat_map(|&op| {
+            let latencies = measurement.op_latencies.map(|l| l[op as usize]);
+            let nanos = move |p: fn(&OpLatencies) -> Duration| {
+                latencies
+                    .map(|l| p(&l).as_nanos().to_string())
+                    .unwrap_or_default()
+            };
+            [
+                nanos(|l| l.p50),
+                nanos(|l| l.p90),
+                nanos(|l| l.p99),
+                nanos(|l| l.p999),
+            ]
+        }))
+        .chain([
+            config
+                .partial_scan
+                .map(|items| items.to_string())
+                .unwrap_or_default(),
+            if config.mode == Mode::Deque {
+                config.deque_burst.to_string()
+            } else {
+                String::new()
+            },
+            measurement
+                .steals
+                .map(|steals| format!("{:.6}", steals.success_rate()))
+                .unwrap_or_default(),
+            measurement
+                .steals
+                .map(|steals| steals.reclaimed.to_string())
+                .unwrap_or_default(),
+            measurement
+                .node_bytes
+                .map(|bytes| bytes.peak.to_string())
+                .unwrap_or_default(),
+            measurement
+                .node_bytes
+                .map(|bytes| bytes.avg.to_string())
+                .unwrap_or_default(),
+            measurement
+                .rss
+                .map(|rss| rss.peak.to_string())
+                .unwrap_or_default(),
+            measurement
+                .rss
+                .map(|rss| rss.avg.to_string())
+                .unwrap_or_default(),
+            config.sample.to_string(),
+            if config.mm == "vbr" {
+                config.validation_policy.to_string()
+            } else {
+                String::new()
+            },
+            measurement
+                .validations
+                .map(|v| v.stats.failures.to_string())
+                .unwrap_or_default(),
+            measurement
+                .validations
+                .map(|v| v.stats.fallbacks.to_string())
+                .unwrap_or_default(),
+            config
+                .pin
+                .as_ref()
+                .map(|(pin, _)| pin.to_string())
+                .unwrap_or_default(),
+        ])
+        .collect::<Vec<_>>();
+        if let Some(output) = &self.output {
+            output.append(&record);
+        }
+        if let Some(baseline) = &self.baseline {
+            baseline.check(&RESULTS_HEADER, &record);
+        }
+    }
+}
+
+/// The detected number of threads of `-t auto[:physical|logical]`.
+pub struct AutoThreads {
+    /// The argument of `-t`.
+    pub arg: String,
+    pub physical: bool,
+    /// The CPUs that the threads run on, one per thread.
+    pub cpus: Vec<usize>,
+}
+
+/// Parses the argument of `-t`, which is a number of threads or
+/// `auto[:physical|logical]`.
+fn parse_threads(arg: &str, socket: Option<usize>) -> (usize, Option<AutoThreads>) {
+    let physical = match arg {
+        "auto" | "auto:logical" => false,
+        "auto:physical" => true,
+        _ => {
+            let threads = arg
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid number of threads: {arg}"));
+            return (threads, None);
+        }
+    };
+    let cpus = topology().available_cpus(socket, physical);
+    assert!(!cpus.is_empty(), "There are no CPUs for -t {arg}");
+    let auto = AutoThreads {
+        arg: arg.to_string(),
+        physical,
+        cpus,
+    };
+    (auto.cpus.len(), Some(auto))
+}
+
+/// Parses the argument of `--sample`, which is `1/N` for one in `N` operations.
+fn parse_sample(arg: &str) -> Result<u64, String> {
+    let period = arg
+        .strip_prefix("1/")
+        .ok_or_else(|| format!("expected 1/<N>, got {arg}"))?
+        .parse::<u64>()
+        .map_err(|e| format!("invalid N in {arg}: {e}"))?;
+    if period == 0 {
+        return Err(format!("N must be positive, got {arg}"));
+    }
+    Ok(period)
+}
+
+pub fn setup(mm: String) -> (Config, BenchWriter) {
+    let m = Command::new(mm.clone())
+        .arg(
+            Arg::new("data structure")
+                .short('d')
+                .value_parser(value_parser!(DS))
+                .required_unless_present("print layout")
+                .ignore_case(true)
                 .help("Data structure(s)"),
         )
         .arg(
             Arg::new("threads")
                 .short('t')
-                .value_parser(value_parser!(usize))
-                .required(true)
-                .help("Numbers of threads to run."),
+                .required_unless_present_any(["print layout", "sweep"])
+                .help(
+                    "Numbers of threads to run, or auto (auto:logical) for one per CPU and \
+                     auto:physical for one per physical core, of the --socket if given",
+                ),
         )
         .arg(
             Arg::new("non-coop")
@@ -226,6 +2565,47 @@ pub fn setup(mm: String) -> (Config, BenchWriter) {
                 .help("Key range: [0..RANGE]")
                 .default_value("100000"),
         )
+        .arg(
+            Arg::new("prefill")
+                .long("prefill")
+                .value_parser(value_parser!(usize))
+                .help("The number of keys to insert before the run (default: RANGE / 2)"),
+        )
+        .arg(
+            Arg::new("distribution")
+                .long("distribution")
+                .value_parser(|s: &str| s.parse::<Skew>())
+                .help(
+                    "The distribution of the keys of the operations: uniform, or zipf:<theta> for \
+                     skewed keys whose ranks are hit with the probability of 1 / rank^theta. \
+                     The prefill stays uniform.",
+                )
+                .default_value("uniform"),
+        )
+        .arg(
+            Arg::new("key universe")
+                .long("key-universe")
+                .value_parser(value_parser!(KeyUniverse))
+                .ignore_case(true)
+                .help(
+                    "How a single-threaded prefill generates its keys. arithmetic generates them \
+                     in constant memory, for large and sparsely populated ranges \
+                     (except with the composite keys).",
+                )
+                .default_value("materialized"),
+        )
+        .arg(
+            Arg::new("tree shape")
+                .long("tree-shape")
+                .value_parser(value_parser!(TreeShape))
+                .ignore_case(true)
+                .help(
+                    "The order in which the keys of nm-tree are prefilled. random: random keys \
+                     with multiple threads, balanced: the medians first, so that the tree is \
+                     balanced, sorted: a decreasing order, so that the tree is a path",
+                )
+                .default_value("random"),
+        )
         .arg(
             Arg::new("interval")
                 .short('i')
@@ -257,11 +2637,662 @@ pub fn setup(mm: String) -> (Config, BenchWriter) {
                 .help("The size of deferred bag")
                 .default_value("small"),
         )
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .value_parser(value_parser!(Mode))
+                .ignore_case(true)
+                .help(
+                    "Whether to benchmark the structure as a map or as a key-only set, to move \
+                     keys between two maps, to insert keys by merging batches of them into the \
+                     structure, to use a map of bounded size as a cache, to count the \
+                     occurrences of keys, or to append to a log that a single thread truncates. \
+                     The set mode is available for lists and skip lists, the move mode for the \
+                     hash map on EBR, the merge mode for hhs-list on EBR and CIRC, the cache and \
+                     counter modes for the hash map on NR and EBR (and HP for the counter mode), \
+                     and the log mode for the log on NR, EBR, and HP.",
+                )
+                .default_value("map"),
+        )
+        .arg(
+            Arg::new("key type")
+                .long("key-type")
+                .value_parser(value_parser!(KeyType))
+                .ignore_case(true)
+                .help(
+                    "The type of the keys, to vary the cost of key comparisons. \
+                     Types other than usize are available for nm-tree and bonsai-tree.",
+                )
+                .default_value("usize"),
+        )
+        .arg(
+            Arg::new("prefill schedule")
+                .long("prefill-schedule")
+                .value_parser(value_parser!(Schedule))
+                .ignore_case(true)
+                .help(
+                    "How the keys are distributed over the threads of a multi-threaded prefill. \
+                     static: an equal share per thread, dynamic: chunks from a shared queue",
+                )
+                .default_value("static"),
+        )
+        .arg(
+            Arg::new("age")
+                .long("age")
+                .value_parser(value_parser!(u64))
+                .help(
+                    "Age the structure with a churn of the given number of inserts and removes of \
+                     random keys after the prefill, which keeps its size but fragments its memory \
+                     and warms up the scheme before the measurement",
+                )
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("warm up")
+                .long("warm-up")
+                .value_parser(value_parser!(usize))
+                .help(
+                    "Make each thread warm its caches with the given number of unrecorded gets \
+                     of random keys, or operations of a structure other than a map, once all \
+                     threads have registered, before they start at once",
+                )
+                .default_value("16"),
+        )
         .arg(
             Arg::new("output")
                 .short('o')
                 .help("Output CSV filename. Appends the data if the file already exists."),
         )
+        .arg(
+            Arg::new("output format")
+                .long("output-format")
+                .value_parser(value_parser!(results::Format))
+                .ignore_case(true)
+                .help(
+                    "The format of the -o file. csv: a row per run after a header, json: a JSON \
+                     object per run on a line, with a member for each column",
+                )
+                .default_value("csv"),
+        )
+        .arg(
+            Arg::new("baseline")
+                .long("baseline")
+                .help(
+                    "Compare the throughput and the memory usage with those of the previous run \
+                     of the same configuration stored in the given directory, or store them if \
+                     there is none, and flag a regression past --baseline-threshold",
+                ),
+        )
+        .arg(
+            Arg::new("baseline threshold")
+                .long("baseline-threshold")
+                .value_parser(value_parser!(f64))
+                .help("The change in the wrong direction that --baseline flags (%)")
+                .default_value("5"),
+        )
+        .arg(
+            Arg::new("baseline update")
+                .long("baseline-update")
+                .action(ArgAction::SetTrue)
+                .help("Replace the stored baseline of the configuration with this run"),
+        )
+        .arg(
+            Arg::new("hygiene")
+                .long("hygiene")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Drain the reclamation scheme and release the allocator caches \
+                     after prefilling and after measuring, to isolate consecutive runs",
+                ),
+        )
+        .arg(
+            Arg::new("harris unlink threshold")
+                .long("harris-unlink-threshold")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "The minimum length of a chain of removed nodes that Harris's list unlinks \
+                     while serving get and remove (available on EBR)",
+                )
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("hhs eventual reads")
+                .long("hhs-eventual-reads")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Make get of hhs-list and hash-map return the values of logically removed \
+                     nodes, which is not linearizable (available on EBR)",
+                ),
+        )
+        .arg(
+            Arg::new("stale ttl")
+                .long("stale-ttl")
+                .value_parser(value_parser!(u64).range(1..))
+                .help(
+                    "Serve get from a per-thread cache of results that are at most the given \
+                     microseconds old (available on NR, EBR, and HP)",
+                ),
+        )
+        .arg(
+            Arg::new("stale cache size")
+                .long("stale-cache-size")
+                .value_parser(value_parser!(usize).range(1..))
+                .help("The number of entries of the per-thread cache for --stale-ttl")
+                .default_value("1024"),
+        )
+        .arg(
+            Arg::new("multi get")
+                .long("multi-get")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "Make each get look up the given number of random keys in a batch, with one \
+                     traversal of each bucket of hash-map (available on EBR and HP)",
+                )
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("range remove")
+                .long("range-remove")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "Make the first thread periodically remove the keys of nm-tree or \
+                     bonsai-tree within a random range of the given number of keys, to see the \
+                     latency spikes of the retirement bursts (available on EBR and HP)",
+                ),
+        )
+        .arg(
+            Arg::new("extract min")
+                .long("extract-min")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Make the removes extract the smallest key instead of a random one, which \
+                     concentrates the protection and the retirement on the leftmost path of the \
+                     tree (nm-tree and efrb-tree on NR and EBR)",
+                ),
+        )
+        .arg(
+            Arg::new("critical")
+                .long("critical")
+                .value_parser(value_parser!(usize))
+                .help(
+                    "Make the given number of threads latency-critical readers, which only get \
+                     and whose latencies are reported apart from the throughput of the other \
+                     threads",
+                )
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("p99 target")
+                .long("p99-target")
+                .value_parser(value_parser!(u64))
+                .help("The target of the p99 latency of the --critical threads (us)"),
+        )
+        .arg(
+            Arg::new("op deadline")
+                .long("op-deadline")
+                .value_parser(value_parser!(u64).range(1..))
+                .help(
+                    "Count the operations that take longer than the given deadline as overdue, \
+                     and report their number after the run (us, in the map mode only)",
+                ),
+        )
+        .arg(
+            Arg::new("op latencies")
+                .long("op-latencies")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Record the latency of every operation of every thread, and report the p50, \
+                     p90, p99 and p99.9 latencies of each type of operations after the run (in \
+                     the map mode only)",
+                ),
+        )
+        .arg(
+            Arg::new("conflict sample")
+                .long("conflict-sample")
+                .value_parser(value_parser!(u64).range(1..))
+                .help(
+                    "Check every given number of operations of each thread for another thread \
+                     operating on the same key, and report the rate of the conflicts after the \
+                     run (in the map mode only)",
+                ),
+        )
+        .arg(
+            Arg::new("sample")
+                .long("sample")
+                .value_parser(parse_sample)
+                .help(
+                    "Time one in N operations of each thread that times its operations, and \
+                     check one in N of the operations that --conflict-sample checks (1/N), to \
+                     bound the cost of the instrumentation. The overdue operations and the times \
+                     by type are scaled back up by N",
+                )
+                .default_value("1/1"),
+        )
+        .arg(
+            Arg::new("update rate")
+                .long("update-rate")
+                .value_parser(value_parser!(u32).range(0..=100))
+                .help(
+                    "Make the given percentage of the operations replace the value of the key, \
+                     in place on kv-hash-map and by a remove and an insert on the other \
+                     structures (available on CIRC)",
+                )
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("get or insert rate")
+                .long("get-or-insert-rate")
+                .value_parser(value_parser!(u32).range(0..=100))
+                .help(
+                    "Make the given percentage of the operations get the key, or insert it if it \
+                     is absent, as an upsert",
+                )
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("range remove period")
+                .long("range-remove-period")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("The period between the range removals with --range-remove (ms)")
+                .default_value("100"),
+        )
+        .arg(
+            Arg::new("trace")
+                .long("trace")
+                .help(
+                    "Replay the operations of the given trace file instead of the get rate and \
+                     the key range (available on NR, EBR, and HP)",
+                ),
+        )
+        .arg(
+            Arg::new("trace format")
+                .long("trace-format")
+                .value_parser(value_parser!(TraceFormat))
+                .ignore_case(true)
+                .help("The format of --trace")
+                .default_value("ycsb"),
+        )
+        .arg(
+            Arg::new("trace split")
+                .long("trace-split")
+                .value_parser(value_parser!(TraceSplit))
+                .ignore_case(true)
+                .help(
+                    "How the threads replay --trace. shared: the threads take the next \
+                     operations of the whole trace in turn, partitioned: each thread replays its \
+                     own contiguous part",
+                )
+                .default_value("shared"),
+        )
+        .arg(
+            Arg::new("target ci")
+                .long("target-ci")
+                .value_parser(value_parser!(f64))
+                .help(
+                    "Repeat the benchmark until the 95% confidence interval of the throughput \
+                     is within the given percentage of its mean",
+                ),
+        )
+        .arg(
+            Arg::new("max reps")
+                .long("max-reps")
+                .value_parser(value_parser!(usize).range(1..))
+                .help("The maximum number of repetitions with --target-ci")
+                .default_value("30"),
+        )
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .value_parser(value_parser!(usize))
+                .help(
+                    "Retain the given number of past versions of the tree in a ring, and run an \
+                     extra thread that looks up keys in them (available for bonsai-tree on CIRC)",
+                )
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("history period")
+                .long("history-period")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("The period to record a new version with --history (us)")
+                .default_value("1000"),
+        )
+        .arg(
+            Arg::new("clone range")
+                .long("clone-range")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "Make --clone-range-rate percent of the operations copy the keys in a random \
+                     range of the given number of keys out of a version of the tree (available \
+                     for bonsai-tree on CIRC)",
+                ),
+        )
+        .arg(
+            Arg::new("clone range rate")
+                .long("clone-range-rate")
+                .value_parser(value_parser!(u32).range(1..=100))
+                .help("The percentage of the operations that copy a range with --clone-range")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("range query")
+                .long("range-query")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "Make --range-query-rate percent of the operations collect the values of the \
+                     keys in a random range of the given number of keys (available for h-list, \
+                     hm-list, hhs-list and hash-map on CIRC)",
+                ),
+        )
+        .arg(
+            Arg::new("range query rate")
+                .long("range-query-rate")
+                .value_parser(value_parser!(u32).range(1..=100))
+                .help("The percentage of the operations that scan a range with --range-query")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("partial scan")
+                .long("partial-scan")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "Make --partial-scan-rate percent of the operations iterate over the given \
+                     number of elements from a random key in the order of the keys (available \
+                     for hhs-list on EBR and HP)",
+                ),
+        )
+        .arg(
+            Arg::new("partial scan rate")
+                .long("partial-scan-rate")
+                .value_parser(value_parser!(u32).range(1..=100))
+                .help("The percentage of the operations that scan with --partial-scan")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("swap period")
+                .long("swap-period")
+                .value_parser(value_parser!(u64).range(1..))
+                .help(
+                    "Run an extra thread that rebuilds the whole map with a prefill of its own \
+                     and swaps it in place of the current one with the given period, while the \
+                     other threads go on with the version they loaded (ms, available on CIRC)",
+                ),
+        )
+        .arg(
+            Arg::new("memory delay")
+                .long("memory-delay")
+                .value_parser(value_parser!(u64).range(1..))
+                .help(
+                    "Spin for the given delay after each load of a snapshot, to emulate a far \
+                     memory with a longer latency (ns, available on CIRC with the slow-memory \
+                     feature)",
+                ),
+        )
+        .arg(
+            Arg::new("inject")
+                .long("inject")
+                .value_parser(|s: &str| s.parse::<Injection>())
+                .action(ArgAction::Append)
+                .help(
+                    "Inject faults at a boundary of the scheme as <point>=<probability>[:<ns>]: \
+                     delay a protection (protect) or a retirement (retire) for the given time, \
+                     or skip a reclamation (reclaim), e.g., protect=0.01:1000 (available on HP \
+                     and HP++ with the interpose feature)",
+                ),
+        )
+        .arg(
+            Arg::new("reclaimers")
+                .long("reclaimers")
+                .value_parser(value_parser!(usize))
+                .help(
+                    "Leave the scans and the frees of the retired pointers to the given number of \
+                     dedicated reclaimer threads, which run besides the benchmark threads \
+                     (available on HP and HP++ in the map mode)",
+                )
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("validation policy")
+                .long("validation-policy")
+                .value_parser(|s: &str| s.parse::<Policy>())
+                .help(
+                    "What a thread does after its validations fail (available on VBR). retry: \
+                     restart the operation, fallback:<N>: restart it with a guard that holds the \
+                     epoch after N failures in a row",
+                )
+                .default_value("retry"),
+        )
+        .arg(
+            Arg::new("thrash cache")
+                .long("thrash-cache")
+                .value_parser(value_parser!(usize))
+                .help(
+                    "Make each thread periodically stream through a buffer of the given size (MB) \
+                     to evict the structure from the caches. The time spent on it is excluded \
+                     from the throughput",
+                )
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("thrash period")
+                .long("thrash-period")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("The number of operations between two streams with --thrash-cache")
+                .default_value("1000"),
+        )
+        .arg(
+            Arg::new("merge batch")
+                .long("merge-batch")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "The number of keys that a thread collects before each merge in the merge mode",
+                )
+                .default_value("64"),
+        )
+        .arg(
+            Arg::new("cache capacity")
+                .long("cache-capacity")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "The number of keys that the map holds in the cache mode. Defaults to a \
+                     quarter of the key range.",
+                ),
+        )
+        .arg(
+            Arg::new("log retain")
+                .long("log-retain")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "The number of entries that the truncator keeps in the log mode. It truncates \
+                     the log back to them once it holds twice as many",
+                )
+                .default_value("1024"),
+        )
+        .arg(
+            Arg::new("deque burst")
+                .long("deque-burst")
+                .value_parser(value_parser!(usize).range(1..))
+                .help(
+                    "The number of items that the owner pushes to the deque in the deque mode \
+                     before it pops them back",
+                )
+                .default_value("1024"),
+        )
+        .arg(
+            Arg::new("slab")
+                .long("slab")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Allocate the nodes of the structure from a slab of its own instead of the \
+                     global allocator (available for h-list, hm-list, hhs-list, and hash-map on EBR)",
+                ),
+        )
+        .arg(
+            Arg::new("thread lifetime")
+                .long("thread-lifetime")
+                .value_parser(value_parser!(u64).range(1..=100))
+                .help(
+                    "Run each thread for the given percentage of the measurement window, with \
+                     the arrivals of the threads staggered over the window, to vary the set of \
+                     the running threads during the measurement (map mode only)",
+                ),
+        )
+        .arg(
+            Arg::new("epoch dump")
+                .long("epoch-dump")
+                .help(
+                    "Write a timeline of the epoch and the epochs that the threads are pinned at \
+                     to the given CSV file, to diagnose the stagnation of the epoch (map mode on \
+                     EBR only)",
+                ),
+        )
+        .arg(
+            Arg::new("epoch dump period")
+                .long("epoch-dump-period")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("The period to sample the epochs with --epoch-dump (ms)")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("reclaim timeline")
+                .long("reclaim-timeline")
+                .help(
+                    "Write a timeline of the events of the reclamations and of the total number \
+                     of operations to the given CSV file (map mode on hp, hp-pp, circ-ebr and \
+                     circ-hp only, built with the reclaim-timeline feature)",
+                ),
+        )
+        .arg(
+            Arg::new("reclaim timeline period")
+                .long("reclaim-timeline-period")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("The period to stamp the total number of operations with --reclaim-timeline (ms)")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("alloc curve")
+                .long("alloc-curve")
+                .help(
+                    "Write the number and bytes of the outstanding nodes of each node layout, \
+                     sampled every --sampling-period, to the given CSV file (map mode on nr, ebr, \
+                     hp, hp-pp, circ-ebr and circ-hp only, built with the alloc-tags feature)",
+                ),
+        )
+        .arg(
+            Arg::new("thread sidecar")
+                .long("thread-sidecar")
+                .help(
+                    "Append a row for each thread of each run, with its operations by type and \
+                     its timing, to the given CSV file (map mode only)",
+                ),
+        )
+        .arg(
+            Arg::new("socket")
+                .long("socket")
+                .value_parser(value_parser!(usize))
+                .help("Restrict the threads to the CPUs of the given socket"),
+        )
+        .arg(
+            Arg::new("pin")
+                .long("pin")
+                .value_parser(|s: &str| s.parse::<Pin>())
+                .help(
+                    "Pin each thread to the CPUs before it starts (Linux only). compact: fill the \
+                     CPUs of a core and the cores of a socket first, scatter: spread the threads \
+                     over the sockets and the cores first, numa:<nodes>: deal the threads over \
+                     the given NUMA nodes, e.g., numa:0,2-3 (map mode only)",
+                ),
+        )
+        .arg(
+            Arg::new("thp")
+                .long("thp")
+                .value_parser(value_parser!(HugePages))
+                .ignore_case(true)
+                .help(
+                    "Whether the memory is backed by transparent huge pages. default: the policy \
+                     of the machine, always: advise the arenas and the heap with MADV_HUGEPAGE, \
+                     never: disable them for the process",
+                )
+                .default_value("default"),
+        )
+        .arg(
+            Arg::new("leak")
+                .long("leak")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Skip every free and count it, so that the scheme does its protection work \
+                     without the cost of reclaiming. The memory is never returned",
+                ),
+        )
+        .arg(
+            Arg::new("pmem flush")
+                .long("pmem-flush")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Execute the write-backs (clwb) and fences (sfence) of the persistent \
+                     structures instead of only counting them, for a run on persistent memory",
+                ),
+        )
+        .arg(
+            Arg::new("signals")
+                .long("signals")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Pause and resume the recording on SIGUSR1, and print the progress on SIGUSR2, \
+                     without terminating the run",
+                ),
+        )
+        .arg(
+            Arg::new("shadow check")
+                .long("shadow-check")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Mirror every operation into a reference map under a lock of its key, and \
+                     panic as soon as an outcome differs from it. 1 to 4 threads only",
+                ),
+        )
+        .arg(
+            Arg::new("immediate free")
+                .long("immediate-free")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Free the nodes of the lists of nr as soon as they are unlinked, while the \
+                     readers may still access them, to check under AddressSanitizer that the \
+                     workload creates use-after-free hazards. Debug builds only",
+                ),
+        )
+        .arg(
+            Arg::new("log level")
+                .long("log-level")
+                .value_parser(|s: &str| s.parse::<LogLevels>())
+                .help(
+                    "The level of the events logged to stderr (off, error, warn, info, debug, \
+                     trace), for every phase and then for each phase (setup, prefill, run, \
+                     validate) as <phase>=<level>, e.g., info,run=debug",
+                )
+                .default_value("warn"),
+        )
+        .arg(
+            Arg::new("print layout")
+                .long("print-layout")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Print the size, the alignment and the cache line footprint of the nodes of \
+                     every backend, and exit",
+                ),
+        )
+        .arg(
+            Arg::new("sweep")
+                .long("sweep")
+                .value_parser(|s: &str| s.parse::<Sweep>())
+                .action(ArgAction::Append)
+                .help(
+                    "Run the benchmark for each value of a parameter (threads, key-range, \
+                     prefill, get-rate, update-rate) as <param>=<start>:<end>:<step>, and for \
+                     the cross product of several --sweeps, each in its own process, and write \
+                     the results to the -o file in long format",
+                ),
+        )
         .arg(
             Arg::new("dry run")
                 .long("dry-run")
@@ -269,9 +3300,359 @@ pub fn setup(mm: String) -> (Config, BenchWriter) {
                 .help("Check whether the arguments are parsable, without running a benchmark"),
         )
         .get_matches();
+    m.get_one::<LogLevels>("log level").unwrap().apply();
+
+    if m.get_flag("print layout") {
+        for layout in node_layouts() {
+            println!("{layout}");
+        }
+        std::process::exit(0);
+    }
+
+    let sweeps = m.get_many::<Sweep>("sweep").into_iter().flatten();
+    let sweeps = sweeps.cloned().collect::<Vec<_>>();
+    if !sweeps.is_empty() {
+        let output = m
+            .get_one::<String>("output")
+            .expect("--sweep writes its results to the -o file");
+        assert!(
+            *m.get_one::<results::Format>("output format").unwrap() == results::Format::Csv,
+            "--sweep writes its results as CSV"
+        );
+        let limit = m
+            .get_one::<DS>("data structure")
+            .and_then(|ds| max_threads(&mm, *ds));
+        std::process::exit(sweep::run(&sweeps, output, limit.as_ref()));
+    }
 
     let ds = m.get_one::<DS>("data structure").cloned().unwrap();
-    let threads = m.get_one::<usize>("threads").copied().unwrap();
+    assert!(
+        (ds == DS::BravoTree) == (mm == "locked"),
+        "{ds:?} is not supported for {mm}"
+    );
+    // The NM tree and the elim AB-tree point to their entry nodes inside the structure, which a
+    // compressed pointer cannot represent.
+    assert!(
+        !cfg!(feature = "compressed-pointers")
+            || mm != "nr"
+            || ![DS::NMTree, DS::ElimAbTree].contains(&ds),
+        "{ds:?} is not supported for {mm} with compressed pointers"
+    );
+    assert!(
+        ds != DS::MarkerSkipList || ["ebr", "hp"].contains(&mm.as_str()),
+        "{ds:?} is not supported for {mm}"
+    );
+    assert!(
+        ds != DS::PersistentHashMap || cfg!(feature = "persistent"),
+        "{ds:?} requires the persistent feature"
+    );
+    assert!(
+        ds != DS::PersistentHashMap || ["ebr", "hp"].contains(&mm.as_str()),
+        "{ds:?} is not supported for {mm}"
+    );
+    let pmem_flush = m.get_flag("pmem flush");
+    assert!(
+        !pmem_flush || ds == DS::PersistentHashMap,
+        "--pmem-flush requires a persistent structure"
+    );
+    let mode = m.get_one::<Mode>("mode").copied().unwrap();
+    let signals = m.get_flag("signals");
+    assert!(
+        !signals || (mode == Mode::Map && ["nr", "ebr", "hp"].contains(&mm.as_str())),
+        "--signals is supported only in the map mode of nr, ebr and hp"
+    );
+    assert!(
+        mode != Mode::Set || ["nr", "ebr", "hp"].contains(&mm.as_str()),
+        "The set mode is not supported for {mm}"
+    );
+    assert!(
+        mode != Mode::Move || (ds == DS::HashMap && mm == "ebr"),
+        "The move mode is not supported for {ds:?} on {mm}"
+    );
+    assert!(
+        mode != Mode::Merge
+            || (ds == DS::HHSList && ["ebr", "circ-ebr", "circ-hp"].contains(&mm.as_str())),
+        "The merge mode is not supported for {ds:?} on {mm}"
+    );
+    assert!(
+        mode != Mode::Cache || (ds == DS::HashMap && ["nr", "ebr"].contains(&mm.as_str())),
+        "The cache mode is not supported for {ds:?} on {mm}"
+    );
+    assert!(
+        mode != Mode::Counter || (ds == DS::HashMap && ["nr", "ebr", "hp"].contains(&mm.as_str())),
+        "The counter mode is not supported for {ds:?} on {mm}"
+    );
+    assert!(
+        (ds == DS::Log) == (mode == Mode::Log),
+        "The log is only benchmarked in the log mode"
+    );
+    assert!(
+        mode != Mode::Log || ["nr", "ebr", "hp"].contains(&mm.as_str()),
+        "The log mode is not supported for {mm}"
+    );
+    assert!(
+        (ds == DS::ChaseLev) == (mode == Mode::Deque),
+        "The deque is only benchmarked in the deque mode"
+    );
+    assert!(
+        mode != Mode::Deque || ["ebr", "hp"].contains(&mm.as_str()),
+        "The deque mode is not supported for {mm}"
+    );
+    let slab = m.get_flag("slab");
+    assert!(
+        !slab
+            || (mode == Mode::Map
+                && [DS::HList, DS::HMList, DS::HHSList, DS::HashMap].contains(&ds)
+                && mm == "ebr"),
+        "The slab allocation is not supported for {ds:?} on {mm}"
+    );
+    let thread_lifetime = m.get_one::<u64>("thread lifetime").copied();
+    assert!(
+        thread_lifetime.is_none() || mode == Mode::Map,
+        "Thread lifetimes are not supported in the {mode:?} mode"
+    );
+    let epoch_dump = m.get_one::<String>("epoch dump").cloned();
+    assert!(
+        epoch_dump.is_none() || (mode == Mode::Map && mm == "ebr"),
+        "The epoch dump is not supported in the {mode:?} mode on {mm}"
+    );
+    let reclaim_timeline = m.get_one::<String>("reclaim timeline").cloned();
+    assert!(
+        reclaim_timeline.is_none() || cfg!(feature = "reclaim-timeline"),
+        "The reclamation timeline requires the reclaim-timeline feature"
+    );
+    assert!(
+        reclaim_timeline.is_none()
+            || (mode == Mode::Map && ["hp", "hp-pp", "circ-ebr", "circ-hp"].contains(&mm.as_str())),
+        "The reclamation timeline is not supported in the {mode:?} mode on {mm}"
+    );
+    let alloc_curve = m.get_one::<String>("alloc curve").cloned();
+    assert!(
+        alloc_curve.is_none() || cfg!(feature = "alloc-tags"),
+        "The allocation curve requires the alloc-tags feature"
+    );
+    assert!(
+        alloc_curve.is_none()
+            || (mode == Mode::Map
+                && ["nr", "ebr", "hp", "hp-pp", "circ-ebr", "circ-hp"].contains(&mm.as_str())),
+        "The allocation curve is not supported in the {mode:?} mode on {mm}"
+    );
+    let thread_sidecar = m.get_one::<String>("thread sidecar");
+    assert!(
+        thread_sidecar.is_none() || mode == Mode::Map,
+        "The thread sidecar is not supported in the {mode:?} mode"
+    );
+    let thread_sidecar = thread_sidecar.map(|path| ResultsFile::open(path, &THREAD_SIDECAR_HEADER));
+    let tree_shape = m.get_one::<TreeShape>("tree shape").copied().unwrap();
+    assert!(
+        tree_shape == TreeShape::Random || (mode == Mode::Map && ds == DS::NMTree),
+        "The {tree_shape:?} tree shape is not supported for {ds:?} in the {mode:?} mode"
+    );
+    let key_type = m.get_one::<KeyType>("key type").copied().unwrap();
+    assert!(
+        key_type == KeyType::Usize
+            || (mode == Mode::Map
+                && [DS::NMTree, DS::BonsaiTree].contains(&ds)
+                && ["nr", "ebr", "hp"].contains(&mm.as_str())),
+        "The {key_type:?} keys are not supported for {ds:?} on {mm}"
+    );
+    let harris_unlink_threshold = m
+        .get_one::<usize>("harris unlink threshold")
+        .copied()
+        .unwrap();
+    assert!(
+        harris_unlink_threshold == 1 || mm == "ebr",
+        "The Harris unlink threshold is not supported for {mm}"
+    );
+    let hhs_eventual_reads = m.get_flag("hhs eventual reads");
+    assert!(
+        !hhs_eventual_reads
+            || (mode == Mode::Map && [DS::HHSList, DS::HashMap].contains(&ds) && mm == "ebr"),
+        "The eventually consistent reads are not supported for {ds:?} on {mm}"
+    );
+    let stale_ttl = m
+        .get_one::<u64>("stale ttl")
+        .map(|ttl| Duration::from_micros(*ttl));
+    assert!(
+        stale_ttl.is_none() || (mode == Mode::Map && ["nr", "ebr", "hp"].contains(&mm.as_str())),
+        "The stale cache is not supported for {mm}"
+    );
+    let multi_get = m.get_one::<usize>("multi get").copied().unwrap();
+    assert!(
+        multi_get == 1
+            || (mode == Mode::Map && ds == DS::HashMap && ["ebr", "hp"].contains(&mm.as_str())),
+        "The multi-get is not supported for {ds:?} on {mm}"
+    );
+    assert!(
+        multi_get == 1 || stale_ttl.is_none(),
+        "The multi-get does not go through the stale cache"
+    );
+    let range_remove = m.get_one::<usize>("range remove").copied();
+    assert!(
+        range_remove.is_none()
+            || (mode == Mode::Map
+                && [DS::NMTree, DS::BonsaiTree].contains(&ds)
+                && ["ebr", "hp"].contains(&mm.as_str())),
+        "The range removal is not supported for {ds:?} on {mm}"
+    );
+    let age = m.get_one::<u64>("age").copied().unwrap();
+    assert!(
+        age == 0 || mode == Mode::Map,
+        "The aging is not supported in the {mode:?} mode"
+    );
+    let extract_min = m.get_flag("extract min");
+    assert!(
+        !extract_min
+            || (mode == Mode::Map
+                && [DS::NMTree, DS::EFRBTree].contains(&ds)
+                && ["nr", "ebr"].contains(&mm.as_str())),
+        "The min extraction is not supported for {ds:?} on {mm} in the {mode:?} mode"
+    );
+    assert!(
+        !extract_min || stale_ttl.is_none(),
+        "The min extraction does not go through the stale cache"
+    );
+    let update_rate = m.get_one::<u32>("update rate").copied().unwrap();
+    assert!(
+        update_rate == 0 || (mode == Mode::Map && ["circ-ebr", "circ-hp"].contains(&mm.as_str())),
+        "The updates are not supported for {mm} in the {mode:?} mode"
+    );
+    let get_or_insert_rate = m.get_one::<u32>("get or insert rate").copied().unwrap();
+    assert!(
+        get_or_insert_rate == 0 || mode == Mode::Map,
+        "The get-or-inserts are not supported in the {mode:?} mode"
+    );
+    let history = m.get_one::<usize>("history").copied().unwrap();
+    assert!(
+        history == 0 || (ds == DS::BonsaiTree && ["circ-ebr", "circ-hp"].contains(&mm.as_str())),
+        "The version history is not supported for {ds:?} on {mm}"
+    );
+    let clone_range = m.get_one::<usize>("clone range").copied();
+    assert!(
+        clone_range.is_none()
+            || (mode == Mode::Map
+                && ds == DS::BonsaiTree
+                && ["circ-ebr", "circ-hp"].contains(&mm.as_str())),
+        "The range clones are not supported for {ds:?} on {mm} in the {mode:?} mode"
+    );
+    let range_query = m.get_one::<usize>("range query").copied();
+    assert!(
+        range_query.is_none()
+            || (mode == Mode::Map
+                && [DS::HList, DS::HMList, DS::HHSList, DS::HashMap].contains(&ds)
+                && ["circ-ebr", "circ-hp"].contains(&mm.as_str())),
+        "The range queries are not supported for {ds:?} on {mm} in the {mode:?} mode"
+    );
+    let partial_scan = m.get_one::<usize>("partial scan").copied();
+    assert!(
+        partial_scan.is_none()
+            || (mode == Mode::Map && ds == DS::HHSList && ["ebr", "hp"].contains(&mm.as_str())),
+        "The partial scans are not supported for {ds:?} on {mm} in the {mode:?} mode"
+    );
+    let swap_period = m.get_one::<u64>("swap period").copied();
+    assert!(
+        swap_period.is_none()
+            || (mode == Mode::Map
+                && history == 0
+                && clone_range.is_none()
+                && range_query.is_none()
+                && ["circ-ebr", "circ-hp"].contains(&mm.as_str())),
+        "The map swaps are not supported for {mm} in the {mode:?} mode"
+    );
+    let memory_delay = m
+        .get_one::<u64>("memory delay")
+        .map(|&nanos| Duration::from_nanos(nanos));
+    assert!(
+        memory_delay.is_none() || cfg!(feature = "slow-memory"),
+        "The memory delay requires the slow-memory feature"
+    );
+    assert!(
+        memory_delay.is_none() || ["circ-ebr", "circ-hp"].contains(&mm.as_str()),
+        "The memory delay is not supported for {mm}"
+    );
+    let injections = m.get_many::<Injection>("inject").into_iter().flatten();
+    let injections = injections.copied().collect::<Vec<_>>();
+    assert!(
+        injections.is_empty() || cfg!(feature = "interpose"),
+        "The fault injection requires the interpose feature"
+    );
+    assert!(
+        injections.is_empty() || ["hp", "hp-pp"].contains(&mm.as_str()),
+        "The fault injection is not supported for {mm}"
+    );
+    let reclaimers = m.get_one::<usize>("reclaimers").copied().unwrap();
+    assert!(
+        reclaimers == 0 || (mode == Mode::Map && ["hp", "hp-pp"].contains(&mm.as_str())),
+        "The dedicated reclaimers are not supported for {mm} in the {mode:?} mode"
+    );
+    let validation_policy = m.get_one::<Policy>("validation policy").copied().unwrap();
+    assert!(
+        validation_policy == Policy::Retry || mm == "vbr",
+        "The validation policy is not supported for {mm}"
+    );
+    let immediate_free = m.get_flag("immediate free");
+    assert!(
+        !immediate_free
+            || (mode == Mode::Map
+                && [DS::HList, DS::HMList, DS::HHSList, DS::HashMap].contains(&ds)
+                && mm == "nr"),
+        "The immediate frees are not supported for {ds:?} on {mm} in the {mode:?} mode"
+    );
+    assert!(
+        !immediate_free || cfg!(debug_assertions),
+        "The immediate frees are only available in the debug builds"
+    );
+    let socket = m.get_one::<usize>("socket").copied();
+    let (mut threads, mut auto_threads) =
+        parse_threads(m.get_one::<String>("threads").unwrap(), socket);
+    if let Some(limit) = max_threads(&mm, ds) {
+        if let Some(auto) = auto_threads.as_mut().filter(|_| threads > limit.max) {
+            log_event!(
+                Warn,
+                Setup,
+                "fewer threads than CPUs",
+                cpus = threads,
+                limit = limit
+            );
+            auto.cpus.truncate(limit.max);
+            threads = limit.max;
+        }
+        assert!(
+            threads <= limit.max,
+            "{ds:?} on {mm} runs at most {limit}, but -t {threads} was given"
+        );
+    }
+    let pin = m.get_one::<Pin>("pin").cloned().map(|pin| {
+        assert!(mode == Mode::Map, "--pin is supported only in the map mode");
+        let cpus = match &auto_threads {
+            Some(auto) => auto.cpus.clone(),
+            None => topology().available_cpus(socket, false),
+        };
+        let placement = Placement::new(&pin, topology(), &cpus, &numa_nodes())
+            .unwrap_or_else(|e| panic!("--pin {pin}: {e}"));
+        (pin, placement)
+    });
+    let shadow_check = m.get_flag("shadow check");
+    assert!(
+        !shadow_check || (1..=4).contains(&threads),
+        "--shadow-check runs 1 to 4 threads, but -t {threads} was given"
+    );
+    assert!(
+        !shadow_check
+            || (mode == Mode::Map
+                && !hhs_eventual_reads
+                && stale_ttl.is_none()
+                && multi_get == 1
+                && range_remove.is_none()
+                && !extract_min
+                && update_rate == 0
+                && get_or_insert_rate == 0
+                && clone_range.is_none()
+                && range_query.is_none()
+                && partial_scan.is_none()
+                && swap_period.is_none()),
+        "--shadow-check checks only the gets, inserts and removes of the map mode"
+    );
     let bag_size = match m.get_one::<String>("bag size").unwrap().as_str() {
         "small" => BagSize::Small,
         "large" => BagSize::Large,
@@ -286,8 +3667,89 @@ pub fn setup(mm: String) -> (Config, BenchWriter) {
         _ => unreachable!("get_rate is invalid"),
     };
     let key_range = m.get_one::<usize>("range").copied().unwrap();
-    let prefill = key_range / 2;
-    let key_dist = Uniform::from(0..key_range);
+    let prefill = m
+        .get_one::<usize>("prefill")
+        .copied()
+        .unwrap_or(key_range / 2);
+    let cache_capacity = m
+        .get_one::<usize>("cache capacity")
+        .copied()
+        .unwrap_or((key_range / 4).max(1));
+    let skew = m.get_one::<Skew>("distribution").copied().unwrap();
+    let key_dist = KeyDist::new(skew, key_range);
+    assert!(
+        prefill <= key_range,
+        "The prefill ({prefill}) must not exceed the key range ({key_range})"
+    );
+    let prefill_dist = Uniform::from(0..key_range);
+    let trace_path = m.get_one::<String>("trace").cloned();
+    assert!(
+        trace_path.is_none() || (mode == Mode::Map && ["nr", "ebr", "hp"].contains(&mm.as_str())),
+        "The traces are not supported for {mm} in the {mode:?} mode"
+    );
+    assert!(
+        trace_path.is_none() || skew == Skew::Uniform,
+        "The keys of a trace do not follow --distribution"
+    );
+    assert!(
+        get_or_insert_rate == 0 || (trace_path.is_none() && stale_ttl.is_none()),
+        "The get-or-inserts do not follow a trace or go through the stale cache"
+    );
+    assert!(
+        partial_scan.is_none() || trace_path.is_none(),
+        "The partial scans do not follow a trace"
+    );
+    let critical = m.get_one::<usize>("critical").copied().unwrap();
+    assert!(
+        critical == 0 || mode == Mode::Map,
+        "The latency-critical threads are not supported in the {mode:?} mode"
+    );
+    assert!(
+        critical < threads,
+        "The latency-critical threads need at least one other thread"
+    );
+    assert!(
+        critical == 0
+            || (trace_path.is_none()
+                && update_rate == 0
+                && clone_range.is_none()
+                && range_query.is_none()
+                && partial_scan.is_none()),
+        "The latency-critical threads only get, without a trace, updates, range clones, range \
+         queries or partial scans"
+    );
+    let p99_target = m
+        .get_one::<u64>("p99 target")
+        .map(|&micros| Duration::from_micros(micros));
+    assert!(
+        p99_target.is_none() || critical > 0,
+        "The p99 target is for the --critical threads"
+    );
+    let op_deadline = m
+        .get_one::<u64>("op deadline")
+        .map(|&micros| Duration::from_micros(micros));
+    assert!(
+        op_deadline.is_none() || mode == Mode::Map,
+        "The operation deadline is not supported in the {mode:?} mode"
+    );
+    let op_latencies = m.get_flag("op latencies");
+    assert!(
+        !op_latencies || mode == Mode::Map,
+        "The latencies by operation type are not supported in the {mode:?} mode"
+    );
+    let conflict_sample = m.get_one::<u64>("conflict sample").copied();
+    let sample = m.get_one::<u64>("sample").copied().unwrap();
+    assert!(
+        conflict_sample.is_none() || mode == Mode::Map,
+        "The conflict sampling is not supported in the {mode:?} mode"
+    );
+    let trace = trace_path.as_ref().map(|path| {
+        let format = m.get_one::<TraceFormat>("trace format").copied().unwrap();
+        let split = m.get_one::<TraceSplit>("trace split").copied().unwrap();
+        let trace = Trace::load(path, format, split, key_range, threads);
+        println!("{path}: {trace}");
+        trace
+    });
     let interval = m.get_one::<u64>("interval").copied().unwrap();
     let sampling_period = m.get_one::<u64>("sampling period").copied().unwrap();
     let sampling = sampling_period > 0 && cfg!(all(not(feature = "sanitize"), target_os = "linux"));
@@ -306,54 +3768,65 @@ pub fn setup(mm: String) -> (Config, BenchWriter) {
     };
     let op_dist = WeightedIndex::new(op_weights).unwrap();
 
+    let output_format = *m.get_one::<results::Format>("output format").unwrap();
     let output = m.get_one::<String>("output").map(|output_name| {
-        let output_path = Path::new(output_name);
-        let dir = output_path.parent().unwrap();
-        create_dir_all(dir).unwrap();
-        match OpenOptions::new().read(true).append(true).open(output_path) {
-            Ok(f) => csv::Writer::from_writer(f),
-            Err(_) => {
-                let f = OpenOptions::new()
-                    .read(true)
-                    .write(true)
-                    .create(true)
-                    .open(output_path)
-                    .unwrap();
-                let mut output = csv::Writer::from_writer(f);
-                // NOTE: `write_record` on `bench`
-                output
-                    .write_record([
-                        // "timestamp",
-                        "ds",
-                        "mm",
-                        "threads",
-                        "bag_size",
-                        "sampling_period",
-                        "non_coop",
-                        "get_rate",
-                        "ops_per_cs",
-                        "throughput",
-                        "peak_mem",
-                        "avg_mem",
-                        "peak_garb",
-                        "avg_garb",
-                        "key_range",
-                        "interval",
-                    ])
-                    .unwrap();
-                output.flush().unwrap();
-                output
+        // NOTE: `write_record` on `bench`
+        ResultsFile::open_with(output_name, &RESULTS_HEADER, output_format)
+    });
+    assert!(
+        !m.get_flag("baseline update") || m.get_one::<String>("baseline").is_some(),
+        "--baseline-update replaces the baseline of --baseline"
+    );
+    let baseline = m.get_one::<String>("baseline").map(|dir| {
+        Baseline::open(
+            dir,
+            m.get_one::<f64>("baseline threshold").copied().unwrap(),
+            m.get_flag("baseline update"),
+        )
+    });
+    if alloc_curve.is_some() {
+        assert!(
+            sampling,
+            "The allocation curve is sampled every --sampling-period"
+        );
+        assert!(
+            key_type == KeyType::Usize,
+            "The allocation curve counts the nodes of usize keys"
+        );
+        // The lists and the hash map share the node of `list`.
+        let structure = match ds {
+            DS::HList | DS::HMList | DS::HHSList | DS::HashMap => "list",
+            _ => ds.to_possible_value().unwrap().get_name(),
+        };
+        let mut tags = 0;
+        for layout in node_layouts() {
+            if layout.backend == mm && layout.structure == structure {
+                let tag = Layout::from_size_align(layout.size, layout.align).unwrap();
+                alloc_tags::register(layout.structure, tag);
+                tags += 1;
             }
         }
-    });
+        assert!(tags > 0, "The nodes of {ds:?} on {mm} have no known layout");
+    }
     let mem_sampler = MemSampler::new();
     let config = Config {
         ds,
         mm,
+        mode,
+        key_type,
         threads,
         bag_size,
 
-        aux_thread: if sampling || non_coop > 0 { 1 } else { 0 },
+        aux_thread: if sampling
+            || non_coop > 0
+            || epoch_dump.is_some()
+            || reclaim_timeline.is_some()
+            || alloc_curve.is_some()
+        {
+            1
+        } else {
+            0
+        },
         aux_thread_period: Duration::from_millis(1),
         non_coop,
         non_coop_period: match non_coop {
@@ -368,18 +3841,162 @@ pub fn setup(mm: String) -> (Config, BenchWriter) {
         get_rate,
         op_dist,
         key_dist,
+        skew,
+        prefill_dist,
         prefill,
+        prefill_schedule: m.get_one::<Schedule>("prefill schedule").copied().unwrap(),
+        age,
+        warm_up: m.get_one::<usize>("warm up").copied().unwrap(),
         key_range,
+        key_universe: m.get_one::<KeyUniverse>("key universe").copied().unwrap(),
+        tree_shape,
         interval,
         duration,
         ops_per_cs,
+        hygiene: m.get_flag("hygiene"),
+        harris_unlink_threshold,
+        hhs_eventual_reads,
+        stale_ttl,
+        stale_cache_size: m.get_one::<usize>("stale cache size").copied().unwrap(),
+        multi_get,
+        range_remove,
+        range_remove_period: Duration::from_millis(
+            m.get_one::<u64>("range remove period").copied().unwrap(),
+        ),
+        update_rate,
+        get_or_insert_rate,
+        extract_min,
+        critical,
+        p99_target,
+        op_deadline,
+        op_latencies,
+        conflict_sample,
+        sample,
+        trace,
+        trace_path,
+        target_ci: m.get_one::<f64>("target ci").copied(),
+        max_reps: m.get_one::<usize>("max reps").copied().unwrap(),
+        history,
+        history_period: Duration::from_micros(m.get_one::<u64>("history period").copied().unwrap()),
+        clone_range,
+        clone_range_rate: m.get_one::<u32>("clone range rate").copied().unwrap(),
+        range_query,
+        range_query_rate: m.get_one::<u32>("range query rate").copied().unwrap(),
+        partial_scan,
+        partial_scan_rate: m.get_one::<u32>("partial scan rate").copied().unwrap(),
+        swap_period: swap_period.map(Duration::from_millis),
+        memory_delay,
+        injections,
+        reclaimers,
+        validation_policy,
+        thrash_cache: m.get_one::<usize>("thrash cache").copied().unwrap() << 20,
+        thrash_period: m.get_one::<u64>("thrash period").copied().unwrap(),
+        socket,
+        auto_threads,
+        pin,
+        huge_pages: m.get_one::<HugePages>("thp").copied().unwrap(),
+        leak: m.get_flag("leak"),
+        immediate_free,
+        pmem_flush,
+        signals,
+        shadow_check,
+        merge_batch: m.get_one::<usize>("merge batch").copied().unwrap(),
+        cache_capacity,
+        log_retain: m.get_one::<usize>("log retain").copied().unwrap(),
+        deque_burst: m.get_one::<usize>("deque burst").copied().unwrap(),
+        slab,
+        thread_lifetime,
+        epoch_dump,
+        epoch_dump_period: Duration::from_millis(
+            m.get_one::<u64>("epoch dump period").copied().unwrap(),
+        ),
+        reclaim_timeline,
+        reclaim_timeline_period: Duration::from_millis(
+            m.get_one::<u64>("reclaim timeline period")
+                .copied()
+                .unwrap(),
+        ),
+        alloc_curve,
+        thread_sidecar,
 
         mem_sampler,
     };
 
+    log_event!(
+        Info,
+        Setup,
+        "configured",
+        scheme = config.mm,
+        ds = config.ds.to_possible_value().unwrap().get_name(),
+        threads = config.threads,
+        range = config.key_range,
+        prefill = config.prefill,
+        duration_ms = config.duration.as_millis(),
+    );
     if m.get_flag("dry run") {
         std::process::exit(0);
     }
 
-    (config, BenchWriter { output })
+    if let Some(socket) = config.socket {
+        topology().restrict_to(socket);
+    }
+    if let Some(auto) = &config.auto_threads {
+        println!(
+            "{}: {} threads on CPUs {}",
+            auto.arg,
+            config.threads,
+            cpu_list(&auto.cpus)
+        );
+        if auto.physical && auto.cpus.len() < topology().available_cpus(config.socket, false).len()
+        {
+            // Keeps the threads off the SMT siblings.
+            topology().restrict_to_cpus(&auto.cpus);
+        }
+    }
+    if let Some((pin, placement)) = &config.pin {
+        println!(
+            "--pin {pin}: threads on CPUs {}",
+            placement.describe(config.threads)
+        );
+    }
+    config.huge_pages.apply();
+    if config.leak {
+        leak::enable();
+    }
+    if config.immediate_free {
+        immediate_free::enable();
+    }
+    persist::set_execute(config.pmem_flush);
+    reclaimer::set_dedicated(config.reclaimers > 0);
+    validation::set_policy(config.validation_policy);
+    if config.signals {
+        signals::install(config.threads);
+    }
+    log_event!(
+        Debug,
+        Setup,
+        "initialized",
+        scheme = config.mm,
+        huge_pages = config.huge_pages.describe(),
+        leak = config.leak,
+        immediate_free = config.immediate_free,
+    );
+
+    (config, BenchWriter { output, baseline })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::arithmetic_prefill;
+
+    #[test]
+    fn arithmetic_prefill_bounds() {
+        assert_eq!(arithmetic_prefill(0, 1000).count(), 0);
+        let full = arithmetic_prefill(1000, 1000).collect::<Vec<_>>();
+        assert_eq!(full, (0..1000).rev().collect::<Vec<_>>());
+        let half = arithmetic_prefill(500, 1000).collect::<Vec<_>>();
+        assert!(half.windows(2).all(|pair| pair[0] > pair[1]));
+        // The number of keys is binomial, with a standard deviation of about 16.
+        assert!((400..600).contains(&half.len()));
+    }
 }
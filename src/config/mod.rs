@@ -1,3 +1,5 @@
 //! Shared runtime configuration for map benchmarks.
 
+pub mod harness;
 pub mod map;
+pub mod sweep;
@@ -0,0 +1,339 @@
+//! Sweeps of a map benchmark over a grid of its parameters.
+//!
+//! `--sweep <param>=<start>:<end>:<step>` runs the benchmark for each value of the parameter from
+//! `start` up to `end`, and several sweeps run the cross product of their values, instead of the
+//! shell loops around the binary. Each point runs in a child process of the same binary with the
+//! same arguments but the swept values, as the points of the scripts do, so that a point does not
+//! inherit the heap, the garbage and the counters of the previous ones. The prefilled structure is
+//! not reused across the points for the same reason: a structure that a run has churned is not the
+//! structure that a fresh prefill of the next point would build.
+//!
+//! The results are written to the `-o` file in long format, with a row for each column of the row
+//! of a point, keyed by the point, its swept values, and its data structure and scheme:
+//!
+//! ```text
+//! point,threads,key_range,ds,mm,metric,value
+//! 0,1,1000,hash-map,ebr,throughput,5512039
+//! ```
+//!
+//! The rows of a point are appended at once when it finishes, so that a sweep that is killed keeps
+//! the points that finished. A point whose process fails is logged and skipped. A point with more
+//! threads than the data structure and the scheme can run on the system is skipped without
+//! running it.
+
+use crate::limits::ThreadLimit;
+use crate::log_event;
+use crate::results::ResultsFile;
+use std::fs;
+use std::process::Command;
+use std::str::FromStr;
+
+/// A parameter that a sweep varies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    Threads,
+    KeyRange,
+    Prefill,
+    GetRate,
+    UpdateRate,
+}
+
+impl Param {
+    const ALL: [Param; 5] = [
+        Param::Threads,
+        Param::KeyRange,
+        Param::Prefill,
+        Param::GetRate,
+        Param::UpdateRate,
+    ];
+
+    /// The name of the parameter in `--sweep`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Param::Threads => "threads",
+            Param::KeyRange => "key-range",
+            Param::Prefill => "prefill",
+            Param::GetRate => "get-rate",
+            Param::UpdateRate => "update-rate",
+        }
+    }
+
+    /// The column of the parameter in the results.
+    fn column(self) -> &'static str {
+        match self {
+            Param::Threads => "threads",
+            Param::KeyRange => "key_range",
+            Param::Prefill => "prefill",
+            Param::GetRate => "get_rate",
+            Param::UpdateRate => "update_rate",
+        }
+    }
+
+    /// The option of the benchmark that sets the parameter.
+    fn flag(self) -> &'static str {
+        match self {
+            Param::Threads => "-t",
+            Param::KeyRange => "-r",
+            Param::Prefill => "--prefill",
+            Param::GetRate => "-g",
+            Param::UpdateRate => "--update-rate",
+        }
+    }
+}
+
+/// The values of a parameter that a sweep runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sweep {
+    pub param: Param,
+    pub values: Vec<usize>,
+}
+
+impl FromStr for Sweep {
+    type Err = String;
+
+    /// Parses `<param>=<start>:<end>:<step>`, whose values run from `start` up to `end`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, range) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected <param>=<start>:<end>:<step>, got {s}"))?;
+        let param = Param::ALL
+            .into_iter()
+            .find(|param| param.name() == name)
+            .ok_or_else(|| {
+                let names = Param::ALL.map(Param::name).join(", ");
+                format!("unknown parameter {name}, expected one of {names}")
+            })?;
+        let bounds = range
+            .split(':')
+            .map(|bound| bound.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid range {range}: {e}"))?;
+        let [start, end, step] = bounds[..] else {
+            return Err(format!("expected <start>:<end>:<step>, got {range}"));
+        };
+        if step == 0 || start > end {
+            return Err(format!("empty range {range}"));
+        }
+        Ok(Self {
+            param,
+            values: (start..=end).step_by(step).collect(),
+        })
+    }
+}
+
+/// The points of the cross product of the sweeps, as the values of the sweeps in order, with the
+/// last sweep varying the fastest.
+fn points(sweeps: &[Sweep]) -> Vec<Vec<usize>> {
+    sweeps.iter().fold(vec![Vec::new()], |points, sweep| {
+        points
+            .iter()
+            .flat_map(|point| {
+                sweep.values.iter().map(move |&value| {
+                    let mut point = point.clone();
+                    point.push(value);
+                    point
+                })
+            })
+            .collect()
+    })
+}
+
+/// Whether `arg` is `flag`, followed by its value in the next argument, or `flag` with its value.
+fn matches_flag(arg: &str, flag: &str) -> Option<bool> {
+    if arg == flag {
+        return Some(true);
+    }
+    let rest = arg.strip_prefix(flag)?;
+    let attached = rest.starts_with('=') || (!flag.starts_with("--") && !rest.is_empty());
+    attached.then_some(false)
+}
+
+/// The arguments of a point: `args` without the swept options, the sweeps and the output, with
+/// the values of the point and `output`.
+fn point_args(args: &[String], sweeps: &[Sweep], point: &[usize], output: &str) -> Vec<String> {
+    let removed = sweeps
+        .iter()
+        .map(|sweep| sweep.param.flag())
+        .chain(["--sweep", "-o"])
+        .collect::<Vec<_>>();
+    let mut kept = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match removed.iter().find_map(|flag| matches_flag(arg, flag)) {
+            Some(true) => {
+                args.next();
+            }
+            Some(false) => {}
+            None => kept.push(arg.clone()),
+        }
+    }
+    for (sweep, value) in sweeps.iter().zip(point) {
+        kept.extend([sweep.param.flag().to_string(), value.to_string()]);
+    }
+    kept.extend(["-o".to_string(), output.to_string()]);
+    kept
+}
+
+/// The number of threads of a point, if it is beyond `limit`.
+fn exceeds(sweeps: &[Sweep], point: &[usize], limit: Option<&ThreadLimit>) -> Option<usize> {
+    let limit = limit?;
+    sweeps
+        .iter()
+        .zip(point)
+        .find(|(sweep, _)| sweep.param == Param::Threads)
+        .map(|(_, &threads)| threads)
+        .filter(|&threads| threads > limit.max)
+}
+
+/// Runs every point of the sweeps, and writes their results to `output`. The points with more
+/// threads than `limit` are skipped. Returns the exit code of the sweep, which is 1 if a point
+/// failed.
+pub fn run(sweeps: &[Sweep], output: &str, limit: Option<&ThreadLimit>) -> i32 {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let exe = std::env::current_exe().unwrap();
+    let header = ["point"]
+        .into_iter()
+        .chain(sweeps.iter().map(|sweep| sweep.param.column()))
+        .chain(["ds", "mm", "metric", "value"])
+        .collect::<Vec<_>>();
+    let results = ResultsFile::open(output, &header);
+    let point_output = format!("{output}.point.{}.csv", std::process::id());
+
+    let points = points(sweeps);
+    let mut failed = 0;
+    let mut skipped = 0;
+    for (index, point) in points.iter().enumerate() {
+        if let Some(threads) = exceeds(sweeps, point, limit) {
+            log_event!(
+                Warn,
+                Run,
+                "sweep point skipped",
+                point = index,
+                threads = threads,
+                limit = limit.unwrap()
+            );
+            skipped += 1;
+            continue;
+        }
+        let point_args = point_args(&args, sweeps, point, &point_output);
+        log_event!(
+            Info,
+            Run,
+            "sweep point",
+            point = index,
+            points = points.len(),
+            args = point_args.join(" "),
+        );
+        let status = Command::new(&exe).args(&point_args).status().unwrap();
+        let rows = fs::read(&point_output).ok();
+        let _ = fs::remove_file(&point_output);
+        if !status.success() {
+            log_event!(
+                Warn,
+                Run,
+                "sweep point failed",
+                point = index,
+                status = status
+            );
+            failed += 1;
+            continue;
+        }
+        // A dry run writes no results.
+        let Some(rows) = rows else { continue };
+
+        let mut reader = csv::Reader::from_reader(rows.as_slice());
+        let columns = reader.headers().unwrap().clone();
+        let row = reader.records().last().unwrap().unwrap();
+        let field = |name: &str| {
+            let column = columns.iter().position(|column| column == name).unwrap();
+            row[column].to_string()
+        };
+        let key = [index.to_string()]
+            .into_iter()
+            .chain(point.iter().map(usize::to_string))
+            .chain([field("ds"), field("mm")])
+            .collect::<Vec<_>>();
+        let swept = sweeps
+            .iter()
+            .map(|sweep| sweep.param.column())
+            .collect::<Vec<_>>();
+        results.append_all(
+            columns
+                .iter()
+                .zip(row.iter())
+                .filter_map(|(metric, value)| {
+                    if metric == "ds" || metric == "mm" || swept.contains(&metric) {
+                        return None;
+                    }
+                    let mut record = key.clone();
+                    record.extend([metric.to_string(), value.to_string()]);
+                    Some(record)
+                }),
+        );
+    }
+    println!(
+        "sweep: {} points, {} failed, {} skipped",
+        points.len(),
+        failed,
+        skipped
+    );
+    (failed > 0) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exceeds, point_args, points, Param, Sweep};
+    use crate::limits::ThreadLimit;
+
+    #[test]
+    fn parse() {
+        let sweep = "threads=1:16:5".parse::<Sweep>().unwrap();
+        assert_eq!(sweep.param, Param::Threads);
+        assert_eq!(sweep.values, [1, 6, 11, 16]);
+        assert_eq!(
+            "get-rate=0:3:1".parse::<Sweep>().unwrap().values,
+            [0, 1, 2, 3]
+        );
+        assert!("buckets=1:2:1".parse::<Sweep>().is_err());
+        assert!("threads=1:2".parse::<Sweep>().is_err());
+        assert!("threads=4:2:1".parse::<Sweep>().is_err());
+        assert!("threads=1:2:0".parse::<Sweep>().is_err());
+    }
+
+    #[test]
+    fn cross_product() {
+        let sweeps = ["threads=1:2:1", "key-range=10:30:10"]
+            .map(|s| s.parse::<Sweep>().unwrap())
+            .to_vec();
+        let points = points(&sweeps);
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0], [1, 10]);
+        assert_eq!(points[1], [1, 20]);
+        assert_eq!(points[5], [2, 30]);
+
+        let args = "-d hash-map -t 8 -r1000 --sweep threads=1:2:1 --sweep=key-range=10:30:10 \
+                    -i 1 -o out.csv"
+            .split(' ')
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            point_args(&args, &sweeps, &points[1], "point.csv").join(" "),
+            "-d hash-map -i 1 -t 1 -r 20 -o point.csv"
+        );
+    }
+
+    #[test]
+    fn skip_beyond_limit() {
+        let sweeps = ["key-range=10:20:10", "threads=1:16:5"]
+            .map(|s| s.parse::<Sweep>().unwrap())
+            .to_vec();
+        let limit = ThreadLimit {
+            max: 8,
+            source: "test".to_string(),
+        };
+        assert_eq!(exceeds(&sweeps, &[10, 6], Some(&limit)), None);
+        assert_eq!(exceeds(&sweeps, &[10, 11], Some(&limit)), Some(11));
+        assert_eq!(exceeds(&sweeps, &[10, 11], None), None);
+        assert_eq!(exceeds(&sweeps[..1], &[10], Some(&limit)), None);
+    }
+}
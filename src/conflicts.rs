@@ -0,0 +1,180 @@
+//! The sampled rate at which the operations of a run conflict on their keys, for
+//! `--conflict-sample`.
+//!
+//! Every operation of a benchmark thread announces its key in a slot of its thread for as long as
+//! it runs, and every `period`-th one of a thread is sampled: it scans the slots of the other
+//! threads when it starts, and conflicts if one of them announces its key. The conflict rate is
+//! thus the fraction of the operations that start while another thread is operating on their key,
+//! which tells whether the key distribution produced the contention it was meant to. An
+//! announcement is a plain store to a line of the thread, and only the sampled operations scan the
+//! others, with a fence, so that a conflict that started a few nanoseconds earlier may be missed.
+
+use crossbeam_utils::CachePadded;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{fence, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The announcement of a thread, which only the thread writes but for the reports.
+#[derive(Default)]
+struct Slot {
+    /// The index of the key of the current operation plus one, or 0 between the operations.
+    key: AtomicUsize,
+    ops: AtomicU64,
+    sampled: AtomicU64,
+    conflicting: AtomicU64,
+    /// The conflicting operations by the index of their key.
+    keys: Mutex<HashMap<usize, u64>>,
+}
+
+/// The sampled operations of a run, and those of them that conflicted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConflictRate {
+    pub sampled: u64,
+    pub conflicting: u64,
+}
+
+impl ConflictRate {
+    pub fn rate(&self) -> f64 {
+        self.conflicting as f64 / self.sampled.max(1) as f64
+    }
+}
+
+impl fmt::Display for ConflictRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} sampled operations ({:.3}%) started while another thread operated on \
+             their key",
+            self.conflicting,
+            self.sampled,
+            100.0 * self.rate()
+        )
+    }
+}
+
+/// The announcements of the threads of one run, which are empty and sample nothing without
+/// `--conflict-sample`.
+pub struct Conflicts {
+    slots: Box<[CachePadded<Slot>]>,
+    period: u64,
+}
+
+impl Conflicts {
+    /// Samples every `period`-th operation of each of the `threads` threads, if `period` is given.
+    pub fn new(threads: usize, period: Option<u64>) -> Self {
+        let slots = if period.is_some() { threads } else { 0 };
+        Self {
+            slots: (0..slots).map(|_| Default::default()).collect(),
+            period: period.unwrap_or(1),
+        }
+    }
+
+    /// Announces that the thread of `thread` starts an operation on the key of `index`, and
+    /// checks whether it conflicts if it is sampled.
+    #[inline]
+    pub fn enter(&self, thread: usize, index: usize) {
+        let Some(slot) = self.slots.get(thread) else {
+            return;
+        };
+        slot.key.store(index + 1, Ordering::Relaxed);
+        let ops = slot.ops.load(Ordering::Relaxed) + 1;
+        slot.ops.store(ops, Ordering::Relaxed);
+        if ops % self.period != 0 {
+            return;
+        }
+        fence(Ordering::SeqCst);
+        let conflicts = self
+            .slots
+            .iter()
+            .enumerate()
+            .any(|(other, s)| other != thread && s.key.load(Ordering::Relaxed) == index + 1);
+        slot.sampled
+            .store(slot.sampled.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+        if conflicts {
+            slot.conflicting.store(
+                slot.conflicting.load(Ordering::Relaxed) + 1,
+                Ordering::Relaxed,
+            );
+            *slot.keys.lock().unwrap().entry(index).or_default() += 1;
+        }
+    }
+
+    /// Announces that the thread of `thread` finished its operation.
+    #[inline]
+    pub fn exit(&self, thread: usize) {
+        if let Some(slot) = self.slots.get(thread) {
+            slot.key.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether the operations are sampled.
+    pub fn enabled(&self) -> bool {
+        !self.slots.is_empty()
+    }
+
+    /// The sampled and the conflicting operations of all the threads so far.
+    pub fn rate(&self) -> ConflictRate {
+        let mut rate = ConflictRate::default();
+        for slot in self.slots.iter() {
+            rate.sampled += slot.sampled.load(Ordering::Relaxed);
+            rate.conflicting += slot.conflicting.load(Ordering::Relaxed);
+        }
+        rate
+    }
+
+    /// The indices of the `n` keys with the most conflicting operations, with their conflicts.
+    pub fn hottest(&self, n: usize) -> Vec<(usize, u64)> {
+        let mut keys = HashMap::<usize, u64>::new();
+        for slot in self.slots.iter() {
+            for (&index, &conflicts) in slot.keys.lock().unwrap().iter() {
+                *keys.entry(index).or_default() += conflicts;
+            }
+        }
+        let mut keys = keys.into_iter().collect::<Vec<_>>();
+        keys.sort_by_key(|&(index, conflicts)| (std::cmp::Reverse(conflicts), index));
+        keys.truncate(n);
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConflictRate, Conflicts};
+
+    #[test]
+    fn samples_conflicts() {
+        let conflicts = Conflicts::new(3, Some(2));
+        // Only the second operation of a thread is sampled.
+        conflicts.enter(1, 5);
+        conflicts.enter(0, 5);
+        conflicts.exit(0);
+        conflicts.enter(0, 5);
+        conflicts.exit(0);
+        conflicts.enter(2, 7);
+        conflicts.enter(2, 5);
+        conflicts.exit(2);
+        conflicts.exit(1);
+        conflicts.enter(0, 5);
+        conflicts.enter(0, 5);
+        assert_eq!(
+            conflicts.rate(),
+            ConflictRate {
+                sampled: 3,
+                conflicting: 2,
+            }
+        );
+        assert_eq!(conflicts.hottest(3), vec![(5, 2)]);
+    }
+
+    #[test]
+    fn disabled() {
+        let conflicts = Conflicts::new(2, None);
+        assert!(!conflicts.enabled());
+        conflicts.enter(0, 1);
+        conflicts.enter(1, 1);
+        conflicts.exit(0);
+        assert_eq!(conflicts.rate(), ConflictRate::default());
+        assert!(conflicts.hottest(1).is_empty());
+    }
+}
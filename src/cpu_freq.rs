@@ -0,0 +1,178 @@
+//! The frequency scaling of the CPUs while a benchmark is measured.
+//!
+//! A governor other than `performance` scales the frequency of a CPU with its load, and turbo
+//! boost raises it while the package has thermal headroom, so two runs of the same configuration
+//! may run at different frequencies, and a comparison of their throughputs partly compares the
+//! frequencies. The governors of the CPUs and the turbo state are read from `cpufreq` when a
+//! measurement starts, and the current frequencies of the CPUs are sampled every `PERIOD` while it
+//! runs. The average frequency and the warnings are printed and recorded with the results.
+
+use std::fmt;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// The period of the samples of the frequencies.
+const PERIOD: Duration = Duration::from_millis(100);
+
+/// The spread of the sampled frequencies, relative to their average, beyond which they drifted.
+const DRIFT_TOLERANCE: f64 = 0.1;
+
+/// Reads the current frequency of `cpu` in MHz.
+fn cur_mhz(cpu: usize) -> Option<u64> {
+    let path = format!("/sys/devices/system/cpu/cpu{cpu}/cpufreq/scaling_cur_freq");
+    let khz = fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()?;
+    Some(khz / 1000)
+}
+
+/// Reads the governors of `cpus`, without duplicates.
+fn governors(cpus: &[usize]) -> Vec<String> {
+    let mut governors = Vec::new();
+    for cpu in cpus {
+        let path = format!("/sys/devices/system/cpu/cpu{cpu}/cpufreq/scaling_governor");
+        if let Ok(governor) = fs::read_to_string(path) {
+            let governor = governor.trim().to_string();
+            if !governors.contains(&governor) {
+                governors.push(governor);
+            }
+        }
+    }
+    governors
+}
+
+/// Whether turbo boost is enabled, from `intel_pstate` or else the generic `boost` knob.
+fn turbo() -> Option<bool> {
+    if let Ok(no_turbo) = fs::read_to_string("/sys/devices/system/cpu/intel_pstate/no_turbo") {
+        return Some(no_turbo.trim() == "0");
+    }
+    let boost = fs::read_to_string("/sys/devices/system/cpu/cpufreq/boost").ok()?;
+    Some(boost.trim() == "1")
+}
+
+/// Samples the frequencies of a set of CPUs on a thread of its own until it is finished.
+pub struct FreqSampler {
+    governors: Vec<String>,
+    turbo: Option<bool>,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Vec<u64>>,
+}
+
+impl FreqSampler {
+    /// Reads the governors of `cpus` and the turbo state, and starts sampling the average of the
+    /// frequencies of `cpus`.
+    pub fn start(cpus: Vec<usize>) -> Self {
+        let governors = governors(&cpus);
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut samples = Vec::new();
+                while !stop.load(Ordering::Relaxed) {
+                    let mhz = cpus
+                        .iter()
+                        .filter_map(|&cpu| cur_mhz(cpu))
+                        .collect::<Vec<_>>();
+                    if !mhz.is_empty() {
+                        samples.push(mhz.iter().sum::<u64>() / mhz.len() as u64);
+                    }
+                    thread::sleep(PERIOD);
+                }
+                samples
+            })
+        };
+        Self {
+            governors,
+            turbo: turbo(),
+            stop,
+            handle,
+        }
+    }
+
+    /// Stops sampling and reports the frequencies, or `None` if the platform exposes none.
+    pub fn finish(self) -> Option<FreqReport> {
+        self.stop.store(true, Ordering::Relaxed);
+        let samples = self.handle.join().unwrap();
+        FreqReport::new(&samples, self.governors, self.turbo)
+    }
+}
+
+/// The frequencies of the CPUs over a measurement, and what may have distorted them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreqReport {
+    pub avg_mhz: u64,
+    pub min_mhz: u64,
+    pub max_mhz: u64,
+    pub warnings: Vec<String>,
+}
+
+impl FreqReport {
+    fn new(samples: &[u64], governors: Vec<String>, turbo: Option<bool>) -> Option<Self> {
+        let avg_mhz = samples.iter().sum::<u64>() / samples.len().max(1) as u64;
+        let min_mhz = *samples.iter().min()?;
+        let max_mhz = *samples.iter().max()?;
+        let mut warnings = Vec::new();
+        for governor in governors.iter().filter(|g| *g != "performance") {
+            warnings.push(format!("{governor} governor"));
+        }
+        if turbo == Some(true) {
+            warnings.push("turbo enabled".to_string());
+        }
+        if avg_mhz > 0 && (max_mhz - min_mhz) as f64 / avg_mhz as f64 > DRIFT_TOLERANCE {
+            warnings.push(format!("drift {min_mhz}-{max_mhz} MHz"));
+        }
+        Some(Self {
+            avg_mhz,
+            min_mhz,
+            max_mhz,
+            warnings,
+        })
+    }
+}
+
+impl fmt::Display for FreqReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "avg {} MHz (min {}, max {})",
+            self.avg_mhz, self.min_mhz, self.max_mhz
+        )?;
+        if !self.warnings.is_empty() {
+            write!(f, ", {}", self.warnings.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FreqReport;
+
+    #[test]
+    fn freq_report() {
+        assert_eq!(FreqReport::new(&[], vec![], None), None);
+
+        let steady = FreqReport::new(&[3000, 3010, 2990], vec!["performance".into()], Some(false));
+        assert_eq!(
+            steady,
+            Some(FreqReport {
+                avg_mhz: 3000,
+                min_mhz: 2990,
+                max_mhz: 3010,
+                warnings: vec![],
+            })
+        );
+
+        let drifting = FreqReport::new(
+            &[3500, 2500],
+            vec!["performance".into(), "powersave".into()],
+            Some(true),
+        )
+        .unwrap();
+        assert_eq!(
+            drifting.warnings,
+            ["powersave governor", "turbo enabled", "drift 2500-3500 MHz"]
+        );
+    }
+}
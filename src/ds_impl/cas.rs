@@ -0,0 +1,296 @@
+//! The results of the CAS on the tagged links of the structures.
+//!
+//! A CAS that expects an unmarked link fails either because the link points to another node, or
+//! because another thread marked it while it still points to the expected node, and an operation
+//! helps the conflicting one only in the latter case. Each backend reports the current link in its
+//! own error type, and the structures used to compare its untagged pointer with the expected one by
+//! hand. [`Classify::classify`] tells the two cases apart once for every backend, and counts each
+//! kind of failure per thread like [`Retry`](super::retry::Retry), so that the benchmark reports
+//! which one a contended workload runs into.
+//!
+//! The lists and `nm-tree` of every backend but `vbr` classify their CAS. The CAS of `vbr` reports
+//! that the expected node was reallocated instead of the current link, which has no such cases.
+
+use super::counters::{Counters, LocalCounts};
+use std::fmt;
+use std::ops::Div;
+
+/// A tagged pointer, as its untagged address and its tag.
+pub trait Link {
+    fn parts(&self) -> (usize, usize);
+}
+
+/// The error of a failed CAS, which holds the current link.
+pub trait CasError {
+    type Current: Link;
+
+    fn current(&self) -> &Self::Current;
+}
+
+/// The result of a CAS, with the error of a failure classified by the current link.
+#[derive(Debug)]
+pub enum Cas<T, E> {
+    /// The link was swapped, and the success value holds the previous link.
+    Success(T),
+    /// The link points to another node than the expected one.
+    CurrentDiffers(E),
+    /// The link points to the expected node, but with another tag.
+    TagDiffers(E),
+}
+
+impl<T, E> Cas<T, E> {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Cas::Success(_))
+    }
+
+    pub fn success(self) -> Option<T> {
+        match self {
+            Cas::Success(previous) => Some(previous),
+            _ => None,
+        }
+    }
+
+    pub fn failure(self) -> Option<E> {
+        match self {
+            Cas::Success(_) => None,
+            Cas::CurrentDiffers(e) | Cas::TagDiffers(e) => Some(e),
+        }
+    }
+}
+
+pub trait Classify<T, E> {
+    /// Classifies the failure of a CAS that expected `expected`, and counts it.
+    fn classify<L: Link + ?Sized>(self, expected: &L) -> Cas<T, E>;
+}
+
+impl<T, E: CasError> Classify<T, E> for Result<T, E> {
+    #[inline]
+    fn classify<L: Link + ?Sized>(self, expected: &L) -> Cas<T, E> {
+        match self {
+            Ok(previous) => Cas::Success(previous),
+            Err(e) => {
+                let failure = if e.current().parts().0 == expected.parts().0 {
+                    Failure::TagDiffers
+                } else {
+                    Failure::CurrentDiffers
+                };
+                failure.count();
+                match failure {
+                    Failure::CurrentDiffers => Cas::CurrentDiffers(e),
+                    Failure::TagDiffers => Cas::TagDiffers(e),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    CurrentDiffers,
+    TagDiffers,
+}
+
+static COUNTERS: Counters<2> = Counters::new(&LOCAL);
+
+thread_local! {
+    static LOCAL: LocalCounts<2> = LocalCounts::new(&COUNTERS);
+}
+
+impl Failure {
+    #[inline]
+    fn count(self) {
+        COUNTERS.count(self as usize);
+    }
+}
+
+/// The numbers of the failed CAS of each kind, of the exited threads and the calling thread.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Failures([usize; 2]);
+
+impl Failures {
+    pub fn get(&self, kind: Failure) -> usize {
+        self.0[kind as usize]
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+/// Returns the numbers of the failed CAS since the start of the process, including those of the
+/// calling thread, but not those of the other running threads.
+pub fn cas_failures() -> Failures {
+    Failures(COUNTERS.get())
+}
+
+/// Marks the start of the measured window of the calling thread.
+pub fn arrive() {
+    COUNTERS.arrive();
+}
+
+/// Adds the failed CAS of the calling thread since it arrived to those of the measured windows.
+pub fn depart() {
+    COUNTERS.depart();
+}
+
+/// Returns the numbers of the failed CAS in the measured windows of the threads that have departed.
+pub fn measured_cas_failures() -> Failures {
+    Failures(COUNTERS.measured())
+}
+
+/// The average over `reps` repetitions.
+impl Div<usize> for Failures {
+    type Output = Failures;
+
+    fn div(self, reps: usize) -> Failures {
+        Failures(self.0.map(|count| count / reps))
+    }
+}
+
+impl fmt::Display for Failures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "current differs {}, tag differs {}",
+            self.get(Failure::CurrentDiffers),
+            self.get(Failure::TagDiffers)
+        )
+    }
+}
+
+impl<T> Link for *mut T {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        (hp_pp::untagged(*self) as usize, hp_pp::tag(*self))
+    }
+}
+
+impl<T> CasError for *mut T {
+    type Current = *mut T;
+
+    #[inline]
+    fn current(&self) -> &*mut T {
+        self
+    }
+}
+
+impl<T> Link for crossbeam_ebr::Shared<'_, T> {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        (self.as_raw() as usize, self.tag())
+    }
+}
+
+impl<'g, T, P: crossbeam_ebr::Pointer<T>> CasError
+    for crossbeam_ebr::CompareExchangeError<'g, T, P>
+{
+    type Current = crossbeam_ebr::Shared<'g, T>;
+
+    #[inline]
+    fn current(&self) -> &Self::Current {
+        &self.current
+    }
+}
+
+impl<T> Link for crossbeam_pebr::Shared<'_, T> {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        (self.as_raw() as usize, self.tag())
+    }
+}
+
+impl<'g, T, P: crossbeam_pebr::Pointer<T>> CasError
+    for crossbeam_pebr::CompareAndSetError<'g, T, P>
+{
+    type Current = crossbeam_pebr::Shared<'g, T>;
+
+    #[inline]
+    fn current(&self) -> &Self::Current {
+        &self.current
+    }
+}
+
+impl<T> Link for hp_brcu::Shared<'_, T> {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        (self.untagged().as_raw(), self.tag())
+    }
+}
+
+impl<'g, T, P: hp_brcu::Pointer> CasError for hp_brcu::CompareExchangeError<'g, T, P> {
+    type Current = hp_brcu::Shared<'g, T>;
+
+    #[inline]
+    fn current(&self) -> &Self::Current {
+        &self.actual
+    }
+}
+
+impl<T> Link for cdrc::TaggedCnt<T> {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        (self.as_raw() as usize, self.tag())
+    }
+}
+
+impl<T, P> CasError for cdrc::CompareExchangeErrorRc<T, P> {
+    type Current = cdrc::TaggedCnt<T>;
+
+    #[inline]
+    fn current(&self) -> &Self::Current {
+        &self.current
+    }
+}
+
+impl<T> Link for circ::Tagged<T> {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        (self.as_raw() as usize, self.tag())
+    }
+}
+
+impl<T, P> CasError for circ::CompareExchangeErrorRc<T, P> {
+    type Current = circ::TaggedCnt<T>;
+
+    #[inline]
+    fn current(&self) -> &Self::Current {
+        &self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cas_failures, Cas, Classify, Failure};
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use std::thread;
+
+    #[test]
+    fn classify() {
+        let before = cas_failures();
+        thread::spawn(|| {
+            let node = Box::into_raw(Box::new(0u64));
+            let other = Box::into_raw(Box::new(1u64));
+            let link = AtomicPtr::new(node);
+            let cas = |expected: *mut u64, new: *mut u64| {
+                link.compare_exchange(expected, new, Ordering::AcqRel, Ordering::Acquire)
+                    .classify(&expected)
+            };
+
+            let marked = hp_pp::tagged(node, 1);
+            assert!(matches!(cas(node, marked), Cas::Success(prev) if prev == node));
+            assert!(matches!(cas(node, other), Cas::TagDiffers(current) if current == marked));
+            link.store(other, Ordering::Relaxed);
+            assert!(matches!(cas(node, other), Cas::CurrentDiffers(current) if current == other));
+            unsafe {
+                drop(Box::from_raw(node));
+                drop(Box::from_raw(other));
+            }
+        })
+        .join()
+        .unwrap();
+        let after = cas_failures();
+        // Other tests may classify their own CAS concurrently.
+        assert!(after.get(Failure::CurrentDiffers) > before.get(Failure::CurrentDiffers));
+        assert!(after.get(Failure::TagDiffers) > before.get(Failure::TagDiffers));
+    }
+}
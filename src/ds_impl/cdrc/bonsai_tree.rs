@@ -31,7 +31,7 @@ impl Retired {
 
 /// a real node in tree or a wrapper of State node
 /// Retired node if Shared ptr of Node has RETIRED tag.
-struct Node<K, V, C: Cs> {
+pub(crate) struct Node<K, V, C: Cs> {
     key: K,
     value: V,
     size: usize,
@@ -714,10 +714,38 @@ mod tests {
         });
     }
 
+    #[test]
+    fn edge_cases_bonsai_tree_ebr() {
+        concurrent_map::tests::edge_cases::<CsEBR, _, BonsaiTreeMap<i32, String, CsEBR>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_bonsai_tree_hp() {
         concurrent_map::tests::smoke::<CsHP, _, BonsaiTreeMap<i32, String, CsHP>, _>(&|a| {
             a.to_string()
         });
     }
+
+    #[test]
+    fn edge_cases_bonsai_tree_hp() {
+        concurrent_map::tests::edge_cases::<CsHP, _, BonsaiTreeMap<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
+    #[test]
+    fn pull_stress_bonsai_tree_ebr() {
+        concurrent_map::tests::pull_stress::<CsEBR, _, BonsaiTreeMap<i32, String, CsEBR>, _>(
+            &|a| a.to_string(),
+        );
+    }
+
+    #[test]
+    fn pull_stress_bonsai_tree_hp() {
+        concurrent_map::tests::pull_stress::<CsHP, _, BonsaiTreeMap<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
 }
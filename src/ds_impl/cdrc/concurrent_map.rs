@@ -1,3 +1,6 @@
+use cdrc::{Cs, Snapshot, StrongPtr};
+use std::ptr;
+
 pub trait OutputHolder<V> {
     fn default() -> Self;
     fn output(&self) -> &V;
@@ -14,6 +17,37 @@ pub trait ConcurrentMap<K, V, C> {
     fn get(&self, key: &K, output: &mut Self::Output, cs: &C) -> bool;
     fn insert(&self, key: K, value: V, output: &mut Self::Output, cs: &C) -> bool;
     fn remove(&self, key: &K, output: &mut Self::Output, cs: &C) -> bool;
+
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    /// The value is also left in `output`, as with `get`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(&self, key: K, value: V, output: &mut Self::Output, cs: &C) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if self.get(&key, output, cs) {
+                return Some(output.output().clone());
+            }
+            if self.insert(key.clone(), value.clone(), output, cs) {
+                return None;
+            }
+        }
+    }
+}
+
+/// Whether one of `snapshots` protects `node`, for the assertions of the `check-protection`
+/// feature on the nodes that a structure dereferences.
+pub fn protects<'s, T: 's, C: Cs + 's>(
+    snapshots: impl IntoIterator<Item = &'s Snapshot<T, C>>,
+    node: &T,
+) -> bool {
+    snapshots
+        .into_iter()
+        .any(|snapshot| snapshot.as_ref().is_some_and(|n| ptr::eq(n, node)))
 }
 
 #[cfg(test)]
@@ -23,10 +57,13 @@ pub mod tests {
     use cdrc::Cs;
     use crossbeam_utils::thread;
     use rand::prelude::*;
+    use std::collections::VecDeque;
     use std::fmt::Debug;
 
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
+    const PULL_KEYS: i32 = 200;
+    const PULL_ROUNDS: i32 = 5;
 
     pub fn smoke<C, V, M, F>(to_value: &F)
     where
@@ -91,4 +128,179 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Checks that an output keeps the value of the last operation that returned `true` with it
+    /// until it is used again or the critical section is cleared, while the same thread uses other
+    /// outputs and another thread removes the keys and inserts them again with other values.
+    pub fn output_holder<C, V, M, F>(to_value: &F)
+    where
+        C: Cs,
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V, C> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let output = &mut M::empty_output();
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(i, to_value(&i), output, &C::new()));
+        }
+
+        let cs = &mut C::new();
+        let got = &mut M::empty_output();
+        let removed = &mut M::empty_output();
+        assert!(map.get(&0, got, cs));
+        assert!(map.remove(&1, removed, cs));
+        assert!(map.remove(&0, output, cs));
+        assert!(map.insert(0, to_value(&-1), output, cs));
+        assert!(map.get(&0, output, cs));
+        assert_eq!(to_value(&-1), *output.output());
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                let output = &mut M::empty_output();
+                let cs = &mut C::new();
+                for i in 0..ELEMENTS_PER_THREADS {
+                    map.remove(&i, output, cs);
+                    assert!(map.insert(i, to_value(&(i + 1)), output, cs));
+                    cs.clear();
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(to_value(&0), *got.output());
+        assert_eq!(to_value(&1), *removed.output());
+        // Reusing an output replaces its value.
+        assert!(map.get(&2, got, cs));
+        assert_eq!(to_value(&3), *got.output());
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<C, V, M, F>(to_value: &F)
+    where
+        C: Cs,
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V, C>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let output = &mut M::empty_output();
+        let cs = &C::new();
+
+        // An empty map.
+        assert!(!map.get(&0, output, cs));
+        assert!(!map.remove(&0, output, cs));
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(0, to_value(&0), output, cs));
+        assert!(!map.insert(0, to_value(&1), output, cs));
+        assert!(map.get(&0, output, cs));
+        assert_eq!(to_value(&0), *output.output());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert!(map.remove(&0, output, cs));
+        assert_eq!(to_value(&0), *output.output());
+        assert!(!map.get(&0, output, cs));
+        assert!(!map.remove(&0, output, cs));
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(i, to_value(&i), output, cs));
+            }
+            assert!(map.remove(&removed, output, cs));
+            assert_eq!(to_value(&removed), *output.output());
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(&i, output, cs));
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert!(map.remove(&i, output, cs));
+                assert_eq!(to_value(&i), *output.output());
+            }
+        }
+        for i in 0..=3 {
+            assert!(!map.get(&i, output, cs));
+        }
+    }
+
+    /// Removes the keys of a tree in the order of the levels of a balanced tree, medians first, and
+    /// inserts them back in the same order, for a few rounds. Most keys then have two children when
+    /// they are removed, which pulls the rightmost node of the left subtree up into their place, and
+    /// the rest have a child on one side, which pulls the leftmost node of the right subtree. Both
+    /// rebuild the path to the pulled node, which the uniform keys of `smoke` rarely do. The tree is
+    /// prefilled in an increasing order, which skews it until it is rebalanced. Each thread owns
+    /// every `THREADS`-th key, and checks that its keys next to each removed key keep their values.
+    pub fn pull_stress<C, V, M, F>(to_value: &F)
+    where
+        C: Cs,
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V, C> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let output = &mut M::empty_output();
+        let cs = &mut C::new();
+        for i in 0..THREADS * PULL_KEYS {
+            assert!(map.insert(i, to_value(&i), output, &C::new()));
+        }
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let output = &mut M::empty_output();
+                    let cs = &mut C::new();
+                    let keys = medians_first(PULL_KEYS);
+                    for _ in 0..PULL_ROUNDS {
+                        let mut removed = vec![false; PULL_KEYS as usize];
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.remove(&i, output, cs));
+                            assert_eq!(to_value(&i), *output.output());
+                            cs.clear();
+                            removed[k as usize] = true;
+                            for n in [k - 1, k + 1] {
+                                if (0..PULL_KEYS).contains(&n) && !removed[n as usize] {
+                                    let j = n * THREADS + t;
+                                    assert!(map.get(&j, output, cs));
+                                    assert_eq!(to_value(&j), *output.output());
+                                    cs.clear();
+                                }
+                            }
+                        }
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.insert(i, to_value(&i), output, &C::new()));
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let output = &mut M::empty_output();
+        let cs = &mut C::new();
+        for j in 0..THREADS * PULL_KEYS {
+            assert!(map.get(&j, output, cs));
+            assert_eq!(to_value(&j), *output.output());
+            cs.clear();
+        }
+    }
+
+    /// The keys in `0..n` in the order of the levels of a balanced tree, medians first.
+    fn medians_first(n: i32) -> Vec<i32> {
+        let mut keys = Vec::with_capacity(n as usize);
+        let mut ranges = VecDeque::from([(0, n)]);
+        while let Some((lo, hi)) = ranges.pop_front() {
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                keys.push(mid);
+                ranges.push_back((lo, mid));
+                ranges.push_back((mid + 1, hi));
+            }
+        }
+        keys
+    }
 }
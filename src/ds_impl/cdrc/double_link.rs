@@ -25,7 +25,7 @@ impl<T, C: Cs> Holder<T, C> {
     }
 }
 
-struct Node<T, C: Cs> {
+pub(crate) struct Node<T, C: Cs> {
     item: Option<T>,
     prev: AtomicWeak<Node<T, C>, C>,
     next: CachePadded<AtomicRc<Node<T, C>, C>>,
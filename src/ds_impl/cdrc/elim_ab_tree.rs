@@ -121,7 +121,7 @@ enum Operation {
     Balance,
 }
 
-struct Node<K, V, C: Cs> {
+pub(crate) struct Node<K, V, C: Cs> {
     keys: [Cell<Option<K>>; DEGREE],
     search_key: K,
     lock: AtomicPtr<MCSLockSlot<K, V, C>>,
@@ -1388,8 +1388,18 @@ mod tests {
         concurrent_map::tests::smoke::<CsEBR, _, ElimABTree<i32, i32, CsEBR>, _>(&|a| *a);
     }
 
+    #[test]
+    fn edge_cases_elim_ab_tree_ebr() {
+        concurrent_map::tests::edge_cases::<CsEBR, _, ElimABTree<i32, i32, CsEBR>, _>(&|a| *a);
+    }
+
     #[test]
     fn smoke_elim_ab_tree_hp() {
         concurrent_map::tests::smoke::<CsHP, _, ElimABTree<i32, i32, CsHP>, _>(&|a| *a);
     }
+
+    #[test]
+    fn edge_cases_elim_ab_tree_hp() {
+        concurrent_map::tests::edge_cases::<CsHP, _, ElimABTree<i32, i32, CsHP>, _>(&|a| *a);
+    }
 }
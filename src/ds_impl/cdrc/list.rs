@@ -1,4 +1,7 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 use cdrc::{AtomicRc, Cs, Pointer, Rc, Snapshot, StrongPtr};
 
 use std::cmp::Ordering::{Equal, Greater, Less};
@@ -97,7 +100,7 @@ impl<K, V, C: Cs> Cursor<K, V, C> {
 impl<K: Ord, V, C: Cs> Cursor<K, V, C> {
     /// Clean up a chain of logically removed nodes in each traversal.
     #[inline]
-    fn find_harris(&mut self, key: &K, cs: &C) -> Result<bool, ()> {
+    fn find_harris(&mut self, key: &K, cs: &C) -> Result<bool, Retry> {
         let found = loop {
             // * 0 deleted: <prev> -> <curr>
             // * 1 deleted: <anchor> -> <prev> -x-> <curr>
@@ -160,7 +163,7 @@ impl<K: Ord, V, C: Cs> Cursor<K, V, C> {
                 Ordering::Relaxed,
                 cs,
             )
-            .map_err(|_| ())?;
+            .map_err(|_| Retry::Unlink)?;
 
         Snapshot::swap(&mut self.anchor, &mut self.prev);
         Ok(found)
@@ -168,7 +171,7 @@ impl<K: Ord, V, C: Cs> Cursor<K, V, C> {
 
     /// Clean up a single logically removed node in each traversal.
     #[inline]
-    fn find_harris_michael(&mut self, key: &K, cs: &C) -> Result<bool, ()> {
+    fn find_harris_michael(&mut self, key: &K, cs: &C) -> Result<bool, Retry> {
         loop {
             debug_assert_eq!(self.curr.tag(), 0);
 
@@ -188,7 +191,7 @@ impl<K: Ord, V, C: Cs> Cursor<K, V, C> {
                         Ordering::Relaxed,
                         cs,
                     )
-                    .map_err(|_| ())?;
+                    .map_err(|_| Retry::Unlink)?;
                 Snapshot::swap(&mut self.curr, &mut self.next);
                 continue;
             }
@@ -206,9 +209,11 @@ impl<K: Ord, V, C: Cs> Cursor<K, V, C> {
 
     /// Gotta go fast. Doesn't fail.
     #[inline]
-    fn find_harris_herlihy_shavit(&mut self, key: &K, cs: &C) -> Result<bool, ()> {
+    fn find_harris_herlihy_shavit(&mut self, key: &K, cs: &C) -> Result<bool, Retry> {
+        wait_free::traversal();
         Ok(loop {
             let curr_node = some_or!(self.curr.as_ref(), break false);
+            wait_free::visit();
             self.next.load(&curr_node.next, cs);
             match curr_node.key.cmp(key) {
                 Less => Snapshot::swap(&mut self.curr, &mut self.next),
@@ -229,21 +234,25 @@ impl<K: Ord, V, C: Cs> Cursor<K, V, C> {
             .next
             .store(&self.curr, Ordering::Relaxed, cs);
 
-        match unsafe { self.prev.deref() }.next.compare_exchange(
-            self.curr.as_ptr(),
-            node,
-            Ordering::Release,
-            Ordering::Relaxed,
-            cs,
-        ) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e.desired),
+        match unsafe { self.prev.deref() }
+            .next
+            .compare_exchange(
+                self.curr.as_ptr(),
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+                cs,
+            )
+            .classify(&self.curr.as_ptr())
+        {
+            Cas::Success(_) => Ok(()),
+            Cas::CurrentDiffers(e) | Cas::TagDiffers(e) => Err(e.desired),
         }
     }
 
     /// removes the current node.
     #[inline]
-    pub fn remove(&mut self, cs: &C) -> Result<(), ()> {
+    pub fn remove(&mut self, cs: &C) -> Result<(), Retry> {
         let curr_node = unsafe { self.curr.deref() };
 
         self.next.load(&curr_node.next, cs);
@@ -258,7 +267,7 @@ impl<K: Ord, V, C: Cs> Cursor<K, V, C> {
             )
             .is_err()
         {
-            return Err(());
+            return Err(Retry::CasFail);
         }
 
         let _ = unsafe { self.prev.deref() }.next.compare_exchange(
@@ -289,12 +298,13 @@ where
     #[inline]
     fn get<F>(&self, key: &K, find: F, cursor: &mut Cursor<K, V, C>, cs: &C) -> bool
     where
-        F: Fn(&mut Cursor<K, V, C>, &K, &C) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V, C>, &K, &C) -> Result<bool, Retry>,
     {
         loop {
             cursor.initialize(&self.head, cs);
-            if let Ok(r) = find(cursor, key, cs) {
-                return r;
+            match find(cursor, key, cs) {
+                Ok(r) => return r,
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -302,7 +312,7 @@ where
     #[inline]
     fn insert<F>(&self, key: K, value: V, find: F, cursor: &mut Cursor<K, V, C>, cs: &C) -> bool
     where
-        F: Fn(&mut Cursor<K, V, C>, &K, &C) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V, C>, &K, &C) -> Result<bool, Retry>,
     {
         let mut node = Rc::new(Node::new(key, value));
         loop {
@@ -313,7 +323,10 @@ where
             }
 
             match cursor.insert(node, cs) {
-                Err(n) => node = n,
+                Err(n) => {
+                    Retry::CasFail.count();
+                    node = n;
+                }
                 Ok(()) => return true,
             }
         }
@@ -322,7 +335,7 @@ where
     #[inline]
     fn remove<F>(&self, key: &K, find: F, cursor: &mut Cursor<K, V, C>, cs: &C) -> bool
     where
-        F: Fn(&mut Cursor<K, V, C>, &K, &C) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V, C>, &K, &C) -> Result<bool, Retry>,
     {
         loop {
             let found = self.get(key, &find, cursor, cs);
@@ -331,7 +344,7 @@ where
             }
 
             match cursor.remove(cs) {
-                Err(()) => continue,
+                Err(retry) => retry.count(),
                 Ok(_) => return true,
             }
         }
@@ -346,7 +359,7 @@ where
             }
 
             match cursor.remove(cs) {
-                Err(()) => continue,
+                Err(retry) => retry.count(),
                 Ok(_) => return true,
             }
         }
@@ -521,11 +534,25 @@ mod tests {
         concurrent_map::tests::smoke::<CsEBR, _, HList<i32, String, CsEBR>, _>(&|a| a.to_string());
     }
 
+    #[test]
+    fn edge_cases_ebr_h_list_ebr() {
+        concurrent_map::tests::edge_cases::<CsEBR, _, HList<i32, String, CsEBR>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_ebr_hm_list_ebr() {
         concurrent_map::tests::smoke::<CsEBR, _, HMList<i32, String, CsEBR>, _>(&|a| a.to_string());
     }
 
+    #[test]
+    fn edge_cases_ebr_hm_list_ebr() {
+        concurrent_map::tests::edge_cases::<CsEBR, _, HMList<i32, String, CsEBR>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_ebr_hhs_list_ebr() {
         concurrent_map::tests::smoke::<CsEBR, _, HHSList<i32, String, CsEBR>, _>(&|a| {
@@ -533,21 +560,87 @@ mod tests {
         });
     }
 
+    #[test]
+    fn edge_cases_ebr_hhs_list_ebr() {
+        concurrent_map::tests::edge_cases::<CsEBR, _, HHSList<i32, String, CsEBR>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_ebr_h_list_hp() {
         concurrent_map::tests::smoke::<CsHP, _, HList<i32, String, CsHP>, _>(&|a| a.to_string());
     }
 
+    #[test]
+    fn edge_cases_ebr_h_list_hp() {
+        concurrent_map::tests::edge_cases::<CsHP, _, HList<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_ebr_hm_list_hp() {
         concurrent_map::tests::smoke::<CsHP, _, HMList<i32, String, CsHP>, _>(&|a| a.to_string());
     }
 
+    #[test]
+    fn edge_cases_ebr_hm_list_hp() {
+        concurrent_map::tests::edge_cases::<CsHP, _, HMList<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_ebr_hhs_list_hp() {
         concurrent_map::tests::smoke::<CsHP, _, HHSList<i32, String, CsHP>, _>(&|a| a.to_string());
     }
 
+    #[test]
+    fn edge_cases_ebr_hhs_list_hp() {
+        concurrent_map::tests::edge_cases::<CsHP, _, HHSList<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
+    fn wait_free_get_hhs_list<C: cdrc::Cs>() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String, C>::new();
+        let output = &mut HHSList::<i32, String, C>::empty_output();
+        let cs = &C::new();
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(key, key.to_string(), output, cs),
+            Op::Remove => map.remove(&key, output, cs),
+            Op::Get => map.get(&key, output, cs),
+        });
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list_ebr() {
+        wait_free_get_hhs_list::<CsEBR>();
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list_hp() {
+        wait_free_get_hhs_list::<CsHP>();
+    }
+
+    #[test]
+    fn output_holder_hhs_list_ebr() {
+        concurrent_map::tests::output_holder::<CsEBR, _, HHSList<i32, String, CsEBR>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
+    #[test]
+    fn output_holder_hhs_list_hp() {
+        concurrent_map::tests::output_holder::<CsHP, _, HHSList<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use cdrc::Cs;
@@ -556,6 +649,7 @@ mod tests {
 
         let output = &mut HHSList::empty_output();
         let cs = &CsEBR::new();
+        assert!(!map.pop(output, cs));
         map.insert(1, "1", output, cs);
         map.insert(2, "2", output, cs);
         map.insert(3, "3", output, cs);
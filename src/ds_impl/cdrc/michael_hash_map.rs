@@ -91,8 +91,22 @@ mod tests {
         });
     }
 
+    #[test]
+    fn edge_cases_hashmap_ebr() {
+        concurrent_map::tests::edge_cases::<CsEBR, _, HashMap<i32, String, CsEBR>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_hashmap_hp() {
         concurrent_map::tests::smoke::<CsHP, _, HashMap<i32, String, CsHP>, _>(&|a| a.to_string());
     }
+
+    #[test]
+    fn edge_cases_hashmap_hp() {
+        concurrent_map::tests::edge_cases::<CsHP, _, HashMap<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
 }
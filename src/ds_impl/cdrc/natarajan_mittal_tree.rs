@@ -1,6 +1,8 @@
 use cdrc::{AtomicRc, Cs, Pointer, Rc, Snapshot, StrongPtr};
 
-use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::concurrent_map::{protects, ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use std::cmp;
 use std::mem::swap;
 use std::sync::atomic::Ordering;
@@ -86,7 +88,7 @@ where
     }
 }
 
-struct Node<K, V, C: Cs> {
+pub(crate) struct Node<K, V, C: Cs> {
     key: Key<K>,
     value: Option<V>,
     left: AtomicRc<Node<K, V, C>, C>,
@@ -130,40 +132,44 @@ enum Direction {
 /// All Shared<_> are unmarked.
 ///
 /// All of the edges of path from `successor` to `parent` are in the process of removal.
+///
+/// A `get` only uses the hot part of the record, `leaf`, `curr`, and `found`, which comes first so
+/// that it shares the fewest cache lines with the cold part that only the updates use.
+#[repr(C)]
 pub struct SeekRecord<K, V, C: Cs> {
+    /// The end of the access path.
+    leaf: Snapshot<Node<K, V, C>, C>,
+    /// The next node of the access path.
+    curr: Snapshot<Node<K, V, C>, C>,
+    /// The found node for Get and Remove operation.
+    found: Snapshot<Node<K, V, C>, C>,
     /// Parent of `successor`
     ancestor: Snapshot<Node<K, V, C>, C>,
     /// The first internal node with a marked outgoing edge.
     /// It is null if the successor is equal to the parent.
     successor: Snapshot<Node<K, V, C>, C>,
-    /// The direction of successor from ancestor.
-    successor_dir: Direction,
     /// Parent of `leaf`
     parent: Snapshot<Node<K, V, C>, C>,
-    /// The end of the access path.
-    leaf: Snapshot<Node<K, V, C>, C>,
+    /// The direction of successor from ancestor.
+    successor_dir: Direction,
     /// The direction of leaf from parent.
     leaf_dir: Direction,
-    /// The next node of the access path.
-    curr: Snapshot<Node<K, V, C>, C>,
     /// The direction of curr from leaf.
     curr_dir: Direction,
-    /// The found node for Get and Remove operation.
-    found: Snapshot<Node<K, V, C>, C>,
 }
 
 impl<K, V, C: Cs> OutputHolder<V> for SeekRecord<K, V, C> {
     fn default() -> Self {
         Self {
+            leaf: Default::default(),
+            curr: Default::default(),
+            found: Default::default(),
             ancestor: Default::default(),
             successor: Default::default(),
-            successor_dir: Default::default(),
             parent: Default::default(),
-            leaf: Default::default(),
+            successor_dir: Default::default(),
             leaf_dir: Default::default(),
-            curr: Default::default(),
             curr_dir: Default::default(),
-            found: Default::default(),
         }
     }
 
@@ -173,6 +179,28 @@ impl<K, V, C: Cs> OutputHolder<V> for SeekRecord<K, V, C> {
 }
 
 impl<K, V, C: Cs> SeekRecord<K, V, C> {
+    /// Returns `node`, a reference that outlives the snapshot that it was dereferenced from, and
+    /// asserts with the `check-protection` feature that another snapshot of the record still
+    /// protects it.
+    #[inline(always)]
+    fn protected<'n>(&self, node: &'n Node<K, V, C>) -> &'n Node<K, V, C> {
+        if cfg!(feature = "check-protection") {
+            let snapshots = [
+                &self.leaf,
+                &self.curr,
+                &self.found,
+                &self.ancestor,
+                &self.successor,
+                &self.parent,
+            ];
+            assert!(
+                protects(snapshots, node),
+                "a node of the seek record is dereferenced without a snapshot protecting it"
+            );
+        }
+        node
+    }
+
     fn successor_addr(&self) -> &AtomicRc<Node<K, V, C>, C> {
         match self.successor_dir {
             Direction::L => &unsafe { self.ancestor.deref() }.left,
@@ -233,7 +261,11 @@ where
         record
             .parent
             .load(&unsafe { record.ancestor.deref() }.left, cs);
-        record.successor.clear();
+        // The seeks only ever clear `successor`, so a reused record has it null already, and
+        // clearing it again would reset its hazard pointer for nothing.
+        if !record.successor.is_null() {
+            record.successor.clear();
+        }
         record.leaf.load(&unsafe { record.parent.deref() }.left, cs);
         record.leaf.set_tag(Marks::empty().bits());
         record.successor_dir = Direction::L;
@@ -252,7 +284,9 @@ where
                 // untagged edge: advance ancestor and successor pointers
                 Snapshot::swap(&mut record.ancestor, &mut record.parent);
                 // `successsor` is equal to `parent` after this step.
-                record.successor.clear();
+                if !record.successor.is_null() {
+                    record.successor.clear();
+                }
                 record.successor_dir = record.leaf_dir;
             }
 
@@ -266,6 +300,7 @@ where
 
             // update other variables
             prev_tag = Marks::from_bits_truncate(curr_tag).tag();
+            let curr_node = record.protected(curr_node);
             if curr_node.key.cmp(key) == cmp::Ordering::Greater {
                 record.curr_dir = Direction::L;
                 record.curr.load(&curr_node.left, cs);
@@ -277,27 +312,26 @@ where
     }
 
     /// Similar to `seek`, but traverse the tree with only two pointers
+    ///
+    /// It starts from the root with `leaf` and `curr`, which go left past the nodes with infinite
+    /// keys, so that it leaves the cold part of the record alone. The tags of `curr` are left as
+    /// is, since a snapshot is dereferenced without its tag and `get` only reads the leaf.
     fn seek_leaf(&self, key: &K, record: &mut SeekRecord<K, V, C>, cs: &C) {
-        record.ancestor.load(&self.r, cs);
-        record
-            .parent
-            .load(&unsafe { record.ancestor.deref() }.left, cs);
-        record.leaf.load(&unsafe { record.parent.deref() }.left, cs);
+        record.leaf.load(&self.r, cs);
         record.curr.load(&unsafe { record.leaf.deref() }.left, cs);
-        record.curr.set_tag(Marks::empty().bits());
 
         while !record.curr.is_null() {
             // Safety of deref: Even if `record.curr` is mutated by `swap`, `curr_node` is
             // protected by `record.leaf`.
             let curr_node = unsafe { record.curr.deref() };
             Snapshot::swap(&mut record.leaf, &mut record.curr);
+            let curr_node = record.protected(curr_node);
 
             if curr_node.key.cmp(key) == cmp::Ordering::Greater {
                 record.curr.load(&curr_node.left, cs);
             } else {
                 record.curr.load(&curr_node.right, cs);
             }
-            record.curr.set_tag(Marks::empty().bits());
         }
     }
 
@@ -308,6 +342,17 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire);
         let leaf_flag = Marks::from_bits_truncate(leaf_marked.tag()).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag || {
+                let sibling_addr = match record.leaf_dir {
+                    Direction::L => &unsafe { record.parent.deref() }.right,
+                    Direction::R => &unsafe { record.parent.deref() }.left,
+                };
+                Marks::from_bits_truncate(sibling_addr.load(Ordering::Acquire).tag()).flag()
+            },
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             match record.leaf_dir {
                 Direction::L => &unsafe { record.parent.deref() }.right,
@@ -329,21 +374,34 @@ where
         // the flag to the new edge (ancestor, sibling).
         record.curr.load(target_sibling_addr, cs);
         let target_sibling = &record.curr;
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(target_sibling.tag()).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(target_sibling.tag()).flag();
+        let successor = if record.successor.is_null() {
+            record.parent.as_ptr()
+        } else {
+            record.successor.as_ptr()
+        };
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            successor.tag(),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         record
             .successor_addr()
             .compare_exchange(
-                if record.successor.is_null() {
-                    record.parent.as_ptr()
-                } else {
-                    record.successor.as_ptr()
-                },
+                successor,
                 target_sibling.with_tag(Marks::new(flag, false).bits()),
                 Ordering::AcqRel,
                 Ordering::Acquire,
                 cs,
             )
-            .is_ok()
+            .classify(&successor)
+            .is_success()
     }
 
     pub fn get(&self, key: &K, record: &mut SeekRecord<K, V, C>, cs: &C) -> bool {
@@ -393,21 +451,28 @@ where
             }
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match record.leaf_addr().compare_exchange(
-                record.leaf.as_ptr(),
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                cs,
-            ) {
-                Ok(_) => return true,
-                Err(e) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf.as_ptr(),
+                    new_internal,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    cs,
+                )
+                .classify(&record.leaf.as_ptr())
+            {
+                Cas::Success(_) => return true,
+                // Insertion failed. Help the conflicting remove operation if needed.
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(e) => {
                     new_internal = e.desired;
-                    if e.current.with_tag(Marks::empty().bits()) == record.leaf.as_ptr() {
-                        self.cleanup(record, cs);
-                    }
+                    InsertConflict::Help.count();
+                    self.cleanup(record, cs);
+                }
+                Cas::CurrentDiffers(e) => {
+                    new_internal = e.desired;
+                    InsertConflict::Retry.count();
                 }
             }
         }
@@ -428,14 +493,19 @@ where
             }
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_exchange_tag(
-                &record.leaf,
-                Marks::new(true, false).bits(),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                cs,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(record.leaf.tag(), 0, "the leaf edge is expected unmarked");
+            match record
+                .leaf_addr()
+                .compare_exchange_tag(
+                    &record.leaf,
+                    Marks::new(true, false).bits(),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    cs,
+                )
+                .classify(&record.leaf.as_ptr())
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(record, cs) {
                         Snapshot::swap(&mut record.leaf, &mut record.found);
@@ -446,15 +516,14 @@ where
                     Snapshot::swap(&mut record.leaf, &mut record.found);
                     break leaf;
                 }
-                Err(e) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if record.leaf.as_ptr() == e.current.with_tag(Marks::empty().bits()) {
-                        self.cleanup(record, cs);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(record, cs);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         };
 
@@ -513,10 +582,24 @@ mod tests {
         });
     }
 
+    #[test]
+    fn edge_cases_nm_tree_ebr() {
+        concurrent_map::tests::edge_cases::<CsEBR, _, NMTreeMap<i32, String, CsEBR>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_nm_tree_hp() {
         concurrent_map::tests::smoke::<CsHP, _, NMTreeMap<i32, String, CsHP>, _>(&|a| {
             a.to_string()
         });
     }
+
+    #[test]
+    fn edge_cases_nm_tree_hp() {
+        concurrent_map::tests::edge_cases::<CsHP, _, NMTreeMap<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
 }
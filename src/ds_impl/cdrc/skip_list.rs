@@ -8,7 +8,7 @@ const MAX_HEIGHT: usize = 32;
 
 type Tower<K, V, C> = [AtomicRc<Node<K, V, C>, C>; MAX_HEIGHT];
 
-struct Node<K, V, C: Cs> {
+pub(crate) struct Node<K, V, C: Cs> {
     key: K,
     value: V,
     next: Tower<K, V, C>,
@@ -443,8 +443,22 @@ mod tests {
         });
     }
 
+    #[test]
+    fn edge_cases_skip_list_ebr() {
+        concurrent_map::tests::edge_cases::<CsEBR, _, SkipList<i32, String, CsEBR>, _>(&|a| {
+            a.to_string()
+        });
+    }
+
     #[test]
     fn smoke_skip_list_hp() {
         concurrent_map::tests::smoke::<CsHP, _, SkipList<i32, String, CsHP>, _>(&|a| a.to_string());
     }
+
+    #[test]
+    fn edge_cases_skip_list_hp() {
+        concurrent_map::tests::edge_cases::<CsHP, _, SkipList<i32, String, CsHP>, _>(&|a| {
+            a.to_string()
+        });
+    }
 }
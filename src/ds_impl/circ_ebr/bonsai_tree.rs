@@ -2,7 +2,7 @@ use circ::{AtomicRc, CsEBR, GraphNode, Pointer, Rc, Snapshot, StrongPtr, TaggedC
 
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
 
-use std::{cmp, sync::atomic::Ordering};
+use std::{cmp, collections::BTreeMap, sync::atomic::Ordering};
 
 static WEIGHT: usize = 2;
 
@@ -626,6 +626,69 @@ where
         }
     }
 
+    /// Returns the current version of the tree.
+    ///
+    /// The tree is persistent, so a version never changes, and the returned `Rc` keeps all of
+    /// its nodes alive (even after later updates remove them from the tree) until it is dropped.
+    pub fn version(&self, cs: &CsEBR) -> Rc<Node<K, V>, CsEBR> {
+        self.root.load_ss(cs).upgrade()
+    }
+
+    /// Looks up `key` in a version returned by [`BonsaiTreeMap::version`].
+    pub fn get_in(
+        version: &Rc<Node<K, V>, CsEBR>,
+        key: &K,
+        cs: &CsEBR,
+    ) -> Option<Snapshot<Node<K, V>, CsEBR>> {
+        let mut node = Snapshot::new();
+        node.protect(version, cs);
+        while !node.is_null() {
+            let node_ref = unsafe { node.deref() };
+            match key.cmp(&node_ref.key) {
+                cmp::Ordering::Equal => return Some(node),
+                cmp::Ordering::Less => node = node_ref.left.load_ss(cs),
+                cmp::Ordering::Greater => node = node_ref.right.load_ss(cs),
+            }
+        }
+        None
+    }
+
+    /// Copies the keys in `lo..=hi` and their values out of the current version of the tree.
+    ///
+    /// The copy is consistent since a version never changes. Its nodes are held by reference
+    /// counts while they are visited, so it protects one node at a time however wide the range is.
+    pub fn clone_range(&self, lo: &K, hi: &K, cs: &CsEBR) -> BTreeMap<K, V> {
+        Self::clone_range_in(&self.version(cs), lo, hi, cs)
+    }
+
+    /// Copies the keys in `lo..=hi` and their values out of a version returned by
+    /// [`BonsaiTreeMap::version`].
+    pub fn clone_range_in(
+        version: &Rc<Node<K, V>, CsEBR>,
+        lo: &K,
+        hi: &K,
+        cs: &CsEBR,
+    ) -> BTreeMap<K, V> {
+        let mut range = BTreeMap::new();
+        let mut child = Snapshot::new();
+        let mut pending = vec![version.clone()];
+        while let Some(node) = pending.pop() {
+            let node_ref = some_or!(node.as_ref(), continue);
+            if lo < &node_ref.key {
+                child.load(&node_ref.left, cs);
+                pending.push(child.upgrade());
+            }
+            if &node_ref.key < hi {
+                child.load(&node_ref.right, cs);
+                pending.push(child.upgrade());
+            }
+            if lo <= &node_ref.key && &node_ref.key <= hi {
+                range.insert(node_ref.key.clone(), node_ref.value.clone());
+            }
+        }
+        range
+    }
+
     pub fn insert(&self, key: K, value: V, cs: &CsEBR) -> bool {
         loop {
             let curr_root = self.root.load_ss(cs);
@@ -707,9 +770,48 @@ where
 mod tests {
     use super::BonsaiTreeMap;
     use crate::ds_impl::circ_ebr::concurrent_map;
+    use circ::{Cs, CsEBR};
 
     #[test]
     fn smoke_bonsai_tree() {
         concurrent_map::tests::smoke::<BonsaiTreeMap<i32, String>>();
     }
+
+    #[test]
+    fn edge_cases_bonsai_tree() {
+        concurrent_map::tests::edge_cases::<BonsaiTreeMap<i32, String>>();
+    }
+
+    #[test]
+    fn pull_stress_bonsai_tree() {
+        concurrent_map::tests::pull_stress::<BonsaiTreeMap<i32, String>>();
+    }
+
+    #[test]
+    fn clone_range_bonsai_tree() {
+        let map = &BonsaiTreeMap::<i32, String>::new();
+        let cs = &CsEBR::new();
+        for i in 0..100 {
+            assert!(map.insert(i, i.to_string(), cs));
+        }
+        let version = map.version(cs);
+        for i in 40..60 {
+            assert!(map.remove(&i, cs).is_some());
+        }
+
+        let range = map.clone_range(&30, &69, cs);
+        assert_eq!(
+            (30..40).chain(60..70).collect::<Vec<_>>(),
+            range.keys().copied().collect::<Vec<_>>()
+        );
+        assert!(range.iter().all(|(k, v)| k.to_string() == *v));
+
+        // The version taken before the removes still has the removed keys.
+        let range = BonsaiTreeMap::clone_range_in(&version, &30, &69, cs);
+        assert_eq!(
+            (30..70).collect::<Vec<_>>(),
+            range.keys().copied().collect::<Vec<_>>()
+        );
+        assert!(map.clone_range(&100, &200, cs).is_empty());
+    }
 }
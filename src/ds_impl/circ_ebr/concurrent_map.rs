@@ -11,6 +11,33 @@ pub trait ConcurrentMap<K, V> {
     fn get(&self, key: &K, cs: &CsEBR) -> Option<Self::Output>;
     fn insert(&self, key: K, value: V, cs: &CsEBR) -> bool;
     fn remove(&self, key: &K, cs: &CsEBR) -> Option<Self::Output>;
+
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(&self, key: K, value: V, cs: &CsEBR) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if let Some(found) = self.get(&key, cs) {
+                return Some(found.output().clone());
+            }
+            if self.insert(key.clone(), value.clone(), cs) {
+                return None;
+            }
+        }
+    }
+
+    /// Replaces the value of `key`, and returns whether the key was present.
+    ///
+    /// By default, the node of the key is removed and a new one is inserted, which re-links the
+    /// key. A structure that updates its values in place replaces this.
+    fn update(&self, key: K, value: V, cs: &CsEBR) -> bool {
+        self.remove(&key, cs).is_some() && self.insert(key, value, cs)
+    }
 }
 
 #[cfg(test)]
@@ -20,9 +47,12 @@ pub mod tests {
     use circ::{Cs, CsEBR};
     use crossbeam_utils::thread;
     use rand::prelude::*;
+    use std::collections::VecDeque;
 
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
+    const PULL_KEYS: i32 = 200;
+    const PULL_ROUNDS: i32 = 5;
 
     pub fn smoke<M: ConcurrentMap<i32, String> + Send + Sync>() {
         let map = &M::new();
@@ -81,4 +111,184 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Checks that the holders of `get` and `remove` keep their values until the critical
+    /// section is cleared, while the same thread and another one remove the keys and insert them
+    /// again with other values.
+    pub fn output_holder<M: ConcurrentMap<i32, String> + Send + Sync>() {
+        let map = &M::new();
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(i, i.to_string(), &CsEBR::new()));
+        }
+
+        let cs = &mut CsEBR::new();
+        let got = map.get(&0, cs).unwrap();
+        let removed = map.remove(&1, cs).unwrap();
+        assert!(map.remove(&0, cs).is_some());
+        assert!(map.insert(0, (-1).to_string(), cs));
+        assert_eq!((-1).to_string(), *map.get(&0, cs).unwrap().output());
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                let cs = &mut CsEBR::new();
+                for i in 0..ELEMENTS_PER_THREADS {
+                    map.remove(&i, cs);
+                    assert!(map.insert(i, (i + 1).to_string(), cs));
+                    cs.clear();
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(0.to_string(), *got.output());
+        assert_eq!(1.to_string(), *removed.output());
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<M: ConcurrentMap<i32, String>>() {
+        let map = &M::new();
+        let cs = &CsEBR::new();
+
+        // An empty map.
+        assert!(map.get(&0, cs).is_none());
+        assert!(map.remove(&0, cs).is_none());
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(0, 0.to_string(), cs));
+        assert!(!map.insert(0, 1.to_string(), cs));
+        assert_eq!(0.to_string(), *map.get(&0, cs).unwrap().output());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert_eq!(0.to_string(), *map.remove(&0, cs).unwrap().output());
+        assert!(map.get(&0, cs).is_none());
+        assert!(map.remove(&0, cs).is_none());
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(i, i.to_string(), cs));
+            }
+            assert_eq!(
+                removed.to_string(),
+                *map.remove(&removed, cs).unwrap().output()
+            );
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(&i, cs).is_some());
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert_eq!(i.to_string(), *map.remove(&i, cs).unwrap().output());
+            }
+        }
+        for i in 0..=3 {
+            assert!(map.get(&i, cs).is_none());
+        }
+    }
+
+    /// Removes the keys of a tree in the order of the levels of a balanced tree, medians first, and
+    /// inserts them back in the same order, for a few rounds. Most keys then have two children when
+    /// they are removed, which pulls the rightmost node of the left subtree up into their place, and
+    /// the rest have a child on one side, which pulls the leftmost node of the right subtree. Both
+    /// rebuild the path to the pulled node, which the uniform keys of `smoke` rarely do. The tree is
+    /// prefilled in an increasing order, which skews it until it is rebalanced. Each thread owns
+    /// every `THREADS`-th key, and checks that its keys next to each removed key keep their values.
+    pub fn pull_stress<M: ConcurrentMap<i32, String> + Send + Sync>() {
+        let map = &M::new();
+        let cs = &mut CsEBR::new();
+        for i in 0..THREADS * PULL_KEYS {
+            assert!(map.insert(i, i.to_string(), &CsEBR::new()));
+        }
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let cs = &mut CsEBR::new();
+                    let keys = medians_first(PULL_KEYS);
+                    for _ in 0..PULL_ROUNDS {
+                        let mut removed = vec![false; PULL_KEYS as usize];
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert_eq!(i.to_string(), *map.remove(&i, cs).unwrap().output());
+                            cs.clear();
+                            removed[k as usize] = true;
+                            for n in [k - 1, k + 1] {
+                                if (0..PULL_KEYS).contains(&n) && !removed[n as usize] {
+                                    let j = n * THREADS + t;
+                                    assert_eq!(j.to_string(), *map.get(&j, cs).unwrap().output());
+                                    cs.clear();
+                                }
+                            }
+                        }
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.insert(i, i.to_string(), &CsEBR::new()));
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let cs = &mut CsEBR::new();
+        for j in 0..THREADS * PULL_KEYS {
+            assert_eq!(j.to_string(), *map.get(&j, cs).unwrap().output());
+            cs.clear();
+        }
+    }
+
+    /// The keys in `0..n` in the order of the levels of a balanced tree, medians first.
+    fn medians_first(n: i32) -> Vec<i32> {
+        let mut keys = Vec::with_capacity(n as usize);
+        let mut ranges = VecDeque::from([(0, n)]);
+        while let Some((lo, hi)) = ranges.pop_front() {
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                keys.push(mid);
+                ranges.push_back((lo, mid));
+                ranges.push_back((mid + 1, hi));
+            }
+        }
+        keys
+    }
+
+    /// Checks that an update replaces the value of a present key, while a holder of the old value
+    /// keeps it, and fails on an absent or removed key.
+    pub fn update<M: ConcurrentMap<i32, String> + Send + Sync>() {
+        let map = &M::new();
+        let cs = &mut CsEBR::new();
+        assert!(!map.update(0, 0.to_string(), cs));
+        assert!(map.get(&0, cs).is_none());
+
+        assert!(map.insert(0, 0.to_string(), cs));
+        let old = map.get(&0, cs).unwrap();
+        assert!(map.update(0, 1.to_string(), cs));
+        assert_eq!(1.to_string(), *map.get(&0, cs).unwrap().output());
+        assert_eq!(0.to_string(), *old.output());
+        assert_eq!(1.to_string(), *map.remove(&0, cs).unwrap().output());
+        assert!(!map.update(0, 2.to_string(), cs));
+        assert!(map.get(&0, cs).is_none());
+        cs.clear();
+
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(i, i.to_string(), cs));
+        }
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let cs = &mut CsEBR::new();
+                    for i in 0..ELEMENTS_PER_THREADS {
+                        assert!(map.update(i, (i + t).to_string(), cs));
+                        cs.clear();
+                    }
+                });
+            }
+        })
+        .unwrap();
+        for i in 0..ELEMENTS_PER_THREADS {
+            let value = map.get(&i, cs).unwrap().output().parse::<i32>().unwrap();
+            assert!((i..i + THREADS).contains(&value));
+        }
+    }
 }
@@ -16,7 +16,7 @@ impl<T> Output<T> {
     }
 }
 
-struct Node<T> {
+pub(crate) struct Node<T> {
     item: Option<T>,
     prev: Weak<Node<T>, CsEBR>,
     next: CachePadded<AtomicRc<Node<T>, CsEBR>>,
@@ -0,0 +1,392 @@
+//! The extendible hash map of `ebr::extendible_hash_map` on CIRC.
+//!
+//! The directories point to their segments with `Rc`s, so that replacing the directory drops the
+//! last reference to the old one, which releases the references to the segments that it points to.
+//! A segment that is no longer pointed to by any directory is then reclaimed with all of its nodes.
+
+use super::concurrent_map::ConcurrentMap;
+use super::list::HHSList;
+use circ::{AtomicRc, CsEBR, GraphNode, Rc, StrongPtr};
+use crossbeam_utils::Backoff;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The numbers of the resizes of all extendible hash maps.
+pub static SEGMENT_SPLITS: AtomicUsize = AtomicUsize::new(0);
+pub static SEGMENT_MERGES: AtomicUsize = AtomicUsize::new(0);
+pub static DIRECTORY_DOUBLINGS: AtomicUsize = AtomicUsize::new(0);
+pub static DIRECTORY_HALVINGS: AtomicUsize = AtomicUsize::new(0);
+
+const SEGMENT_BUCKETS: usize = 64;
+const SPLIT_LEN: isize = 4 * SEGMENT_BUCKETS as isize;
+const MERGE_LEN: isize = SEGMENT_BUCKETS as isize;
+/// The directory uses the low half of the hash, and the segments use the high half.
+const MAX_DEPTH: u32 = 32;
+
+struct Segment<K, V> {
+    /// The number of the low bits of the hashes that the keys of the segment share.
+    depth: u32,
+    /// The number of keys, which the updates count after they finish.
+    len: AtomicIsize,
+    /// The number of updates in the segment.
+    writers: AtomicUsize,
+    frozen: AtomicBool,
+    buckets: Box<[HHSList<K, V>]>,
+}
+
+impl<K, V> GraphNode<CsEBR> for Segment<K, V> {
+    const UNIQUE_OUTDEGREE: bool = false;
+
+    #[inline]
+    fn pop_outgoings(&mut self, _: &mut Vec<Rc<Self, CsEBR>>)
+    where
+        Self: Sized,
+    {
+    }
+
+    #[inline]
+    fn pop_unique(&mut self) -> Rc<Self, CsEBR>
+    where
+        Self: Sized,
+    {
+        unimplemented!()
+    }
+}
+
+impl<K, V> Segment<K, V>
+where
+    K: Ord + Default,
+    V: Default,
+{
+    fn new(depth: u32) -> Self {
+        Self {
+            depth,
+            len: AtomicIsize::new(0),
+            writers: AtomicUsize::new(0),
+            frozen: AtomicBool::new(false),
+            buckets: (0..SEGMENT_BUCKETS).map(|_| HHSList::new()).collect(),
+        }
+    }
+
+    #[inline]
+    fn bucket(&self, hash: u64) -> &HHSList<K, V> {
+        &self.buckets[(hash >> MAX_DEPTH) as usize % SEGMENT_BUCKETS]
+    }
+
+    /// Enters the segment for an update, or returns `false` if it is frozen.
+    #[inline]
+    fn enter(&self) -> bool {
+        self.writers.fetch_add(1, Ordering::SeqCst);
+        if self.frozen.load(Ordering::SeqCst) {
+            self.writers.fetch_sub(1, Ordering::Release);
+            return false;
+        }
+        true
+    }
+
+    #[inline]
+    fn exit(&self) {
+        self.writers.fetch_sub(1, Ordering::Release);
+    }
+
+    /// Freezes the segment for good, and waits for the updates in it to finish.
+    fn freeze(&self) {
+        self.frozen.store(true, Ordering::SeqCst);
+        let backoff = Backoff::new();
+        while self.writers.load(Ordering::SeqCst) != 0 {
+            backoff.snooze();
+        }
+    }
+}
+
+impl<K, V> Segment<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    /// Copies the keys of the frozen segment `from` into this segment.
+    fn copy_from(&self, from: &Self, cs: &CsEBR) {
+        let mut copied = 0;
+        for bucket in from.buckets.iter() {
+            bucket.for_each(
+                |k, v| {
+                    let hash = hash_of(k);
+                    if self.bucket(hash).insert(k.clone(), v.clone(), cs) {
+                        copied += 1;
+                    }
+                },
+                cs,
+            );
+        }
+        self.len.fetch_add(copied, Ordering::Relaxed);
+    }
+}
+
+struct Directory<K, V> {
+    depth: u32,
+    segments: Box<[Rc<Segment<K, V>, CsEBR>]>,
+}
+
+impl<K, V> GraphNode<CsEBR> for Directory<K, V> {
+    const UNIQUE_OUTDEGREE: bool = false;
+
+    #[inline]
+    fn pop_outgoings(&mut self, _: &mut Vec<Rc<Self, CsEBR>>)
+    where
+        Self: Sized,
+    {
+    }
+
+    #[inline]
+    fn pop_unique(&mut self) -> Rc<Self, CsEBR>
+    where
+        Self: Sized,
+    {
+        unimplemented!()
+    }
+}
+
+impl<K, V> Directory<K, V> {
+    #[inline]
+    fn segment(&self, hash: u64) -> &Rc<Segment<K, V>, CsEBR> {
+        &self.segments[hash as usize & ((1 << self.depth) - 1)]
+    }
+}
+
+pub struct ExtendibleHashMap<K, V> {
+    directory: AtomicRc<Directory<K, V>, CsEBR>,
+    resize: Mutex<()>,
+}
+
+#[inline]
+fn hash_of<K: Hash>(k: &K) -> u64 {
+    let mut s = DefaultHasher::new();
+    k.hash(&mut s);
+    s.finish()
+}
+
+impl<K, V> Default for ExtendibleHashMap<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ExtendibleHashMap<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    /// Creates a map with a directory of one entry.
+    pub fn new() -> Self {
+        Self {
+            directory: AtomicRc::new(Directory {
+                depth: 0,
+                segments: Box::new([Rc::new(Segment::new(0))]),
+            }),
+            resize: Mutex::new(()),
+        }
+    }
+
+    /// The segment of `hash` in the current directory, which is protected by `cs`.
+    #[inline]
+    fn segment<'c>(&self, hash: u64, cs: &'c CsEBR) -> &'c Segment<K, V> {
+        let directory = unsafe { self.directory.load_ss(cs).deref() };
+        unsafe { directory.segment(hash).deref() }
+    }
+
+    /// Enters the segment of `hash` for an update, waiting for it to be replaced if it is frozen.
+    #[inline]
+    fn enter<'c>(&self, hash: u64, cs: &'c CsEBR) -> &'c Segment<K, V> {
+        let backoff = Backoff::new();
+        loop {
+            let segment = self.segment(hash, cs);
+            if segment.enter() {
+                return segment;
+            }
+            backoff.snooze();
+        }
+    }
+
+    pub fn get(&self, k: &K, cs: &CsEBR) -> Option<<HHSList<K, V> as ConcurrentMap<K, V>>::Output> {
+        let hash = hash_of(k);
+        self.segment(hash, cs).bucket(hash).get(k, cs)
+    }
+
+    pub fn insert(&self, k: K, v: V, cs: &CsEBR) -> bool {
+        let hash = hash_of(&k);
+        let segment = self.enter(hash, cs);
+        let inserted = segment.bucket(hash).insert(k, v, cs);
+        segment.exit();
+        if inserted && segment.len.fetch_add(1, Ordering::Relaxed) >= SPLIT_LEN {
+            self.try_split(hash, cs);
+        }
+        inserted
+    }
+
+    pub fn remove(
+        &self,
+        k: &K,
+        cs: &CsEBR,
+    ) -> Option<<HHSList<K, V> as ConcurrentMap<K, V>>::Output> {
+        let hash = hash_of(k);
+        let segment = self.enter(hash, cs);
+        let removed = segment.bucket(hash).remove(k, cs);
+        segment.exit();
+        if removed.is_some() && segment.len.fetch_sub(1, Ordering::Relaxed) <= MERGE_LEN / 2 {
+            self.try_merge(hash, cs);
+        }
+        removed
+    }
+
+    /// Splits the segment of `hash` if it is still too large, unless another resize is running.
+    #[cold]
+    fn try_split(&self, hash: u64, cs: &CsEBR) {
+        let Ok(_resize) = self.resize.try_lock() else {
+            return;
+        };
+        let directory = unsafe { self.directory.load_ss(cs).deref() };
+        let old = directory.segment(hash);
+        let segment = unsafe { old.deref() };
+        if segment.len.load(Ordering::Relaxed) <= SPLIT_LEN || segment.depth == MAX_DEPTH {
+            return;
+        }
+
+        segment.freeze();
+        let halves = [(); 2].map(|_| Segment::new(segment.depth + 1));
+        for bucket in segment.buckets.iter() {
+            bucket.for_each(
+                |k, v| {
+                    let hash = hash_of(k);
+                    let half = &halves[(hash >> segment.depth) as usize & 1];
+                    half.bucket(hash).insert(k.clone(), v.clone(), cs);
+                    half.len.fetch_add(1, Ordering::Relaxed);
+                },
+                cs,
+            );
+        }
+        let halves = halves.map(Rc::new);
+
+        let depth = directory.depth.max(segment.depth + 1);
+        let segments = (0..1 << depth)
+            .map(|i| {
+                let entry = directory.segment(i as u64);
+                if entry == old {
+                    halves[(i >> segment.depth) & 1].clone()
+                } else {
+                    entry.clone()
+                }
+            })
+            .collect();
+        // Drops the last reference to the old directory.
+        self.directory.store(
+            Rc::new(Directory { depth, segments }),
+            Ordering::Release,
+            cs,
+        );
+        SEGMENT_SPLITS.fetch_add(1, Ordering::Relaxed);
+        if depth > directory.depth {
+            DIRECTORY_DOUBLINGS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Merges the segment of `hash` with its buddy if they are still small enough, unless another
+    /// resize is running.
+    #[cold]
+    fn try_merge(&self, hash: u64, cs: &CsEBR) {
+        let Ok(_resize) = self.resize.try_lock() else {
+            return;
+        };
+        let directory = unsafe { self.directory.load_ss(cs).deref() };
+        let old = directory.segment(hash);
+        let segment = unsafe { old.deref() };
+        if segment.depth == 0 {
+            return;
+        }
+        // The buddy differs only in the highest bit of the segment.
+        let old_buddy = directory.segment(hash ^ (1 << (segment.depth - 1)));
+        let buddy = unsafe { old_buddy.deref() };
+        if buddy.depth != segment.depth
+            || segment.len.load(Ordering::Relaxed) + buddy.len.load(Ordering::Relaxed) >= MERGE_LEN
+        {
+            return;
+        }
+
+        segment.freeze();
+        buddy.freeze();
+        let merged = Segment::new(segment.depth - 1);
+        merged.copy_from(segment, cs);
+        merged.copy_from(buddy, cs);
+        let merged = Rc::new(merged);
+
+        let entries = directory.segments.iter().map(|entry| {
+            if entry == old || entry == old_buddy {
+                &merged
+            } else {
+                entry
+            }
+        });
+        let depth = entries
+            .clone()
+            .map(|entry| unsafe { entry.deref() }.depth)
+            .max()
+            .unwrap();
+        let segments = entries.take(1 << depth).map(Rc::clone).collect();
+        // Drops the last reference to the old directory.
+        self.directory.store(
+            Rc::new(Directory { depth, segments }),
+            Ordering::Release,
+            cs,
+        );
+        SEGMENT_MERGES.fetch_add(1, Ordering::Relaxed);
+        if depth < directory.depth {
+            DIRECTORY_HALVINGS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for ExtendibleHashMap<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    type Output = <HHSList<K, V> as ConcurrentMap<K, V>>::Output;
+
+    fn new() -> Self {
+        Self::new()
+    }
+
+    #[inline(always)]
+    fn get(&self, key: &K, cs: &CsEBR) -> Option<Self::Output> {
+        self.get(key, cs)
+    }
+    #[inline(always)]
+    fn insert(&self, key: K, value: V, cs: &CsEBR) -> bool {
+        self.insert(key, value, cs)
+    }
+    #[inline(always)]
+    fn remove(&self, key: &K, cs: &CsEBR) -> Option<Self::Output> {
+        self.remove(key, cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExtendibleHashMap;
+    use crate::ds_impl::circ_ebr::concurrent_map;
+
+    #[test]
+    fn smoke_extendible_hash_map() {
+        concurrent_map::tests::smoke::<ExtendibleHashMap<i32, String>>();
+    }
+
+    #[test]
+    fn edge_cases_extendible_hash_map() {
+        concurrent_map::tests::edge_cases::<ExtendibleHashMap<i32, String>>();
+    }
+}
@@ -0,0 +1,162 @@
+//! A hash map whose values are stored apart from its nodes.
+//!
+//! Each node of the buckets holds an `AtomicRc` to its value instead of the value itself, so that
+//! an update swaps in a new value without re-linking the node, and the reference count of the old
+//! value reclaims it once the readers that loaded it are done.
+//!
+//! A remove takes the value out of the node after unlinking the node, and an update fails on a
+//! node whose value was taken out. An update that races with the remove of its key may thus still
+//! succeed after the key is logically removed, in which case the remove returns the updated value.
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::michael_hash_map::HashMap;
+use circ::{AtomicRc, CsEBR, GraphNode, Pointer, Rc, Snapshot, StrongPtr};
+use std::hash::Hash;
+use std::sync::atomic::Ordering;
+
+/// A value, allocated and reference-counted on its own.
+pub struct Value<V>(V);
+
+impl<V> GraphNode<CsEBR> for Value<V> {
+    const UNIQUE_OUTDEGREE: bool = false;
+
+    #[inline]
+    fn pop_outgoings(&mut self, _: &mut Vec<Rc<Self, CsEBR>>)
+    where
+        Self: Sized,
+    {
+    }
+
+    #[inline]
+    fn pop_unique(&mut self) -> Rc<Self, CsEBR>
+    where
+        Self: Sized,
+    {
+        unimplemented!()
+    }
+}
+
+impl<V> OutputHolder<V> for Snapshot<Value<V>, CsEBR> {
+    fn output(&self) -> &V {
+        self.as_ref().map(|value| &value.0).unwrap()
+    }
+}
+
+/// The field of a node that points to its value, which is null once the node is removed.
+pub struct ValueSlot<V>(AtomicRc<Value<V>, CsEBR>);
+
+impl<V> Default for ValueSlot<V> {
+    fn default() -> Self {
+        Self(AtomicRc::null())
+    }
+}
+
+pub struct KvHashMap<K, V> {
+    inner: HashMap<K, ValueSlot<V>>,
+}
+
+impl<K, V> KvHashMap<K, V>
+where
+    K: Ord + Hash + Default,
+{
+    pub fn get(&self, key: &K, cs: &CsEBR) -> Option<Snapshot<Value<V>, CsEBR>> {
+        let node = self.inner.get(key, cs)?;
+        let value = node.output().0.load_ss(cs);
+        (!value.is_null()).then_some(value)
+    }
+
+    pub fn insert(&self, key: K, value: V, cs: &CsEBR) -> bool {
+        self.inner
+            .insert(key, ValueSlot(AtomicRc::new(Value(value))), cs)
+    }
+
+    pub fn remove(&self, key: &K, cs: &CsEBR) -> Option<Snapshot<Value<V>, CsEBR>> {
+        let node = self.inner.remove(key, cs)?;
+        let old = node.output().0.swap(Rc::null(), Ordering::AcqRel);
+        let mut value = Snapshot::new();
+        value.protect(&old, cs);
+        old.finalize(cs);
+        (!value.is_null()).then_some(value)
+    }
+
+    /// Replaces the value of `key` in its node, and returns whether the key was present.
+    pub fn update(&self, key: K, value: V, cs: &CsEBR) -> bool {
+        let Some(node) = self.inner.get(&key, cs) else {
+            return false;
+        };
+        let slot = &node.output().0;
+        let mut desired = Rc::new(Value(value));
+        let mut current = slot.load(Ordering::Acquire);
+        loop {
+            if current.is_null() {
+                return false;
+            }
+            match slot.compare_exchange(current, desired, Ordering::AcqRel, Ordering::Acquire, cs) {
+                Ok(old) => {
+                    old.finalize(cs);
+                    return true;
+                }
+                Err(e) => {
+                    current = e.current;
+                    desired = e.desired;
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for KvHashMap<K, V>
+where
+    K: Ord + Hash + Default,
+{
+    type Output = Snapshot<Value<V>, CsEBR>;
+
+    fn new() -> Self {
+        Self {
+            inner: HashMap::with_capacity(30000),
+        }
+    }
+
+    #[inline(always)]
+    fn get(&self, key: &K, cs: &CsEBR) -> Option<Self::Output> {
+        self.get(key, cs)
+    }
+    #[inline(always)]
+    fn insert(&self, key: K, value: V, cs: &CsEBR) -> bool {
+        self.insert(key, value, cs)
+    }
+    #[inline(always)]
+    fn remove(&self, key: &K, cs: &CsEBR) -> Option<Self::Output> {
+        self.remove(key, cs)
+    }
+    #[inline(always)]
+    fn update(&self, key: K, value: V, cs: &CsEBR) -> bool {
+        self.update(key, value, cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KvHashMap;
+    use crate::ds_impl::circ_ebr::concurrent_map;
+
+    #[test]
+    fn smoke_kv_hash_map() {
+        concurrent_map::tests::smoke::<KvHashMap<i32, String>>();
+    }
+
+    #[test]
+    fn edge_cases_kv_hash_map() {
+        concurrent_map::tests::edge_cases::<KvHashMap<i32, String>>();
+    }
+
+    #[test]
+    fn output_holder_kv_hash_map() {
+        concurrent_map::tests::output_holder::<KvHashMap<i32, String>>();
+    }
+
+    #[test]
+    fn update_kv_hash_map() {
+        concurrent_map::tests::update::<KvHashMap<i32, String>>();
+    }
+}
@@ -1,4 +1,7 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 use circ::{AtomicRc, CsEBR, GraphNode, Pointer, Rc, Snapshot, StrongPtr};
 
 use std::cmp::Ordering::{Equal, Greater, Less};
@@ -101,7 +104,7 @@ impl<K, V> Cursor<K, V> {
 impl<K: Ord, V> Cursor<K, V> {
     /// Clean up a chain of logically removed nodes in each traversal.
     #[inline]
-    fn find_harris(&mut self, key: &K, cs: &CsEBR) -> Result<bool, ()> {
+    fn find_harris(&mut self, key: &K, cs: &CsEBR) -> Result<bool, Retry> {
         let mut prev_next = self.curr;
         let found = loop {
             let curr_node = some_or!(self.curr.as_ref(), break false);
@@ -140,14 +143,14 @@ impl<K: Ord, V> Cursor<K, V> {
                 Ordering::Relaxed,
                 cs,
             )
-            .map_err(|_| ())?;
+            .map_err(|_| Retry::Unlink)?;
 
         Ok(found)
     }
 
     /// Clean up a single logically removed node in each traversal.
     #[inline]
-    fn find_harris_michael(&mut self, key: &K, cs: &CsEBR) -> Result<bool, ()> {
+    fn find_harris_michael(&mut self, key: &K, cs: &CsEBR) -> Result<bool, Retry> {
         loop {
             debug_assert_eq!(self.curr.tag(), 0);
 
@@ -176,9 +179,11 @@ impl<K: Ord, V> Cursor<K, V> {
 
     /// Gotta go fast. Doesn't fail.
     #[inline]
-    fn find_harris_herlihy_shavit(&mut self, key: &K, cs: &CsEBR) -> Result<bool, ()> {
+    fn find_harris_herlihy_shavit(&mut self, key: &K, cs: &CsEBR) -> Result<bool, Retry> {
+        wait_free::traversal();
         Ok(loop {
             let curr_node = some_or!(self.curr.as_ref(), break false);
+            wait_free::visit();
             let next = curr_node.next.load_ss(cs);
             match curr_node.key.cmp(key) {
                 Less => self.curr = next,
@@ -189,7 +194,7 @@ impl<K: Ord, V> Cursor<K, V> {
     }
 
     #[inline]
-    fn try_unlink_curr(&self, next: Snapshot<Node<K, V>, CsEBR>, cs: &CsEBR) -> Result<(), ()> {
+    fn try_unlink_curr(&self, next: Snapshot<Node<K, V>, CsEBR>, cs: &CsEBR) -> Result<(), Retry> {
         unsafe { self.prev.deref() }
             .next
             .compare_exchange(
@@ -200,7 +205,7 @@ impl<K: Ord, V> Cursor<K, V> {
                 cs,
             )
             .map(|_| ())
-            .map_err(|_| ())
+            .map_err(|_| Retry::Unlink)
     }
 
     /// Inserts a value.
@@ -214,7 +219,7 @@ impl<K: Ord, V> Cursor<K, V> {
             .next
             .store(self.curr.upgrade(), Ordering::Relaxed, cs);
 
-        unsafe { self.prev.deref() }
+        match unsafe { self.prev.deref() }
             .next
             .compare_exchange(
                 self.curr.as_ptr(),
@@ -223,20 +228,23 @@ impl<K: Ord, V> Cursor<K, V> {
                 Ordering::Relaxed,
                 cs,
             )
-            .map(|_| ())
-            .map_err(|e| e.desired)
+            .classify(&self.curr.as_ptr())
+        {
+            Cas::Success(_) => Ok(()),
+            Cas::CurrentDiffers(e) | Cas::TagDiffers(e) => Err(e.desired),
+        }
     }
 
     /// removes the current node.
     #[inline]
-    pub fn remove(&self, cs: &CsEBR) -> Result<(), ()> {
+    pub fn remove(&self, cs: &CsEBR) -> Result<(), Retry> {
         let curr_node = unsafe { self.curr.deref() };
 
         let next = curr_node.next.load_ss(cs);
         curr_node
             .next
             .compare_exchange_tag(next.with_tag(0), 1, Ordering::AcqRel, Ordering::Relaxed, cs)
-            .map_err(|_| ())?;
+            .map_err(|_| Retry::CasFail)?;
 
         let _ = self.try_unlink_curr(next, cs);
 
@@ -259,13 +267,14 @@ where
     #[inline]
     fn get<F>(&self, key: &K, find: F, cs: &CsEBR) -> (Cursor<K, V>, bool)
     where
-        F: Fn(&mut Cursor<K, V>, &K, &CsEBR) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V>, &K, &CsEBR) -> Result<bool, Retry>,
     {
         loop {
             let mut cursor = Cursor::new();
             cursor.initialize(&self.head, cs);
-            if let Ok(r) = find(&mut cursor, key, cs) {
-                return (cursor, r);
+            match find(&mut cursor, key, cs) {
+                Ok(r) => return (cursor, r),
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -273,7 +282,7 @@ where
     #[inline]
     fn insert<F>(&self, key: K, value: V, find: F, cs: &CsEBR) -> bool
     where
-        F: Fn(&mut Cursor<K, V>, &K, &CsEBR) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V>, &K, &CsEBR) -> Result<bool, Retry>,
     {
         let mut node = Rc::new(Node::new(key, value));
         loop {
@@ -284,7 +293,10 @@ where
             }
 
             match cursor.insert(node, cs) {
-                Err(n) => node = n,
+                Err(n) => {
+                    Retry::CasFail.count();
+                    node = n;
+                }
                 Ok(()) => return true,
             }
         }
@@ -293,7 +305,7 @@ where
     #[inline]
     fn remove<F>(&self, key: &K, find: F, cs: &CsEBR) -> Option<Snapshot<Node<K, V>, CsEBR>>
     where
-        F: Fn(&mut Cursor<K, V>, &K, &CsEBR) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V>, &K, &CsEBR) -> Result<bool, Retry>,
     {
         loop {
             let (cursor, found) = self.get(key, &find, cs);
@@ -302,7 +314,7 @@ where
             }
 
             match cursor.remove(cs) {
-                Err(()) => continue,
+                Err(retry) => retry.count(),
                 Ok(_) => return Some(cursor.curr),
             }
         }
@@ -318,12 +330,49 @@ where
             }
 
             match cursor.remove(cs) {
-                Err(()) => continue,
+                Err(retry) => retry.count(),
                 Ok(_) => return Some(cursor.curr),
             }
         }
     }
 
+    /// Calls `f` with the key and value of each node that is not logically removed, in the order
+    /// of the keys. The nodes that are inserted or removed concurrently may or may not be visited.
+    fn for_each<F: FnMut(&K, &V)>(&self, mut f: F, cs: &CsEBR) {
+        let mut cursor = Cursor::new();
+        cursor.initialize(&self.head, cs);
+        let mut curr = cursor.curr;
+        while let Some(node) = curr.as_ref() {
+            let mut next = node.next.load_ss(cs);
+            if next.tag() == 0 {
+                f(&node.key, &node.value);
+            }
+            next.set_tag(0);
+            curr = next;
+        }
+    }
+
+    /// Calls `f` with the key and value of each node in `lo..=hi` that is not logically removed,
+    /// in the order of the keys. The traversal skips the removed nodes without unlinking them, as
+    /// `harris_herlihy_shavit_get` does, and stops at the first key past `hi`. The nodes that are
+    /// inserted or removed concurrently may or may not be visited.
+    fn for_each_in_range<F: FnMut(&K, &V)>(&self, lo: &K, hi: &K, mut f: F, cs: &CsEBR) {
+        let mut cursor = Cursor::new();
+        cursor.initialize(&self.head, cs);
+        let mut curr = cursor.curr;
+        while let Some(node) = curr.as_ref() {
+            if &node.key > hi {
+                break;
+            }
+            let mut next = node.next.load_ss(cs);
+            if next.tag() == 0 && lo <= &node.key {
+                f(&node.key, &node.value);
+            }
+            next.set_tag(0);
+            curr = next;
+        }
+    }
+
     /// Omitted
     pub fn harris_get(&self, key: &K, cs: &CsEBR) -> Option<Snapshot<Node<K, V>, CsEBR>> {
         let (cursor, found) = self.get(key, Cursor::find_harris, cs);
@@ -412,6 +461,21 @@ where
     }
 }
 
+impl<K, V> HList<K, V>
+where
+    K: Ord + Default,
+    V: Default,
+{
+    /// Pushes the values of the keys in `lo..=hi` to `out`, in the order of the keys.
+    pub fn range(&self, lo: &K, hi: &K, out: &mut Vec<V>, cs: &CsEBR)
+    where
+        V: Clone,
+    {
+        self.inner
+            .for_each_in_range(lo, hi, |_, value| out.push(value.clone()), cs)
+    }
+}
+
 pub struct HMList<K, V> {
     inner: List<K, V>,
 }
@@ -430,6 +494,15 @@ where
     ) -> Option<Snapshot<Node<K, V>, CsEBR>> {
         self.inner.harris_herlihy_shavit_get(key, cs)
     }
+
+    /// Pushes the values of the keys in `lo..=hi` to `out`, in the order of the keys.
+    pub fn range(&self, lo: &K, hi: &K, out: &mut Vec<V>, cs: &CsEBR)
+    where
+        V: Clone,
+    {
+        self.inner
+            .for_each_in_range(lo, hi, |_, value| out.push(value.clone()), cs)
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for HMList<K, V>
@@ -471,6 +544,52 @@ where
     pub fn pop(&self, cs: &CsEBR) -> Option<Snapshot<Node<K, V>, CsEBR>> {
         self.inner.pop(cs)
     }
+
+    /// Calls `f` with each element of the list, in the order of the keys.
+    pub fn for_each<F: FnMut(&K, &V)>(&self, f: F, cs: &CsEBR) {
+        self.inner.for_each(f, cs)
+    }
+
+    /// Calls `f` with each element of the list whose key is in `lo..=hi`, in the order of the
+    /// keys.
+    pub fn for_each_in_range<F: FnMut(&K, &V)>(&self, lo: &K, hi: &K, f: F, cs: &CsEBR) {
+        self.inner.for_each_in_range(lo, hi, f, cs)
+    }
+
+    /// Pushes the values of the keys in `lo..=hi` to `out`, in the order of the keys.
+    pub fn range(&self, lo: &K, hi: &K, out: &mut Vec<V>, cs: &CsEBR)
+    where
+        V: Clone,
+    {
+        self.inner
+            .for_each_in_range(lo, hi, |_, value| out.push(value.clone()), cs)
+    }
+}
+
+impl<K, V> HHSList<K, V>
+where
+    K: Ord + Default + Clone,
+    V: Default + Clone,
+{
+    /// Moves all elements of `other` into this list, and returns the number of elements that are
+    /// inserted. An element whose key is already in this list is dropped.
+    ///
+    /// The nodes of `other` are popped one by one, and their elements are inserted as new nodes.
+    /// A popped node is not linked into this list instead, since a concurrent traversal of
+    /// `other` may follow its `next` until the node is unreachable.
+    pub fn merge_from(&self, other: &Self, cs: &CsEBR) -> usize {
+        let mut merged = 0;
+        while let Some(popped) = other.pop(cs) {
+            let node = popped.as_ref().unwrap();
+            if self
+                .inner
+                .harris_insert(node.key.clone(), node.value.clone(), cs)
+            {
+                merged += 1;
+            }
+        }
+        merged
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for HHSList<K, V>
@@ -508,16 +627,124 @@ mod tests {
         concurrent_map::tests::smoke::<HList<i32, String>>();
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<HList<i32, String>>();
+    }
+
     #[test]
     fn smoke_hm_list() {
         concurrent_map::tests::smoke::<HMList<i32, String>>();
     }
 
+    #[test]
+    fn edge_cases_hm_list() {
+        concurrent_map::tests::edge_cases::<HMList<i32, String>>();
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<HHSList<i32, String>>();
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<HHSList<i32, String>>();
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use circ::{Cs, CsEBR};
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String>::new();
+        let cs = &CsEBR::new();
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(key, key.to_string(), cs),
+            Op::Remove => map.remove(&key, cs).is_some(),
+            Op::Get => map.get(&key, cs).is_some(),
+        });
+    }
+
+    #[test]
+    fn output_holder_hhs_list() {
+        concurrent_map::tests::output_holder::<HHSList<i32, String>>();
+    }
+
+    #[test]
+    fn merge_hhs_list() {
+        use circ::{Cs, CsEBR};
+        use concurrent_map::ConcurrentMap;
+        use crossbeam_utils::thread;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const THREADS: i32 = 8;
+        const ELEMENTS_PER_THREADS: i32 = 1000;
+
+        let map = &HHSList::<i32, String>::new();
+        let merged = &AtomicUsize::new(0);
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let other = HHSList::new();
+                    let cs = &CsEBR::new();
+                    for i in 0..ELEMENTS_PER_THREADS {
+                        let key = i * THREADS + t;
+                        other.insert(key, key.to_string(), cs);
+                    }
+                    // A key that every thread merges, but only one inserts.
+                    other.insert(-1, (-1).to_string(), cs);
+                    merged.fetch_add(map.merge_from(&other, cs), Ordering::Relaxed);
+                    assert!(other.pop(cs).is_none());
+                });
+            }
+        })
+        .unwrap();
+
+        let total = THREADS * ELEMENTS_PER_THREADS;
+        assert_eq!(merged.load(Ordering::Relaxed), total as usize + 1);
+        let cs = &CsEBR::new();
+        for key in -1..total {
+            assert!(map.get(&key, cs).is_some());
+        }
+    }
+
+    #[test]
+    fn range_lists() {
+        use circ::{Cs, CsEBR};
+        use concurrent_map::ConcurrentMap;
+
+        let cs = &CsEBR::new();
+        let h_list = HList::<i32, String>::new();
+        let hm_list = HMList::<i32, String>::new();
+        let hhs_list = HHSList::<i32, String>::new();
+        for i in 0..100 {
+            h_list.insert(i * 2, (i * 2).to_string(), cs);
+            hm_list.insert(i * 2, (i * 2).to_string(), cs);
+            hhs_list.insert(i * 2, (i * 2).to_string(), cs);
+        }
+        for key in [10, 14] {
+            h_list.remove(&key, cs);
+            hm_list.remove(&key, cs);
+            hhs_list.remove(&key, cs);
+        }
+
+        let expected = ["8", "12", "16"].map(String::from).to_vec();
+        let mut out = vec![];
+        h_list.range(&7, &17, &mut out, cs);
+        assert_eq!(out, expected);
+        out.clear();
+        hm_list.range(&7, &17, &mut out, cs);
+        assert_eq!(out, expected);
+        out.clear();
+        hhs_list.range(&8, &16, &mut out, cs);
+        assert_eq!(out, expected);
+        out.clear();
+        hhs_list.range(&201, &300, &mut out, cs);
+        assert!(out.is_empty());
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use circ::{Cs, CsEBR, StrongPtr};
@@ -525,6 +752,7 @@ mod tests {
         let map = HHSList::new();
 
         let cs = &CsEBR::new();
+        assert!(map.pop(cs).is_none());
         map.insert(1, "1", cs);
         map.insert(2, "2", cs);
         map.insert(3, "3", cs);
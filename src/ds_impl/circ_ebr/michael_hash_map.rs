@@ -53,6 +53,28 @@ where
         let i = Self::hash(k);
         self.get_bucket(i).remove(k, cs)
     }
+
+    /// Pushes the values of the keys in `lo..=hi` to `out`, in the order of the keys.
+    ///
+    /// The keys of a range are scattered over the buckets by their hashes, so every bucket is
+    /// scanned for the keys of the range, and the gathered entries are sorted by their keys.
+    pub fn range(&self, lo: &K, hi: &K, out: &mut Vec<V>, cs: &CsEBR)
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut entries = Vec::new();
+        for bucket in &self.buckets {
+            bucket.for_each_in_range(
+                lo,
+                hi,
+                |key, value| entries.push((key.clone(), value.clone())),
+                cs,
+            );
+        }
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        out.extend(entries.into_iter().map(|(_, value)| value));
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for HashMap<K, V>
@@ -89,4 +111,24 @@ mod tests {
     fn smoke_hashmap() {
         concurrent_map::tests::smoke::<HashMap<i32, String>>();
     }
+
+    #[test]
+    fn edge_cases_hashmap() {
+        concurrent_map::tests::edge_cases::<HashMap<i32, String>>();
+    }
+
+    #[test]
+    fn range_hashmap() {
+        use circ::{Cs, CsEBR};
+
+        let map = HashMap::<i32, String>::with_capacity(7);
+        let cs = &CsEBR::new();
+        for i in (0..100).rev() {
+            map.insert(i, i.to_string(), cs);
+        }
+        map.remove(&42, cs);
+        let mut out = vec![];
+        map.range(&40, &45, &mut out, cs);
+        assert_eq!(out, ["40", "41", "43", "44", "45"].map(String::from));
+    }
 }
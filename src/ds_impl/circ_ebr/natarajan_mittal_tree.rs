@@ -1,6 +1,8 @@
 use circ::{AtomicRc, CsEBR, GraphNode, Pointer, Rc, Snapshot, StrongPtr};
 
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use std::cmp;
 use std::sync::atomic::Ordering;
 
@@ -320,6 +322,15 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire);
         let leaf_flag = Marks::from_bits_truncate(leaf_marked.tag()).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag
+                || Marks::from_bits_truncate(
+                    record.leaf_sibling_addr().load(Ordering::Acquire).tag()
+                )
+                .flag(),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -348,7 +359,18 @@ where
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load_ss(cs);
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(target_sibling.tag()).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(target_sibling.tag()).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            record.successor.tag(),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         record
             .successor_addr()
             .compare_exchange(
@@ -360,7 +382,8 @@ where
                 Ordering::Acquire,
                 cs,
             )
-            .is_ok()
+            .classify(&record.successor.as_ptr())
+            .is_success()
     }
 
     pub fn get(&self, key: &K, cs: &CsEBR) -> Option<Snapshot<Node<K, V>, CsEBR>> {
@@ -416,32 +439,38 @@ where
             };
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match record.leaf_addr().compare_exchange(
-                record.leaf.as_ptr(),
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                cs,
-            ) {
-                Ok(_) => return true,
-                Err(e) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is
-                    // redundant.
-                    new_internal = e.desired;
-                    let new_internal_ref = unsafe { new_internal.deref() };
-
-                    let new_leaf_link = match leaf_pos {
-                        Direction::L => &new_internal_ref.right,
-                        Direction::R => &new_internal_ref.left,
-                    };
-
-                    new_leaf = new_leaf_link.swap(Rc::null(), Ordering::Relaxed);
-
-                    if e.current.with_tag(Marks::empty().bits()) == record.leaf.as_ptr() {
-                        self.cleanup(&record, cs);
-                    }
-                }
+            let (e, tag_differs) = match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf.as_ptr(),
+                    new_internal,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    cs,
+                )
+                .classify(&record.leaf.as_ptr())
+            {
+                Cas::Success(_) => return true,
+                Cas::TagDiffers(e) => (e, true),
+                Cas::CurrentDiffers(e) => (e, false),
+            };
+            // Insertion failed. Help the conflicting remove operation if needed.
+            // NOTE: The paper version checks if any of the mark is set, which is redundant.
+            new_internal = e.desired;
+            let new_internal_ref = unsafe { new_internal.deref() };
+
+            let new_leaf_link = match leaf_pos {
+                Direction::L => &new_internal_ref.right,
+                Direction::R => &new_internal_ref.left,
+            };
+
+            new_leaf = new_leaf_link.swap(Rc::null(), Ordering::Relaxed);
+
+            if tag_differs {
+                InsertConflict::Help.count();
+                self.cleanup(&record, cs);
+            } else {
+                InsertConflict::Retry.count();
             }
         }
     }
@@ -461,14 +490,19 @@ where
             }
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_exchange_tag(
-                &record.leaf,
-                Marks::new(true, false).bits(),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                cs,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(record.leaf.tag(), 0, "the leaf edge is expected unmarked");
+            match record
+                .leaf_addr()
+                .compare_exchange_tag(
+                    &record.leaf,
+                    Marks::new(true, false).bits(),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    cs,
+                )
+                .classify(&record.leaf.as_ptr())
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(&record, cs) {
                         return Some(record.leaf);
@@ -476,15 +510,14 @@ where
                     // In-place cleanup failed. Enter the cleanup phase.
                     break record.leaf;
                 }
-                Err(e) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if record.leaf.as_ptr() == e.current.with_tag(Marks::empty().bits()) {
-                        self.cleanup(&record, cs);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(&record, cs);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         };
 
@@ -538,4 +571,9 @@ mod tests {
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<NMTreeMap<i32, String>>();
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<NMTreeMap<i32, String>>();
+    }
 }
@@ -449,4 +449,9 @@ mod tests {
     fn smoke_skip_list() {
         concurrent_map::tests::smoke::<SkipList<i32, String>>();
     }
+
+    #[test]
+    fn edge_cases_skip_list() {
+        concurrent_map::tests::edge_cases::<SkipList<i32, String>>();
+    }
 }
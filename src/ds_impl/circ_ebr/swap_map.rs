@@ -0,0 +1,236 @@
+//! A map behind an `AtomicRc`, which is replaced as a whole.
+//!
+//! A service that reloads a configuration snapshot builds the new snapshot aside and publishes it
+//! by exchanging the pointer to the current one, while its readers go on with the version that
+//! they have loaded. [`SwapMap`] does the same with any of the maps: an operation runs on the
+//! version that is current when it starts, and [`SwapMap::compare_and_publish`] installs a new
+//! version if the current one is still the expected one. The old version is dropped once its last
+//! reader is done, which releases all of its nodes at once. [`take_swap_stats`] reports how long
+//! the replaced versions were retained and how long it took to drop them.
+
+use circ::{AtomicRc, CsEBR, GraphNode, Pointer, Rc, Snapshot};
+
+use super::concurrent_map::ConcurrentMap;
+
+use std::fmt;
+use std::mem::{self, ManuallyDrop};
+use std::ops::Deref;
+use std::sync::atomic::Ordering;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A version of the map of a [`SwapMap`].
+pub struct Version<M> {
+    map: ManuallyDrop<M>,
+    /// When the version was replaced by a newer one.
+    replaced: OnceLock<Instant>,
+}
+
+impl<M> Version<M> {
+    pub fn new(map: M) -> Self {
+        Self {
+            map: ManuallyDrop::new(map),
+            replaced: OnceLock::new(),
+        }
+    }
+}
+
+impl<M> Deref for Version<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.map
+    }
+}
+
+impl<M> GraphNode<CsEBR> for Version<M> {
+    const UNIQUE_OUTDEGREE: bool = false;
+
+    #[inline]
+    fn pop_outgoings(&mut self, _: &mut Vec<Rc<Self, CsEBR>>)
+    where
+        Self: Sized,
+    {
+    }
+
+    #[inline]
+    fn pop_unique(&mut self) -> Rc<Self, CsEBR>
+    where
+        Self: Sized,
+    {
+        unimplemented!()
+    }
+}
+
+impl<M> Drop for Version<M> {
+    fn drop(&mut self) {
+        let start = Instant::now();
+        unsafe { ManuallyDrop::drop(&mut self.map) };
+        if let Some(replaced) = self.replaced.get() {
+            let mut stats = SWAP_STATS.lock().unwrap();
+            stats.dropped += 1;
+            stats.retention += start - *replaced;
+            stats.max_retention = stats.max_retention.max(start - *replaced);
+            stats.drop_time += start.elapsed();
+            stats.max_drop_time = stats.max_drop_time.max(start.elapsed());
+        }
+    }
+}
+
+/// A map whose current version is replaced as a whole.
+pub struct SwapMap<M> {
+    current: AtomicRc<Version<M>, CsEBR>,
+}
+
+impl<M> SwapMap<M> {
+    pub fn from_map(map: M) -> Self {
+        Self {
+            current: AtomicRc::new(Version::new(map)),
+        }
+    }
+
+    /// Loads the current version, which stays valid for the caller after it is replaced.
+    pub fn load(&self, cs: &CsEBR) -> Snapshot<Version<M>, CsEBR> {
+        self.current.load_ss(cs)
+    }
+
+    /// Publishes `desired` if `expected` is still the current version, and returns `desired`
+    /// back otherwise.
+    pub fn compare_and_publish(
+        &self,
+        expected: &Snapshot<Version<M>, CsEBR>,
+        desired: Rc<Version<M>, CsEBR>,
+        cs: &CsEBR,
+    ) -> Result<(), Rc<Version<M>, CsEBR>> {
+        match self.current.compare_exchange(
+            expected.as_ptr(),
+            desired,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            cs,
+        ) {
+            Ok(old) => {
+                let _ = unsafe { old.deref() }.replaced.set(Instant::now());
+                old.finalize(cs);
+                Ok(())
+            }
+            Err(e) => Err(e.desired),
+        }
+    }
+
+    /// Publishes `map` as the current version unconditionally.
+    pub fn publish(&self, map: M, cs: &CsEBR) {
+        let mut desired = Rc::new(Version::new(map));
+        loop {
+            let expected = self.load(cs);
+            match self.compare_and_publish(&expected, desired, cs) {
+                Ok(()) => return,
+                Err(returned) => desired = returned,
+            }
+        }
+    }
+}
+
+impl<K, V, M: ConcurrentMap<K, V>> ConcurrentMap<K, V> for SwapMap<M> {
+    type Output = M::Output;
+
+    fn new() -> Self {
+        Self::from_map(M::new())
+    }
+
+    #[inline(always)]
+    fn get(&self, key: &K, cs: &CsEBR) -> Option<Self::Output> {
+        unsafe { self.load(cs).deref() }.get(key, cs)
+    }
+
+    #[inline(always)]
+    fn insert(&self, key: K, value: V, cs: &CsEBR) -> bool {
+        unsafe { self.load(cs).deref() }.insert(key, value, cs)
+    }
+
+    #[inline(always)]
+    fn remove(&self, key: &K, cs: &CsEBR) -> Option<Self::Output> {
+        unsafe { self.load(cs).deref() }.remove(key, cs)
+    }
+
+    #[inline(always)]
+    fn update(&self, key: K, value: V, cs: &CsEBR) -> bool {
+        unsafe { self.load(cs).deref() }.update(key, value, cs)
+    }
+}
+
+/// The replaced versions that were dropped, how long they were retained after their
+/// replacement, and how long it took to drop them.
+#[derive(Default, Clone, Copy)]
+pub struct SwapStats {
+    pub dropped: u64,
+    pub retention: Duration,
+    pub max_retention: Duration,
+    pub drop_time: Duration,
+    pub max_drop_time: Duration,
+}
+
+static SWAP_STATS: Mutex<SwapStats> = Mutex::new(SwapStats {
+    dropped: 0,
+    retention: Duration::ZERO,
+    max_retention: Duration::ZERO,
+    drop_time: Duration::ZERO,
+    max_drop_time: Duration::ZERO,
+});
+
+/// Returns the statistics of the versions dropped since the last call, and resets them.
+pub fn take_swap_stats() -> SwapStats {
+    mem::take(&mut *SWAP_STATS.lock().unwrap())
+}
+
+impl fmt::Display for SwapStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dropped = self.dropped.max(1) as u32;
+        write!(
+            f,
+            "{} dropped, retained for {:?} on average (max {:?}), dropped in {:?} on average \
+             (max {:?})",
+            self.dropped,
+            self.retention / dropped,
+            self.max_retention,
+            self.drop_time / dropped,
+            self.max_drop_time
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SwapMap, Version};
+    use crate::ds_impl::circ_ebr::concurrent_map::{self, ConcurrentMap, OutputHolder};
+    use crate::ds_impl::circ_ebr::HHSList;
+    use circ::{Cs, CsEBR, Pointer, Rc};
+
+    #[test]
+    fn smoke_swap_map() {
+        concurrent_map::tests::smoke::<SwapMap<HHSList<i32, String>>>();
+    }
+
+    #[test]
+    fn publish_swap_map() {
+        let map = &SwapMap::<HHSList<i32, String>>::new();
+        let cs = &mut CsEBR::new();
+        assert!(map.insert(0, 0.to_string(), cs));
+
+        // A reader keeps the version that it has loaded.
+        let old = map.load(cs);
+        let fresh = HHSList::new();
+        assert!(fresh.insert(1, 1.to_string(), cs));
+        map.publish(fresh, cs);
+        assert!(map.get(&0, cs).is_none());
+        assert_eq!(1.to_string(), *map.get(&1, cs).unwrap().output());
+        assert_eq!(
+            0.to_string(),
+            *unsafe { old.deref() }.get(&0, cs).unwrap().output()
+        );
+
+        // The stale expectation fails.
+        let desired = Rc::new(Version::new(HHSList::new()));
+        assert!(map.compare_and_publish(&old, desired, cs).is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use circ::{AtomicRc, CsHP, GraphNode, Pointer, Rc, Snapshot, StrongPtr, TaggedCnt};
 
-use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::concurrent_map::{ConcurrentMap, OutputHolder, ProtectionSet};
+use crate::sched::yield_point;
 
-use std::{cmp, sync::atomic::Ordering};
+use std::{cmp, collections::BTreeMap, sync::atomic::Ordering};
 
 static WEIGHT: usize = 2;
 
@@ -31,7 +32,7 @@ impl Retired {
 
 /// a real node in tree or a wrapper of State node
 /// Retired node if Shared ptr of Node has RETIRED tag.
-struct Node<K, V> {
+pub struct Node<K, V> {
     key: K,
     value: V,
     size: usize,
@@ -99,12 +100,14 @@ where
         }
     }
 
-    fn load_children(&self, cs: &CsHP) -> (Snapshot<Self, CsHP>, Snapshot<Self, CsHP>) {
-        let mut left = Snapshot::new();
-        left.load(&self.left, cs);
-        let mut right = Snapshot::new();
-        right.load(&self.right, cs);
-        (left, right)
+    fn load_children<'s>(
+        &self,
+        path: &'s mut ProtectionSet<Self>,
+        cs: &CsHP,
+    ) -> (&'s Snapshot<Self, CsHP>, &'s Snapshot<Self, CsHP>) {
+        let children = path.load_pair(&self.left, &self.right, cs);
+        yield_point();
+        children
     }
 }
 
@@ -112,6 +115,8 @@ pub struct Holder<K, V> {
     root: TaggedCnt<Node<K, V>>,
     curr: Snapshot<Node<K, V>, CsHP>,
     temp: Snapshot<Node<K, V>, CsHP>,
+    /// The children of the nodes on the path of an update, protected until the update ends.
+    path: ProtectionSet<Node<K, V>>,
     found: Option<V>,
 }
 
@@ -134,6 +139,7 @@ impl<K, V> OutputHolder<V> for Cursor<K, V> {
                 root: Default::default(),
                 curr: Default::default(),
                 temp: Default::default(),
+                path: ProtectionSet::new(),
                 found: None,
             },
             root_snapshot: Default::default(),
@@ -203,7 +209,7 @@ where
             return Node::retired_node();
         }
 
-        let cur_ref = unsafe { cur.deref() };
+        let cur_ref = unsafe { self.deref(cur) };
         let key = cur_ref.key.clone();
         let value = cur_ref.value.clone();
 
@@ -236,8 +242,8 @@ where
         P1: StrongPtr<Node<K, V>, CsHP>,
         P2: StrongPtr<Node<K, V>, CsHP>,
     {
-        let right_ref = unsafe { right.deref() };
-        let (right_left, right_right) = right_ref.load_children(cs);
+        let right_ref = unsafe { self.deref(&right) };
+        let (right_left, right_right) = right_ref.load_children(self.path(), cs);
 
         if !self.check_root()
             || Node::is_retired_spot(&right_left)
@@ -272,7 +278,7 @@ where
         P3: StrongPtr<Node<K, V>, CsHP>,
         P4: StrongPtr<Node<K, V>, CsHP>,
     {
-        let right_ref = unsafe { right.deref() };
+        let right_ref = unsafe { self.deref(&right) };
         let new_left = self.mk_node(left, right_left, key, value, cs);
 
         self.mk_node(
@@ -301,9 +307,9 @@ where
         P3: StrongPtr<Node<K, V>, CsHP>,
         P4: StrongPtr<Node<K, V>, CsHP>,
     {
-        let right_ref = unsafe { right.deref() };
-        let right_left_ref = unsafe { right_left.deref() };
-        let (right_left_left, right_left_right) = right_left_ref.load_children(cs);
+        let right_ref = unsafe { self.deref(&right) };
+        let right_left_ref = unsafe { self.deref(&right_left) };
+        let (right_left_left, right_left_right) = right_left_ref.load_children(self.path(), cs);
 
         if !self.check_root()
             || Node::is_retired_spot(&right_left_left)
@@ -343,8 +349,8 @@ where
         P1: StrongPtr<Node<K, V>, CsHP>,
         P2: StrongPtr<Node<K, V>, CsHP>,
     {
-        let left_ref = unsafe { left.deref() };
-        let (left_left, left_right) = left_ref.load_children(cs);
+        let left_ref = unsafe { self.deref(&left) };
+        let (left_left, left_right) = left_ref.load_children(self.path(), cs);
 
         if !self.check_root()
             || Node::is_retired_spot(&left_right)
@@ -378,7 +384,7 @@ where
         P3: StrongPtr<Node<K, V>, CsHP>,
         P4: StrongPtr<Node<K, V>, CsHP>,
     {
-        let left_ref = unsafe { left.deref() };
+        let left_ref = unsafe { self.deref(&left) };
         let new_right = self.mk_node(left_right, right, key, value, cs);
 
         self.mk_node(
@@ -407,9 +413,9 @@ where
         P3: StrongPtr<Node<K, V>, CsHP>,
         P4: StrongPtr<Node<K, V>, CsHP>,
     {
-        let left_ref = unsafe { left.deref() };
-        let left_right_ref = unsafe { left_right.deref() };
-        let (left_right_left, left_right_right) = left_right_ref.load_children(cs);
+        let left_ref = unsafe { self.deref(&left) };
+        let left_right_ref = unsafe { self.deref(&left_right) };
+        let (left_right_left, left_right_right) = left_right_ref.load_children(self.path(), cs);
 
         if !self.check_root()
             || Node::is_retired_spot(&left_right_left)
@@ -458,8 +464,8 @@ where
             );
         }
 
-        let node_ref = unsafe { node.deref() };
-        let (left, right) = node_ref.load_children(cs);
+        let node_ref = unsafe { self.deref(&node) };
+        let (left, right) = node_ref.load_children(self.path(), cs);
 
         if !self.check_root() || Node::is_retired_spot(&left) || Node::is_retired_spot(&right) {
             return (Node::retired_node(), false);
@@ -491,8 +497,8 @@ where
             return (Rc::null(), false);
         }
 
-        let node_ref = unsafe { node.deref() };
-        let (left, right) = node_ref.load_children(cs);
+        let node_ref = unsafe { self.deref(&node) };
+        let (left, right) = node_ref.load_children(self.path(), cs);
 
         if !self.check_root() || Node::is_retired_spot(&left) || Node::is_retired_spot(&right) {
             return (Node::retired_node(), false);
@@ -535,8 +541,8 @@ where
             return (Node::retired_node(), Node::retired_node());
         }
 
-        let node_ref = unsafe { node.deref() };
-        let (left, right) = node_ref.load_children(cs);
+        let node_ref = unsafe { self.deref(&node) };
+        let (left, right) = node_ref.load_children(self.path(), cs);
 
         if !self.check_root() || Node::is_retired_spot(&left) || Node::is_retired_spot(&right) {
             return (Node::retired_node(), Node::retired_node());
@@ -569,8 +575,8 @@ where
             return (Node::retired_node(), Node::retired_node());
         }
 
-        let node_ref = unsafe { node.deref() };
-        let (left, right) = node_ref.load_children(cs);
+        let node_ref = unsafe { self.deref(&node) };
+        let (left, right) = node_ref.load_children(self.path(), cs);
 
         if !self.check_root() || Node::is_retired_spot(&left) || Node::is_retired_spot(&right) {
             return (Node::retired_node(), Node::retired_node());
@@ -591,9 +597,39 @@ where
         (left.into_rc(), succ)
     }
 
+    /// Dereferences `node`, which is either counted or a snapshot of the update, and asserts with
+    /// the `check-protection` feature that a snapshot is the loaded root or in the path.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be non-null, and protected for as long as the returned reference is used.
+    #[inline(always)]
+    unsafe fn deref<'n, P>(&self, node: &P) -> &'n Node<K, V>
+    where
+        P: StrongPtr<Node<K, V>, CsHP>,
+    {
+        let node_ref = node.deref();
+        if cfg!(feature = "check-protection") && !P::OWNS_REF_COUNT {
+            assert!(
+                node.as_ptr().as_raw() == self.holder.root.as_raw()
+                    || self.holder.path.protects(node_ref),
+                "a node of the path is dereferenced without a snapshot protecting it"
+            );
+        }
+        node_ref
+    }
+
     pub fn check_root(&self) -> bool {
         self.holder.root == self.root_link.load(Ordering::Acquire)
     }
+
+    /// The protection set of the holder, with a lifetime that is not tied to `self`, so that the
+    /// children loaded into it can be passed on to the calls that borrow `self` again. A loaded
+    /// snapshot stays in place and protected until the update releases the set.
+    #[inline]
+    fn path<'s>(&mut self) -> &'s mut ProtectionSet<Node<K, V>> {
+        unsafe { &mut *(&mut self.holder.path as *mut _) }
+    }
 }
 
 pub struct BonsaiTreeMap<K, V> {
@@ -635,6 +671,7 @@ where
                 };
                 holder.temp.load(next_link, cs);
                 Snapshot::swap(&mut holder.curr, &mut holder.temp);
+                yield_point();
             }
 
             if Node::is_retired_spot(&holder.curr) {
@@ -650,17 +687,88 @@ where
         }
     }
 
+    /// Returns the current version of the tree.
+    ///
+    /// The tree is persistent, so a version never changes, and the returned `Rc` keeps all of
+    /// its nodes alive (even after later updates remove them from the tree) until it is dropped.
+    pub fn version(&self, cs: &CsHP) -> Rc<Node<K, V>, CsHP> {
+        let mut root = Snapshot::new();
+        root.load(&self.root, cs);
+        root.upgrade()
+    }
+
+    /// Looks up `key` in a version returned by [`BonsaiTreeMap::version`].
+    pub fn get_in(
+        version: &Rc<Node<K, V>, CsHP>,
+        key: &K,
+        cursor: &mut Cursor<K, V>,
+        cs: &CsHP,
+    ) -> bool {
+        let holder = &mut cursor.holder;
+        holder.curr.protect(version, cs);
+        loop {
+            let curr_node = some_or!(holder.curr.as_ref(), return false);
+            let next_link = match key.cmp(&curr_node.key) {
+                cmp::Ordering::Equal => break,
+                cmp::Ordering::Less => &curr_node.left,
+                cmp::Ordering::Greater => &curr_node.right,
+            };
+            holder.temp.load(next_link, cs);
+            Snapshot::swap(&mut holder.curr, &mut holder.temp);
+        }
+        holder.found = Some(unsafe { holder.curr.deref() }.value.clone());
+        true
+    }
+
+    /// Copies the keys in `lo..=hi` and their values out of the current version of the tree.
+    ///
+    /// The copy is consistent since a version never changes. Its nodes are held by reference
+    /// counts while they are visited, so it protects one node at a time however wide the range is.
+    pub fn clone_range(&self, lo: &K, hi: &K, cs: &CsHP) -> BTreeMap<K, V> {
+        Self::clone_range_in(&self.version(cs), lo, hi, cs)
+    }
+
+    /// Copies the keys in `lo..=hi` and their values out of a version returned by
+    /// [`BonsaiTreeMap::version`].
+    pub fn clone_range_in(
+        version: &Rc<Node<K, V>, CsHP>,
+        lo: &K,
+        hi: &K,
+        cs: &CsHP,
+    ) -> BTreeMap<K, V> {
+        let mut range = BTreeMap::new();
+        let mut child = Snapshot::new();
+        let mut pending = vec![version.clone()];
+        while let Some(node) = pending.pop() {
+            let node_ref = some_or!(node.as_ref(), continue);
+            if lo < &node_ref.key {
+                child.load(&node_ref.left, cs);
+                pending.push(child.upgrade());
+            }
+            if &node_ref.key < hi {
+                child.load(&node_ref.right, cs);
+                pending.push(child.upgrade());
+            }
+            if lo <= &node_ref.key && &node_ref.key <= hi {
+                range.insert(node_ref.key.clone(), node_ref.value.clone());
+            }
+        }
+        range
+    }
+
     pub fn insert(&self, key: K, value: V, cursor: &mut Cursor<K, V>, cs: &CsHP) -> bool {
         let mut state = State::new(&self.root, &mut cursor.holder);
         loop {
             cursor.root_snapshot.load(&self.root, cs);
             state.holder.root = cursor.root_snapshot.as_ptr();
             let (new_root, inserted) = state.do_insert(&cursor.root_snapshot, &key, &value, cs);
+            state.holder.path.release();
 
             if Node::is_retired(new_root.as_ptr()) {
                 continue;
             }
 
+            yield_point();
             if self
                 .root
                 .compare_exchange(
@@ -683,11 +791,13 @@ where
             cursor.root_snapshot.load(&self.root, cs);
             state.holder.root = cursor.root_snapshot.as_ptr();
             let (new_root, found) = state.do_remove(&cursor.root_snapshot, key, cs);
+            state.holder.path.release();
 
             if Node::is_retired(new_root.as_ptr()) {
                 continue;
             }
 
+            yield_point();
             if self
                 .root
                 .compare_exchange(
@@ -732,10 +842,56 @@ where
 #[cfg(test)]
 mod tests {
     use super::BonsaiTreeMap;
-    use crate::ds_impl::circ_hp::concurrent_map;
+    use crate::ds_impl::circ_hp::concurrent_map::{self, ConcurrentMap};
+    use circ::{Cs, CsHP};
 
     #[test]
     fn smoke_bonsai_tree() {
         concurrent_map::tests::smoke::<_, BonsaiTreeMap<i32, String>, _>(&|a| a.to_string());
     }
+
+    #[test]
+    fn edge_cases_bonsai_tree() {
+        concurrent_map::tests::edge_cases::<_, BonsaiTreeMap<i32, String>, _>(&|a| a.to_string());
+    }
+
+    #[test]
+    fn pull_stress_bonsai_tree() {
+        concurrent_map::tests::pull_stress::<_, BonsaiTreeMap<i32, String>, _>(&|a| a.to_string());
+    }
+
+    #[cfg(feature = "shuttle")]
+    #[test]
+    fn shuttle_bonsai_tree() {
+        concurrent_map::tests::shuttle::<_, BonsaiTreeMap<i32, String>>(|a| a.to_string(), 1000);
+    }
+
+    #[test]
+    fn clone_range_bonsai_tree() {
+        let map = &BonsaiTreeMap::<i32, String>::new();
+        let cursor = &mut BonsaiTreeMap::empty_output();
+        let cs = &CsHP::new();
+        for i in 0..100 {
+            assert!(map.insert(i, i.to_string(), cursor, cs));
+        }
+        let version = map.version(cs);
+        for i in 40..60 {
+            assert!(map.remove(&i, cursor, cs));
+        }
+
+        let range = map.clone_range(&30, &69, cs);
+        assert_eq!(
+            (30..40).chain(60..70).collect::<Vec<_>>(),
+            range.keys().copied().collect::<Vec<_>>()
+        );
+        assert!(range.iter().all(|(k, v)| k.to_string() == *v));
+
+        // The version taken before the removes still has the removed keys.
+        let range = BonsaiTreeMap::clone_range_in(&version, &30, &69, cs);
+        assert_eq!(
+            (30..70).collect::<Vec<_>>(),
+            range.keys().copied().collect::<Vec<_>>()
+        );
+        assert!(map.clone_range(&100, &200, cs).is_empty());
+    }
 }
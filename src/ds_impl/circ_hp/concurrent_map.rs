@@ -1,4 +1,5 @@
-use circ::CsHP;
+use circ::{AtomicRc, CsHP, GraphNode, Snapshot, StrongPtr};
+use std::ptr;
 
 pub trait OutputHolder<V> {
     fn default() -> Self;
@@ -16,6 +17,109 @@ pub trait ConcurrentMap<K, V> {
     fn get(&self, key: &K, output: &mut Self::Output, cs: &CsHP) -> bool;
     fn insert(&self, key: K, value: V, output: &mut Self::Output, cs: &CsHP) -> bool;
     fn remove(&self, key: &K, output: &mut Self::Output, cs: &CsHP) -> bool;
+
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    /// The value is also left in `output`, as with `get`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(&self, key: K, value: V, output: &mut Self::Output, cs: &CsHP) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if self.get(&key, output, cs) {
+                return Some(output.output().clone());
+            }
+            if self.insert(key.clone(), value.clone(), output, cs) {
+                return None;
+            }
+        }
+    }
+
+    /// Replaces the value of `key`, and returns whether the key was present.
+    ///
+    /// By default, the node of the key is removed and a new one is inserted, which re-links the
+    /// key. A structure that updates its values in place replaces this.
+    fn update(&self, key: K, value: V, output: &mut Self::Output, cs: &CsHP) -> bool {
+        self.remove(&key, output, cs) && self.insert(key, value, output, cs)
+    }
+}
+
+/// A set of snapshots that grows with the depth of an operation, for the structures whose
+/// operations protect a number of nodes that depends on the height of the structure, e.g., the two
+/// children of each node on the path of a tree.
+///
+/// The slots are taken in order during an operation and released together at its end. They stay
+/// in the set for the next operations, so a thread adds slots only when an operation goes deeper
+/// than every operation before it. Each slot is boxed, so that a taken snapshot stays in place
+/// while the set grows.
+pub struct ProtectionSet<T> {
+    slots: Vec<Box<Snapshot<T, CsHP>>>,
+    taken: usize,
+}
+
+impl<T: GraphNode<CsHP>> Default for ProtectionSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: GraphNode<CsHP>> ProtectionSet<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            taken: 0,
+        }
+    }
+
+    /// Loads `first` and `second` into the next two slots, and adds them to the set if every slot
+    /// is taken.
+    pub fn load_pair(
+        &mut self,
+        first: &AtomicRc<T, CsHP>,
+        second: &AtomicRc<T, CsHP>,
+        cs: &CsHP,
+    ) -> (&Snapshot<T, CsHP>, &Snapshot<T, CsHP>) {
+        while self.slots.len() < self.taken + 2 {
+            self.slots.push(Box::new(Snapshot::new()));
+        }
+        let (head, tail) = self.slots[self.taken..].split_at_mut(1);
+        self.taken += 2;
+        head[0].load(first, cs);
+        tail[0].load(second, cs);
+        (&head[0], &tail[0])
+    }
+
+    /// Clears the taken slots, and keeps them for the next operation.
+    pub fn release(&mut self) {
+        for slot in &mut self.slots[..self.taken] {
+            slot.clear();
+        }
+        self.taken = 0;
+    }
+
+    /// The number of slots in the set.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Whether one of the taken slots protects `node`.
+    pub fn protects(&self, node: &T) -> bool {
+        protects(self.slots[..self.taken].iter().map(|slot| &**slot), node)
+    }
+}
+
+/// Whether one of `snapshots` protects `node`, for the assertions of the `check-protection`
+/// feature on the nodes that a structure dereferences.
+pub fn protects<'s, T: 's>(
+    snapshots: impl IntoIterator<Item = &'s Snapshot<T, CsHP>>,
+    node: &T,
+) -> bool {
+    snapshots
+        .into_iter()
+        .any(|snapshot| snapshot.as_ref().is_some_and(|n| ptr::eq(n, node)))
 }
 
 #[cfg(test)]
@@ -25,10 +129,13 @@ pub mod tests {
     use circ::{Cs, CsHP};
     use crossbeam_utils::thread;
     use rand::prelude::*;
+    use std::collections::VecDeque;
     use std::fmt::Debug;
 
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
+    const PULL_KEYS: i32 = 200;
+    const PULL_ROUNDS: i32 = 5;
 
     pub fn smoke<V, M, F>(to_value: &F)
     where
@@ -92,4 +199,290 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Checks that an output keeps the value of the last operation that returned `true` with it
+    /// until it is used again or the critical section is cleared, while the same thread uses other
+    /// outputs and another thread removes the keys and inserts them again with other values.
+    pub fn output_holder<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let output = &mut M::empty_output();
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(i, to_value(&i), output, &CsHP::new()));
+        }
+
+        let cs = &mut CsHP::new();
+        let got = &mut M::empty_output();
+        let removed = &mut M::empty_output();
+        assert!(map.get(&0, got, cs));
+        assert!(map.remove(&1, removed, cs));
+        assert!(map.remove(&0, output, cs));
+        assert!(map.insert(0, to_value(&-1), output, cs));
+        assert!(map.get(&0, output, cs));
+        assert_eq!(to_value(&-1), *output.output());
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                let output = &mut M::empty_output();
+                let cs = &mut CsHP::new();
+                for i in 0..ELEMENTS_PER_THREADS {
+                    map.remove(&i, output, cs);
+                    assert!(map.insert(i, to_value(&(i + 1)), output, cs));
+                    cs.clear();
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(to_value(&0), *got.output());
+        assert_eq!(to_value(&1), *removed.output());
+        // Reusing an output replaces its value.
+        assert!(map.get(&2, got, cs));
+        assert_eq!(to_value(&3), *got.output());
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let output = &mut M::empty_output();
+        let cs = &CsHP::new();
+
+        // An empty map.
+        assert!(!map.get(&0, output, cs));
+        assert!(!map.remove(&0, output, cs));
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(0, to_value(&0), output, cs));
+        assert!(!map.insert(0, to_value(&1), output, cs));
+        assert!(map.get(&0, output, cs));
+        assert_eq!(to_value(&0), *output.output());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert!(map.remove(&0, output, cs));
+        assert_eq!(to_value(&0), *output.output());
+        assert!(!map.get(&0, output, cs));
+        assert!(!map.remove(&0, output, cs));
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(i, to_value(&i), output, cs));
+            }
+            assert!(map.remove(&removed, output, cs));
+            assert_eq!(to_value(&removed), *output.output());
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(&i, output, cs));
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert!(map.remove(&i, output, cs));
+                assert_eq!(to_value(&i), *output.output());
+            }
+        }
+        for i in 0..=3 {
+            assert!(!map.get(&i, output, cs));
+        }
+    }
+
+    /// Removes the keys of a tree in the order of the levels of a balanced tree, medians first, and
+    /// inserts them back in the same order, for a few rounds. Most keys then have two children when
+    /// they are removed, which pulls the rightmost node of the left subtree up into their place, and
+    /// the rest have a child on one side, which pulls the leftmost node of the right subtree. Both
+    /// rebuild the path to the pulled node, which the uniform keys of `smoke` rarely do. The tree is
+    /// prefilled in an increasing order, which skews it until it is rebalanced. Each thread owns
+    /// every `THREADS`-th key, and checks that its keys next to each removed key keep their values.
+    pub fn pull_stress<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let output = &mut M::empty_output();
+        let cs = &mut CsHP::new();
+        for i in 0..THREADS * PULL_KEYS {
+            assert!(map.insert(i, to_value(&i), output, &CsHP::new()));
+        }
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let output = &mut M::empty_output();
+                    let cs = &mut CsHP::new();
+                    let keys = medians_first(PULL_KEYS);
+                    for _ in 0..PULL_ROUNDS {
+                        let mut removed = vec![false; PULL_KEYS as usize];
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.remove(&i, output, cs));
+                            assert_eq!(to_value(&i), *output.output());
+                            cs.clear();
+                            removed[k as usize] = true;
+                            for n in [k - 1, k + 1] {
+                                if (0..PULL_KEYS).contains(&n) && !removed[n as usize] {
+                                    let j = n * THREADS + t;
+                                    assert!(map.get(&j, output, cs));
+                                    assert_eq!(to_value(&j), *output.output());
+                                    cs.clear();
+                                }
+                            }
+                        }
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.insert(i, to_value(&i), output, &CsHP::new()));
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let output = &mut M::empty_output();
+        let cs = &mut CsHP::new();
+        for j in 0..THREADS * PULL_KEYS {
+            assert!(map.get(&j, output, cs));
+            assert_eq!(to_value(&j), *output.output());
+            cs.clear();
+        }
+    }
+
+    /// The keys in `0..n` in the order of the levels of a balanced tree, medians first.
+    fn medians_first(n: i32) -> Vec<i32> {
+        let mut keys = Vec::with_capacity(n as usize);
+        let mut ranges = VecDeque::from([(0, n)]);
+        while let Some((lo, hi)) = ranges.pop_front() {
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                keys.push(mid);
+                ranges.push_back((lo, mid));
+                ranges.push_back((mid + 1, hi));
+            }
+        }
+        keys
+    }
+
+    /// Checks that an update replaces the value of a present key, while an output of the old value
+    /// keeps it, and fails on an absent or removed key.
+    pub fn update<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let output = &mut M::empty_output();
+        let cs = &mut CsHP::new();
+        assert!(!map.update(0, to_value(&0), output, cs));
+        assert!(!map.get(&0, output, cs));
+
+        assert!(map.insert(0, to_value(&0), output, cs));
+        let old = &mut M::empty_output();
+        assert!(map.get(&0, old, cs));
+        assert!(map.update(0, to_value(&1), output, cs));
+        assert!(map.get(&0, output, cs));
+        assert_eq!(to_value(&1), *output.output());
+        assert_eq!(to_value(&0), *old.output());
+        assert!(map.remove(&0, output, cs));
+        assert_eq!(to_value(&1), *output.output());
+        assert!(!map.update(0, to_value(&2), output, cs));
+        assert!(!map.get(&0, output, cs));
+        cs.clear();
+
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(i, to_value(&i), output, cs));
+        }
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let output = &mut M::empty_output();
+                    let cs = &mut CsHP::new();
+                    for i in 0..ELEMENTS_PER_THREADS {
+                        assert!(map.update(i, to_value(&(i + t)), output, cs));
+                        cs.clear();
+                    }
+                });
+            }
+        })
+        .unwrap();
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.get(&i, output, cs));
+            assert!((i..i + THREADS).any(|v| to_value(&v) == *output.output()));
+        }
+    }
+
+    #[cfg(feature = "shuttle")]
+    const SHUTTLE_THREADS: i32 = 3;
+    #[cfg(feature = "shuttle")]
+    const SHUTTLE_ELEMENTS_PER_THREADS: i32 = 4;
+
+    /// Runs a few threads of `insert`, `get`, and `remove` on interleaved key ranges under
+    /// `shuttle`, for `iterations` random schedules.
+    ///
+    /// Each thread inserts its keys and then removes every other one, checking each result on
+    /// the way. The structure is checked at the end of each schedule.
+    #[cfg(feature = "shuttle")]
+    pub fn shuttle<V, M>(to_value: fn(&i32) -> V, iterations: usize)
+    where
+        V: Eq + Debug + 'static,
+        M: ConcurrentMap<i32, V> + Send + Sync + 'static,
+    {
+        use std::sync::Arc;
+
+        crate::sched::enable_yield_points();
+        shuttle::check_random(
+            move || {
+                let map = Arc::new(M::new());
+                let threads = (0..SHUTTLE_THREADS)
+                    .map(|t| {
+                        let map = map.clone();
+                        shuttle::thread::spawn(move || {
+                            let output = &mut M::empty_output();
+                            let cs = &mut CsHP::new();
+                            let keys =
+                                (0..SHUTTLE_ELEMENTS_PER_THREADS).map(|k| k * SHUTTLE_THREADS + t);
+                            for i in keys.clone() {
+                                assert!(map.insert(i, to_value(&i), output, cs));
+                                cs.clear();
+                                assert!(map.get(&i, output, cs));
+                                assert_eq!(to_value(&i), *output.output());
+                                cs.clear();
+                            }
+                            for i in keys.step_by(2) {
+                                assert!(map.remove(&i, output, cs));
+                                assert_eq!(to_value(&i), *output.output());
+                                cs.clear();
+                                assert!(!map.get(&i, output, cs));
+                                cs.clear();
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                for thread in threads {
+                    thread.join().unwrap();
+                }
+
+                let output = &mut M::empty_output();
+                let cs = &mut CsHP::new();
+                for k in 0..SHUTTLE_ELEMENTS_PER_THREADS {
+                    for t in 0..SHUTTLE_THREADS {
+                        let i = k * SHUTTLE_THREADS + t;
+                        assert_eq!(k % 2 == 1, map.get(&i, output, cs));
+                        cs.clear();
+                    }
+                }
+            },
+            iterations,
+        );
+    }
 }
@@ -0,0 +1,11 @@
+use circ::CsHP;
+
+/// A concurrent LIFO stack.
+pub trait ConcurrentStack<T> {
+    type Holder: Default;
+
+    fn new() -> Self;
+    fn push(&self, item: T, holder: &mut Self::Holder, cs: &CsHP);
+    /// Removes the last pushed item, if there is one.
+    fn pop(&self, holder: &mut Self::Holder, cs: &CsHP) -> Option<T>;
+}
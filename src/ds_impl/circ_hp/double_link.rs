@@ -25,7 +25,7 @@ impl<T> Holder<T> {
     }
 }
 
-struct Node<T> {
+pub(crate) struct Node<T> {
     item: Option<T>,
     prev: AtomicWeak<Node<T>, CsHP>,
     next: CachePadded<AtomicRc<Node<T>, CsHP>>,
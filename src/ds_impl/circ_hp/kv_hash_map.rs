@@ -0,0 +1,174 @@
+//! The hash map of `circ_ebr::kv_hash_map`, whose values are stored apart from its nodes, on
+//! CIRC-HP.
+//!
+//! An output protects the value that it holds with a snapshot of its own, so that the value
+//! outlives the protection of its node by the cursor.
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::list::Cursor;
+use super::michael_hash_map::HashMap;
+use circ::{AtomicRc, CsHP, GraphNode, Pointer, Rc, Snapshot, StrongPtr};
+use std::hash::Hash;
+use std::sync::atomic::Ordering;
+
+/// A value, allocated and reference-counted on its own.
+pub struct Value<V>(V);
+
+impl<V> GraphNode<CsHP> for Value<V> {
+    const UNIQUE_OUTDEGREE: bool = false;
+
+    #[inline]
+    fn pop_outgoings(&mut self, _: &mut Vec<Rc<Self, CsHP>>)
+    where
+        Self: Sized,
+    {
+    }
+
+    #[inline]
+    fn pop_unique(&mut self) -> Rc<Self, CsHP>
+    where
+        Self: Sized,
+    {
+        unimplemented!()
+    }
+}
+
+/// The field of a node that points to its value, which is null once the node is removed.
+pub struct ValueSlot<V>(AtomicRc<Value<V>, CsHP>);
+
+impl<V> Default for ValueSlot<V> {
+    fn default() -> Self {
+        Self(AtomicRc::null())
+    }
+}
+
+pub struct Output<K, V> {
+    cursor: Cursor<K, ValueSlot<V>>,
+    value: Snapshot<Value<V>, CsHP>,
+}
+
+impl<K, V> OutputHolder<V> for Output<K, V> {
+    fn default() -> Self {
+        Self {
+            cursor: <Cursor<K, ValueSlot<V>> as OutputHolder<ValueSlot<V>>>::default(),
+            value: Snapshot::new(),
+        }
+    }
+
+    fn output(&self) -> &V {
+        self.value.as_ref().map(|value| &value.0).unwrap()
+    }
+}
+
+pub struct KvHashMap<K, V> {
+    inner: HashMap<K, ValueSlot<V>>,
+}
+
+impl<K, V> KvHashMap<K, V>
+where
+    K: Ord + Hash + Default,
+{
+    pub fn get(&self, key: &K, output: &mut Output<K, V>, cs: &CsHP) -> bool {
+        if !self.inner.get(key, &mut output.cursor, cs) {
+            return false;
+        }
+        output.value.load(&output.cursor.output().0, cs);
+        !output.value.is_null()
+    }
+
+    pub fn insert(&self, key: K, value: V, output: &mut Output<K, V>, cs: &CsHP) -> bool {
+        let slot = ValueSlot(AtomicRc::new(Value(value)));
+        self.inner.insert(key, slot, &mut output.cursor, cs)
+    }
+
+    pub fn remove(&self, key: &K, output: &mut Output<K, V>, cs: &CsHP) -> bool {
+        if !self.inner.remove(key, &mut output.cursor, cs) {
+            return false;
+        }
+        let old = output.cursor.output().0.swap(Rc::null(), Ordering::AcqRel);
+        output.value.protect(&old, cs);
+        old.finalize(cs);
+        !output.value.is_null()
+    }
+
+    /// Replaces the value of `key` in its node, and returns whether the key was present.
+    pub fn update(&self, key: K, value: V, output: &mut Output<K, V>, cs: &CsHP) -> bool {
+        if !self.inner.get(&key, &mut output.cursor, cs) {
+            return false;
+        }
+        let slot = &output.cursor.output().0;
+        let mut desired = Rc::new(Value(value));
+        let mut current = slot.load(Ordering::Acquire);
+        loop {
+            if current.is_null() {
+                return false;
+            }
+            match slot.compare_exchange(current, desired, Ordering::AcqRel, Ordering::Acquire, cs) {
+                Ok(old) => {
+                    old.finalize(cs);
+                    return true;
+                }
+                Err(e) => {
+                    current = e.current;
+                    desired = e.desired;
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for KvHashMap<K, V>
+where
+    K: Ord + Hash + Default,
+{
+    type Output = Output<K, V>;
+
+    fn new() -> Self {
+        Self {
+            inner: HashMap::with_capacity(30000),
+        }
+    }
+
+    #[inline(always)]
+    fn get(&self, key: &K, output: &mut Self::Output, cs: &CsHP) -> bool {
+        self.get(key, output, cs)
+    }
+    #[inline(always)]
+    fn insert(&self, key: K, value: V, output: &mut Self::Output, cs: &CsHP) -> bool {
+        self.insert(key, value, output, cs)
+    }
+    #[inline(always)]
+    fn remove(&self, key: &K, output: &mut Self::Output, cs: &CsHP) -> bool {
+        self.remove(key, output, cs)
+    }
+    #[inline(always)]
+    fn update(&self, key: K, value: V, output: &mut Self::Output, cs: &CsHP) -> bool {
+        self.update(key, value, output, cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KvHashMap;
+    use crate::ds_impl::circ_hp::concurrent_map;
+
+    #[test]
+    fn smoke_kv_hash_map() {
+        concurrent_map::tests::smoke::<_, KvHashMap<i32, String>, _>(&|a| a.to_string());
+    }
+
+    #[test]
+    fn edge_cases_kv_hash_map() {
+        concurrent_map::tests::edge_cases::<_, KvHashMap<i32, String>, _>(&|a| a.to_string());
+    }
+
+    #[test]
+    fn output_holder_kv_hash_map() {
+        concurrent_map::tests::output_holder::<_, KvHashMap<i32, String>, _>(&|a| a.to_string());
+    }
+
+    #[test]
+    fn update_kv_hash_map() {
+        concurrent_map::tests::update::<_, KvHashMap<i32, String>, _>(&|a| a.to_string());
+    }
+}
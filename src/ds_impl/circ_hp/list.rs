@@ -1,4 +1,7 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 use circ::{AtomicRc, CsHP, GraphNode, Pointer, Rc, Snapshot, StrongPtr};
 
 use std::cmp::Ordering::{Equal, Greater, Less};
@@ -114,7 +117,7 @@ impl<K, V> Cursor<K, V> {
 impl<K: Ord, V> Cursor<K, V> {
     /// Clean up a chain of logically removed nodes in each traversal.
     #[inline]
-    fn find_harris(&mut self, key: &K, cs: &CsHP) -> Result<bool, ()> {
+    fn find_harris(&mut self, key: &K, cs: &CsHP) -> Result<bool, Retry> {
         let found = loop {
             // * 0 deleted: <prev> -> <curr>
             // * 1 deleted: <anchor> -> <prev> -x-> <curr>
@@ -177,7 +180,7 @@ impl<K: Ord, V> Cursor<K, V> {
                 Ordering::Relaxed,
                 cs,
             )
-            .map_err(|_| ())?;
+            .map_err(|_| Retry::Unlink)?;
 
         Snapshot::swap(&mut self.anchor, &mut self.prev);
         Ok(found)
@@ -185,7 +188,7 @@ impl<K: Ord, V> Cursor<K, V> {
 
     /// Clean up a single logically removed node in each traversal.
     #[inline]
-    fn find_harris_michael(&mut self, key: &K, cs: &CsHP) -> Result<bool, ()> {
+    fn find_harris_michael(&mut self, key: &K, cs: &CsHP) -> Result<bool, Retry> {
         loop {
             debug_assert_eq!(self.curr.tag(), 0);
 
@@ -214,9 +217,11 @@ impl<K: Ord, V> Cursor<K, V> {
 
     /// Gotta go fast. Doesn't fail.
     #[inline]
-    fn find_harris_herlihy_shavit(&mut self, key: &K, cs: &CsHP) -> Result<bool, ()> {
+    fn find_harris_herlihy_shavit(&mut self, key: &K, cs: &CsHP) -> Result<bool, Retry> {
+        wait_free::traversal();
         Ok(loop {
             let curr_node = some_or!(self.curr.as_ref(), break false);
+            wait_free::visit();
             self.next.load(&curr_node.next, cs);
             match curr_node.key.cmp(key) {
                 Less => Snapshot::swap(&mut self.curr, &mut self.next),
@@ -227,7 +232,7 @@ impl<K: Ord, V> Cursor<K, V> {
     }
 
     #[inline]
-    fn try_unlink_curr(&mut self, cs: &CsHP) -> Result<(), ()> {
+    fn try_unlink_curr(&mut self, cs: &CsHP) -> Result<(), Retry> {
         unsafe { self.prev.deref() }
             .next
             .compare_exchange(
@@ -238,7 +243,7 @@ impl<K: Ord, V> Cursor<K, V> {
                 cs,
             )
             .map(|_| ())
-            .map_err(|_| ())
+            .map_err(|_| Retry::Unlink)
     }
 
     /// Inserts a value.
@@ -252,7 +257,7 @@ impl<K: Ord, V> Cursor<K, V> {
             .next
             .store(self.curr.upgrade(), Ordering::Relaxed, cs);
 
-        unsafe { self.prev.deref() }
+        match unsafe { self.prev.deref() }
             .next
             .compare_exchange(
                 self.curr.as_ptr(),
@@ -261,13 +266,16 @@ impl<K: Ord, V> Cursor<K, V> {
                 Ordering::Relaxed,
                 cs,
             )
-            .map(|_| ())
-            .map_err(|e| e.desired)
+            .classify(&self.curr.as_ptr())
+        {
+            Cas::Success(_) => Ok(()),
+            Cas::CurrentDiffers(e) | Cas::TagDiffers(e) => Err(e.desired),
+        }
     }
 
     /// removes the current node.
     #[inline]
-    pub fn remove(&mut self, cs: &CsHP) -> Result<(), ()> {
+    pub fn remove(&mut self, cs: &CsHP) -> Result<(), Retry> {
         let curr_node = unsafe { self.curr.deref() };
 
         self.next.load(&curr_node.next, cs);
@@ -280,7 +288,7 @@ impl<K: Ord, V> Cursor<K, V> {
                 Ordering::Relaxed,
                 cs,
             )
-            .map_err(|_| ())?;
+            .map_err(|_| Retry::CasFail)?;
 
         let _ = self.try_unlink_curr(cs);
 
@@ -303,12 +311,13 @@ where
     #[inline]
     fn get<F>(&self, key: &K, find: F, cursor: &mut Cursor<K, V>, cs: &CsHP) -> bool
     where
-        F: Fn(&mut Cursor<K, V>, &K, &CsHP) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V>, &K, &CsHP) -> Result<bool, Retry>,
     {
         loop {
             cursor.initialize(&self.head, cs);
-            if let Ok(r) = find(cursor, key, cs) {
-                return r;
+            match find(cursor, key, cs) {
+                Ok(r) => return r,
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -316,7 +325,7 @@ where
     #[inline]
     fn insert<F>(&self, key: K, value: V, find: F, cursor: &mut Cursor<K, V>, cs: &CsHP) -> bool
     where
-        F: Fn(&mut Cursor<K, V>, &K, &CsHP) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V>, &K, &CsHP) -> Result<bool, Retry>,
     {
         let mut node = Rc::new(Node::new(key, value));
         loop {
@@ -327,7 +336,10 @@ where
             }
 
             match cursor.insert(node, cs) {
-                Err(n) => node = n,
+                Err(n) => {
+                    Retry::CasFail.count();
+                    node = n;
+                }
                 Ok(()) => return true,
             }
         }
@@ -336,7 +348,7 @@ where
     #[inline]
     fn remove<F>(&self, key: &K, find: F, cursor: &mut Cursor<K, V>, cs: &CsHP) -> bool
     where
-        F: Fn(&mut Cursor<K, V>, &K, &CsHP) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<K, V>, &K, &CsHP) -> Result<bool, Retry>,
     {
         loop {
             let found = self.get(key, &find, cursor, cs);
@@ -345,7 +357,7 @@ where
             }
 
             match cursor.remove(cs) {
-                Err(()) => continue,
+                Err(retry) => retry.count(),
                 Ok(_) => return true,
             }
         }
@@ -360,7 +372,7 @@ where
             }
 
             match cursor.remove(cs) {
-                Err(()) => continue,
+                Err(retry) => retry.count(),
                 Ok(_) => return true,
             }
         }
@@ -406,6 +418,26 @@ where
     pub fn harris_herlihy_shavit_get(&self, key: &K, cursor: &mut Cursor<K, V>, cs: &CsHP) -> bool {
         self.get(key, Cursor::find_harris_herlihy_shavit, cursor, cs)
     }
+
+    /// Calls `f` with the key and value of each node in `lo..=hi` that is not logically removed,
+    /// in the order of the keys. The traversal skips the removed nodes without unlinking them, as
+    /// `harris_herlihy_shavit_get` does, and stops at the first key past `hi`, protecting two
+    /// nodes at a time with the snapshots of a cursor of its own. The nodes that are inserted or
+    /// removed concurrently may or may not be visited.
+    fn for_each_in_range<F: FnMut(&K, &V)>(&self, lo: &K, hi: &K, mut f: F, cs: &CsHP) {
+        let mut cursor = Cursor::new();
+        cursor.initialize(&self.head, cs);
+        while let Some(node) = cursor.curr.as_ref() {
+            if &node.key > hi {
+                break;
+            }
+            cursor.next.load(&node.next, cs);
+            if cursor.next.tag() == 0 && lo <= &node.key {
+                f(&node.key, &node.value);
+            }
+            Snapshot::swap(&mut cursor.curr, &mut cursor.next);
+        }
+    }
 }
 
 pub struct HList<K, V> {
@@ -437,6 +469,21 @@ where
     }
 }
 
+impl<K, V> HList<K, V>
+where
+    K: Ord + Default,
+    V: Default,
+{
+    /// Pushes the values of the keys in `lo..=hi` to `out`, in the order of the keys.
+    pub fn range(&self, lo: &K, hi: &K, out: &mut Vec<V>, cs: &CsHP)
+    where
+        V: Clone,
+    {
+        self.inner
+            .for_each_in_range(lo, hi, |_, value| out.push(value.clone()), cs)
+    }
+}
+
 pub struct HMList<K, V> {
     inner: List<K, V>,
 }
@@ -451,6 +498,21 @@ where
     pub fn get_harris_herlihy_shavit(&self, key: &K, cursor: &mut Cursor<K, V>, cs: &CsHP) -> bool {
         self.inner.harris_herlihy_shavit_get(key, cursor, cs)
     }
+
+    /// Calls `f` with each element of the list whose key is in `lo..=hi`, in the order of the
+    /// keys.
+    pub fn for_each_in_range<F: FnMut(&K, &V)>(&self, lo: &K, hi: &K, f: F, cs: &CsHP) {
+        self.inner.for_each_in_range(lo, hi, f, cs)
+    }
+
+    /// Pushes the values of the keys in `lo..=hi` to `out`, in the order of the keys.
+    pub fn range(&self, lo: &K, hi: &K, out: &mut Vec<V>, cs: &CsHP)
+    where
+        V: Clone,
+    {
+        self.inner
+            .for_each_in_range(lo, hi, |_, value| out.push(value.clone()), cs)
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for HMList<K, V>
@@ -492,6 +554,39 @@ where
     pub fn pop(&self, cursor: &mut Cursor<K, V>, cs: &CsHP) -> bool {
         self.inner.pop(cursor, cs)
     }
+
+    /// Pushes the values of the keys in `lo..=hi` to `out`, in the order of the keys.
+    pub fn range(&self, lo: &K, hi: &K, out: &mut Vec<V>, cs: &CsHP)
+    where
+        V: Clone,
+    {
+        self.inner
+            .for_each_in_range(lo, hi, |_, value| out.push(value.clone()), cs)
+    }
+}
+
+impl<K, V> HHSList<K, V>
+where
+    K: Ord + Default + Clone,
+    V: Default + Clone,
+{
+    /// Moves all elements of `other` into this list, and returns the number of elements that are
+    /// inserted. An element whose key is already in this list is dropped.
+    ///
+    /// The nodes of `other` are popped one by one, and their elements are inserted as new nodes.
+    /// A popped node is not linked into this list instead, since a concurrent traversal of
+    /// `other` may follow its `next` until the node is unreachable.
+    pub fn merge_from(&self, other: &Self, cursor: &mut Cursor<K, V>, cs: &CsHP) -> usize {
+        let mut merged = 0;
+        while other.pop(cursor, cs) {
+            let node = unsafe { cursor.curr.deref() };
+            let (key, value) = (node.key.clone(), node.value.clone());
+            if self.inner.harris_insert(key, value, cursor, cs) {
+                merged += 1;
+            }
+        }
+        merged
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for HHSList<K, V>
@@ -530,16 +625,128 @@ mod tests {
         concurrent_map::tests::smoke::<_, HList<i32, String>, _>(&|a| a.to_string());
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<_, HList<i32, String>, _>(&|a| a.to_string());
+    }
+
     #[test]
     fn smoke_hm_list() {
         concurrent_map::tests::smoke::<_, HMList<i32, String>, _>(&|a| a.to_string());
     }
 
+    #[test]
+    fn edge_cases_hm_list() {
+        concurrent_map::tests::edge_cases::<_, HMList<i32, String>, _>(&|a| a.to_string());
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<_, HHSList<i32, String>, _>(&|a| a.to_string());
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<_, HHSList<i32, String>, _>(&|a| a.to_string());
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use circ::Cs;
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String>::new();
+        let output = &mut HHSList::<i32, String>::empty_output();
+        let cs = &CsHP::new();
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(key, key.to_string(), output, cs),
+            Op::Remove => map.remove(&key, output, cs),
+            Op::Get => map.get(&key, output, cs),
+        });
+    }
+
+    #[test]
+    fn output_holder_hhs_list() {
+        concurrent_map::tests::output_holder::<_, HHSList<i32, String>, _>(&|a| a.to_string());
+    }
+
+    #[test]
+    fn merge_hhs_list() {
+        use circ::Cs;
+        use concurrent_map::ConcurrentMap;
+        use crossbeam_utils::thread;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const THREADS: i32 = 8;
+        const ELEMENTS_PER_THREADS: i32 = 1000;
+
+        let map = &HHSList::<i32, String>::new();
+        let merged = &AtomicUsize::new(0);
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let other = HHSList::new();
+                    let output = &mut HHSList::empty_output();
+                    let cs = &CsHP::new();
+                    for i in 0..ELEMENTS_PER_THREADS {
+                        let key = i * THREADS + t;
+                        other.insert(key, key.to_string(), output, cs);
+                    }
+                    // A key that every thread merges, but only one inserts.
+                    other.insert(-1, (-1).to_string(), output, cs);
+                    merged.fetch_add(map.merge_from(&other, output, cs), Ordering::Relaxed);
+                    assert!(!other.pop(output, cs));
+                });
+            }
+        })
+        .unwrap();
+
+        let total = THREADS * ELEMENTS_PER_THREADS;
+        assert_eq!(merged.load(Ordering::Relaxed), total as usize + 1);
+        let output = &mut HHSList::empty_output();
+        let cs = &CsHP::new();
+        for key in -1..total {
+            assert!(map.get(&key, output, cs));
+        }
+    }
+
+    #[test]
+    fn range_lists() {
+        use circ::Cs;
+        use concurrent_map::ConcurrentMap;
+
+        let output = &mut HHSList::<i32, String>::empty_output();
+        let cs = &CsHP::new();
+        let h_list = HList::<i32, String>::new();
+        let hm_list = HMList::<i32, String>::new();
+        let hhs_list = HHSList::<i32, String>::new();
+        for i in 0..100 {
+            h_list.insert(i * 2, (i * 2).to_string(), output, cs);
+            hm_list.insert(i * 2, (i * 2).to_string(), output, cs);
+            hhs_list.insert(i * 2, (i * 2).to_string(), output, cs);
+        }
+        for key in [10, 14] {
+            h_list.remove(&key, output, cs);
+            hm_list.remove(&key, output, cs);
+            hhs_list.remove(&key, output, cs);
+        }
+
+        let expected = ["8", "12", "16"].map(String::from).to_vec();
+        let mut out = vec![];
+        h_list.range(&7, &17, &mut out, cs);
+        assert_eq!(out, expected);
+        out.clear();
+        hm_list.range(&7, &17, &mut out, cs);
+        assert_eq!(out, expected);
+        out.clear();
+        hhs_list.range(&8, &16, &mut out, cs);
+        assert_eq!(out, expected);
+        out.clear();
+        hhs_list.range(&201, &300, &mut out, cs);
+        assert!(out.is_empty());
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use circ::Cs;
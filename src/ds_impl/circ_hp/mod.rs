@@ -1,19 +1,27 @@
 pub mod concurrent_map;
+pub mod concurrent_stack;
 
 pub mod bonsai_tree;
 pub mod double_link;
 pub mod elim_ab_tree;
+pub mod kv_hash_map;
 pub mod list;
 pub mod michael_hash_map;
 pub mod natarajan_mittal_tree;
 pub mod skip_list;
+pub mod swap_map;
+pub mod treiber_stack;
 
 pub use self::concurrent_map::{ConcurrentMap, OutputHolder};
+pub use self::concurrent_stack::ConcurrentStack;
 
 pub use self::bonsai_tree::BonsaiTreeMap;
 pub use self::double_link::DoubleLink;
 pub use self::elim_ab_tree::ElimABTree;
+pub use self::kv_hash_map::KvHashMap;
 pub use self::list::{HHSList, HList, HMList};
 pub use self::michael_hash_map::HashMap;
 pub use self::natarajan_mittal_tree::NMTreeMap;
 pub use self::skip_list::SkipList;
+pub use self::swap_map::SwapMap;
+pub use self::treiber_stack::{EliminationStack, TreiberStack};
@@ -1,6 +1,8 @@
 use circ::{AtomicRc, CsHP, GraphNode, Pointer, Rc, Snapshot, StrongPtr};
 
-use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::concurrent_map::{protects, ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use std::cmp;
 use std::mem::swap;
 use std::sync::atomic::Ordering;
@@ -86,7 +88,7 @@ where
     }
 }
 
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: Key<K>,
     value: Option<V>,
     left: AtomicRc<Node<K, V>, CsHP>,
@@ -148,40 +150,44 @@ enum Direction {
 /// All Shared<_> are unmarked.
 ///
 /// All of the edges of path from `successor` to `parent` are in the process of removal.
+///
+/// A `get` only uses the hot part of the record, `leaf`, `curr`, and `found`, which comes first so
+/// that it shares the fewest cache lines with the cold part that only the updates use.
+#[repr(C)]
 pub struct SeekRecord<K, V> {
+    /// The end of the access path.
+    leaf: Snapshot<Node<K, V>, CsHP>,
+    /// The next node of the access path.
+    curr: Snapshot<Node<K, V>, CsHP>,
+    /// The found node for Get and Remove operation.
+    found: Snapshot<Node<K, V>, CsHP>,
     /// Parent of `successor`
     ancestor: Snapshot<Node<K, V>, CsHP>,
     /// The first internal node with a marked outgoing edge.
     /// It is null if the successor is equal to the parent.
     successor: Snapshot<Node<K, V>, CsHP>,
-    /// The direction of successor from ancestor.
-    successor_dir: Direction,
     /// Parent of `leaf`
     parent: Snapshot<Node<K, V>, CsHP>,
-    /// The end of the access path.
-    leaf: Snapshot<Node<K, V>, CsHP>,
+    /// The direction of successor from ancestor.
+    successor_dir: Direction,
     /// The direction of leaf from parent.
     leaf_dir: Direction,
-    /// The next node of the access path.
-    curr: Snapshot<Node<K, V>, CsHP>,
     /// The direction of curr from leaf.
     curr_dir: Direction,
-    /// The found node for Get and Remove operation.
-    found: Snapshot<Node<K, V>, CsHP>,
 }
 
 impl<K, V> OutputHolder<V> for SeekRecord<K, V> {
     fn default() -> Self {
         Self {
+            leaf: Default::default(),
+            curr: Default::default(),
+            found: Default::default(),
             ancestor: Default::default(),
             successor: Default::default(),
-            successor_dir: Default::default(),
             parent: Default::default(),
-            leaf: Default::default(),
+            successor_dir: Default::default(),
             leaf_dir: Default::default(),
-            curr: Default::default(),
             curr_dir: Default::default(),
-            found: Default::default(),
         }
     }
 
@@ -191,6 +197,28 @@ impl<K, V> OutputHolder<V> for SeekRecord<K, V> {
 }
 
 impl<K, V> SeekRecord<K, V> {
+    /// Returns `node`, a reference that outlives the snapshot that it was dereferenced from, and
+    /// asserts with the `check-protection` feature that another snapshot of the record still
+    /// protects it.
+    #[inline(always)]
+    fn protected<'n>(&self, node: &'n Node<K, V>) -> &'n Node<K, V> {
+        if cfg!(feature = "check-protection") {
+            let snapshots = [
+                &self.leaf,
+                &self.curr,
+                &self.found,
+                &self.ancestor,
+                &self.successor,
+                &self.parent,
+            ];
+            assert!(
+                protects(snapshots, node),
+                "a node of the seek record is dereferenced without a snapshot protecting it"
+            );
+        }
+        node
+    }
+
     fn successor_addr(&self) -> &AtomicRc<Node<K, V>, CsHP> {
         match self.successor_dir {
             Direction::L => &unsafe { self.ancestor.deref() }.left,
@@ -249,7 +277,11 @@ where
         record
             .parent
             .load(&unsafe { record.ancestor.deref() }.left, cs);
-        record.successor.clear();
+        // The seeks only ever clear `successor`, so a reused record has it null already, and
+        // clearing it again would reset its hazard pointer for nothing.
+        if !record.successor.is_null() {
+            record.successor.clear();
+        }
         record.leaf.load(&unsafe { record.parent.deref() }.left, cs);
         record.leaf.set_tag(Marks::empty().bits());
         record.successor_dir = Direction::L;
@@ -268,7 +300,9 @@ where
                 // untagged edge: advance ancestor and successor pointers
                 Snapshot::swap(&mut record.ancestor, &mut record.parent);
                 // `successsor` is equal to `parent` after this step.
-                record.successor.clear();
+                if !record.successor.is_null() {
+                    record.successor.clear();
+                }
                 record.successor_dir = record.leaf_dir;
             }
 
@@ -282,6 +316,7 @@ where
 
             // update other variables
             prev_tag = Marks::from_bits_truncate(curr_tag).tag();
+            let curr_node = record.protected(curr_node);
             if curr_node.key.cmp(key) == cmp::Ordering::Greater {
                 record.curr_dir = Direction::L;
                 record.curr.load(&curr_node.left, cs);
@@ -293,27 +328,26 @@ where
     }
 
     /// Similar to `seek`, but traverse the tree with only two pointers
+    ///
+    /// It starts from the root with `leaf` and `curr`, which go left past the nodes with infinite
+    /// keys, so that it leaves the cold part of the record alone. The tags of `curr` are left as
+    /// is, since a snapshot is dereferenced without its tag and `get` only reads the leaf.
     fn seek_leaf(&self, key: &K, record: &mut SeekRecord<K, V>, cs: &CsHP) {
-        record.ancestor.load(&self.r, cs);
-        record
-            .parent
-            .load(&unsafe { record.ancestor.deref() }.left, cs);
-        record.leaf.load(&unsafe { record.parent.deref() }.left, cs);
+        record.leaf.load(&self.r, cs);
         record.curr.load(&unsafe { record.leaf.deref() }.left, cs);
-        record.curr.set_tag(Marks::empty().bits());
 
         while !record.curr.is_null() {
             // Safety of deref: Even if `record.curr` is mutated by `swap`, `curr_node` is
             // protected by `record.leaf`.
             let curr_node = unsafe { record.curr.deref() };
             Snapshot::swap(&mut record.leaf, &mut record.curr);
+            let curr_node = record.protected(curr_node);
 
             if curr_node.key.cmp(key) == cmp::Ordering::Greater {
                 record.curr.load(&curr_node.left, cs);
             } else {
                 record.curr.load(&curr_node.right, cs);
             }
-            record.curr.set_tag(Marks::empty().bits());
         }
     }
 
@@ -324,6 +358,17 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire);
         let leaf_flag = Marks::from_bits_truncate(leaf_marked.tag()).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag || {
+                let sibling_addr = match record.leaf_dir {
+                    Direction::L => &unsafe { record.parent.deref() }.right,
+                    Direction::R => &unsafe { record.parent.deref() }.left,
+                };
+                Marks::from_bits_truncate(sibling_addr.load(Ordering::Acquire).tag()).flag()
+            },
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             match record.leaf_dir {
                 Direction::L => &unsafe { record.parent.deref() }.right,
@@ -359,15 +404,27 @@ where
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = &record.curr;
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(target_sibling.tag()).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(target_sibling.tag()).flag();
+        let successor = if record.successor.is_null() {
+            record.parent.as_ptr()
+        } else {
+            record.successor.as_ptr()
+        };
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            successor.tag(),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         record
             .successor_addr()
             .compare_exchange(
-                if record.successor.is_null() {
-                    record.parent.as_ptr()
-                } else {
-                    record.successor.as_ptr()
-                },
+                successor,
                 target_sibling
                     .upgrade()
                     .with_tag(Marks::new(flag, false).bits()),
@@ -375,7 +432,8 @@ where
                 Ordering::Acquire,
                 cs,
             )
-            .is_ok()
+            .classify(&successor)
+            .is_success()
     }
 
     pub fn get(&self, key: &K, record: &mut SeekRecord<K, V>, cs: &CsHP) -> bool {
@@ -428,32 +486,38 @@ where
             };
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match record.leaf_addr().compare_exchange(
-                record.leaf.as_ptr(),
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                cs,
-            ) {
-                Ok(_) => return true,
-                Err(e) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is
-                    // redundant.
-                    new_internal = e.desired;
-                    let new_internal_ref = unsafe { new_internal.deref() };
-
-                    let new_leaf_link = match leaf_pos {
-                        Direction::L => &new_internal_ref.right,
-                        Direction::R => &new_internal_ref.left,
-                    };
-
-                    new_leaf = new_leaf_link.swap(Rc::null(), Ordering::Relaxed);
-
-                    if e.current.with_tag(Marks::empty().bits()) == record.leaf.as_ptr() {
-                        self.cleanup(record, cs);
-                    }
-                }
+            let (e, tag_differs) = match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf.as_ptr(),
+                    new_internal,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    cs,
+                )
+                .classify(&record.leaf.as_ptr())
+            {
+                Cas::Success(_) => return true,
+                Cas::TagDiffers(e) => (e, true),
+                Cas::CurrentDiffers(e) => (e, false),
+            };
+            // Insertion failed. Help the conflicting remove operation if needed.
+            // NOTE: The paper version checks if any of the mark is set, which is redundant.
+            new_internal = e.desired;
+            let new_internal_ref = unsafe { new_internal.deref() };
+
+            let new_leaf_link = match leaf_pos {
+                Direction::L => &new_internal_ref.right,
+                Direction::R => &new_internal_ref.left,
+            };
+
+            new_leaf = new_leaf_link.swap(Rc::null(), Ordering::Relaxed);
+
+            if tag_differs {
+                InsertConflict::Help.count();
+                self.cleanup(record, cs);
+            } else {
+                InsertConflict::Retry.count();
             }
         }
     }
@@ -473,14 +537,19 @@ where
             }
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_exchange_tag(
-                &record.leaf,
-                Marks::new(true, false).bits(),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                cs,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(record.leaf.tag(), 0, "the leaf edge is expected unmarked");
+            match record
+                .leaf_addr()
+                .compare_exchange_tag(
+                    &record.leaf,
+                    Marks::new(true, false).bits(),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    cs,
+                )
+                .classify(&record.leaf.as_ptr())
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(record, cs) {
                         Snapshot::swap(&mut record.leaf, &mut record.found);
@@ -491,15 +560,14 @@ where
                     Snapshot::swap(&mut record.leaf, &mut record.found);
                     break leaf;
                 }
-                Err(e) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if record.leaf.as_ptr() == e.current.with_tag(Marks::empty().bits()) {
-                        self.cleanup(record, cs);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(record, cs);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         };
 
@@ -553,4 +621,9 @@ mod tests {
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<_, NMTreeMap<i32, String>, _>(&|a| a.to_string());
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<_, NMTreeMap<i32, String>, _>(&|a| a.to_string());
+    }
 }
@@ -8,7 +8,7 @@ const MAX_HEIGHT: usize = 32;
 
 type Tower<K, V> = [AtomicRc<Node<K, V>, CsHP>; MAX_HEIGHT];
 
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: K,
     value: V,
     next: Tower<K, V>,
@@ -475,4 +475,9 @@ mod tests {
     fn smoke_skip_list() {
         concurrent_map::tests::smoke::<_, SkipList<i32, String>, _>(&|a| a.to_string());
     }
+
+    #[test]
+    fn edge_cases_skip_list() {
+        concurrent_map::tests::edge_cases::<_, SkipList<i32, String>, _>(&|a| a.to_string());
+    }
 }
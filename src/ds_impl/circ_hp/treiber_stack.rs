@@ -0,0 +1,293 @@
+//! Treiber's stack, and its variant with an elimination array.
+//!
+//! A pop reads the next node of the head and swings the head to it, which is the textbook case of
+//! the ABA problem: the head may be popped, freed, and pushed again at the same address in
+//! between. The snapshot of the head keeps it from being freed until the CAS, which rules it out.
+
+use super::concurrent_stack::ConcurrentStack;
+use crate::ds_impl::elimination::EliminationArray;
+
+use std::cell::Cell;
+use std::sync::atomic::Ordering;
+
+use circ::{AtomicRc, CsHP, GraphNode, Pointer, Rc, Snapshot, StrongPtr};
+use crossbeam_utils::CachePadded;
+
+pub struct Node<T> {
+    /// Taken by the pop that unlinks the node.
+    item: Cell<Option<T>>,
+    next: AtomicRc<Self, CsHP>,
+}
+
+impl<T> GraphNode<CsHP> for Node<T> {
+    const UNIQUE_OUTDEGREE: bool = false;
+
+    #[inline]
+    fn pop_outgoings(&mut self, _: &mut Vec<Rc<Self, CsHP>>)
+    where
+        Self: Sized,
+    {
+    }
+
+    #[inline]
+    fn pop_unique(&mut self) -> Rc<Self, CsHP>
+    where
+        Self: Sized,
+    {
+        unimplemented!()
+    }
+}
+
+impl<T> Node<T> {
+    fn new(item: T) -> Self {
+        Self {
+            item: Cell::new(Some(item)),
+            next: AtomicRc::null(),
+        }
+    }
+}
+
+unsafe impl<T: Send> Sync for Node<T> {}
+unsafe impl<T: Send> Send for Node<T> {}
+
+pub struct Holder<T> {
+    head: Snapshot<Node<T>, CsHP>,
+    next: Snapshot<Node<T>, CsHP>,
+}
+
+impl<T> Default for Holder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Holder<T> {
+    pub fn new() -> Self {
+        Self {
+            head: Snapshot::new(),
+            next: Snapshot::new(),
+        }
+    }
+}
+
+pub struct TreiberStack<T> {
+    head: CachePadded<AtomicRc<Node<T>, CsHP>>,
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TreiberStack<T> {
+    pub fn new() -> Self {
+        Self {
+            head: CachePadded::new(AtomicRc::null()),
+        }
+    }
+
+    /// Tries to link `node` on the head once, and returns it back if the head changed.
+    #[inline]
+    fn try_link(
+        &self,
+        node: Rc<Node<T>, CsHP>,
+        holder: &mut Holder<T>,
+        cs: &CsHP,
+    ) -> Result<(), Rc<Node<T>, CsHP>> {
+        holder.head.load(&self.head, cs);
+        unsafe { node.deref() }
+            .next
+            .store(&holder.head, Ordering::Relaxed, cs);
+        self.head
+            .compare_exchange(
+                holder.head.as_ptr(),
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+                cs,
+            )
+            .map(|_| ())
+            .map_err(|e| e.desired)
+    }
+
+    /// Tries to unlink the head once, and fails if the head changed.
+    #[inline]
+    fn try_pop(&self, holder: &mut Holder<T>, cs: &CsHP) -> Result<Option<T>, ()> {
+        holder.head.load(&self.head, cs);
+        let Some(head) = holder.head.as_ref() else {
+            return Ok(None);
+        };
+        holder.next.load(&head.next, cs);
+        self.head
+            .compare_exchange(
+                holder.head.as_ptr(),
+                holder.next.upgrade(),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+                cs,
+            )
+            .map(|_| head.item.take())
+            .map_err(|_| ())
+    }
+
+    pub fn push(&self, item: T, holder: &mut Holder<T>, cs: &CsHP) {
+        let mut node = Rc::new(Node::new(item));
+        while let Err(back) = self.try_link(node, holder, cs) {
+            node = back;
+        }
+    }
+
+    pub fn pop(&self, holder: &mut Holder<T>, cs: &CsHP) -> Option<T> {
+        loop {
+            if let Ok(item) = self.try_pop(holder, cs) {
+                return item;
+            }
+        }
+    }
+}
+
+/// Treiber's stack that backs off to an elimination array after each failed CAS on its head.
+pub struct EliminationStack<T> {
+    stack: TreiberStack<T>,
+    elimination: EliminationArray<T>,
+}
+
+impl<T> Default for EliminationStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> EliminationStack<T> {
+    pub fn new() -> Self {
+        Self {
+            stack: TreiberStack::new(),
+            elimination: EliminationArray::default(),
+        }
+    }
+
+    pub fn push(&self, item: T, holder: &mut Holder<T>, cs: &CsHP) {
+        let mut item = item;
+        loop {
+            match self.stack.try_link(Rc::new(Node::new(item)), holder, cs) {
+                Ok(()) => return,
+                Err(node) => item = unsafe { node.deref() }.item.take().unwrap(),
+            }
+            match self.elimination.push(item) {
+                Ok(()) => return,
+                Err(back) => item = back,
+            }
+        }
+    }
+
+    pub fn pop(&self, holder: &mut Holder<T>, cs: &CsHP) -> Option<T> {
+        loop {
+            if let Ok(item) = self.stack.try_pop(holder, cs) {
+                return item;
+            }
+            if let Some(item) = self.elimination.pop() {
+                return Some(item);
+            }
+        }
+    }
+}
+
+impl<T> ConcurrentStack<T> for TreiberStack<T> {
+    type Holder = Holder<T>;
+
+    fn new() -> Self {
+        TreiberStack::new()
+    }
+
+    #[inline(always)]
+    fn push(&self, item: T, holder: &mut Self::Holder, cs: &CsHP) {
+        self.push(item, holder, cs)
+    }
+
+    #[inline(always)]
+    fn pop(&self, holder: &mut Self::Holder, cs: &CsHP) -> Option<T> {
+        self.pop(holder, cs)
+    }
+}
+
+impl<T> ConcurrentStack<T> for EliminationStack<T> {
+    type Holder = Holder<T>;
+
+    fn new() -> Self {
+        EliminationStack::new()
+    }
+
+    #[inline(always)]
+    fn push(&self, item: T, holder: &mut Self::Holder, cs: &CsHP) {
+        self.push(item, holder, cs)
+    }
+
+    #[inline(always)]
+    fn pop(&self, holder: &mut Self::Holder, cs: &CsHP) -> Option<T> {
+        self.pop(holder, cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EliminationStack, TreiberStack};
+    use crate::ds_impl::circ_hp::concurrent_stack::ConcurrentStack;
+    use circ::{Cs, CsHP};
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn simple<S: ConcurrentStack<String>>() {
+        let stack = S::new();
+        let holder = &mut S::Holder::default();
+        let cs = &CsHP::new();
+        assert!(stack.pop(holder, cs).is_none());
+        stack.push("1".to_string(), holder, cs);
+        stack.push("2".to_string(), holder, cs);
+        assert_eq!(stack.pop(holder, cs).unwrap(), "2");
+        stack.push("3".to_string(), holder, cs);
+        assert_eq!(stack.pop(holder, cs).unwrap(), "3");
+        assert_eq!(stack.pop(holder, cs).unwrap(), "1");
+        assert!(stack.pop(holder, cs).is_none());
+        // The remaining items are dropped with the stack.
+        stack.push("4".to_string(), holder, cs);
+    }
+
+    fn smoke<S: ConcurrentStack<usize> + Sync>() {
+        const THREADS: usize = 30;
+        const ELEMENTS_PER_THREAD: usize = 10000;
+
+        let stack = S::new();
+        let mut found = Vec::new();
+        found.resize_with(THREADS * ELEMENTS_PER_THREAD, || AtomicU32::new(0));
+
+        scope(|s| {
+            for t in 0..THREADS {
+                let (stack, found) = (&stack, &found);
+                s.spawn(move |_| {
+                    let holder = &mut S::Holder::default();
+                    for i in 0..ELEMENTS_PER_THREAD {
+                        let cs = &CsHP::new();
+                        stack.push(t * ELEMENTS_PER_THREAD + i, holder, cs);
+                        let item = stack.pop(holder, cs).unwrap();
+                        found[item].fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        })
+        .unwrap();
+        assert!(found.iter().all(|f| f.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn treiber() {
+        simple::<TreiberStack<_>>();
+        smoke::<TreiberStack<_>>();
+    }
+
+    #[test]
+    fn elimination() {
+        simple::<EliminationStack<_>>();
+        smoke::<EliminationStack<_>>();
+    }
+}
@@ -0,0 +1,189 @@
+//! Counts of events of the structures, kept per thread.
+//!
+//! A structure counts an event, e.g., a retry, on a thread-local counter, which is added to the
+//! totals when the thread exits, so that counting does not add contention to the operations that
+//! it counts. A [`Counters`] of `N` kinds of events is declared with its thread-local counts:
+//!
+//! ```ignore
+//! static COUNTERS: Counters<2> = Counters::new(&LOCAL);
+//!
+//! thread_local! {
+//!     static LOCAL: LocalCounts<2> = LocalCounts::new(&COUNTERS);
+//! }
+//! ```
+//!
+//! A benchmark thread also marks the start and the end of its measured window with
+//! [`Counters::arrive`] and [`Counters::depart`], so that the counts of the prefill and of the
+//! other phases are left out of [`Counters::measured`].
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::LocalKey;
+
+/// The counts of `N` kinds of events, of the exited threads and of the running ones.
+pub struct Counters<const N: usize> {
+    totals: [AtomicUsize; N],
+    /// The counts of the measured windows of the threads that have departed.
+    measured: [AtomicUsize; N],
+    local: &'static LocalKey<LocalCounts<N>>,
+}
+
+/// The counts of the calling thread, which are added to the totals when it exits.
+pub struct LocalCounts<const N: usize> {
+    counts: [Cell<usize>; N],
+    /// The counts when the thread arrived at its measured window.
+    arrival: [Cell<usize>; N],
+    counters: &'static Counters<N>,
+}
+
+impl<const N: usize> Counters<N> {
+    pub const fn new(local: &'static LocalKey<LocalCounts<N>>) -> Self {
+        Self {
+            totals: [const { AtomicUsize::new(0) }; N],
+            measured: [const { AtomicUsize::new(0) }; N],
+            local,
+        }
+    }
+
+    /// Counts an event of the kind `index`.
+    #[inline]
+    pub fn count(&self, index: usize) {
+        self.add(index, 1);
+    }
+
+    /// Counts `n` events of the kind `index`.
+    #[inline]
+    pub fn add(&self, index: usize, n: usize) {
+        if self
+            .local
+            .try_with(|local| local.counts[index].set(local.counts[index].get() + n))
+            .is_err()
+        {
+            // The thread is exiting.
+            self.totals[index].fetch_add(n, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the counts since the start of the process, including those of the calling thread,
+    /// but not those of the other running threads.
+    pub fn get(&self) -> [usize; N] {
+        let mut counts = [0; N];
+        for (count, total) in counts.iter_mut().zip(self.totals.iter()) {
+            *count = total.load(Ordering::Relaxed);
+        }
+        let _ = self.local.try_with(|local| {
+            for (count, local) in counts.iter_mut().zip(local.counts.iter()) {
+                *count += local.get();
+            }
+        });
+        counts
+    }
+
+    /// Marks the start of the measured window of the calling thread.
+    pub fn arrive(&self) {
+        let _ = self.local.try_with(|local| {
+            for (arrival, count) in local.arrival.iter().zip(local.counts.iter()) {
+                arrival.set(count.get());
+            }
+        });
+    }
+
+    /// Adds the counts of the calling thread since it arrived to those of the measured windows.
+    pub fn depart(&self) {
+        let _ = self.local.try_with(|local| {
+            for ((measured, arrival), count) in self
+                .measured
+                .iter()
+                .zip(local.arrival.iter())
+                .zip(local.counts.iter())
+            {
+                measured.fetch_add(count.get() - arrival.get(), Ordering::Relaxed);
+                arrival.set(count.get());
+            }
+        });
+    }
+
+    /// Returns the counts of the measured windows of the threads that have departed.
+    pub fn measured(&self) -> [usize; N] {
+        self.measured
+            .each_ref()
+            .map(|measured| measured.load(Ordering::Relaxed))
+    }
+}
+
+impl<const N: usize> LocalCounts<N> {
+    pub const fn new(counters: &'static Counters<N>) -> Self {
+        Self {
+            counts: [const { Cell::new(0) }; N],
+            arrival: [const { Cell::new(0) }; N],
+            counters,
+        }
+    }
+}
+
+impl<const N: usize> Drop for LocalCounts<N> {
+    fn drop(&mut self) {
+        for (total, count) in self.counters.totals.iter().zip(self.counts.iter()) {
+            total.fetch_add(count.get(), Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Counters, LocalCounts};
+    use std::thread;
+
+    static COUNTERS: Counters<2> = Counters::new(&LOCAL);
+
+    thread_local! {
+        static LOCAL: LocalCounts<2> = LocalCounts::new(&COUNTERS);
+    }
+
+    #[test]
+    fn counts_of_exited_and_calling_threads() {
+        COUNTERS.count(0);
+        assert_eq!(COUNTERS.get(), [1, 0]);
+        let threads = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    COUNTERS.count(1);
+                    COUNTERS.add(0, 2);
+                    // The counts of the other running threads are not included yet.
+                    assert!(COUNTERS.get()[1] >= 1);
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(COUNTERS.get(), [9, 4]);
+    }
+
+    #[test]
+    fn counts_of_measured_windows() {
+        static COUNTERS: Counters<2> = Counters::new(&LOCAL);
+
+        thread_local! {
+            static LOCAL: LocalCounts<2> = LocalCounts::new(&COUNTERS);
+        }
+
+        let threads = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    COUNTERS.add(0, 5);
+                    COUNTERS.arrive();
+                    COUNTERS.count(0);
+                    COUNTERS.count(1);
+                    COUNTERS.depart();
+                    COUNTERS.count(1);
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(COUNTERS.measured(), [4, 4]);
+        assert_eq!(COUNTERS.get(), [24, 8]);
+    }
+}
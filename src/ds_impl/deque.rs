@@ -0,0 +1,44 @@
+//! What the work-stealing deques of the schemes share: the result of a steal, and the counts of
+//! the buffers that the deques retire when they resize and that are reclaimed.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The result of a steal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Steal<T> {
+    /// The deque was empty.
+    Empty,
+    /// The first item was taken.
+    Success(T),
+    /// Another stealer or the owner took the first item first, so the steal may be retried.
+    Retry,
+}
+
+static RETIRED: AtomicU64 = AtomicU64::new(0);
+static RECLAIMED: AtomicU64 = AtomicU64::new(0);
+
+/// The buffers that the deques retired, and the buffers that were freed, which are the retired
+/// ones until a deque is dropped with its last buffer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BufferStats {
+    pub retired: u64,
+    pub reclaimed: u64,
+}
+
+#[inline]
+pub(crate) fn count_retired() {
+    RETIRED.fetch_add(1, Ordering::Relaxed);
+}
+
+#[inline]
+pub(crate) fn count_reclaimed() {
+    RECLAIMED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the buffers retired and freed since the last call, and resets the counts.
+pub fn take_buffer_stats() -> BufferStats {
+    BufferStats {
+        retired: RETIRED.swap(0, Ordering::Relaxed),
+        reclaimed: RECLAIMED.swap(0, Ordering::Relaxed),
+    }
+}
@@ -33,7 +33,7 @@ impl Retired {
 /// a real node in tree or a wrapper of State node
 /// Retired node if Shared ptr of Node has RETIRED tag.
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: K,
     value: V,
     size: usize,
@@ -499,6 +499,135 @@ where
         }
     }
 
+    /// Removes the keys of the subtree of `node` that are at least `lo` and below `hi`, where a
+    /// missing bound is one that every key of the subtree is known to satisfy, and returns the new
+    /// subtree and the number of the removed keys.
+    ///
+    /// The paths to the bounds are copied, and a subtree within both bounds is retired whole
+    /// instead of being rebuilt. The two subtrees left by a removed node are joined back.
+    fn do_remove_range(
+        &mut self,
+        node: Shared<'g, Node<K, V>>,
+        lo: Option<&K>,
+        hi: Option<&K>,
+        guard: &'g Guard,
+    ) -> (Shared<'g, Node<K, V>>, usize) {
+        if lo.is_none() && hi.is_none() {
+            return self.retire_subtree(node, guard);
+        }
+
+        if Node::is_retired_spot(node, guard) {
+            return (Node::retired_node(), 0);
+        }
+
+        if node.is_null() {
+            return (Shared::null(), 0);
+        }
+
+        let node_ref = unsafe { node.deref() };
+        let left = node_ref.left.load(Ordering::Acquire, guard);
+        let right = node_ref.right.load(Ordering::Acquire, guard);
+
+        if !self.check_root(guard)
+            || Node::is_retired_spot(left, guard)
+            || Node::is_retired_spot(right, guard)
+        {
+            return (Node::retired_node(), 0);
+        }
+
+        if lo.is_some_and(|lo| node_ref.key < *lo) {
+            let (new_right, removed) = self.do_remove_range(right, lo, hi, guard);
+            return (self.mk_balanced(node, left, new_right, guard), removed);
+        }
+        if hi.is_some_and(|hi| node_ref.key >= *hi) {
+            let (new_left, removed) = self.do_remove_range(left, lo, hi, guard);
+            return (self.mk_balanced(node, new_left, right, guard), removed);
+        }
+        // The keys of the left subtree are below `hi`, and those of the right one at least `lo`.
+        let (new_left, left_removed) = self.do_remove_range(left, lo, None, guard);
+        let (new_right, right_removed) = self.do_remove_range(right, None, hi, guard);
+        self.retire_node(node);
+        (
+            self.join(new_left, new_right, guard),
+            left_removed + right_removed + 1,
+        )
+    }
+
+    /// Retires every node of the subtree of `node`, and returns the number of them.
+    fn retire_subtree(
+        &mut self,
+        node: Shared<'g, Node<K, V>>,
+        guard: &'g Guard,
+    ) -> (Shared<'g, Node<K, V>>, usize) {
+        let mut stack = vec![node];
+        let mut retired = 0;
+        while let Some(node) = stack.pop() {
+            if Node::is_retired_spot(node, guard) {
+                return (Node::retired_node(), 0);
+            }
+            if node.is_null() {
+                continue;
+            }
+
+            let node_ref = unsafe { node.deref() };
+            stack.push(node_ref.left.load(Ordering::Acquire, guard));
+            stack.push(node_ref.right.load(Ordering::Acquire, guard));
+            if !self.check_root(guard) {
+                return (Node::retired_node(), 0);
+            }
+            self.retire_node(node);
+            retired += 1;
+        }
+        (Shared::null(), retired)
+    }
+
+    /// Joins `left` and `right`, whose keys are all below those of `right`, into a balanced tree.
+    ///
+    /// The larger one is descended on the side of the other until they are of similar sizes, where
+    /// the leftmost node of `right` is pulled up to join them, and the path is rebalanced back up.
+    fn join(
+        &mut self,
+        left: Shared<'g, Node<K, V>>,
+        right: Shared<'g, Node<K, V>>,
+        guard: &'g Guard,
+    ) -> Shared<'g, Node<K, V>> {
+        if Node::is_retired_spot(left, guard) || Node::is_retired_spot(right, guard) {
+            return Node::retired_node();
+        }
+
+        if left.is_null() {
+            return right;
+        }
+        if right.is_null() {
+            return left;
+        }
+
+        let l_size = Node::node_size(left);
+        let r_size = Node::node_size(right);
+        if l_size > WEIGHT * r_size {
+            let left_ref = unsafe { left.deref() };
+            let left_left = left_ref.left.load(Ordering::Acquire, guard);
+            let left_right = left_ref.right.load(Ordering::Acquire, guard);
+            if !self.check_root(guard) {
+                return Node::retired_node();
+            }
+            let new_right = self.join(left_right, right, guard);
+            return self.mk_balanced(left, left_left, new_right, guard);
+        }
+        if r_size > WEIGHT * l_size {
+            let right_ref = unsafe { right.deref() };
+            let right_left = right_ref.left.load(Ordering::Acquire, guard);
+            let right_right = right_ref.right.load(Ordering::Acquire, guard);
+            if !self.check_root(guard) {
+                return Node::retired_node();
+            }
+            let new_left = self.join(left, right_left, guard);
+            return self.mk_balanced(right, new_left, right_right, guard);
+        }
+        let (new_right, succ) = self.pull_leftmost(right, guard);
+        self.mk_balanced(succ, left, new_right, guard)
+    }
+
     fn pull_leftmost(
         &mut self,
         node: Shared<'g, Node<K, V>>,
@@ -687,6 +816,45 @@ where
             state.abort();
         }
     }
+
+    /// Removes the keys in `lo..hi` with one CAS of the root, and returns the number of them.
+    ///
+    /// The new tree shares the subtrees outside of the range with the old one, as the other
+    /// updates do, while the subtrees within the range are retired whole, so a call retires
+    /// all the nodes of the range at once.
+    pub fn remove_range(&self, lo: &K, hi: &K, guard: &Guard) -> usize {
+        if lo >= hi {
+            return 0;
+        }
+        let mut state = State::new(&self.root);
+        loop {
+            state.load_root(guard);
+            let old_root = state.curr_root;
+            let (new_root, removed) = state.do_remove_range(old_root, Some(lo), Some(hi), guard);
+
+            if Node::is_retired(new_root) {
+                state.abort();
+                continue;
+            }
+
+            if self
+                .root
+                .compare_exchange(
+                    old_root,
+                    new_root,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    guard,
+                )
+                .is_ok()
+            {
+                state.commit(guard);
+                return removed;
+            }
+
+            state.abort();
+        }
+    }
 }
 
 impl<K, V> Drop for BonsaiTreeMap<K, V> {
@@ -709,7 +877,6 @@ impl<K, V> Drop for BonsaiTreeMap<K, V> {
     }
 }
 
-// TODO: move it to somewhere else...
 impl<K, V> ConcurrentMap<K, V> for BonsaiTreeMap<K, V>
 where
     K: Ord + Clone,
@@ -731,6 +898,10 @@ where
     fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.remove(key, guard)
     }
+    #[inline(always)]
+    fn remove_range(&self, lo: &K, hi: &K, guard: &Guard) -> usize {
+        self.remove_range(lo, hi, guard)
+    }
 }
 
 #[cfg(test)]
@@ -742,4 +913,19 @@ mod tests {
     fn smoke_bonsai_tree() {
         concurrent_map::tests::smoke::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases() {
+        concurrent_map::tests::edge_cases::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn pull_stress_bonsai_tree() {
+        concurrent_map::tests::pull_stress::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn remove_range_bonsai_tree() {
+        concurrent_map::tests::remove_range::<BonsaiTreeMap<i32, i32>>();
+    }
 }
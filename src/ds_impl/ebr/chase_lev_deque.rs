@@ -0,0 +1,298 @@
+//! The Chase-Lev work-stealing deque, with the memory orderings of Lê et al. (PPoPP 2013).
+//!
+//! The owner pushes and pops at the bottom, and the stealers take from the top, racing with each
+//! other and with the pop of the last item on a CAS of the top. The items are kept in a circular
+//! buffer, which the owner replaces with one of twice the capacity when it is full, and with one of
+//! half the capacity when a pop leaves it at most a quarter full. A stealer may still read the old
+//! buffer, so the old buffer is retired: the deque retires a large block whenever its length
+//! swings, rather than a node per operation.
+
+use super::concurrent_deque::ConcurrentDeque;
+use crate::ds_impl::deque::{self, Steal};
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicIsize, Ordering};
+
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
+use crossbeam_utils::CachePadded;
+
+/// The capacity of a new deque, below which it does not shrink.
+const MIN_CAPACITY: usize = 16;
+
+struct Buffer<T> {
+    /// A power of two number of slots, where the item at index `i` is in the slot `i` modulo the
+    /// capacity.
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+impl<T> Buffer<T> {
+    fn new(capacity: usize) -> Self {
+        debug_assert!(capacity.is_power_of_two());
+        Self {
+            slots: (0..capacity)
+                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    #[inline]
+    fn slot(&self, index: isize) -> *mut MaybeUninit<T> {
+        self.slots[index as usize & (self.capacity() - 1)].get()
+    }
+
+    /// Copies the item at `index` out, which is a valid item only if no other thread takes it.
+    /// The read is volatile, since the owner may be writing the slot for a later index.
+    #[inline]
+    unsafe fn read(&self, index: isize) -> MaybeUninit<T> {
+        unsafe { ptr::read_volatile(self.slot(index)) }
+    }
+
+    #[inline]
+    unsafe fn write(&self, index: isize, item: MaybeUninit<T>) {
+        unsafe { ptr::write_volatile(self.slot(index), item) }
+    }
+}
+
+impl<T> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        // The items are dropped by the deque, or moved to the next buffer.
+        deque::count_reclaimed();
+    }
+}
+
+pub struct ChaseLevDeque<T> {
+    /// The index of the first item, which only increases.
+    top: CachePadded<AtomicIsize>,
+    /// The index after the last item, which only the owner changes.
+    bottom: CachePadded<AtomicIsize>,
+    buffer: CachePadded<Atomic<Buffer<T>>>,
+}
+
+unsafe impl<T: Send> Send for ChaseLevDeque<T> {}
+unsafe impl<T: Send> Sync for ChaseLevDeque<T> {}
+
+impl<T> Default for ChaseLevDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ChaseLevDeque<T> {
+    pub fn new() -> Self {
+        Self {
+            top: CachePadded::new(AtomicIsize::new(0)),
+            bottom: CachePadded::new(AtomicIsize::new(0)),
+            buffer: CachePadded::new(Atomic::new(Buffer::new(MIN_CAPACITY))),
+        }
+    }
+
+    /// Replaces `buffer` with a buffer of `capacity` that holds the items from `top` to `bottom`,
+    /// and retires it. Only the owner may resize.
+    fn resize<'g>(
+        &self,
+        buffer: Shared<'g, Buffer<T>>,
+        top: isize,
+        bottom: isize,
+        capacity: usize,
+        guard: &'g Guard,
+    ) -> Shared<'g, Buffer<T>> {
+        let old = unsafe { buffer.deref() };
+        let new = Buffer::new(capacity);
+        for index in top..bottom {
+            unsafe { new.write(index, old.read(index)) };
+        }
+        let new = Owned::new(new).into_shared(guard);
+        self.buffer.store(new, Ordering::Release);
+        unsafe { guard.defer_destroy(buffer) };
+        deque::count_retired();
+        new
+    }
+
+    /// Pushes `item` at the bottom.
+    ///
+    /// # Safety
+    ///
+    /// Only the owner may push and pop, i.e., no two threads may push or pop concurrently.
+    pub unsafe fn push(&self, item: T, guard: &Guard) {
+        let bottom = self.bottom.load(Ordering::Relaxed);
+        let top = self.top.load(Ordering::Acquire);
+        let mut buffer = self.buffer.load(Ordering::Relaxed, guard);
+        let capacity = unsafe { buffer.deref() }.capacity();
+        if bottom - top >= capacity as isize {
+            buffer = self.resize(buffer, top, bottom, 2 * capacity, guard);
+        }
+        unsafe { buffer.deref().write(bottom, MaybeUninit::new(item)) };
+        fence(Ordering::Release);
+        self.bottom.store(bottom + 1, Ordering::Relaxed);
+    }
+
+    /// Removes the last pushed item, if there is one.
+    ///
+    /// # Safety
+    ///
+    /// Only the owner may push and pop, i.e., no two threads may push or pop concurrently.
+    pub unsafe fn pop(&self, guard: &Guard) -> Option<T> {
+        let bottom = self.bottom.load(Ordering::Relaxed) - 1;
+        let buffer = self.buffer.load(Ordering::Relaxed, guard);
+        // Claims the last item before reading the top, so that a stealer that reads the top after
+        // this sees the new bottom.
+        self.bottom.store(bottom, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let top = self.top.load(Ordering::Relaxed);
+        if top > bottom {
+            self.bottom.store(bottom + 1, Ordering::Relaxed);
+            return None;
+        }
+
+        let buffer_ref = unsafe { buffer.deref() };
+        let item = unsafe { buffer_ref.read(bottom) };
+        if top == bottom {
+            // The last item, which a stealer may take first.
+            let won = self
+                .top
+                .compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok();
+            self.bottom.store(bottom + 1, Ordering::Relaxed);
+            return won.then(|| unsafe { item.assume_init() });
+        }
+        let capacity = buffer_ref.capacity();
+        if capacity > MIN_CAPACITY && bottom - top <= capacity as isize / 4 {
+            self.resize(buffer, top, bottom, capacity / 2, guard);
+        }
+        Some(unsafe { item.assume_init() })
+    }
+
+    /// Removes the first item, unless the deque is empty or another thread takes it first.
+    pub fn steal(&self, guard: &Guard) -> Steal<T> {
+        let top = self.top.load(Ordering::Acquire);
+        fence(Ordering::SeqCst);
+        let bottom = self.bottom.load(Ordering::Acquire);
+        if top >= bottom {
+            return Steal::Empty;
+        }
+
+        // The buffer may be retired as soon as it is replaced, but not reclaimed while pinned.
+        let buffer = self.buffer.load(Ordering::Acquire, guard);
+        let item = unsafe { buffer.deref().read(top) };
+        if self.buffer.load(Ordering::Acquire, guard) != buffer
+            || self
+                .top
+                .compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_err()
+        {
+            return Steal::Retry;
+        }
+        Steal::Success(unsafe { item.assume_init() })
+    }
+}
+
+impl<T> Drop for ChaseLevDeque<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+            let buffer = self.buffer.load(Ordering::Relaxed, guard);
+            for index in *self.top.get_mut()..*self.bottom.get_mut() {
+                (*buffer.deref().slot(index)).assume_init_drop();
+            }
+            drop(buffer.into_owned());
+        }
+    }
+}
+
+impl<T> ConcurrentDeque<T> for ChaseLevDeque<T> {
+    fn new() -> Self {
+        ChaseLevDeque::new()
+    }
+
+    #[inline(always)]
+    unsafe fn push(&self, item: T, guard: &Guard) {
+        unsafe { self.push(item, guard) }
+    }
+
+    #[inline(always)]
+    unsafe fn pop(&self, guard: &Guard) -> Option<T> {
+        unsafe { self.pop(guard) }
+    }
+
+    #[inline(always)]
+    fn steal(&self, guard: &Guard) -> Steal<T> {
+        self.steal(guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChaseLevDeque;
+    use crate::ds_impl::deque::Steal;
+    use crossbeam_ebr::pin;
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    #[test]
+    fn simple() {
+        let deque = ChaseLevDeque::new();
+        let guard = &pin();
+        assert!(unsafe { deque.pop(guard) }.is_none());
+        assert_eq!(deque.steal(guard), Steal::Empty);
+        // Grows past the initial capacity, and shrinks back as the items are taken.
+        for i in 0..100 {
+            unsafe { deque.push(i.to_string(), guard) };
+        }
+        assert_eq!(deque.steal(guard), Steal::Success("0".to_string()));
+        assert_eq!(unsafe { deque.pop(guard) }.unwrap(), "99");
+        for i in (50..99).rev() {
+            assert_eq!(unsafe { deque.pop(guard) }.unwrap(), i.to_string());
+        }
+        for i in 1..50 {
+            assert_eq!(deque.steal(guard), Steal::Success(i.to_string()));
+        }
+        assert!(unsafe { deque.pop(guard) }.is_none());
+        // The remaining items are dropped with the deque.
+        unsafe { deque.push("100".to_string(), guard) };
+    }
+
+    /// Pushes bursts of items and pops them back while the stealers take from the top, and checks
+    /// that each item is taken exactly once.
+    #[test]
+    fn smoke() {
+        const STEALERS: usize = 7;
+        const BURSTS: usize = 1000;
+        const BURST: usize = 100;
+
+        let deque = &ChaseLevDeque::new();
+        let mut found = Vec::new();
+        found.resize_with(BURSTS * BURST, || AtomicU32::new(0));
+        let found = &found;
+        let done = &AtomicBool::new(false);
+
+        scope(|s| {
+            for _ in 0..STEALERS {
+                s.spawn(move |_| {
+                    while !done.load(Ordering::Acquire) {
+                        if let Steal::Success(item) = deque.steal(&pin()) {
+                            found[item].fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+            for burst in 0..BURSTS {
+                for i in 0..BURST {
+                    unsafe { deque.push(burst * BURST + i, &pin()) };
+                }
+                while let Some(item) = unsafe { deque.pop(&pin()) } {
+                    found[item].fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            done.store(true, Ordering::Release);
+        })
+        .unwrap();
+        assert!(found.iter().all(|f| f.load(Ordering::Relaxed) == 1));
+    }
+}
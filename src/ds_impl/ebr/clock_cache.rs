@@ -0,0 +1,223 @@
+//! A hash map of bounded size, which evicts keys with CLOCK, an approximation of LRU.
+//!
+//! Each key in the map owns one of `capacity` slots in a ring, and a `get` that finds the key sets
+//! the reference bit of its slot. To make room for a new key, the inserting thread advances the
+//! hand of the clock over the ring, clearing the reference bits on its way, until it reaches a slot
+//! that has not been referenced since the hand passed it last. It then puts the new key in the slot
+//! and evicts the old key of the slot from the map.
+//!
+//! The eviction is approximate under concurrency. A key that is removed and inserted again while
+//! its old slot is being reused may be evicted early, and the map may hold a few more keys than
+//! its capacity while insertions are in flight. Once they finish, every key in the map owns a
+//! distinct slot.
+
+use super::concurrent_map::OutputHolder;
+use super::HashMap;
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
+use crossbeam_utils::CachePadded;
+use std::hash::Hash;
+use std::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+
+/// A value in the map, with the slot of its key.
+#[derive(Clone, Default)]
+pub struct Entry<V> {
+    value: V,
+    slot: usize,
+}
+
+struct Slot<K> {
+    key: Atomic<K>,
+    referenced: AtomicBool,
+}
+
+pub struct ClockCache<K, V> {
+    map: HashMap<K, Entry<V>>,
+    slots: Box<[CachePadded<Slot<K>>]>,
+    hand: CachePadded<AtomicUsize>,
+}
+
+impl<K, V> ClockCache<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    /// Creates an empty cache that holds at most `capacity` keys.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "The capacity of a cache must be positive");
+        Self {
+            map: HashMap::with_capacity(capacity),
+            slots: (0..capacity)
+                .map(|_| {
+                    CachePadded::new(Slot {
+                        key: Atomic::null(),
+                        referenced: AtomicBool::new(false),
+                    })
+                })
+                .collect(),
+            hand: CachePadded::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn get(&self, key: &K, guard: &Guard) -> Option<V> {
+        let entry = self.map.get(key, guard)?;
+        let entry = entry.output();
+        let referenced = &self.slots[entry.slot].referenced;
+        // Avoids writing to the slot of a hot key on every hit.
+        if !referenced.load(Ordering::Relaxed) {
+            referenced.store(true, Ordering::Relaxed);
+        }
+        Some(entry.value.clone())
+    }
+
+    /// Inserts a key, evicting another one if the cache is full.
+    pub fn insert(&self, key: K, value: V, guard: &Guard) -> bool {
+        // Does not evict a key for an insertion that fails anyway.
+        if self.map.get(&key, guard).is_some() {
+            return false;
+        }
+        let (slot, claimed) = self.claim(key.clone(), guard);
+        if !self.map.insert(key, Entry { value, slot }, guard) {
+            return false;
+        }
+        fence(Ordering::SeqCst);
+        // Another inserter may have reused the slot before the key was in the map, in which case
+        // it has missed the key in its eviction.
+        if self.slots[slot].key.load(Ordering::Relaxed, guard) != claimed {
+            self.evict(unsafe { claimed.deref() }, slot, guard);
+        }
+        true
+    }
+
+    pub fn remove(&self, key: &K, guard: &Guard) -> Option<V> {
+        // The slot of the key is left as is, and is reused when the hand reaches it.
+        self.map
+            .remove(key, guard)
+            .map(|entry| entry.output().value.clone())
+    }
+
+    /// Advances the hand to an unreferenced slot, puts `key` in it, and evicts its old key.
+    fn claim<'g>(&self, key: K, guard: &'g Guard) -> (usize, Shared<'g, K>) {
+        let mut key = Owned::new(key);
+        loop {
+            let index = self.hand.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+            let slot = &self.slots[index];
+            if slot.referenced.swap(false, Ordering::Relaxed) {
+                // Gives the key of the slot a second chance.
+                continue;
+            }
+            let old = slot.key.load(Ordering::Acquire, guard);
+            match slot
+                .key
+                .compare_exchange(old, key, Ordering::AcqRel, Ordering::Relaxed, guard)
+            {
+                Ok(claimed) => {
+                    if let Some(old_key) = unsafe { old.as_ref() } {
+                        fence(Ordering::SeqCst);
+                        self.evict(old_key, index, guard);
+                        unsafe { guard.defer_destroy(old) };
+                    }
+                    return (index, claimed);
+                }
+                // Another inserter has claimed the slot.
+                Err(e) => key = e.new,
+            }
+        }
+    }
+
+    /// Evicts `key` if it still owns the slot `index`.
+    fn evict(&self, key: &K, index: usize, guard: &Guard) {
+        let owned = self
+            .map
+            .get(key, guard)
+            .is_some_and(|entry| entry.output().slot == index);
+        if owned {
+            self.map.remove(key, guard);
+        }
+    }
+}
+
+impl<K, V> Drop for ClockCache<K, V> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter() {
+            let key = slot
+                .key
+                .swap(Shared::null(), Ordering::Relaxed, unsafe { unprotected() });
+            if !key.is_null() {
+                drop(unsafe { key.into_owned() });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClockCache;
+    use crossbeam_ebr::pin;
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
+
+    const THREADS: usize = 30;
+    const CAPACITY: usize = 100;
+    const ELEMENTS: i32 = 1000;
+    const OPS_PER_THREADS: usize = 10000;
+
+    #[test]
+    fn evict_in_clock_order() {
+        let cache = &ClockCache::<i32, String>::with_capacity(CAPACITY);
+        let guard = &pin();
+        for i in 0..ELEMENTS {
+            assert!(cache.insert(i, i.to_string(), guard));
+        }
+        // Nothing has been referenced, so the last keys remain.
+        for i in 0..ELEMENTS {
+            let expected = (i >= ELEMENTS - CAPACITY as i32).then(|| i.to_string());
+            assert_eq!(cache.get(&i, guard), expected);
+        }
+
+        // All keys have been referenced, so the hand clears the reference bits in a lap and then
+        // evicts the oldest key.
+        assert!(cache.insert(ELEMENTS, ELEMENTS.to_string(), guard));
+        assert_eq!(cache.get(&ELEMENTS, guard), Some(ELEMENTS.to_string()));
+        assert_eq!(cache.get(&(ELEMENTS - CAPACITY as i32), guard), None);
+        let present = (0..=ELEMENTS)
+            .filter(|i| cache.get(i, guard).is_some())
+            .count();
+        assert_eq!(present, CAPACITY);
+    }
+
+    #[test]
+    fn smoke_clock_cache() {
+        let cache = &ClockCache::<i32, String>::with_capacity(CAPACITY);
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..OPS_PER_THREADS {
+                        let key = rng.gen_range(0..ELEMENTS);
+                        let guard = &pin();
+                        match cache.get(&key, guard) {
+                            Some(value) => assert_eq!(key.to_string(), value),
+                            None => {
+                                cache.insert(key, key.to_string(), guard);
+                            }
+                        }
+                        if rng.gen_ratio(1, 10) {
+                            cache.remove(&key, guard);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let guard = &pin();
+        let present = (0..ELEMENTS)
+            .filter(|i| cache.get(i, guard).is_some())
+            .count();
+        assert!(present <= CAPACITY);
+    }
+}
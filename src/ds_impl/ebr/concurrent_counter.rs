@@ -0,0 +1,129 @@
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::HashMap;
+use crossbeam_ebr::Guard;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A concurrent multiset, which counts the occurrences of each key.
+pub trait ConcurrentCounter<K> {
+    fn new() -> Self;
+    /// Returns the number of occurrences of `key`.
+    fn count(&self, key: &K, guard: &Guard) -> usize;
+    /// Adds an occurrence of `key`, and returns the new count.
+    fn increment(&self, key: K, guard: &Guard) -> usize;
+    /// Removes an occurrence of `key` if there is one, and returns the new count.
+    fn decrement(&self, key: &K, guard: &Guard) -> usize;
+}
+
+/// A multiset backed by a map from the keys to atomic counters.
+///
+/// The node of a key is inserted on its first increment and is never removed, even when its count
+/// drops to zero, so the counting operations only protect and update the nodes in place and never
+/// retire one. This isolates the cost of the protection of the traversals.
+pub struct CounterMap<M> {
+    inner: M,
+}
+
+impl<K, M> ConcurrentCounter<K> for CounterMap<M>
+where
+    K: Clone,
+    M: ConcurrentMap<K, AtomicUsize>,
+{
+    fn new() -> Self {
+        CounterMap { inner: M::new() }
+    }
+
+    #[inline(always)]
+    fn count(&self, key: &K, guard: &Guard) -> usize {
+        self.inner
+            .get(key, guard)
+            .map_or(0, |counter| counter.output().load(Ordering::Relaxed))
+    }
+
+    #[inline(always)]
+    fn increment(&self, key: K, guard: &Guard) -> usize {
+        loop {
+            if let Some(counter) = self.inner.get(&key, guard) {
+                return counter.output().fetch_add(1, Ordering::Relaxed) + 1;
+            }
+            // Another thread may insert the counter first, which is then incremented instead.
+            if self.inner.insert(key.clone(), AtomicUsize::new(1), guard) {
+                return 1;
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn decrement(&self, key: &K, guard: &Guard) -> usize {
+        let Some(counter) = self.inner.get(key, guard) else {
+            return 0;
+        };
+        counter
+            .output()
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                count.checked_sub(1)
+            })
+            .map_or(0, |count| count - 1)
+    }
+}
+
+pub type HashMapCounter<K> = CounterMap<HashMap<K, AtomicUsize>>;
+
+#[cfg(test)]
+pub mod tests {
+    use super::{ConcurrentCounter, HashMapCounter};
+    use crossbeam_ebr::pin;
+    use crossbeam_utils::thread;
+
+    const THREADS: i32 = 30;
+    const KEYS: i32 = 100;
+    const INCREMENTS_PER_KEY: usize = 100;
+
+    pub fn smoke<C>()
+    where
+        C: ConcurrentCounter<i32> + Send + Sync,
+    {
+        let counter = &C::new();
+
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    for _ in 0..INCREMENTS_PER_KEY {
+                        for key in 0..KEYS {
+                            assert!(counter.increment(key, &pin()) > 0);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let total = THREADS as usize * INCREMENTS_PER_KEY;
+        for key in 0..KEYS {
+            assert_eq!(counter.count(&key, &pin()), total);
+        }
+        assert_eq!(counter.count(&KEYS, &pin()), 0);
+
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    for _ in 0..INCREMENTS_PER_KEY {
+                        for key in 0..KEYS {
+                            counter.decrement(&key, &pin());
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        for key in 0..KEYS {
+            assert_eq!(counter.count(&key, &pin()), 0);
+            assert_eq!(counter.decrement(&key, &pin()), 0);
+        }
+    }
+
+    #[test]
+    fn smoke_hash_map_counter() {
+        smoke::<HashMapCounter<i32>>();
+    }
+}
@@ -0,0 +1,22 @@
+use crate::ds_impl::deque::Steal;
+use crossbeam_ebr::Guard;
+
+/// A concurrent work-stealing deque, whose owner pushes and pops at the bottom while the other
+/// threads steal from the top.
+pub trait ConcurrentDeque<T> {
+    fn new() -> Self;
+    /// Pushes `item` at the bottom.
+    ///
+    /// # Safety
+    ///
+    /// Only the owner may push and pop, i.e., no two threads may push or pop concurrently.
+    unsafe fn push(&self, item: T, guard: &Guard);
+    /// Removes the last pushed item, if there is one.
+    ///
+    /// # Safety
+    ///
+    /// Only the owner may push and pop, i.e., no two threads may push or pop concurrently.
+    unsafe fn pop(&self, guard: &Guard) -> Option<T>;
+    /// Removes the first item, unless the deque is empty or another thread takes it first.
+    fn steal(&self, guard: &Guard) -> Steal<T>;
+}
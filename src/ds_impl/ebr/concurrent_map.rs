@@ -18,10 +18,155 @@ impl<V> OutputHolder<V> for V {
 
 pub trait ConcurrentMap<K, V> {
     fn new() -> Self;
+    /// Creates a map that allocates its nodes from a slab of its own instead of the global
+    /// allocator, for the structures that support it.
+    fn with_slab() -> Self
+    where
+        Self: Sized,
+    {
+        panic!("Unsupported(or unimplemented) slab allocation")
+    }
     fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>>;
     fn insert(&self, key: K, value: V, guard: &Guard) -> bool;
     fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>>;
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    ///
+    /// By default, the key is looked up and then inserted, which is retried if another thread
+    /// inserts the key in between. The lists and the hash map replace this with one traversal,
+    /// which finds either the node of the key or the position of the new node.
+    fn get_or_insert(&self, key: K, value: V, guard: &Guard) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if let Some(found) = self.get(&key, guard) {
+                return Some(found.output().clone());
+            }
+            if self.insert(key.clone(), value.clone(), guard) {
+                return None;
+            }
+        }
+    }
+    /// Looks up `keys` in a batch, and returns the number of them that are found, for the
+    /// structures that support it.
+    fn multi_get(&self, _keys: &[K], _guard: &Guard) -> usize {
+        panic!("Unsupported(or unimplemented) multi-get")
+    }
+    /// Removes the keys in `lo..hi`, and returns the number of them that are removed, for the
+    /// structures that support it.
+    fn remove_range(&self, _lo: &K, _hi: &K, _guard: &Guard) -> usize {
+        panic!("Unsupported(or unimplemented) range removal")
+    }
+    /// Scans up to `items` elements in the order of the keys from `key`, and returns the number of
+    /// them that are scanned, for the structures that support it.
+    fn partial_scan(&self, _key: &K, _items: usize, _guard: &Guard) -> usize {
+        panic!("Unsupported(or unimplemented) partial scan")
+    }
+    /// Returns the smallest key and its value, for the structures that support it.
+    fn min<'g>(&'g self, _guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        panic!("Unsupported(or unimplemented) min")
+    }
+    /// Returns the largest key and its value, for the structures that support it.
+    fn max<'g>(&'g self, _guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        panic!("Unsupported(or unimplemented) max")
+    }
+    /// Removes the smallest key, retrying if another thread removes it first, and returns whether
+    /// the map had a key to remove.
+    fn extract_min(&self, guard: &Guard) -> bool {
+        while let Some((key, _)) = self.min(guard) {
+            if self.remove(key, guard).is_some() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// The result of an inherent `insert`, which tells whether the key was inserted.
+pub trait Inserted {
+    fn inserted(self) -> bool;
+}
+
+impl Inserted for bool {
+    fn inserted(self) -> bool {
+        self
+    }
+}
+
+/// An `Err` gives back the key and value that were not inserted.
+impl<T, E> Inserted for Result<T, E> {
+    fn inserted(self) -> bool {
+        self.is_ok()
+    }
+}
+
+/// Implements [`ConcurrentMap`] for a map `$map<K, V>` by forwarding to its inherent `new`, `get`,
+/// `insert` and `remove`, which take the same arguments as the trait methods. The inherent
+/// `insert` may return a `bool` or a `Result`.
+///
+/// With `#[test] <name>;`, it also generates a test `<name>` that runs the smoke test on
+/// `$map<i32, String>`.
+///
+/// ```ignore
+/// impl_concurrent_map! {
+///     impl [K: Ord + Clone, V: Clone] for WaitFreeTreap;
+///     #[test] smoke_wait_free_treap;
+/// }
+/// ```
+macro_rules! impl_concurrent_map {
+    (impl [$($bounds:tt)*] for $map:ident; $(#[test] $test:ident;)?) => {
+        impl<$($bounds)*> $crate::ds_impl::ebr::ConcurrentMap<K, V> for $map<K, V> {
+            fn new() -> Self {
+                Self::new()
+            }
+
+            #[inline(always)]
+            fn get<'g>(
+                &'g self,
+                key: &'g K,
+                guard: &'g ::crossbeam_ebr::Guard,
+            ) -> Option<impl $crate::ds_impl::ebr::concurrent_map::OutputHolder<V>> {
+                self.get(key, guard)
+            }
+
+            #[inline(always)]
+            fn insert(&self, key: K, value: V, guard: &::crossbeam_ebr::Guard) -> bool {
+                $crate::ds_impl::ebr::concurrent_map::Inserted::inserted(
+                    self.insert(key, value, guard),
+                )
+            }
+
+            #[inline(always)]
+            fn remove<'g>(
+                &'g self,
+                key: &'g K,
+                guard: &'g ::crossbeam_ebr::Guard,
+            ) -> Option<impl $crate::ds_impl::ebr::concurrent_map::OutputHolder<V>> {
+                self.remove(key, guard)
+            }
+        }
+
+        $(
+            #[cfg(test)]
+            mod tests {
+                use super::$map;
+                use $crate::ds_impl::ebr::concurrent_map;
+
+                #[test]
+                fn $test() {
+                    concurrent_map::tests::smoke::<_, $map<i32, String>, _>(&i32::to_string);
+                }
+
+                #[test]
+                fn edge_cases() {
+                    concurrent_map::tests::edge_cases::<_, $map<i32, String>, _>(&i32::to_string);
+                }
+            }
+        )?
+    };
 }
+pub(crate) use impl_concurrent_map;
 
 #[cfg(test)]
 pub mod tests {
@@ -30,10 +175,13 @@ pub mod tests {
     use crossbeam_ebr::pin;
     use crossbeam_utils::thread;
     use rand::prelude::*;
+    use std::collections::{BTreeSet, VecDeque};
     use std::fmt::Debug;
 
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
+    const PULL_KEYS: i32 = 200;
+    const PULL_ROUNDS: i32 = 5;
 
     pub fn smoke<V, M, F>(to_value: &F)
     where
@@ -41,7 +189,17 @@ pub mod tests {
         M: ConcurrentMap<i32, V> + Send + Sync,
         F: Sync + Fn(&i32) -> V,
     {
-        let map = &M::new();
+        smoke_with(M::new(), to_value);
+    }
+
+    /// Runs the smoke test on `map`, which must be empty.
+    pub fn smoke_with<V, M, F>(map: M, to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &map;
 
         thread::scope(|s| {
             for t in 0..THREADS {
@@ -88,4 +246,339 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Checks that the holders of `get` and `remove` keep their values until the guard is dropped,
+    /// while the same thread and another one remove the keys, insert them again with other values,
+    /// and flush their garbage.
+    pub fn output_holder<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(i, to_value(&i), &pin()));
+        }
+
+        let guard = &pin();
+        let got = map.get(&0, guard).unwrap();
+        let removed = map.remove(&1, guard).unwrap();
+        assert!(map.remove(&0, guard).is_some());
+        assert!(map.insert(0, to_value(&-1), guard));
+        assert_eq!(to_value(&-1), *map.get(&0, guard).unwrap().output());
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                for i in 0..ELEMENTS_PER_THREADS {
+                    let guard = &pin();
+                    map.remove(&i, guard);
+                    assert!(map.insert(i, to_value(&(i + 1)), guard));
+                    guard.flush();
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(to_value(&0), *got.output());
+        assert_eq!(to_value(&1), *removed.output());
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let guard = &pin();
+
+        // An empty map.
+        assert!(map.get(&0, guard).is_none());
+        assert!(map.remove(&0, guard).is_none());
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(0, to_value(&0), guard));
+        assert!(!map.insert(0, to_value(&1), guard));
+        assert_eq!(to_value(&0), *map.get(&0, guard).unwrap().output());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert_eq!(to_value(&0), *map.remove(&0, guard).unwrap().output());
+        assert!(map.get(&0, guard).is_none());
+        assert!(map.remove(&0, guard).is_none());
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(i, to_value(&i), guard));
+            }
+            assert_eq!(
+                to_value(&removed),
+                *map.remove(&removed, guard).unwrap().output()
+            );
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(&i, guard).is_some());
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert_eq!(to_value(&i), *map.remove(&i, guard).unwrap().output());
+            }
+        }
+        for i in 0..=3 {
+            assert!(map.get(&i, guard).is_none());
+        }
+    }
+
+    /// Removes the keys of a tree in the order of the levels of a balanced tree, medians first, and
+    /// inserts them back in the same order, for a few rounds. Most keys then have two children when
+    /// they are removed, which pulls the rightmost node of the left subtree up into their place, and
+    /// the rest have a child on one side, which pulls the leftmost node of the right subtree. Both
+    /// rebuild the path to the pulled node, which the uniform keys of `smoke` rarely do. The tree is
+    /// prefilled in an increasing order, which skews it until it is rebalanced. Each thread owns
+    /// every `THREADS`-th key, and checks that its keys next to each removed key keep their values.
+    pub fn pull_stress<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        for i in 0..THREADS * PULL_KEYS {
+            assert!(map.insert(i, to_value(&i), &pin()));
+        }
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let keys = medians_first(PULL_KEYS);
+                    for _ in 0..PULL_ROUNDS {
+                        let mut removed = vec![false; PULL_KEYS as usize];
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert_eq!(to_value(&i), *map.remove(&i, &pin()).unwrap().output());
+                            removed[k as usize] = true;
+                            for n in [k - 1, k + 1] {
+                                if (0..PULL_KEYS).contains(&n) && !removed[n as usize] {
+                                    let j = n * THREADS + t;
+                                    assert_eq!(
+                                        to_value(&j),
+                                        *map.get(&j, &pin()).unwrap().output()
+                                    );
+                                }
+                            }
+                        }
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.insert(i, to_value(&i), &pin()));
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        for j in 0..THREADS * PULL_KEYS {
+            assert_eq!(to_value(&j), *map.get(&j, &pin()).unwrap().output());
+        }
+    }
+
+    /// Checks `min` and `max` against a `BTreeSet` of the keys after each of random inserts and
+    /// removes, and then while the keys are removed from both ends.
+    pub fn min_max<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let guard = &pin();
+        let mut rng = rand::thread_rng();
+        let mut keys = BTreeSet::new();
+        let check = |keys: &BTreeSet<i32>| {
+            let min = map.min(guard).map(|(key, value)| {
+                assert_eq!(to_value(key), *value);
+                *key
+            });
+            let max = map.max(guard).map(|(key, value)| {
+                assert_eq!(to_value(key), *value);
+                *key
+            });
+            assert_eq!(min, keys.first().copied());
+            assert_eq!(max, keys.last().copied());
+        };
+
+        check(&keys);
+        for _ in 0..ELEMENTS_PER_THREADS {
+            let key = rng.gen_range(0..PULL_KEYS);
+            if rng.gen() {
+                assert_eq!(map.insert(key, to_value(&key), guard), keys.insert(key));
+            } else {
+                assert_eq!(map.remove(&key, guard).is_some(), keys.remove(&key));
+            }
+            check(&keys);
+        }
+        while let Some(key) = if keys.len() % 2 == 0 {
+            keys.pop_first()
+        } else {
+            keys.pop_last()
+        } {
+            assert!(map.remove(&key, guard).is_some());
+            check(&keys);
+        }
+    }
+
+    /// Extracts the minimum from each thread while the others also do, until the map is empty, and
+    /// checks that every prefilled key is extracted exactly once and in an increasing order by
+    /// each thread.
+    pub fn extract_min<M>()
+    where
+        M: ConcurrentMap<i32, i32> + Send + Sync,
+    {
+        let map = &M::new();
+        let mut keys = (0..THREADS * ELEMENTS_PER_THREADS).collect::<Vec<_>>();
+        keys.shuffle(&mut rand::thread_rng());
+        for &key in &keys {
+            assert!(map.insert(key, key, &pin()));
+        }
+
+        let mut extracted = thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|_| {
+                    s.spawn(move |_| {
+                        let mut extracted = vec![];
+                        loop {
+                            let guard = &pin();
+                            let Some((&key, _)) = map.min(guard) else {
+                                break;
+                            };
+                            if map.remove(&key, guard).is_some() {
+                                extracted.push(key);
+                            }
+                        }
+                        assert!(extracted.windows(2).all(|keys| keys[0] < keys[1]));
+                        extracted
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+
+        extracted.sort_unstable();
+        keys.sort_unstable();
+        assert_eq!(extracted, keys);
+        assert!(!map.extract_min(&pin()));
+    }
+
+    /// Races the get-or-inserts of the same keys from each thread with a value of its own, and
+    /// checks that every thread gets the value of the one insert of each key.
+    pub fn get_or_insert<M>()
+    where
+        M: ConcurrentMap<i32, i32> + Send + Sync,
+    {
+        let map = &M::new();
+        let values = thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|t| {
+                    s.spawn(move |_| {
+                        (0..PULL_KEYS)
+                            .map(|key| map.get_or_insert(key, t, &pin()).unwrap_or(t))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+
+        for key in 0..PULL_KEYS {
+            let value = *map.get(&key, &pin()).unwrap().output();
+            assert!(values.iter().all(|values| values[key as usize] == value));
+        }
+    }
+
+    /// Removes a range of the keys of a prefilled map, and checks that exactly the keys of the range
+    /// are removed. Then removes the range over and over while the other threads remove and insert
+    /// the keys in it and insert the keys around it, and checks that the keys around it are never
+    /// lost.
+    pub fn remove_range<M>()
+    where
+        M: ConcurrentMap<i32, i32> + Send + Sync,
+    {
+        let keys = THREADS * PULL_KEYS;
+        let (lo, hi) = (keys / 4, 3 * keys / 4);
+        let map = &M::new();
+        let mut shuffled = (0..keys).collect::<Vec<_>>();
+        shuffled.shuffle(&mut rand::thread_rng());
+        for &key in &shuffled {
+            assert!(map.insert(key, key, &pin()));
+        }
+
+        {
+            let guard = &pin();
+            assert_eq!(map.remove_range(&hi, &lo, guard), 0);
+            assert_eq!(map.remove_range(&lo, &hi, guard), (hi - lo) as usize);
+            assert_eq!(map.remove_range(&lo, &hi, guard), 0);
+            for key in 0..keys {
+                assert_eq!(map.get(&key, guard).is_some(), !(lo..hi).contains(&key));
+            }
+        }
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                for _ in 0..PULL_KEYS {
+                    map.remove_range(&lo, &hi, &pin());
+                }
+            });
+            for _ in 0..THREADS / 4 {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..ELEMENTS_PER_THREADS * 10 {
+                        let key = rng.gen_range(0..keys);
+                        let guard = &pin();
+                        if (lo..hi).contains(&key) && rng.gen() {
+                            if let Some(value) = map.remove(&key, guard) {
+                                assert_eq!(*value.output(), key);
+                            }
+                        } else {
+                            map.insert(key, key, guard);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let guard = &pin();
+        let remaining = (lo..hi).filter(|key| map.get(key, guard).is_some()).count();
+        assert_eq!(map.remove_range(&lo, &hi, guard), remaining);
+        for key in 0..keys {
+            let expected = (!(lo..hi).contains(&key)).then_some(key);
+            assert_eq!(map.get(&key, guard).map(|value| *value.output()), expected);
+        }
+    }
+
+    /// The keys in `0..n` in the order of the levels of a balanced tree, medians first.
+    fn medians_first(n: i32) -> Vec<i32> {
+        let mut keys = Vec::with_capacity(n as usize);
+        let mut ranges = VecDeque::from([(0, n)]);
+        while let Some((lo, hi)) = ranges.pop_front() {
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                keys.push(mid);
+                ranges.push_back((lo, mid));
+                ranges.push_back((mid + 1, hi));
+            }
+        }
+        keys
+    }
 }
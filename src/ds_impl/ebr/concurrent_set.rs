@@ -0,0 +1,143 @@
+use super::concurrent_map::ConcurrentMap;
+use super::{HHSList, HList, HMList, SkipList};
+use crossbeam_ebr::Guard;
+
+pub trait ConcurrentSet<K> {
+    fn new() -> Self;
+    fn contains(&self, key: &K, guard: &Guard) -> bool;
+    fn insert(&self, key: K, guard: &Guard) -> bool;
+    fn remove(&self, key: &K, guard: &Guard) -> bool;
+}
+
+/// A set backed by a map whose nodes carry no value.
+///
+/// Since the value type is `()`, the nodes of the underlying structure only hold a key
+/// and the links, so a set does not pay for the memory traffic of an unused value.
+pub struct KeySet<M> {
+    inner: M,
+}
+
+impl<K, M> ConcurrentSet<K> for KeySet<M>
+where
+    M: ConcurrentMap<K, ()>,
+{
+    fn new() -> Self {
+        KeySet { inner: M::new() }
+    }
+
+    #[inline(always)]
+    fn contains(&self, key: &K, guard: &Guard) -> bool {
+        self.inner.get(key, guard).is_some()
+    }
+
+    #[inline(always)]
+    fn insert(&self, key: K, guard: &Guard) -> bool {
+        self.inner.insert(key, (), guard)
+    }
+
+    #[inline(always)]
+    fn remove(&self, key: &K, guard: &Guard) -> bool {
+        self.inner.remove(key, guard).is_some()
+    }
+}
+
+impl<K> KeySet<HHSList<K, ()>>
+where
+    K: Ord + Default + Clone,
+{
+    /// Moves all keys of `other` into this set. See [`HHSList::merge_from`].
+    pub fn merge_from(&self, other: &Self, guard: &Guard) -> usize {
+        self.inner.merge_from(&other.inner, guard)
+    }
+}
+
+pub type HListSet<K> = KeySet<HList<K, ()>>;
+pub type HMListSet<K> = KeySet<HMList<K, ()>>;
+pub type HHSListSet<K> = KeySet<HHSList<K, ()>>;
+pub type SkipListSet<K> = KeySet<SkipList<K, ()>>;
+
+#[cfg(test)]
+pub mod tests {
+    extern crate rand;
+    use super::{ConcurrentSet, HHSListSet, HListSet, HMListSet, SkipListSet};
+    use crossbeam_ebr::pin;
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
+
+    const THREADS: i32 = 30;
+    const ELEMENTS_PER_THREADS: i32 = 1000;
+
+    pub fn smoke<S>()
+    where
+        S: ConcurrentSet<i32> + Send + Sync,
+    {
+        let set = &S::new();
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert!(set.insert(i, &pin()));
+                        assert!(!set.insert(i, &pin()));
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        thread::scope(|s| {
+            for t in 0..(THREADS / 2) {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert!(set.remove(&i, &pin()));
+                        assert!(!set.contains(&i, &pin()));
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        thread::scope(|s| {
+            for t in (THREADS / 2)..THREADS {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert!(set.contains(&i, &pin()));
+                    }
+                });
+            }
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn smoke_h_list_set() {
+        smoke::<HListSet<i32>>();
+    }
+
+    #[test]
+    fn smoke_hm_list_set() {
+        smoke::<HMListSet<i32>>();
+    }
+
+    #[test]
+    fn smoke_hhs_list_set() {
+        smoke::<HHSListSet<i32>>();
+    }
+
+    #[test]
+    fn smoke_skip_list_set() {
+        smoke::<SkipListSet<i32>>();
+    }
+}
@@ -0,0 +1,9 @@
+use crossbeam_ebr::Guard;
+
+/// A concurrent LIFO stack.
+pub trait ConcurrentStack<T> {
+    fn new() -> Self;
+    fn push(&self, item: T, guard: &Guard);
+    /// Removes the last pushed item, if there is one.
+    fn pop(&self, guard: &Guard) -> Option<T>;
+}
@@ -3,7 +3,7 @@ use std::sync::atomic::Ordering;
 use crossbeam_ebr::{unprotected, Atomic, Guard, Owned};
 use crossbeam_utils::CachePadded;
 
-struct Node<T> {
+pub(crate) struct Node<T> {
     item: Option<T>,
     prev: Atomic<Node<T>>,
     next: CachePadded<Atomic<Node<T>>>,
@@ -257,6 +257,34 @@ where
         }
     }
 
+    /// Returns the smallest key and its value, at the end of the leftmost path.
+    pub fn min<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.search_end(false, guard)
+    }
+
+    /// Returns the largest key and its value.
+    pub fn max<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.search_end(true, guard)
+    }
+
+    /// Descends to the leftmost or rightmost leaf with a finite key, like `Cursor::search`. The
+    /// finite keys under an internal node with an infinite key are all in its left subtree, so
+    /// going right turns left at such a node.
+    fn search_end<'g>(&'g self, right: bool, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        let mut l_node = unsafe { self.root.load(Ordering::Relaxed, guard).deref() };
+        while !l_node.is_leaf(guard) {
+            let next = match &l_node.key {
+                Key::Fin(_) if right => l_node.right.load(Ordering::Acquire, guard),
+                _ => l_node.left.load(Ordering::Acquire, guard),
+            };
+            l_node = unsafe { next.deref() };
+        }
+        match &l_node.key {
+            Key::Fin(key) => Some((key, l_node.value.as_ref().unwrap())),
+            _ => None,
+        }
+    }
+
     pub fn insert(&self, key: &K, value: V, guard: &Guard) -> bool {
         loop {
             let mut cursor = Cursor::new(self.root.load(Ordering::Relaxed, guard));
@@ -551,6 +579,16 @@ where
     fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.delete(key, guard)
     }
+
+    #[inline(always)]
+    fn min<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.min(guard)
+    }
+
+    #[inline(always)]
+    fn max<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.max(guard)
+    }
 }
 
 #[cfg(test)]
@@ -562,4 +600,19 @@ mod tests {
     fn smoke_efrb_tree() {
         concurrent_map::tests::smoke::<_, EFRBTree<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_efrb_tree() {
+        concurrent_map::tests::edge_cases::<_, EFRBTree<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn min_max_efrb_tree() {
+        concurrent_map::tests::min_max::<_, EFRBTree<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn extract_min_efrb_tree() {
+        concurrent_map::tests::extract_min::<EFRBTree<i32, i32>>();
+    }
 }
@@ -0,0 +1,388 @@
+//! An extendible hash map, whose directory points to segments of buckets.
+//!
+//! A key belongs to the segment of the directory entry of the low `depth` bits of its hash, and to
+//! the bucket of the high bits of its hash in the segment, where each bucket is an `HHSList`. A
+//! segment whose own depth is lower than that of the directory is pointed to by the entries of all
+//! the bits above its depth.
+//!
+//! A segment that grows over `SPLIT_LEN` keys is split into two segments of one more bit, and a
+//! segment that shrinks below `MERGE_LEN / 2` keys is merged with its buddy if they have fewer than
+//! `MERGE_LEN` keys together. Either resize copies the keys into new segments and replaces the
+//! directory with a copy, which is twice as large if the split segment had the depth of the
+//! directory, and half as large if no segment needs the highest bit anymore. The old directory and
+//! the old segments, with all of their nodes, are then retired at once, while the readers that
+//! loaded the old directory may still be traversing it.
+//!
+//! The resizes are serialized by a lock. A resize freezes the segments that it replaces with the
+//! [`Gate`] of a migrating table, and an update of a frozen segment waits until the new directory
+//! is installed, so the updates of a segment block until its resize finishes.
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::list::HHSList;
+use crate::ds_impl::migration::Gate;
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
+use crossbeam_utils::Backoff;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The numbers of the resizes of all extendible hash maps.
+pub static SEGMENT_SPLITS: AtomicUsize = AtomicUsize::new(0);
+pub static SEGMENT_MERGES: AtomicUsize = AtomicUsize::new(0);
+pub static DIRECTORY_DOUBLINGS: AtomicUsize = AtomicUsize::new(0);
+pub static DIRECTORY_HALVINGS: AtomicUsize = AtomicUsize::new(0);
+
+const SEGMENT_BUCKETS: usize = 64;
+const SPLIT_LEN: isize = 4 * SEGMENT_BUCKETS as isize;
+const MERGE_LEN: isize = SEGMENT_BUCKETS as isize;
+/// The directory uses the low half of the hash, and the segments use the high half.
+const MAX_DEPTH: u32 = 32;
+
+struct Segment<K, V> {
+    /// The number of the low bits of the hashes that the keys of the segment share.
+    depth: u32,
+    /// The number of keys, which the updates count after they finish.
+    len: AtomicIsize,
+    /// The updates in the segment, which a resize freezes.
+    gate: Gate,
+    buckets: Box<[HHSList<K, V>]>,
+}
+
+impl<K, V> Segment<K, V>
+where
+    K: Ord + Default,
+    V: Default,
+{
+    fn new(depth: u32) -> Self {
+        Self {
+            depth,
+            len: AtomicIsize::new(0),
+            gate: Gate::default(),
+            buckets: (0..SEGMENT_BUCKETS).map(|_| HHSList::new()).collect(),
+        }
+    }
+
+    #[inline]
+    fn bucket(&self, hash: u64) -> &HHSList<K, V> {
+        &self.buckets[(hash >> MAX_DEPTH) as usize % SEGMENT_BUCKETS]
+    }
+}
+
+impl<K, V> Segment<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    /// Copies the keys of the frozen segment `from` into this segment.
+    fn copy_from(&self, from: &Self, guard: &Guard) {
+        let mut copied = 0;
+        for bucket in from.buckets.iter() {
+            bucket.for_each(
+                |k, v| {
+                    let hash = hash_of(k);
+                    if self.bucket(hash).insert(k.clone(), v.clone(), guard) {
+                        copied += 1;
+                    }
+                },
+                guard,
+            );
+        }
+        self.len.fetch_add(copied, Ordering::Relaxed);
+    }
+}
+
+struct Directory<K, V> {
+    depth: u32,
+    segments: Box<[Atomic<Segment<K, V>>]>,
+}
+
+impl<K, V> Directory<K, V> {
+    #[inline]
+    fn segment<'g>(&self, hash: u64, guard: &'g Guard) -> Shared<'g, Segment<K, V>> {
+        let index = hash as usize & ((1 << self.depth) - 1);
+        self.segments[index].load(Ordering::Relaxed, guard)
+    }
+}
+
+pub struct ExtendibleHashMap<K, V> {
+    directory: Atomic<Directory<K, V>>,
+    resize: Mutex<()>,
+}
+
+#[inline]
+fn hash_of<K: Hash>(k: &K) -> u64 {
+    let mut s = DefaultHasher::new();
+    k.hash(&mut s);
+    s.finish()
+}
+
+impl<K, V> Default for ExtendibleHashMap<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ExtendibleHashMap<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    /// Creates a map with a directory of one entry.
+    pub fn new() -> Self {
+        let segment = Atomic::new(Segment::new(0));
+        Self {
+            directory: Atomic::new(Directory {
+                depth: 0,
+                segments: Box::new([segment]),
+            }),
+            resize: Mutex::new(()),
+        }
+    }
+
+    /// The segment of `hash` in the current directory.
+    #[inline]
+    fn segment<'g>(&self, hash: u64, guard: &'g Guard) -> &'g Segment<K, V> {
+        let directory = unsafe { self.directory.load(Ordering::Acquire, guard).deref() };
+        unsafe { directory.segment(hash, guard).deref() }
+    }
+
+    /// Enters the segment of `hash` for an update, waiting for it to be replaced if it is frozen.
+    #[inline]
+    fn enter<'g>(&self, hash: u64, guard: &'g Guard) -> &'g Segment<K, V> {
+        let backoff = Backoff::new();
+        loop {
+            let segment = self.segment(hash, guard);
+            if segment.gate.enter() {
+                return segment;
+            }
+            backoff.snooze();
+        }
+    }
+
+    pub fn get<'g>(&'g self, k: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V> + 'g> {
+        let hash = hash_of(k);
+        self.segment(hash, guard).bucket(hash).get(k, guard)
+    }
+
+    pub fn insert(&self, k: K, v: V, guard: &Guard) -> bool {
+        let hash = hash_of(&k);
+        let segment = self.enter(hash, guard);
+        let inserted = segment.bucket(hash).insert(k, v, guard);
+        segment.gate.exit();
+        if inserted && segment.len.fetch_add(1, Ordering::Relaxed) >= SPLIT_LEN {
+            self.try_split(hash, guard);
+        }
+        inserted
+    }
+
+    pub fn remove<'g>(&'g self, k: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V> + 'g> {
+        let hash = hash_of(k);
+        let segment = self.enter(hash, guard);
+        let removed = segment.bucket(hash).remove(k, guard);
+        segment.gate.exit();
+        if removed.is_some() && segment.len.fetch_sub(1, Ordering::Relaxed) <= MERGE_LEN / 2 {
+            self.try_merge(hash, guard);
+        }
+        removed
+    }
+
+    /// Splits the segment of `hash` if it is still too large, unless another resize is running.
+    #[cold]
+    fn try_split(&self, hash: u64, guard: &Guard) {
+        let Ok(_resize) = self.resize.try_lock() else {
+            return;
+        };
+        let old_directory = self.directory.load(Ordering::Acquire, guard);
+        let directory = unsafe { old_directory.deref() };
+        let old = directory.segment(hash, guard);
+        let segment = unsafe { old.deref() };
+        if segment.len.load(Ordering::Relaxed) <= SPLIT_LEN || segment.depth == MAX_DEPTH {
+            return;
+        }
+
+        segment.gate.freeze();
+        let halves = [(); 2].map(|_| Segment::new(segment.depth + 1));
+        for bucket in segment.buckets.iter() {
+            bucket.for_each(
+                |k, v| {
+                    let hash = hash_of(k);
+                    let half = &halves[(hash >> segment.depth) as usize & 1];
+                    half.bucket(hash).insert(k.clone(), v.clone(), guard);
+                    half.len.fetch_add(1, Ordering::Relaxed);
+                },
+                guard,
+            );
+        }
+        let halves = halves.map(|half| Owned::new(half).into_shared(guard));
+
+        let depth = directory.depth.max(segment.depth + 1);
+        let segments = (0..1 << depth)
+            .map(|i| {
+                let entry = directory.segment(i as u64, guard);
+                if entry == old {
+                    Atomic::from(halves[(i >> segment.depth) & 1])
+                } else {
+                    Atomic::from(entry)
+                }
+            })
+            .collect();
+        self.install(old_directory, Directory { depth, segments }, guard);
+        SEGMENT_SPLITS.fetch_add(1, Ordering::Relaxed);
+        if depth > directory.depth {
+            DIRECTORY_DOUBLINGS.fetch_add(1, Ordering::Relaxed);
+        }
+        unsafe { guard.defer_destroy(old) };
+    }
+
+    /// Merges the segment of `hash` with its buddy if they are still small enough, unless another
+    /// resize is running.
+    #[cold]
+    fn try_merge(&self, hash: u64, guard: &Guard) {
+        let Ok(_resize) = self.resize.try_lock() else {
+            return;
+        };
+        let old_directory = self.directory.load(Ordering::Acquire, guard);
+        let directory = unsafe { old_directory.deref() };
+        let old = directory.segment(hash, guard);
+        let segment = unsafe { old.deref() };
+        if segment.depth == 0 {
+            return;
+        }
+        // The buddy differs only in the highest bit of the segment.
+        let old_buddy = directory.segment(hash ^ (1 << (segment.depth - 1)), guard);
+        let buddy = unsafe { old_buddy.deref() };
+        if buddy.depth != segment.depth
+            || segment.len.load(Ordering::Relaxed) + buddy.len.load(Ordering::Relaxed) >= MERGE_LEN
+        {
+            return;
+        }
+
+        segment.gate.freeze();
+        buddy.gate.freeze();
+        let merged = Segment::new(segment.depth - 1);
+        merged.copy_from(segment, guard);
+        merged.copy_from(buddy, guard);
+        let merged = Owned::new(merged).into_shared(guard);
+
+        let entries = directory.segments.iter().map(|entry| {
+            let entry = entry.load(Ordering::Relaxed, guard);
+            if entry == old || entry == old_buddy {
+                merged
+            } else {
+                entry
+            }
+        });
+        let depth = entries
+            .clone()
+            .map(|entry| unsafe { entry.deref() }.depth)
+            .max()
+            .unwrap();
+        let segments = entries.take(1 << depth).map(Atomic::from).collect();
+        self.install(old_directory, Directory { depth, segments }, guard);
+        SEGMENT_MERGES.fetch_add(1, Ordering::Relaxed);
+        if depth < directory.depth {
+            DIRECTORY_HALVINGS.fetch_add(1, Ordering::Relaxed);
+        }
+        unsafe {
+            guard.defer_destroy(old);
+            guard.defer_destroy(old_buddy);
+        }
+    }
+
+    /// Replaces the directory `old` with `new`, while holding the lock of the resizes.
+    fn install(&self, old: Shared<Directory<K, V>>, new: Directory<K, V>, guard: &Guard) {
+        self.directory.store(Owned::new(new), Ordering::Release);
+        unsafe { guard.defer_destroy(old) };
+    }
+}
+
+impl<K, V> Drop for ExtendibleHashMap<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+            let directory = self.directory.load(Ordering::Relaxed, guard);
+            for (i, entry) in directory.deref().segments.iter().enumerate() {
+                let segment = entry.load(Ordering::Relaxed, guard);
+                // The first entry of a segment is the one of its low bits alone.
+                if i < 1 << segment.deref().depth {
+                    drop(segment.into_owned());
+                }
+            }
+            drop(directory.into_owned());
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for ExtendibleHashMap<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    fn new() -> Self {
+        Self::new()
+    }
+
+    #[inline(always)]
+    fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
+        self.get(key, guard)
+    }
+    #[inline(always)]
+    fn insert(&self, key: K, value: V, guard: &Guard) -> bool {
+        self.insert(key, value, guard)
+    }
+    #[inline(always)]
+    fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
+        self.remove(key, guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExtendibleHashMap, SEGMENT_BUCKETS};
+    use crate::ds_impl::ebr::concurrent_map::{self, OutputHolder};
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn smoke_extendible_hash_map() {
+        concurrent_map::tests::smoke::<_, ExtendibleHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn edge_cases_extendible_hash_map() {
+        concurrent_map::tests::edge_cases::<_, ExtendibleHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn split_and_merge_extendible_hash_map() {
+        let map = ExtendibleHashMap::new();
+        let depth = |map: &ExtendibleHashMap<i32, String>| {
+            let guard = &crossbeam_ebr::pin();
+            unsafe { map.directory.load(Ordering::Acquire, guard).deref() }.depth
+        };
+        let keys = 0..(SEGMENT_BUCKETS * 64) as i32;
+        for key in keys.clone() {
+            assert!(map.insert(key, key.to_string(), &crossbeam_ebr::pin()));
+        }
+        let grown = depth(&map);
+        assert!(grown > 0);
+
+        for key in keys.clone().step_by(2) {
+            let guard = &crossbeam_ebr::pin();
+            assert_eq!(*map.remove(&key, guard).unwrap().output(), key.to_string());
+        }
+        for key in keys.clone() {
+            let guard = &crossbeam_ebr::pin();
+            let value = map.get(&key, guard).map(|value| value.output().clone());
+            assert_eq!(value, (key % 2 == 1).then(|| key.to_string()));
+        }
+        for key in keys.skip(1).step_by(2) {
+            assert!(map.remove(&key, &crossbeam_ebr::pin()).is_some());
+        }
+        assert!(depth(&map) < grown);
+    }
+}
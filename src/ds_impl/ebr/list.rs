@@ -1,11 +1,61 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
-use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
+use super::slab::NodeAlloc;
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::counters::{Counters, LocalCounts};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
+use crossbeam_ebr::{unprotected, Atomic, Guard, Shared};
 
 use std::cmp::Ordering::{Equal, Greater, Less};
-use std::sync::atomic::Ordering;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// The minimum length of a chain of logically removed nodes that `find_harris` unlinks while
+/// serving `get` and `remove`. `insert` always unlinks the chain before the insertion point.
+static HARRIS_UNLINK_THRESHOLD: AtomicUsize = AtomicUsize::new(1);
+
+/// The chains that `find_harris` left in place because they were too short, and their total
+/// length, counted per thread as they are on the path of every traversal.
+static HARRIS_SKIPS: Counters<2> = Counters::new(&HARRIS_SKIPS_LOCAL);
+
+thread_local! {
+    static HARRIS_SKIPS_LOCAL: LocalCounts<2> = LocalCounts::new(&HARRIS_SKIPS);
+}
+
+/// Returns the numbers of chains that `find_harris` left in place because they were too short,
+/// and of their nodes, since the start of the process, of the exited threads and the calling
+/// thread.
+pub fn harris_skipped_unlinks() -> (usize, usize) {
+    let [chains, nodes] = HARRIS_SKIPS.get();
+    (chains, nodes)
+}
+
+/// Sets the minimum length of a chain of logically removed nodes that Harris's list unlinks
+/// while traversing for `get` and `remove`. The default, 1, unlinks every chain.
+pub fn set_harris_unlink_threshold(threshold: usize) {
+    assert!(threshold > 0, "The unlink threshold must be positive");
+    HARRIS_UNLINK_THRESHOLD.store(threshold, Ordering::Relaxed);
+}
+
+/// Whether `find_harris_herlihy_shavit` reports a logically removed node with the key as found.
+static HHS_EVENTUAL_READS: AtomicBool = AtomicBool::new(false);
+
+/// The number of `get`s of `HHSList` that returned the value of a logically removed node.
+pub static HHS_STALE_HITS: AtomicUsize = AtomicUsize::new(0);
+
+/// Makes the `get` of `HHSList` (and of `HashMap`, whose buckets are `HHSList`s) an eventually
+/// consistent read, which does not check the mark of the node with the key and returns its value
+/// even if the node is logically removed.
+///
+/// Such a `get` is not linearizable: it may return a value after the `remove` of the value has
+/// returned, until the node is unlinked. It bounds how much the check of the logical deletion
+/// costs, and the reads that returned a removed value are counted in `HHS_STALE_HITS`.
+pub fn set_hhs_eventual_reads(eventual: bool) {
+    HHS_EVENTUAL_READS.store(eventual, Ordering::Relaxed);
+}
 
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     /// Mark: tag(), Tag: not needed
     next: Atomic<Node<K, V>>,
     key: K,
@@ -14,6 +64,8 @@ struct Node<K, V> {
 
 struct List<K, V> {
     head: Atomic<Node<K, V>>,
+    /// The allocator of the nodes except the head.
+    alloc: NodeAlloc<Node<K, V>>,
 }
 
 impl<K, V> Default for List<K, V>
@@ -29,12 +81,14 @@ where
 impl<K, V> Drop for List<K, V> {
     fn drop(&mut self) {
         unsafe {
-            let mut curr = self.head.load(Ordering::Relaxed, unprotected());
+            let head = self.head.load(Ordering::Relaxed, unprotected());
+            let mut curr = head.deref().next.load(Ordering::Relaxed, unprotected());
+            drop(head.into_owned());
 
             while !curr.is_null() {
                 let curr_ref = curr.deref_mut();
                 let next = curr_ref.next.load(Ordering::Relaxed, unprotected());
-                drop(curr.into_owned());
+                self.alloc.free(curr);
                 curr = next;
             }
         }
@@ -96,8 +150,15 @@ where
     /// Creates a new list.
     #[inline]
     pub fn new() -> Self {
+        Self::with_alloc(NodeAlloc::Global)
+    }
+
+    /// Creates a new list that allocates its nodes with `alloc`.
+    #[inline]
+    pub fn with_alloc(alloc: NodeAlloc<Node<K, V>>) -> Self {
         List {
             head: Atomic::new(Node::head()),
+            alloc,
         }
     }
 
@@ -107,13 +168,37 @@ where
         &'g self,
         key: &K,
         guard: &'g Guard,
-    ) -> Result<(bool, Cursor<'g, K, V>), ()> {
+    ) -> Result<(bool, Cursor<'g, K, V>), Retry> {
+        self.find_harris_deferring(key, 1, guard)
+    }
+
+    /// Clean up a chain of logically removed nodes only if it has at least
+    /// `HARRIS_UNLINK_THRESHOLD` nodes. The returned cursor may not be adjacent, so it must not
+    /// be used to insert a node.
+    #[inline]
+    fn find_harris_deferred<'g>(
+        &'g self,
+        key: &K,
+        guard: &'g Guard,
+    ) -> Result<(bool, Cursor<'g, K, V>), Retry> {
+        let threshold = HARRIS_UNLINK_THRESHOLD.load(Ordering::Relaxed);
+        self.find_harris_deferring(key, threshold, guard)
+    }
+
+    #[inline]
+    fn find_harris_deferring<'g>(
+        &'g self,
+        key: &K,
+        threshold: usize,
+        guard: &'g Guard,
+    ) -> Result<(bool, Cursor<'g, K, V>), Retry> {
         // Finding phase
         // - cursor.curr: first unmarked node w/ key >= search key (4)
         // - cursor.prev: the ref of .next in previous unmarked node (1 -> 2)
         // 1 -> 2 -x-> 3 -x-> 4 -> 5 -> ∅  (search key: 4)
         let mut cursor = Cursor::head(&self.head, guard);
         let mut prev_next = cursor.curr;
+        let mut chain_len = 0;
         let found = loop {
             let curr_node = some_or!(unsafe { cursor.curr.as_ref() }, break false);
             let next = curr_node.next.load(Ordering::Acquire, guard);
@@ -126,6 +211,7 @@ where
             if next.tag() != 0 {
                 // We add a 0 tag here so that `self.curr`s tag is always 0.
                 cursor.curr = next.with_tag(0);
+                chain_len += 1;
                 continue;
             }
 
@@ -134,6 +220,7 @@ where
                     cursor.curr = next;
                     cursor.prev = &curr_node.next;
                     prev_next = next;
+                    chain_len = 0;
                 }
                 Equal => break true,
                 Greater => break false,
@@ -145,6 +232,12 @@ where
             return Ok((found, cursor));
         }
 
+        if chain_len < threshold {
+            HARRIS_SKIPS.count(0);
+            HARRIS_SKIPS.add(1, chain_len);
+            return Ok((found, cursor));
+        }
+
         // cleanup marked nodes between prev and curr
         cursor
             .prev
@@ -155,13 +248,13 @@ where
                 Ordering::Relaxed,
                 guard,
             )
-            .map_err(|_| ())?;
+            .map_err(|_| Retry::Unlink)?;
 
         // defer_destroy from cursor.prev.load() to cursor.curr (exclusive)
         let mut node = prev_next;
         while node.with_tag(0) != cursor.curr {
             let next = unsafe { node.deref() }.next.load(Ordering::Acquire, guard);
-            unsafe { guard.defer_destroy(node) };
+            unsafe { self.alloc.retire(node, guard) };
             node = next;
         }
 
@@ -174,7 +267,7 @@ where
         &'g self,
         key: &K,
         guard: &'g Guard,
-    ) -> Result<(bool, Cursor<'g, K, V>), ()> {
+    ) -> Result<(bool, Cursor<'g, K, V>), Retry> {
         let mut cursor = Cursor::head(&self.head, guard);
         loop {
             debug_assert_eq!(cursor.curr.tag(), 0);
@@ -195,8 +288,8 @@ where
                         Ordering::Relaxed,
                         guard,
                     )
-                    .map_err(|_| ())?;
-                unsafe { guard.defer_destroy(cursor.curr) };
+                    .map_err(|_| Retry::Unlink)?;
+                unsafe { self.alloc.retire(cursor.curr, guard) };
                 cursor.curr = next;
                 continue;
             }
@@ -218,10 +311,12 @@ where
         &'g self,
         key: &K,
         guard: &'g Guard,
-    ) -> Result<(bool, Cursor<'g, K, V>), ()> {
+    ) -> Result<(bool, Cursor<'g, K, V>), Retry> {
+        wait_free::traversal();
         let mut cursor = Cursor::head(&self.head, guard);
         Ok(loop {
             let curr_node = some_or!(unsafe { cursor.curr.as_ref() }, break (false, cursor));
+            wait_free::visit();
             let next = curr_node.next.load(Ordering::Acquire, guard);
             match curr_node.key.cmp(key) {
                 Less => {
@@ -230,19 +325,58 @@ where
                     cursor.prev = &curr_node.next;
                     continue;
                 }
-                Equal => break (next.tag() == 0, cursor),
+                Equal => {
+                    if next.tag() != 0 && HHS_EVENTUAL_READS.load(Ordering::Relaxed) {
+                        HHS_STALE_HITS.fetch_add(1, Ordering::Relaxed);
+                        break (true, cursor);
+                    }
+                    break (next.tag() == 0, cursor);
+                }
                 Greater => break (false, cursor),
             }
         })
     }
 
+    /// Looks up the keys of `requests`, which must be sorted in an increasing order of the keys,
+    /// with one Harris-Herlihy-Shavit traversal, and calls `found` with the index of each request
+    /// and the value of its key.
+    pub fn harris_herlihy_shavit_get_sorted<'g, F>(
+        &'g self,
+        requests: &[(usize, &K)],
+        mut found: F,
+        guard: &'g Guard,
+    ) where
+        F: FnMut(usize, Option<&'g V>),
+    {
+        let mut curr = self.head.load(Ordering::Acquire, guard);
+        for &(request, key) in requests {
+            let value = loop {
+                let curr_node = some_or!(unsafe { curr.as_ref() }, break None);
+                let next = curr_node.next.load(Ordering::Acquire, guard);
+                match curr_node.key.cmp(key) {
+                    Less => curr = next,
+                    Equal => {
+                        if next.tag() != 0 && HHS_EVENTUAL_READS.load(Ordering::Relaxed) {
+                            HHS_STALE_HITS.fetch_add(1, Ordering::Relaxed);
+                            break Some(&curr_node.value);
+                        }
+                        break (next.tag() == 0).then_some(&curr_node.value);
+                    }
+                    Greater => break None,
+                }
+            };
+            // `curr` stays at the node of the key, which may also be the key of the next request.
+            found(request, value);
+        }
+    }
+
     #[inline]
     fn get<'g, F>(&'g self, key: &K, find: F, guard: &'g Guard) -> Option<&'g V>
     where
-        F: for<'h> Fn(&'h Self, &K, &'h Guard) -> Result<(bool, Cursor<'h, K, V>), ()>,
+        F: for<'h> Fn(&'h Self, &K, &'h Guard) -> Result<(bool, Cursor<'h, K, V>), Retry>,
     {
         loop {
-            let (found, cursor) = ok_or!(find(self, key, guard), continue);
+            let (found, cursor) = ok_or_retry!(find(self, key, guard), continue);
             if found {
                 return unsafe { cursor.curr.as_ref().map(|n| &n.value) };
             }
@@ -253,28 +387,47 @@ where
     #[inline]
     fn insert<'g, F>(&'g self, key: K, value: V, find: F, guard: &'g Guard) -> bool
     where
-        F: for<'h> Fn(&'h Self, &K, &'h Guard) -> Result<(bool, Cursor<'h, K, V>), ()>,
+        F: for<'h> Fn(&'h Self, &K, &'h Guard) -> Result<(bool, Cursor<'h, K, V>), Retry>,
+    {
+        self.get_or_insert(key, value, find, guard).is_none()
+    }
+
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`,
+    /// with the same traversal that finds the position of the new node.
+    #[inline]
+    fn get_or_insert<'g, F>(&'g self, key: K, value: V, find: F, guard: &'g Guard) -> Option<&'g V>
+    where
+        F: for<'h> Fn(&'h Self, &K, &'h Guard) -> Result<(bool, Cursor<'h, K, V>), Retry>,
     {
-        let mut node = Owned::new(Node::new(key, value));
+        let mut node = self.alloc.alloc(Node::new(key, value), guard);
+        let node_ref = unsafe { node.deref() };
         loop {
-            let (found, mut cursor) = ok_or!(find(self, &node.key, guard), continue);
+            let (found, mut cursor) = ok_or_retry!(find(self, &node_ref.key, guard), continue);
             if found {
-                return false;
+                unsafe { self.alloc.free(node) };
+                return unsafe { cursor.curr.as_ref().map(|n| &n.value) };
             }
 
-            node.next.store(cursor.curr, Ordering::Relaxed);
-            match cursor.prev.compare_exchange(
-                cursor.curr,
-                node,
-                Ordering::Release,
-                Ordering::Relaxed,
-                guard,
-            ) {
-                Ok(node) => {
+            node_ref.next.store(cursor.curr, Ordering::Relaxed);
+            match cursor
+                .prev
+                .compare_exchange(
+                    cursor.curr,
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    guard,
+                )
+                .classify(&cursor.curr)
+            {
+                Cas::Success(node) => {
                     cursor.curr = node;
-                    return true;
+                    return None;
+                }
+                Cas::CurrentDiffers(e) | Cas::TagDiffers(e) => {
+                    Retry::CasFail.count();
+                    node = e.new;
                 }
-                Err(e) => node = e.new,
             }
         }
     }
@@ -282,10 +435,10 @@ where
     #[inline]
     fn remove<'g, F>(&'g self, key: &K, find: F, guard: &'g Guard) -> Option<&'g V>
     where
-        F: for<'h> Fn(&'h Self, &K, &'h Guard) -> Result<(bool, Cursor<'h, K, V>), ()>,
+        F: for<'h> Fn(&'h Self, &K, &'h Guard) -> Result<(bool, Cursor<'h, K, V>), Retry>,
     {
         loop {
-            let (found, cursor) = ok_or!(find(self, key, guard), continue);
+            let (found, cursor) = ok_or_retry!(find(self, key, guard), continue);
             if !found {
                 return None;
             }
@@ -294,6 +447,7 @@ where
 
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel, guard);
             if next.tag() == 1 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -308,7 +462,7 @@ where
                 )
                 .is_ok()
             {
-                unsafe { guard.defer_destroy(cursor.curr) };
+                unsafe { self.alloc.retire(cursor.curr, guard) };
             }
 
             return Some(&curr_node.value);
@@ -327,6 +481,7 @@ where
 
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel, guard);
             if next.tag() == 1 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -341,16 +496,48 @@ where
                 )
                 .is_ok()
             {
-                unsafe { guard.defer_destroy(cursor.curr) };
+                unsafe { self.alloc.retire(cursor.curr, guard) };
             }
 
             return Some((&curr_node.key, &curr_node.value));
         }
     }
 
+    /// Calls `f` with the key and value of each node that is not logically removed, in the order
+    /// of the keys. The nodes that are inserted or removed concurrently may or may not be visited.
+    pub fn for_each<'g, F>(&'g self, mut f: F, guard: &'g Guard)
+    where
+        F: FnMut(&'g K, &'g V),
+    {
+        let mut curr = Cursor::head(&self.head, guard).curr;
+        while let Some(node) = unsafe { curr.as_ref() } {
+            let next = node.next.load(Ordering::Acquire, guard);
+            if next.tag() == 0 {
+                f(&node.key, &node.value);
+            }
+            curr = next.with_tag(0);
+        }
+    }
+
+    /// Returns an iterator over the elements whose keys are not less than `key`, in the order of
+    /// the keys. See [`Iter`] for what it yields while the list is modified concurrently.
+    pub fn iter_from<'g>(&'g self, key: &K, guard: &'g Guard) -> Iter<'g, K, V> {
+        let mut curr = Cursor::head(&self.head, guard).curr;
+        while let Some(node) = unsafe { curr.as_ref() } {
+            if node.key >= *key {
+                break;
+            }
+            curr = node.next.load(Ordering::Acquire, guard);
+        }
+        Iter {
+            curr: curr.with_tag(0),
+            guard,
+        }
+    }
+
     #[inline]
     pub fn harris_get<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
-        self.get(key, Self::find_harris, guard)
+        self.get(key, Self::find_harris_deferred, guard)
     }
 
     #[inline]
@@ -358,9 +545,14 @@ where
         self.insert(key, value, Self::find_harris, guard)
     }
 
+    #[inline]
+    pub fn harris_get_or_insert<'g>(&'g self, key: K, value: V, guard: &'g Guard) -> Option<&'g V> {
+        self.get_or_insert(key, value, Self::find_harris, guard)
+    }
+
     #[inline]
     pub fn harris_remove<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
-        self.remove(key, Self::find_harris, guard)
+        self.remove(key, Self::find_harris_deferred, guard)
     }
 
     #[inline]
@@ -373,6 +565,16 @@ where
         self.insert(key, value, Self::find_harris_michael, guard)
     }
 
+    #[inline]
+    pub fn harris_michael_get_or_insert<'g>(
+        &'g self,
+        key: K,
+        value: V,
+        guard: &'g Guard,
+    ) -> Option<&'g V> {
+        self.get_or_insert(key, value, Self::find_harris_michael, guard)
+    }
+
     #[inline]
     pub fn harris_michael_remove<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
         self.remove(key, Self::find_harris_michael, guard)
@@ -384,10 +586,47 @@ where
     }
 }
 
+impl<K: Debug, V: Debug> List<K, V> {
+    /// Prints the chain of nodes after the head to stderr. A node whose `next` is marked, i.e.,
+    /// a logically removed one, is followed by the tag of its `next`.
+    #[cfg(debug_assertions)]
+    fn dump(&self, guard: &Guard) {
+        use std::io::Write;
+
+        let mut out = std::io::stderr().lock();
+        let head = unsafe { self.head.load(Ordering::Acquire, guard).deref() };
+        let mut curr = head.next.load(Ordering::Acquire, guard);
+        let _ = write!(out, "head");
+        while let Some(node) = unsafe { curr.as_ref() } {
+            let next = node.next.load(Ordering::Acquire, guard);
+            let _ = write!(
+                out,
+                " -> {:?}: {:?} @ {:p}",
+                node.key,
+                node.value,
+                curr.as_raw()
+            );
+            if next.tag() != 0 {
+                let _ = write!(out, " [tag {}]", next.tag());
+            }
+            curr = next;
+        }
+        let _ = writeln!(out, " -> null");
+    }
+}
+
 pub struct HList<K, V> {
     inner: List<K, V>,
 }
 
+impl<K: Debug, V: Debug> HList<K, V> {
+    /// Prints the chain of nodes to stderr, with the tags of the logically removed ones.
+    #[cfg(debug_assertions)]
+    pub fn dump(&self, guard: &Guard) {
+        self.inner.dump(guard)
+    }
+}
+
 impl<K, V> ConcurrentMap<K, V> for HList<K, V>
 where
     K: Ord + Default,
@@ -397,6 +636,12 @@ where
         HList { inner: List::new() }
     }
 
+    fn with_slab() -> Self {
+        HList {
+            inner: List::with_alloc(NodeAlloc::slab()),
+        }
+    }
+
     #[inline(always)]
     fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.inner.harris_get(key, guard)
@@ -406,6 +651,14 @@ where
         self.inner.harris_insert(key, value, guard)
     }
     #[inline(always)]
+    fn get_or_insert(&self, key: K, value: V, guard: &Guard) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.inner.harris_get_or_insert(key, value, guard).cloned()
+    }
+    #[inline(always)]
     fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.inner.harris_remove(key, guard)
     }
@@ -415,6 +668,14 @@ pub struct HMList<K, V> {
     inner: List<K, V>,
 }
 
+impl<K: Debug, V: Debug> HMList<K, V> {
+    /// Prints the chain of nodes to stderr, with the tags of the logically removed ones.
+    #[cfg(debug_assertions)]
+    pub fn dump(&self, guard: &Guard) {
+        self.inner.dump(guard)
+    }
+}
+
 impl<K, V> ConcurrentMap<K, V> for HMList<K, V>
 where
     K: Ord + Default,
@@ -424,6 +685,12 @@ where
         HMList { inner: List::new() }
     }
 
+    fn with_slab() -> Self {
+        HMList {
+            inner: List::with_alloc(NodeAlloc::slab()),
+        }
+    }
+
     #[inline(always)]
     fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.inner.harris_michael_get(key, guard)
@@ -433,25 +700,128 @@ where
         self.inner.harris_michael_insert(key, value, guard)
     }
     #[inline(always)]
+    fn get_or_insert(&self, key: K, value: V, guard: &Guard) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.inner
+            .harris_michael_get_or_insert(key, value, guard)
+            .cloned()
+    }
+    #[inline(always)]
     fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.inner.harris_michael_remove(key, guard)
     }
 }
 
+/// An iterator over the elements of a list from a key, in the order of the keys, which walks the
+/// nodes without unlinking the logically removed ones, as the gets of `HHSList` do. It does not
+/// take a snapshot of the list while the list is modified concurrently, but
+///
+/// * the keys it yields are strictly increasing,
+/// * it yields every key that is in the list from its creation until the end of the iteration, and
+/// * each element that it yields was in the list at some point after its previous element was
+///   yielded, while a key that is inserted or removed during the iteration may or may not be
+///   yielded.
+///
+/// The elements are protected by the guard, so the iteration holds back the epoch until it ends.
+pub struct Iter<'g, K, V> {
+    /// The next node to visit, which may be logically removed.
+    curr: Shared<'g, Node<K, V>>,
+    guard: &'g Guard,
+}
+
+impl<'g, K, V> Iterator for Iter<'g, K, V> {
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = unsafe { self.curr.as_ref() } {
+            let next = node.next.load(Ordering::Acquire, self.guard);
+            self.curr = next.with_tag(0);
+            if next.tag() == 0 {
+                return Some((&node.key, &node.value));
+            }
+        }
+        None
+    }
+}
+
 pub struct HHSList<K, V> {
     inner: List<K, V>,
 }
 
+impl<K: Debug, V: Debug> HHSList<K, V> {
+    /// Prints the chain of nodes to stderr, with the tags of the logically removed ones.
+    #[cfg(debug_assertions)]
+    pub fn dump(&self, guard: &Guard) {
+        self.inner.dump(guard)
+    }
+}
+
 impl<K, V> HHSList<K, V>
 where
     K: Ord + Default,
     V: Default,
 {
+    /// Creates a list that allocates its nodes with `alloc`, which may be shared with other lists.
+    pub(crate) fn with_alloc(alloc: NodeAlloc<Node<K, V>>) -> Self {
+        HHSList {
+            inner: List::with_alloc(alloc),
+        }
+    }
+
     /// Pop the first element efficiently.
     /// This method is used for only the fine grained benchmark (src/bin/long_running).
     pub fn pop<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
         self.inner.pop(guard)
     }
+
+    /// Calls `f` with each element of the list, in the order of the keys.
+    pub fn for_each<'g, F>(&'g self, f: F, guard: &'g Guard)
+    where
+        F: FnMut(&'g K, &'g V),
+    {
+        self.inner.for_each(f, guard)
+    }
+
+    /// Returns an iterator over the elements whose keys are not less than `key`, in the order of
+    /// the keys, with the guarantees of [`Iter`] under concurrent updates.
+    pub fn iter_from<'g>(&'g self, key: &K, guard: &'g Guard) -> Iter<'g, K, V> {
+        self.inner.iter_from(key, guard)
+    }
+
+    /// Looks up the keys of `requests`, which must be sorted by their keys, with one traversal.
+    #[inline]
+    pub fn get_sorted<'g, F>(&'g self, requests: &[(usize, &K)], found: F, guard: &'g Guard)
+    where
+        F: FnMut(usize, Option<&'g V>),
+    {
+        self.inner
+            .harris_herlihy_shavit_get_sorted(requests, found, guard)
+    }
+}
+
+impl<K, V> HHSList<K, V>
+where
+    K: Ord + Default + Clone,
+    V: Default + Clone,
+{
+    /// Moves all elements of `other` into this list, and returns the number of elements that are
+    /// inserted. An element whose key is already in this list is dropped.
+    ///
+    /// The nodes of `other` are popped and retired one by one, and their elements are inserted as
+    /// new nodes. A popped node cannot be linked into this list instead, since a concurrent
+    /// traversal of `other` may follow its `next` until the node is reclaimed.
+    pub fn merge_from(&self, other: &Self, guard: &Guard) -> usize {
+        let mut merged = 0;
+        while let Some((key, value)) = other.pop(guard) {
+            if self.inner.harris_insert(key.clone(), value.clone(), guard) {
+                merged += 1;
+            }
+        }
+        merged
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for HHSList<K, V>
@@ -463,6 +833,12 @@ where
         HHSList { inner: List::new() }
     }
 
+    fn with_slab() -> Self {
+        HHSList {
+            inner: List::with_alloc(NodeAlloc::slab()),
+        }
+    }
+
     #[inline(always)]
     fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.inner.harris_herlihy_shavit_get(key, guard)
@@ -472,9 +848,21 @@ where
         self.inner.harris_insert(key, value, guard)
     }
     #[inline(always)]
+    fn get_or_insert(&self, key: K, value: V, guard: &Guard) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.inner.harris_get_or_insert(key, value, guard).cloned()
+    }
+    #[inline(always)]
     fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.inner.harris_remove(key, guard)
     }
+    #[inline(always)]
+    fn partial_scan(&self, key: &K, items: usize, guard: &Guard) -> usize {
+        self.iter_from(key, guard).take(items).count()
+    }
 }
 
 #[cfg(test)]
@@ -487,22 +875,193 @@ mod tests {
         concurrent_map::tests::smoke::<_, HList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<_, HList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hm_list() {
         concurrent_map::tests::smoke::<_, HMList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hm_list() {
+        concurrent_map::tests::edge_cases::<_, HMList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<_, HHSList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String>::new();
+        let guard = &crossbeam_ebr::pin();
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(key, key.to_string(), guard),
+            Op::Remove => map.remove(&key, guard).is_some(),
+            Op::Get => map.get(&key, guard).is_some(),
+        });
+    }
+
+    #[test]
+    fn output_holder_hhs_list() {
+        concurrent_map::tests::output_holder::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn get_or_insert_h_list() {
+        concurrent_map::tests::get_or_insert::<HList<i32, i32>>();
+    }
+
+    #[test]
+    fn get_or_insert_hm_list() {
+        concurrent_map::tests::get_or_insert::<HMList<i32, i32>>();
+    }
+
+    #[test]
+    fn get_or_insert_hhs_list() {
+        concurrent_map::tests::get_or_insert::<HHSList<i32, i32>>();
+    }
+
+    #[test]
+    fn smoke_hhs_list_slab() {
+        use concurrent_map::ConcurrentMap;
+        concurrent_map::tests::smoke_with(HHSList::with_slab(), &i32::to_string);
+    }
+
+    #[test]
+    fn merge_hhs_list() {
+        use concurrent_map::{ConcurrentMap, OutputHolder};
+        use crossbeam_ebr::pin;
+        use crossbeam_utils::thread;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const THREADS: i32 = 8;
+        const ELEMENTS_PER_THREADS: i32 = 1000;
+
+        let map = &HHSList::<i32, String>::new();
+        let merged = &AtomicUsize::new(0);
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let other = HHSList::new();
+                    for i in 0..ELEMENTS_PER_THREADS {
+                        let key = i * THREADS + t;
+                        other.insert(key, key.to_string(), &pin());
+                    }
+                    // A key that every thread merges, but only one inserts.
+                    other.insert(-1, (-1).to_string(), &pin());
+                    merged.fetch_add(map.merge_from(&other, &pin()), Ordering::Relaxed);
+                    assert!(other.pop(&pin()).is_none());
+                });
+            }
+        })
+        .unwrap();
+
+        let total = THREADS * ELEMENTS_PER_THREADS;
+        assert_eq!(merged.load(Ordering::Relaxed), total as usize + 1);
+        let guard = &pin();
+        for key in -1..total {
+            assert_eq!(*map.get(&key, guard).unwrap().output(), key.to_string());
+        }
+    }
+
+    #[test]
+    fn iter_from_hhs_list() {
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::new();
+        let guard = &crossbeam_ebr::pin();
+        for key in (0..100).step_by(10) {
+            map.insert(key, key.to_string(), guard);
+        }
+        map.remove(&50, guard);
+
+        let keys = |from, items| {
+            map.iter_from(&from, guard)
+                .take(items)
+                .map(|(k, v)| {
+                    assert_eq!(*v, k.to_string());
+                    *k
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(-1, 3), [0, 10, 20]);
+        assert_eq!(keys(30, 3), [30, 40, 60]);
+        assert_eq!(keys(31, 100), [40, 60, 70, 80, 90]);
+        assert!(keys(91, 100).is_empty());
+    }
+
+    #[test]
+    fn concurrent_iter_from_hhs_list() {
+        use concurrent_map::ConcurrentMap;
+        use crossbeam_ebr::pin;
+        use crossbeam_utils::thread;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        const THREADS: i32 = 4;
+        const KEYS: i32 = 1000;
+        const ITEMS: usize = 100;
+
+        let map = &HHSList::<i32, String>::new();
+        for key in 0..KEYS {
+            map.insert(key, key.to_string(), &pin());
+        }
+        let done = &AtomicBool::new(false);
+        thread::scope(|s| {
+            // The odd keys are removed and inserted back, while the even keys stay.
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    while !done.load(Ordering::Relaxed) {
+                        for key in (2 * t + 1..KEYS).step_by(2 * THREADS as usize) {
+                            let guard = &pin();
+                            assert!(map.remove(&key, guard).is_some());
+                            assert!(map.insert(key, key.to_string(), guard));
+                        }
+                    }
+                });
+            }
+            for from in (0..KEYS).step_by(7).cycle().take(2000) {
+                let guard = &pin();
+                let mut last = None;
+                let mut even = from + from % 2;
+                let mut yielded = 0;
+                for (key, value) in map.iter_from(&from, guard).take(ITEMS) {
+                    assert!(*key >= from && last < Some(*key));
+                    assert_eq!(*value, key.to_string());
+                    if key % 2 == 0 {
+                        assert_eq!(*key, even, "an even key is missed");
+                        even += 2;
+                    }
+                    last = Some(*key);
+                    yielded += 1;
+                }
+                if yielded < ITEMS {
+                    assert!(even >= KEYS, "an even key is missed");
+                }
+            }
+            done.store(true, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use concurrent_map::ConcurrentMap;
         let map = HHSList::new();
 
         let guard = &crossbeam_ebr::pin();
+        assert!(map.pop(guard).is_none());
         map.insert(1, "1", guard);
         map.insert(2, "2", guard);
         map.insert(3, "3", guard);
@@ -0,0 +1,502 @@
+//! A skip list that marks the levels of a removed node with marker nodes, after Fraser.
+//!
+//! Instead of setting the mark bit of a link of the removed node, a remove swings the link to a
+//! marker node that holds the successor, as the lock-free skip lists without spare pointer bits
+//! do. An insert after the node then fails its CAS as it does on a marked link, and a traversal
+//! that finds a marker unlinks the node together with its marker. A link to a marker carries the
+//! tag 1, so that a traversal tells a marker from a node without reading it. A remove thus
+//! allocates a marker per level of the node, which is retired with the node, and a traversal
+//! takes an extra hop through the marker to find the successor.
+
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+
+const MAX_HEIGHT: usize = 32;
+
+type Tower<K, V> = [Atomic<Node<K, V>>; MAX_HEIGHT];
+
+pub(crate) struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Tower<K, V>,
+    height: usize,
+    refs: AtomicUsize,
+}
+
+/// The marker of a level of a removed node, which holds the successor of the node at the level.
+struct Marker<K, V> {
+    next: Atomic<Node<K, V>>,
+}
+
+/// The marker that a link tagged with 1 points to.
+fn marker<'g, K, V>(link: Shared<'g, Node<K, V>>) -> &'g Marker<K, V> {
+    debug_assert!(link.tag() & 1 != 0);
+    unsafe { &*(link.as_raw() as *const Marker<K, V>) }
+}
+
+impl<K, V> Node<K, V> {
+    pub fn new(key: K, value: V) -> Self {
+        let height = Self::generate_height();
+        let next: [Atomic<Node<K, V>>; MAX_HEIGHT] = Default::default();
+        for link in next.iter().take(height) {
+            link.store(Shared::null().with_tag(2), Ordering::Relaxed);
+        }
+        Self {
+            key,
+            value,
+            next,
+            height,
+            refs: AtomicUsize::new(height + 1),
+        }
+    }
+
+    fn generate_height() -> usize {
+        // returns 1 with probability 3/4
+        if rand::random::<usize>() % 4 < 3 {
+            return 1;
+        }
+        // returns h with probability 2^(−(h+1))
+        let mut height = 2;
+        while height < MAX_HEIGHT && rand::random::<bool>() {
+            height += 1;
+        }
+        height
+    }
+
+    pub fn decrement(&self, guard: &Guard) {
+        if self.refs.fetch_sub(1, Ordering::Release) == 1 {
+            fence(Ordering::Acquire);
+            // The markers are only reachable through the node, so they go with it.
+            for link in self.next.iter().take(self.height) {
+                let link = link.load(Ordering::Relaxed, guard);
+                if link.tag() & 1 != 0 {
+                    unsafe {
+                        guard.defer_destroy(Shared::from(marker(link) as *const Marker<K, V>))
+                    };
+                }
+            }
+            unsafe { guard.defer_destroy(Shared::from(self as *const _)) };
+        }
+    }
+
+    /// Swings every level of the tower to a marker, from the top. Returns whether this call has
+    /// marked the level 0, which makes it the remove of the node.
+    pub fn mark_tower(&self, guard: &Guard) -> bool {
+        for level in (0..self.height).rev() {
+            let mut new_marker = None;
+            loop {
+                let succ = self.next[level].load(Ordering::SeqCst, guard);
+                if succ.tag() & 1 != 0 {
+                    // Somebody else has marked this level, and if it is the level 0, removed the
+                    // node.
+                    if let Some(m) = new_marker {
+                        drop(unsafe { m.into_owned() });
+                    }
+                    if level == 0 {
+                        return false;
+                    }
+                    break;
+                }
+                let m = new_marker.take().unwrap_or_else(|| {
+                    Owned::new(Marker {
+                        next: Atomic::null(),
+                    })
+                    .into_shared(guard)
+                });
+                // A level that is not built yet keeps its tag 2 in the marker.
+                unsafe { m.deref() }.next.store(succ, Ordering::Relaxed);
+                let link = Shared::from(m.as_raw() as *const Node<K, V>).with_tag(1);
+                if self.next[level]
+                    .compare_exchange(succ, link, Ordering::SeqCst, Ordering::SeqCst, guard)
+                    .is_ok()
+                {
+                    break;
+                }
+                new_marker = Some(m);
+            }
+        }
+        true
+    }
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Default,
+    V: Default,
+{
+    pub fn head() -> Self {
+        Self {
+            key: K::default(),
+            value: V::default(),
+            next: Default::default(),
+            height: MAX_HEIGHT,
+            refs: AtomicUsize::new(0),
+        }
+    }
+}
+
+struct Cursor<'g, K, V> {
+    found: Option<&'g Node<K, V>>,
+    preds: [&'g Tower<K, V>; MAX_HEIGHT],
+    succs: [Shared<'g, Node<K, V>>; MAX_HEIGHT],
+}
+
+impl<'g, K, V> Cursor<'g, K, V> {
+    fn new(head: &Atomic<Node<K, V>>, guard: &'g Guard) -> Self {
+        let head = head.load(Ordering::Relaxed, guard);
+        let next = &unsafe { head.deref() }.next;
+        Self {
+            found: None,
+            preds: [next; MAX_HEIGHT],
+            succs: [Shared::null(); MAX_HEIGHT],
+        }
+    }
+}
+
+pub struct MarkerSkipList<K, V> {
+    head: Atomic<Node<K, V>>,
+}
+
+impl<K, V> Drop for MarkerSkipList<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+            let mut node = self.head.load(Ordering::Relaxed, guard).into_owned().next[0]
+                .load(Ordering::Relaxed, guard);
+            while let Some(node_ref) = node.as_ref() {
+                let mut next = node_ref.next[0].load(Ordering::Relaxed, guard);
+                if next.tag() & 1 != 0 {
+                    next = marker(next).next.load(Ordering::Relaxed, guard);
+                }
+                for link in node_ref.next.iter().take(node_ref.height) {
+                    let link = link.load(Ordering::Relaxed, guard);
+                    if link.tag() & 1 != 0 {
+                        drop(Box::from_raw(marker(link) as *const _ as *mut Marker<K, V>));
+                    }
+                }
+                drop(node.into_owned());
+                node = next;
+            }
+        }
+    }
+}
+
+impl<K, V> Default for MarkerSkipList<K, V>
+where
+    K: Ord + Clone + Default,
+    V: Clone + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> MarkerSkipList<K, V>
+where
+    K: Ord + Clone + Default,
+    V: Clone + Default,
+{
+    pub fn new() -> Self {
+        Self {
+            head: Atomic::new(Node::head()),
+        }
+    }
+
+    fn find_optimistic<'g>(&'g self, key: &K, guard: &'g Guard) -> Cursor<'g, K, V> {
+        let mut cursor = Cursor::new(&self.head, guard);
+        let head = cursor.preds[0];
+
+        let mut level = MAX_HEIGHT;
+        while level >= 1 && head[level - 1].load(Ordering::Relaxed, guard).is_null() {
+            level -= 1;
+        }
+
+        let mut pred = head;
+        let mut curr = Shared::null();
+        while level >= 1 {
+            level -= 1;
+            curr = pred[level].load(Ordering::Acquire, guard);
+            if curr.tag() & 1 != 0 {
+                // `pred` has been removed, and its successor is behind the marker.
+                curr = marker(curr).next.load(Ordering::Acquire, guard);
+            }
+
+            loop {
+                let curr_node = some_or!(unsafe { curr.as_ref() }, break);
+                let succ = curr_node.next[level].load(Ordering::Acquire, guard);
+
+                if succ.tag() & 1 != 0 {
+                    curr = marker(succ).next.load(Ordering::Acquire, guard);
+                    continue;
+                }
+
+                if curr_node.key < *key {
+                    pred = &curr_node.next;
+                    curr = succ;
+                } else {
+                    break;
+                }
+            }
+        }
+
+        if let Some(curr_node) = unsafe { curr.as_ref() } {
+            if curr_node.key == *key {
+                cursor.found = Some(curr_node);
+            }
+        }
+        cursor
+    }
+
+    fn find<'g>(&'g self, key: &K, guard: &'g Guard) -> Cursor<'g, K, V> {
+        'search: loop {
+            let mut cursor = Cursor::new(&self.head, guard);
+            let head = cursor.preds[0];
+
+            let mut level = MAX_HEIGHT;
+            while level >= 1 && head[level - 1].load(Ordering::Relaxed, guard).is_null() {
+                level -= 1;
+            }
+
+            let mut pred = head;
+            while level >= 1 {
+                level -= 1;
+                let mut curr = pred[level].load_consume(guard);
+                // If `curr` is a marker, that means `pred` is removed and we have to restart the
+                // search.
+                if curr.tag() & 1 != 0 {
+                    continue 'search;
+                }
+
+                while let Some(curr_ref) = unsafe { curr.as_ref() } {
+                    let succ = curr_ref.next[level].load_consume(guard);
+
+                    if succ.tag() & 1 != 0 {
+                        let succ = marker(succ).next.load(Ordering::Acquire, guard);
+                        if self.help_unlink(&pred[level], curr, succ, guard) {
+                            curr = succ.with_tag(0);
+                            continue;
+                        } else {
+                            // On failure, we cannot do anything reasonable to continue
+                            // searching from the current position. Restart the search.
+                            continue 'search;
+                        }
+                    }
+
+                    // If `curr` contains a key that is greater than or equal to `key`, we're
+                    // done with this level.
+                    match curr_ref.key.cmp(key) {
+                        std::cmp::Ordering::Greater => break,
+                        std::cmp::Ordering::Equal => {
+                            cursor.found = Some(curr_ref);
+                            break;
+                        }
+                        std::cmp::Ordering::Less => {}
+                    }
+
+                    // Move one step forward.
+                    pred = &curr_ref.next;
+                    curr = succ;
+                }
+
+                cursor.preds[level] = pred;
+                cursor.succs[level] = curr;
+            }
+
+            return cursor;
+        }
+    }
+
+    /// Unlinks `curr`, whose marker holds `succ`, together with the marker.
+    fn help_unlink<'g>(
+        &'g self,
+        pred: &'g Atomic<Node<K, V>>,
+        curr: Shared<'g, Node<K, V>>,
+        succ: Shared<'g, Node<K, V>>,
+        guard: &'g Guard,
+    ) -> bool {
+        let success = pred
+            .compare_exchange(
+                curr.with_tag(0),
+                succ.with_tag(0),
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            )
+            .is_ok();
+
+        if success {
+            unsafe { curr.deref().decrement(guard) };
+        }
+        success
+    }
+
+    pub fn insert(&self, key: K, value: V, guard: &Guard) -> bool {
+        let mut cursor = self.find(&key, guard);
+        if cursor.found.is_some() {
+            return false;
+        }
+
+        // The reference count is initially two to account for
+        // 1. The link at the level 0 of the tower.
+        // 2. The current reference in this function.
+        let new_node = Owned::new(Node::new(key, value)).into_shared(guard);
+        let new_node_ref = unsafe { new_node.deref() };
+        let height = new_node_ref.height;
+
+        loop {
+            new_node_ref.next[0].store(cursor.succs[0], Ordering::Relaxed);
+
+            if cursor.preds[0][0]
+                .compare_exchange(
+                    cursor.succs[0],
+                    new_node,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                    guard,
+                )
+                .is_ok()
+            {
+                break;
+            }
+
+            // We failed. Let's search for the key and try again.
+            cursor = self.find(&new_node_ref.key, guard);
+            if cursor.found.is_some() {
+                drop(unsafe { new_node.into_owned() });
+                return false;
+            }
+        }
+
+        // The new node was successfully installed.
+        // Build the rest of the tower above level 0.
+        'build: for level in 1..height {
+            loop {
+                let pred = cursor.preds[level];
+                let succ = cursor.succs[level];
+                let next = new_node_ref.next[level].load(Ordering::SeqCst, guard);
+
+                // If the current level points to a marker, that means another thread is already
+                // removing the node we've just inserted. In that case, let's just stop building
+                // the tower.
+                if (next.tag() & 1) != 0 {
+                    new_node_ref
+                        .refs
+                        .fetch_sub(height - level, Ordering::SeqCst);
+                    break 'build;
+                }
+
+                if new_node_ref.next[level]
+                    .compare_exchange(
+                        Shared::null().with_tag(2),
+                        succ,
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                        guard,
+                    )
+                    .is_err()
+                {
+                    new_node_ref
+                        .refs
+                        .fetch_sub(height - level, Ordering::SeqCst);
+                    break 'build;
+                }
+
+                // Try installing the new node at the current level.
+                if pred[level]
+                    .compare_exchange(succ, new_node, Ordering::SeqCst, Ordering::SeqCst, guard)
+                    .is_ok()
+                {
+                    // Success! Continue on the next level.
+                    break;
+                }
+
+                // Installation failed.
+                cursor = self.find(&new_node_ref.key, guard);
+            }
+        }
+
+        new_node_ref.decrement(guard);
+        true
+    }
+
+    pub fn remove<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        loop {
+            let cursor = self.find(key, guard);
+            let node = cursor.found?;
+
+            // Try removing the node by marking its tower.
+            if node.mark_tower(guard) {
+                for level in (0..node.height).rev() {
+                    let link = node.next[level].load(Ordering::SeqCst, guard);
+                    let succ = marker(link).next.load(Ordering::SeqCst, guard);
+                    if (succ.tag() & 2) != 0 {
+                        continue;
+                    }
+
+                    // Try linking the predecessor and successor at this level.
+                    if cursor.preds[level][level]
+                        .compare_exchange(
+                            Shared::from(node as *const _),
+                            succ.with_tag(0),
+                            Ordering::SeqCst,
+                            Ordering::SeqCst,
+                            guard,
+                        )
+                        .is_ok()
+                    {
+                        node.decrement(guard);
+                    } else {
+                        self.find(key, guard);
+                        break;
+                    }
+                }
+                return Some(&node.value);
+            }
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for MarkerSkipList<K, V>
+where
+    K: Ord + Clone + Default,
+    V: Clone + Default,
+{
+    fn new() -> Self {
+        MarkerSkipList::new()
+    }
+
+    #[inline(always)]
+    fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
+        let cursor = self.find_optimistic(key, guard);
+        cursor.found.map(|node| &node.value)
+    }
+
+    #[inline(always)]
+    fn insert(&self, key: K, value: V, guard: &Guard) -> bool {
+        self.insert(key, value, guard)
+    }
+
+    #[inline(always)]
+    fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
+        self.remove(key, guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MarkerSkipList;
+    use crate::ds_impl::ebr::concurrent_map;
+
+    #[test]
+    fn smoke_marker_skip_list() {
+        concurrent_map::tests::smoke::<_, MarkerSkipList<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn edge_cases_marker_skip_list() {
+        concurrent_map::tests::edge_cases::<_, MarkerSkipList<i32, String>, _>(&i32::to_string);
+    }
+}
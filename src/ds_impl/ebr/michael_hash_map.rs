@@ -4,6 +4,7 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
 use super::list::HHSList;
+use super::slab::NodeAlloc;
 
 pub struct HashMap<K, V> {
     buckets: Vec<HHSList<K, V>>,
@@ -23,6 +24,13 @@ where
         HashMap { buckets }
     }
 
+    /// Creates a map of `n` buckets whose nodes are allocated from one slab of the map.
+    pub fn with_capacity_in_slab(n: usize) -> Self {
+        let alloc = NodeAlloc::slab();
+        let buckets = (0..n).map(|_| HHSList::with_alloc(alloc.clone())).collect();
+        HashMap { buckets }
+    }
+
     #[inline]
     pub fn get_bucket(&self, index: usize) -> &HHSList<K, V> {
         unsafe { self.buckets.get_unchecked(index % self.buckets.len()) }
@@ -49,6 +57,40 @@ where
         let i = Self::hash(k);
         self.get_bucket(i).remove(k, guard)
     }
+
+    pub fn get_or_insert(&self, k: K, v: V, guard: &Guard) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let i = Self::hash(&k);
+        self.get_bucket(i).get_or_insert(k, v, guard)
+    }
+
+    /// Looks up `keys` in a batch, and calls `found` with the index of each key and its value.
+    ///
+    /// The keys are sorted by their buckets and then by themselves, so that the keys of a bucket
+    /// are looked up with one traversal of the bucket, all in the critical section of `guard`.
+    pub fn multi_get<'g, F>(&'g self, keys: &[K], mut found: F, guard: &'g Guard)
+    where
+        F: FnMut(usize, Option<&'g V>),
+    {
+        let mut requests = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (Self::hash(k) % self.buckets.len(), (i, k)))
+            .collect::<Vec<_>>();
+        requests.sort_unstable_by(|(a, (_, ka)), (b, (_, kb))| a.cmp(b).then_with(|| ka.cmp(kb)));
+        let (buckets, requests): (Vec<_>, Vec<_>) = requests.into_iter().unzip();
+        let mut begin = 0;
+        while begin < requests.len() {
+            let bucket = buckets[begin];
+            let end = begin + buckets[begin..].partition_point(|b| *b == bucket);
+            self.get_bucket(bucket)
+                .get_sorted(&requests[begin..end], &mut found, guard);
+            begin = end;
+        }
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for HashMap<K, V>
@@ -60,6 +102,10 @@ where
         Self::with_capacity(30000)
     }
 
+    fn with_slab() -> Self {
+        Self::with_capacity_in_slab(30000)
+    }
+
     #[inline(always)]
     fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.get(key, guard)
@@ -72,6 +118,20 @@ where
     fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.remove(key, guard)
     }
+    #[inline(always)]
+    fn get_or_insert(&self, key: K, value: V, guard: &Guard) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.get_or_insert(key, value, guard)
+    }
+    #[inline(always)]
+    fn multi_get(&self, keys: &[K], guard: &Guard) -> usize {
+        let mut count = 0;
+        self.multi_get(keys, |_, value| count += value.is_some() as usize, guard);
+        count
+    }
 }
 
 #[cfg(test)]
@@ -83,4 +143,35 @@ mod tests {
     fn smoke_hashmap() {
         concurrent_map::tests::smoke::<_, HashMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_hashmap() {
+        concurrent_map::tests::edge_cases::<_, HashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn smoke_hashmap_slab() {
+        concurrent_map::tests::smoke_with(HashMap::with_capacity_in_slab(30000), &i32::to_string);
+    }
+
+    #[test]
+    fn get_or_insert_hashmap() {
+        concurrent_map::tests::get_or_insert::<HashMap<i32, i32>>();
+    }
+
+    #[test]
+    fn multi_get_hashmap() {
+        let map = HashMap::with_capacity(4);
+        let guard = &crossbeam_ebr::pin();
+        for key in (0..100).step_by(2) {
+            assert!(map.insert(key, key.to_string(), guard));
+        }
+        assert!(map.remove(&10, guard).is_some());
+        // Some keys share a bucket, and a key may be requested more than once.
+        let keys = [7, 4, 98, 4, 10, 99, 0, 12];
+        let mut values = vec![None; keys.len()];
+        map.multi_get(&keys, |i, value| values[i] = value.cloned(), guard);
+        let expected = keys.map(|key| (key % 2 == 0 && key != 10).then(|| key.to_string()));
+        assert_eq!(values, expected);
+    }
 }
@@ -0,0 +1,299 @@
+//! A hash map of `HHSList` buckets that can be migrated into a table with twice the buckets while
+//! the other operations continue, with the help of the updates that run into the migration.
+//!
+//! See `ds_impl::migration` for the protocol. The old table is retired with `defer_destroy` once
+//! the new table is installed, so its nodes are freed together when the readers that loaded it
+//! have all unpinned.
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::list::HHSList;
+use crate::ds_impl::migration::{Gate, Migration, Progress};
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned};
+use crossbeam_utils::Backoff;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+struct Bucket<K, V> {
+    gate: Gate,
+    list: HHSList<K, V>,
+}
+
+struct Table<K, V> {
+    buckets: Box<[Bucket<K, V>]>,
+    /// The table that this table is migrated into, if a migration started.
+    next: Atomic<Table<K, V>>,
+    progress: Progress,
+}
+
+impl<K, V> Table<K, V>
+where
+    K: Ord + Default,
+    V: Default,
+{
+    fn new(len: usize) -> Self {
+        Self {
+            buckets: (0..len)
+                .map(|_| Bucket {
+                    gate: Gate::default(),
+                    list: HHSList::new(),
+                })
+                .collect(),
+            next: Atomic::null(),
+            progress: Progress::default(),
+        }
+    }
+}
+
+impl<K, V> Table<K, V> {
+    /// The bucket of `hash`, whose keys split into the buckets of the same index and of the index
+    /// plus the old length in a table with twice the buckets.
+    #[inline]
+    fn bucket(&self, hash: u64) -> &Bucket<K, V> {
+        &self.buckets[hash as usize % self.buckets.len()]
+    }
+}
+
+pub struct MigratingHashMap<K, V> {
+    table: Atomic<Table<K, V>>,
+    /// Serializes the migrations, so that a table is migrated at most once.
+    migrate: Mutex<()>,
+}
+
+#[inline]
+fn hash_of<K: Hash>(k: &K) -> u64 {
+    let mut s = DefaultHasher::new();
+    k.hash(&mut s);
+    s.finish()
+}
+
+impl<K, V> MigratingHashMap<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    pub fn with_buckets(len: usize) -> Self {
+        assert!(len > 0, "A table must have a bucket");
+        Self {
+            table: Atomic::new(Table::new(len)),
+            migrate: Mutex::new(()),
+        }
+    }
+
+    /// The number of buckets of the current table.
+    pub fn buckets(&self, guard: &Guard) -> usize {
+        self.table(guard).buckets.len()
+    }
+
+    #[inline]
+    fn table<'g>(&self, guard: &'g Guard) -> &'g Table<K, V> {
+        unsafe { self.table.load(Ordering::Acquire, guard).deref() }
+    }
+
+    /// Enters the bucket of `hash` for an update, helping the migration of each table on the way
+    /// with a chunk, and waiting for the bucket to be migrated if it is frozen.
+    #[inline]
+    fn enter<'g>(&self, hash: u64, guard: &'g Guard) -> &'g Bucket<K, V> {
+        let backoff = Backoff::new();
+        let mut table = self.table(guard);
+        loop {
+            if !table.next.load(Ordering::Acquire, guard).is_null() {
+                self.help(table, guard);
+            }
+            let bucket = table.bucket(hash);
+            if bucket.gate.enter() {
+                return bucket;
+            }
+            if bucket.gate.is_migrated() {
+                table = unsafe { table.next.load(Ordering::Acquire, guard).deref() };
+                backoff.reset();
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// Migrates a chunk of the buckets of `table` into its next table, if any is left, and returns
+    /// the number of the migrated buckets. The helper of the last chunk installs the next table.
+    fn help(&self, table: &Table<K, V>, guard: &Guard) -> usize {
+        let len = table.buckets.len();
+        let Some(chunk) = table.progress.claim(len) else {
+            return 0;
+        };
+        let next = table.next.load(Ordering::Acquire, guard);
+        let next_ref = unsafe { next.deref() };
+        let mut copied = 0;
+        for bucket in &table.buckets[chunk.clone()] {
+            bucket.gate.freeze();
+            bucket.list.for_each(
+                |k, v| {
+                    let list = &next_ref.bucket(hash_of(k)).list;
+                    if list.insert(k.clone(), v.clone(), guard) {
+                        copied += 1;
+                    }
+                },
+                guard,
+            );
+            bucket.gate.finish();
+        }
+        if table.progress.finish(chunk.clone(), copied, len) {
+            let old = self.table.swap(next, Ordering::AcqRel, guard);
+            debug_assert_eq!(old.as_raw(), table as *const _);
+            unsafe { guard.defer_destroy(old) };
+        }
+        chunk.len()
+    }
+
+    /// Migrates all keys into a table with twice the buckets, with the help of the concurrent
+    /// updates, and returns once the new table is installed.
+    pub fn migrate(&self, guard: &Guard) -> Migration {
+        let _migrate = self.migrate.lock().unwrap();
+        let old = self.table.load(Ordering::Acquire, guard);
+        let table = unsafe { old.deref() };
+        let len = table.buckets.len();
+        table
+            .next
+            .store(Owned::new(Table::new(2 * len)), Ordering::Release);
+
+        let mut own = 0;
+        loop {
+            let migrated = self.help(table, guard);
+            if migrated == 0 {
+                break;
+            }
+            own += migrated;
+        }
+        // The last chunk may still be copied by a helper.
+        let backoff = Backoff::new();
+        while self.table.load(Ordering::Acquire, guard) == old {
+            backoff.snooze();
+        }
+        Migration {
+            buckets: len,
+            copied: table.progress.copied(),
+            helped: len - own,
+        }
+    }
+
+    pub fn get<'g>(&'g self, k: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V> + 'g> {
+        let hash = hash_of(k);
+        let mut table = self.table(guard);
+        loop {
+            let bucket = table.bucket(hash);
+            if !bucket.gate.is_migrated() {
+                return bucket.list.get(k, guard);
+            }
+            table = unsafe { table.next.load(Ordering::Acquire, guard).deref() };
+        }
+    }
+
+    pub fn insert(&self, k: K, v: V, guard: &Guard) -> bool {
+        let bucket = self.enter(hash_of(&k), guard);
+        let inserted = bucket.list.insert(k, v, guard);
+        bucket.gate.exit();
+        inserted
+    }
+
+    pub fn remove<'g>(&'g self, k: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V> + 'g> {
+        let bucket = self.enter(hash_of(k), guard);
+        let removed = bucket.list.remove(k, guard);
+        bucket.gate.exit();
+        removed
+    }
+}
+
+impl<K, V> Drop for MigratingHashMap<K, V> {
+    fn drop(&mut self) {
+        // A migration finishes before `migrate` returns, so there is no next table.
+        unsafe {
+            let table = self.table.load(Ordering::Relaxed, unprotected());
+            drop(table.into_owned());
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for MigratingHashMap<K, V>
+where
+    K: Ord + Hash + Default + Clone,
+    V: Default + Clone,
+{
+    fn new() -> Self {
+        Self::with_buckets(30000)
+    }
+
+    #[inline(always)]
+    fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
+        self.get(key, guard)
+    }
+    #[inline(always)]
+    fn insert(&self, key: K, value: V, guard: &Guard) -> bool {
+        self.insert(key, value, guard)
+    }
+    #[inline(always)]
+    fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
+        self.remove(key, guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MigratingHashMap;
+    use crate::ds_impl::ebr::concurrent_map::{self, OutputHolder};
+    use crossbeam_ebr::pin;
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn smoke_migrating_hash_map() {
+        concurrent_map::tests::smoke::<_, MigratingHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn edge_cases_migrating_hash_map() {
+        concurrent_map::tests::edge_cases::<_, MigratingHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn concurrent_migrations() {
+        const THREADS: i32 = 8;
+        const KEYS: i32 = 4096;
+        const BUCKETS: usize = 16;
+        const MIGRATIONS: u32 = 4;
+
+        let map = &MigratingHashMap::with_buckets(BUCKETS);
+        for key in 0..KEYS {
+            assert!(map.insert(key, key.to_string(), &pin()));
+        }
+        let done = &AtomicBool::new(false);
+        scope(|s| {
+            // Each thread removes and inserts back its own odd keys, and checks that the even keys
+            // are never missed.
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    while !done.load(Ordering::Relaxed) {
+                        for key in (2 * t + 1..KEYS).step_by(2 * THREADS as usize) {
+                            let guard = &pin();
+                            let removed = map.remove(&key, guard).unwrap();
+                            assert_eq!(*removed.output(), key.to_string());
+                            assert!(map.insert(key, key.to_string(), guard));
+                            assert!(map.get(&(key - 1), guard).is_some());
+                        }
+                    }
+                });
+            }
+            for i in 0..MIGRATIONS {
+                let migration = map.migrate(&pin());
+                assert_eq!(migration.buckets, BUCKETS << i);
+            }
+            done.store(true, Ordering::Relaxed);
+        })
+        .unwrap();
+
+        let guard = &pin();
+        assert_eq!(map.buckets(guard), BUCKETS << MIGRATIONS);
+        for key in 0..KEYS {
+            assert_eq!(*map.get(&key, guard).unwrap().output(), key.to_string());
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! A pair of maps with a composite `move` operation, which removes a key from one map and inserts
+//! it into the other without losing the key.
+//!
+//! A move runs in two phases. It first announces a descriptor of the move in the slot of the
+//! moving thread and removes the key from the source map. It then claims the descriptor and
+//! commits the move by inserting the key into the destination map. Between the two phases the key
+//! is in neither map, so a `get` that misses the key in both maps looks for a removed descriptor
+//! of the key and helps the move by claiming and committing it itself. Only the thread that claims
+//! a descriptor inserts its key, so a move is never committed twice.
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
+use crossbeam_utils::CachePadded;
+use std::hint::spin_loop;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// One of the two maps of a [`MovePair`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    A,
+    B,
+}
+
+impl Side {
+    pub fn other(self) -> Self {
+        match self {
+            Side::A => Side::B,
+            Side::B => Side::A,
+        }
+    }
+}
+
+/// The key may still be in the source map.
+const PREPARED: u8 = 0;
+/// The key is removed from the source map, and the move waits to be claimed.
+const REMOVED: u8 = 1;
+/// A thread is inserting the key into the destination map.
+const CLAIMED: u8 = 2;
+/// The key is in the destination map.
+const DONE: u8 = 3;
+
+struct Descriptor<K, V> {
+    key: K,
+    /// The value removed from the source map. Set before the state becomes `REMOVED`.
+    value: OnceLock<V>,
+    to: Side,
+    state: AtomicU8,
+}
+
+pub struct MovePair<K, V, M> {
+    a: M,
+    b: M,
+    /// The descriptor of the ongoing move of each thread.
+    slots: Box<[CachePadded<Atomic<Descriptor<K, V>>>]>,
+}
+
+impl<K, V, M> MovePair<K, V, M>
+where
+    K: Eq + Clone,
+    V: Clone,
+    M: ConcurrentMap<K, V>,
+{
+    /// Creates a pair of empty maps for `threads` moving threads.
+    pub fn new(threads: usize) -> Self {
+        Self {
+            a: M::new(),
+            b: M::new(),
+            slots: (0..threads)
+                .map(|_| CachePadded::new(Atomic::null()))
+                .collect(),
+        }
+    }
+
+    fn map(&self, side: Side) -> &M {
+        match side {
+            Side::A => &self.a,
+            Side::B => &self.b,
+        }
+    }
+
+    /// Inserts a key into one of the maps. The key must not be in the other map.
+    pub fn insert(&self, side: Side, key: K, value: V, guard: &Guard) -> bool {
+        self.map(side).insert(key, value, guard)
+    }
+
+    /// Returns the value of `key` and the map that holds it.
+    pub fn get(&self, key: &K, guard: &Guard) -> Option<(Side, V)> {
+        for side in [Side::A, Side::B] {
+            if let Some(value) = self.map(side).get(key, guard) {
+                return Some((side, value.output().clone()));
+            }
+        }
+        // The key may be moving between the maps.
+        let side = self.help(key, guard)?;
+        self.map(side)
+            .get(key, guard)
+            .map(|value| (side, value.output().clone()))
+    }
+
+    /// Moves `key` from the map on `from` to the other one, with the slot of the thread `tid`.
+    ///
+    /// Returns `false` if the key is not in the map on `from`.
+    pub fn move_key(&self, tid: usize, key: &K, from: Side, guard: &Guard) -> bool {
+        let slot = &self.slots[tid];
+        let desc = Owned::new(Descriptor {
+            key: key.clone(),
+            value: OnceLock::new(),
+            to: from.other(),
+            state: AtomicU8::new(PREPARED),
+        })
+        .into_shared(guard);
+        slot.store(desc, Ordering::Release);
+        let desc_ref = unsafe { desc.deref() };
+
+        let Some(value) = self
+            .map(from)
+            .remove(key, guard)
+            .map(|v| v.output().clone())
+        else {
+            slot.store(Shared::null(), Ordering::Release);
+            // Other threads may have loaded the descriptor, but they only act on removed ones.
+            unsafe { guard.defer_destroy(desc) };
+            return false;
+        };
+        let _ = desc_ref.value.set(value);
+        desc_ref.state.store(REMOVED, Ordering::Release);
+
+        if desc_ref
+            .state
+            .compare_exchange(REMOVED, CLAIMED, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            self.commit(desc_ref, guard);
+        } else {
+            // A helper claimed the move; wait until it inserts the key.
+            while desc_ref.state.load(Ordering::Acquire) != DONE {
+                spin_loop();
+            }
+        }
+        slot.store(Shared::null(), Ordering::Release);
+        unsafe { guard.defer_destroy(desc) };
+        true
+    }
+
+    fn commit(&self, desc: &Descriptor<K, V>, guard: &Guard) {
+        let value = desc.value.get().unwrap().clone();
+        let inserted = self.map(desc.to).insert(desc.key.clone(), value, guard);
+        // The key was in neither map since it was removed, and only the claimer inserts it.
+        debug_assert!(inserted);
+        desc.state.store(DONE, Ordering::Release);
+    }
+
+    /// Completes an ongoing move of `key`, if any, and returns its destination.
+    fn help(&self, key: &K, guard: &Guard) -> Option<Side> {
+        for slot in self.slots.iter() {
+            let desc = slot.load(Ordering::Acquire, guard);
+            let Some(desc_ref) = (unsafe { desc.as_ref() }) else {
+                continue;
+            };
+            if desc_ref.key != *key {
+                continue;
+            }
+            match desc_ref.state.load(Ordering::Acquire) {
+                PREPARED => continue,
+                REMOVED => {
+                    if desc_ref
+                        .state
+                        .compare_exchange(REMOVED, CLAIMED, Ordering::AcqRel, Ordering::Acquire)
+                        .is_ok()
+                    {
+                        self.commit(desc_ref, guard);
+                    }
+                }
+                _ => {}
+            }
+            while desc_ref.state.load(Ordering::Acquire) != DONE {
+                spin_loop();
+            }
+            return Some(desc_ref.to);
+        }
+        None
+    }
+
+    /// Returns the number of maps that hold `key`.
+    ///
+    /// Meaningful only while no thread is moving keys, when every key is in at most one map.
+    pub fn copies(&self, key: &K, guard: &Guard) -> usize {
+        [Side::A, Side::B]
+            .into_iter()
+            .filter(|side| self.map(*side).get(key, guard).is_some())
+            .count()
+    }
+}
+
+impl<K, V, M> Drop for MovePair<K, V, M> {
+    fn drop(&mut self) {
+        // A thread clears its slot at the end of every move, but a panicking one may not have.
+        for slot in self.slots.iter() {
+            let desc = slot.swap(Shared::null(), Ordering::Relaxed, unsafe { unprotected() });
+            if !desc.is_null() {
+                drop(unsafe { desc.into_owned() });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MovePair, Side};
+    use crate::ds_impl::ebr::HashMap;
+    use crossbeam_ebr::pin;
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
+
+    const THREADS: usize = 30;
+    const ELEMENTS: i32 = 1000;
+    const MOVES_PER_THREADS: usize = 10000;
+
+    #[test]
+    fn smoke_move_pair() {
+        let pair = &MovePair::<i32, String, HashMap<i32, String>>::new(THREADS);
+        for i in 0..ELEMENTS {
+            assert!(pair.insert(Side::A, i, i.to_string(), &pin()));
+        }
+
+        thread::scope(|s| {
+            for tid in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..MOVES_PER_THREADS {
+                        let key = rng.gen_range(0..ELEMENTS);
+                        let from = if rng.gen() { Side::A } else { Side::B };
+                        pair.move_key(tid, &key, from, &pin());
+                        // A `get` may miss a key that is moved twice in the meantime.
+                        if let Some((_, value)) = pair.get(&key, &pin()) {
+                            assert_eq!(key.to_string(), value);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        for i in 0..ELEMENTS {
+            assert_eq!(pair.copies(&i, &pin()), 1);
+        }
+    }
+}
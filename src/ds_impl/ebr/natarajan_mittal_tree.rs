@@ -1,16 +1,24 @@
 use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
 
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use std::cmp;
+use std::fmt::Debug;
 use std::sync::atomic::Ordering;
+use std::sync::Mutex;
 
 bitflags! {
     /// TODO
     /// A remove operation is registered by marking the corresponding edges: the (parent, target)
     /// edge is _flagged_ and the (parent, sibling) edge is _tagged_.
+    ///
+    /// A range removal _freezes_ the edges of the subtree that it detaches, so that no insert,
+    /// remove, or cleanup can change the subtree until it is unlinked.
     struct Marks: usize {
         const FLAG = 1usize.wrapping_shl(1);
         const TAG  = 1usize.wrapping_shl(0);
+        const FROZEN = 1usize.wrapping_shl(2);
     }
 }
 
@@ -27,6 +35,10 @@ impl Marks {
     fn tag(self) -> bool {
         !(self & Marks::TAG).is_empty()
     }
+
+    fn frozen(self) -> bool {
+        !(self & Marks::FROZEN).is_empty()
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -86,7 +98,7 @@ where
 }
 
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: Key<K>,
     value: Option<V>,
     left: Atomic<Node<K, V>>,
@@ -167,6 +179,9 @@ impl<'g, K, V> SeekRecord<'g, K, V> {
 
 pub struct NMTreeMap<K, V> {
     r: Atomic<Node<K, V>>,
+    /// Serializes the range removals, so that a frozen edge that a range removal comes across is
+    /// always of a subtree that an earlier one has detached.
+    range_lock: Mutex<()>,
 }
 
 impl<K, V> Default for NMTreeMap<K, V>
@@ -222,11 +237,25 @@ where
         let inf2 = Node::new_leaf(Key::Inf, None);
         let s = Node::new_internal(inf0, inf1);
         let r = Node::new_internal(s, inf2);
-        NMTreeMap { r: Atomic::new(r) }
+        NMTreeMap {
+            r: Atomic::new(r),
+            range_lock: Mutex::new(()),
+        }
     }
 
     // All `Shared<_>` fields are unmarked.
+    #[inline]
     fn seek<'g>(&'g self, key: &K, guard: &'g Guard) -> SeekRecord<'g, K, V> {
+        self.seek_to(key, Shared::null(), guard)
+    }
+
+    /// Similar to `seek`, but stops early if the access path of `key` reaches `target`.
+    fn seek_to<'g>(
+        &'g self,
+        key: &K,
+        target: Shared<'g, Node<K, V>>,
+        guard: &'g Guard,
+    ) -> SeekRecord<'g, K, V> {
         let r = self.r.load(Ordering::Relaxed, guard);
         let s = unsafe { r.deref() }.left.load(Ordering::Relaxed, guard);
         let s_node = unsafe { s.deref() };
@@ -250,6 +279,9 @@ where
         let mut curr = leaf_node.left.load(Ordering::Relaxed, guard);
 
         while let Some(curr_node) = unsafe { curr.as_ref() } {
+            if record.leaf == target {
+                break;
+            }
             if !prev_tag {
                 // untagged edge: advance ancestor and successor pointers
                 record.ancestor = record.parent;
@@ -311,6 +343,82 @@ where
         record
     }
 
+    /// Similar to `seek`, but ends at the highest node on or to the right of the access path of
+    /// `lo` whose keys all fall within `lo..hi`, or at the leaf of the access path if there is
+    /// none.
+    fn seek_range<'g>(&'g self, lo: &K, hi: &K, guard: &'g Guard) -> SeekRecord<'g, K, V> {
+        let r = self.r.load(Ordering::Relaxed, guard);
+        let s = unsafe { r.deref() }.left.load(Ordering::Relaxed, guard);
+        let s_node = unsafe { s.deref() };
+        let leaf = s_node.left.load(Ordering::Acquire, guard);
+
+        let mut record = SeekRecord {
+            ancestor: r,
+            successor: s,
+            successor_dir: Direction::L,
+            parent: s,
+            leaf: leaf.with_tag(Marks::empty().bits()),
+            leaf_dir: Direction::L,
+        };
+        let mut prev_tag = Marks::from_bits_truncate(leaf.tag()).tag();
+
+        // The keys under `record.leaf` are at least `lower`, if any, and less than `upper`.
+        let mut lower = None;
+        let mut upper = &s_node.key;
+        loop {
+            let leaf_node = unsafe { record.leaf.deref() };
+            let below_hi = upper.cmp(hi) != cmp::Ordering::Greater;
+            if below_hi && lower.is_some_and(|lower: &Key<K>| lower.cmp(lo) != cmp::Ordering::Less)
+            {
+                break;
+            }
+
+            let (curr, curr_dir, found) = if leaf_node.key.cmp(lo) == cmp::Ordering::Greater {
+                // The keys of the right subtree are at least the key of the node, which is
+                // greater than `lo`.
+                if below_hi {
+                    (
+                        leaf_node.right.load(Ordering::Acquire, guard),
+                        Direction::R,
+                        true,
+                    )
+                } else {
+                    upper = &leaf_node.key;
+                    (
+                        leaf_node.left.load(Ordering::Acquire, guard),
+                        Direction::L,
+                        false,
+                    )
+                }
+            } else {
+                lower = Some(&leaf_node.key);
+                (
+                    leaf_node.right.load(Ordering::Acquire, guard),
+                    Direction::R,
+                    false,
+                )
+            };
+            if curr.is_null() {
+                break;
+            }
+
+            if !prev_tag {
+                record.ancestor = record.parent;
+                record.successor = record.leaf;
+                record.successor_dir = record.leaf_dir;
+            }
+            record.parent = record.leaf;
+            record.leaf = curr.with_tag(Marks::empty().bits());
+            record.leaf_dir = curr_dir;
+            prev_tag = Marks::from_bits_truncate(curr.tag()).tag();
+            if found {
+                break;
+            }
+        }
+
+        record
+    }
+
     /// Physically removes node.
     ///
     /// Returns true if it successfully unlinks the flagged node in `record`.
@@ -318,6 +426,18 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire, guard);
         let leaf_flag = Marks::from_bits_truncate(leaf_marked.tag()).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag
+                || Marks::from_bits_truncate(
+                    record
+                        .leaf_sibling_addr()
+                        .load(Ordering::Acquire, guard)
+                        .tag()
+                )
+                .flag(),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -326,14 +446,45 @@ where
 
         // NOTE: the ibr implementation uses CAS
         // tag (parent, sibling) edge -> all of the parent's edges can't change now
-        // TODO: Is Release enough?
-        target_sibling_addr.fetch_or(Marks::TAG.bits(), Ordering::AcqRel, guard);
+        // A frozen edge is not tagged, so that the range removal that froze it sees every cleanup
+        // in its subtree. The cleanup waits for the subtree to be unlinked or unfrozen.
+        let mut target_sibling = target_sibling_addr.load(Ordering::Acquire, guard);
+        loop {
+            let marks = Marks::from_bits_truncate(target_sibling.tag());
+            if marks.frozen() {
+                return false;
+            }
+            if marks.tag() {
+                break;
+            }
+            match target_sibling_addr.compare_exchange(
+                target_sibling,
+                target_sibling.with_tag((marks | Marks::TAG).bits()),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                guard,
+            ) {
+                Ok(_) => break,
+                Err(e) => target_sibling = e.current,
+            }
+        }
 
         // Try to replace (ancestor, successor) w/ (ancestor, sibling).
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load(Ordering::Acquire, guard);
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(target_sibling.tag()).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(target_sibling.tag()).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            record.successor.tag(),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         let is_unlinked = record
             .successor_addr()
             .compare_exchange(
@@ -343,7 +494,8 @@ where
                 Ordering::Acquire,
                 guard,
             )
-            .is_ok();
+            .classify(&record.successor)
+            .is_success();
 
         if is_unlinked {
             unsafe {
@@ -381,6 +533,41 @@ where
         Some(leaf_node.value.as_ref().unwrap())
     }
 
+    /// Returns the smallest key and its value, at the end of the leftmost path.
+    pub fn min<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.seek_end(Direction::L, guard)
+    }
+
+    /// Returns the largest key and its value.
+    pub fn max<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.seek_end(Direction::R, guard)
+    }
+
+    /// Descends from the subtree of the finite keys to its leftmost or rightmost finite leaf,
+    /// without marking anything, like `seek_leaf`. The finite keys under an internal node with an
+    /// infinite key are all in its left subtree, so going right turns left at such a node.
+    fn seek_end<'g>(&'g self, dir: Direction, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        let r = self.r.load(Ordering::Relaxed, guard);
+        let s = unsafe { r.deref() }.left.load(Ordering::Relaxed, guard);
+        let mut leaf = unsafe { s.deref() }.left.load(Ordering::Acquire, guard);
+        let leaf_node = loop {
+            let node = unsafe { leaf.with_tag(Marks::empty().bits()).deref() };
+            let next = match (&dir, &node.key) {
+                (Direction::R, Key::Fin(_)) => node.right.load(Ordering::Acquire, guard),
+                _ => node.left.load(Ordering::Acquire, guard),
+            };
+            if next.is_null() {
+                break node;
+            }
+            leaf = next;
+        };
+
+        match &leaf_node.key {
+            Key::Fin(key) => Some((key, leaf_node.value.as_ref().unwrap())),
+            Key::Inf => None,
+        }
+    }
+
     pub fn insert(&self, key: K, value: V, guard: &Guard) -> Result<(), (K, V)> {
         let mut new_leaf = Owned::new(Node::new_leaf(Key::Fin(key.clone()), Some(value)))
             .into_shared(unsafe { unprotected() });
@@ -415,21 +602,26 @@ where
             new_internal_node.right.store(new_right, Ordering::Relaxed);
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match record.leaf_addr().compare_exchange(
-                record.leaf,
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                guard,
-            ) {
-                Ok(_) => return Ok(()),
-                Err(e) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if e.current.with_tag(Marks::empty().bits()) == record.leaf {
-                        self.cleanup(&record, guard);
-                    }
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf,
+                    new_internal,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    guard,
+                )
+                .classify(&record.leaf)
+            {
+                Cas::Success(_) => return Ok(()),
+                // Insertion failed. Help the conflicting remove operation if needed.
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                // An edge frozen by a range removal is retried until the removal is done.
+                Cas::TagDiffers(e) if !Marks::from_bits_truncate(e.current.tag()).frozen() => {
+                    InsertConflict::Help.count();
+                    self.cleanup(&record, guard);
                 }
+                Cas::TagDiffers(_) | Cas::CurrentDiffers(_) => InsertConflict::Retry.count(),
             }
         }
     }
@@ -453,14 +645,19 @@ where
             let value = leaf_node.value.as_ref().unwrap();
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_exchange(
-                record.leaf,
-                record.leaf.with_tag(Marks::new(true, false).bits()),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                guard,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(record.leaf.tag(), 0, "the leaf edge is expected unmarked");
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf,
+                    record.leaf.with_tag(Marks::new(true, false).bits()),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    guard,
+                )
+                .classify(&record.leaf)
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(&record, guard) {
                         return Some(value);
@@ -468,15 +665,14 @@ where
                     // In-place cleanup failed. Enter the cleanup phase.
                     break (leaf, value);
                 }
-                Err(e) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if record.leaf == e.current.with_tag(Marks::empty().bits()) {
-                        self.cleanup(&record, guard);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(e) if !Marks::from_bits_truncate(e.current.tag()).frozen() => {
+                    self.cleanup(&record, guard);
                 }
+                Cas::TagDiffers(_) | Cas::CurrentDiffers(_) => {}
             }
         };
 
@@ -494,6 +690,233 @@ where
             }
         }
     }
+
+    /// Returns the smallest key that is greater than `key`, or at least `key` with `inclusive`.
+    fn next_key(&self, key: &K, inclusive: bool, guard: &Guard) -> Option<K> {
+        let mut from = key.clone();
+        let mut inclusive = inclusive;
+        loop {
+            let r = self.r.load(Ordering::Relaxed, guard);
+            let s = unsafe { r.deref() }.left.load(Ordering::Relaxed, guard);
+            let mut curr = unsafe { s.deref() }.left.load(Ordering::Acquire, guard);
+            // The key of the last node where the path turned left.
+            let mut bound = None;
+            let leaf_node = loop {
+                let node = unsafe { curr.with_tag(Marks::empty().bits()).deref() };
+                let next = if node.key.cmp(&from) == cmp::Ordering::Greater {
+                    bound = Some(&node.key);
+                    node.left.load(Ordering::Acquire, guard)
+                } else {
+                    node.right.load(Ordering::Acquire, guard)
+                };
+                if next.is_null() {
+                    break node;
+                }
+                curr = next;
+            };
+
+            match leaf_node.key.cmp(&from) {
+                cmp::Ordering::Greater => {
+                    return match &leaf_node.key {
+                        Key::Fin(key) => Some(key.clone()),
+                        Key::Inf => None,
+                    }
+                }
+                cmp::Ordering::Equal if inclusive => return Some(from),
+                _ => {}
+            }
+            // The leaf is the largest key up to `from`, so the next key is the smallest one of the
+            // right subtree of the last node where the path turned left.
+            match bound {
+                Some(Key::Fin(bound)) => {
+                    from = bound.clone();
+                    inclusive = true;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    /// Removes the keys in `lo..hi`, and returns the number of them that are removed.
+    ///
+    /// From the smallest key of the range up, each key is removed with the subtree that
+    /// `remove_subtree` detaches on or to the right of its access path, or on its own if that
+    /// subtree does not hold it. Each key is found by a seek for the next key after the last one,
+    /// so a key that is inserted again behind the removals stays in the tree, and the removals
+    /// always end.
+    pub fn remove_range(&self, lo: &K, hi: &K, guard: &Guard) -> usize {
+        let mut removed = 0;
+        let mut next = self.next_key(lo, true, guard);
+        while let Some(key) = next.take().filter(|key| key < hi) {
+            removed += self.remove_subtree(&key, hi, guard);
+            if self.remove(&key, guard).is_some() {
+                removed += 1;
+            }
+            next = self.next_key(&key, false, guard);
+        }
+        removed
+    }
+
+    /// Removes the highest subtree on or to the right of the access path of `lo` whose keys all
+    /// fall within `lo..hi`, and returns the number of keys that it held.
+    ///
+    /// The subtree is detached at once by flagging the edge to its root, which retires all of its
+    /// nodes in one operation, so a call removes the whole range only if the range happens to
+    /// match a subtree. The edges of the subtree are frozen beforehand, and the inserts and
+    /// removes in the subtree retry until it is unlinked, instead of being lost with it. They
+    /// block on this call in the meantime, which is serialized with the other range removals.
+    pub fn remove_subtree(&self, lo: &K, hi: &K, guard: &Guard) -> usize {
+        let _lock = self.range_lock.lock().unwrap();
+        let mut frozen = Vec::new();
+
+        // injection phase
+        let (target, keys) = loop {
+            let record = self.seek_range(lo, hi, guard);
+            let target = record.leaf;
+            let target_node = unsafe { target.deref() };
+
+            if target_node.left.load(Ordering::Relaxed, guard).is_null() {
+                // No subtree is within the range, but the leaf of `lo` may be.
+                let removed = match &target_node.key {
+                    Key::Fin(key) if key >= lo && key < hi => self.remove(key, guard).is_some(),
+                    _ => false,
+                };
+                return removed as usize;
+            }
+
+            let Some(keys) = self.freeze(target, &mut frozen, guard) else {
+                Self::unfreeze(&mut frozen, guard);
+                continue;
+            };
+
+            debug_assert_eq!(
+                target.tag(),
+                0,
+                "the edge to the subtree is expected unmarked"
+            );
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    target,
+                    target.with_tag(Marks::new(true, false).bits()),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    guard,
+                )
+                .classify(&target)
+            {
+                Cas::Success(_) => {
+                    if self.cleanup(&record, guard) {
+                        return keys;
+                    }
+                    break (target, keys);
+                }
+                failure => {
+                    // The subtree may have moved or been removed. Unfreeze it and restart.
+                    Self::unfreeze(&mut frozen, guard);
+                    if let Cas::TagDiffers(e) = failure {
+                        if !Marks::from_bits_truncate(e.current.tag()).frozen() {
+                            self.cleanup(&record, guard);
+                        }
+                    }
+                }
+            }
+        };
+
+        // cleanup phase
+        let target_key = match &unsafe { target.deref() }.key {
+            Key::Fin(key) => key,
+            Key::Inf => unreachable!("a subtree within a range has finite keys"),
+        };
+        loop {
+            // The position of the subtree follows the path of its key while it is in the tree.
+            let record = self.seek_to(target_key, target, guard);
+            if record.leaf != target || self.cleanup(&record, guard) {
+                return keys;
+            }
+        }
+    }
+
+    /// Freezes the edges under the internal node `root` top-down, and pushes them to `frozen`.
+    ///
+    /// Returns the number of keys under `root` that are not being removed, or `None` if a
+    /// removal is being cleaned up under `root`, or if `root` has already been detached.
+    fn freeze<'g>(
+        &'g self,
+        root: Shared<'g, Node<K, V>>,
+        frozen: &mut Vec<&'g Atomic<Node<K, V>>>,
+        guard: &'g Guard,
+    ) -> Option<usize> {
+        let root_node = unsafe { root.deref() };
+        let mut stack = vec![&root_node.left, &root_node.right];
+        let mut keys = 0;
+
+        while let Some(edge) = stack.pop() {
+            let child = edge.fetch_or(Marks::FROZEN.bits(), Ordering::AcqRel, guard);
+            let marks = Marks::from_bits_truncate(child.tag());
+            if marks.frozen() {
+                // Frozen by an earlier range removal, which has detached it.
+                return None;
+            }
+            frozen.push(edge);
+            if marks.tag() {
+                // The cleanup needs an unfrozen edge to finish.
+                return None;
+            }
+
+            let child_node = unsafe { child.deref() };
+            if child_node.left.load(Ordering::Acquire, guard).is_null() {
+                // A flagged leaf is being removed on its own.
+                if !marks.flag() && matches!(child_node.key, Key::Fin(_)) {
+                    keys += 1;
+                }
+            } else {
+                stack.push(&child_node.left);
+                stack.push(&child_node.right);
+            }
+        }
+
+        Some(keys)
+    }
+
+    /// Unfreezes the edges that `freeze` has frozen.
+    fn unfreeze(frozen: &mut Vec<&Atomic<Node<K, V>>>, guard: &Guard) {
+        for edge in frozen.drain(..) {
+            edge.fetch_and(!Marks::FROZEN.bits(), Ordering::AcqRel, guard);
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> NMTreeMap<K, V> {
+    /// Prints the shape of the tree to stderr, one node per line and indented by its depth.
+    /// The marks of the edge to a node are printed after it: `F` for a flagged edge and `T` for
+    /// a tagged one.
+    #[cfg(debug_assertions)]
+    pub fn dump(&self, guard: &Guard) {
+        use std::io::Write;
+
+        let mut out = std::io::stderr().lock();
+        let mut stack = vec![(0, self.r.load(Ordering::Acquire, guard))];
+        while let Some((depth, edge)) = stack.pop() {
+            let Some(node) = (unsafe { edge.as_ref() }) else {
+                continue;
+            };
+            let marks = Marks::from_bits_truncate(edge.tag());
+            let _ = write!(out, "{:indent$}{:?}", "", node.key, indent = 2 * depth);
+            if let Some(value) = &node.value {
+                let _ = write!(out, ": {:?}", value);
+            }
+            let _ = writeln!(
+                out,
+                "{}{}",
+                if marks.flag() { " F" } else { "" },
+                if marks.tag() { " T" } else { "" }
+            );
+            // Prints the left subtree first.
+            stack.push((depth + 1, node.right.load(Ordering::Acquire, guard)));
+            stack.push((depth + 1, node.left.load(Ordering::Acquire, guard)));
+        }
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for NMTreeMap<K, V>
@@ -517,15 +940,148 @@ where
     fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
         self.remove(key, guard)
     }
+    #[inline(always)]
+    fn remove_range(&self, lo: &K, hi: &K, guard: &Guard) -> usize {
+        self.remove_range(lo, hi, guard)
+    }
+    #[inline(always)]
+    fn min<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.min(guard)
+    }
+    #[inline(always)]
+    fn max<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.max(guard)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::NMTreeMap;
     use crate::ds_impl::ebr::concurrent_map;
+    use crossbeam_ebr::pin;
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
 
     #[test]
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn min_max_nm_tree() {
+        concurrent_map::tests::min_max::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn extract_min_nm_tree() {
+        concurrent_map::tests::extract_min::<NMTreeMap<i32, i32>>();
+    }
+
+    /// Contends on the few edges of a small tree, so that most removals help or are helped by
+    /// another one, which exercises the debug assertions on the marks in `cleanup` and `remove`.
+    #[test]
+    fn stress_nm_tree_cleanup() {
+        const THREADS: usize = 16;
+        const KEYS: i32 = 8;
+        const OPS_PER_THREAD: usize = 20000;
+
+        let map = &NMTreeMap::<i32, i32>::new();
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..OPS_PER_THREAD {
+                        let key = rng.gen_range(0..KEYS);
+                        let guard = &pin();
+                        if rng.gen() {
+                            let _ = map.insert(key, key, guard);
+                        } else if let Some(value) = map.remove(&key, guard) {
+                            assert_eq!(*value, key);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let guard = &pin();
+        for key in 0..KEYS {
+            if map.get(&key, guard).is_some() {
+                assert_eq!(map.remove(&key, guard), Some(&key));
+            }
+            assert!(map.get(&key, guard).is_none());
+        }
+    }
+
+    #[test]
+    fn remove_range_nm_tree() {
+        concurrent_map::tests::remove_range::<NMTreeMap<i32, i32>>();
+    }
+
+    /// Removes subtrees of a range while other threads insert and remove around and in it, and
+    /// checks that the keys outside the range that are only inserted are never lost.
+    #[test]
+    fn remove_subtree_nm_tree() {
+        const THREADS: usize = 8;
+        const KEYS: i32 = 1024;
+        const OPS_PER_THREAD: usize = 20000;
+        let (lo, hi) = (KEYS / 4, 3 * KEYS / 4);
+
+        let map = &NMTreeMap::<i32, i32>::new();
+        let guard = &pin();
+        let mut keys = (0..KEYS).collect::<Vec<_>>();
+        keys.shuffle(&mut rand::thread_rng());
+        for &key in &keys {
+            assert!(map.insert(key, key, guard).is_ok());
+        }
+        let mut removed = 0;
+        loop {
+            let keys = map.remove_subtree(&lo, &hi, guard);
+            if keys == 0 {
+                break;
+            }
+            removed += keys;
+        }
+        assert!(removed > 0);
+        let remaining = (lo..hi).filter(|key| map.get(key, guard).is_some()).count();
+        assert_eq!(removed + remaining, (hi - lo) as usize);
+        for key in (0..lo).chain(hi..KEYS) {
+            assert_eq!(map.get(&key, guard), Some(&key));
+        }
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                for _ in 0..OPS_PER_THREAD / 10 {
+                    map.remove_subtree(&lo, &hi, &pin());
+                }
+            });
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..OPS_PER_THREAD {
+                        let key = rng.gen_range(0..KEYS);
+                        let guard = &pin();
+                        if (lo..hi).contains(&key) && rng.gen() {
+                            if let Some(value) = map.remove(&key, guard) {
+                                assert_eq!(*value, key);
+                            }
+                        } else {
+                            let _ = map.insert(key, key, guard);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let guard = &pin();
+        for key in (0..lo).chain(hi..KEYS) {
+            assert_eq!(map.get(&key, guard), Some(&key));
+        }
+    }
 }
@@ -0,0 +1,280 @@
+//! Michael's hash map whose buckets persist their links, as on persistent memory.
+//!
+//! The buckets are Harris-Michael lists that follow the link-and-persist discipline of the
+//! log-free structures (David et al., USENIX ATC '18): a new node is written back before it is
+//! linked, and every link that an operation changes, i.e., the link to an inserted node, the
+//! mark of a removed node and the link past an unlinked node, is written back and fenced before
+//! the operation returns. A `get` writes back and fences the link that its answer depends on, so
+//! that it never returns a change that a crash could still undo. The write-backs and fences go
+//! through [`persist`], which counts them and executes them only if asked to.
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::persist;
+use crate::ds_impl::retry::Retry;
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
+
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::Ordering;
+
+struct Node<K, V> {
+    /// Mark: tag()
+    next: Atomic<Node<K, V>>,
+    key: K,
+    value: V,
+}
+
+struct Bucket<K, V> {
+    head: Atomic<Node<K, V>>,
+}
+
+impl<K, V> Drop for Bucket<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut curr = self.head.load(Ordering::Relaxed, unprotected());
+            while !curr.is_null() {
+                let next = curr.deref().next.load(Ordering::Relaxed, unprotected());
+                drop(curr.into_owned());
+                curr = next;
+            }
+        }
+    }
+}
+
+struct Cursor<'g, K, V> {
+    prev: &'g Atomic<Node<K, V>>,
+    curr: Shared<'g, Node<K, V>>,
+}
+
+impl<K: Ord, V> Bucket<K, V> {
+    fn new() -> Self {
+        Self {
+            head: Atomic::null(),
+        }
+    }
+
+    /// Finds the first node whose key is not less than `key`, and unlinks each logically removed
+    /// node on the way, writing the link past it back.
+    fn find<'g>(&'g self, key: &K, guard: &'g Guard) -> Result<(bool, Cursor<'g, K, V>), Retry> {
+        let mut cursor = Cursor {
+            prev: &self.head,
+            curr: self.head.load(Ordering::Acquire, guard),
+        };
+        loop {
+            let Some(curr_node) = (unsafe { cursor.curr.as_ref() }) else {
+                return Ok((false, cursor));
+            };
+            let next = curr_node.next.load(Ordering::Acquire, guard);
+            if next.tag() != 0 {
+                let next = next.with_tag(0);
+                cursor
+                    .prev
+                    .compare_exchange(
+                        cursor.curr,
+                        next,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                        guard,
+                    )
+                    .map_err(|_| Retry::Unlink)?;
+                // The mark of the node is already durable, so the unlink needs no fence of its
+                // own: it is ordered by the next fence of the thread.
+                persist::flush(cursor.prev);
+                unsafe { guard.defer_destroy(cursor.curr) };
+                cursor.curr = next;
+                continue;
+            }
+            match curr_node.key.cmp(key) {
+                Less => {
+                    cursor.prev = &curr_node.next;
+                    cursor.curr = next;
+                }
+                Equal => return Ok((true, cursor)),
+                Greater => return Ok((false, cursor)),
+            }
+        }
+    }
+
+    fn get<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        loop {
+            match self.find(key, guard) {
+                Ok((found, cursor)) => {
+                    persist::flush(cursor.prev);
+                    persist::fence();
+                    return found.then(|| &unsafe { cursor.curr.deref() }.value);
+                }
+                Err(retry) => retry.count(),
+            }
+        }
+    }
+
+    fn insert(&self, key: K, value: V, guard: &Guard) -> bool {
+        let mut node = Owned::new(Node {
+            next: Atomic::null(),
+            key,
+            value,
+        });
+        loop {
+            let (found, cursor) = match self.find(&node.key, guard) {
+                Ok(found) => found,
+                Err(retry) => {
+                    retry.count();
+                    continue;
+                }
+            };
+            if found {
+                persist::flush(cursor.prev);
+                persist::fence();
+                return false;
+            }
+            node.next.store(cursor.curr, Ordering::Relaxed);
+            // The node must be durable before a durable link can point to it.
+            persist::flush(&*node);
+            persist::fence();
+            match cursor.prev.compare_exchange(
+                cursor.curr,
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            ) {
+                Ok(_) => {
+                    persist::flush(cursor.prev);
+                    persist::fence();
+                    return true;
+                }
+                Err(e) => {
+                    Retry::CasFail.count();
+                    node = e.new;
+                }
+            }
+        }
+    }
+
+    fn remove<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        loop {
+            let (found, cursor) = match self.find(key, guard) {
+                Ok(found) => found,
+                Err(retry) => {
+                    retry.count();
+                    continue;
+                }
+            };
+            if !found {
+                persist::flush(cursor.prev);
+                persist::fence();
+                return None;
+            }
+            let curr_node = unsafe { cursor.curr.deref() };
+            let next = curr_node.next.fetch_or(1, Ordering::AcqRel, guard);
+            if next.tag() == 1 {
+                Retry::CasFail.count();
+                continue;
+            }
+            // The removal is durable once its mark is.
+            persist::flush(&curr_node.next);
+            persist::fence();
+            if cursor
+                .prev
+                .compare_exchange(
+                    cursor.curr,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    guard,
+                )
+                .is_ok()
+            {
+                persist::flush(cursor.prev);
+                unsafe { guard.defer_destroy(cursor.curr) };
+            }
+            return Some(&curr_node.value);
+        }
+    }
+}
+
+pub struct PersistentHashMap<K, V> {
+    buckets: Vec<Bucket<K, V>>,
+}
+
+impl<K, V> PersistentHashMap<K, V>
+where
+    K: Ord + Hash,
+{
+    pub fn with_capacity(n: usize) -> Self {
+        PersistentHashMap {
+            buckets: (0..n).map(|_| Bucket::new()).collect(),
+        }
+    }
+
+    #[inline]
+    fn get_bucket(&self, k: &K) -> &Bucket<K, V> {
+        let mut s = DefaultHasher::new();
+        k.hash(&mut s);
+        let index = s.finish() as usize % self.buckets.len();
+        unsafe { self.buckets.get_unchecked(index) }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for PersistentHashMap<K, V>
+where
+    K: Ord + Hash,
+{
+    fn new() -> Self {
+        Self::with_capacity(30000)
+    }
+
+    #[inline(always)]
+    fn get<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
+        self.get_bucket(key).get(key, guard)
+    }
+    #[inline(always)]
+    fn insert(&self, key: K, value: V, guard: &Guard) -> bool {
+        self.get_bucket(&key).insert(key, value, guard)
+    }
+    #[inline(always)]
+    fn remove<'g>(&'g self, key: &'g K, guard: &'g Guard) -> Option<impl OutputHolder<V>> {
+        self.get_bucket(key).remove(key, guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PersistentHashMap;
+    use crate::ds_impl::ebr::concurrent_map::{self, ConcurrentMap, OutputHolder};
+    use crate::ds_impl::persist::persists;
+
+    #[test]
+    fn smoke_persistent_hash_map() {
+        concurrent_map::tests::smoke::<_, PersistentHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn edge_cases_persistent_hash_map() {
+        concurrent_map::tests::edge_cases::<_, PersistentHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn persists_each_operation() {
+        std::thread::spawn(|| {
+            let map = PersistentHashMap::with_capacity(1);
+            let guard = &crossbeam_ebr::pin();
+            let before = persists();
+            assert!(map.insert(1, 1, guard));
+            let after = persists();
+            // The node, and then the link to it.
+            assert_eq!(after.flushes - before.flushes, 2);
+            assert_eq!(after.fences - before.fences, 2);
+            assert_eq!(map.get(&1, guard).map(|v| *v.output()), Some(1));
+            assert_eq!(map.remove(&1, guard).map(|v| *v.output()), Some(1));
+            assert!(map.get(&1, guard).is_none());
+            let after = persists();
+            // The found link, the mark, the unlink and the link of the last `get`.
+            assert_eq!(after.flushes - before.flushes, 6);
+            assert_eq!(after.fences - before.fences, 5);
+        })
+        .join()
+        .unwrap();
+    }
+}
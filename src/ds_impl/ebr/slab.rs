@@ -0,0 +1,195 @@
+//! A slab allocator of the nodes of one structure.
+//!
+//! The nodes are carved out of chunks of `CHUNK_LEN` nodes, and a freed node goes back to a free
+//! list of the slab instead of the global allocator. A retired node is freed once EBR reclaims it,
+//! so that it is only reused after no thread can be reading it. The free lists are sharded by
+//! thread to keep their locks uncontended, and a thread whose list is empty takes the free nodes of
+//! another shard before it allocates a new chunk.
+//!
+//! The chunks are freed with the slab, after the structure and all of its deferred frees, each of
+//! which holds a reference to the slab, are gone.
+
+use crossbeam_ebr::{Guard, Owned, Shared};
+use crossbeam_utils::CachePadded;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+const CHUNK_LEN: usize = 1024;
+const SHARDS: usize = 64;
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SHARD: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed) % SHARDS;
+}
+
+/// An uninitialized node in a chunk of a slab.
+struct Block<T>(*mut MaybeUninit<T>);
+
+unsafe impl<T> Send for Block<T> {}
+
+pub struct Slab<T> {
+    shards: Box<[CachePadded<Mutex<Vec<Block<T>>>>]>,
+    chunks: Mutex<Vec<Box<[MaybeUninit<T>]>>>,
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Slab<T> {
+    pub fn new() -> Self {
+        Self {
+            shards: (0..SHARDS)
+                .map(|_| CachePadded::new(Mutex::new(Vec::new())))
+                .collect(),
+            chunks: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn local(&self) -> &Mutex<Vec<Block<T>>> {
+        &self.shards[SHARD.with(|shard| *shard)]
+    }
+
+    fn alloc(&self, value: T) -> *mut T {
+        let mut local = self.local().lock().unwrap();
+        let block = match local.pop() {
+            Some(block) => block,
+            None => {
+                self.refill(&mut local);
+                local.pop().unwrap()
+            }
+        };
+        unsafe { (*block.0).write(value) as *mut T }
+    }
+
+    /// Fills an empty free list from another shard, or from a new chunk.
+    fn refill(&self, local: &mut Vec<Block<T>>) {
+        for shard in self.shards.iter() {
+            let Ok(mut other) = shard.try_lock() else {
+                continue;
+            };
+            if !other.is_empty() {
+                let len = other.len();
+                local.extend(other.drain(len.saturating_sub(CHUNK_LEN)..));
+                return;
+            }
+        }
+        let mut chunk: Box<[MaybeUninit<T>]> = Box::new_uninit_slice(CHUNK_LEN);
+        local.extend(chunk.iter_mut().map(|block| Block(block as *mut _)));
+        self.chunks.lock().unwrap().push(chunk);
+    }
+
+    /// Drops the node at `ptr` and puts it in the free list of the current thread.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated by this slab, and no thread may access the node anymore.
+    unsafe fn free(&self, ptr: *mut T) {
+        ptr.drop_in_place();
+        self.local().lock().unwrap().push(Block(ptr.cast()));
+    }
+}
+
+/// Where a structure allocates its nodes.
+pub enum NodeAlloc<T> {
+    /// The global allocator.
+    Global,
+    /// A slab of the structure.
+    Slab(Arc<Slab<T>>),
+}
+
+impl<T> NodeAlloc<T> {
+    pub fn slab() -> Self {
+        NodeAlloc::Slab(Arc::new(Slab::new()))
+    }
+
+    pub fn alloc<'g>(&self, value: T, guard: &'g Guard) -> Shared<'g, T> {
+        match self {
+            NodeAlloc::Global => Owned::new(value).into_shared(guard),
+            NodeAlloc::Slab(slab) => Shared::from(slab.alloc(value) as *const T),
+        }
+    }
+
+    /// Frees a node once no thread that is currently pinned can access it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated by this allocator, and be unreachable for the threads that pin
+    /// after this call.
+    pub unsafe fn retire(&self, ptr: Shared<'_, T>, guard: &Guard) {
+        match self {
+            NodeAlloc::Global => guard.defer_destroy(ptr),
+            NodeAlloc::Slab(slab) => {
+                let slab = slab.clone();
+                let ptr = ptr.as_raw() as *mut T;
+                guard.defer_unchecked(move || slab.free(ptr));
+            }
+        }
+    }
+
+    /// Frees a node immediately.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated by this allocator, and no thread may access it anymore.
+    pub unsafe fn free(&self, ptr: Shared<'_, T>) {
+        match self {
+            NodeAlloc::Global => drop(ptr.into_owned()),
+            NodeAlloc::Slab(slab) => slab.free(ptr.as_raw() as *mut T),
+        }
+    }
+}
+
+impl<T> Clone for NodeAlloc<T> {
+    fn clone(&self) -> Self {
+        match self {
+            NodeAlloc::Global => NodeAlloc::Global,
+            NodeAlloc::Slab(slab) => NodeAlloc::Slab(slab.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NodeAlloc;
+    use crossbeam_ebr::pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn slab_reuses_freed_nodes() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let alloc = NodeAlloc::slab();
+        {
+            let guard = &pin();
+            let first = alloc.alloc(Counted(drops.clone()), guard);
+            let address = first.as_raw();
+            unsafe { alloc.free(first) };
+            assert_eq!(drops.load(Ordering::Relaxed), 1);
+            // The free list of the thread is a stack.
+            let second = alloc.alloc(Counted(drops.clone()), guard);
+            assert_eq!(second.as_raw(), address);
+            unsafe { alloc.retire(second, guard) };
+        }
+        drop(alloc);
+        // The deferred free keeps the slab alive.
+        let mut retries = 0;
+        while drops.load(Ordering::Relaxed) < 2 && retries < 1000 {
+            pin().flush();
+            retries += 1;
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+}
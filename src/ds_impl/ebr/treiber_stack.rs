@@ -0,0 +1,235 @@
+//! Treiber's stack, and its variant with an elimination array.
+//!
+//! A pop reads the next node of the head and swings the head to it, which is the textbook case of
+//! the ABA problem: the head may be popped, freed, and pushed again at the same address in
+//! between. The pin of the pop keeps the head from being freed, which rules it out.
+
+use super::concurrent_stack::ConcurrentStack;
+use crate::ds_impl::elimination::EliminationArray;
+
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::Ordering;
+
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned};
+use crossbeam_utils::CachePadded;
+
+struct Node<T> {
+    /// Moved out by the pop that unlinks the node.
+    item: ManuallyDrop<T>,
+    next: Atomic<Node<T>>,
+}
+
+pub struct TreiberStack<T> {
+    head: CachePadded<Atomic<Node<T>>>,
+}
+
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TreiberStack<T> {
+    pub fn new() -> Self {
+        Self {
+            head: CachePadded::new(Atomic::null()),
+        }
+    }
+
+    /// Tries to link `node` on the head once, and returns it back if the head changed.
+    #[inline]
+    fn try_link(&self, node: Owned<Node<T>>, guard: &Guard) -> Result<(), Owned<Node<T>>> {
+        let head = self.head.load(Ordering::Relaxed, guard);
+        node.next.store(head, Ordering::Relaxed);
+        self.head
+            .compare_exchange(head, node, Ordering::Release, Ordering::Relaxed, guard)
+            .map(|_| ())
+            .map_err(|e| e.new)
+    }
+
+    /// Tries to unlink the head once, and fails if the head changed.
+    #[inline]
+    fn try_pop(&self, guard: &Guard) -> Result<Option<T>, ()> {
+        let head = self.head.load(Ordering::Acquire, guard);
+        let Some(head_ref) = (unsafe { head.as_ref() }) else {
+            return Ok(None);
+        };
+        let next = head_ref.next.load(Ordering::Relaxed, guard);
+        self.head
+            .compare_exchange(head, next, Ordering::Relaxed, Ordering::Relaxed, guard)
+            .map_err(|_| ())?;
+        let item = unsafe { ptr::read(&*head_ref.item) };
+        unsafe { guard.defer_destroy(head) };
+        Ok(Some(item))
+    }
+
+    pub fn push(&self, item: T, guard: &Guard) {
+        let mut node = Owned::new(Node {
+            item: ManuallyDrop::new(item),
+            next: Atomic::null(),
+        });
+        while let Err(back) = self.try_link(node, guard) {
+            node = back;
+        }
+    }
+
+    pub fn pop(&self, guard: &Guard) -> Option<T> {
+        loop {
+            if let Ok(item) = self.try_pop(guard) {
+                return item;
+            }
+        }
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        while self.pop(unsafe { unprotected() }).is_some() {}
+    }
+}
+
+/// Treiber's stack that backs off to an elimination array after each failed CAS on its head.
+pub struct EliminationStack<T> {
+    stack: TreiberStack<T>,
+    elimination: EliminationArray<T>,
+}
+
+impl<T> Default for EliminationStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> EliminationStack<T> {
+    pub fn new() -> Self {
+        Self {
+            stack: TreiberStack::new(),
+            elimination: EliminationArray::default(),
+        }
+    }
+
+    pub fn push(&self, item: T, guard: &Guard) {
+        let mut item = item;
+        loop {
+            let node = Owned::new(Node {
+                item: ManuallyDrop::new(item),
+                next: Atomic::null(),
+            });
+            match self.stack.try_link(node, guard) {
+                Ok(()) => return,
+                Err(node) => item = ManuallyDrop::into_inner(node.into_box().item),
+            }
+            match self.elimination.push(item) {
+                Ok(()) => return,
+                Err(back) => item = back,
+            }
+        }
+    }
+
+    pub fn pop(&self, guard: &Guard) -> Option<T> {
+        loop {
+            if let Ok(item) = self.stack.try_pop(guard) {
+                return item;
+            }
+            if let Some(item) = self.elimination.pop() {
+                return Some(item);
+            }
+        }
+    }
+}
+
+impl<T> ConcurrentStack<T> for TreiberStack<T> {
+    fn new() -> Self {
+        TreiberStack::new()
+    }
+
+    #[inline(always)]
+    fn push(&self, item: T, guard: &Guard) {
+        self.push(item, guard)
+    }
+
+    #[inline(always)]
+    fn pop(&self, guard: &Guard) -> Option<T> {
+        self.pop(guard)
+    }
+}
+
+impl<T> ConcurrentStack<T> for EliminationStack<T> {
+    fn new() -> Self {
+        EliminationStack::new()
+    }
+
+    #[inline(always)]
+    fn push(&self, item: T, guard: &Guard) {
+        self.push(item, guard)
+    }
+
+    #[inline(always)]
+    fn pop(&self, guard: &Guard) -> Option<T> {
+        self.pop(guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EliminationStack, TreiberStack};
+    use crate::ds_impl::ebr::concurrent_stack::ConcurrentStack;
+    use crossbeam_ebr::pin;
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn simple<S: ConcurrentStack<String>>() {
+        let stack = S::new();
+        let guard = &pin();
+        assert!(stack.pop(guard).is_none());
+        stack.push("1".to_string(), guard);
+        stack.push("2".to_string(), guard);
+        assert_eq!(stack.pop(guard).unwrap(), "2");
+        stack.push("3".to_string(), guard);
+        assert_eq!(stack.pop(guard).unwrap(), "3");
+        assert_eq!(stack.pop(guard).unwrap(), "1");
+        assert!(stack.pop(guard).is_none());
+        // The remaining items are dropped with the stack.
+        stack.push("4".to_string(), guard);
+    }
+
+    fn smoke<S: ConcurrentStack<usize> + Sync>() {
+        const THREADS: usize = 30;
+        const ELEMENTS_PER_THREAD: usize = 10000;
+
+        let stack = S::new();
+        let mut found = Vec::new();
+        found.resize_with(THREADS * ELEMENTS_PER_THREAD, || AtomicU32::new(0));
+
+        scope(|s| {
+            for t in 0..THREADS {
+                let (stack, found) = (&stack, &found);
+                s.spawn(move |_| {
+                    for i in 0..ELEMENTS_PER_THREAD {
+                        stack.push(t * ELEMENTS_PER_THREAD + i, &pin());
+                        let item = stack.pop(&pin()).unwrap();
+                        found[item].fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        })
+        .unwrap();
+        assert!(found.iter().all(|f| f.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn treiber() {
+        simple::<TreiberStack<_>>();
+        smoke::<TreiberStack<_>>();
+    }
+
+    #[test]
+    fn elimination() {
+        simple::<EliminationStack<_>>();
+        smoke::<EliminationStack<_>>();
+    }
+}
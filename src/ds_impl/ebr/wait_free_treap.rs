@@ -0,0 +1,512 @@
+//! A wait-free map built by an announce-and-help transformation (in the style of Kogan and
+//! Petrank) over a persistent treap.
+//!
+//! Every update is announced in the slot of its thread, together with a phase number. A thread
+//! that updates the map helps all pending announcements: it takes the current version of the
+//! treap, applies the pending operations in the order of their phases by path copying, and
+//! installs the resulting version with a single CAS. An update returns once a version that
+//! includes it is installed. A helper that starts after an announcement always includes it, so
+//! an update returns after at most one failed attempt per concurrent thread. `get` runs on the
+//! current version, which is immutable, and never helps.
+//!
+//! Unlike the other structures, a successful helper retires the nodes replaced on behalf of
+//! other threads, and the announcements and the versions themselves are retired as well.
+
+use crossbeam_ebr::{unprotected, Atomic, Guard, Owned, Shared};
+use crossbeam_utils::CachePadded;
+
+use super::concurrent_map::impl_concurrent_map;
+
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The maximum number of threads that can use wait-free maps at the same time.
+pub const MAX_THREADS: usize = 1024;
+
+/// The slot indices of the exited threads, to be reused by new threads.
+static FREE_SLOTS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+/// One past the largest slot index handed out so far.
+static SLOTS_IN_USE: AtomicUsize = AtomicUsize::new(0);
+
+struct ThreadSlot(usize);
+
+impl ThreadSlot {
+    fn new() -> Self {
+        let index = FREE_SLOTS
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_else(|| SLOTS_IN_USE.fetch_add(1, Ordering::Relaxed));
+        assert!(index < MAX_THREADS, "Too many threads for a wait-free map");
+        Self(index)
+    }
+}
+
+impl Drop for ThreadSlot {
+    fn drop(&mut self) {
+        FREE_SLOTS.lock().unwrap().push(self.0);
+    }
+}
+
+thread_local! {
+    static SLOT: ThreadSlot = ThreadSlot::new();
+}
+
+pub(crate) struct Node<K, V> {
+    key: K,
+    value: V,
+    priority: u32,
+    /// The version that allocated this node. The nodes of a version that is not installed yet
+    /// are private to its helper, which modifies them in place.
+    version: u64,
+    left: Atomic<Node<K, V>>,
+    right: Atomic<Node<K, V>>,
+}
+
+enum Operation<K, V> {
+    Insert(K, V),
+    Remove(K),
+}
+
+struct Announcement<K, V> {
+    op: Operation<K, V>,
+    /// The number of operations announced in the slot so far, including this one.
+    seq: u64,
+    phase: u64,
+}
+
+#[derive(Clone)]
+enum Outcome<V> {
+    Insert(bool),
+    Remove(Option<V>),
+}
+
+#[derive(Clone)]
+struct Response<V> {
+    seq: u64,
+    outcome: Outcome<V>,
+}
+
+struct State<K, V> {
+    root: Atomic<Node<K, V>>,
+    version: u64,
+    /// The response to the last applied announcement of each slot.
+    responses: Vec<Option<Response<V>>>,
+}
+
+impl<K, V> State<K, V> {
+    fn applied(&self, slot: usize) -> u64 {
+        self.responses
+            .get(slot)
+            .and_then(Option::as_ref)
+            .map_or(0, |response| response.seq)
+    }
+}
+
+/// Applies operations on a private copy of a version.
+struct Builder<'g, K, V> {
+    version: u64,
+    guard: &'g Guard,
+    /// All nodes allocated for the new version.
+    allocated: Vec<Shared<'g, Node<K, V>>>,
+    /// The nodes allocated for the new version that it no longer contains.
+    discarded: Vec<Shared<'g, Node<K, V>>>,
+    /// The nodes of the base version that the new version no longer contains.
+    replaced: Vec<Shared<'g, Node<K, V>>>,
+}
+
+impl<'g, K, V> Builder<'g, K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn new(version: u64, guard: &'g Guard) -> Self {
+        Self {
+            version,
+            guard,
+            allocated: vec![],
+            discarded: vec![],
+            replaced: vec![],
+        }
+    }
+
+    fn alloc(
+        &mut self,
+        key: K,
+        value: V,
+        priority: u32,
+        left: Shared<'g, Node<K, V>>,
+        right: Shared<'g, Node<K, V>>,
+    ) -> Shared<'g, Node<K, V>> {
+        let node = Owned::new(Node {
+            key,
+            value,
+            priority,
+            version: self.version,
+            left: Atomic::from(left),
+            right: Atomic::from(right),
+        })
+        .into_shared(self.guard);
+        self.allocated.push(node);
+        node
+    }
+
+    /// Returns `node` if it belongs to the new version, or a copy of it otherwise.
+    fn own(&mut self, node: Shared<'g, Node<K, V>>) -> Shared<'g, Node<K, V>> {
+        let node_ref = unsafe { node.deref() };
+        if node_ref.version == self.version {
+            return node;
+        }
+        self.replaced.push(node);
+        self.alloc(
+            node_ref.key.clone(),
+            node_ref.value.clone(),
+            node_ref.priority,
+            node_ref.left.load(Ordering::Relaxed, self.guard),
+            node_ref.right.load(Ordering::Relaxed, self.guard),
+        )
+    }
+
+    fn discard(&mut self, node: Shared<'g, Node<K, V>>) {
+        if unsafe { node.deref() }.version == self.version {
+            self.discarded.push(node);
+        } else {
+            self.replaced.push(node);
+        }
+    }
+
+    /// Inserts the key into the subtree of `node`, and returns the new root of the subtree.
+    /// Returns `None` if the key is already in the subtree.
+    fn insert(
+        &mut self,
+        node: Shared<'g, Node<K, V>>,
+        key: &K,
+        value: &V,
+    ) -> Option<Shared<'g, Node<K, V>>> {
+        let Some(node_ref) = (unsafe { node.as_ref() }) else {
+            return Some(self.alloc(
+                key.clone(),
+                value.clone(),
+                rand::random(),
+                Shared::null(),
+                Shared::null(),
+            ));
+        };
+
+        // The returned subtree roots always belong to the new version, so the rotations below
+        // modify only the private nodes.
+        match key.cmp(&node_ref.key) {
+            Equal => None,
+            Less => {
+                let left = self.insert(
+                    node_ref.left.load(Ordering::Relaxed, self.guard),
+                    key,
+                    value,
+                )?;
+                let node = self.own(node);
+                let (node_ref, left_ref) = unsafe { (node.deref(), left.deref()) };
+                if left_ref.priority > node_ref.priority {
+                    let left_right = left_ref.right.load(Ordering::Relaxed, self.guard);
+                    node_ref.left.store(left_right, Ordering::Relaxed);
+                    left_ref.right.store(node, Ordering::Relaxed);
+                    Some(left)
+                } else {
+                    node_ref.left.store(left, Ordering::Relaxed);
+                    Some(node)
+                }
+            }
+            Greater => {
+                let right = self.insert(
+                    node_ref.right.load(Ordering::Relaxed, self.guard),
+                    key,
+                    value,
+                )?;
+                let node = self.own(node);
+                let (node_ref, right_ref) = unsafe { (node.deref(), right.deref()) };
+                if right_ref.priority > node_ref.priority {
+                    let right_left = right_ref.left.load(Ordering::Relaxed, self.guard);
+                    node_ref.right.store(right_left, Ordering::Relaxed);
+                    right_ref.left.store(node, Ordering::Relaxed);
+                    Some(right)
+                } else {
+                    node_ref.right.store(right, Ordering::Relaxed);
+                    Some(node)
+                }
+            }
+        }
+    }
+
+    /// Removes the key from the subtree of `node`, and returns the new root of the subtree with
+    /// the removed value. Returns `None` if the key is not in the subtree.
+    fn remove(
+        &mut self,
+        node: Shared<'g, Node<K, V>>,
+        key: &K,
+    ) -> Option<(Shared<'g, Node<K, V>>, V)> {
+        let node_ref = unsafe { node.as_ref() }?;
+        match key.cmp(&node_ref.key) {
+            Equal => {
+                let left = node_ref.left.load(Ordering::Relaxed, self.guard);
+                let right = node_ref.right.load(Ordering::Relaxed, self.guard);
+                let value = node_ref.value.clone();
+                self.discard(node);
+                Some((self.merge(left, right), value))
+            }
+            Less => {
+                let (left, value) =
+                    self.remove(node_ref.left.load(Ordering::Relaxed, self.guard), key)?;
+                let node = self.own(node);
+                unsafe { node.deref() }.left.store(left, Ordering::Relaxed);
+                Some((node, value))
+            }
+            Greater => {
+                let (right, value) =
+                    self.remove(node_ref.right.load(Ordering::Relaxed, self.guard), key)?;
+                let node = self.own(node);
+                unsafe { node.deref() }
+                    .right
+                    .store(right, Ordering::Relaxed);
+                Some((node, value))
+            }
+        }
+    }
+
+    /// Merges two subtrees whose keys are all smaller on the left than on the right.
+    fn merge(
+        &mut self,
+        left: Shared<'g, Node<K, V>>,
+        right: Shared<'g, Node<K, V>>,
+    ) -> Shared<'g, Node<K, V>> {
+        let Some(left_ref) = (unsafe { left.as_ref() }) else {
+            return right;
+        };
+        let Some(right_ref) = (unsafe { right.as_ref() }) else {
+            return left;
+        };
+
+        if left_ref.priority > right_ref.priority {
+            let left = self.own(left);
+            let left_ref = unsafe { left.deref() };
+            let merged = self.merge(left_ref.right.load(Ordering::Relaxed, self.guard), right);
+            left_ref.right.store(merged, Ordering::Relaxed);
+            left
+        } else {
+            let right = self.own(right);
+            let right_ref = unsafe { right.deref() };
+            let merged = self.merge(left, right_ref.left.load(Ordering::Relaxed, self.guard));
+            right_ref.left.store(merged, Ordering::Relaxed);
+            right
+        }
+    }
+
+    /// Frees the nodes that no installed version has contained, and retires the nodes that the
+    /// installed new version no longer contains.
+    unsafe fn commit(self) {
+        for node in self.discarded {
+            drop(node.into_owned());
+        }
+        for node in self.replaced {
+            self.guard.defer_destroy(node);
+        }
+    }
+
+    /// Frees the nodes of the new version, which failed to be installed.
+    unsafe fn abort(self) {
+        for node in self.allocated {
+            drop(node.into_owned());
+        }
+    }
+}
+
+pub struct WaitFreeTreap<K, V> {
+    state: Atomic<State<K, V>>,
+    announcements: Box<[CachePadded<Atomic<Announcement<K, V>>>]>,
+    phase: CachePadded<AtomicU64>,
+}
+
+impl<K, V> Drop for WaitFreeTreap<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let state = self
+                .state
+                .load(Ordering::Relaxed, unprotected())
+                .into_owned();
+            let mut stack = vec![state.root.load(Ordering::Relaxed, unprotected())];
+
+            while let Some(node) = stack.pop() {
+                if node.is_null() {
+                    continue;
+                }
+
+                let node_ref = node.deref();
+
+                stack.push(node_ref.left.load(Ordering::Relaxed, unprotected()));
+                stack.push(node_ref.right.load(Ordering::Relaxed, unprotected()));
+                drop(node.into_owned());
+            }
+
+            for announcement in self.announcements.iter() {
+                let announcement = announcement.load(Ordering::Relaxed, unprotected());
+                if !announcement.is_null() {
+                    drop(announcement.into_owned());
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Default for WaitFreeTreap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> WaitFreeTreap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            state: Atomic::new(State {
+                root: Atomic::null(),
+                version: 0,
+                responses: vec![],
+            }),
+            announcements: (0..MAX_THREADS)
+                .map(|_| CachePadded::new(Atomic::null()))
+                .collect(),
+            phase: CachePadded::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn get<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        let state = unsafe { self.state.load(Ordering::Acquire, guard).deref() };
+        let mut node = state.root.load(Ordering::Acquire, guard);
+        while let Some(node_ref) = unsafe { node.as_ref() } {
+            match key.cmp(&node_ref.key) {
+                Equal => return Some(&node_ref.value),
+                Less => node = node_ref.left.load(Ordering::Acquire, guard),
+                Greater => node = node_ref.right.load(Ordering::Acquire, guard),
+            }
+        }
+        None
+    }
+
+    pub fn insert(&self, key: K, value: V, guard: &Guard) -> bool {
+        match self.update(Operation::Insert(key, value), guard) {
+            Outcome::Insert(inserted) => inserted,
+            Outcome::Remove(_) => unreachable!(),
+        }
+    }
+
+    pub fn remove(&self, key: &K, guard: &Guard) -> Option<V> {
+        match self.update(Operation::Remove(key.clone()), guard) {
+            Outcome::Remove(removed) => removed,
+            Outcome::Insert(_) => unreachable!(),
+        }
+    }
+
+    /// Announces `op` and helps the pending operations until a version that includes `op` is
+    /// installed.
+    fn update(&self, op: Operation<K, V>, guard: &Guard) -> Outcome<V> {
+        let slot = SLOT.with(|slot| slot.0);
+        let announcement = &self.announcements[slot];
+        let prev = announcement.load(Ordering::Relaxed, guard);
+        let seq = unsafe { prev.as_ref() }.map_or(0, |prev| prev.seq) + 1;
+        let phase = self.phase.fetch_add(1, Ordering::Relaxed);
+        announcement.store(
+            Owned::new(Announcement { op, seq, phase }),
+            Ordering::Release,
+        );
+        if !prev.is_null() {
+            unsafe { guard.defer_destroy(prev) };
+        }
+
+        loop {
+            let state = self.state.load(Ordering::Acquire, guard);
+            let state_ref = unsafe { state.deref() };
+            if let Some(response) = state_ref.responses.get(slot).and_then(Option::as_ref) {
+                if response.seq == seq {
+                    return response.outcome.clone();
+                }
+            }
+            self.help(state, guard);
+        }
+    }
+
+    /// Applies all operations pending on `state` and tries to install the resulting version.
+    fn help<'g>(&self, state: Shared<'g, State<K, V>>, guard: &'g Guard) {
+        let state_ref = unsafe { state.deref() };
+        let slots = SLOTS_IN_USE.load(Ordering::Acquire).min(MAX_THREADS);
+
+        let mut pending = vec![];
+        for (slot, announcement) in self.announcements[..slots].iter().enumerate() {
+            let announcement = announcement.load(Ordering::Acquire, guard);
+            if let Some(announcement) = unsafe { announcement.as_ref() } {
+                if announcement.seq > state_ref.applied(slot) {
+                    pending.push((slot, announcement));
+                }
+            }
+        }
+        pending.sort_by_key(|(_, announcement)| announcement.phase);
+
+        let mut builder = Builder::new(state_ref.version + 1, guard);
+        let mut root = state_ref.root.load(Ordering::Relaxed, guard);
+        let mut responses = state_ref.responses.clone();
+        if responses.len() < slots {
+            responses.resize(slots, None);
+        }
+        for (slot, announcement) in pending {
+            let outcome = match &announcement.op {
+                Operation::Insert(key, value) => match builder.insert(root, key, value) {
+                    Some(new_root) => {
+                        root = new_root;
+                        Outcome::Insert(true)
+                    }
+                    None => Outcome::Insert(false),
+                },
+                Operation::Remove(key) => match builder.remove(root, key) {
+                    Some((new_root, value)) => {
+                        root = new_root;
+                        Outcome::Remove(Some(value))
+                    }
+                    None => Outcome::Remove(None),
+                },
+            };
+            responses[slot] = Some(Response {
+                seq: announcement.seq,
+                outcome,
+            });
+        }
+
+        let new_state = Owned::new(State {
+            root: Atomic::from(root),
+            version: builder.version,
+            responses,
+        });
+        match self.state.compare_exchange(
+            state,
+            new_state,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            guard,
+        ) {
+            Ok(_) => unsafe {
+                builder.commit();
+                guard.defer_destroy(state);
+            },
+            Err(_) => unsafe { builder.abort() },
+        }
+    }
+}
+
+impl_concurrent_map! {
+    impl [K: Ord + Clone, V: Clone] for WaitFreeTreap;
+    #[test] smoke_wait_free_treap;
+}
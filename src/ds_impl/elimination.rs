@@ -0,0 +1,144 @@
+//! The elimination array of the `elimination` stacks, after Hendler, Shavit and Yerushalmi.
+//!
+//! A push and a pop that collide on the head of a stack can cancel each other out without touching
+//! it: the push offers its item in a random slot of the array and waits for a while, and a pop that
+//! takes the item from the slot returns it as if the push had completed right before it. An offered
+//! item is owned by its slot until a pop takes it or the push withdraws it, and a pop only reads
+//! the item after it has taken it, so the array needs no reclamation and is the same for every
+//! scheme.
+
+use crossbeam_utils::CachePadded;
+use rand::Rng;
+use std::hint::spin_loop;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// The number of slots of an array, which is small so that the pushes and the pops that back off
+/// at the same time meet in the same slots.
+pub const DEFAULT_WIDTH: usize = 8;
+
+/// The number of iterations for which a push waits for a pop in its slot.
+const PATIENCE: usize = 128;
+
+/// A slot holds null if it is empty, the boxed item of a push if it is offered, or its own address
+/// if a pop took the item and the push has not yet seen it.
+pub struct EliminationArray<T> {
+    slots: Box<[CachePadded<AtomicPtr<T>>]>,
+}
+
+impl<T> Default for EliminationArray<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_WIDTH)
+    }
+}
+
+impl<T> EliminationArray<T> {
+    pub fn new(width: usize) -> Self {
+        assert!(width > 0, "An elimination array must have a slot");
+        Self {
+            slots: (0..width)
+                .map(|_| CachePadded::new(AtomicPtr::new(ptr::null_mut())))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn slot(&self) -> &AtomicPtr<T> {
+        &self.slots[rand::thread_rng().gen_range(0..self.slots.len())]
+    }
+
+    /// The mark of a slot whose item is taken, which no item can have since the slot is not one.
+    #[inline]
+    fn taken(slot: &AtomicPtr<T>) -> *mut T {
+        slot as *const _ as *mut T
+    }
+
+    /// Offers `item` to a pop, and returns it back if no pop takes it in time.
+    pub fn push(&self, item: T) -> Result<(), T> {
+        let slot = self.slot();
+        let offer = Box::into_raw(Box::new(item));
+        if slot
+            .compare_exchange(ptr::null_mut(), offer, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(*unsafe { Box::from_raw(offer) });
+        }
+        for _ in 0..PATIENCE {
+            if slot.load(Ordering::Relaxed) != offer {
+                break;
+            }
+            spin_loop();
+        }
+        match slot.compare_exchange(offer, ptr::null_mut(), Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => Err(*unsafe { Box::from_raw(offer) }),
+            Err(_) => {
+                // Only this push empties a taken slot, so that no other push can offer an item at
+                // the same address in it before this one sees that its item is taken.
+                slot.store(ptr::null_mut(), Ordering::Relaxed);
+                Ok(())
+            }
+        }
+    }
+
+    /// Takes the item of a push, if one is offered in the slot that the pop picks.
+    pub fn pop(&self) -> Option<T> {
+        let slot = self.slot();
+        let offer = slot.load(Ordering::Acquire);
+        if offer.is_null() || offer == Self::taken(slot) {
+            return None;
+        }
+        slot.compare_exchange(
+            offer,
+            Self::taken(slot),
+            Ordering::Acquire,
+            Ordering::Relaxed,
+        )
+        .ok()?;
+        Some(*unsafe { Box::from_raw(offer) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EliminationArray;
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn withdraws_unmatched_offers() {
+        let array = EliminationArray::new(1);
+        assert_eq!(array.push(String::from("a")), Err(String::from("a")));
+        assert_eq!(array.pop(), None);
+    }
+
+    #[test]
+    fn exchanges() {
+        const THREADS: usize = 4;
+        const ITEMS: usize = 10000;
+
+        let array = EliminationArray::new(1);
+        let pushed = AtomicUsize::new(0);
+        let popped = AtomicUsize::new(0);
+        scope(|s| {
+            for t in 0..THREADS {
+                let (array, pushed, popped) = (&array, &pushed, &popped);
+                s.spawn(move |_| {
+                    for i in 0..ITEMS {
+                        if t % 2 == 0 {
+                            if array.push(Box::new(i)).is_ok() {
+                                pushed.fetch_add(i, Ordering::Relaxed);
+                            }
+                        } else if let Some(item) = array.pop() {
+                            popped.fetch_add(*item, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+        assert_eq!(
+            pushed.load(Ordering::Relaxed),
+            popped.load(Ordering::Relaxed)
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! How the inserts of `nm-tree` resolve their failed CASes.
+//!
+//! An insert whose CAS on the edge to the leaf fails either helps the remove that flagged or
+//! tagged the edge by running its cleanup, or retries at once because another insert or a cleanup
+//! replaced the leaf. The counts are kept per thread with [`Counters`](super::counters::Counters),
+//! so that the helping frequency of the schemes can be compared with their throughput.
+
+use super::counters::{Counters, LocalCounts};
+use std::fmt;
+use std::ops::Div;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertConflict {
+    /// The edge was marked by a remove, which the insert helped to clean up before retrying.
+    Help,
+    /// The edge pointed to another node, or was frozen by a range removal.
+    Retry,
+}
+
+static COUNTERS: Counters<2> = Counters::new(&LOCAL);
+
+thread_local! {
+    static LOCAL: LocalCounts<2> = LocalCounts::new(&COUNTERS);
+}
+
+impl InsertConflict {
+    /// Counts a failed insert CAS resolved this way.
+    #[inline]
+    pub fn count(self) {
+        COUNTERS.count(self as usize);
+    }
+}
+
+/// The numbers of failed insert CASes by resolution, of the exited threads and the calling thread.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InsertConflicts([usize; 2]);
+
+impl InsertConflicts {
+    pub fn get(&self, conflict: InsertConflict) -> usize {
+        self.0[conflict as usize]
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+/// Returns the numbers of failed insert CASes since the start of the process, including those of
+/// the calling thread, but not those of the other running threads.
+pub fn insert_conflicts() -> InsertConflicts {
+    InsertConflicts(COUNTERS.get())
+}
+
+/// Marks the start of the measured window of the calling thread.
+pub fn arrive() {
+    COUNTERS.arrive();
+}
+
+/// Adds the failed insert CASes of the calling thread since it arrived to those of the measured windows.
+pub fn depart() {
+    COUNTERS.depart();
+}
+
+/// Returns the numbers of failed insert CASes in the measured windows of the threads that have departed.
+pub fn measured_insert_conflicts() -> InsertConflicts {
+    InsertConflicts(COUNTERS.measured())
+}
+
+/// The average over `reps` repetitions.
+impl Div<usize> for InsertConflicts {
+    type Output = InsertConflicts;
+
+    fn div(self, reps: usize) -> InsertConflicts {
+        InsertConflicts(self.0.map(|count| count / reps))
+    }
+}
+
+impl fmt::Display for InsertConflicts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        write!(
+            f,
+            "help {}, retry {} ({:.1}% helped)",
+            self.get(InsertConflict::Help),
+            self.get(InsertConflict::Retry),
+            self.get(InsertConflict::Help) as f64 * 100.0 / total.max(1) as f64
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{insert_conflicts, InsertConflict};
+    use std::thread;
+
+    #[test]
+    fn insert_conflicts_of_exited_threads() {
+        let before = insert_conflicts();
+        thread::spawn(|| {
+            InsertConflict::Help.count();
+            InsertConflict::Retry.count();
+            InsertConflict::Retry.count();
+        })
+        .join()
+        .unwrap();
+        let after = insert_conflicts();
+        // Other tests may count conflicts concurrently.
+        assert!(after.get(InsertConflict::Help) > before.get(InsertConflict::Help));
+        assert!(after.get(InsertConflict::Retry) >= before.get(InsertConflict::Retry) + 2);
+    }
+}
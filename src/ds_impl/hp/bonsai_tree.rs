@@ -1,7 +1,7 @@
 use hp_pp::{light_membarrier, Thread};
 use hp_pp::{tag, tagged, untagged, HazardPointer, DEFAULT_DOMAIN};
 
-use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::concurrent_map::{ConcurrentMap, OutputHolder, ProtectionSet};
 
 use std::cmp;
 use std::ptr;
@@ -108,6 +108,8 @@ pub struct State<'domain, K, V> {
     root_h: HazardPointer<'domain>,
     succ_h: HazardPointer<'domain>, // Used for traversing in get
     removed_h: HazardPointer<'domain>,
+    /// The children of the nodes on the path of an update, protected until the update ends.
+    path: ProtectionSet<'domain>,
     /// Nodes that current op wants to remove from the tree. Should be retired if CAS succeeds.
     /// (`retire`). If not, ignore.
     retired_nodes: Vec<*mut Node<K, V>>,
@@ -125,6 +127,7 @@ impl<K, V> Default for State<'static, K, V> {
             root_h: HazardPointer::new(&mut thread),
             succ_h: HazardPointer::new(&mut thread),
             removed_h: HazardPointer::new(&mut thread),
+            path: ProtectionSet::new(),
             retired_nodes: vec![],
             new_nodes: vec![],
             thread,
@@ -148,6 +151,7 @@ where
     /// Destroy the newly created state (self) that lost the race (reclaim_state)
     fn abort(&mut self) {
         self.root_h.reset_protection();
+        self.path.release();
         self.retired_nodes.clear();
 
         for node in self.new_nodes.drain(..) {
@@ -158,6 +162,7 @@ where
     /// Retire the old state replaced by the new_state and the new_state.retired_nodes
     fn commit(&mut self) {
         self.root_h.reset_protection();
+        self.path.release();
         self.new_nodes.clear();
 
         for node in self.retired_nodes.drain(..) {
@@ -247,12 +252,8 @@ where
         value: V,
     ) -> Result<*mut Node<K, V>, ()> {
         let right_ref = unsafe { &*untagged(right) };
-        let (mut right_left_h, mut right_right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (right_left, right_right) =
-            right_ref.protect_next(&mut right_left_h, &mut right_right_h);
+        let (right_left_h, right_right_h) = self.path.take_pair(&mut self.thread);
+        let (right_left, right_right) = right_ref.protect_next(right_left_h, right_right_h);
         self.check_root()?;
 
         if Node::is_retired_spot(right_left) || Node::is_retired_spot(right_right) {
@@ -302,12 +303,9 @@ where
     ) -> Result<*mut Node<K, V>, ()> {
         let right_ref = unsafe { &*untagged(right) };
         let right_left_ref = unsafe { &*untagged(right_left) };
-        let (mut right_left_left_h, mut right_left_right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
+        let (right_left_left_h, right_left_right_h) = self.path.take_pair(&mut self.thread);
         let (right_left_left, right_left_right) =
-            right_left_ref.protect_next(&mut right_left_left_h, &mut right_left_right_h);
+            right_left_ref.protect_next(right_left_left_h, right_left_right_h);
         self.check_root()?;
 
         if Node::is_retired_spot(right_left_left) || Node::is_retired_spot(right_left_right) {
@@ -341,11 +339,8 @@ where
         value: V,
     ) -> Result<*mut Node<K, V>, ()> {
         let left_ref = unsafe { &*untagged(left) };
-        let (mut left_left_h, mut left_right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left_left, left_right) = left_ref.protect_next(&mut left_left_h, &mut left_right_h);
+        let (left_left_h, left_right_h) = self.path.take_pair(&mut self.thread);
+        let (left_left, left_right) = left_ref.protect_next(left_left_h, left_right_h);
         self.check_root()?;
 
         if Node::is_retired_spot(left_right) || Node::is_retired_spot(left_left) {
@@ -394,12 +389,9 @@ where
     ) -> Result<*mut Node<K, V>, ()> {
         let left_ref = unsafe { &*untagged(left) };
         let left_right_ref = unsafe { &*untagged(left_right) };
-        let (mut left_right_left_h, mut left_right_right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
+        let (left_right_left_h, left_right_right_h) = self.path.take_pair(&mut self.thread);
         let (left_right_left, left_right_right) =
-            left_right_ref.protect_next(&mut left_right_left_h, &mut left_right_right_h);
+            left_right_ref.protect_next(left_right_left_h, left_right_right_h);
         self.check_root()?;
 
         if Node::is_retired_spot(left_right_left) || Node::is_retired_spot(left_right_right) {
@@ -443,11 +435,8 @@ where
         }
 
         let node_ref = unsafe { &*untagged(node) };
-        let (mut left_h, mut right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left, right) = node_ref.protect_next(&mut left_h, &mut right_h);
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h);
         self.check_root()?;
 
         if Node::is_retired_spot(left) || Node::is_retired_spot(right) {
@@ -482,11 +471,8 @@ where
         }
 
         let node_ref = unsafe { &*untagged(node) };
-        let (mut left_h, mut right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left, right) = node_ref.protect_next(&mut left_h, &mut right_h);
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h);
         self.check_root()?;
 
         if Node::is_retired_spot(left) || Node::is_retired_spot(right) {
@@ -505,10 +491,10 @@ where
                 }
 
                 if !left.is_null() {
-                    let (new_left, succ, _new_left_h) = self.pull_rightmost(left)?;
+                    let (new_left, succ) = self.pull_rightmost(left)?;
                     return Ok((self.mk_balanced(succ, new_left, right)?, value));
                 }
-                let (new_right, succ, _new_right_h) = self.pull_leftmost(right)?;
+                let (new_right, succ) = self.pull_leftmost(right)?;
                 Ok((self.mk_balanced(succ, left, new_right)?, value))
             }
             cmp::Ordering::Less => {
@@ -522,36 +508,148 @@ where
         }
     }
 
+    /// Removes the keys of the subtree of `node` that are at least `lo` and below `hi`, where a
+    /// missing bound is one that every key of the subtree is known to satisfy, and returns the new
+    /// subtree and the number of the removed keys.
+    ///
+    /// The paths to the bounds are copied, and a subtree within both bounds is retired whole
+    /// instead of being rebuilt. The two subtrees left by a removed node are joined back.
+    fn do_remove_range(
+        &mut self,
+        node: *mut Node<K, V>,
+        lo: Option<&K>,
+        hi: Option<&K>,
+    ) -> Result<(*mut Node<K, V>, usize), ()> {
+        if lo.is_none() && hi.is_none() {
+            return self.retire_subtree(node);
+        }
+
+        if Node::is_retired_spot(node) {
+            return Ok((Node::retired_node(), 0));
+        }
+
+        if node.is_null() {
+            return Ok((ptr::null_mut(), 0));
+        }
+
+        let node_ref = unsafe { &*untagged(node) };
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h);
+        self.check_root()?;
+
+        if Node::is_retired_spot(left) || Node::is_retired_spot(right) {
+            return Ok((Node::retired_node(), 0));
+        }
+
+        if lo.is_some_and(|lo| node_ref.key < *lo) {
+            let (new_right, removed) = self.do_remove_range(right, lo, hi)?;
+            return Ok((self.mk_balanced(node, left, new_right)?, removed));
+        }
+        if hi.is_some_and(|hi| node_ref.key >= *hi) {
+            let (new_left, removed) = self.do_remove_range(left, lo, hi)?;
+            return Ok((self.mk_balanced(node, new_left, right)?, removed));
+        }
+        // The keys of the left subtree are below `hi`, and those of the right one at least `lo`.
+        let (new_left, left_removed) = self.do_remove_range(left, lo, None)?;
+        let (new_right, right_removed) = self.do_remove_range(right, None, hi)?;
+        self.retire_node(node);
+        Ok((
+            self.join(new_left, new_right)?,
+            left_removed + right_removed + 1,
+        ))
+    }
+
+    /// Retires every node of the subtree of `node`, and returns the number of them.
+    ///
+    /// The nodes below `node` are not on the path, so each of them is protected by `removed_h`
+    /// only while its children are read, which is enough since they are not read again.
+    fn retire_subtree(&mut self, node: *mut Node<K, V>) -> Result<(*mut Node<K, V>, usize), ()> {
+        let mut stack = vec![node];
+        let mut retired = 0;
+        while let Some(node) = stack.pop() {
+            if Node::is_retired(node) {
+                return Ok((Node::retired_node(), 0));
+            }
+            if node.is_null() {
+                continue;
+            }
+
+            self.removed_h.protect_raw(untagged(node));
+            light_membarrier();
+            self.check_root()?;
+
+            let node_ref = unsafe { &*untagged(node) };
+            stack.push(node_ref.left.load(Ordering::Acquire));
+            stack.push(node_ref.right.load(Ordering::Acquire));
+            self.retire_node(node);
+            retired += 1;
+        }
+        self.removed_h.reset_protection();
+        Ok((ptr::null_mut(), retired))
+    }
+
+    /// Joins `left` and `right`, whose keys are all below those of `right`, into a balanced tree.
+    ///
+    /// The larger one is descended on the side of the other until they are of similar sizes, where
+    /// the leftmost node of `right` is pulled up to join them, and the path is rebalanced back up.
+    fn join(
+        &mut self,
+        left: *mut Node<K, V>,
+        right: *mut Node<K, V>,
+    ) -> Result<*mut Node<K, V>, ()> {
+        if Node::is_retired_spot(left) || Node::is_retired_spot(right) {
+            return Ok(Node::retired_node());
+        }
+
+        if left.is_null() {
+            return Ok(right);
+        }
+        if right.is_null() {
+            return Ok(left);
+        }
+
+        let l_size = Node::node_size(left);
+        let r_size = Node::node_size(right);
+        if l_size > WEIGHT * r_size {
+            let left_ref = unsafe { &*untagged(left) };
+            let (left_left_h, left_right_h) = self.path.take_pair(&mut self.thread);
+            let (left_left, left_right) = left_ref.protect_next(left_left_h, left_right_h);
+            self.check_root()?;
+            let new_right = self.join(left_right, right)?;
+            return self.mk_balanced(left, left_left, new_right);
+        }
+        if r_size > WEIGHT * l_size {
+            let right_ref = unsafe { &*untagged(right) };
+            let (right_left_h, right_right_h) = self.path.take_pair(&mut self.thread);
+            let (right_left, right_right) = right_ref.protect_next(right_left_h, right_right_h);
+            self.check_root()?;
+            let new_left = self.join(left, right_left)?;
+            return self.mk_balanced(right, new_left, right_right);
+        }
+        let (new_right, succ) = self.pull_leftmost(right)?;
+        self.mk_balanced(succ, left, new_right)
+    }
+
     fn pull_leftmost(
         &mut self,
         node: *mut Node<K, V>,
-    ) -> Result<
-        (
-            *mut Node<K, V>,
-            *mut Node<K, V>,
-            Option<HazardPointer<'domain>>,
-        ),
-        (),
-    > {
+    ) -> Result<(*mut Node<K, V>, *mut Node<K, V>), ()> {
         if Node::is_retired_spot(node) {
-            return Ok((Node::retired_node(), Node::retired_node(), None));
+            return Ok((Node::retired_node(), Node::retired_node()));
         }
 
         let node_ref = unsafe { &*untagged(node) };
-        let (mut left_h, mut right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left, right) = node_ref.protect_next(&mut left_h, &mut right_h);
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h);
         self.check_root()?;
 
         if Node::is_retired_spot(left) || Node::is_retired_spot(right) {
-            return Ok((Node::retired_node(), Node::retired_node(), None));
+            return Ok((Node::retired_node(), Node::retired_node()));
         }
 
         if !left.is_null() {
-            let (new_left, succ, _new_left_h) = self.pull_leftmost(left)?;
-            return Ok((self.mk_balanced(node, new_left, right)?, succ, None));
+            let (new_left, succ) = self.pull_leftmost(left)?;
+            return Ok((self.mk_balanced(node, new_left, right)?, succ));
         }
         // node is the leftmost
         let succ = self.mk_node(
@@ -561,39 +659,29 @@ where
             node_ref.value.clone(),
         );
         self.retire_node(node);
-        Ok((right, succ, Some(right_h)))
+        Ok((right, succ))
     }
 
     fn pull_rightmost(
         &mut self,
         node: *mut Node<K, V>,
-    ) -> Result<
-        (
-            *mut Node<K, V>,
-            *mut Node<K, V>,
-            Option<HazardPointer<'domain>>,
-        ),
-        (),
-    > {
+    ) -> Result<(*mut Node<K, V>, *mut Node<K, V>), ()> {
         if Node::is_retired_spot(node) {
-            return Ok((Node::retired_node(), Node::retired_node(), None));
+            return Ok((Node::retired_node(), Node::retired_node()));
         }
 
         let node_ref = unsafe { &*untagged(node) };
-        let (mut left_h, mut right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left, right) = node_ref.protect_next(&mut left_h, &mut right_h);
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h);
         self.check_root()?;
 
         if Node::is_retired_spot(left) || Node::is_retired_spot(right) {
-            return Ok((Node::retired_node(), Node::retired_node(), None));
+            return Ok((Node::retired_node(), Node::retired_node()));
         }
 
         if !right.is_null() {
-            let (new_right, succ, _new_right_h) = self.pull_rightmost(right)?;
-            return Ok((self.mk_balanced(node, left, new_right)?, succ, None));
+            let (new_right, succ) = self.pull_rightmost(right)?;
+            return Ok((self.mk_balanced(node, left, new_right)?, succ));
         }
         // node is the rightmost
         let succ = self.mk_node(
@@ -603,7 +691,7 @@ where
             node_ref.value.clone(),
         );
         self.retire_node(node);
-        Ok((left, succ, Some(left_h)))
+        Ok((left, succ))
     }
 
     pub fn check_root(&self) -> Result<(), ()> {
@@ -750,6 +838,42 @@ where
             state.abort();
         }
     }
+
+    /// Removes the keys in `lo..hi` with one CAS of the root, and returns the number of them.
+    ///
+    /// The new tree shares the subtrees outside of the range with the old one, as the other
+    /// updates do, while the subtrees within the range are retired whole, so a call retires
+    /// all the nodes of the range at once.
+    pub fn remove_range(&self, lo: &K, hi: &K, state: &mut State<'_, K, V>) -> usize {
+        if lo >= hi {
+            return 0;
+        }
+        loop {
+            self.protect_root(state);
+            state.root_link = &self.root;
+            let old_root = state.curr_root;
+            let (new_root, removed) =
+                ok_or!(state.do_remove_range(old_root, Some(lo), Some(hi)), {
+                    state.abort();
+                    continue;
+                });
+            if Node::is_retired(new_root) {
+                state.abort();
+                continue;
+            }
+
+            if self
+                .root
+                .compare_exchange(old_root, new_root, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                state.commit();
+                return removed;
+            }
+
+            state.abort();
+        }
+    }
 }
 
 impl<K, V> Drop for BonsaiTreeMap<K, V> {
@@ -809,6 +933,11 @@ where
     ) -> Option<impl OutputHolder<V>> {
         self.remove(key, handle)
     }
+
+    #[inline(always)]
+    fn remove_range(&self, handle: &mut Self::Handle<'_>, lo: &K, hi: &K) -> usize {
+        self.remove_range(lo, hi, handle)
+    }
 }
 
 #[cfg(test)]
@@ -820,4 +949,19 @@ mod tests {
     fn smoke_bonsai_tree() {
         concurrent_map::tests::smoke::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_bonsai_tree() {
+        concurrent_map::tests::edge_cases::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn pull_stress_bonsai_tree() {
+        concurrent_map::tests::pull_stress::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn remove_range_bonsai_tree() {
+        concurrent_map::tests::remove_range::<BonsaiTreeMap<i32, i32>>();
+    }
 }
@@ -0,0 +1,248 @@
+//! A hash map of bounded size, which evicts keys with CLOCK, an approximation of LRU.
+//!
+//! Each key in the map owns one of `capacity` slots in a ring, and a `get` that finds the key sets
+//! the reference bit of its slot. To make room for a new key, the inserting thread advances the
+//! hand of the clock over the ring, clearing the reference bits on its way, until it reaches a slot
+//! that has not been referenced since the hand passed it last. It then puts the new key in the slot
+//! and evicts the old key of the slot from the map.
+//!
+//! The eviction is approximate under concurrency. A key that is removed and inserted again while
+//! its old slot is being reused may be evicted early, and the map may hold a few more keys than
+//! its capacity while insertions are in flight. Once they finish, every key in the map owns a
+//! distinct slot.
+//!
+//! The inserting thread protects the old key of a slot while it evicts it, and then retires it.
+//! It keeps a copy of its own key to evict it again if the slot was reused meanwhile, as the key
+//! in the slot may already be retired by then.
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::michael_hash_map::Handle as MapHandle;
+use super::HashMap;
+use crossbeam_utils::CachePadded;
+use hp_pp::{HazardPointer, Thread, DEFAULT_DOMAIN};
+use std::hash::Hash;
+use std::ptr::null_mut;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+/// A value in the map, with the slot of its key.
+#[derive(Clone)]
+pub struct Entry<V> {
+    value: V,
+    slot: usize,
+}
+
+struct Slot<K> {
+    key: AtomicPtr<K>,
+    referenced: AtomicBool,
+}
+
+pub struct ClockCache<K, V> {
+    map: HashMap<K, Entry<V>>,
+    slots: Box<[CachePadded<Slot<K>>]>,
+    hand: CachePadded<AtomicUsize>,
+}
+
+pub struct Handle<'domain> {
+    map: MapHandle<'domain>,
+    /// Protects the old key of the slot that the thread claims.
+    slot_h: HazardPointer<'domain>,
+    thread: Box<Thread<'domain>>,
+}
+
+impl Default for Handle<'static> {
+    fn default() -> Self {
+        let mut thread = Box::new(Thread::new(&DEFAULT_DOMAIN));
+        Self {
+            map: MapHandle::default(),
+            slot_h: HazardPointer::new(&mut thread),
+            thread,
+        }
+    }
+}
+
+impl<K, V> ClockCache<K, V>
+where
+    K: Ord + Hash + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    /// Creates an empty cache that holds at most `capacity` keys.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "The capacity of a cache must be positive");
+        Self {
+            map: HashMap::with_capacity(capacity),
+            slots: (0..capacity)
+                .map(|_| {
+                    CachePadded::new(Slot {
+                        key: AtomicPtr::new(null_mut()),
+                        referenced: AtomicBool::new(false),
+                    })
+                })
+                .collect(),
+            hand: CachePadded::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn get(&self, key: &K, handle: &mut Handle<'_>) -> Option<V> {
+        let entry = self.map.get(&mut handle.map, key)?;
+        let entry = entry.output();
+        let referenced = &self.slots[entry.slot].referenced;
+        // Avoids writing to the slot of a hot key on every hit.
+        if !referenced.load(Ordering::Relaxed) {
+            referenced.store(true, Ordering::Relaxed);
+        }
+        Some(entry.value.clone())
+    }
+
+    /// Inserts a key, evicting another one if the cache is full.
+    pub fn insert(&self, key: K, value: V, handle: &mut Handle<'_>) -> bool {
+        // Does not evict a key for an insertion that fails anyway.
+        if self.map.get(&mut handle.map, &key).is_some() {
+            return false;
+        }
+        let (slot, claimed) = self.claim(key.clone(), handle);
+        if !self
+            .map
+            .insert(&mut handle.map, key.clone(), Entry { value, slot })
+        {
+            return false;
+        }
+        fence(Ordering::SeqCst);
+        // Another inserter may have reused the slot before the key was in the map, in which case
+        // it has missed the key in its eviction.
+        if self.slots[slot].key.load(Ordering::Relaxed) != claimed {
+            self.evict(&key, slot, &mut handle.map);
+        }
+        true
+    }
+
+    pub fn remove(&self, key: &K, handle: &mut Handle<'_>) -> Option<V> {
+        // The slot of the key is left as is, and is reused when the hand reaches it.
+        self.map
+            .remove(&mut handle.map, key)
+            .map(|entry| entry.output().value.clone())
+    }
+
+    /// Advances the hand to an unreferenced slot, puts `key` in it, and evicts its old key.
+    fn claim(&self, key: K, handle: &mut Handle<'_>) -> (usize, *mut K) {
+        let key = Box::into_raw(Box::new(key));
+        loop {
+            let index = self.hand.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+            let slot = &self.slots[index];
+            if slot.referenced.swap(false, Ordering::Relaxed) {
+                // Gives the key of the slot a second chance.
+                continue;
+            }
+            let old = handle.slot_h.protect(&slot.key);
+            match slot
+                .key
+                .compare_exchange(old, key, Ordering::AcqRel, Ordering::Relaxed)
+            {
+                Ok(_) => {
+                    if let Some(old_key) = unsafe { old.as_ref() } {
+                        fence(Ordering::SeqCst);
+                        self.evict(old_key, index, &mut handle.map);
+                        handle.slot_h.reset_protection();
+                        unsafe { handle.thread.retire(old) };
+                    }
+                    return (index, key);
+                }
+                // Another inserter has claimed the slot.
+                Err(_) => continue,
+            }
+        }
+    }
+
+    /// Evicts `key` if it still owns the slot `index`.
+    fn evict(&self, key: &K, index: usize, map: &mut MapHandle<'_>) {
+        let owned = self
+            .map
+            .get(map, key)
+            .is_some_and(|entry| entry.output().slot == index);
+        if owned {
+            self.map.remove(map, key);
+        }
+    }
+}
+
+impl<K, V> Drop for ClockCache<K, V> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            let key = *slot.key.get_mut();
+            if !key.is_null() {
+                drop(unsafe { Box::from_raw(key) });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClockCache, Handle};
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
+
+    const THREADS: usize = 30;
+    const CAPACITY: usize = 100;
+    const ELEMENTS: i32 = 1000;
+    const OPS_PER_THREADS: usize = 10000;
+
+    #[test]
+    fn evict_in_clock_order() {
+        let cache = &ClockCache::<i32, String>::with_capacity(CAPACITY);
+        let handle = &mut Handle::default();
+        for i in 0..ELEMENTS {
+            assert!(cache.insert(i, i.to_string(), handle));
+        }
+        // Nothing has been referenced, so the last keys remain.
+        for i in 0..ELEMENTS {
+            let expected = (i >= ELEMENTS - CAPACITY as i32).then(|| i.to_string());
+            assert_eq!(cache.get(&i, handle), expected);
+        }
+
+        // All keys have been referenced, so the hand clears the reference bits in a lap and then
+        // evicts the oldest key.
+        assert!(cache.insert(ELEMENTS, ELEMENTS.to_string(), handle));
+        assert_eq!(cache.get(&ELEMENTS, handle), Some(ELEMENTS.to_string()));
+        assert_eq!(cache.get(&(ELEMENTS - CAPACITY as i32), handle), None);
+        let present = (0..=ELEMENTS)
+            .filter(|i| cache.get(i, handle).is_some())
+            .count();
+        assert_eq!(present, CAPACITY);
+    }
+
+    #[test]
+    fn smoke_clock_cache() {
+        let cache = &ClockCache::<i32, String>::with_capacity(CAPACITY);
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let handle = &mut Handle::default();
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..OPS_PER_THREADS {
+                        let key = rng.gen_range(0..ELEMENTS);
+                        match cache.get(&key, handle) {
+                            Some(value) => assert_eq!(key.to_string(), value),
+                            None => {
+                                cache.insert(key, key.to_string(), handle);
+                            }
+                        }
+                        if rng.gen_ratio(1, 10) {
+                            cache.remove(&key, handle);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let handle = &mut Handle::default();
+        let present = (0..ELEMENTS)
+            .filter(|i| cache.get(i, handle).is_some())
+            .count();
+        assert!(present <= CAPACITY);
+    }
+}
@@ -0,0 +1,143 @@
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A concurrent multiset, which counts the occurrences of each key.
+pub trait ConcurrentCounter<K> {
+    type Handle<'domain>;
+
+    fn new() -> Self;
+
+    fn handle() -> Self::Handle<'static>;
+
+    /// Returns the number of occurrences of `key`.
+    fn count(&self, handle: &mut Self::Handle<'_>, key: &K) -> usize;
+
+    /// Adds an occurrence of `key`, and returns the new count.
+    fn increment(&self, handle: &mut Self::Handle<'_>, key: K) -> usize;
+
+    /// Removes an occurrence of `key` if there is one, and returns the new count.
+    fn decrement(&self, handle: &mut Self::Handle<'_>, key: &K) -> usize;
+}
+
+/// A multiset backed by a map from the keys to atomic counters.
+///
+/// The node of a key is inserted on its first increment and is never removed, even when its count
+/// drops to zero, so the counting operations only protect and update the nodes in place and never
+/// retire one. This isolates the cost of the protection of the traversals.
+pub struct CounterMap<M> {
+    inner: M,
+}
+
+impl<K, M> ConcurrentCounter<K> for CounterMap<M>
+where
+    K: Clone,
+    M: ConcurrentMap<K, AtomicUsize>,
+{
+    type Handle<'domain> = M::Handle<'domain>;
+
+    fn new() -> Self {
+        CounterMap { inner: M::new() }
+    }
+
+    fn handle() -> Self::Handle<'static> {
+        M::handle()
+    }
+
+    #[inline(always)]
+    fn count(&self, handle: &mut Self::Handle<'_>, key: &K) -> usize {
+        self.inner
+            .get(handle, key)
+            .map_or(0, |counter| counter.output().load(Ordering::Relaxed))
+    }
+
+    #[inline(always)]
+    fn increment(&self, handle: &mut Self::Handle<'_>, key: K) -> usize {
+        loop {
+            if let Some(counter) = self.inner.get(handle, &key) {
+                return counter.output().fetch_add(1, Ordering::Relaxed) + 1;
+            }
+            // Another thread may insert the counter first, which is then incremented instead.
+            if self.inner.insert(handle, key.clone(), AtomicUsize::new(1)) {
+                return 1;
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn decrement(&self, handle: &mut Self::Handle<'_>, key: &K) -> usize {
+        let Some(counter) = self.inner.get(handle, key) else {
+            return 0;
+        };
+        counter
+            .output()
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                count.checked_sub(1)
+            })
+            .map_or(0, |count| count - 1)
+    }
+}
+
+pub type HashMapCounter<K> = CounterMap<HashMap<K, AtomicUsize>>;
+
+#[cfg(test)]
+pub mod tests {
+    use super::{ConcurrentCounter, HashMapCounter};
+    use crossbeam_utils::thread;
+
+    const THREADS: i32 = 30;
+    const KEYS: i32 = 100;
+    const INCREMENTS_PER_KEY: usize = 100;
+
+    pub fn smoke<C>()
+    where
+        C: ConcurrentCounter<i32> + Send + Sync,
+    {
+        let counter = &C::new();
+
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut handle = C::handle();
+                    for _ in 0..INCREMENTS_PER_KEY {
+                        for key in 0..KEYS {
+                            assert!(counter.increment(&mut handle, key) > 0);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let mut handle = C::handle();
+        let total = THREADS as usize * INCREMENTS_PER_KEY;
+        for key in 0..KEYS {
+            assert_eq!(counter.count(&mut handle, &key), total);
+        }
+        assert_eq!(counter.count(&mut handle, &KEYS), 0);
+
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut handle = C::handle();
+                    for _ in 0..INCREMENTS_PER_KEY {
+                        for key in 0..KEYS {
+                            counter.decrement(&mut handle, &key);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        for key in 0..KEYS {
+            assert_eq!(counter.count(&mut handle, &key), 0);
+            assert_eq!(counter.decrement(&mut handle, &key), 0);
+        }
+    }
+
+    #[test]
+    fn smoke_hash_map_counter() {
+        smoke::<HashMapCounter<i32>>();
+    }
+}
@@ -0,0 +1,28 @@
+use crate::ds_impl::deque::Steal;
+
+/// A concurrent work-stealing deque, whose owner pushes and pops at the bottom while the other
+/// threads steal from the top.
+pub trait ConcurrentDeque<T> {
+    type Handle<'domain>;
+
+    fn new() -> Self;
+
+    fn handle() -> Self::Handle<'static>;
+
+    /// Pushes `item` at the bottom.
+    ///
+    /// # Safety
+    ///
+    /// Only the owner may push and pop, i.e., no two threads may push or pop concurrently.
+    unsafe fn push(&self, handle: &mut Self::Handle<'_>, item: T);
+
+    /// Removes the last pushed item, if there is one.
+    ///
+    /// # Safety
+    ///
+    /// Only the owner may push and pop, i.e., no two threads may push or pop concurrently.
+    unsafe fn pop(&self, handle: &mut Self::Handle<'_>) -> Option<T>;
+
+    /// Removes the first item, unless the deque is empty or another thread takes it first.
+    fn steal(&self, handle: &mut Self::Handle<'_>) -> Steal<T>;
+}
@@ -1,3 +1,5 @@
+use hp_pp::{HazardPointer, Thread};
+
 pub trait OutputHolder<V> {
     fn output(&self) -> &V;
 }
@@ -34,6 +36,97 @@ pub trait ConcurrentMap<K, V> {
         handle: &'hp mut Self::Handle<'_>,
         key: &'hp K,
     ) -> Option<impl OutputHolder<V>>;
+
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    ///
+    /// By default, the key is looked up and then inserted, which is retried if another thread
+    /// inserts the key in between. The lists and the hash map replace this with one traversal,
+    /// which finds either the node of the key or the position of the new node.
+    fn get_or_insert(&self, handle: &mut Self::Handle<'_>, key: K, value: V) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if let Some(found) = self.get(handle, &key) {
+                return Some(found.output().clone());
+            }
+            if self.insert(handle, key.clone(), value.clone()) {
+                return None;
+            }
+        }
+    }
+
+    /// Looks up `keys` in a batch, and returns the number of them that are found, for the
+    /// structures that support it.
+    fn multi_get(&self, _handle: &mut Self::Handle<'_>, _keys: &[K]) -> usize {
+        panic!("Unsupported(or unimplemented) multi-get")
+    }
+
+    /// Removes the keys in `lo..hi`, and returns the number of them that are removed, for the
+    /// structures that support it.
+    fn remove_range(&self, _handle: &mut Self::Handle<'_>, _lo: &K, _hi: &K) -> usize {
+        panic!("Unsupported(or unimplemented) range removal")
+    }
+
+    /// Scans up to `items` elements in the order of the keys from `key`, and returns the number of
+    /// them that are scanned, for the structures that support it.
+    fn partial_scan(&self, _handle: &mut Self::Handle<'_>, _key: &K, _items: usize) -> usize {
+        panic!("Unsupported(or unimplemented) partial scan")
+    }
+}
+
+/// A set of hazard pointers that grows with the depth of an operation, for the structures whose
+/// operations protect a number of nodes that depends on the height of the structure, e.g., the two
+/// children of each node on the path of a tree.
+///
+/// The slots are taken in order during an operation and released together at its end. They stay
+/// in the set for the next operations, so a thread adds slots only when an operation goes deeper
+/// than every operation before it.
+pub struct ProtectionSet<'domain> {
+    slots: Vec<HazardPointer<'domain>>,
+    taken: usize,
+}
+
+impl<'domain> Default for ProtectionSet<'domain> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'domain> ProtectionSet<'domain> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            taken: 0,
+        }
+    }
+
+    /// Takes the next two slots, and adds them to the set if every slot is taken.
+    pub fn take_pair(
+        &mut self,
+        thread: &mut Thread<'domain>,
+    ) -> (&mut HazardPointer<'domain>, &mut HazardPointer<'domain>) {
+        while self.slots.len() < self.taken + 2 {
+            self.slots.push(HazardPointer::new(thread));
+        }
+        let (first, second) = self.slots[self.taken..].split_at_mut(1);
+        self.taken += 2;
+        (&mut first[0], &mut second[0])
+    }
+
+    /// Resets the taken slots, and keeps them for the next operation.
+    pub fn release(&mut self) {
+        for slot in &mut self.slots[..self.taken] {
+            slot.reset_protection();
+        }
+        self.taken = 0;
+    }
+
+    /// The number of slots in the set.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
 }
 
 #[cfg(test)]
@@ -42,10 +135,13 @@ pub mod tests {
     use super::{ConcurrentMap, OutputHolder};
     use crossbeam_utils::thread;
     use rand::prelude::*;
+    use std::collections::VecDeque;
     use std::fmt::Debug;
 
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
+    const PULL_KEYS: i32 = 200;
+    const PULL_ROUNDS: i32 = 5;
 
     pub fn smoke<V, M, F>(to_value: &F)
     where
@@ -103,4 +199,340 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Checks that the holders of `get` and `remove` keep their values until their handles are
+    /// used again, while other handles remove the keys, insert them again with other values, and
+    /// reclaim their garbage. The holder of a handle borrows it, so a handle that is used again
+    /// gives a holder of the new operation.
+    pub fn output_holder<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let handle = &mut M::handle();
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(handle, i, to_value(&i)));
+        }
+
+        let get_handle = &mut M::handle();
+        let remove_handle = &mut M::handle();
+        let got = map.get(get_handle, &0).unwrap();
+        let removed = map.remove(remove_handle, &1).unwrap();
+        assert!(map.remove(handle, &0).is_some());
+        assert!(map.insert(handle, 0, to_value(&-1)));
+        assert_eq!(to_value(&-1), *map.get(handle, &0).unwrap().output());
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                // The garbage of the handle is reclaimed at the latest when it is dropped.
+                let mut handle = M::handle();
+                for i in 0..ELEMENTS_PER_THREADS {
+                    map.remove(&mut handle, &i);
+                    assert!(map.insert(&mut handle, i, to_value(&(i + 1))));
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(to_value(&0), *got.output());
+        assert_eq!(to_value(&1), *removed.output());
+        assert_eq!(to_value(&3), *map.get(get_handle, &2).unwrap().output());
+    }
+
+    /// Races the get-or-inserts of the same keys from each thread with a value of its own, and
+    /// checks that every thread gets the value of the one insert of each key.
+    pub fn get_or_insert<M>()
+    where
+        M: ConcurrentMap<i32, i32> + Send + Sync,
+    {
+        let map = &M::new();
+        let values = thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|t| {
+                    s.spawn(move |_| {
+                        let handle = &mut M::handle();
+                        (0..PULL_KEYS)
+                            .map(|key| map.get_or_insert(handle, key, t).unwrap_or(t))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+
+        let handle = &mut M::handle();
+        for key in 0..PULL_KEYS {
+            let value = *map.get(handle, &key).unwrap().output();
+            assert!(values.iter().all(|values| values[key as usize] == value));
+        }
+    }
+
+    /// Removes a range of the keys of a prefilled map, and checks that exactly the keys of the range
+    /// are removed. Then removes the range over and over while the other threads remove and insert
+    /// the keys in it and insert the keys around it, and checks that the keys around it are never
+    /// lost.
+    pub fn remove_range<M>()
+    where
+        M: ConcurrentMap<i32, i32> + Send + Sync,
+    {
+        let keys = THREADS * PULL_KEYS;
+        let (lo, hi) = (keys / 4, 3 * keys / 4);
+        let map = &M::new();
+        let handle = &mut M::handle();
+        let mut shuffled = (0..keys).collect::<Vec<_>>();
+        shuffled.shuffle(&mut rand::thread_rng());
+        for &key in &shuffled {
+            assert!(map.insert(handle, key, key));
+        }
+
+        assert_eq!(map.remove_range(handle, &hi, &lo), 0);
+        assert_eq!(map.remove_range(handle, &lo, &hi), (hi - lo) as usize);
+        assert_eq!(map.remove_range(handle, &lo, &hi), 0);
+        for key in 0..keys {
+            assert_eq!(map.get(handle, &key).is_some(), !(lo..hi).contains(&key));
+        }
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                let handle = &mut M::handle();
+                for _ in 0..PULL_KEYS {
+                    map.remove_range(handle, &lo, &hi);
+                }
+            });
+            for _ in 0..THREADS / 4 {
+                s.spawn(move |_| {
+                    let handle = &mut M::handle();
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..ELEMENTS_PER_THREADS * 10 {
+                        let key = rng.gen_range(0..keys);
+                        if (lo..hi).contains(&key) && rng.gen() {
+                            if let Some(value) = map.remove(handle, &key) {
+                                assert_eq!(*value.output(), key);
+                            }
+                        } else {
+                            map.insert(handle, key, key);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let remaining = (lo..hi)
+            .filter(|key| map.get(handle, key).is_some())
+            .count();
+        assert_eq!(map.remove_range(handle, &lo, &hi), remaining);
+        for key in 0..keys {
+            let expected = (!(lo..hi).contains(&key)).then_some(key);
+            assert_eq!(map.get(handle, &key).map(|value| *value.output()), expected);
+        }
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let handle = &mut M::handle();
+
+        // An empty map.
+        assert!(map.get(handle, &0).is_none());
+        assert!(map.remove(handle, &0).is_none());
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(handle, 0, to_value(&0)));
+        assert!(!map.insert(handle, 0, to_value(&1)));
+        assert_eq!(to_value(&0), *map.get(handle, &0).unwrap().output());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert_eq!(to_value(&0), *map.remove(handle, &0).unwrap().output());
+        assert!(map.get(handle, &0).is_none());
+        assert!(map.remove(handle, &0).is_none());
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(handle, i, to_value(&i)));
+            }
+            assert_eq!(
+                to_value(&removed),
+                *map.remove(handle, &removed).unwrap().output()
+            );
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(handle, &i).is_some());
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert_eq!(to_value(&i), *map.remove(handle, &i).unwrap().output());
+            }
+        }
+        for i in 0..=3 {
+            assert!(map.get(handle, &i).is_none());
+        }
+    }
+
+    /// Removes the keys of a tree in the order of the levels of a balanced tree, medians first, and
+    /// inserts them back in the same order, for a few rounds. Most keys then have two children when
+    /// they are removed, which pulls the rightmost node of the left subtree up into their place, and
+    /// the rest have a child on one side, which pulls the leftmost node of the right subtree. Both
+    /// rebuild the path to the pulled node, which the uniform keys of `smoke` rarely do. The tree is
+    /// prefilled in an increasing order, which skews it until it is rebalanced. Each thread owns
+    /// every `THREADS`-th key, and checks that its keys next to each removed key keep their values.
+    pub fn pull_stress<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let mut handle = M::handle();
+        for i in 0..THREADS * PULL_KEYS {
+            assert!(map.insert(&mut handle, i, to_value(&i)));
+        }
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut handle = M::handle();
+                    let keys = medians_first(PULL_KEYS);
+                    for _ in 0..PULL_ROUNDS {
+                        let mut removed = vec![false; PULL_KEYS as usize];
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert_eq!(
+                                to_value(&i),
+                                *map.remove(&mut handle, &i).unwrap().output()
+                            );
+                            removed[k as usize] = true;
+                            for n in [k - 1, k + 1] {
+                                if (0..PULL_KEYS).contains(&n) && !removed[n as usize] {
+                                    let j = n * THREADS + t;
+                                    assert_eq!(
+                                        to_value(&j),
+                                        *map.get(&mut handle, &j).unwrap().output()
+                                    );
+                                }
+                            }
+                        }
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.insert(&mut handle, i, to_value(&i)));
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let mut handle = M::handle();
+        for j in 0..THREADS * PULL_KEYS {
+            assert_eq!(to_value(&j), *map.get(&mut handle, &j).unwrap().output());
+        }
+    }
+
+    /// The keys in `0..n` in the order of the levels of a balanced tree, medians first.
+    fn medians_first(n: i32) -> Vec<i32> {
+        let mut keys = Vec::with_capacity(n as usize);
+        let mut ranges = VecDeque::from([(0, n)]);
+        while let Some((lo, hi)) = ranges.pop_front() {
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                keys.push(mid);
+                ranges.push_back((lo, mid));
+                ranges.push_back((mid + 1, hi));
+            }
+        }
+        keys
+    }
+
+    #[cfg(feature = "shuttle")]
+    const SHUTTLE_THREADS: i32 = 3;
+    #[cfg(feature = "shuttle")]
+    const SHUTTLE_ELEMENTS_PER_THREADS: i32 = 4;
+
+    /// Runs a few threads of `insert`, `get`, and `remove` on interleaved key ranges under
+    /// `shuttle`, for `iterations` random schedules.
+    ///
+    /// Each thread inserts its keys and then removes every other one, checking each result on
+    /// the way. The structure is checked at the end of each schedule.
+    #[cfg(feature = "shuttle")]
+    pub fn shuttle<V, M>(to_value: fn(&i32) -> V, iterations: usize)
+    where
+        V: Eq + Debug + 'static,
+        M: ConcurrentMap<i32, V> + Send + Sync + 'static,
+    {
+        use std::sync::Arc;
+
+        crate::sched::enable_yield_points();
+        shuttle::check_random(
+            move || {
+                let map = Arc::new(M::new());
+                let threads = (0..SHUTTLE_THREADS)
+                    .map(|t| {
+                        let map = map.clone();
+                        shuttle::thread::spawn(move || {
+                            let mut handle = M::handle();
+                            let keys =
+                                (0..SHUTTLE_ELEMENTS_PER_THREADS).map(|k| k * SHUTTLE_THREADS + t);
+                            for i in keys.clone() {
+                                assert!(map.insert(&mut handle, i, to_value(&i)));
+                                assert_eq!(
+                                    to_value(&i),
+                                    *map.get(&mut handle, &i).unwrap().output()
+                                );
+                            }
+                            for i in keys.step_by(2) {
+                                assert_eq!(
+                                    to_value(&i),
+                                    *map.remove(&mut handle, &i).unwrap().output()
+                                );
+                                assert!(map.get(&mut handle, &i).is_none());
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                for thread in threads {
+                    thread.join().unwrap();
+                }
+
+                let mut handle = M::handle();
+                for k in 0..SHUTTLE_ELEMENTS_PER_THREADS {
+                    for t in 0..SHUTTLE_THREADS {
+                        let i = k * SHUTTLE_THREADS + t;
+                        assert_eq!(k % 2 == 1, map.get(&mut handle, &i).is_some());
+                    }
+                }
+            },
+            iterations,
+        );
+    }
+
+    #[test]
+    fn protection_set() {
+        use super::ProtectionSet;
+        use hp_pp::{Thread, DEFAULT_DOMAIN};
+
+        let mut thread = Box::new(Thread::new(&DEFAULT_DOMAIN));
+        let mut set = ProtectionSet::new();
+        for _ in 0..3 {
+            set.take_pair(&mut thread);
+        }
+        assert_eq!(set.capacity(), 6);
+        // A shallower operation reuses the slots of the deeper one.
+        set.release();
+        set.take_pair(&mut thread);
+        assert_eq!(set.capacity(), 6);
+        set.release();
+    }
 }
@@ -0,0 +1,147 @@
+use super::concurrent_map::ConcurrentMap;
+use super::{HHSList, HList, HMList, SkipList};
+
+pub trait ConcurrentSet<K> {
+    type Handle<'domain>;
+
+    fn new() -> Self;
+
+    fn handle() -> Self::Handle<'static>;
+
+    fn contains(&self, handle: &mut Self::Handle<'_>, key: &K) -> bool;
+
+    fn insert(&self, handle: &mut Self::Handle<'_>, key: K) -> bool;
+
+    fn remove(&self, handle: &mut Self::Handle<'_>, key: &K) -> bool;
+}
+
+/// A set backed by a map whose nodes carry no value.
+///
+/// Since the value type is `()`, the nodes of the underlying structure only hold a key
+/// and the links, so a set does not pay for the memory traffic of an unused value.
+pub struct KeySet<M> {
+    inner: M,
+}
+
+impl<K, M> ConcurrentSet<K> for KeySet<M>
+where
+    M: ConcurrentMap<K, ()>,
+{
+    type Handle<'domain> = M::Handle<'domain>;
+
+    fn new() -> Self {
+        KeySet { inner: M::new() }
+    }
+
+    fn handle() -> Self::Handle<'static> {
+        M::handle()
+    }
+
+    #[inline(always)]
+    fn contains(&self, handle: &mut Self::Handle<'_>, key: &K) -> bool {
+        self.inner.get(handle, key).is_some()
+    }
+
+    #[inline(always)]
+    fn insert(&self, handle: &mut Self::Handle<'_>, key: K) -> bool {
+        self.inner.insert(handle, key, ())
+    }
+
+    #[inline(always)]
+    fn remove(&self, handle: &mut Self::Handle<'_>, key: &K) -> bool {
+        self.inner.remove(handle, key).is_some()
+    }
+}
+
+pub type HListSet<K> = KeySet<HList<K, ()>>;
+pub type HMListSet<K> = KeySet<HMList<K, ()>>;
+pub type HHSListSet<K> = KeySet<HHSList<K, ()>>;
+pub type SkipListSet<K> = KeySet<SkipList<K, ()>>;
+
+#[cfg(test)]
+pub mod tests {
+    extern crate rand;
+    use super::{ConcurrentSet, HHSListSet, HListSet, HMListSet, SkipListSet};
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
+
+    const THREADS: i32 = 30;
+    const ELEMENTS_PER_THREADS: i32 = 1000;
+
+    pub fn smoke<S>()
+    where
+        S: ConcurrentSet<i32> + Send + Sync,
+    {
+        let set = &S::new();
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut handle = S::handle();
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert!(set.insert(&mut handle, i));
+                        assert!(!set.insert(&mut handle, i));
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        thread::scope(|s| {
+            for t in 0..(THREADS / 2) {
+                s.spawn(move |_| {
+                    let mut handle = S::handle();
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert!(set.remove(&mut handle, &i));
+                        assert!(!set.contains(&mut handle, &i));
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        thread::scope(|s| {
+            for t in (THREADS / 2)..THREADS {
+                s.spawn(move |_| {
+                    let mut handle = S::handle();
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert!(set.contains(&mut handle, &i));
+                    }
+                });
+            }
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn smoke_h_list_set() {
+        smoke::<HListSet<i32>>();
+    }
+
+    #[test]
+    fn smoke_hm_list_set() {
+        smoke::<HMListSet<i32>>();
+    }
+
+    #[test]
+    fn smoke_hhs_list_set() {
+        smoke::<HHSListSet<i32>>();
+    }
+
+    #[test]
+    fn smoke_skip_list_set() {
+        smoke::<SkipListSet<i32>>();
+    }
+}
@@ -0,0 +1,13 @@
+/// A concurrent LIFO stack.
+pub trait ConcurrentStack<T> {
+    type Handle<'domain>;
+
+    fn new() -> Self;
+
+    fn handle() -> Self::Handle<'static>;
+
+    fn push(&self, handle: &mut Self::Handle<'_>, item: T);
+
+    /// Removes the last pushed item, if there is one.
+    fn pop(&self, handle: &mut Self::Handle<'_>) -> Option<T>;
+}
@@ -6,7 +6,7 @@ use std::{
 use crossbeam_utils::CachePadded;
 use hp_pp::{light_membarrier, HazardPointer, Thread, DEFAULT_DOMAIN};
 
-struct Node<T> {
+pub(crate) struct Node<T> {
     item: Option<T>,
     prev: *mut Node<T>,
     next: CachePadded<AtomicPtr<Node<T>>>,
@@ -881,4 +881,9 @@ mod tests {
     fn smoke_efrb_tree() {
         concurrent_map::tests::smoke::<_, EFRBTree<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_efrb_tree() {
+        concurrent_map::tests::edge_cases::<_, EFRBTree<i32, String>, _>(&i32::to_string);
+    }
 }
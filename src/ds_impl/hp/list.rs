@@ -1,6 +1,9 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
 
 use super::pointers::{Atomic, Pointer, Shared};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 use core::mem;
 use std::cmp::Ordering::{Equal, Greater, Less};
 use std::sync::atomic::Ordering;
@@ -46,6 +49,8 @@ pub struct Handle<'domain> {
     // `anchor_h` and `anchor_next_h` are used for `find_harris`
     anchor_h: HazardPointer<'domain>,
     anchor_next_h: HazardPointer<'domain>,
+    // `iter_h` protects the node that an `Iter` yielded last.
+    iter_h: HazardPointer<'domain>,
     thread: Box<Thread<'domain>>,
 }
 
@@ -57,6 +62,7 @@ impl Default for Handle<'static> {
             curr_h: HazardPointer::new(&mut thread),
             anchor_h: HazardPointer::new(&mut thread),
             anchor_next_h: HazardPointer::new(&mut thread),
+            iter_h: HazardPointer::new(&mut thread),
             thread,
         }
     }
@@ -100,7 +106,7 @@ where
     /// Optimistically traverses while maintaining `anchor` and `anchor_next`.
     /// It is used for both Harris and Harris-Herlihy-Shavit traversals.
     #[inline]
-    fn traverse_with_anchor(&mut self, key: &K) -> Result<bool, ()> {
+    fn traverse_with_anchor(&mut self, key: &K) -> Result<bool, Retry> {
         // Invariants:
         // anchor, anchor_next: protected if they are not null.
         // prev: always protected with prev_sh
@@ -110,6 +116,7 @@ where
             if self.curr.is_null() {
                 break false;
             }
+            wait_free::visit();
 
             let prev_next = unsafe { &self.prev.deref().next };
             self.handle
@@ -130,7 +137,7 @@ where
                 let an_new = unsafe { &self.anchor.deref().next }.load(Ordering::Acquire);
 
                 if an_new.tag() != 0 {
-                    return Err(());
+                    return Err(Retry::Validation);
                 } else if an_new != self.anchor_next {
                     // Anchor is updated but clear, so can restart from anchor.
 
@@ -150,7 +157,7 @@ where
 
                 if curr_new.tag() != 0 {
                     // If prev is marked, then restart from head.
-                    return Err(());
+                    return Err(Retry::Validation);
                 } else if curr_new != self.curr {
                     // self.curr's tag was 0, so the above comparison ignores tags.
 
@@ -188,7 +195,7 @@ where
     }
 
     #[inline]
-    fn find_harris(&mut self, key: &K) -> Result<bool, ()> {
+    fn find_harris(&mut self, key: &K) -> Result<bool, Retry> {
         // Finding phase
         // - cursor.curr: first unmarked node w/ key >= search key (4)
         // - cursor.prev: the ref of .next in previous unmarked node (1 -> 2)
@@ -212,7 +219,7 @@ where
                 )
                 .map_err(|_| {
                     self.curr = self.curr.with_tag(0);
-                    ()
+                    Retry::Unlink
                 })?;
 
             let mut node = self.anchor_next;
@@ -231,7 +238,7 @@ where
     }
 
     #[inline]
-    fn find_harris_michael(&mut self, key: &K) -> Result<bool, ()> {
+    fn find_harris_michael(&mut self, key: &K) -> Result<bool, Retry> {
         loop {
             debug_assert_eq!(self.curr.tag(), 0);
             if self.curr.is_null() {
@@ -246,7 +253,7 @@ where
             light_membarrier();
             let curr_new = prev.load(Ordering::Acquire);
             if curr_new.tag() != 0 {
-                return Err(());
+                return Err(Retry::Validation);
             } else if curr_new.with_tag(0) != self.curr {
                 // In contrary to what HP04 paper does, it's fine to retry protecting the new node
                 // without restarting from head as long as prev is not logically deleted.
@@ -278,13 +285,14 @@ where
             {
                 unsafe { self.handle.thread.retire(self.curr.with_tag(0).into_raw()) };
             } else {
-                return Err(());
+                return Err(Retry::Unlink);
             }
             self.curr = next.with_tag(0);
         }
     }
 
-    fn find_harris_herlihy_shavit(&mut self, key: &K) -> Result<bool, ()> {
+    fn find_harris_herlihy_shavit(&mut self, key: &K) -> Result<bool, Retry> {
+        wait_free::traversal();
         let found = self.traverse_with_anchor(key)?;
         // Return only the found `curr` node.
         // Others are not necessary because we are not going to do insertion or deletion
@@ -313,14 +321,14 @@ where
         handle: &'hp mut Handle<'domain>,
     ) -> Option<&'hp V>
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         loop {
             let mut cursor = Cursor::new(&self.head, handle.launder());
             match find(&mut cursor, key) {
                 Ok(true) => return Some(&unsafe { cursor.curr.deref() }.value),
                 Ok(false) => return None,
-                Err(_) => continue,
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -330,28 +338,28 @@ where
         mut node: Box<Node<K, V>>,
         find: &F,
         handle: &'hp mut Handle<'domain>,
-    ) -> bool
+    ) -> Option<&'hp V>
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         loop {
             let mut cursor = Cursor::new(&self.head, handle.launder());
-            let Ok(found) = find(&mut cursor, &node.key) else {
-                continue;
-            };
+            let found = ok_or_retry!(find(&mut cursor, &node.key), continue);
             if found {
-                return false;
+                return Some(&unsafe { cursor.curr.deref() }.value);
             }
 
             node.next = cursor.curr.into();
-            match unsafe { cursor.prev.deref() }.next.compare_exchange(
-                cursor.curr,
-                node,
-                Ordering::Release,
-                Ordering::Relaxed,
-            ) {
-                Ok(_) => return true,
-                Err(e) => node = e.new,
+            match unsafe { cursor.prev.deref() }
+                .next
+                .compare_exchange(cursor.curr, node, Ordering::Release, Ordering::Relaxed)
+                .classify(&cursor.curr)
+            {
+                Cas::Success(_) => return None,
+                Cas::CurrentDiffers(e) | Cas::TagDiffers(e) => {
+                    Retry::CasFail.count();
+                    node = e.new;
+                }
             }
         }
     }
@@ -365,7 +373,23 @@ where
         handle: &'hp mut Handle<'domain>,
     ) -> bool
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
+    {
+        self.get_or_insert(key, value, find, handle).is_none()
+    }
+
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`,
+    /// with the same traversal that finds the position of the new node.
+    #[inline]
+    fn get_or_insert<'domain, 'hp, F>(
+        &self,
+        key: K,
+        value: V,
+        find: F,
+        handle: &'hp mut Handle<'domain>,
+    ) -> Option<&'hp V>
+    where
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         let node = Box::new(Node {
             key,
@@ -373,7 +397,7 @@ where
             next: Atomic::null(),
         });
 
-        self.insert_inner(node, &find, handle.launder())
+        self.insert_inner(node, &find, handle)
     }
 
     fn remove_inner<'domain, 'hp, F>(
@@ -383,13 +407,11 @@ where
         handle: &'hp mut Handle<'domain>,
     ) -> Option<&'hp V>
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         loop {
             let mut cursor = Cursor::new(&self.head, handle.launder());
-            let Ok(found) = find(&mut cursor, key) else {
-                continue;
-            };
+            let found = ok_or_retry!(find(&mut cursor, key), continue);
             if !found {
                 return None;
             }
@@ -397,6 +419,7 @@ where
             let curr_node = unsafe { cursor.curr.deref() };
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel);
             if next.tag() == 1 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -426,13 +449,16 @@ where
         handle: &'hp mut Handle<'domain>,
     ) -> Option<&'hp V>
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         self.remove_inner(key, &find, handle.launder())
     }
 
     #[inline]
-    fn pop_inner<'hp>(&self, handle: &'hp mut Handle<'_>) -> Result<Option<(&'hp K, &'hp V)>, ()> {
+    fn pop_inner<'hp>(
+        &self,
+        handle: &'hp mut Handle<'_>,
+    ) -> Result<Option<(&'hp K, &'hp V)>, Retry> {
         let cursor = Cursor::new(&self.head, handle.launder());
         let prev = unsafe { &cursor.prev.deref().next };
 
@@ -442,7 +468,7 @@ where
         light_membarrier();
         let curr_new = prev.load(Ordering::Acquire);
         if curr_new.tag() != 0 || curr_new.with_tag(0) != cursor.curr {
-            return Err(());
+            return Err(Retry::Validation);
         }
 
         if cursor.curr.is_null() {
@@ -453,7 +479,7 @@ where
 
         let next = curr_node.next.fetch_or(1, Ordering::AcqRel);
         if next.tag() == 1 {
-            return Err(());
+            return Err(Retry::CasFail);
         }
 
         if prev
@@ -471,11 +497,43 @@ where
         loop {
             match self.pop_inner(handle.launder()) {
                 Ok(r) => return r,
-                Err(_) => continue,
+                Err(retry) => retry.count(),
             }
         }
     }
 
+    /// Calls `f` with the key and value of each node that is not logically removed, in the order
+    /// of the keys.
+    ///
+    /// # Safety
+    ///
+    /// The nodes are not protected, so no node may be removed from the list during the call.
+    pub unsafe fn for_each<F: FnMut(&K, &V)>(&self, mut f: F) {
+        let mut curr = self.head.load(Ordering::Acquire);
+        while let Some(node) = unsafe { curr.with_tag(0).as_ref() } {
+            let next = node.next.load(Ordering::Acquire);
+            if next.tag() == 0 {
+                f(&node.key, &node.value);
+            }
+            curr = next;
+        }
+    }
+
+    /// Returns an iterator over the elements whose keys are not less than `key`, in the order of
+    /// the keys. See [`Iter`] for what it yields while the list is modified concurrently.
+    pub fn iter_from<'domain, 'hp>(
+        &'hp self,
+        key: &'hp K,
+        handle: &'hp mut Handle<'domain>,
+    ) -> Iter<'domain, 'hp, K, V> {
+        Iter {
+            head: &self.head,
+            from: key,
+            last: Shared::null(),
+            cursor: Cursor::new(&self.head, handle),
+        }
+    }
+
     pub fn harris_get<'hp>(&self, key: &K, handle: &'hp mut Handle<'_>) -> Option<&'hp V> {
         self.get(key, Cursor::find_harris, handle)
     }
@@ -484,6 +542,15 @@ where
         self.insert(key, value, Cursor::find_harris, handle)
     }
 
+    pub fn harris_get_or_insert<'hp>(
+        &self,
+        key: K,
+        value: V,
+        handle: &'hp mut Handle<'_>,
+    ) -> Option<&'hp V> {
+        self.get_or_insert(key, value, Cursor::find_harris, handle)
+    }
+
     pub fn harris_remove<'hp>(&self, key: &K, handle: &'hp mut Handle<'_>) -> Option<&'hp V> {
         self.remove(key, Cursor::find_harris, handle)
     }
@@ -496,6 +563,15 @@ where
         self.insert(key, value, Cursor::find_harris_michael, handle)
     }
 
+    pub fn harris_michael_get_or_insert<'hp>(
+        &self,
+        key: K,
+        value: V,
+        handle: &'hp mut Handle<'_>,
+    ) -> Option<&'hp V> {
+        self.get_or_insert(key, value, Cursor::find_harris_michael, handle)
+    }
+
     pub fn harris_michael_remove<'hp>(
         &self,
         key: &K,
@@ -511,6 +587,123 @@ where
     ) -> Option<&'hp V> {
         self.get(key, Cursor::find_harris_herlihy_shavit, handle)
     }
+
+    /// Looks up the keys of `requests`, which must be sorted in an increasing order of the keys,
+    /// with one Harris-Herlihy-Shavit traversal, and calls `found` with the index of each request
+    /// and the value of its key. The value is protected only during the call.
+    ///
+    /// The traversal for a key resumes where the traversal for the previous key stopped, and
+    /// restarts from the head for the remaining requests if it fails.
+    pub fn harris_herlihy_shavit_get_sorted<F>(
+        &self,
+        requests: &[(usize, &K)],
+        mut found: F,
+        handle: &mut Handle<'_>,
+    ) where
+        F: FnMut(usize, Option<&V>),
+    {
+        let mut next = 0;
+        while next < requests.len() {
+            let mut cursor = Cursor::new(&self.head, handle.launder());
+            while let Some(&(request, key)) = requests.get(next) {
+                match cursor.traverse_with_anchor(key) {
+                    Ok(true) => {
+                        let curr_node = unsafe { cursor.curr.with_tag(0).deref() };
+                        found(request, Some(&curr_node.value));
+                    }
+                    Ok(false) => found(request, None),
+                    Err(retry) => {
+                        retry.count();
+                        break;
+                    }
+                }
+                next += 1;
+            }
+        }
+    }
+}
+
+/// An iterator over the elements of a list from a key, in the order of the keys, which traverses
+/// the nodes as the gets of `HHSList` do. It does not take a snapshot of the list while the list is
+/// modified concurrently, but
+///
+/// * the keys it yields are strictly increasing,
+/// * it yields every key that is in the list from its creation until the end of the iteration, and
+/// * each element that it yields was in the list at some point after its previous element was
+///   yielded, while a key that is inserted or removed during the iteration may or may not be
+///   yielded.
+///
+/// Only the element that was yielded last is protected, until the next one is taken, so the
+/// iterator lends its elements with `next_entry` instead of implementing `Iterator`. The protection
+/// is handed over from node to node, and a traversal that fails its validation, e.g., because the
+/// last yielded node was removed, restarts from the head for the keys after the last yielded one.
+pub struct Iter<'domain, 'hp, K, V> {
+    head: &'hp Atomic<Node<K, V>>,
+    /// The lower bound of the keys, until the first element is yielded.
+    from: &'hp K,
+    /// The node that was yielded last, which is protected by `iter_h`, or null before the first.
+    last: Shared<Node<K, V>>,
+    /// Positioned after `last`, or at the head.
+    cursor: Cursor<'domain, 'hp, K, V>,
+}
+
+impl<'domain, 'hp, K, V> Iter<'domain, 'hp, K, V>
+where
+    K: Ord,
+{
+    /// Returns the next element, which stays protected until this method is called again or the
+    /// iterator is dropped.
+    pub fn next_entry(&mut self) -> Option<(&K, &V)> {
+        loop {
+            let key = match unsafe { self.last.as_ref() } {
+                Some(last) => &last.key,
+                None => self.from,
+            };
+            if self.cursor.curr.tag() != 0 {
+                // The last yielded node is logically removed, so its successor may be retired.
+                self.restart();
+            }
+            if let Err(retry) = self.cursor.traverse_with_anchor(key) {
+                retry.count();
+                self.restart();
+                continue;
+            }
+            if self.cursor.curr.is_null() {
+                return None;
+            }
+
+            // Move past the found node as the traversal does past a smaller key, with the found
+            // node protected by `prev_h`.
+            let curr_node = unsafe { self.cursor.curr.deref() };
+            self.cursor.prev = self.cursor.curr;
+            self.cursor.curr = curr_node.next.load(Ordering::Acquire);
+            self.cursor.anchor = Shared::null();
+            let handle = &mut *self.cursor.handle;
+            HazardPointer::swap(&mut handle.curr_h, &mut handle.prev_h);
+            if !self.last.is_null() && curr_node.key == *key {
+                // A traversal from the head stops at the last yielded key again.
+                continue;
+            }
+            // The found node is already protected, so it needs no validation.
+            handle.iter_h.protect_raw(self.cursor.prev.into_raw());
+            self.last = self.cursor.prev;
+            return Some((&curr_node.key, &curr_node.value));
+        }
+    }
+
+    #[inline]
+    fn restart(&mut self) {
+        self.cursor.prev = unsafe { Shared::from_raw(self.head as *const _ as *mut _) };
+        self.cursor.curr = self.head.load(Ordering::Acquire);
+        self.cursor.anchor = Shared::null();
+        self.cursor.anchor_next = Shared::null();
+    }
+}
+
+impl<'domain, 'hp, K, V> Drop for Iter<'domain, 'hp, K, V> {
+    fn drop(&mut self) {
+        self.cursor.handle.iter_h.reset_protection();
+    }
 }
 
 pub struct HList<K, V> {
@@ -544,6 +737,14 @@ where
         self.inner.harris_insert(key, value, handle)
     }
     #[inline(always)]
+    fn get_or_insert(&self, handle: &mut Self::Handle<'_>, key: K, value: V) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.inner.harris_get_or_insert(key, value, handle).cloned()
+    }
+    #[inline(always)]
     fn remove<'hp>(
         &'hp self,
         handle: &'hp mut Self::Handle<'_>,
@@ -595,6 +796,16 @@ where
         self.inner.harris_michael_insert(key, value, handle)
     }
     #[inline(always)]
+    fn get_or_insert(&self, handle: &mut Self::Handle<'_>, key: K, value: V) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.inner
+            .harris_michael_get_or_insert(key, value, handle)
+            .cloned()
+    }
+    #[inline(always)]
     fn remove<'hp>(
         &'hp self,
         handle: &'hp mut Self::Handle<'_>,
@@ -608,6 +819,41 @@ pub struct HHSList<K, V> {
     inner: List<K, V>,
 }
 
+impl<K, V> HHSList<K, V>
+where
+    K: Ord + 'static,
+{
+    /// Looks up the keys of `requests`, which must be sorted by their keys, with one traversal.
+    #[inline]
+    pub fn get_sorted<F>(&self, requests: &[(usize, &K)], found: F, handle: &mut Handle<'_>)
+    where
+        F: FnMut(usize, Option<&V>),
+    {
+        self.inner
+            .harris_herlihy_shavit_get_sorted(requests, found, handle)
+    }
+
+    /// Calls `f` with each element of the list, in the order of the keys.
+    ///
+    /// # Safety
+    ///
+    /// No element may be removed from the list during the call.
+    #[inline]
+    pub unsafe fn for_each<F: FnMut(&K, &V)>(&self, f: F) {
+        unsafe { self.inner.for_each(f) }
+    }
+
+    /// Returns an iterator over the elements whose keys are not less than `key`, in the order of
+    /// the keys, with the guarantees of [`Iter`] under concurrent updates.
+    pub fn iter_from<'domain, 'hp>(
+        &'hp self,
+        handle: &'hp mut Handle<'domain>,
+        key: &'hp K,
+    ) -> Iter<'domain, 'hp, K, V> {
+        self.inner.iter_from(key, handle)
+    }
+}
+
 impl<K, V> ConcurrentMap<K, V> for HHSList<K, V>
 where
     K: Ord + 'static,
@@ -635,6 +881,14 @@ where
         self.inner.harris_insert(key, value, handle)
     }
     #[inline(always)]
+    fn get_or_insert(&self, handle: &mut Self::Handle<'_>, key: K, value: V) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.inner.harris_get_or_insert(key, value, handle).cloned()
+    }
+    #[inline(always)]
     fn remove<'hp>(
         &'hp self,
         handle: &'hp mut Self::Handle<'_>,
@@ -642,6 +896,15 @@ where
     ) -> Option<impl OutputHolder<V>> {
         self.inner.harris_remove(key, handle)
     }
+    #[inline(always)]
+    fn partial_scan(&self, handle: &mut Self::Handle<'_>, key: &K, items: usize) -> usize {
+        let mut iter = self.iter_from(handle, key);
+        let mut scanned = 0;
+        while scanned < items && iter.next_entry().is_some() {
+            scanned += 1;
+        }
+        scanned
+    }
 }
 
 #[cfg(test)]
@@ -654,22 +917,156 @@ mod tests {
         concurrent_map::tests::smoke::<_, HList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<_, HList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hm_list() {
         concurrent_map::tests::smoke::<_, HMList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hm_list() {
+        concurrent_map::tests::edge_cases::<_, HMList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<_, HHSList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String>::new();
+        let handle = &mut HHSList::<i32, String>::handle();
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(handle, key, key.to_string()),
+            Op::Remove => map.remove(handle, &key).is_some(),
+            Op::Get => map.get(handle, &key).is_some(),
+        });
+    }
+
+    #[test]
+    fn output_holder_hhs_list() {
+        concurrent_map::tests::output_holder::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn get_or_insert_h_list() {
+        concurrent_map::tests::get_or_insert::<HList<i32, i32>>();
+    }
+
+    #[test]
+    fn get_or_insert_hm_list() {
+        concurrent_map::tests::get_or_insert::<HMList<i32, i32>>();
+    }
+
+    #[test]
+    fn get_or_insert_hhs_list() {
+        concurrent_map::tests::get_or_insert::<HHSList<i32, i32>>();
+    }
+
+    #[test]
+    fn iter_from_hhs_list() {
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::new();
+        let handle = &mut HHSList::<i32, String>::handle();
+        for key in (0..100).step_by(10) {
+            map.insert(handle, key, key.to_string());
+        }
+        map.remove(handle, &50);
+
+        let mut keys = |from, items| {
+            let mut iter = map.iter_from(handle, &from);
+            let mut keys = Vec::new();
+            while keys.len() < items {
+                let Some((k, v)) = iter.next_entry() else {
+                    break;
+                };
+                assert_eq!(*v, k.to_string());
+                keys.push(*k);
+            }
+            keys
+        };
+        assert_eq!(keys(-1, 3), [0, 10, 20]);
+        assert_eq!(keys(30, 3), [30, 40, 60]);
+        assert_eq!(keys(31, 100), [40, 60, 70, 80, 90]);
+        assert!(keys(91, 100).is_empty());
+    }
+
+    #[test]
+    fn concurrent_iter_from_hhs_list() {
+        use concurrent_map::ConcurrentMap;
+        use crossbeam_utils::thread;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        const THREADS: i32 = 4;
+        const KEYS: i32 = 1000;
+        const ITEMS: usize = 100;
+
+        let map = &HHSList::<i32, String>::new();
+        let handle = &mut HHSList::<i32, String>::handle();
+        for key in 0..KEYS {
+            map.insert(handle, key, key.to_string());
+        }
+        let done = &AtomicBool::new(false);
+        thread::scope(|s| {
+            // The odd keys are removed and inserted back, while the even keys stay.
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let handle = &mut HHSList::<i32, String>::handle();
+                    while !done.load(Ordering::Relaxed) {
+                        for key in (2 * t + 1..KEYS).step_by(2 * THREADS as usize) {
+                            assert!(map.remove(handle, &key).is_some());
+                            assert!(map.insert(handle, key, key.to_string()));
+                        }
+                    }
+                });
+            }
+            for from in (0..KEYS).step_by(7).cycle().take(2000) {
+                let mut iter = map.iter_from(handle, &from);
+                let mut last = None;
+                let mut even = from + from % 2;
+                let mut yielded = 0;
+                while yielded < ITEMS {
+                    let Some((key, value)) = iter.next_entry() else {
+                        break;
+                    };
+                    assert!(*key >= from && last < Some(*key));
+                    assert_eq!(*value, key.to_string());
+                    if key % 2 == 0 {
+                        assert_eq!(*key, even, "an even key is missed");
+                        even += 2;
+                    }
+                    last = Some(*key);
+                    yielded += 1;
+                }
+                if yielded < ITEMS {
+                    assert!(even >= KEYS, "an even key is missed");
+                }
+            }
+            done.store(true, Ordering::Relaxed);
+        })
+        .unwrap();
+    }
+
     #[test]
     fn litmus_hm_pop() {
         use concurrent_map::ConcurrentMap;
         let map = HMList::new();
 
         let handle = &mut HMList::<i32, String>::handle();
+        assert!(map.pop(handle).is_none());
         map.insert(handle, 1, "1".to_string());
         map.insert(handle, 2, "2".to_string());
         map.insert(handle, 3, "3".to_string());
@@ -0,0 +1,307 @@
+//! An append log with prefix truncation, after the Michael-Scott queue.
+//!
+//! Each entry gets the next index when it is appended at the tail, and [`Log::truncate`] drops
+//! every entry up to an index at once by moving the head past them, which retires the whole
+//! prefix in one burst of a single thread. The head is a dummy node, whose index is that of the
+//! last truncated entry. A truncation never moves the head past the tail, so that an append never
+//! links a node after a retired one.
+//!
+//! A traversal protects the nodes hand over hand. A node is retired only after `head_index` has
+//! been raised past its index, so a node that is still ahead of `head_index` once it is protected
+//! has not been retired before.
+
+use std::{
+    mem,
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, AtomicU64, Ordering},
+};
+
+use crossbeam_utils::CachePadded;
+use hp_pp::{light_membarrier, HazardPointer, Thread, DEFAULT_DOMAIN};
+
+pub(crate) struct Node<T> {
+    index: u64,
+    item: Option<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+unsafe impl<T: Sync> Sync for Node<T> {}
+unsafe impl<T: Sync> Send for Node<T> {}
+
+pub struct Log<T: Sync + Send> {
+    head: CachePadded<AtomicPtr<Node<T>>>,
+    tail: CachePadded<AtomicPtr<Node<T>>>,
+    /// The index of the head, which is raised after the head moves and before the nodes behind it
+    /// are retired.
+    head_index: CachePadded<AtomicU64>,
+}
+
+pub struct Handle<'domain> {
+    pri: HazardPointer<'domain>,
+    sub: HazardPointer<'domain>,
+    head: HazardPointer<'domain>,
+    thread: Box<Thread<'domain>>,
+}
+
+impl Default for Handle<'static> {
+    fn default() -> Self {
+        let mut thread = Box::new(Thread::new(&DEFAULT_DOMAIN));
+        Self {
+            pri: HazardPointer::new(&mut thread),
+            sub: HazardPointer::new(&mut thread),
+            head: HazardPointer::new(&mut thread),
+            thread,
+        }
+    }
+}
+
+impl<T: Sync + Send> Default for Log<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Sync + Send> Log<T> {
+    pub fn new() -> Self {
+        let sentinel = Box::into_raw(Box::new(Node {
+            index: 0,
+            item: None,
+            next: AtomicPtr::new(null_mut()),
+        }));
+        Self {
+            head: CachePadded::new(AtomicPtr::new(sentinel)),
+            tail: CachePadded::new(AtomicPtr::new(sentinel)),
+            head_index: CachePadded::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The index of the first entry that is not truncated.
+    pub fn first(&self) -> u64 {
+        self.head_index.load(Ordering::Acquire) + 1
+    }
+
+    /// The index of the last entry, which may lag behind an append in progress.
+    pub fn last(&self, handle: &mut Handle) -> u64 {
+        let ltail = protect_link(&self.tail, &mut handle.pri);
+        let index = unsafe { &*ltail }.index;
+        handle.pri.reset_protection();
+        index
+    }
+
+    /// Appends `item`, and returns its index.
+    pub fn append(&self, item: T, handle: &mut Handle) -> u64 {
+        let node = Box::into_raw(Box::new(Node {
+            index: 0,
+            item: Some(item),
+            next: AtomicPtr::new(null_mut()),
+        }));
+        loop {
+            let ltail = protect_link(&self.tail, &mut handle.pri);
+            let ltail_ref = unsafe { &*ltail };
+            let lnext = ltail_ref.next.load(Ordering::Acquire);
+            if !lnext.is_null() {
+                // Help the previous append to swing the tail.
+                let _ =
+                    self.tail
+                        .compare_exchange(ltail, lnext, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+            let index = ltail_ref.index + 1;
+            unsafe { (*node).index = index };
+            if ltail_ref
+                .next
+                .compare_exchange(null_mut(), node, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                let _ =
+                    self.tail
+                        .compare_exchange(ltail, node, Ordering::Release, Ordering::Relaxed);
+                handle.pri.reset_protection();
+                return index;
+            }
+        }
+    }
+
+    /// Walks from `from`, which `handle.pri` protects, to the node at `index`, which is left
+    /// protected by `handle.pri`. Returns null if the walk reaches the tail first, and `None` if a
+    /// truncation may have overtaken the walk.
+    fn walk(&self, from: *mut Node<T>, index: u64, handle: &mut Handle) -> Option<*mut Node<T>> {
+        let mut cur = from;
+        while unsafe { &*cur }.index < index {
+            let next = unsafe { &*cur }.next.load(Ordering::Acquire);
+            if next.is_null() {
+                return Some(null_mut());
+            }
+            handle.sub.protect_raw(next);
+            light_membarrier();
+            if self.head_index.load(Ordering::SeqCst) > unsafe { &*cur }.index + 1 {
+                // `next` may have been retired before it was protected.
+                return None;
+            }
+            mem::swap(&mut handle.pri, &mut handle.sub);
+            cur = next;
+        }
+        Some(cur)
+    }
+
+    /// Returns the entry at `index`, if it is appended and not truncated.
+    pub fn get<'h>(&self, index: u64, handle: &'h mut Handle) -> Option<&'h T> {
+        loop {
+            let lhead = protect_link(&self.head, &mut handle.pri);
+            if index <= unsafe { &*lhead }.index {
+                handle.pri.reset_protection();
+                return None;
+            }
+            if let Some(node) = self.walk(lhead, index, handle) {
+                handle.sub.reset_protection();
+                return unsafe { node.as_ref() }?.item.as_ref();
+            }
+        }
+    }
+
+    /// Drops the entries up to `index`, or up to the tail if it is behind, and returns the number
+    /// of dropped entries.
+    pub fn truncate(&self, index: u64, handle: &mut Handle) -> usize {
+        loop {
+            let target = index.min(self.last(handle));
+            // The old head stays protected until the head moves, as a third node besides the
+            // two of the walk.
+            let lhead = protect_link(&self.head, &mut handle.head);
+            let lhead_index = unsafe { &*lhead }.index;
+            if target <= lhead_index {
+                handle.head.reset_protection();
+                return 0;
+            }
+            handle.pri.protect_raw(lhead);
+            // A truncation never passes the tail, so the walk never reaches its end.
+            let Some(new_head) = self.walk(lhead, target, handle) else {
+                continue;
+            };
+            if self
+                .head
+                .compare_exchange(lhead, new_head, Ordering::SeqCst, Ordering::Relaxed)
+                .is_err()
+            {
+                continue;
+            }
+            self.head_index.fetch_max(target, Ordering::SeqCst);
+
+            let mut node = lhead;
+            while node != new_head {
+                let next = unsafe { &*node }.next.load(Ordering::Relaxed);
+                unsafe { handle.thread.retire(node) };
+                node = next;
+            }
+            handle.head.reset_protection();
+            handle.pri.reset_protection();
+            handle.sub.reset_protection();
+            return (target - lhead_index) as usize;
+        }
+    }
+}
+
+impl<T: Sync + Send> Drop for Log<T> {
+    fn drop(&mut self) {
+        // The nodes before `head` have already been retired.
+        let mut node = self.head.load(Ordering::Relaxed);
+        while !node.is_null() {
+            let next = unsafe { &*node }.next.load(Ordering::Relaxed);
+            drop(unsafe { Box::from_raw(node) });
+            node = next;
+        }
+    }
+}
+
+fn protect_link<T>(link: &AtomicPtr<Node<T>>, hazptr: &mut HazardPointer<'_>) -> *mut Node<T> {
+    let mut ptr = link.load(Ordering::Relaxed);
+    loop {
+        hazptr.protect_raw(ptr);
+        light_membarrier();
+        let new_ptr = link.load(Ordering::Acquire);
+        if ptr == new_ptr {
+            return ptr;
+        }
+        ptr = new_ptr;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Handle, Log};
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const THREADS: u64 = 8;
+    const APPENDS_PER_THREAD: u64 = 10000;
+    const RETAIN: u64 = 256;
+
+    #[test]
+    fn simple() {
+        let log = Log::<u64>::new();
+        let handle = &mut Handle::default();
+        assert!(log.get(1, handle).is_none());
+        for i in 1..=10 {
+            assert_eq!(log.append(i * 100, handle), i);
+        }
+        assert_eq!(*log.get(3, handle).unwrap(), 300);
+        assert_eq!(log.truncate(4, handle), 4);
+        assert_eq!(log.first(), 5);
+        assert!(log.get(4, handle).is_none());
+        assert_eq!(*log.get(5, handle).unwrap(), 500);
+        // A truncation stops at the tail.
+        assert_eq!(log.truncate(100, handle), 6);
+        assert_eq!(log.first(), 11);
+        assert!(log.get(10, handle).is_none());
+        assert_eq!(log.append(1100, handle), 11);
+        assert_eq!(*log.get(11, handle).unwrap(), 1100);
+    }
+
+    /// Appends from several threads while a truncator keeps the last `RETAIN` entries and
+    /// readers look up the retained ones, and checks that every append got a distinct index.
+    #[test]
+    fn smoke() {
+        let log = &Log::new();
+        let done = &AtomicBool::new(false);
+        let mut indices = scope(|s| {
+            let appenders = (0..THREADS)
+                .map(|t| {
+                    s.spawn(move |_| {
+                        let handle = &mut Handle::default();
+                        (0..APPENDS_PER_THREAD)
+                            .map(|_| log.append(t, handle))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            for _ in 0..2 {
+                s.spawn(move |_| {
+                    let handle = &mut Handle::default();
+                    while !done.load(Ordering::Relaxed) {
+                        for index in (log.first()..=log.last(handle)).step_by(17) {
+                            if let Some(item) = log.get(index, handle) {
+                                assert!(*item < THREADS);
+                            }
+                        }
+                    }
+                });
+            }
+            s.spawn(move |_| {
+                let handle = &mut Handle::default();
+                let mut truncated = 0;
+                while truncated + RETAIN < THREADS * APPENDS_PER_THREAD {
+                    let target = log.last(handle).saturating_sub(RETAIN);
+                    truncated += log.truncate(target, handle) as u64;
+                }
+                done.store(true, Ordering::Relaxed);
+            });
+            appenders
+                .into_iter()
+                .flat_map(|appender| appender.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+        indices.sort_unstable();
+        assert!(indices.iter().copied().eq(1..=THREADS * APPENDS_PER_THREAD));
+        assert_eq!(log.first(), THREADS * APPENDS_PER_THREAD - RETAIN + 1);
+    }
+}
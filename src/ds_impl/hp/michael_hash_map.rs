@@ -33,6 +33,33 @@ where
         k.hash(&mut s);
         s.finish() as usize
     }
+
+    /// Looks up `keys` in a batch, and calls `found` with the index of each key and its value,
+    /// which is protected only during the call.
+    ///
+    /// The keys are sorted by their buckets and then by themselves, so that the keys of a bucket
+    /// are looked up with one traversal of the bucket, which reuses the hazard pointers of the
+    /// previous key instead of protecting the bucket again from its head.
+    pub fn multi_get<F>(&self, handle: &mut Handle<'_>, keys: &[K], mut found: F)
+    where
+        F: FnMut(usize, Option<&V>),
+    {
+        let mut requests = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (Self::hash(k) % self.buckets.len(), (i, k)))
+            .collect::<Vec<_>>();
+        requests.sort_unstable_by(|(a, (_, ka)), (b, (_, kb))| a.cmp(b).then_with(|| ka.cmp(kb)));
+        let (buckets, requests): (Vec<_>, Vec<_>) = requests.into_iter().unzip();
+        let mut begin = 0;
+        while begin < requests.len() {
+            let bucket = buckets[begin];
+            let end = begin + buckets[begin..].partition_point(|b| *b == bucket);
+            self.get_bucket(bucket)
+                .get_sorted(&requests[begin..end], &mut found, handle);
+            begin = end;
+        }
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for HashMap<K, V>
@@ -73,15 +100,56 @@ where
         let i = Self::hash(key);
         self.get_bucket(i).remove(handle, key)
     }
+    #[inline(always)]
+    fn get_or_insert(&self, handle: &mut Self::Handle<'_>, key: K, value: V) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let i = Self::hash(&key);
+        self.get_bucket(i).get_or_insert(handle, key, value)
+    }
+    #[inline(always)]
+    fn multi_get(&self, handle: &mut Self::Handle<'_>, keys: &[K]) -> usize {
+        let mut count = 0;
+        self.multi_get(handle, keys, |_, value| count += value.is_some() as usize);
+        count
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::HashMap;
-    use crate::ds_impl::hp::concurrent_map;
+    use crate::ds_impl::hp::concurrent_map::{self, ConcurrentMap};
 
     #[test]
     fn smoke_hashmap() {
         concurrent_map::tests::smoke::<_, HashMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_hashmap() {
+        concurrent_map::tests::edge_cases::<_, HashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn get_or_insert_hashmap() {
+        concurrent_map::tests::get_or_insert::<HashMap<i32, i32>>();
+    }
+
+    #[test]
+    fn multi_get_hashmap() {
+        let map = &HashMap::with_capacity(4);
+        let handle = &mut HashMap::<i32, String>::handle();
+        for key in (0..100).step_by(2) {
+            assert!(map.insert(handle, key, key.to_string()));
+        }
+        assert!(map.remove(handle, &10).is_some());
+        // Some keys share a bucket, and a key may be requested more than once.
+        let keys = [7, 4, 98, 4, 10, 99, 0, 12];
+        let mut values = vec![None; keys.len()];
+        map.multi_get(handle, &keys, |i, value| values[i] = value.cloned());
+        let expected = keys.map(|key| (key % 2 == 0 && key != 10).then(|| key.to_string()));
+        assert_eq!(values, expected);
+    }
 }
@@ -0,0 +1,342 @@
+//! A hash map of `HHSList` buckets that can be migrated into a table with twice the buckets while
+//! the other operations continue, with the help of the updates that run into the migration.
+//!
+//! See `ds_impl::migration` for the protocol. An operation protects the table that it uses, and
+//! moves the protection to the next table when it finds its bucket migrated. The next table is
+//! retired only after it has been installed and replaced in turn, so it is validated against the
+//! current table after it is protected. The old table is retired whole once the new table is
+//! installed, and its nodes are freed together when no thread protects it.
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::list::{self, HHSList};
+use crate::ds_impl::migration::{Gate, Migration, Progress};
+use crossbeam_utils::Backoff;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Mutex;
+
+use hp_pp::{light_membarrier, HazardPointer, Thread, DEFAULT_DOMAIN};
+
+struct Bucket<K, V> {
+    gate: Gate,
+    list: HHSList<K, V>,
+}
+
+struct Table<K, V> {
+    buckets: Box<[Bucket<K, V>]>,
+    /// The table that this table is migrated into, if a migration started.
+    next: AtomicPtr<Table<K, V>>,
+    progress: Progress,
+}
+
+impl<K, V> Table<K, V>
+where
+    K: Ord + 'static,
+{
+    fn new(len: usize) -> Self {
+        Self {
+            buckets: (0..len)
+                .map(|_| Bucket {
+                    gate: Gate::default(),
+                    list: HHSList::new(),
+                })
+                .collect(),
+            next: AtomicPtr::new(null_mut()),
+            progress: Progress::default(),
+        }
+    }
+}
+
+impl<K, V> Table<K, V> {
+    /// The bucket of `hash`, whose keys split into the buckets of the same index and of the index
+    /// plus the old length in a table with twice the buckets.
+    #[inline]
+    fn bucket(&self, hash: u64) -> &Bucket<K, V> {
+        &self.buckets[hash as usize % self.buckets.len()]
+    }
+}
+
+pub struct Handle<'domain> {
+    table: HazardPointer<'domain>,
+    next: HazardPointer<'domain>,
+    list: list::Handle<'domain>,
+    thread: Box<Thread<'domain>>,
+}
+
+impl Default for Handle<'static> {
+    fn default() -> Self {
+        let mut thread = Box::new(Thread::new(&DEFAULT_DOMAIN));
+        Self {
+            table: HazardPointer::new(&mut thread),
+            next: HazardPointer::new(&mut thread),
+            list: list::Handle::default(),
+            thread,
+        }
+    }
+}
+
+pub struct MigratingHashMap<K, V> {
+    table: AtomicPtr<Table<K, V>>,
+    /// Serializes the migrations, so that a table is migrated at most once.
+    migrate: Mutex<()>,
+}
+
+#[inline]
+fn hash_of<K: Hash>(k: &K) -> u64 {
+    let mut s = DefaultHasher::new();
+    k.hash(&mut s);
+    s.finish()
+}
+
+impl<K, V> MigratingHashMap<K, V>
+where
+    K: Ord + Hash + Clone + 'static,
+    V: Clone,
+{
+    pub fn with_buckets(len: usize) -> Self {
+        assert!(len > 0, "A table must have a bucket");
+        Self {
+            table: AtomicPtr::new(Box::into_raw(Box::new(Table::new(len)))),
+            migrate: Mutex::new(()),
+        }
+    }
+
+    /// The number of buckets of the current table.
+    pub fn buckets(&self, handle: &mut Handle<'_>) -> usize {
+        let table = handle.table.protect(&self.table);
+        let len = unsafe { &*table }.buckets.len();
+        handle.table.reset_protection();
+        len
+    }
+
+    /// Moves the protection of `table` to its next table, or protects the current table again if
+    /// the next table may have been retired.
+    #[inline]
+    fn follow(&self, table: *mut Table<K, V>, handle: &mut Handle<'_>) -> *mut Table<K, V> {
+        let next = unsafe { &*table }.next.load(Ordering::Acquire);
+        handle.next.protect_raw(next);
+        light_membarrier();
+        let current = self.table.load(Ordering::Acquire);
+        if current != table && current != next {
+            handle.next.reset_protection();
+            return handle.table.protect(&self.table);
+        }
+        HazardPointer::swap(&mut handle.table, &mut handle.next);
+        handle.next.reset_protection();
+        next
+    }
+
+    /// Enters the bucket of `hash` for an update, helping the migration of each table on the way
+    /// with a chunk, and waiting for the bucket to be migrated if it is frozen.
+    #[inline]
+    fn enter<'t>(&self, hash: u64, handle: &mut Handle<'_>) -> &'t Bucket<K, V> {
+        let backoff = Backoff::new();
+        let mut table = handle.table.protect(&self.table);
+        loop {
+            let table_ref = unsafe { &*table };
+            if !table_ref.next.load(Ordering::Acquire).is_null() {
+                self.help(table, handle);
+            }
+            let bucket = table_ref.bucket(hash);
+            if bucket.gate.enter() {
+                return bucket;
+            }
+            if bucket.gate.is_migrated() {
+                table = self.follow(table, handle);
+                backoff.reset();
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// Migrates a chunk of the buckets of the protected `table` into its next table, if any is
+    /// left, and returns the number of the migrated buckets. The helper of the last chunk installs
+    /// the next table.
+    fn help(&self, table: *mut Table<K, V>, handle: &mut Handle<'_>) -> usize {
+        let table_ref = unsafe { &*table };
+        let len = table_ref.buckets.len();
+        let Some(chunk) = table_ref.progress.claim(len) else {
+            return 0;
+        };
+        // The next table cannot be installed, let alone retired, before the claimed chunk is
+        // migrated, so it needs no protection.
+        let next = table_ref.next.load(Ordering::Acquire);
+        let next_ref = unsafe { &*next };
+        let mut copied = 0;
+        for bucket in &table_ref.buckets[chunk.clone()] {
+            bucket.gate.freeze();
+            // The frozen bucket has no remove that could retire its nodes.
+            unsafe {
+                bucket.list.for_each(|k, v| {
+                    let list = &next_ref.bucket(hash_of(k)).list;
+                    if list.insert(&mut handle.list, k.clone(), v.clone()) {
+                        copied += 1;
+                    }
+                })
+            };
+            bucket.gate.finish();
+        }
+        if table_ref.progress.finish(chunk.clone(), copied, len) {
+            self.table.store(next, Ordering::Release);
+            unsafe { handle.thread.retire(table) };
+        }
+        chunk.len()
+    }
+
+    /// Migrates all keys into a table with twice the buckets, with the help of the concurrent
+    /// updates, and returns once the new table is installed.
+    pub fn migrate(&self, handle: &mut Handle<'_>) -> Migration {
+        let _migrate = self.migrate.lock().unwrap();
+        let table = handle.table.protect(&self.table);
+        let table_ref = unsafe { &*table };
+        let len = table_ref.buckets.len();
+        table_ref.next.store(
+            Box::into_raw(Box::new(Table::new(2 * len))),
+            Ordering::Release,
+        );
+
+        let mut own = 0;
+        loop {
+            let migrated = self.help(table, handle);
+            if migrated == 0 {
+                break;
+            }
+            own += migrated;
+        }
+        // The last chunk may still be copied by a helper.
+        let backoff = Backoff::new();
+        while self.table.load(Ordering::Acquire) == table {
+            backoff.snooze();
+        }
+        let migration = Migration {
+            buckets: len,
+            copied: table_ref.progress.copied(),
+            helped: len - own,
+        };
+        handle.table.reset_protection();
+        migration
+    }
+}
+
+impl<K, V> Drop for MigratingHashMap<K, V> {
+    fn drop(&mut self) {
+        // A migration finishes before `migrate` returns, so there is no next table.
+        drop(unsafe { Box::from_raw(*self.table.get_mut()) });
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for MigratingHashMap<K, V>
+where
+    K: Ord + Hash + Clone + Send + 'static,
+    V: Clone + Send,
+{
+    type Handle<'domain> = Handle<'domain>;
+
+    fn new() -> Self {
+        Self::with_buckets(30000)
+    }
+
+    fn handle() -> Self::Handle<'static> {
+        Handle::default()
+    }
+
+    #[inline(always)]
+    fn get<'hp>(
+        &'hp self,
+        handle: &'hp mut Self::Handle<'_>,
+        key: &'hp K,
+    ) -> Option<impl OutputHolder<V>> {
+        let hash = hash_of(key);
+        let mut table = handle.table.protect(&self.table);
+        loop {
+            let bucket = unsafe { &*table }.bucket(hash);
+            if !bucket.gate.is_migrated() {
+                return bucket.list.get(&mut handle.list, key);
+            }
+            table = self.follow(table, handle);
+        }
+    }
+    #[inline(always)]
+    fn insert(&self, handle: &mut Self::Handle<'_>, key: K, value: V) -> bool {
+        let bucket = self.enter(hash_of(&key), handle);
+        let inserted = bucket.list.insert(&mut handle.list, key, value);
+        bucket.gate.exit();
+        inserted
+    }
+    #[inline(always)]
+    fn remove<'hp>(
+        &'hp self,
+        handle: &'hp mut Self::Handle<'_>,
+        key: &'hp K,
+    ) -> Option<impl OutputHolder<V>> {
+        let bucket = self.enter(hash_of(key), handle);
+        let removed = bucket.list.remove(&mut handle.list, key);
+        bucket.gate.exit();
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MigratingHashMap;
+    use crate::ds_impl::hp::concurrent_map::{self, ConcurrentMap, OutputHolder};
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn smoke_migrating_hash_map() {
+        concurrent_map::tests::smoke::<_, MigratingHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn edge_cases_migrating_hash_map() {
+        concurrent_map::tests::edge_cases::<_, MigratingHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn concurrent_migrations() {
+        const THREADS: i32 = 8;
+        const KEYS: i32 = 4096;
+        const BUCKETS: usize = 16;
+        const MIGRATIONS: u32 = 4;
+
+        let map = &MigratingHashMap::with_buckets(BUCKETS);
+        let handle = &mut MigratingHashMap::<i32, String>::handle();
+        for key in 0..KEYS {
+            assert!(map.insert(handle, key, key.to_string()));
+        }
+        let done = &AtomicBool::new(false);
+        scope(|s| {
+            // Each thread removes and inserts back its own odd keys, and checks that the even keys
+            // are never missed.
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let handle = &mut MigratingHashMap::<i32, String>::handle();
+                    while !done.load(Ordering::Relaxed) {
+                        for key in (2 * t + 1..KEYS).step_by(2 * THREADS as usize) {
+                            let removed = map.remove(handle, &key).unwrap().output().clone();
+                            assert_eq!(removed, key.to_string());
+                            assert!(map.insert(handle, key, key.to_string()));
+                            assert!(map.get(handle, &(key - 1)).is_some());
+                        }
+                    }
+                });
+            }
+            let handle = &mut MigratingHashMap::<i32, String>::handle();
+            for i in 0..MIGRATIONS {
+                let migration = map.migrate(handle);
+                assert_eq!(migration.buckets, BUCKETS << i);
+            }
+            done.store(true, Ordering::Relaxed);
+        })
+        .unwrap();
+
+        assert_eq!(map.buckets(handle), BUCKETS << MIGRATIONS);
+        for key in 0..KEYS {
+            assert_eq!(*map.get(handle, &key).unwrap().output(), key.to_string());
+        }
+    }
+}
@@ -1,22 +1,46 @@
+pub mod concurrent_counter;
+pub mod concurrent_deque;
 pub mod concurrent_map;
+pub mod concurrent_set;
+pub mod concurrent_stack;
 pub mod pointers;
 
 pub mod bonsai_tree;
+pub mod chase_lev_deque;
+pub mod clock_cache;
 pub mod double_link;
 pub mod elim_ab_tree;
 pub mod ellen_tree;
 pub mod list;
+pub mod log;
+pub mod marker_skip_list;
 pub mod michael_hash_map;
+pub mod migrating_hash_map;
 pub mod natarajan_mittal_tree;
+#[cfg(feature = "persistent")]
+pub mod persistent_hash_map;
 pub mod skip_list;
+pub mod treiber_stack;
 
+pub use self::concurrent_counter::{ConcurrentCounter, HashMapCounter};
+pub use self::concurrent_deque::ConcurrentDeque;
 pub use self::concurrent_map::ConcurrentMap;
+pub use self::concurrent_set::{ConcurrentSet, HHSListSet, HListSet, HMListSet, SkipListSet};
+pub use self::concurrent_stack::ConcurrentStack;
 
 pub use self::bonsai_tree::BonsaiTreeMap;
+pub use self::chase_lev_deque::ChaseLevDeque;
+pub use self::clock_cache::ClockCache;
 pub use self::double_link::DoubleLink;
 pub use self::elim_ab_tree::ElimABTree;
 pub use self::ellen_tree::EFRBTree;
 pub use self::list::{HHSList, HList, HMList};
+pub use self::log::Log;
+pub use self::marker_skip_list::MarkerSkipList;
 pub use self::michael_hash_map::HashMap;
+pub use self::migrating_hash_map::MigratingHashMap;
 pub use self::natarajan_mittal_tree::NMTreeMap;
+#[cfg(feature = "persistent")]
+pub use self::persistent_hash_map::PersistentHashMap;
 pub use self::skip_list::SkipList;
+pub use self::treiber_stack::{EliminationStack, TreiberStack};
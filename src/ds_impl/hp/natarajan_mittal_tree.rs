@@ -2,6 +2,8 @@ use hp_pp::{light_membarrier, Thread};
 use hp_pp::{tag, tagged, untagged, HazardPointer, DEFAULT_DOMAIN};
 
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use std::cmp;
 use std::ptr;
 use std::sync::atomic::{AtomicPtr, Ordering};
@@ -92,7 +94,7 @@ where
 }
 
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: Key<K>,
     value: Option<V>,
     left: AtomicPtr<Node<K, V>>,
@@ -179,6 +181,11 @@ pub struct SeekRecord<'domain, 'hp, K, V> {
     leaf: *mut Node<K, V>,
     /// The direction of leaf from parent.
     leaf_dir: Direction,
+    /// Whether the seek records `bound`, which only `next_key` needs.
+    bounded: bool,
+    /// The key of the last internal node whose left edge is on the access path, which the keys of
+    /// its right subtree are at least, or `None` if the path only turns left at the sentinels.
+    bound: Option<Key<K>>,
 
     handle: &'hp mut Handle<'domain>,
 }
@@ -192,6 +199,8 @@ impl<'domain, 'hp, K, V> SeekRecord<'domain, 'hp, K, V> {
             parent: ptr::null_mut(),
             leaf: ptr::null_mut(),
             leaf_dir: Direction::L,
+            bounded: false,
+            bound: None,
             handle,
         }
     }
@@ -300,6 +309,7 @@ where
         }
         record.leaf = leaf;
         record.leaf_dir = Direction::L;
+        record.bound = None;
 
         let mut prev_tag = Marks::from_bits_truncate(tag(leaf)).tag();
         let mut curr_dir = Direction::L;
@@ -395,6 +405,9 @@ where
             prev_tag = Marks::from_bits_truncate(tag(curr)).tag();
             let curr_node = unsafe { &*curr_base };
             if curr_node.key.cmp(key) == cmp::Ordering::Greater {
+                if record.bounded {
+                    record.bound = Some(curr_node.key.clone());
+                }
                 curr_dir = Direction::L;
                 curr = curr_node.left.load(Ordering::Acquire);
             } else {
@@ -412,6 +425,15 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire);
         let leaf_flag = Marks::from_bits_truncate(tag(leaf_marked)).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag
+                || Marks::from_bits_truncate(tag(record
+                    .leaf_sibling_addr()
+                    .load(Ordering::Acquire)))
+                .flag(),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -427,7 +449,18 @@ where
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load(Ordering::Acquire);
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(tag(target_sibling)).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(tag(target_sibling)).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            tag(record.successor),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         let is_unlinked = record
             .successor_addr()
             .compare_exchange(
@@ -436,7 +469,8 @@ where
                 Ordering::AcqRel,
                 Ordering::Acquire,
             )
-            .is_ok();
+            .classify(&untagged(record.successor))
+            .is_success();
 
         if is_unlinked {
             unsafe {
@@ -526,20 +560,19 @@ where
             new_internal_node.right.store(new_right, Ordering::Relaxed);
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match record.leaf_addr().compare_exchange(
-                leaf,
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => return Ok(()),
-                Err(current) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if untagged(current) == leaf {
-                        self.cleanup(record);
-                    }
+            match record
+                .leaf_addr()
+                .compare_exchange(leaf, new_internal, Ordering::AcqRel, Ordering::Acquire)
+                .classify(&leaf)
+            {
+                Cas::Success(_) => return Ok(()),
+                // Insertion failed. Help the conflicting remove operation if needed.
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    InsertConflict::Help.count();
+                    self.cleanup(record);
                 }
+                Cas::CurrentDiffers(_) => InsertConflict::Retry.count(),
             }
         }
     }
@@ -578,13 +611,17 @@ where
             let value = leaf_node.value.as_ref().unwrap();
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_exchange(
-                leaf,
-                tagged(leaf, Marks::new(true, false).bits()),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => {
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    leaf,
+                    tagged(leaf, Marks::new(true, false).bits()),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .classify(&leaf)
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(&mut record) {
                         return Ok(Some(value));
@@ -592,15 +629,14 @@ where
                     // In-place cleanup failed. Enter the cleanup phase.
                     break (leaf, value);
                 }
-                Err(current) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if leaf == tagged(current, Marks::empty().bits()) {
-                        self.cleanup(&mut record);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(&mut record);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         };
 
@@ -632,6 +668,56 @@ where
             }
         }
     }
+
+    /// Returns the smallest key that is greater than `key`, or at least `key` with `inclusive`.
+    fn next_key(&self, key: &K, inclusive: bool, handle: &mut Handle<'_>) -> Option<K> {
+        let mut from = key.clone();
+        let mut inclusive = inclusive;
+        loop {
+            let mut record = SeekRecord::new(handle.launder());
+            record.bounded = true;
+            if self.seek(&from, &mut record).is_err() {
+                continue;
+            }
+            let leaf_node = unsafe { &*untagged(record.leaf) };
+            match leaf_node.key.cmp(&from) {
+                cmp::Ordering::Greater => {
+                    return match &leaf_node.key {
+                        Key::Fin(key) => Some(key.clone()),
+                        Key::Inf => None,
+                    }
+                }
+                cmp::Ordering::Equal if inclusive => return Some(from),
+                _ => {}
+            }
+            // The leaf is the largest key up to `from`, so the next key is the smallest one of the
+            // right subtree of the last node where the path turned left.
+            match record.bound.take() {
+                Some(Key::Fin(bound)) => {
+                    from = bound;
+                    inclusive = true;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    /// Removes the keys in `lo..hi` one at a time, from the smallest one up, and returns the number
+    /// of them that are removed.
+    ///
+    /// Each key is found by a seek for the next key after the last removed one, so a key that is
+    /// inserted again behind the removals stays in the tree, and the removals always end.
+    pub fn remove_range(&self, lo: &K, hi: &K, handle: &mut Handle<'_>) -> usize {
+        let mut removed = 0;
+        let mut next = self.next_key(lo, true, handle);
+        while let Some(key) = next.take().filter(|key| key < hi) {
+            if self.remove(&key, handle).is_some() {
+                removed += 1;
+            }
+            next = self.next_key(&key, false, handle);
+        }
+        removed
+    }
 }
 
 impl<K, V> ConcurrentMap<K, V> for NMTreeMap<K, V>
@@ -671,15 +757,68 @@ where
     ) -> Option<impl OutputHolder<V>> {
         self.remove(key, handle)
     }
+
+    #[inline(always)]
+    fn remove_range(&self, handle: &mut Self::Handle<'_>, lo: &K, hi: &K) -> usize {
+        self.remove_range(lo, hi, handle)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::NMTreeMap;
+    use super::{Handle, NMTreeMap};
     use crate::ds_impl::hp::concurrent_map;
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
 
     #[test]
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn remove_range_nm_tree() {
+        concurrent_map::tests::remove_range::<NMTreeMap<i32, i32>>();
+    }
+
+    /// Contends on the few edges of a small tree, so that most removals help or are helped by
+    /// another one, which exercises the debug assertions on the marks in `cleanup` and `remove`.
+    #[test]
+    fn stress_nm_tree_cleanup() {
+        const THREADS: usize = 16;
+        const KEYS: i32 = 8;
+        const OPS_PER_THREAD: usize = 20000;
+
+        let map = &NMTreeMap::<i32, i32>::new();
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let handle = &mut Handle::default();
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..OPS_PER_THREAD {
+                        let key = rng.gen_range(0..KEYS);
+                        if rng.gen() {
+                            let _ = map.insert(key, key, handle);
+                        } else if let Some(value) = map.remove(&key, handle) {
+                            assert_eq!(*value, key);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let handle = &mut Handle::default();
+        for key in 0..KEYS {
+            if map.get(&key, handle).is_some() {
+                assert_eq!(map.remove(&key, handle).copied(), Some(key));
+            }
+            assert!(map.get(&key, handle).is_none());
+        }
+    }
 }
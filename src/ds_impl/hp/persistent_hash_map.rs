@@ -0,0 +1,282 @@
+//! Michael's hash map whose buckets persist their links, as on persistent memory.
+//!
+//! The buckets are Harris-Michael lists that follow the link-and-persist discipline of the
+//! log-free structures (David et al., USENIX ATC '18), as in the map of the same name of `ebr`: a
+//! new node is written back before it is linked, every link that an operation changes is written
+//! back and fenced before the operation returns, and a `get` writes back and fences the link that
+//! its answer depends on. The write-backs and fences go through [`persist`].
+
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::pointers::{Atomic, Pointer, Shared};
+use crate::ds_impl::persist;
+use crate::ds_impl::retry::Retry;
+use core::mem;
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::Ordering;
+
+use hp_pp::{light_membarrier, HazardPointer, Thread, DEFAULT_DOMAIN};
+
+// `#[repr(C)]` is used to ensure the first field
+// is also the first data in the memory alignment.
+#[repr(C)]
+struct Node<K, V> {
+    /// Mark: tag()
+    next: Atomic<Node<K, V>>,
+    key: K,
+    value: V,
+}
+
+struct Bucket<K, V> {
+    head: Atomic<Node<K, V>>,
+}
+
+impl<K, V> Drop for Bucket<K, V> {
+    fn drop(&mut self) {
+        let mut o_curr = mem::take(&mut self.head);
+
+        while let Some(curr) = unsafe { o_curr.try_into_owned() } {
+            o_curr = curr.next;
+        }
+    }
+}
+
+pub struct Handle<'domain> {
+    prev_h: HazardPointer<'domain>,
+    curr_h: HazardPointer<'domain>,
+    thread: Box<Thread<'domain>>,
+}
+
+impl Default for Handle<'static> {
+    fn default() -> Self {
+        let mut thread = Box::new(Thread::new(&DEFAULT_DOMAIN));
+        Self {
+            prev_h: HazardPointer::new(&mut thread),
+            curr_h: HazardPointer::new(&mut thread),
+            thread,
+        }
+    }
+}
+
+/// The node whose link points to the found node, or the head as a node, and the found node.
+type Position<K, V> = (Shared<Node<K, V>>, Shared<Node<K, V>>);
+
+impl<K: Ord, V> Bucket<K, V> {
+    fn new() -> Self {
+        Self {
+            head: Atomic::null(),
+        }
+    }
+
+    /// Finds the first node whose key is not less than `key`, and unlinks each logically removed
+    /// node on the way, writing the link past it back. The two nodes of the position are
+    /// protected by `prev_h` and `curr_h`.
+    fn find(&self, key: &K, handle: &mut Handle<'_>) -> Result<(bool, Position<K, V>), Retry> {
+        let mut prev = unsafe { Shared::from_raw(&self.head as *const _ as *mut Node<K, V>) };
+        let mut curr = self.head.load(Ordering::Acquire);
+        loop {
+            debug_assert_eq!(curr.tag(), 0);
+            if curr.is_null() {
+                return Ok((false, (prev, curr)));
+            }
+
+            let prev_link = unsafe { &prev.deref().next };
+            handle.curr_h.protect_raw(curr.into_raw());
+            light_membarrier();
+            let curr_new = prev_link.load(Ordering::Acquire);
+            if curr_new.tag() != 0 {
+                return Err(Retry::Validation);
+            } else if curr_new != curr {
+                curr = curr_new;
+                continue;
+            }
+
+            let curr_node = unsafe { curr.deref() };
+            let next = curr_node.next.load(Ordering::Acquire);
+            if next.tag() == 0 {
+                match curr_node.key.cmp(key) {
+                    Less => {
+                        prev = curr;
+                        HazardPointer::swap(&mut handle.prev_h, &mut handle.curr_h);
+                    }
+                    Equal => return Ok((true, (prev, curr))),
+                    Greater => return Ok((false, (prev, curr))),
+                }
+            } else if prev_link
+                .compare_exchange(curr, next.with_tag(0), Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                // The mark of the node is already durable, so the unlink needs no fence of its
+                // own: it is ordered by the next fence of the thread.
+                persist::flush(prev_link);
+                unsafe { handle.thread.retire(curr.into_raw()) };
+            } else {
+                return Err(Retry::Unlink);
+            }
+            curr = next.with_tag(0);
+        }
+    }
+
+    fn get<'hp>(&self, key: &K, handle: &'hp mut Handle<'_>) -> Option<&'hp V> {
+        loop {
+            match self.find(key, handle) {
+                Ok((found, (prev, curr))) => {
+                    persist::flush(unsafe { &prev.deref().next });
+                    persist::fence();
+                    return found.then(|| &unsafe { curr.deref() }.value);
+                }
+                Err(retry) => retry.count(),
+            }
+        }
+    }
+
+    fn insert(&self, key: K, value: V, handle: &mut Handle<'_>) -> bool {
+        let mut node = Box::new(Node {
+            next: Atomic::null(),
+            key,
+            value,
+        });
+        loop {
+            let (found, (prev, curr)) = match self.find(&node.key, handle) {
+                Ok(found) => found,
+                Err(retry) => {
+                    retry.count();
+                    continue;
+                }
+            };
+            let prev_link = unsafe { &prev.deref().next };
+            if found {
+                persist::flush(prev_link);
+                persist::fence();
+                return false;
+            }
+            node.next = curr.into();
+            // The node must be durable before a durable link can point to it.
+            persist::flush(&*node);
+            persist::fence();
+            match prev_link.compare_exchange(curr, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => {
+                    persist::flush(prev_link);
+                    persist::fence();
+                    return true;
+                }
+                Err(e) => {
+                    Retry::CasFail.count();
+                    node = e.new;
+                }
+            }
+        }
+    }
+
+    fn remove<'hp>(&self, key: &K, handle: &'hp mut Handle<'_>) -> Option<&'hp V> {
+        loop {
+            let (found, (prev, curr)) = match self.find(key, handle) {
+                Ok(found) => found,
+                Err(retry) => {
+                    retry.count();
+                    continue;
+                }
+            };
+            let prev_link = unsafe { &prev.deref().next };
+            if !found {
+                persist::flush(prev_link);
+                persist::fence();
+                return None;
+            }
+            let curr_node = unsafe { curr.deref() };
+            let next = curr_node.next.fetch_or(1, Ordering::AcqRel);
+            if next.tag() == 1 {
+                Retry::CasFail.count();
+                continue;
+            }
+            // The removal is durable once its mark is.
+            persist::flush(&curr_node.next);
+            persist::fence();
+            if prev_link
+                .compare_exchange(curr, next, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                persist::flush(prev_link);
+                unsafe { handle.thread.retire(curr.into_raw()) };
+            }
+            return Some(&curr_node.value);
+        }
+    }
+}
+
+pub struct PersistentHashMap<K, V> {
+    buckets: Vec<Bucket<K, V>>,
+}
+
+impl<K, V> PersistentHashMap<K, V>
+where
+    K: Ord + Hash,
+{
+    pub fn with_capacity(n: usize) -> Self {
+        PersistentHashMap {
+            buckets: (0..n).map(|_| Bucket::new()).collect(),
+        }
+    }
+
+    #[inline]
+    fn get_bucket(&self, k: &K) -> &Bucket<K, V> {
+        let mut s = DefaultHasher::new();
+        k.hash(&mut s);
+        let index = s.finish() as usize % self.buckets.len();
+        unsafe { self.buckets.get_unchecked(index) }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for PersistentHashMap<K, V>
+where
+    K: Ord + Hash + Send + 'static,
+    V: Send,
+{
+    type Handle<'domain> = Handle<'domain>;
+
+    fn new() -> Self {
+        Self::with_capacity(30000)
+    }
+
+    fn handle() -> Self::Handle<'static> {
+        Handle::default()
+    }
+
+    #[inline(always)]
+    fn get<'hp>(
+        &'hp self,
+        handle: &'hp mut Self::Handle<'_>,
+        key: &'hp K,
+    ) -> Option<impl OutputHolder<V>> {
+        self.get_bucket(key).get(key, handle)
+    }
+    #[inline(always)]
+    fn insert(&self, handle: &mut Self::Handle<'_>, key: K, value: V) -> bool {
+        self.get_bucket(&key).insert(key, value, handle)
+    }
+    #[inline(always)]
+    fn remove<'hp>(
+        &'hp self,
+        handle: &'hp mut Self::Handle<'_>,
+        key: &'hp K,
+    ) -> Option<impl OutputHolder<V>> {
+        self.get_bucket(key).remove(key, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PersistentHashMap;
+    use crate::ds_impl::hp::concurrent_map;
+
+    #[test]
+    fn smoke_persistent_hash_map() {
+        concurrent_map::tests::smoke::<_, PersistentHashMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn edge_cases_persistent_hash_map() {
+        concurrent_map::tests::edge_cases::<_, PersistentHashMap<i32, String>, _>(&i32::to_string);
+    }
+}
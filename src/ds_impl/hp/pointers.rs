@@ -1,3 +1,4 @@
+use crate::ds_impl::cas::{CasError, Link};
 use core::mem;
 use std::{
     ptr::null_mut,
@@ -211,6 +212,22 @@ impl<T> PartialEq for Shared<T> {
 
 impl<T> Eq for Shared<T> {}
 
+impl<T> Link for Shared<T> {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        (base_ptr(self.ptr) as usize, tag(self.ptr))
+    }
+}
+
+impl<T, P: Pointer<T>> CasError for CompareExchangeError<T, P> {
+    type Current = Shared<T>;
+
+    #[inline]
+    fn current(&self) -> &Shared<T> {
+        &self.current
+    }
+}
+
 pub trait Pointer<T> {
     fn into_raw(self) -> *mut T;
     unsafe fn from_raw(val: *mut T) -> Self;
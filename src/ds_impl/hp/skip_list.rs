@@ -13,7 +13,7 @@ type Tower<K, V> = [AtomicPtr<Node<K, V>>; MAX_HEIGHT];
 // `#[repr(C)]` is used to ensure the first field
 // is also the first data in the memory alignment.
 #[repr(C)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     next: Tower<K, V>,
     key: K,
     value: V,
@@ -539,4 +539,9 @@ mod tests {
     fn smoke_skip_list() {
         concurrent_map::tests::smoke::<_, SkipList<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_skip_list() {
+        concurrent_map::tests::edge_cases::<_, SkipList<i32, String>, _>(&i32::to_string);
+    }
 }
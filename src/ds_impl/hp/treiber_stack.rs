@@ -0,0 +1,272 @@
+//! Treiber's stack, and its variant with an elimination array.
+//!
+//! A pop reads the next node of the head and swings the head to it, which is the textbook case of
+//! the ABA problem: the head may be popped, freed, and pushed again at the same address in
+//! between. The pop protects the head and validates it against the head of the stack before it
+//! reads the next node, so that the head cannot be freed until the CAS, which rules it out.
+
+use super::concurrent_stack::ConcurrentStack;
+use crate::ds_impl::elimination::EliminationArray;
+
+use std::mem::ManuallyDrop;
+use std::ptr::{self, null_mut};
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use crossbeam_utils::CachePadded;
+use hp_pp::{HazardPointer, Thread, DEFAULT_DOMAIN};
+
+struct Node<T> {
+    /// Moved out by the pop that unlinks the node.
+    item: ManuallyDrop<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(item: T) -> Self {
+        Self {
+            item: ManuallyDrop::new(item),
+            next: AtomicPtr::new(null_mut()),
+        }
+    }
+}
+
+pub struct TreiberStack<T> {
+    head: CachePadded<AtomicPtr<Node<T>>>,
+}
+
+pub struct Handle<'domain> {
+    head: HazardPointer<'domain>,
+    thread: Box<Thread<'domain>>,
+}
+
+impl Default for Handle<'static> {
+    fn default() -> Self {
+        let mut thread = Box::new(Thread::new(&DEFAULT_DOMAIN));
+        Self {
+            head: HazardPointer::new(&mut thread),
+            thread,
+        }
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TreiberStack<T> {
+    pub fn new() -> Self {
+        Self {
+            head: CachePadded::new(AtomicPtr::new(null_mut())),
+        }
+    }
+
+    /// Tries to link `node` on the head once, and fails if the head changed.
+    #[inline]
+    fn try_link(&self, node: *mut Node<T>) -> Result<(), ()> {
+        let head = self.head.load(Ordering::Relaxed);
+        unsafe { &*node }.next.store(head, Ordering::Relaxed);
+        self.head
+            .compare_exchange(head, node, Ordering::Release, Ordering::Relaxed)
+            .map(|_| ())
+            .map_err(|_| ())
+    }
+
+    /// Tries to unlink the head once, and fails if the head changed.
+    #[inline]
+    fn try_pop(&self, handle: &mut Handle<'_>) -> Result<Option<T>, ()> {
+        let head = handle.head.protect(&self.head);
+        if head.is_null() {
+            handle.head.reset_protection();
+            return Ok(None);
+        }
+        let next = unsafe { &*head }.next.load(Ordering::Relaxed);
+        let result = self
+            .head
+            .compare_exchange(head, next, Ordering::Acquire, Ordering::Relaxed)
+            .map(|_| unsafe {
+                let item = ptr::read(&*(*head).item);
+                handle.thread.retire(head);
+                Some(item)
+            })
+            .map_err(|_| ());
+        handle.head.reset_protection();
+        result
+    }
+
+    pub fn push(&self, item: T) {
+        let node = Box::into_raw(Box::new(Node::new(item)));
+        while self.try_link(node).is_err() {}
+    }
+
+    pub fn pop(&self, handle: &mut Handle<'_>) -> Option<T> {
+        loop {
+            if let Ok(item) = self.try_pop(handle) {
+                return item;
+            }
+        }
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        let mut curr = *self.head.get_mut();
+        while !curr.is_null() {
+            let mut node = unsafe { Box::from_raw(curr) };
+            unsafe { ManuallyDrop::drop(&mut node.item) };
+            curr = *node.next.get_mut();
+        }
+    }
+}
+
+/// Treiber's stack that backs off to an elimination array after each failed CAS on its head.
+pub struct EliminationStack<T> {
+    stack: TreiberStack<T>,
+    elimination: EliminationArray<T>,
+}
+
+impl<T> Default for EliminationStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> EliminationStack<T> {
+    pub fn new() -> Self {
+        Self {
+            stack: TreiberStack::new(),
+            elimination: EliminationArray::default(),
+        }
+    }
+
+    pub fn push(&self, item: T) {
+        let mut item = item;
+        loop {
+            let node = Box::into_raw(Box::new(Node::new(item)));
+            if self.stack.try_link(node).is_ok() {
+                return;
+            }
+            item = ManuallyDrop::into_inner(unsafe { Box::from_raw(node) }.item);
+            match self.elimination.push(item) {
+                Ok(()) => return,
+                Err(back) => item = back,
+            }
+        }
+    }
+
+    pub fn pop(&self, handle: &mut Handle<'_>) -> Option<T> {
+        loop {
+            if let Ok(item) = self.stack.try_pop(handle) {
+                return item;
+            }
+            if let Some(item) = self.elimination.pop() {
+                return Some(item);
+            }
+        }
+    }
+}
+
+impl<T> ConcurrentStack<T> for TreiberStack<T> {
+    type Handle<'domain> = Handle<'domain>;
+
+    fn new() -> Self {
+        TreiberStack::new()
+    }
+
+    fn handle() -> Self::Handle<'static> {
+        Handle::default()
+    }
+
+    #[inline(always)]
+    fn push(&self, _: &mut Self::Handle<'_>, item: T) {
+        self.push(item)
+    }
+
+    #[inline(always)]
+    fn pop(&self, handle: &mut Self::Handle<'_>) -> Option<T> {
+        self.pop(handle)
+    }
+}
+
+impl<T> ConcurrentStack<T> for EliminationStack<T> {
+    type Handle<'domain> = Handle<'domain>;
+
+    fn new() -> Self {
+        EliminationStack::new()
+    }
+
+    fn handle() -> Self::Handle<'static> {
+        Handle::default()
+    }
+
+    #[inline(always)]
+    fn push(&self, _: &mut Self::Handle<'_>, item: T) {
+        self.push(item)
+    }
+
+    #[inline(always)]
+    fn pop(&self, handle: &mut Self::Handle<'_>) -> Option<T> {
+        self.pop(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EliminationStack, TreiberStack};
+    use crate::ds_impl::hp::concurrent_stack::ConcurrentStack;
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn simple<S: ConcurrentStack<String>>() {
+        let stack = S::new();
+        let handle = &mut S::handle();
+        assert!(stack.pop(handle).is_none());
+        stack.push(handle, "1".to_string());
+        stack.push(handle, "2".to_string());
+        assert_eq!(stack.pop(handle).unwrap(), "2");
+        stack.push(handle, "3".to_string());
+        assert_eq!(stack.pop(handle).unwrap(), "3");
+        assert_eq!(stack.pop(handle).unwrap(), "1");
+        assert!(stack.pop(handle).is_none());
+        // The remaining items are dropped with the stack.
+        stack.push(handle, "4".to_string());
+    }
+
+    fn smoke<S: ConcurrentStack<usize> + Sync>() {
+        const THREADS: usize = 30;
+        const ELEMENTS_PER_THREAD: usize = 10000;
+
+        let stack = S::new();
+        let mut found = Vec::new();
+        found.resize_with(THREADS * ELEMENTS_PER_THREAD, || AtomicU32::new(0));
+
+        scope(|s| {
+            for t in 0..THREADS {
+                let (stack, found) = (&stack, &found);
+                s.spawn(move |_| {
+                    let handle = &mut S::handle();
+                    for i in 0..ELEMENTS_PER_THREAD {
+                        stack.push(handle, t * ELEMENTS_PER_THREAD + i);
+                        let item = stack.pop(handle).unwrap();
+                        found[item].fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        })
+        .unwrap();
+        assert!(found.iter().all(|f| f.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn treiber() {
+        simple::<TreiberStack<_>>();
+        smoke::<TreiberStack<_>>();
+    }
+
+    #[test]
+    fn elimination() {
+        simple::<EliminationStack<_>>();
+        smoke::<EliminationStack<_>>();
+    }
+}
@@ -33,7 +33,7 @@ impl Retired {
 
 /// a real node in tree or a wrapper of State node
 /// Retired node if Shared ptr of Node has RETIRED tag.
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: K,
     value: V,
     size: usize,
@@ -809,4 +809,14 @@ mod tests {
     fn smoke_bonsai_tree() {
         concurrent_map::tests::smoke::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_bonsai_tree() {
+        concurrent_map::tests::edge_cases::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn pull_stress_bonsai_tree() {
+        concurrent_map::tests::pull_stress::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
+    }
 }
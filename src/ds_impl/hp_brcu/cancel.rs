@@ -0,0 +1,163 @@
+//! Deterministic cancellation points in the critical sections of the `hp-brcu` structures.
+//!
+//! A reclaimer neutralizes a thread that delays it, which restarts the critical section of the
+//! thread from its beginning, but only under the contention of a real run and wherever the signal
+//! happens to arrive, so the rollback paths of a traversal run rarely and at random positions. The
+//! traversals of the lists and `nm-tree` call [`point`] at each node that they visit in a critical
+//! section, and a test arms a schedule with [`arm`] that restarts the section at every
+//! `period`-th point of the thread, until a budget of restarts is spent. The points do nothing
+//! outside the test builds.
+
+use hp_brcu::CsGuard;
+
+#[cfg(test)]
+use std::cell::Cell;
+
+#[cfg(test)]
+#[derive(Clone, Copy)]
+struct Schedule {
+    period: usize,
+    points: usize,
+    budget: usize,
+    restarts: usize,
+}
+
+#[cfg(test)]
+thread_local! {
+    static SCHEDULE: Cell<Schedule> = const {
+        Cell::new(Schedule {
+            period: 1,
+            points: 0,
+            budget: 0,
+            restarts: 0,
+        })
+    };
+}
+
+/// A cancellation point of a critical section, which restarts the section if the schedule of the
+/// thread says so.
+#[inline(always)]
+pub fn point(guard: &CsGuard) {
+    #[cfg(test)]
+    {
+        let mut schedule = SCHEDULE.with(Cell::get);
+        if schedule.budget == 0 {
+            return;
+        }
+        schedule.points += 1;
+        let cancel = schedule.points % schedule.period == 0;
+        if cancel {
+            schedule.budget -= 1;
+            schedule.restarts += 1;
+        }
+        SCHEDULE.with(|s| s.set(schedule));
+        if cancel {
+            guard.restart();
+        }
+    }
+    #[cfg(not(test))]
+    let _ = guard;
+}
+
+/// Makes the calling thread restart its critical section at every `period`-th cancellation point
+/// from now on, `budget` times at most.
+#[cfg(test)]
+pub fn arm(period: usize, budget: usize) {
+    assert!(period > 0);
+    SCHEDULE.with(|s| {
+        s.set(Schedule {
+            period,
+            points: 0,
+            budget,
+            restarts: 0,
+        })
+    });
+}
+
+/// Stops restarting the critical sections of the calling thread, and returns the number of
+/// restarts since it was armed.
+#[cfg(test)]
+pub fn disarm() -> usize {
+    SCHEDULE.with(|s| {
+        let schedule = s.get();
+        s.set(Schedule {
+            budget: 0,
+            ..schedule
+        });
+        schedule.restarts
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{arm, disarm};
+    use crate::ds_impl::hp_brcu::concurrent_map::{ConcurrentMap, OutputHolder};
+    use crossbeam_utils::thread;
+    use hp_brcu::THREAD;
+    use rand::prelude::*;
+    use std::collections::BTreeMap;
+
+    const THREADS: i32 = 8;
+    const KEYS: i32 = 64;
+    const OPS: usize = 10000;
+    const MAX_RESTARTS: usize = 3;
+
+    /// Runs random operations on a map, each with its critical sections restarted at every
+    /// `period`-th cancellation point for a random period up to twice the number of keys, a few
+    /// times at most, and checks the result of each against a sequential model. A few threads do
+    /// the same on their own keys meanwhile, so that the restarted traversals also meet the nodes
+    /// that the others unlink. An operation must take effect exactly once however often its
+    /// sections restart, or the model and the map diverge.
+    pub fn restarts<M: ConcurrentMap<i32, String> + Send + Sync>() {
+        let map = &M::new();
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    THREAD.with(|thread| {
+                        let thread = &mut **thread.borrow_mut();
+                        let output = &mut M::empty_output(thread);
+                        let rng = &mut rand::thread_rng();
+                        let mut model = BTreeMap::new();
+                        let mut restarts = 0;
+                        for _ in 0..OPS {
+                            let key = rng.gen_range(0..KEYS) * THREADS + t;
+                            arm(
+                                rng.gen_range(1..=2 * KEYS as usize),
+                                rng.gen_range(1..=MAX_RESTARTS),
+                            );
+                            match rng.gen_range(0..3) {
+                                0 => {
+                                    let found = map.get(&key, output, thread);
+                                    assert_eq!(model.contains_key(&key), found, "get {key}");
+                                    if found {
+                                        assert_eq!(model[&key], *output.output());
+                                    }
+                                }
+                                1 => {
+                                    let value = rng.gen::<u32>().to_string();
+                                    let inserted = map.insert(key, value.clone(), output, thread);
+                                    assert_eq!(!model.contains_key(&key), inserted, "insert {key}");
+                                    model.entry(key).or_insert(value);
+                                }
+                                _ => {
+                                    let removed = map.remove(&key, output, thread);
+                                    assert_eq!(
+                                        model.remove(&key).is_some(),
+                                        removed,
+                                        "remove {key}"
+                                    );
+                                }
+                            }
+                            restarts += disarm();
+                        }
+                        assert!(restarts > 0, "no critical section was restarted");
+                        for key in (0..KEYS).map(|k| k * THREADS + t) {
+                            assert_eq!(model.contains_key(&key), map.get(&key, output, thread));
+                        }
+                    });
+                });
+            }
+        })
+        .unwrap();
+    }
+}
@@ -16,6 +16,32 @@ pub trait ConcurrentMap<K, V> {
     fn get(&self, key: &K, output: &mut Self::Output, thread: &mut Thread) -> bool;
     fn insert(&self, key: K, value: V, output: &mut Self::Output, thread: &mut Thread) -> bool;
     fn remove(&self, key: &K, output: &mut Self::Output, thread: &mut Thread) -> bool;
+
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    /// The value is also left in `output`, as with `get`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(
+        &self,
+        key: K,
+        value: V,
+        output: &mut Self::Output,
+        thread: &mut Thread,
+    ) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if self.get(&key, output, thread) {
+                return Some(output.output().clone());
+            }
+            if self.insert(key.clone(), value.clone(), output, thread) {
+                return None;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -25,10 +51,13 @@ pub mod tests {
     use crossbeam_utils::thread;
     use hp_brcu::THREAD;
     use rand::prelude::*;
+    use std::collections::VecDeque;
     use std::fmt::Debug;
 
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
+    const PULL_KEYS: i32 = 200;
+    const PULL_ROUNDS: i32 = 5;
 
     pub fn smoke<V, M, F>(to_value: &F)
     where
@@ -96,4 +125,184 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Checks that an output of `get` keeps its value until it is used again, while the same
+    /// thread uses other outputs and another thread removes the keys and inserts them again with
+    /// other values. The output is protected by its hazard pointers after the critical section of
+    /// the operation ends.
+    ///
+    /// The output of `remove` is not checked, as some structures do not protect the removed node
+    /// with it.
+    pub fn output_holder<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        THREAD.with(|thread| {
+            let thread = &mut **thread.borrow_mut();
+            let output = &mut M::empty_output(thread);
+            for i in 0..ELEMENTS_PER_THREADS {
+                assert!(map.insert(i, to_value(&i), output, thread));
+            }
+
+            let got = &mut M::empty_output(thread);
+            assert!(map.get(&0, got, thread));
+            assert!(map.remove(&0, output, thread));
+            assert!(map.insert(0, to_value(&-1), output, thread));
+            assert!(map.get(&0, output, thread));
+            assert_eq!(to_value(&-1), *output.output());
+
+            thread::scope(|s| {
+                s.spawn(move |_| {
+                    THREAD.with(|thread| {
+                        let thread = &mut **thread.borrow_mut();
+                        let output = &mut M::empty_output(thread);
+                        for i in 0..ELEMENTS_PER_THREADS {
+                            map.remove(&i, output, thread);
+                            assert!(map.insert(i, to_value(&(i + 1)), output, thread));
+                        }
+                    });
+                });
+            })
+            .unwrap();
+
+            assert_eq!(to_value(&0), *got.output());
+            // Reusing an output replaces its value.
+            assert!(map.get(&2, got, thread));
+            assert_eq!(to_value(&3), *got.output());
+        });
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    ///
+    /// The output of `remove` is not checked, as some structures do not protect the removed
+    /// node with it.
+    pub fn edge_cases<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        THREAD.with(|thread| {
+            let thread = &mut **thread.borrow_mut();
+            let output = &mut M::empty_output(thread);
+
+            // An empty map.
+            assert!(!map.get(&0, output, thread));
+            assert!(!map.remove(&0, output, thread));
+
+            // A duplicate insert keeps the old value.
+            assert!(map.insert(0, to_value(&0), output, thread));
+            assert!(!map.insert(0, to_value(&1), output, thread));
+            assert!(map.get(&0, output, thread));
+            assert_eq!(to_value(&0), *output.output());
+
+            // Removing the only key empties the map, which replaces the root of some trees.
+            assert!(map.remove(&0, output, thread));
+            assert!(!map.get(&0, output, thread));
+            assert!(!map.remove(&0, output, thread));
+
+            // Removing the smallest, the middle, and the largest of three keys.
+            for removed in 1..=3 {
+                for i in 1..=3 {
+                    assert!(map.insert(i, to_value(&i), output, thread));
+                }
+                assert!(map.remove(&removed, output, thread));
+                for i in 1..=3 {
+                    assert_eq!(i != removed, map.get(&i, output, thread));
+                }
+                for i in (1..=3).filter(|&i| i != removed) {
+                    assert!(map.remove(&i, output, thread));
+                }
+            }
+            for i in 0..=3 {
+                assert!(!map.get(&i, output, thread));
+            }
+        });
+    }
+
+    /// Removes the keys of a tree in the order of the levels of a balanced tree, medians first, and
+    /// inserts them back in the same order, for a few rounds. Most keys then have two children when
+    /// they are removed, which pulls the rightmost node of the left subtree up into their place, and
+    /// the rest have a child on one side, which pulls the leftmost node of the right subtree. Both
+    /// rebuild the path to the pulled node, which the uniform keys of `smoke` rarely do. The tree is
+    /// prefilled in an increasing order, which skews it until it is rebalanced. Each thread owns
+    /// every `THREADS`-th key, and checks that its keys next to each removed key keep their values.
+    pub fn pull_stress<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        THREAD.with(|thread| {
+            let thread = &mut **thread.borrow_mut();
+            let output = &mut M::empty_output(thread);
+            for i in 0..THREADS * PULL_KEYS {
+                assert!(map.insert(i, to_value(&i), output, thread));
+            }
+        });
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    THREAD.with(|thread| {
+                        let thread = &mut **thread.borrow_mut();
+                        let output = &mut M::empty_output(thread);
+                        let keys = medians_first(PULL_KEYS);
+                        for _ in 0..PULL_ROUNDS {
+                            let mut removed = vec![false; PULL_KEYS as usize];
+                            for &k in &keys {
+                                let i = k * THREADS + t;
+                                assert!(map.remove(&i, output, thread));
+                                removed[k as usize] = true;
+                                for n in [k - 1, k + 1] {
+                                    if (0..PULL_KEYS).contains(&n) && !removed[n as usize] {
+                                        let j = n * THREADS + t;
+                                        assert!(map.get(&j, output, thread));
+                                        assert_eq!(to_value(&j), *output.output());
+                                    }
+                                }
+                            }
+                            for &k in &keys {
+                                let i = k * THREADS + t;
+                                assert!(map.insert(i, to_value(&i), output, thread));
+                            }
+                        }
+                    });
+                });
+            }
+        })
+        .unwrap();
+
+        THREAD.with(|thread| {
+            let thread = &mut **thread.borrow_mut();
+            let output = &mut M::empty_output(thread);
+            for j in 0..THREADS * PULL_KEYS {
+                assert!(map.get(&j, output, thread));
+                assert_eq!(to_value(&j), *output.output());
+            }
+        });
+    }
+
+    /// The keys in `0..n` in the order of the levels of a balanced tree, medians first.
+    fn medians_first(n: i32) -> Vec<i32> {
+        let mut keys = Vec::with_capacity(n as usize);
+        let mut ranges = VecDeque::from([(0, n)]);
+        while let Some((lo, hi)) = ranges.pop_front() {
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                keys.push(mid);
+                ranges.push_back((lo, mid));
+                ranges.push_back((mid + 1, hi));
+            }
+        }
+        keys
+    }
 }
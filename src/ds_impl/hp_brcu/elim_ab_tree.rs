@@ -121,7 +121,7 @@ enum Operation {
     Balance,
 }
 
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     keys: [Cell<Option<K>>; DEGREE],
     search_key: K,
     lock: AtomicPtr<MCSLockSlot<K, V>>,
@@ -1389,4 +1389,9 @@ mod tests {
     fn smoke_elim_ab_tree() {
         concurrent_map::tests::smoke::<_, ElimABTree<i32, i32>, _>(&|a| *a);
     }
+
+    #[test]
+    fn edge_cases_elim_ab_tree() {
+        concurrent_map::tests::edge_cases::<_, ElimABTree<i32, i32>, _>(&|a| *a);
+    }
 }
@@ -1,11 +1,15 @@
+use super::cancel;
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 
 use hp_brcu::{Atomic, CsGuard, Owned, RollbackProof, Shared, Shield, Thread, Unprotected};
 
 use std::cmp::Ordering::{Equal, Greater, Less};
 use std::sync::atomic::Ordering;
 
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     /// Mark: tag(), Tag: not needed
     next: Atomic<Node<K, V>>,
     key: K,
@@ -126,7 +130,7 @@ where
         key: &K,
         cursor: &mut Cursor<K, V>,
         thread: &mut Thread,
-    ) -> Result<bool, ()> {
+    ) -> Result<bool, Retry> {
         let found = unsafe {
             thread.critical_section(|guard| {
                 let (mut prev, mut curr) = initialize(&self.head, guard);
@@ -136,6 +140,7 @@ where
                 // - cursor.prev: the ref of .next in previous unmarked node (1 -> 2)
                 // 1 -> 2 -x-> 3 -x-> 4 -> 5 -> ∅  (search key: 4)
                 let found = loop {
+                    cancel::point(guard);
                     let Some(curr_node) = curr.as_ref() else {
                         break false;
                     };
@@ -190,7 +195,7 @@ where
             )
             .is_err()
         {
-            return Err(());
+            return Err(Retry::Unlink);
         }
 
         // retire from cursor.prev.load() to cursor.curr (exclusive)
@@ -215,11 +220,12 @@ where
         key: &K,
         cursor: &mut Cursor<K, V>,
         thread: &mut Thread,
-    ) -> Result<bool, ()> {
+    ) -> Result<bool, Retry> {
         unsafe {
             thread.critical_section(|guard| {
                 let (mut prev, mut curr) = initialize(&self.head, guard);
                 let found = loop {
+                    cancel::point(guard);
                     let Some(curr_node) = curr.as_ref() else {
                         break false;
                     };
@@ -242,7 +248,7 @@ where
                                     guard,
                                 )
                                 .map(|_| guard.retire(curr))
-                                .map_err(|_| ())
+                                .map_err(|_| Retry::Unlink)
                         })?;
                         curr = next;
                         continue;
@@ -271,14 +277,17 @@ where
         key: &K,
         cursor: &mut Cursor<K, V>,
         thread: &mut Thread,
-    ) -> Result<bool, ()> {
+    ) -> Result<bool, Retry> {
         unsafe {
             thread.critical_section(|guard| {
+                wait_free::traversal();
                 let (_, mut curr) = initialize(&self.head, guard);
                 let found = loop {
+                    cancel::point(guard);
                     let Some(curr_node) = curr.as_ref() else {
                         break false;
                     };
+                    wait_free::visit();
                     let next = curr_node.next.load(Ordering::Acquire, guard);
                     match curr_node.key.cmp(key) {
                         Less => {
@@ -298,11 +307,12 @@ where
     #[inline]
     pub fn get<F>(&self, find: &F, key: &K, cursor: &mut Cursor<K, V>, thread: &mut Thread) -> bool
     where
-        F: Fn(&List<K, V>, &K, &mut Cursor<K, V>, &mut Thread) -> Result<bool, ()>,
+        F: Fn(&List<K, V>, &K, &mut Cursor<K, V>, &mut Thread) -> Result<bool, Retry>,
     {
         loop {
-            if let Ok(found) = find(self, key, cursor, thread) {
-                return found;
+            match find(self, key, cursor, thread) {
+                Ok(found) => return found,
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -317,7 +327,7 @@ where
         thread: &mut Thread,
     ) -> bool
     where
-        F: Fn(&List<K, V>, &K, &mut Cursor<K, V>, &mut Thread) -> Result<bool, ()>,
+        F: Fn(&List<K, V>, &K, &mut Cursor<K, V>, &mut Thread) -> Result<bool, Retry>,
     {
         let mut new_node = Owned::new(Node::new(key, value));
         loop {
@@ -329,15 +339,25 @@ where
                 .next
                 .store(cursor.curr.shared(), Ordering::Relaxed, thread);
 
-            match cursor.prev.as_ref().unwrap().next.compare_exchange(
-                cursor.curr.shared(),
-                new_node,
-                Ordering::Release,
-                Ordering::Relaxed,
-                thread,
-            ) {
-                Ok(_) => return true,
-                Err(e) => new_node = e.new,
+            match cursor
+                .prev
+                .as_ref()
+                .unwrap()
+                .next
+                .compare_exchange(
+                    cursor.curr.shared(),
+                    new_node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    thread,
+                )
+                .classify(&cursor.curr.shared())
+            {
+                Cas::Success(_) => return true,
+                Cas::CurrentDiffers(e) | Cas::TagDiffers(e) => {
+                    Retry::CasFail.count();
+                    new_node = e.new;
+                }
             }
         }
     }
@@ -351,7 +371,7 @@ where
         thread: &mut Thread,
     ) -> bool
     where
-        F: Fn(&List<K, V>, &K, &mut Cursor<K, V>, &mut Thread) -> Result<bool, ()>,
+        F: Fn(&List<K, V>, &K, &mut Cursor<K, V>, &mut Thread) -> Result<bool, Retry>,
     {
         loop {
             if !self.get(&find, &key, cursor, thread) {
@@ -362,6 +382,7 @@ where
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel, thread);
 
             if (next.tag() & 1) != 0 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -528,12 +549,64 @@ fn smoke_h_list() {
     super::concurrent_map::tests::smoke::<_, HList<i32, String>, _>(&i32::to_string);
 }
 
+#[test]
+fn edge_cases_h_list() {
+    super::concurrent_map::tests::edge_cases::<_, HList<i32, String>, _>(&i32::to_string);
+}
+
 #[test]
 fn smoke_hm_list() {
     super::concurrent_map::tests::smoke::<_, HMList<i32, String>, _>(&i32::to_string);
 }
 
+#[test]
+fn edge_cases_hm_list() {
+    super::concurrent_map::tests::edge_cases::<_, HMList<i32, String>, _>(&i32::to_string);
+}
+
 #[test]
 fn smoke_hhs_list() {
     super::concurrent_map::tests::smoke::<_, HHSList<i32, String>, _>(&i32::to_string);
 }
+
+#[test]
+fn edge_cases_hhs_list() {
+    super::concurrent_map::tests::edge_cases::<_, HHSList<i32, String>, _>(&i32::to_string);
+}
+
+#[test]
+fn restarts_h_list() {
+    super::cancel::tests::restarts::<HList<i32, String>>();
+}
+
+#[test]
+fn restarts_hm_list() {
+    super::cancel::tests::restarts::<HMList<i32, String>>();
+}
+
+#[test]
+fn restarts_hhs_list() {
+    super::cancel::tests::restarts::<HHSList<i32, String>>();
+}
+
+#[test]
+fn output_holder_hhs_list() {
+    super::concurrent_map::tests::output_holder::<_, HHSList<i32, String>, _>(&i32::to_string);
+}
+
+#[test]
+fn wait_free_get_hhs_list() {
+    use crate::ds_impl::wait_free::{check_get, Op};
+    use hp_brcu::THREAD;
+    let map = HHSList::<i32, String>::new();
+    THREAD.with(|thread| {
+        let thread = &mut **thread.borrow_mut();
+        let output = &mut HHSList::<i32, String>::empty_output(thread);
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(key, key.to_string(), output, thread),
+            Op::Remove => map.remove(&key, output, thread),
+            Op::Get => map.get(&key, output, thread),
+        });
+    });
+}
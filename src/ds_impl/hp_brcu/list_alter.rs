@@ -1,4 +1,5 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::retry::Retry;
 
 use hp_brcu::{
     Atomic, CsGuard, Owned, Protector, RollbackProof, Shared, Shield, Thread, Unprotected,
@@ -189,7 +190,7 @@ where
         key: &K,
         output: &mut Output<K, V>,
         thread: &mut Thread,
-    ) -> Result<bool, ()> {
+    ) -> Result<bool, Retry> {
         let found = unsafe {
             thread.traverse(
                 &mut output.0,
@@ -247,7 +248,7 @@ where
             )
             .is_err()
         {
-            return Err(());
+            return Err(Retry::Unlink);
         }
 
         // retire from cursor.prev.load() to cursor.curr (exclusive)
@@ -272,7 +273,7 @@ where
         key: &K,
         output: &mut Output<K, V>,
         thread: &mut Thread,
-    ) -> Result<bool, ()> {
+    ) -> Result<bool, Retry> {
         unsafe {
             thread.traverse(
                 &mut output.0,
@@ -313,7 +314,7 @@ where
                             cursor.curr = next;
                             return (cursor, None);
                         }
-                        return (cursor, Some(Err(())));
+                        return (cursor, Some(Err(Retry::Unlink)));
                     }
 
                     match curr_node.key.as_ref().map(|k| k.cmp(key)).unwrap_or(Less) {
@@ -336,7 +337,7 @@ where
         key: &K,
         output: &mut Output<K, V>,
         thread: &mut Thread,
-    ) -> Result<bool, ()> {
+    ) -> Result<bool, Retry> {
         Ok(unsafe {
             thread.traverse(
                 &mut output.0,
@@ -364,11 +365,12 @@ where
     #[inline]
     pub fn get<F>(&self, find: &F, key: &K, output: &mut Output<K, V>, thread: &mut Thread) -> bool
     where
-        F: Fn(&List<K, V>, &K, &mut Output<K, V>, &mut Thread) -> Result<bool, ()>,
+        F: Fn(&List<K, V>, &K, &mut Output<K, V>, &mut Thread) -> Result<bool, Retry>,
     {
         loop {
-            if let Ok(found) = find(self, key, output, thread) {
-                return found;
+            match find(self, key, output, thread) {
+                Ok(found) => return found,
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -383,7 +385,7 @@ where
         thread: &mut Thread,
     ) -> bool
     where
-        F: Fn(&List<K, V>, &K, &mut Output<K, V>, &mut Thread) -> Result<bool, ()>,
+        F: Fn(&List<K, V>, &K, &mut Output<K, V>, &mut Thread) -> Result<bool, Retry>,
     {
         let mut new_node = Owned::new(Node::new(key, value));
         loop {
@@ -404,7 +406,10 @@ where
                 thread,
             ) {
                 Ok(_) => return true,
-                Err(e) => new_node = e.new,
+                Err(e) => {
+                    Retry::CasFail.count();
+                    new_node = e.new;
+                }
             }
         }
     }
@@ -418,7 +423,7 @@ where
         thread: &mut Thread,
     ) -> bool
     where
-        F: Fn(&List<K, V>, &K, &mut Output<K, V>, &mut Thread) -> Result<bool, ()>,
+        F: Fn(&List<K, V>, &K, &mut Output<K, V>, &mut Thread) -> Result<bool, Retry>,
     {
         loop {
             if !self.get(&find, &key, output, thread) {
@@ -430,6 +435,7 @@ where
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel, thread);
 
             if (next.tag() & 1) != 0 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -472,6 +478,7 @@ where
                 let next = curr_node.next.fetch_or(1, Ordering::AcqRel, thread);
 
                 if (next.tag() & 1) != 0 {
+                    Retry::CasFail.count();
                     continue;
                 }
 
@@ -649,12 +656,27 @@ fn smoke_h_list() {
     super::concurrent_map::tests::smoke::<_, HList<i32, String>, _>(&i32::to_string);
 }
 
+#[test]
+fn edge_cases_h_list() {
+    super::concurrent_map::tests::edge_cases::<_, HList<i32, String>, _>(&i32::to_string);
+}
+
 #[test]
 fn smoke_hm_list() {
     super::concurrent_map::tests::smoke::<_, HMList<i32, String>, _>(&i32::to_string);
 }
 
+#[test]
+fn edge_cases_hm_list() {
+    super::concurrent_map::tests::edge_cases::<_, HMList<i32, String>, _>(&i32::to_string);
+}
+
 #[test]
 fn smoke_hhs_list() {
     super::concurrent_map::tests::smoke::<_, HHSList<i32, String>, _>(&i32::to_string);
 }
+
+#[test]
+fn edge_cases_hhs_list() {
+    super::concurrent_map::tests::edge_cases::<_, HHSList<i32, String>, _>(&i32::to_string);
+}
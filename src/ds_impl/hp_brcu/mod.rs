@@ -1,12 +1,13 @@
 pub mod concurrent_map;
 
-mod bonsai_tree;
-mod elim_ab_tree;
-mod list;
+pub(crate) mod bonsai_tree;
+pub(crate) mod cancel;
+pub(crate) mod elim_ab_tree;
+pub(crate) mod list;
 pub mod list_alter;
 mod michael_hash_map;
-mod natarajan_mittal_tree;
-mod skip_list;
+pub(crate) mod natarajan_mittal_tree;
+pub(crate) mod skip_list;
 
 pub use self::concurrent_map::ConcurrentMap;
 pub use bonsai_tree::BonsaiTreeMap;
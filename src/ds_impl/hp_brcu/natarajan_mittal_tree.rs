@@ -2,7 +2,10 @@ use std::{cmp, sync::atomic::Ordering};
 
 use hp_brcu::{Atomic, Owned, Pointer, RollbackProof, Shared, Shield, Thread, Unprotected};
 
+use super::cancel;
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 
 bitflags! {
     /// TODO
@@ -85,7 +88,7 @@ where
     }
 }
 
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: Key<K>,
     value: Option<V>,
     left: Atomic<Node<K, V>>,
@@ -273,6 +276,7 @@ where
                 let mut prev_tag = Marks::from_bits_truncate(leaf.tag()).tag();
 
                 while let Some(curr_node) = curr.as_ref() {
+                    cancel::point(guard);
                     if !prev_tag {
                         // untagged edge: advance ancestor and successor pointers
                         ancestor = parent;
@@ -318,6 +322,7 @@ where
                 let mut curr = leaf_node.left.load(Ordering::Acquire, guard).with_tag(0);
 
                 while let Some(curr_node) = curr.as_ref() {
+                    cancel::point(guard);
                     leaf = curr;
                     if curr_node.key.cmp(key) == cmp::Ordering::Greater {
                         curr = curr_node.left.load(Ordering::Acquire, guard);
@@ -338,6 +343,18 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire, handle);
         let leaf_flag = Marks::from_bits_truncate(leaf_marked.tag()).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag
+                || Marks::from_bits_truncate(
+                    record
+                        .leaf_sibling_addr()
+                        .load(Ordering::Acquire, handle)
+                        .tag()
+                )
+                .flag(),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -353,7 +370,18 @@ where
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load(Ordering::Acquire, handle);
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(target_sibling.tag()).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(target_sibling.tag()).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            record.successor.shared().tag(),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         let is_unlinked = record
             .successor_addr()
             .compare_exchange(
@@ -363,7 +391,8 @@ where
                 Ordering::Acquire,
                 handle,
             )
-            .is_ok();
+            .classify(&record.successor.shared())
+            .is_success();
 
         if is_unlinked {
             unsafe {
@@ -442,21 +471,25 @@ where
                 .store(new_right, Ordering::Relaxed, handle);
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match output.leaf_addr().compare_exchange(
-                output.leaf.shared(),
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                handle,
-            ) {
-                Ok(_) => return true,
-                Err(e) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if e.actual.with_tag(Marks::empty().bits()) == output.leaf.shared() {
-                        self.cleanup(output, handle);
-                    }
+            match output
+                .leaf_addr()
+                .compare_exchange(
+                    output.leaf.shared(),
+                    new_internal,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    handle,
+                )
+                .classify(&output.leaf.shared())
+            {
+                Cas::Success(_) => return true,
+                // Insertion failed. Help the conflicting remove operation if needed.
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    InsertConflict::Help.count();
+                    self.cleanup(output, handle);
                 }
+                Cas::CurrentDiffers(_) => InsertConflict::Retry.count(),
             }
         }
     }
@@ -475,17 +508,26 @@ where
             }
 
             // Try injecting the deletion flag.
-            match output.leaf_addr().compare_exchange(
-                output.leaf.shared(),
-                output
-                    .leaf
-                    .shared()
-                    .with_tag(Marks::new(true, false).bits()),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-                handle,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(
+                output.leaf.shared().tag(),
+                0,
+                "the leaf edge is expected unmarked"
+            );
+            match output
+                .leaf_addr()
+                .compare_exchange(
+                    output.leaf.shared(),
+                    output
+                        .leaf
+                        .shared()
+                        .with_tag(Marks::new(true, false).bits()),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    handle,
+                )
+                .classify(&output.leaf.shared())
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(output, handle) {
                         return true;
@@ -493,15 +535,14 @@ where
                     // In-place cleanup failed. Enter the cleanup phase.
                     break;
                 }
-                Err(e) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if output.leaf.shared() == e.actual.with_tag(Marks::empty().bits()) {
-                        self.cleanup(output, handle);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(output, handle);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         }
 
@@ -558,10 +599,20 @@ where
 #[cfg(test)]
 mod tests {
     use super::NMTreeMap;
-    use crate::ds_impl::hp_brcu::concurrent_map;
+    use crate::ds_impl::hp_brcu::{cancel, concurrent_map};
 
     #[test]
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn restarts_nm_tree() {
+        cancel::tests::restarts::<NMTreeMap<i32, String>>();
+    }
 }
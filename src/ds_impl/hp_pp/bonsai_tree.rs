@@ -1,7 +1,7 @@
 use hp_pp::{light_membarrier, Invalidate, Thread, Unlink};
 use hp_pp::{tag, tagged, untagged, HazardPointer, ProtectError, DEFAULT_DOMAIN};
 
-use crate::ds_impl::hp::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::hp::concurrent_map::{ConcurrentMap, OutputHolder, ProtectionSet};
 
 use std::cmp;
 use std::ptr;
@@ -114,6 +114,8 @@ pub struct State<'domain, K, V> {
     root_h: HazardPointer<'domain>,
     succ_h: HazardPointer<'domain>, // Used for traversing in get
     removed_h: HazardPointer<'domain>,
+    /// The children of the nodes on the path of an update, protected until the update ends.
+    path: ProtectionSet<'domain>,
     /// Nodes that current op wants to remove from the tree. Should be retired if CAS succeeds.
     /// (`retire`). If not, ignore.
     retired_nodes: Vec<*mut Node<K, V>>,
@@ -131,6 +133,7 @@ impl<K, V> Default for State<'static, K, V> {
             root_h: HazardPointer::new(&mut thread),
             succ_h: HazardPointer::new(&mut thread),
             removed_h: HazardPointer::new(&mut thread),
+            path: ProtectionSet::new(),
             retired_nodes: vec![],
             new_nodes: vec![],
             thread,
@@ -154,6 +157,7 @@ where
     /// Destroy the newly created state (self) that lost the race (reclaim_state)
     fn abort(&mut self) {
         self.root_h.reset_protection();
+        self.path.release();
         self.retired_nodes.clear();
 
         for node in self.new_nodes.drain(..) {
@@ -163,6 +167,7 @@ where
 
     fn clear(&mut self) {
         self.root_h.reset_protection();
+        self.path.release();
         self.new_nodes.clear();
         self.retired_nodes.clear();
     }
@@ -242,12 +247,8 @@ where
         value: V,
     ) -> Result<*mut Node<K, V>, ()> {
         let right_ref = unsafe { &*untagged(right) };
-        let (mut right_left_h, mut right_right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (right_left, right_right) =
-            right_ref.protect_next(&mut right_left_h, &mut right_right_h)?;
+        let (right_left_h, right_right_h) = self.path.take_pair(&mut self.thread);
+        let (right_left, right_right) = right_ref.protect_next(right_left_h, right_right_h)?;
 
         if !self.check_root()
             || Node::is_retired_spot(right_left)
@@ -299,12 +300,9 @@ where
     ) -> Result<*mut Node<K, V>, ()> {
         let right_ref = unsafe { &*untagged(right) };
         let right_left_ref = unsafe { &*untagged(right_left) };
-        let (mut right_left_left_h, mut right_left_right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
+        let (right_left_left_h, right_left_right_h) = self.path.take_pair(&mut self.thread);
         let (right_left_left, right_left_right) =
-            right_left_ref.protect_next(&mut right_left_left_h, &mut right_left_right_h)?;
+            right_left_ref.protect_next(right_left_left_h, right_left_right_h)?;
 
         if !self.check_root()
             || Node::is_retired_spot(right_left_left)
@@ -340,11 +338,8 @@ where
         value: V,
     ) -> Result<*mut Node<K, V>, ()> {
         let left_ref = unsafe { &*untagged(left) };
-        let (mut left_left_h, mut left_right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left_left, left_right) = left_ref.protect_next(&mut left_left_h, &mut left_right_h)?;
+        let (left_left_h, left_right_h) = self.path.take_pair(&mut self.thread);
+        let (left_left, left_right) = left_ref.protect_next(left_left_h, left_right_h)?;
 
         if !self.check_root()
             || Node::is_retired_spot(left_right)
@@ -395,12 +390,9 @@ where
     ) -> Result<*mut Node<K, V>, ()> {
         let left_ref = unsafe { &*untagged(left) };
         let left_right_ref = unsafe { &*untagged(left_right) };
-        let (mut left_right_left_h, mut left_right_right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
+        let (left_right_left_h, left_right_right_h) = self.path.take_pair(&mut self.thread);
         let (left_right_left, left_right_right) =
-            left_right_ref.protect_next(&mut left_right_left_h, &mut left_right_right_h)?;
+            left_right_ref.protect_next(left_right_left_h, left_right_right_h)?;
 
         if !self.check_root()
             || Node::is_retired_spot(left_right_left)
@@ -446,11 +438,8 @@ where
         }
 
         let node_ref = unsafe { &*untagged(node) };
-        let (mut left_h, mut right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left, right) = node_ref.protect_next(&mut left_h, &mut right_h)?;
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h)?;
 
         if !self.check_root() || Node::is_retired_spot(left) || Node::is_retired_spot(right) {
             return Ok((Node::retired_node(), false));
@@ -484,11 +473,8 @@ where
         }
 
         let node_ref = unsafe { &*untagged(node) };
-        let (mut left_h, mut right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left, right) = node_ref.protect_next(&mut left_h, &mut right_h)?;
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h)?;
 
         if !self.check_root() || Node::is_retired_spot(left) || Node::is_retired_spot(right) {
             return Ok((Node::retired_node(), None));
@@ -506,10 +492,10 @@ where
                 }
 
                 if !left.is_null() {
-                    let (new_left, succ, _new_left_h) = self.pull_rightmost(left)?;
+                    let (new_left, succ) = self.pull_rightmost(left)?;
                     return Ok((self.mk_balanced(succ, new_left, right)?, value));
                 }
-                let (new_right, succ, _new_right_h) = self.pull_leftmost(right)?;
+                let (new_right, succ) = self.pull_leftmost(right)?;
                 Ok((self.mk_balanced(succ, left, new_right)?, value))
             }
             cmp::Ordering::Less => {
@@ -526,32 +512,22 @@ where
     fn pull_leftmost(
         &mut self,
         node: *mut Node<K, V>,
-    ) -> Result<
-        (
-            *mut Node<K, V>,
-            *mut Node<K, V>,
-            Option<HazardPointer<'domain>>,
-        ),
-        (),
-    > {
+    ) -> Result<(*mut Node<K, V>, *mut Node<K, V>), ()> {
         if Node::is_retired_spot(node) {
-            return Ok((Node::retired_node(), Node::retired_node(), None));
+            return Ok((Node::retired_node(), Node::retired_node()));
         }
 
         let node_ref = unsafe { &*untagged(node) };
-        let (mut left_h, mut right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left, right) = node_ref.protect_next(&mut left_h, &mut right_h)?;
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h)?;
 
         if !self.check_root() || Node::is_retired_spot(left) || Node::is_retired_spot(right) {
-            return Ok((Node::retired_node(), Node::retired_node(), None));
+            return Ok((Node::retired_node(), Node::retired_node()));
         }
 
         if !left.is_null() {
-            let (new_left, succ, _new_left_h) = self.pull_leftmost(left)?;
-            return Ok((self.mk_balanced(node, new_left, right)?, succ, None));
+            let (new_left, succ) = self.pull_leftmost(left)?;
+            return Ok((self.mk_balanced(node, new_left, right)?, succ));
         }
         // node is the leftmost
         let succ = self.mk_node(
@@ -561,38 +537,28 @@ where
             node_ref.value.clone(),
         );
         self.retire_node(node);
-        return Ok((right, succ, Some(right_h)));
+        return Ok((right, succ));
     }
 
     fn pull_rightmost(
         &mut self,
         node: *mut Node<K, V>,
-    ) -> Result<
-        (
-            *mut Node<K, V>,
-            *mut Node<K, V>,
-            Option<HazardPointer<'domain>>,
-        ),
-        (),
-    > {
+    ) -> Result<(*mut Node<K, V>, *mut Node<K, V>), ()> {
         if Node::is_retired_spot(node) {
-            return Ok((Node::retired_node(), Node::retired_node(), None));
+            return Ok((Node::retired_node(), Node::retired_node()));
         }
 
         let node_ref = unsafe { &*untagged(node) };
-        let (mut left_h, mut right_h) = (
-            HazardPointer::new(&mut self.thread),
-            HazardPointer::new(&mut self.thread),
-        );
-        let (left, right) = node_ref.protect_next(&mut left_h, &mut right_h)?;
+        let (left_h, right_h) = self.path.take_pair(&mut self.thread);
+        let (left, right) = node_ref.protect_next(left_h, right_h)?;
 
         if !self.check_root() || Node::is_retired_spot(left) || Node::is_retired_spot(right) {
-            return Ok((Node::retired_node(), Node::retired_node(), None));
+            return Ok((Node::retired_node(), Node::retired_node()));
         }
 
         if !right.is_null() {
-            let (new_right, succ, _new_right_h) = self.pull_rightmost(right)?;
-            return Ok((self.mk_balanced(node, left, new_right)?, succ, None));
+            let (new_right, succ) = self.pull_rightmost(right)?;
+            return Ok((self.mk_balanced(node, left, new_right)?, succ));
         }
         // node is the rightmost
         let succ = self.mk_node(
@@ -602,7 +568,7 @@ where
             node_ref.value.clone(),
         );
         self.retire_node(node);
-        return Ok((left, succ, Some(left_h)));
+        return Ok((left, succ));
     }
 
     pub fn check_root(&self) -> bool {
@@ -886,4 +852,14 @@ mod tests {
     fn smoke_bonsai_tree() {
         concurrent_map::tests::smoke::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_bonsai_tree() {
+        concurrent_map::tests::edge_cases::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn pull_stress_bonsai_tree() {
+        concurrent_map::tests::pull_stress::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
+    }
 }
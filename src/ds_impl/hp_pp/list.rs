@@ -1,4 +1,7 @@
+use crate::ds_impl::cas::{Cas, Classify};
 use crate::ds_impl::hp::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 
 use std::cmp::Ordering::{Equal, Greater, Less};
 use std::sync::atomic::{AtomicPtr, Ordering};
@@ -168,7 +171,7 @@ where
 {
     /// Clean up a chain of logically removed nodes in each traversal.
     #[inline]
-    fn find_harris(&mut self, key: &K) -> Result<bool, ()> {
+    fn find_harris(&mut self, key: &K) -> Result<bool, Retry> {
         // Finding phase
         // - cursor.curr: first unmarked node w/ key >= search key (4)
         // - cursor.prev: the ref of .next in previous unmarked node (1 -> 2)
@@ -189,7 +192,7 @@ where
                 )
                 .is_err()
             {
-                return Err(());
+                return Err(Retry::Validation);
             }
 
             let curr_node = unsafe { &*self.curr };
@@ -233,13 +236,13 @@ where
                 self.prev = self.anchor;
                 Ok(found)
             } else {
-                Err(())
+                Err(Retry::Unlink)
             }
         }
     }
 
     #[inline]
-    fn find_harris_michael(&mut self, key: &K) -> Result<bool, ()> {
+    fn find_harris_michael(&mut self, key: &K) -> Result<bool, Retry> {
         loop {
             debug_assert_eq!(tag(self.curr), 0);
             if self.curr.is_null() {
@@ -254,7 +257,7 @@ where
             let (curr_new_base, curr_new_tag) = decompose_ptr(prev.load(Ordering::Acquire));
             if curr_new_tag == 3 {
                 // Invalidated. Restart from head.
-                return Err(());
+                return Err(Retry::Validation);
             } else if curr_new_base != self.curr {
                 // If link changed but not invalidated, retry protecting the new node.
                 self.curr = curr_new_base;
@@ -283,7 +286,7 @@ where
                     next_base,
                 };
                 if unsafe { !self.handle.thread.try_unlink(unlink, links) } {
-                    return Err(());
+                    return Err(Retry::Unlink);
                 }
             }
             self.curr = next_base;
@@ -291,11 +294,13 @@ where
     }
 
     #[inline]
-    fn find_harris_herlihy_shavit(&mut self, key: &K) -> Result<bool, ()> {
+    fn find_harris_herlihy_shavit(&mut self, key: &K) -> Result<bool, Retry> {
+        wait_free::traversal();
         loop {
             if self.curr.is_null() {
                 return Ok(false);
             }
+            wait_free::visit();
 
             let prev = unsafe { &(*self.prev).next };
 
@@ -305,7 +310,7 @@ where
             let (curr_new_base, curr_new_tag) = decompose_ptr(prev.load(Ordering::Acquire));
             if curr_new_tag == 3 {
                 // Invalidated. Restart from head.
-                return Err(());
+                return Err(Retry::Validation);
             } else if curr_new_base != self.curr {
                 // If link changed but not invalidated, retry protecting the new node.
                 self.curr = curr_new_base;
@@ -348,14 +353,14 @@ where
         handle: &'hp mut Handle<'domain>,
     ) -> Option<&'hp V>
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         loop {
             let mut cursor = Cursor::new(&self.head, handle.launder());
             match find(&mut cursor, key) {
                 Ok(true) => return unsafe { Some(&((*cursor.curr).value)) },
                 Ok(false) => return None,
-                Err(_) => continue,
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -365,9 +370,9 @@ where
         node: *mut Node<K, V>,
         find: &F,
         handle: &'hp mut Handle<'domain>,
-    ) -> Result<bool, ()>
+    ) -> Result<bool, Retry>
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         loop {
             let mut cursor = Cursor::new(&self.head, handle.launder());
@@ -381,10 +386,12 @@ where
             if unsafe { &*cursor.prev }
                 .next
                 .compare_exchange(cursor.curr, node, Ordering::Release, Ordering::Relaxed)
-                .is_ok()
+                .classify(&cursor.curr)
+                .is_success()
             {
                 return Ok(true);
             }
+            Retry::CasFail.count();
         }
     }
 
@@ -397,7 +404,7 @@ where
         handle: &'hp mut Handle<'domain>,
     ) -> bool
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         let node = Box::into_raw(Box::new(Node {
             key,
@@ -408,7 +415,7 @@ where
         loop {
             match self.insert_inner(node, &find, handle.launder()) {
                 Ok(r) => return r,
-                Err(()) => continue,
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -418,9 +425,9 @@ where
         key: &K,
         find: &F,
         handle: &'hp mut Handle<'domain>,
-    ) -> Result<Option<&'hp V>, ()>
+    ) -> Result<Option<&'hp V>, Retry>
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         loop {
             let mut cursor = Cursor::new(&self.head, handle.launder());
@@ -433,6 +440,7 @@ where
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel);
             let next_tag = tag(next);
             if next_tag == 1 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -456,12 +464,12 @@ where
         handle: &'hp mut Handle<'domain>,
     ) -> Option<&'hp V>
     where
-        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, ()>,
+        F: Fn(&mut Cursor<'domain, 'hp, K, V>, &K) -> Result<bool, Retry>,
     {
         loop {
             match self.remove_inner(key, &find, handle.launder()) {
                 Ok(r) => return r,
-                Err(_) => continue,
+                Err(retry) => retry.count(),
             }
         }
     }
@@ -478,6 +486,7 @@ where
             let (curr_new_base, curr_new_tag) = decompose_ptr(prev.load(Ordering::Acquire));
             if curr_new_tag == 3 || curr_new_base != cursor.curr {
                 // Invalidated or link changed. Restart from head.
+                Retry::Validation.count();
                 continue;
             }
 
@@ -489,6 +498,7 @@ where
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel);
             let next_tag = tag(next);
             if (next_tag & 1) != 0 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -719,22 +729,52 @@ mod tests {
         concurrent_map::tests::smoke::<_, HList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<_, HList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hm_list() {
         concurrent_map::tests::smoke::<_, HMList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hm_list() {
+        concurrent_map::tests::edge_cases::<_, HMList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<_, HHSList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String>::new();
+        let handle = &mut HHSList::<i32, String>::handle();
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(handle, key, key.to_string()),
+            Op::Remove => map.remove(handle, &key).is_some(),
+            Op::Get => map.get(handle, &key).is_some(),
+        });
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use concurrent_map::ConcurrentMap;
         let map = HHSList::new();
 
         let handle = &mut HHSList::<i32, String>::handle();
+        assert!(map.pop(handle).is_none());
         map.insert(handle, 1, "1".to_string());
         map.insert(handle, 2, "2".to_string());
         map.insert(handle, 3, "3".to_string());
@@ -85,4 +85,9 @@ mod tests {
     fn smoke_hashmap() {
         concurrent_map::tests::smoke::<_, HashMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_hashmap() {
+        concurrent_map::tests::edge_cases::<_, HashMap<i32, String>, _>(&i32::to_string);
+    }
 }
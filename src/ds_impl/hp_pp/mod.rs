@@ -1,4 +1,4 @@
-// NOTE: hp_pp can use hp concurrent_map interface
+// NOTE: hp_pp can use hp concurrent_map and concurrent_stack interfaces
 
 pub mod bonsai_tree;
 pub mod ellen_tree;
@@ -6,6 +6,7 @@ pub mod list;
 pub mod michael_hash_map;
 pub mod natarajan_mittal_tree;
 pub mod skip_list;
+pub mod treiber_stack;
 
 pub use self::bonsai_tree::BonsaiTreeMap;
 pub use self::ellen_tree::EFRBTree;
@@ -13,3 +14,4 @@ pub use self::list::{HHSList, HList, HMList};
 pub use self::michael_hash_map::HashMap;
 pub use self::natarajan_mittal_tree::NMTreeMap;
 pub use self::skip_list::SkipList;
+pub use self::treiber_stack::{EliminationStack, TreiberStack};
@@ -2,7 +2,10 @@ use hp_pp::{
     light_membarrier, tag, tagged, untagged, HazardPointer, ProtectError, Thread, DEFAULT_DOMAIN,
 };
 
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use crate::ds_impl::hp::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::sched::yield_point;
 use std::mem;
 use std::ptr;
 use std::sync::atomic::{AtomicPtr, Ordering};
@@ -96,7 +99,7 @@ where
 }
 
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: Key<K>,
     // TODO(@jeehoonkang): how about having another type that is either (1) value, or (2) left and
     // right.
@@ -295,7 +298,8 @@ impl<K, V> hp_pp::Unlink<Node<K, V>> for Unlink<K, V> {
         );
         if unsafe { &*self.successor_addr }
             .compare_exchange(self.successor, link, Ordering::AcqRel, Ordering::Acquire)
-            .is_ok()
+            .classify(&self.successor)
+            .is_success()
         {
             // destroy the subtree of successor except target_sibling
             let mut stack = vec![self.successor];
@@ -432,6 +436,7 @@ where
 
             record.leaf = curr_base;
             record.leaf_dir = curr_dir;
+            yield_point();
 
             // update other variables
             prev_tag = Marks::from_bits_truncate(tag(curr)).tag();
@@ -499,6 +504,7 @@ where
 
             record.leaf = curr_base;
             record.leaf_dir = curr_dir;
+            yield_point();
 
             let curr_node = unsafe { &*curr_base };
             if curr_node.key.cmp(key) == cmp::Ordering::Greater {
@@ -520,6 +526,15 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire);
         let leaf_flag = Marks::from_bits_truncate(tag(leaf_marked)).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag
+                || Marks::from_bits_truncate(tag(record
+                    .leaf_sibling_addr()
+                    .load(Ordering::Acquire)))
+                .flag(),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -530,12 +545,24 @@ where
         // tag (parent, sibling) edge -> all of the parent's edges can't change now
         // TODO: Is Release enough?
         target_sibling_addr.fetch_or(Marks::TAG.bits(), Ordering::AcqRel);
+        yield_point();
 
         // Try to replace (ancestor, successor) w/ (ancestor, sibling).
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load(Ordering::Acquire);
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(tag(target_sibling)).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(tag(target_sibling)).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            tag(record.successor),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         let link = tagged(target_sibling, Marks::new(false, flag, false).bits());
         let unlink = Unlink {
             successor_addr: record.successor_addr() as *const _ as *mut _,
@@ -622,20 +649,20 @@ where
             new_internal_node.right.store(new_right, Ordering::Relaxed);
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match record.leaf_addr().compare_exchange(
-                leaf,
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => return Ok(()),
-                Err(current) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if untagged(current) == leaf {
-                        self.cleanup(record);
-                    }
+            yield_point();
+            match record
+                .leaf_addr()
+                .compare_exchange(leaf, new_internal, Ordering::AcqRel, Ordering::Acquire)
+                .classify(&leaf)
+            {
+                Cas::Success(_) => return Ok(()),
+                // Insertion failed. Help the conflicting remove operation if needed.
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    InsertConflict::Help.count();
+                    self.cleanup(record);
                 }
+                Cas::CurrentDiffers(_) => InsertConflict::Retry.count(),
             }
         }
     }
@@ -679,29 +706,35 @@ where
             let value = leaf_node.value.as_ref().unwrap();
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_exchange(
-                leaf,
-                tagged(leaf, Marks::new(false, true, false).bits()),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(tag(leaf), 0, "the leaf edge is expected unmarked");
+            yield_point();
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    leaf,
+                    tagged(leaf, Marks::new(false, true, false).bits()),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .classify(&leaf)
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
+                    yield_point();
                     if self.cleanup(&mut record) {
                         return Ok(Some(value));
                     }
                     // In-place cleanup failed. Enter the cleanup phase.
                     break (leaf, value);
                 }
-                Err(current) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if leaf == tagged(current, Marks::empty().bits()) {
-                        self.cleanup(&mut record);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(&mut record);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         };
 
@@ -783,4 +816,15 @@ mod tests {
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[cfg(feature = "shuttle")]
+    #[test]
+    fn shuttle_nm_tree() {
+        concurrent_map::tests::shuttle::<_, NMTreeMap<i32, String>>(|i| i.to_string(), 1000);
+    }
 }
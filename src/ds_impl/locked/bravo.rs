@@ -0,0 +1,202 @@
+//! BRAVO, a biased reader-writer lock by Dice and Kogan (USENIX ATC 2019).
+//!
+//! BRAVO wraps an underlying reader-writer lock. While the lock is biased towards readers, a
+//! reader does not touch the shared state of the underlying lock at all: it publishes itself in
+//! a slot of a global table of visible readers, chosen by hashing the thread and the lock, and
+//! reads the protected value directly. A writer acquires the underlying lock, revokes the bias,
+//! and waits until no slot of the table refers to the lock any more. As the revocation is costly,
+//! the bias is not restored until a period proportional to the time it took has passed. A reader
+//! that finds the bias revoked or its slot taken falls back to the underlying lock.
+
+use std::cell::UnsafeCell;
+use std::hint::spin_loop;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Instant;
+
+/// The number of slots of the visible readers table, which is shared by all BRAVO locks.
+const TABLE_SIZE: usize = 4096;
+
+/// A revocation that took `t` inhibits the bias for `t * INHIBIT_MULTIPLIER` after it.
+const INHIBIT_MULTIPLIER: u64 = 9;
+
+/// Each slot holds the address of the lock that a fast-path reader is reading, or 0.
+static VISIBLE_READERS: [AtomicUsize; TABLE_SIZE] = [const { AtomicUsize::new(0) }; TABLE_SIZE];
+
+thread_local! {
+    /// Its address identifies the current thread.
+    static THREAD_TAG: u8 = const { 0 };
+}
+
+/// Returns the nanoseconds since the first call.
+fn now() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+fn slot_of(lock: usize) -> &'static AtomicUsize {
+    let thread = THREAD_TAG.with(|tag| tag as *const u8 as usize);
+    let hash = ((thread ^ lock.rotate_left(17)) as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    &VISIBLE_READERS[(hash >> 32) as usize % TABLE_SIZE]
+}
+
+pub struct BravoRwLock<T> {
+    /// Whether readers may take the fast path.
+    rbias: AtomicBool,
+    /// The time (see [`now`]) until which the bias is not restored.
+    inhibit_until: AtomicU64,
+    underlying: RwLock<()>,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Send for BravoRwLock<T> {}
+unsafe impl<T: Send + Sync> Sync for BravoRwLock<T> {}
+
+impl<T> BravoRwLock<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            rbias: AtomicBool::new(true),
+            inhibit_until: AtomicU64::new(0),
+            underlying: RwLock::new(()),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    fn addr(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    pub fn read(&self) -> BravoReadGuard<'_, T> {
+        if self.rbias.load(Ordering::Acquire) {
+            let slot = slot_of(self.addr());
+            if slot
+                .compare_exchange(0, self.addr(), Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                // Pairs with the revocation of a writer: either it sees the slot, or this sees
+                // the revoked bias.
+                if self.rbias.load(Ordering::SeqCst) {
+                    return BravoReadGuard {
+                        lock: self,
+                        inner: ReadInner::Fast(slot),
+                    };
+                }
+                slot.store(0, Ordering::Release);
+            }
+        }
+
+        let guard = self.underlying.read().unwrap();
+        if !self.rbias.load(Ordering::Relaxed)
+            && now() >= self.inhibit_until.load(Ordering::Relaxed)
+        {
+            // No writer holds the underlying lock, so it cannot be revoking the bias.
+            self.rbias.store(true, Ordering::Release);
+        }
+        BravoReadGuard {
+            lock: self,
+            inner: ReadInner::Slow(guard),
+        }
+    }
+
+    pub fn write(&self) -> BravoWriteGuard<'_, T> {
+        let guard = self.underlying.write().unwrap();
+        if self.rbias.load(Ordering::Relaxed) {
+            self.rbias.store(false, Ordering::SeqCst);
+            let start = now();
+            for slot in VISIBLE_READERS.iter() {
+                while slot.load(Ordering::SeqCst) == self.addr() {
+                    spin_loop();
+                }
+            }
+            let end = now();
+            self.inhibit_until
+                .store(end + (end - start) * INHIBIT_MULTIPLIER, Ordering::Relaxed);
+        }
+        BravoWriteGuard {
+            lock: self,
+            _guard: guard,
+        }
+    }
+}
+
+enum ReadInner<'a> {
+    /// Published in a slot of the visible readers table.
+    Fast(&'static AtomicUsize),
+    /// Holding the underlying lock.
+    Slow(RwLockReadGuard<'a, ()>),
+}
+
+pub struct BravoReadGuard<'a, T> {
+    lock: &'a BravoRwLock<T>,
+    inner: ReadInner<'a>,
+}
+
+impl<'a, T> Deref for BravoReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<'a, T> Drop for BravoReadGuard<'a, T> {
+    fn drop(&mut self) {
+        if let ReadInner::Fast(slot) = self.inner {
+            slot.store(0, Ordering::Release);
+        }
+    }
+}
+
+pub struct BravoWriteGuard<'a, T> {
+    lock: &'a BravoRwLock<T>,
+    _guard: RwLockWriteGuard<'a, ()>,
+}
+
+impl<'a, T> Deref for BravoWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<'a, T> DerefMut for BravoWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BravoRwLock;
+    use crossbeam_utils::thread;
+
+    const THREADS: usize = 16;
+    const OPS_PER_THREADS: usize = 1 << 16;
+
+    #[test]
+    fn readers_see_whole_writes() {
+        let lock = &BravoRwLock::new((0usize, 0usize));
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    for i in 0..OPS_PER_THREADS {
+                        // Mostly reads, so that the bias is repeatedly restored and revoked.
+                        if (i + t) % 64 == 0 {
+                            let mut pair = lock.write();
+                            pair.0 += 1;
+                            pair.1 += 1;
+                        } else {
+                            let pair = lock.read();
+                            assert_eq!(pair.0, pair.1);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+        let pair = lock.read();
+        assert_eq!(pair.0, THREADS * OPS_PER_THREADS / 64);
+    }
+}
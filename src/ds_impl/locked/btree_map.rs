@@ -0,0 +1,56 @@
+use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::BravoRwLock;
+use std::collections::BTreeMap;
+
+/// A sequential `BTreeMap` protected by a BRAVO reader-writer lock.
+pub struct BravoBTreeMap<K, V> {
+    inner: BravoRwLock<BTreeMap<K, V>>,
+}
+
+impl<K, V> ConcurrentMap<K, V> for BravoBTreeMap<K, V>
+where
+    K: Ord,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self {
+            inner: BravoRwLock::new(BTreeMap::new()),
+        }
+    }
+
+    #[inline(always)]
+    fn get(&self, key: &K) -> Option<impl OutputHolder<V>> {
+        self.inner.read().get(key).cloned()
+    }
+
+    #[inline(always)]
+    fn insert(&self, key: K, value: V) -> bool {
+        let mut map = self.inner.write();
+        if map.contains_key(&key) {
+            return false;
+        }
+        map.insert(key, value);
+        true
+    }
+
+    #[inline(always)]
+    fn remove(&self, key: &K) -> Option<impl OutputHolder<V>> {
+        self.inner.write().remove(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BravoBTreeMap;
+    use crate::ds_impl::locked::concurrent_map;
+
+    #[test]
+    fn smoke_bravo_btree_map() {
+        concurrent_map::tests::smoke::<_, BravoBTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn edge_cases_bravo_btree_map() {
+        concurrent_map::tests::edge_cases::<_, BravoBTreeMap<i32, String>, _>(&i32::to_string);
+    }
+}
@@ -0,0 +1,150 @@
+pub trait OutputHolder<V> {
+    fn output(&self) -> &V;
+}
+
+impl<'g, V> OutputHolder<V> for &'g V {
+    fn output(&self) -> &V {
+        self
+    }
+}
+
+impl<V> OutputHolder<V> for V {
+    fn output(&self) -> &V {
+        self
+    }
+}
+
+pub trait ConcurrentMap<K, V> {
+    fn new() -> Self;
+    fn get(&self, key: &K) -> Option<impl OutputHolder<V>>;
+    fn insert(&self, key: K, value: V) -> bool;
+    fn remove(&self, key: &K) -> Option<impl OutputHolder<V>>;
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(&self, key: K, value: V) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if let Some(found) = self.get(&key) {
+                return Some(found.output().clone());
+            }
+            if self.insert(key.clone(), value.clone()) {
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate rand;
+    use super::{ConcurrentMap, OutputHolder};
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
+    use std::fmt::Debug;
+
+    const THREADS: i32 = 30;
+    const ELEMENTS_PER_THREADS: i32 = 1000;
+
+    pub fn smoke<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert!(map.insert(i, to_value(&i)));
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        thread::scope(|s| {
+            for t in 0..(THREADS / 2) {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert_eq!(to_value(&i), *map.remove(&i).unwrap().output());
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        thread::scope(|s| {
+            for t in (THREADS / 2)..THREADS {
+                s.spawn(move |_| {
+                    let mut rng = rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(&mut rng);
+                    for i in keys {
+                        assert_eq!(to_value(&i), *map.get(&i).unwrap().output());
+                    }
+                });
+            }
+        })
+        .unwrap();
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+
+        // An empty map.
+        assert!(map.get(&0).is_none());
+        assert!(map.remove(&0).is_none());
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(0, to_value(&0)));
+        assert!(!map.insert(0, to_value(&1)));
+        assert_eq!(to_value(&0), *map.get(&0).unwrap().output());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert_eq!(to_value(&0), *map.remove(&0).unwrap().output());
+        assert!(map.get(&0).is_none());
+        assert!(map.remove(&0).is_none());
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(i, to_value(&i)));
+            }
+            assert_eq!(to_value(&removed), *map.remove(&removed).unwrap().output());
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(&i).is_some());
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert_eq!(to_value(&i), *map.remove(&i).unwrap().output());
+            }
+        }
+        for i in 0..=3 {
+            assert!(map.get(&i).is_none());
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Lock-based baselines, which protect a sequential structure with a lock instead of reclaiming
+//! memory concurrently.
+
+pub mod concurrent_map;
+
+pub mod bravo;
+pub mod btree_map;
+
+pub use self::concurrent_map::ConcurrentMap;
+
+pub use self::bravo::BravoRwLock;
+pub use self::btree_map::BravoBTreeMap;
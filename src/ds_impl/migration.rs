@@ -0,0 +1,159 @@
+//! The bookkeeping of the help-based migration of a hash table into a table with twice the
+//! buckets, which is the same for every scheme.
+//!
+//! A migration has two phases. In the first one, the buckets of the old table are claimed in chunks
+//! by the migrating thread and by every update that runs into the migration, and each claimed
+//! bucket is frozen, waits for the updates in it to finish, and is copied into the two buckets of
+//! the new table that its keys split into. In the second one, the thread that migrates the last
+//! chunk installs the new table and retires the old one, with all of its nodes, at once.
+//!
+//! An update of a bucket that is being copied waits until it is migrated, and an update or a `get`
+//! of a migrated bucket goes on to the new table. A `get` never waits, as the keys of a frozen
+//! bucket do not change until they are all in the new table.
+
+use crossbeam_utils::Backoff;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// The number of buckets that a helper claims at once.
+pub const CHUNK: usize = 16;
+
+/// The state of a bucket of a table that may be migrated. The segments of `ext-hash-map` are
+/// frozen with it too, when they are split or merged.
+#[derive(Default)]
+pub struct Gate {
+    /// The number of updates in the bucket.
+    writers: AtomicUsize,
+    frozen: AtomicBool,
+    /// Whether the keys of the bucket are in the new table.
+    migrated: AtomicBool,
+}
+
+impl Gate {
+    /// Enters the bucket for an update, or returns `false` if it is frozen.
+    #[inline]
+    pub fn enter(&self) -> bool {
+        self.writers.fetch_add(1, Ordering::SeqCst);
+        if self.frozen.load(Ordering::SeqCst) {
+            self.writers.fetch_sub(1, Ordering::Release);
+            return false;
+        }
+        true
+    }
+
+    #[inline]
+    pub fn exit(&self) {
+        self.writers.fetch_sub(1, Ordering::Release);
+    }
+
+    /// Freezes the bucket for good, and waits for the updates in it to finish.
+    pub fn freeze(&self) {
+        self.frozen.store(true, Ordering::SeqCst);
+        let backoff = Backoff::new();
+        while self.writers.load(Ordering::SeqCst) != 0 {
+            backoff.snooze();
+        }
+    }
+
+    /// Marks the frozen bucket as copied into the new table.
+    #[inline]
+    pub fn finish(&self) {
+        self.migrated.store(true, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn is_migrated(&self) -> bool {
+        self.migrated.load(Ordering::Acquire)
+    }
+}
+
+/// The progress of the migration of a table.
+#[derive(Default)]
+pub struct Progress {
+    claimed: AtomicUsize,
+    migrated: AtomicUsize,
+    copied: AtomicUsize,
+}
+
+impl Progress {
+    /// Claims the next chunk of the `len` buckets of the table, if any is left.
+    #[inline]
+    pub fn claim(&self, len: usize) -> Option<Range<usize>> {
+        let start = self.claimed.fetch_add(CHUNK, Ordering::Relaxed);
+        (start < len).then(|| start..len.min(start + CHUNK))
+    }
+
+    /// Records that `chunk` was migrated with `copied` keys, and returns whether it was the last
+    /// chunk of the `len` buckets of the table.
+    #[inline]
+    pub fn finish(&self, chunk: Range<usize>, copied: usize, len: usize) -> bool {
+        self.copied.fetch_add(copied, Ordering::Relaxed);
+        let buckets = chunk.len();
+        self.migrated.fetch_add(buckets, Ordering::AcqRel) + buckets == len
+    }
+
+    /// The number of keys copied so far, which is final once the last chunk is finished.
+    #[inline]
+    pub fn copied(&self) -> usize {
+        self.copied.load(Ordering::Relaxed)
+    }
+}
+
+/// A migration, as seen by the thread that started it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Migration {
+    /// The number of buckets of the old table.
+    pub buckets: usize,
+    /// The number of keys copied into the new table, which were retired with the old table.
+    pub copied: usize,
+    /// The number of buckets migrated by the updates that helped.
+    pub helped: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gate, Progress, CHUNK};
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn frozen_gate() {
+        let gate = Gate::default();
+        assert!(gate.enter());
+        gate.exit();
+        gate.freeze();
+        assert!(!gate.enter());
+        assert!(!gate.is_migrated());
+        gate.finish();
+        assert!(gate.is_migrated());
+    }
+
+    #[test]
+    fn claims_every_bucket_once() {
+        const THREADS: usize = 8;
+        const LEN: usize = 100 * CHUNK + 3;
+
+        let progress = Progress::default();
+        let claimed = (0..LEN).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+        let lasts = AtomicUsize::new(0);
+        scope(|s| {
+            for _ in 0..THREADS {
+                let (progress, claimed, lasts) = (&progress, &claimed, &lasts);
+                s.spawn(move |_| {
+                    while let Some(chunk) = progress.claim(LEN) {
+                        for bucket in chunk.clone() {
+                            claimed[bucket].fetch_add(1, Ordering::Relaxed);
+                        }
+                        if progress.finish(chunk.clone(), chunk.len(), LEN) {
+                            lasts.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+        assert!(claimed.iter().all(|c| c.load(Ordering::Relaxed) == 1));
+        assert_eq!(lasts.load(Ordering::Relaxed), 1);
+        assert_eq!(progress.copied(), LEN);
+    }
+}
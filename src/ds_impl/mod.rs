@@ -1,11 +1,21 @@
+pub mod cas;
 pub mod cdrc;
 pub mod circ_ebr;
 pub mod circ_hp;
+pub mod counters;
+pub mod deque;
 pub mod ebr;
+pub mod elimination;
+pub mod helping;
 pub mod hp;
 pub mod hp_brcu;
 pub mod hp_pp;
+pub mod locked;
+pub mod migration;
 pub mod nbr;
 pub mod nr;
 pub mod pebr;
+pub mod persist;
+pub mod retry;
 pub mod vbr;
+pub mod wait_free;
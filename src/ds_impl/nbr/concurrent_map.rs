@@ -13,6 +13,24 @@ pub trait ConcurrentMap<K, V> {
         handle: &mut Self::Handle,
         guard: &'g Guard,
     ) -> Option<&'g V>;
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(&self, key: K, value: V, handle: &mut Self::Handle, guard: &Guard) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if let Some(found) = self.get(&key, handle, guard) {
+                return Some(found.clone());
+            }
+            if self.insert(key.clone(), value.clone(), handle, guard) {
+                return None;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -81,4 +99,49 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<M: ConcurrentMap<i32, String>>() {
+        let collector = Collector::new(1, 256, 32, 16);
+        let guard = &mut collector.register();
+        let handle = &mut M::handle(guard);
+        let map = &M::new();
+
+        // An empty map.
+        assert!(map.get(&0, handle, guard).is_none());
+        assert!(map.remove(&0, handle, guard).is_none());
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(0, 0.to_string(), handle, guard));
+        assert!(!map.insert(0, 1.to_string(), handle, guard));
+        assert_eq!(0.to_string(), *map.get(&0, handle, guard).unwrap());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert_eq!(0.to_string(), *map.remove(&0, handle, guard).unwrap());
+        assert!(map.get(&0, handle, guard).is_none());
+        assert!(map.remove(&0, handle, guard).is_none());
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(i, i.to_string(), handle, guard));
+            }
+            assert_eq!(
+                removed.to_string(),
+                *map.remove(&removed, handle, guard).unwrap()
+            );
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(&i, handle, guard).is_some());
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert_eq!(i.to_string(), *map.remove(&i, handle, guard).unwrap());
+            }
+        }
+        for i in 0..=3 {
+            assert!(map.get(&i, handle, guard).is_none());
+        }
+    }
 }
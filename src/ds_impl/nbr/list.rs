@@ -1,4 +1,6 @@
 use super::concurrent_map::ConcurrentMap;
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::wait_free;
 use nbr::{read_phase, Guard, Shield};
 
 use hp_pp::{tag, untagged};
@@ -10,7 +12,7 @@ use std::sync::atomic::{AtomicPtr, Ordering};
 // is also the first data in the memory alignment.
 #[repr(C)]
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     next: AtomicPtr<Node<K, V>>,
     key: K,
     value: V,
@@ -159,6 +161,7 @@ where
 
         read_phase!(guard => {
             cursor = {
+                wait_free::traversal();
                 // Declaring inner cursor is important to let the compiler to conduct register
                 // optimization.
                 let mut cursor = Cursor {
@@ -169,6 +172,7 @@ where
 
                 cursor.found = loop {
                     let curr_node = some_or!(unsafe { untagged(cursor.curr).as_ref() }, break false);
+                    wait_free::visit();
                     let next = curr_node.next.load(Ordering::Acquire);
 
                     match curr_node.key.cmp(key) {
@@ -224,14 +228,20 @@ where
             new_node.next.store(cursor.curr, Ordering::Relaxed);
             let new_node_ptr = Box::into_raw(new_node);
 
-            match unsafe { &*cursor.prev }.next.compare_exchange(
-                cursor.curr,
-                new_node_ptr,
-                Ordering::Release,
-                Ordering::Relaxed,
-            ) {
-                Ok(_) => return true,
-                Err(_) => new_node = unsafe { Box::from_raw(new_node_ptr) },
+            match unsafe { &*cursor.prev }
+                .next
+                .compare_exchange(
+                    cursor.curr,
+                    new_node_ptr,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+                .classify(&cursor.curr)
+            {
+                Cas::Success(_) => return true,
+                Cas::CurrentDiffers(_) | Cas::TagDiffers(_) => {
+                    new_node = unsafe { Box::from_raw(new_node_ptr) }
+                }
             }
         }
     }
@@ -468,11 +478,37 @@ mod tests {
         concurrent_map::tests::smoke::<HList<i32, String>>();
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<HList<i32, String>>();
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<HHSList<i32, String>>();
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<HHSList<i32, String>>();
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String>::new();
+        let collector = nbr::Collector::new(1, 256, 32, 2);
+        let guard = &mut collector.register();
+        let handle = &mut HHSList::<i32, String>::handle(guard);
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(key, key.to_string(), handle, guard),
+            Op::Remove => map.remove(&key, handle, guard).is_some(),
+            Op::Get => map.get(&key, handle, guard).is_some(),
+        });
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use concurrent_map::ConcurrentMap;
@@ -481,6 +517,7 @@ mod tests {
         let collector = nbr::Collector::new(1, 256, 32, 2);
         let guard = &mut collector.register();
         let mut handle = HHSList::<i32, &str>::handle(guard);
+        assert!(map.pop(&mut handle, guard).is_none());
         map.insert(1, "1", &mut handle, guard);
         map.insert(2, "2", &mut handle, guard);
         map.insert(3, "3", &mut handle, guard);
@@ -94,4 +94,9 @@ mod tests {
     fn smoke_hashmap() {
         concurrent_map::tests::smoke::<HashMap<i32, String>>();
     }
+
+    #[test]
+    fn edge_cases_hashmap() {
+        concurrent_map::tests::edge_cases::<HashMap<i32, String>>();
+    }
 }
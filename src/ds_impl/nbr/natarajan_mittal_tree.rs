@@ -1,4 +1,6 @@
 use super::concurrent_map::ConcurrentMap;
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use hp_pp::tagged;
 use hp_pp::{tag, untagged};
 use nbr::{read_phase, Guard, Shield};
@@ -88,7 +90,7 @@ where
 }
 
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: Key<K>,
     // TODO(@jeehoonkang): how about having another type that is either (1) value, or (2) left and
     // right.
@@ -340,6 +342,15 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire);
         let leaf_flag = Marks::from_bits_truncate(tag(leaf_marked)).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag
+                || Marks::from_bits_truncate(tag(record
+                    .leaf_sibling_addr()
+                    .load(Ordering::Acquire)))
+                .flag(),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -355,7 +366,18 @@ where
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load(Ordering::Acquire);
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(tag(target_sibling)).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(tag(target_sibling)).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            tag(record.successor),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         let is_unlinked = record
             .successor_addr()
             .compare_exchange(
@@ -364,7 +386,8 @@ where
                 Ordering::AcqRel,
                 Ordering::Acquire,
             )
-            .is_ok();
+            .classify(&record.successor)
+            .is_success();
 
         if is_unlinked {
             unsafe {
@@ -441,20 +464,24 @@ where
             new_internal_ref.right.store(new_right, Ordering::Relaxed);
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match record.leaf_addr().compare_exchange(
-                record.leaf,
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => return Ok(()),
-                Err(e) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if untagged(e) == record.leaf {
-                        self.cleanup(&record, guard);
-                    }
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf,
+                    new_internal,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .classify(&record.leaf)
+            {
+                Cas::Success(_) => return Ok(()),
+                // Insertion failed. Help the conflicting remove operation if needed.
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    InsertConflict::Help.count();
+                    self.cleanup(&record, guard);
                 }
+                Cas::CurrentDiffers(_) => InsertConflict::Retry.count(),
             }
         }
     }
@@ -478,13 +505,18 @@ where
             let value = leaf_node.value.as_ref().unwrap();
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_exchange(
-                record.leaf,
-                tagged(record.leaf, Marks::new(true, false).bits()),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(tag(record.leaf), 0, "the leaf edge is expected unmarked");
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf,
+                    tagged(record.leaf, Marks::new(true, false).bits()),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .classify(&record.leaf)
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(&record, guard) {
                         return Some(value);
@@ -492,15 +524,14 @@ where
                     // In-place cleanup failed. Enter the cleanup phase.
                     break (leaf, value);
                 }
-                Err(e) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if record.leaf == untagged(e) {
-                        self.cleanup(&record, guard);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(&record, guard);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         };
 
@@ -563,4 +594,9 @@ mod tests {
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<NMTreeMap<i32, String>>();
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<NMTreeMap<i32, String>>();
+    }
 }
@@ -1,4 +1,4 @@
-use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::concurrent_map::impl_concurrent_map;
 use super::pointers::{Atomic, Shared};
 
 use std::cmp;
@@ -31,7 +31,7 @@ impl Retired {
 
 /// a real node in tree or a wrapper of State node
 /// Retired node if Shared ptr of Node has RETIRED tag.
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: K,
     value: V,
     size: usize,
@@ -502,6 +502,26 @@ pub struct BonsaiTreeMap<K, V> {
     root: Atomic<Node<K, V>>,
 }
 
+impl<K, V> Drop for BonsaiTreeMap<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut stack = vec![self.root.load(Ordering::Relaxed)];
+
+            while let Some(node) = stack.pop() {
+                if node.is_null() {
+                    continue;
+                }
+
+                let node_ref = node.deref();
+
+                stack.push(node_ref.left.load(Ordering::Relaxed));
+                stack.push(node_ref.right.load(Ordering::Relaxed));
+                drop(node.into_owned());
+            }
+        }
+    }
+}
+
 impl<K, V> Default for BonsaiTreeMap<K, V>
 where
     K: Ord + Clone + 'static,
@@ -523,7 +543,7 @@ where
         }
     }
 
-    pub fn get(&self, key: &K) -> Option<&'static V> {
+    pub fn get(&self, key: &K) -> Option<&V> {
         loop {
             let mut node = self.root.load(Ordering::Acquire);
             while !node.is_null() && !Node::is_retired(node) {
@@ -573,7 +593,7 @@ where
         }
     }
 
-    pub fn remove(&self, key: &K) -> Option<&'static V> {
+    pub fn remove(&self, key: &K) -> Option<&V> {
         let mut state = State::new(&self.root);
         loop {
             state.load_root();
@@ -600,36 +620,14 @@ where
 }
 
 // TODO: move it to somewhere else...
-impl<K, V> ConcurrentMap<K, V> for BonsaiTreeMap<K, V>
-where
-    K: Ord + Clone + 'static,
-    V: Clone + 'static,
-{
-    fn new() -> Self {
-        Self::new()
-    }
-
-    #[inline(always)]
-    fn get(&self, key: &K) -> Option<impl OutputHolder<V>> {
-        self.get(key)
-    }
-    #[inline(always)]
-    fn insert(&self, key: K, value: V) -> bool {
-        self.insert(key, value)
-    }
-    #[inline(always)]
-    fn remove(&self, key: &K) -> Option<impl OutputHolder<V>> {
-        self.remove(key)
-    }
+impl_concurrent_map! {
+    impl [K: Ord + Clone + 'static, V: Clone + 'static] for BonsaiTreeMap;
+    #[test] smoke_bonsai_tree;
 }
 
-#[cfg(test)]
-mod tests {
-    use super::BonsaiTreeMap;
-    use crate::ds_impl::nr::concurrent_map;
-
-    #[test]
-    fn smoke_bonsai_tree() {
-        concurrent_map::tests::smoke::<_, BonsaiTreeMap<i32, String>, _>(&i32::to_string);
-    }
+#[test]
+fn pull_stress_bonsai_tree() {
+    crate::ds_impl::nr::concurrent_map::tests::pull_stress::<_, BonsaiTreeMap<i32, String>, _>(
+        &i32::to_string,
+    );
 }
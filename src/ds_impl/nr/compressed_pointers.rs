@@ -0,0 +1,357 @@
+//! The pointers of NR with the `compressed-pointers` feature.
+//!
+//! A pointer is a 32-bit offset into an arena of 4 GiB that is reserved once, instead of a 64-bit
+//! address. Every allocation in the arena is aligned to at least 8 bytes, so the low 3 bits of an
+//! offset are free for a tag, as those of an address are. The offset 0 is the null pointer.
+//!
+//! The tag is composed and decomposed by integer arithmetic on the offset, never by reinterpreting
+//! its bytes, so the handling of the tags does not depend on the endianness of the target.
+//!
+//! Like the rest of NR, the arena never frees its memory, and `into_owned` only moves the value out
+//! of it. The arena is not allocated by the global allocator, so the memory usage that the
+//! benchmark samples does not include it.
+//!
+//! Only NR has compressed pointers. The `ebr` structures use the pointers of `crossbeam-ebr`, and
+//! compressing them would take a compressed `Atomic`, `Shared`, and `Owned` that `crossbeam-ebr`
+//! retires and destroys, with an arena that reuses the freed nodes. Without the reuse, the memory
+//! of `ebr` would grow as that of NR does, and the overhead of its reclamation would not compare.
+
+use crate::ds_impl::cas::{CasError, Link};
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// The size of the arena, which the 32-bit offsets can address.
+const ARENA_SIZE: usize = 1 << 32;
+/// The size of the chunks that a thread takes from the arena to allocate from.
+const CHUNK_SIZE: usize = 1 << 20;
+/// The minimum alignment of the allocations, which leaves the low bits of an offset for a tag.
+const MIN_ALIGN: usize = 8;
+const TAG_MASK: u32 = MIN_ALIGN as u32 - 1;
+
+struct Arena {
+    base: usize,
+    /// The offset of the next chunk to take.
+    next: AtomicUsize,
+}
+
+fn arena() -> &'static Arena {
+    static ARENA: OnceLock<Arena> = OnceLock::new();
+    ARENA.get_or_init(|| {
+        // Only the touched pages are backed by memory.
+        let base = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                ARENA_SIZE,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE,
+                -1,
+                0,
+            )
+        };
+        assert!(
+            base != libc::MAP_FAILED,
+            "Failed to reserve the arena of the compressed pointers"
+        );
+        crate::huge_pages::advise_region(base, ARENA_SIZE);
+        Arena {
+            base: base as usize,
+            // The first chunk is never taken, so that no allocation is at the offset 0.
+            next: AtomicUsize::new(CHUNK_SIZE),
+        }
+    })
+}
+
+thread_local! {
+    /// The offsets of the next allocation and of the end of the current chunk of this thread.
+    static LOCAL_CHUNK: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+/// Allocates room for a `T` in the arena and returns its offset.
+fn alloc<T>() -> u32 {
+    let align = mem::align_of::<T>().max(MIN_ALIGN);
+    let size = mem::size_of::<T>().max(1);
+    assert!(
+        size + align <= CHUNK_SIZE,
+        "A compressed pointer cannot point to a value of {size} bytes"
+    );
+    LOCAL_CHUNK.with(|local| {
+        let (mut next, mut end) = local.get();
+        next = next.next_multiple_of(align);
+        if next + size > end {
+            let chunk = arena().next.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+            assert!(
+                chunk + CHUNK_SIZE <= ARENA_SIZE,
+                "The arena of the compressed pointers is exhausted"
+            );
+            next = chunk.next_multiple_of(align);
+            end = chunk + CHUNK_SIZE;
+        }
+        local.set((next + size, end));
+        next as u32
+    })
+}
+
+/// Returns a bitmask containing the unused least significant bits of an offset of a `T`.
+#[inline]
+fn low_bits<T: Sized>() -> u32 {
+    ((1 << mem::align_of::<T>().trailing_zeros()) - 1) as u32 | TAG_MASK
+}
+
+/// Given a tagged offset `word`, returns the same offset, but tagged with `tag`.
+///
+/// `tag` is truncated to fit into the unused bits of the offset of a `T`.
+#[inline]
+pub(crate) fn compose_tag<T: Sized>(word: u32, tag: usize) -> u32 {
+    (word & !low_bits::<T>()) | (tag as u32 & low_bits::<T>())
+}
+
+/// Decomposes a tagged offset `word` into the offset and the tag.
+#[inline]
+pub(crate) fn decompose_tag<T: Sized>(word: u32) -> (u32, usize) {
+    (word & !low_bits::<T>(), (word & low_bits::<T>()) as usize)
+}
+
+#[inline]
+fn decompress<T>(offset: u32) -> *mut T {
+    if offset == 0 {
+        return std::ptr::null_mut();
+    }
+    (arena().base + offset as usize) as *mut T
+}
+
+#[inline]
+fn compress<T>(ptr: *const T) -> u32 {
+    if ptr.is_null() {
+        return 0;
+    }
+    let offset = (ptr as usize).wrapping_sub(arena().base);
+    assert!(
+        offset < ARENA_SIZE,
+        "A compressed pointer can only point into the arena"
+    );
+    offset as u32
+}
+
+pub struct CompareExchangeError<T, P: Pointer<T>> {
+    pub new: P,
+    pub current: Shared<T>,
+}
+
+pub struct Atomic<T> {
+    link: AtomicU32,
+    _marker: PhantomData<*mut T>,
+}
+
+unsafe impl<T> Sync for Atomic<T> {}
+unsafe impl<T> Send for Atomic<T> {}
+
+impl<T> Atomic<T> {
+    pub fn new(init: T) -> Self {
+        Self::from(Shared::from_owned(init))
+    }
+
+    pub fn null() -> Self {
+        Self::from(Shared::null())
+    }
+
+    pub fn load(&self, order: Ordering) -> Shared<T> {
+        Shared::from_bits(self.link.load(order))
+    }
+
+    pub fn store(&self, ptr: Shared<T>, order: Ordering) {
+        self.link.store(ptr.into_word(), order)
+    }
+
+    pub fn fetch_or(&self, val: usize, order: Ordering) -> Shared<T> {
+        Shared::from_bits(self.link.fetch_or(val as u32 & low_bits::<T>(), order))
+    }
+
+    pub fn compare_exchange<P: Pointer<T>>(
+        &self,
+        current: Shared<T>,
+        new: P,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Shared<T>, CompareExchangeError<T, P>> {
+        let current = current.into_word();
+        let new = new.into_word();
+
+        match self.link.compare_exchange(current, new, success, failure) {
+            Ok(current) => Ok(Shared::from_bits(current)),
+            Err(current) => {
+                let new = unsafe { P::from_word(new) };
+                Err(CompareExchangeError {
+                    new,
+                    current: Shared::from_bits(current),
+                })
+            }
+        }
+    }
+
+    pub unsafe fn into_owned(self) -> T {
+        Shared::<T>::from_bits(self.link.into_inner()).into_owned()
+    }
+}
+
+impl<T> Default for Atomic<T> {
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T> From<Shared<T>> for Atomic<T> {
+    fn from(value: Shared<T>) -> Self {
+        Self {
+            link: AtomicU32::new(value.into_word()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Atomic<T> {
+    fn clone(&self) -> Self {
+        Self::from(self.load(Ordering::Relaxed))
+    }
+}
+
+pub struct Shared<T> {
+    word: u32,
+    _marker: PhantomData<*mut T>,
+}
+
+impl<T> Shared<T> {
+    fn from_bits(word: u32) -> Self {
+        Self {
+            word,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn from_owned(init: T) -> Shared<T> {
+        let offset = alloc::<T>();
+        unsafe { decompress::<T>(offset).write(init) };
+        Self::from_bits(offset)
+    }
+
+    pub unsafe fn into_owned(self) -> T {
+        decompress::<T>(decompose_tag::<T>(self.word).0).read()
+    }
+
+    pub fn null() -> Self {
+        Self::from_bits(0)
+    }
+
+    pub fn tag(&self) -> usize {
+        decompose_tag::<T>(self.word).1
+    }
+
+    pub fn is_null(&self) -> bool {
+        decompose_tag::<T>(self.word).0 == 0
+    }
+
+    pub fn with_tag(&self, tag: usize) -> Self {
+        Self::from_bits(compose_tag::<T>(self.word, tag))
+    }
+
+    pub unsafe fn as_ref<'g>(&self) -> Option<&'g T> {
+        decompress::<T>(decompose_tag::<T>(self.word).0).as_ref()
+    }
+
+    pub unsafe fn as_mut<'g>(&self) -> Option<&'g mut T> {
+        decompress::<T>(decompose_tag::<T>(self.word).0).as_mut()
+    }
+
+    pub unsafe fn deref<'g>(&self) -> &'g T {
+        &*decompress::<T>(decompose_tag::<T>(self.word).0)
+    }
+
+    pub unsafe fn deref_mut<'g>(&mut self) -> &'g mut T {
+        &mut *decompress::<T>(decompose_tag::<T>(self.word).0)
+    }
+}
+
+/// Converts a tagged address in the arena.
+impl<T> From<usize> for Shared<T> {
+    fn from(val: usize) -> Self {
+        let mask = low_bits::<T>() as usize;
+        let offset = compress((val & !mask) as *const T);
+        Self::from_bits(compose_tag::<T>(offset, val & mask))
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Shared<T> {}
+
+impl<T> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.word == other.word
+    }
+}
+
+impl<T> Eq for Shared<T> {}
+
+impl<T> Link for Shared<T> {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        let (offset, tag) = decompose_tag::<T>(self.word);
+        (offset as usize, tag)
+    }
+}
+
+impl<T, P: Pointer<T>> CasError for CompareExchangeError<T, P> {
+    type Current = Shared<T>;
+
+    #[inline]
+    fn current(&self) -> &Shared<T> {
+        &self.current
+    }
+}
+
+pub trait Pointer<T> {
+    fn into_word(self) -> u32;
+    unsafe fn from_word(word: u32) -> Self;
+}
+
+impl<T> Pointer<T> for Shared<T> {
+    fn into_word(self) -> u32 {
+        self.word
+    }
+
+    unsafe fn from_word(word: u32) -> Self {
+        Shared::from_bits(word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Atomic, Shared};
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn compressed_tags() {
+        let shared = Shared::from_owned(42u64);
+        assert_eq!(shared.tag(), 0);
+        let tagged = shared.with_tag(5);
+        assert_eq!(tagged.tag(), 5);
+        assert_eq!(unsafe { *tagged.deref() }, 42);
+        assert_eq!(tagged.with_tag(0), shared);
+
+        let atomic = Atomic::from(shared);
+        atomic.fetch_or(2, Ordering::Relaxed);
+        let loaded = atomic.load(Ordering::Relaxed);
+        assert_eq!(loaded.tag(), 2);
+        let address = unsafe { loaded.deref() } as *const u64 as usize;
+        assert_eq!(Shared::<u64>::from(address | 2), loaded);
+        assert_eq!(unsafe { atomic.into_owned() }, 42);
+
+        assert!(Shared::<u64>::null().with_tag(1).is_null());
+    }
+}
@@ -19,7 +19,124 @@ pub trait ConcurrentMap<K, V> {
     fn get(&self, key: &K) -> Option<impl OutputHolder<V>>;
     fn insert(&self, key: K, value: V) -> bool;
     fn remove(&self, key: &K) -> Option<impl OutputHolder<V>>;
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(&self, key: K, value: V) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if let Some(found) = self.get(&key) {
+                return Some(found.output().clone());
+            }
+            if self.insert(key.clone(), value.clone()) {
+                return None;
+            }
+        }
+    }
+    /// Returns the smallest key and its value, for the structures that support it.
+    fn min(&self) -> Option<(&K, &V)> {
+        panic!("Unsupported(or unimplemented) min")
+    }
+    /// Returns the largest key and its value, for the structures that support it.
+    fn max(&self) -> Option<(&K, &V)> {
+        panic!("Unsupported(or unimplemented) max")
+    }
+    /// Removes the smallest key, retrying if another thread removes it first, and returns whether
+    /// the map had a key to remove.
+    fn extract_min(&self) -> bool {
+        while let Some((key, _)) = self.min() {
+            if self.remove(key).is_some() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// The result of an inherent `insert`, which tells whether the key was inserted.
+pub trait Inserted {
+    fn inserted(self) -> bool;
+}
+
+impl Inserted for bool {
+    fn inserted(self) -> bool {
+        self
+    }
+}
+
+/// An `Err` gives back the key and value that were not inserted.
+impl<T, E> Inserted for Result<T, E> {
+    fn inserted(self) -> bool {
+        self.is_ok()
+    }
+}
+
+/// Implements [`ConcurrentMap`] for a map `$map<K, V>` by forwarding to its inherent `new`, `get`,
+/// `insert` and `remove`, which take the same arguments as the trait methods. The inherent
+/// `insert` may return a `bool` or a `Result`.
+///
+/// With `#[test] <name>;`, it also generates a test `<name>` that runs the smoke test on
+/// `$map<i32, String>`.
+///
+/// ```ignore
+/// impl_concurrent_map! {
+///     impl [K: Ord + Clone, V: Clone] for NMTreeMap;
+///     #[test] smoke_nm_tree;
+/// }
+/// ```
+macro_rules! impl_concurrent_map {
+    (impl [$($bounds:tt)*] for $map:ident; $(#[test] $test:ident;)?) => {
+        impl<$($bounds)*> $crate::ds_impl::nr::ConcurrentMap<K, V> for $map<K, V> {
+            fn new() -> Self {
+                Self::new()
+            }
+
+            #[inline(always)]
+            fn get(
+                &self,
+                key: &K,
+            ) -> Option<impl $crate::ds_impl::nr::concurrent_map::OutputHolder<V>> {
+                self.get(key)
+            }
+
+            #[inline(always)]
+            fn insert(&self, key: K, value: V) -> bool {
+                $crate::ds_impl::nr::concurrent_map::Inserted::inserted(self.insert(key, value))
+            }
+
+            #[inline(always)]
+            fn remove(
+                &self,
+                key: &K,
+            ) -> Option<impl $crate::ds_impl::nr::concurrent_map::OutputHolder<V>> {
+                self.remove(key)
+            }
+        }
+
+        $(
+            #[cfg(test)]
+            mod tests {
+                use super::$map;
+                use $crate::ds_impl::nr::concurrent_map;
+
+                #[test]
+                fn $test() {
+                    concurrent_map::tests::smoke::<_, $map<i32, String>, _>(&i32::to_string);
+                }
+
+                #[test]
+                fn edge_cases() {
+                    concurrent_map::tests::edge_cases::<_, $map<i32, String>, _>(&i32::to_string);
+                }
+            }
+        )?
+    };
 }
+pub(crate) use impl_concurrent_map;
 
 #[cfg(test)]
 pub mod tests {
@@ -27,10 +144,13 @@ pub mod tests {
     use super::{ConcurrentMap, OutputHolder};
     use crossbeam_utils::thread;
     use rand::prelude::*;
+    use std::collections::{BTreeSet, VecDeque};
     use std::fmt::Debug;
 
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
+    const PULL_KEYS: i32 = 200;
+    const PULL_ROUNDS: i32 = 5;
 
     pub fn smoke<V, M, F>(to_value: &F)
     where
@@ -85,4 +205,236 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Checks that the holders of `get` and `remove` keep their values for as long as the map
+    /// lives, as NR never frees a node, while the same thread and another one remove the keys and
+    /// insert them again with other values.
+    pub fn output_holder<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(i, to_value(&i)));
+        }
+
+        let got = map.get(&0).unwrap();
+        let removed = map.remove(&1).unwrap();
+        assert!(map.remove(&0).is_some());
+        assert!(map.insert(0, to_value(&-1)));
+        assert_eq!(to_value(&-1), *map.get(&0).unwrap().output());
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                for i in 0..ELEMENTS_PER_THREADS {
+                    map.remove(&i);
+                    assert!(map.insert(i, to_value(&(i + 1))));
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(to_value(&0), *got.output());
+        assert_eq!(to_value(&1), *removed.output());
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+
+        // An empty map.
+        assert!(map.get(&0).is_none());
+        assert!(map.remove(&0).is_none());
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(0, to_value(&0)));
+        assert!(!map.insert(0, to_value(&1)));
+        assert_eq!(to_value(&0), *map.get(&0).unwrap().output());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert_eq!(to_value(&0), *map.remove(&0).unwrap().output());
+        assert!(map.get(&0).is_none());
+        assert!(map.remove(&0).is_none());
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(i, to_value(&i)));
+            }
+            assert_eq!(to_value(&removed), *map.remove(&removed).unwrap().output());
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(&i).is_some());
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert_eq!(to_value(&i), *map.remove(&i).unwrap().output());
+            }
+        }
+        for i in 0..=3 {
+            assert!(map.get(&i).is_none());
+        }
+    }
+
+    /// Removes the keys of a tree in the order of the levels of a balanced tree, medians first, and
+    /// inserts them back in the same order, for a few rounds. Most keys then have two children when
+    /// they are removed, which pulls the rightmost node of the left subtree up into their place, and
+    /// the rest have a child on one side, which pulls the leftmost node of the right subtree. Both
+    /// rebuild the path to the pulled node, which the uniform keys of `smoke` rarely do. The tree is
+    /// prefilled in an increasing order, which skews it until it is rebalanced. Each thread owns
+    /// every `THREADS`-th key, and checks that its keys next to each removed key keep their values.
+    pub fn pull_stress<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        for i in 0..THREADS * PULL_KEYS {
+            assert!(map.insert(i, to_value(&i)));
+        }
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let keys = medians_first(PULL_KEYS);
+                    for _ in 0..PULL_ROUNDS {
+                        let mut removed = vec![false; PULL_KEYS as usize];
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert_eq!(to_value(&i), *map.remove(&i).unwrap().output());
+                            removed[k as usize] = true;
+                            for n in [k - 1, k + 1] {
+                                if (0..PULL_KEYS).contains(&n) && !removed[n as usize] {
+                                    let j = n * THREADS + t;
+                                    assert_eq!(to_value(&j), *map.get(&j).unwrap().output());
+                                }
+                            }
+                        }
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.insert(i, to_value(&i)));
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        for j in 0..THREADS * PULL_KEYS {
+            assert_eq!(to_value(&j), *map.get(&j).unwrap().output());
+        }
+    }
+
+    /// Checks `min` and `max` against a `BTreeSet` of the keys after each of random inserts and
+    /// removes, and then while the keys are removed from both ends.
+    pub fn min_max<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let mut rng = rand::thread_rng();
+        let mut keys = BTreeSet::new();
+        let check = |keys: &BTreeSet<i32>| {
+            let min = map.min().map(|(key, value)| {
+                assert_eq!(to_value(key), *value);
+                *key
+            });
+            let max = map.max().map(|(key, value)| {
+                assert_eq!(to_value(key), *value);
+                *key
+            });
+            assert_eq!(min, keys.first().copied());
+            assert_eq!(max, keys.last().copied());
+        };
+
+        check(&keys);
+        for _ in 0..ELEMENTS_PER_THREADS {
+            let key = rng.gen_range(0..PULL_KEYS);
+            if rng.gen() {
+                assert_eq!(map.insert(key, to_value(&key)), keys.insert(key));
+            } else {
+                assert_eq!(map.remove(&key).is_some(), keys.remove(&key));
+            }
+            check(&keys);
+        }
+        while let Some(key) = if keys.len() % 2 == 0 {
+            keys.pop_first()
+        } else {
+            keys.pop_last()
+        } {
+            assert!(map.remove(&key).is_some());
+            check(&keys);
+        }
+    }
+
+    /// Extracts the minimum from each thread while the others also do, until the map is empty, and
+    /// checks that every prefilled key is extracted exactly once and in an increasing order by
+    /// each thread.
+    pub fn extract_min<M>()
+    where
+        M: ConcurrentMap<i32, i32> + Send + Sync,
+    {
+        let map = &M::new();
+        let mut keys = (0..THREADS * ELEMENTS_PER_THREADS).collect::<Vec<_>>();
+        keys.shuffle(&mut rand::thread_rng());
+        for &key in &keys {
+            assert!(map.insert(key, key));
+        }
+
+        let mut extracted = thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|_| {
+                    s.spawn(move |_| {
+                        let mut extracted = vec![];
+                        loop {
+                            let Some((&key, _)) = map.min() else {
+                                break;
+                            };
+                            if map.remove(&key).is_some() {
+                                extracted.push(key);
+                            }
+                        }
+                        assert!(extracted.windows(2).all(|keys| keys[0] < keys[1]));
+                        extracted
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+
+        extracted.sort_unstable();
+        keys.sort_unstable();
+        assert_eq!(extracted, keys);
+        assert!(!map.extract_min());
+    }
+
+    /// The keys in `0..n` in the order of the levels of a balanced tree, medians first.
+    fn medians_first(n: i32) -> Vec<i32> {
+        let mut keys = Vec::with_capacity(n as usize);
+        let mut ranges = VecDeque::from([(0, n)]);
+        while let Some((lo, hi)) = ranges.pop_front() {
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                keys.push(mid);
+                ranges.push_back((lo, mid));
+                ranges.push_back((mid + 1, hi));
+            }
+        }
+        keys
+    }
 }
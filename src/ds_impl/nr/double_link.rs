@@ -3,7 +3,7 @@ use std::sync::atomic::Ordering;
 use super::pointers::{Atomic, Shared};
 use crossbeam_utils::CachePadded;
 
-struct Node<T> {
+pub(crate) struct Node<T> {
     item: Option<T>,
     prev: Atomic<Node<T>>,
     next: CachePadded<Atomic<Node<T>>>,
@@ -75,7 +75,7 @@ impl<T: Sync + Send> DoubleLink<T> {
     }
 
     #[inline]
-    pub fn dequeue(&self) -> Option<&'static T> {
+    pub fn dequeue(&self) -> Option<&T> {
         loop {
             let lhead = self.head.load(Ordering::Acquire);
             let lnext = unsafe { lhead.deref().next.load(Ordering::Acquire) };
@@ -96,6 +96,18 @@ impl<T: Sync + Send> DoubleLink<T> {
     }
 }
 
+impl<T: Sync + Send> Drop for DoubleLink<T> {
+    fn drop(&mut self) {
+        // The nodes before `head` have already been dequeued and are never reclaimed.
+        let mut node = self.head.load(Ordering::Relaxed);
+        while !node.is_null() {
+            let next = unsafe { node.deref() }.next.load(Ordering::Relaxed);
+            drop(unsafe { node.into_owned() });
+            node = next;
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::sync::atomic::{AtomicU32, Ordering};
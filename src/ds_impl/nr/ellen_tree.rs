@@ -183,6 +183,30 @@ pub struct EFRBTree<K, V> {
     root: Atomic<Node<K, V>>,
 }
 
+impl<K, V> Drop for EFRBTree<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut stack = vec![self.root.load(Ordering::Relaxed)];
+
+            while let Some(node) = stack.pop() {
+                if node.is_null() {
+                    continue;
+                }
+
+                let node_ref = node.deref();
+
+                stack.push(node_ref.left.load(Ordering::Relaxed));
+                stack.push(node_ref.right.load(Ordering::Relaxed));
+                let update = node_ref.update.load(Ordering::Relaxed);
+                if !update.is_null() {
+                    drop(update.into_owned());
+                }
+                drop(node.into_owned());
+            }
+        }
+    }
+}
+
 impl<K, V> Default for EFRBTree<K, V>
 where
     K: Ord + Clone + 'static,
@@ -209,7 +233,7 @@ where
         }
     }
 
-    pub fn find(&self, key: &K) -> Option<&'static Node<K, V>> {
+    pub fn find(&self, key: &K) -> Option<&Node<K, V>> {
         let mut cursor = Cursor::new(self.root.load(Ordering::Relaxed));
         cursor.search(key);
         let l_node = unsafe { cursor.l.as_ref().unwrap() };
@@ -220,6 +244,34 @@ where
         }
     }
 
+    /// Returns the smallest key and its value, at the end of the leftmost path.
+    pub fn min(&self) -> Option<(&K, &V)> {
+        self.search_end(false)
+    }
+
+    /// Returns the largest key and its value.
+    pub fn max(&self) -> Option<(&K, &V)> {
+        self.search_end(true)
+    }
+
+    /// Descends to the leftmost or rightmost leaf with a finite key, like `Cursor::search`. The
+    /// finite keys under an internal node with an infinite key are all in its left subtree, so
+    /// going right turns left at such a node.
+    fn search_end(&self, right: bool) -> Option<(&K, &V)> {
+        let mut l_node = unsafe { self.root.load(Ordering::Relaxed).deref() };
+        while !l_node.is_leaf() {
+            let next = match &l_node.key {
+                Key::Fin(_) if right => l_node.right.load(Ordering::Acquire),
+                _ => l_node.left.load(Ordering::Acquire),
+            };
+            l_node = unsafe { next.deref() };
+        }
+        match &l_node.key {
+            Key::Fin(key) => Some((key, l_node.value.as_ref().unwrap())),
+            _ => None,
+        }
+    }
+
     pub fn insert(&self, key: &K, value: V) -> bool {
         loop {
             let mut cursor = Cursor::new(self.root.load(Ordering::Relaxed));
@@ -284,7 +336,7 @@ where
         }
     }
 
-    pub fn delete(&self, key: &K) -> Option<&'static V> {
+    pub fn delete(&self, key: &K) -> Option<&V> {
         loop {
             let mut cursor = Cursor::new(self.root.load(Ordering::Relaxed));
             cursor.search(key);
@@ -484,6 +536,16 @@ where
     fn remove(&self, key: &K) -> Option<impl OutputHolder<V>> {
         self.delete(key)
     }
+
+    #[inline(always)]
+    fn min(&self) -> Option<(&K, &V)> {
+        self.min()
+    }
+
+    #[inline(always)]
+    fn max(&self) -> Option<(&K, &V)> {
+        self.max()
+    }
 }
 
 #[cfg(test)]
@@ -495,4 +557,19 @@ mod tests {
     fn smoke_efrb_tree() {
         concurrent_map::tests::smoke::<_, EFRBTree<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_efrb_tree() {
+        concurrent_map::tests::edge_cases::<_, EFRBTree<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn min_max_efrb_tree() {
+        concurrent_map::tests::min_max::<_, EFRBTree<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn extract_min_efrb_tree() {
+        concurrent_map::tests::extract_min::<EFRBTree<i32, i32>>();
+    }
 }
@@ -0,0 +1,44 @@
+//! An oracle that frees the nodes of the lists as soon as they are unlinked.
+//!
+//! `nr` never frees a node, so that its readers never touch freed memory. With the oracle enabled,
+//! the thread whose CAS unlinks a node frees it right away, as a scheme that knew the node to be
+//! unreachable would, while the other threads may still be reading it. Under AddressSanitizer, a
+//! reader that loaded the node before it was unlinked reports a heap-use-after-free, which shows
+//! that the workload creates the hazardous accesses that a scheme must protect against. A workload
+//! that runs under the oracle without a report does not test the schemes.
+//!
+//! The accesses are undefined behavior by design, so the oracle is only available in the debug
+//! builds. Only `h-list`, `hm-list`, `hhs-list` and `hash-map` free their nodes, and the removes
+//! return values that may already be freed, which the benchmark does not read.
+
+use super::pointers::Shared;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Makes the lists free their nodes once they are unlinked, from now on.
+pub fn enable() {
+    assert!(
+        cfg!(debug_assertions),
+        "The immediate frees are only available in the debug builds"
+    );
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether the lists free their nodes once they are unlinked.
+#[inline(always)]
+pub fn enabled() -> bool {
+    cfg!(debug_assertions) && ENABLED.load(Ordering::Relaxed)
+}
+
+/// Frees `node` if the oracle is enabled, on behalf of the thread that unlinked it.
+///
+/// # Safety
+///
+/// `node` must have been unlinked by the calling thread, and must not be freed by anyone else.
+#[inline(always)]
+pub unsafe fn unlinked<T>(node: Shared<T>) {
+    if enabled() {
+        drop(node.with_tag(0).into_owned());
+    }
+}
@@ -1,10 +1,14 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use super::immediate_free;
 use super::pointers::{Atomic, Shared};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 
 use std::cmp::Ordering::{Equal, Greater, Less};
 use std::sync::atomic::Ordering;
 
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     /// Mark: tag(), Tag: not needed
     next: Atomic<Node<K, V>>,
     key: K,
@@ -15,6 +19,20 @@ struct List<K, V> {
     head: Atomic<Node<K, V>>,
 }
 
+impl<K, V> Drop for List<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut curr = self.head.load(Ordering::Relaxed);
+
+            while !curr.is_null() {
+                let next = curr.deref().next.load(Ordering::Relaxed);
+                drop(curr.into_owned());
+                curr = next;
+            }
+        }
+    }
+}
+
 impl<K, V> Default for List<K, V>
 where
     K: Ord + 'static,
@@ -73,7 +91,7 @@ where
 
     /// Clean up a chain of logically removed nodes in each traversal.
     #[inline]
-    fn find_harris(&self, key: &K) -> Result<(bool, Cursor<K, V>), ()> {
+    fn find_harris(&self, key: &K) -> Result<(bool, Cursor<K, V>), Retry> {
         // Finding phase
         // - cursor.curr: first unmarked node w/ key >= search key (4)
         // - cursor.prev: the ref of .next in previous unmarked node (1 -> 2)
@@ -114,14 +132,23 @@ where
         // cleanup marked nodes between prev and curr
         unsafe { &*cursor.prev }
             .compare_exchange(prev_next, cursor.curr, Ordering::Release, Ordering::Relaxed)
-            .map_err(|_| ())?;
+            .map_err(|_| Retry::Unlink)?;
+
+        if immediate_free::enabled() {
+            let mut node = prev_next;
+            while node != cursor.curr {
+                let next = unsafe { node.deref() }.next.load(Ordering::Relaxed);
+                unsafe { immediate_free::unlinked(node) };
+                node = next.with_tag(0);
+            }
+        }
 
         Ok((found, cursor))
     }
 
     /// Clean up a single logically removed node in each traversal.
     #[inline]
-    fn find_harris_michael(&self, key: &K) -> Result<(bool, Cursor<K, V>), ()> {
+    fn find_harris_michael(&self, key: &K) -> Result<(bool, Cursor<K, V>), Retry> {
         let mut cursor = Cursor::head(&self.head);
         loop {
             debug_assert_eq!(cursor.curr.tag(), 0);
@@ -135,7 +162,8 @@ where
                 next = next.with_tag(0);
                 unsafe { &*cursor.prev }
                     .compare_exchange(cursor.curr, next, Ordering::Release, Ordering::Relaxed)
-                    .map_err(|_| ())?;
+                    .map_err(|_| Retry::Unlink)?;
+                unsafe { immediate_free::unlinked(cursor.curr) };
                 cursor.curr = next;
                 continue;
             }
@@ -153,10 +181,12 @@ where
 
     /// Gotta go fast. Doesn't fail.
     #[inline]
-    fn find_harris_herlihy_shavit(&self, key: &K) -> Result<(bool, Cursor<K, V>), ()> {
+    fn find_harris_herlihy_shavit(&self, key: &K) -> Result<(bool, Cursor<K, V>), Retry> {
+        wait_free::traversal();
         let mut cursor = Cursor::head(&self.head);
         Ok(loop {
             let curr_node = some_or!(unsafe { cursor.curr.as_ref() }, break (false, cursor));
+            wait_free::visit();
             let next = curr_node.next.load(Ordering::Acquire);
             match curr_node.key.cmp(key) {
                 Less => {
@@ -172,10 +202,10 @@ where
     #[inline]
     fn get<F>(&self, key: &K, find: F) -> Option<&'static V>
     where
-        F: Fn(&Self, &K) -> Result<(bool, Cursor<K, V>), ()>,
+        F: Fn(&Self, &K) -> Result<(bool, Cursor<K, V>), Retry>,
     {
         loop {
-            let (found, cursor) = ok_or!(find(self, key), continue);
+            let (found, cursor) = ok_or_retry!(find(self, key), continue);
             if found {
                 return unsafe { cursor.curr.as_ref().map(|n| &n.value) };
             }
@@ -186,27 +216,30 @@ where
     #[inline]
     fn insert<F>(&self, key: K, value: V, find: F) -> bool
     where
-        F: Fn(&Self, &K) -> Result<(bool, Cursor<K, V>), ()>,
+        F: Fn(&Self, &K) -> Result<(bool, Cursor<K, V>), Retry>,
     {
-        let mut node = Box::new(Node::new(key, value));
+        let mut node = Shared::from_owned(Node::new(key, value));
+        let node_ref = unsafe { node.deref() };
         loop {
-            let (found, mut cursor) = ok_or!(find(self, &node.key), continue);
+            let (found, mut cursor) = ok_or_retry!(find(self, &node_ref.key), continue);
             if found {
+                drop(unsafe { node.into_owned() });
                 return false;
             }
 
-            node.next.store(cursor.curr, Ordering::Relaxed);
-            match unsafe { &*cursor.prev }.compare_exchange(
-                cursor.curr,
-                node,
-                Ordering::Release,
-                Ordering::Relaxed,
-            ) {
-                Ok(node) => {
+            node_ref.next.store(cursor.curr, Ordering::Relaxed);
+            match unsafe { &*cursor.prev }
+                .compare_exchange(cursor.curr, node, Ordering::Release, Ordering::Relaxed)
+                .classify(&cursor.curr)
+            {
+                Cas::Success(node) => {
                     cursor.curr = node;
                     return true;
                 }
-                Err(e) => node = e.new,
+                Cas::CurrentDiffers(e) | Cas::TagDiffers(e) => {
+                    Retry::CasFail.count();
+                    node = e.new;
+                }
             }
         }
     }
@@ -214,10 +247,10 @@ where
     #[inline]
     fn remove<F>(&self, key: &K, find: F) -> Option<&'static V>
     where
-        F: Fn(&Self, &K) -> Result<(bool, Cursor<K, V>), ()>,
+        F: Fn(&Self, &K) -> Result<(bool, Cursor<K, V>), Retry>,
     {
         loop {
-            let (found, cursor) = ok_or!(find(self, key), continue);
+            let (found, cursor) = ok_or_retry!(find(self, key), continue);
             if !found {
                 return None;
             }
@@ -226,15 +259,16 @@ where
 
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel);
             if next.tag() == 1 {
+                Retry::CasFail.count();
                 continue;
             }
 
-            let _ = unsafe { &*cursor.prev }.compare_exchange(
-                cursor.curr,
-                next,
-                Ordering::Release,
-                Ordering::Relaxed,
-            );
+            if unsafe { &*cursor.prev }
+                .compare_exchange(cursor.curr, next, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                unsafe { immediate_free::unlinked(cursor.curr) };
+            }
 
             return Some(&curr_node.value);
         }
@@ -252,6 +286,7 @@ where
 
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel);
             if next.tag() == 1 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -267,7 +302,7 @@ where
     }
 
     #[inline]
-    pub fn harris_get(&self, key: &K) -> Option<&'static V> {
+    pub fn harris_get(&self, key: &K) -> Option<&V> {
         self.get(key, Self::find_harris)
     }
 
@@ -277,12 +312,12 @@ where
     }
 
     #[inline]
-    pub fn harris_remove(&self, key: &K) -> Option<&'static V> {
+    pub fn harris_remove(&self, key: &K) -> Option<&V> {
         self.remove(key, Self::find_harris)
     }
 
     #[inline]
-    pub fn harris_michael_get(&self, key: &K) -> Option<&'static V> {
+    pub fn harris_michael_get(&self, key: &K) -> Option<&V> {
         self.get(key, Self::find_harris_michael)
     }
 
@@ -292,12 +327,12 @@ where
     }
 
     #[inline]
-    pub fn harris_michael_remove(&self, key: &K) -> Option<&'static V> {
+    pub fn harris_michael_remove(&self, key: &K) -> Option<&V> {
         self.remove(key, Self::find_harris_michael)
     }
 
     #[inline]
-    pub fn harris_herlihy_shavit_get(&self, key: &K) -> Option<&'static V> {
+    pub fn harris_herlihy_shavit_get(&self, key: &K) -> Option<&V> {
         self.get(key, Self::find_harris_herlihy_shavit)
     }
 }
@@ -405,21 +440,55 @@ mod tests {
         concurrent_map::tests::smoke::<_, HList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<_, HList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hm_list() {
         concurrent_map::tests::smoke::<_, HMList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hm_list() {
+        concurrent_map::tests::edge_cases::<_, HMList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<_, HHSList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String>::new();
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(key, key.to_string()),
+            Op::Remove => map.remove(&key).is_some(),
+            Op::Get => map.get(&key).is_some(),
+        });
+    }
+
+    #[test]
+    fn output_holder_hhs_list() {
+        concurrent_map::tests::output_holder::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use concurrent_map::ConcurrentMap;
         let map = HHSList::new();
 
+        assert!(map.pop().is_none());
         map.insert(1, "1");
         map.insert(2, "2");
         map.insert(3, "3");
@@ -0,0 +1,216 @@
+//! An append log with prefix truncation, after the Michael-Scott queue.
+//!
+//! Each entry gets the next index when it is appended at the tail, and [`Log::truncate`] drops
+//! every entry up to an index at once by moving the head past them. The head is a dummy node,
+//! whose index is that of the last truncated entry. A truncation never moves the head past the
+//! tail. The truncated nodes are never reclaimed.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::pointers::{Atomic, Shared};
+use crossbeam_utils::CachePadded;
+
+pub(crate) struct Node<T> {
+    index: u64,
+    item: Option<T>,
+    next: Atomic<Node<T>>,
+}
+
+unsafe impl<T: Sync> Sync for Node<T> {}
+unsafe impl<T: Sync> Send for Node<T> {}
+
+pub struct Log<T: Sync + Send> {
+    head: CachePadded<Atomic<Node<T>>>,
+    tail: CachePadded<Atomic<Node<T>>>,
+    /// The index of the head, which is raised after the head moves.
+    head_index: CachePadded<AtomicU64>,
+}
+
+impl<T: Sync + Send> Default for Log<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Sync + Send> Log<T> {
+    pub fn new() -> Self {
+        let sentinel = Shared::from_owned(Node {
+            index: 0,
+            item: None,
+            next: Atomic::null(),
+        });
+        Self {
+            head: CachePadded::new(Atomic::from(sentinel)),
+            tail: CachePadded::new(Atomic::from(sentinel)),
+            head_index: CachePadded::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The index of the first entry that is not truncated.
+    pub fn first(&self) -> u64 {
+        self.head_index.load(Ordering::Acquire) + 1
+    }
+
+    /// The index of the last entry, which may lag behind an append in progress.
+    pub fn last(&self) -> u64 {
+        unsafe { self.tail.load(Ordering::Acquire).deref() }.index
+    }
+
+    /// Appends `item`, and returns its index.
+    pub fn append(&self, item: T) -> u64 {
+        let mut node = Shared::from_owned(Node {
+            index: 0,
+            item: Some(item),
+            next: Atomic::null(),
+        });
+        loop {
+            let ltail = self.tail.load(Ordering::Acquire);
+            let ltail_ref = unsafe { ltail.deref() };
+            let lnext = ltail_ref.next.load(Ordering::Acquire);
+            if !lnext.is_null() {
+                // Help the previous append to swing the tail.
+                let _ =
+                    self.tail
+                        .compare_exchange(ltail, lnext, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+            let index = ltail_ref.index + 1;
+            unsafe { node.deref_mut() }.index = index;
+            if ltail_ref
+                .next
+                .compare_exchange(Shared::null(), node, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                let _ =
+                    self.tail
+                        .compare_exchange(ltail, node, Ordering::Release, Ordering::Relaxed);
+                return index;
+            }
+        }
+    }
+
+    /// Returns the entry at `index`, if it is appended and not truncated.
+    pub fn get(&self, index: u64) -> Option<&T> {
+        let mut node = unsafe { self.head.load(Ordering::Acquire).deref() };
+        if index <= node.index {
+            return None;
+        }
+        while node.index < index {
+            node = unsafe { node.next.load(Ordering::Acquire).as_ref()? };
+        }
+        node.item.as_ref()
+    }
+
+    /// Drops the entries up to `index`, or up to the tail if it is behind, and returns the number
+    /// of dropped entries.
+    pub fn truncate(&self, index: u64) -> usize {
+        loop {
+            let lhead = self.head.load(Ordering::Acquire);
+            let lhead_index = unsafe { lhead.deref() }.index;
+            let target = index.min(self.last());
+            if target <= lhead_index {
+                return 0;
+            }
+            let mut new_head = lhead;
+            while unsafe { new_head.deref() }.index < target {
+                new_head = unsafe { new_head.deref() }.next.load(Ordering::Acquire);
+            }
+            if self
+                .head
+                .compare_exchange(lhead, new_head, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                self.head_index.fetch_max(target, Ordering::Release);
+                return (target - lhead_index) as usize;
+            }
+        }
+    }
+}
+
+impl<T: Sync + Send> Drop for Log<T> {
+    fn drop(&mut self) {
+        // The nodes before `head` have already been truncated and are never reclaimed.
+        let mut node = self.head.load(Ordering::Relaxed);
+        while !node.is_null() {
+            let next = unsafe { node.deref() }.next.load(Ordering::Relaxed);
+            drop(unsafe { node.into_owned() });
+            node = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Log;
+    use crossbeam_utils::thread::scope;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const THREADS: u64 = 8;
+    const APPENDS_PER_THREAD: u64 = 10000;
+    const RETAIN: u64 = 256;
+
+    #[test]
+    fn simple() {
+        let log = Log::<u64>::new();
+        assert!(log.get(1).is_none());
+        for i in 1..=10 {
+            assert_eq!(log.append(i * 100), i);
+        }
+        assert_eq!(*log.get(3).unwrap(), 300);
+        assert_eq!(log.truncate(4), 4);
+        assert_eq!(log.first(), 5);
+        assert!(log.get(4).is_none());
+        assert_eq!(*log.get(5).unwrap(), 500);
+        // A truncation stops at the tail.
+        assert_eq!(log.truncate(100), 6);
+        assert_eq!(log.first(), 11);
+        assert!(log.get(10).is_none());
+        assert_eq!(log.append(1100), 11);
+        assert_eq!(*log.get(11).unwrap(), 1100);
+    }
+
+    /// Appends from several threads while a truncator keeps the last `RETAIN` entries and
+    /// readers look up the retained ones, and checks that every append got a distinct index.
+    #[test]
+    fn smoke() {
+        let log = &Log::new();
+        let done = &AtomicBool::new(false);
+        let mut indices = scope(|s| {
+            let appenders = (0..THREADS)
+                .map(|t| {
+                    s.spawn(move |_| {
+                        (0..APPENDS_PER_THREAD)
+                            .map(|_| log.append(t))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            for _ in 0..2 {
+                s.spawn(move |_| {
+                    while !done.load(Ordering::Relaxed) {
+                        for index in (log.first()..=log.last()).step_by(17) {
+                            if let Some(item) = log.get(index) {
+                                assert!(*item < THREADS);
+                            }
+                        }
+                    }
+                });
+            }
+            s.spawn(move |_| {
+                let mut truncated = 0;
+                while truncated + RETAIN < THREADS * APPENDS_PER_THREAD {
+                    truncated += log.truncate(log.last().saturating_sub(RETAIN)) as u64;
+                }
+                done.store(true, Ordering::Relaxed);
+            });
+            appenders
+                .into_iter()
+                .flat_map(|appender| appender.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+        indices.sort_unstable();
+        assert!(indices.iter().copied().eq(1..=THREADS * APPENDS_PER_THREAD));
+        assert_eq!(log.first(), THREADS * APPENDS_PER_THREAD - RETAIN + 1);
+    }
+}
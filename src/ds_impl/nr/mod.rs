@@ -1,22 +1,36 @@
+pub mod concurrent_counter;
 pub mod concurrent_map;
+pub mod concurrent_set;
+pub mod immediate_free;
+// The compressed pointers take precedence over the raw pointers, so that `--all-features` builds.
+#[cfg(not(feature = "compressed-pointers"))]
+pub mod pointers;
+#[cfg(feature = "compressed-pointers")]
+#[path = "compressed_pointers.rs"]
 pub mod pointers;
 
 pub mod bonsai_tree;
+pub mod clock_cache;
 pub mod double_link;
 pub mod elim_ab_tree;
 pub mod ellen_tree;
 pub mod list;
+pub mod log;
 pub mod michael_hash_map;
 pub mod natarajan_mittal_tree;
 pub mod skip_list;
 
+pub use self::concurrent_counter::{ConcurrentCounter, HashMapCounter};
 pub use self::concurrent_map::ConcurrentMap;
+pub use self::concurrent_set::{ConcurrentSet, HHSListSet, HListSet, HMListSet, SkipListSet};
 
 pub use self::bonsai_tree::BonsaiTreeMap;
+pub use self::clock_cache::ClockCache;
 pub use self::double_link::DoubleLink;
 pub use self::elim_ab_tree::ElimABTree;
 pub use self::ellen_tree::EFRBTree;
 pub use self::list::{HHSList, HList, HMList};
+pub use self::log::Log;
 pub use self::michael_hash_map::HashMap;
 pub use self::natarajan_mittal_tree::NMTreeMap;
 pub use self::skip_list::SkipList;
@@ -1,5 +1,7 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
 use super::pointers::{Atomic, Shared};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use std::cmp;
 use std::sync::atomic::Ordering;
 
@@ -84,7 +86,7 @@ where
     }
 }
 
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: Key<K>,
     value: Option<V>,
     left: Atomic<Node<K, V>>,
@@ -167,6 +169,29 @@ pub struct NMTreeMap<K, V> {
     r: Node<K, V>,
 }
 
+impl<K, V> Drop for NMTreeMap<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut stack = vec![
+                self.r.left.load(Ordering::Relaxed),
+                self.r.right.load(Ordering::Relaxed),
+            ];
+
+            while let Some(node) = stack.pop() {
+                if node.is_null() {
+                    continue;
+                }
+
+                let node_ref = node.deref();
+
+                stack.push(node_ref.left.load(Ordering::Relaxed));
+                stack.push(node_ref.right.load(Ordering::Relaxed));
+                drop(node.into_owned());
+            }
+        }
+    }
+}
+
 impl<K, V> Default for NMTreeMap<K, V>
 where
     K: Ord + Clone + 'static,
@@ -286,6 +311,15 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire);
         let leaf_flag = Marks::from_bits_truncate(leaf_marked.tag()).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag
+                || Marks::from_bits_truncate(
+                    record.leaf_sibling_addr().load(Ordering::Acquire).tag()
+                )
+                .flag(),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -301,7 +335,18 @@ where
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load(Ordering::Acquire);
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(target_sibling.tag()).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(target_sibling.tag()).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            record.successor.tag(),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         record
             .successor_addr()
             .compare_exchange(
@@ -310,10 +355,11 @@ where
                 Ordering::AcqRel,
                 Ordering::Acquire,
             )
-            .is_ok()
+            .classify(&record.successor)
+            .is_success()
     }
 
-    pub fn get(&self, key: &K) -> Option<&'static V> {
+    pub fn get(&self, key: &K) -> Option<&V> {
         let record = self.seek_leaf(key);
         let leaf_node = unsafe { record.leaf.deref() };
 
@@ -324,6 +370,40 @@ where
         Some(leaf_node.value.as_ref().unwrap())
     }
 
+    /// Returns the smallest key and its value, at the end of the leftmost path.
+    pub fn min(&self) -> Option<(&K, &V)> {
+        self.seek_end(Direction::L)
+    }
+
+    /// Returns the largest key and its value.
+    pub fn max(&self) -> Option<(&K, &V)> {
+        self.seek_end(Direction::R)
+    }
+
+    /// Descends from the subtree of the finite keys to its leftmost or rightmost finite leaf,
+    /// without marking anything, like `seek_leaf`. The finite keys under an internal node with an
+    /// infinite key are all in its left subtree, so going right turns left at such a node.
+    fn seek_end(&self, dir: Direction) -> Option<(&K, &V)> {
+        let s = self.r.left.load(Ordering::Relaxed);
+        let mut leaf = unsafe { s.deref() }.left.load(Ordering::Acquire);
+        let leaf_node = loop {
+            let node = unsafe { leaf.with_tag(0).deref() };
+            let next = match (&dir, &node.key) {
+                (Direction::R, Key::Fin(_)) => node.right.load(Ordering::Acquire),
+                _ => node.left.load(Ordering::Acquire),
+            };
+            if next.is_null() {
+                break node;
+            }
+            leaf = next;
+        };
+
+        match &leaf_node.key {
+            Key::Fin(key) => Some((key, leaf_node.value.as_ref().unwrap())),
+            Key::Inf => None,
+        }
+    }
+
     pub fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
         let mut new_leaf = Shared::from_owned(Node::new_leaf(Key::Fin(key.clone()), Some(value)));
 
@@ -356,25 +436,29 @@ where
             new_internal_node.right.store(new_right, Ordering::Relaxed);
 
             // NOTE: record.leaf_addr is called childAddr in the paper.
-            match record.leaf_addr().compare_exchange(
-                record.leaf,
-                new_internal,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => return Ok(()),
-                Err(e) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if e.current == record.leaf {
-                        self.cleanup(&record);
-                    }
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf,
+                    new_internal,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .classify(&record.leaf)
+            {
+                Cas::Success(_) => return Ok(()),
+                // Insertion failed. Help the conflicting remove operation if needed.
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    InsertConflict::Help.count();
+                    self.cleanup(&record);
                 }
+                Cas::CurrentDiffers(_) => InsertConflict::Retry.count(),
             }
         }
     }
 
-    pub fn remove(&self, key: &K) -> Option<&'static V> {
+    pub fn remove(&self, key: &K) -> Option<&V> {
         let mut record;
         // `leaf` and `value` are the snapshot of the node to be deleted.
         // NOTE: The paper version uses one big loop for both phases.
@@ -393,13 +477,18 @@ where
             let value = leaf_node.value.as_ref().unwrap();
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_exchange(
-                record.leaf,
-                record.leaf.with_tag(Marks::new(true, false).bits()),
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(record.leaf.tag(), 0, "the leaf edge is expected unmarked");
+            match record
+                .leaf_addr()
+                .compare_exchange(
+                    record.leaf,
+                    record.leaf.with_tag(Marks::new(true, false).bits()),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .classify(&record.leaf)
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(&record) {
                         return Some(value);
@@ -407,15 +496,14 @@ where
                     // In-place cleanup failed. Enter the cleanup phase.
                     break (leaf, value);
                 }
-                Err(e) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if record.leaf == e.current {
-                        self.cleanup(&record);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(&record);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         };
 
@@ -456,6 +544,14 @@ where
     fn remove(&self, key: &K) -> Option<impl OutputHolder<V>> {
         self.remove(key)
     }
+    #[inline(always)]
+    fn min(&self) -> Option<(&K, &V)> {
+        self.min()
+    }
+    #[inline(always)]
+    fn max(&self) -> Option<(&K, &V)> {
+        self.max()
+    }
 }
 
 #[cfg(test)]
@@ -467,4 +563,19 @@ mod tests {
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn min_max_nm_tree() {
+        concurrent_map::tests::min_max::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn extract_min_nm_tree() {
+        concurrent_map::tests::extract_min::<NMTreeMap<i32, i32>>();
+    }
 }
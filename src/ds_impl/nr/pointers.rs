@@ -1,3 +1,4 @@
+use crate::ds_impl::cas::{CasError, Link};
 use core::mem;
 use std::{
     ptr::null_mut,
@@ -9,6 +10,7 @@ pub struct CompareExchangeError<T, P: Pointer<T>> {
     pub current: Shared<T>,
 }
 
+#[cfg_attr(feature = "raw-pointers", repr(transparent))]
 pub struct Atomic<T> {
     link: AtomicPtr<T>,
 }
@@ -63,8 +65,8 @@ impl<T> Atomic<T> {
         }
     }
 
-    pub unsafe fn into_owned(self) -> Box<T> {
-        Box::from_raw(self.link.into_inner())
+    pub unsafe fn into_owned(self) -> T {
+        *Box::from_raw(self.link.into_inner())
     }
 }
 
@@ -91,6 +93,7 @@ impl<T> Clone for Atomic<T> {
     }
 }
 
+#[cfg_attr(feature = "raw-pointers", repr(transparent))]
 pub struct Shared<T> {
     ptr: *mut T,
 }
@@ -163,6 +166,23 @@ impl<T> PartialEq for Shared<T> {
 
 impl<T> Eq for Shared<T> {}
 
+impl<T> Link for Shared<T> {
+    #[inline]
+    fn parts(&self) -> (usize, usize) {
+        let (ptr, tag) = decompose_tag(self.ptr);
+        (ptr as usize, tag)
+    }
+}
+
+impl<T, P: Pointer<T>> CasError for CompareExchangeError<T, P> {
+    type Current = Shared<T>;
+
+    #[inline]
+    fn current(&self) -> &Shared<T> {
+        &self.current
+    }
+}
+
 pub trait Pointer<T> {
     fn into_raw(self) -> *mut T;
     unsafe fn from_raw(val: *mut T) -> Self;
@@ -189,7 +209,8 @@ impl<T> Pointer<T> for Box<T> {
 }
 
 /// Returns a bitmask containing the unused least significant bits of an aligned pointer to `T`.
-#[inline]
+#[cfg_attr(not(feature = "raw-pointers"), inline)]
+#[cfg_attr(feature = "raw-pointers", inline(always))]
 fn low_bits<T: Sized>() -> usize {
     (1 << mem::align_of::<T>().trailing_zeros()) - 1
 }
@@ -197,6 +218,7 @@ fn low_bits<T: Sized>() -> usize {
 /// Given a tagged pointer `data`, returns the same pointer, but tagged with `tag`.
 ///
 /// `tag` is truncated to fit into the unused bits of the pointer to `T`.
+#[cfg(not(feature = "raw-pointers"))]
 #[inline]
 pub(crate) fn compose_tag<T: Sized>(ptr: *mut T, tag: usize) -> *mut T {
     int_to_ptr_with_provenance(
@@ -206,6 +228,7 @@ pub(crate) fn compose_tag<T: Sized>(ptr: *mut T, tag: usize) -> *mut T {
 }
 
 /// Decomposes a tagged pointer `data` into the pointer and the tag.
+#[cfg(not(feature = "raw-pointers"))]
 #[inline]
 pub(crate) fn decompose_tag<T: Sized>(ptr: *mut T) -> (*mut T, usize) {
     (
@@ -215,8 +238,33 @@ pub(crate) fn decompose_tag<T: Sized>(ptr: *mut T) -> (*mut T, usize) {
 }
 
 // HACK: https://github.com/rust-lang/miri/issues/1866#issuecomment-985802751
+#[cfg(not(feature = "raw-pointers"))]
 #[inline]
 fn int_to_ptr_with_provenance<T>(addr: usize, prov: *mut T) -> *mut T {
     let ptr = prov.cast::<u8>();
     ptr.wrapping_add(addr.wrapping_sub(ptr as usize)).cast()
 }
+
+// With `raw-pointers`, the tags are masked off and on by integer casts instead of pointer
+// arithmetic on the original pointer, so that a traversal compiles to the loads and compares that
+// an unprotected C implementation would do. The casts lose the provenance of the addresses, so
+// Miri cannot check the structures with them.
+
+/// Given a tagged pointer `data`, returns the same pointer, but tagged with `tag`.
+///
+/// `tag` is truncated to fit into the unused bits of the pointer to `T`.
+#[cfg(feature = "raw-pointers")]
+#[inline(always)]
+pub(crate) fn compose_tag<T: Sized>(ptr: *mut T, tag: usize) -> *mut T {
+    ((ptr as usize & !low_bits::<T>()) | (tag & low_bits::<T>())) as *mut T
+}
+
+/// Decomposes a tagged pointer `data` into the pointer and the tag.
+#[cfg(feature = "raw-pointers")]
+#[inline(always)]
+pub(crate) fn decompose_tag<T: Sized>(ptr: *mut T) -> (*mut T, usize) {
+    (
+        (ptr as usize & !low_bits::<T>()) as *mut T,
+        ptr as usize & low_bits::<T>(),
+    )
+}
@@ -8,7 +8,7 @@ const MAX_HEIGHT: usize = 32;
 
 type Tower<K, V> = [Atomic<Node<K, V>>; MAX_HEIGHT];
 
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: K,
     value: V,
     next: Tower<K, V>,
@@ -77,6 +77,17 @@ pub struct SkipList<K, V> {
     head: Tower<K, V>,
 }
 
+impl<K, V> Drop for SkipList<K, V> {
+    fn drop(&mut self) {
+        let mut node = self.head[0].load(Ordering::Relaxed);
+        while let Some(node_ref) = unsafe { node.as_ref() } {
+            let next = node_ref.next[0].load(Ordering::Relaxed);
+            drop(unsafe { node.into_owned() });
+            node = next;
+        }
+    }
+}
+
 impl<K, V> Default for SkipList<K, V>
 where
     K: Ord + Clone,
@@ -356,4 +367,9 @@ mod tests {
     fn smoke_skip_list() {
         concurrent_map::tests::smoke::<_, SkipList<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_skip_list() {
+        concurrent_map::tests::edge_cases::<_, SkipList<i32, String>, _>(&i32::to_string);
+    }
 }
@@ -36,6 +36,30 @@ pub trait ConcurrentMap<K, V> {
         key: &K,
         guard: &mut Guard,
     ) -> Option<impl OutputHolder<V>>;
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(
+        &self,
+        handle: &mut Self::Handle,
+        key: K,
+        value: V,
+        guard: &mut Guard,
+    ) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if let Some(found) = self.get(handle, &key, guard) {
+                return Some(found.output().clone());
+            }
+            if self.insert(handle, key.clone(), value.clone(), guard) {
+                return None;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -45,10 +69,13 @@ pub mod tests {
     use crossbeam_pebr::pin;
     use crossbeam_utils::thread;
     use rand::prelude::*;
+    use std::collections::VecDeque;
     use std::fmt::Debug;
 
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
+    const PULL_KEYS: i32 = 200;
+    const PULL_ROUNDS: i32 = 5;
 
     pub fn smoke<V, M, F>(to_value: &F)
     where
@@ -112,4 +139,178 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Checks that the holder of `get` keeps its value until its handle and guard are used again,
+    /// and that of `remove` for as long as it lives, while the same thread and another one remove
+    /// the keys and insert them again with other values.
+    pub fn output_holder<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let handle = &mut M::handle(&pin());
+        for i in 0..ELEMENTS_PER_THREADS {
+            assert!(map.insert(handle, i, to_value(&i), &mut pin()));
+        }
+
+        let get_handle = &mut M::handle(&pin());
+        let get_guard = &mut pin();
+        let got = map.get(get_handle, &0, get_guard).unwrap();
+        let removed = map.remove(handle, &1, &mut pin()).unwrap();
+        assert!(map.remove(handle, &0, &mut pin()).is_some());
+        assert!(map.insert(handle, 0, to_value(&-1), &mut pin()));
+        assert_eq!(
+            to_value(&-1),
+            *map.get(handle, &0, &mut pin()).unwrap().output()
+        );
+
+        thread::scope(|s| {
+            s.spawn(move |_| {
+                let mut handle = M::handle(&pin());
+                for i in 0..ELEMENTS_PER_THREADS {
+                    let guard = &mut pin();
+                    map.remove(&mut handle, &i, guard);
+                    assert!(map.insert(&mut handle, i, to_value(&(i + 1)), guard));
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(to_value(&0), *got.output());
+        assert_eq!(to_value(&1), *removed.output());
+    }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V>,
+        F: Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let handle = &mut M::handle(&pin());
+        let guard = &mut pin();
+
+        // An empty map.
+        assert!(map.get(handle, &0, guard).is_none());
+        assert!(map.remove(handle, &0, guard).is_none());
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(handle, 0, to_value(&0), guard));
+        assert!(!map.insert(handle, 0, to_value(&1), guard));
+        assert_eq!(to_value(&0), *map.get(handle, &0, guard).unwrap().output());
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert_eq!(
+            to_value(&0),
+            *map.remove(handle, &0, guard).unwrap().output()
+        );
+        assert!(map.get(handle, &0, guard).is_none());
+        assert!(map.remove(handle, &0, guard).is_none());
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(handle, i, to_value(&i), guard));
+            }
+            assert_eq!(
+                to_value(&removed),
+                *map.remove(handle, &removed, guard).unwrap().output()
+            );
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(handle, &i, guard).is_some());
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert_eq!(
+                    to_value(&i),
+                    *map.remove(handle, &i, guard).unwrap().output()
+                );
+            }
+        }
+        for i in 0..=3 {
+            assert!(map.get(handle, &i, guard).is_none());
+        }
+    }
+
+    /// Removes the keys of a tree in the order of the levels of a balanced tree, medians first, and
+    /// inserts them back in the same order, for a few rounds. Most keys then have two children when
+    /// they are removed, which pulls the rightmost node of the left subtree up into their place, and
+    /// the rest have a child on one side, which pulls the leftmost node of the right subtree. Both
+    /// rebuild the path to the pulled node, which the uniform keys of `smoke` rarely do. The tree is
+    /// prefilled in an increasing order, which skews it until it is rebalanced. Each thread owns
+    /// every `THREADS`-th key, and checks that its keys next to each removed key keep their values.
+    pub fn pull_stress<V, M, F>(to_value: &F)
+    where
+        V: Eq + Debug,
+        M: ConcurrentMap<i32, V> + Send + Sync,
+        F: Sync + Fn(&i32) -> V,
+    {
+        let map = &M::new();
+        let mut handle = M::handle(&pin());
+        for i in 0..THREADS * PULL_KEYS {
+            assert!(map.insert(&mut handle, i, to_value(&i), &mut pin()));
+        }
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut handle = M::handle(&pin());
+                    let keys = medians_first(PULL_KEYS);
+                    for _ in 0..PULL_ROUNDS {
+                        let mut removed = vec![false; PULL_KEYS as usize];
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert_eq!(
+                                to_value(&i),
+                                *map.remove(&mut handle, &i, &mut pin()).unwrap().output()
+                            );
+                            removed[k as usize] = true;
+                            for n in [k - 1, k + 1] {
+                                if (0..PULL_KEYS).contains(&n) && !removed[n as usize] {
+                                    let j = n * THREADS + t;
+                                    assert_eq!(
+                                        to_value(&j),
+                                        *map.get(&mut handle, &j, &mut pin()).unwrap().output()
+                                    );
+                                }
+                            }
+                        }
+                        for &k in &keys {
+                            let i = k * THREADS + t;
+                            assert!(map.insert(&mut handle, i, to_value(&i), &mut pin()));
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let mut handle = M::handle(&pin());
+        for j in 0..THREADS * PULL_KEYS {
+            assert_eq!(
+                to_value(&j),
+                *map.get(&mut handle, &j, &mut pin()).unwrap().output()
+            );
+        }
+    }
+
+    /// The keys in `0..n` in the order of the levels of a balanced tree, medians first.
+    fn medians_first(n: i32) -> Vec<i32> {
+        let mut keys = Vec::with_capacity(n as usize);
+        let mut ranges = VecDeque::from([(0, n)]);
+        while let Some((lo, hi)) = ranges.pop_front() {
+            if lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                keys.push(mid);
+                ranges.push_back((lo, mid));
+                ranges.push_back((mid + 1, hi));
+            }
+        }
+        keys
+    }
 }
@@ -1,4 +1,7 @@
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 use crossbeam_pebr::{unprotected, Atomic, Guard, Owned, Pointer, Shared, Shield, ShieldError};
 
 use std::cmp::Ordering::{Equal, Greater, Less};
@@ -7,7 +10,7 @@ use std::ptr;
 use std::sync::atomic::Ordering;
 
 enum FindError {
-    Retry,
+    Retry(Retry),
     ShieldError(ShieldError),
 }
 
@@ -15,7 +18,7 @@ enum FindError {
 // is also the first data in the memory alignment.
 #[repr(C)]
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     /// Mark: tag(), Tag: not needed
     next: Atomic<Node<K, V>>,
     key: K,
@@ -142,7 +145,7 @@ where
             .compare_and_set(prev_next, curr, Ordering::Release, guard)
             .is_err()
         {
-            return Err(FindError::Retry);
+            return Err(FindError::Retry(Retry::Unlink));
         }
 
         // defer_destroy from cursor.prev.load() to cursor.curr (exclusive)
@@ -194,7 +197,7 @@ where
                 {
                     unsafe { guard.defer_destroy(curr) };
                 } else {
-                    break Err(FindError::Retry);
+                    break Err(FindError::Retry(Retry::Unlink));
                 }
             }
             curr = next;
@@ -209,6 +212,7 @@ where
         key: &K,
         guard: &'g Guard,
     ) -> Result<bool, FindError> {
+        wait_free::traversal();
         let head = unsafe { &*(self.prev.shared().into_usize() as *const Atomic<Node<K, V>>) };
         let mut curr = head.load(Ordering::Acquire, guard);
 
@@ -217,6 +221,7 @@ where
                 unsafe { self.curr.defend_fake(curr) };
                 return Ok(false);
             }
+            wait_free::visit();
 
             self.curr
                 .defend(curr, guard)
@@ -262,7 +267,7 @@ where
             cursor.init_find(&self.head);
             match find(cursor, key, unsafe { &*(guard as *mut Guard) }) {
                 Ok(r) => return r,
-                Err(FindError::Retry) => continue,
+                Err(FindError::Retry(retry)) => retry.count(),
                 Err(FindError::ShieldError(ShieldError::Ejected)) => guard.repin(),
             }
         }
@@ -315,10 +320,12 @@ where
             if unsafe { cursor.prev.deref() }
                 .next
                 .compare_and_set(cursor.curr.shared(), node, Ordering::Release, guard)
-                .is_ok()
+                .classify(&cursor.curr.shared())
+                .is_success()
             {
                 return Ok(true);
             }
+            Retry::CasFail.count();
         }
     }
 
@@ -344,7 +351,7 @@ where
         loop {
             match self.insert_inner(node, &find, cursor, unsafe { &mut *(guard as *mut Guard) }) {
                 Ok(r) => return r,
-                Err(FindError::Retry) => continue,
+                Err(FindError::Retry(retry)) => retry.count(),
                 Err(FindError::ShieldError(ShieldError::Ejected)) => guard.repin(),
             }
         }
@@ -370,6 +377,7 @@ where
             let curr_node = unsafe { cursor.curr.as_ref() }.unwrap();
             let next = curr_node.next.fetch_or(1, Ordering::AcqRel, guard);
             if next.tag() == 1 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -401,7 +409,7 @@ where
         loop {
             match self.remove_inner(key, &find, cursor, unsafe { &mut *(guard as *mut Guard) }) {
                 Ok(r) => return r,
-                Err(FindError::Retry) => continue,
+                Err(FindError::Retry(retry)) => retry.count(),
                 Err(FindError::ShieldError(ShieldError::Ejected)) => guard.repin(),
             }
         }
@@ -429,7 +437,7 @@ where
         let curr_node = unsafe { cursor.curr.as_ref() }.unwrap();
         let next = curr_node.next.fetch_or(1, Ordering::AcqRel, guard);
         if next.tag() == 1 {
-            return Err(FindError::Retry);
+            return Err(FindError::Retry(Retry::CasFail));
         }
 
         let key = curr_node.key.clone();
@@ -451,7 +459,7 @@ where
         loop {
             match self.pop_inner(cursor, unsafe { &mut *(guard as *mut Guard) }) {
                 Ok(r) => return r,
-                Err(FindError::Retry) => continue,
+                Err(FindError::Retry(retry)) => retry.count(),
                 Err(FindError::ShieldError(ShieldError::Ejected)) => guard.repin(),
             }
         }
@@ -698,16 +706,51 @@ mod tests {
         concurrent_map::tests::smoke::<_, HList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<_, HList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hm_list() {
         concurrent_map::tests::smoke::<_, HMList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hm_list() {
+        concurrent_map::tests::edge_cases::<_, HMList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<_, HHSList<i32, String>, _>(&i32::to_string);
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use concurrent_map::ConcurrentMap;
+        let map = HHSList::<i32, String>::new();
+        let guard = &mut crossbeam_pebr::pin();
+        let handle = &mut HHSList::<i32, String>::handle(guard);
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(handle, key, key.to_string(), guard),
+            Op::Remove => map.remove(handle, &key, guard).is_some(),
+            Op::Get => map.get(handle, &key, guard).is_some(),
+        });
+    }
+
+    #[test]
+    fn output_holder_hhs_list() {
+        concurrent_map::tests::output_holder::<_, HHSList<i32, String>, _>(&i32::to_string);
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use concurrent_map::ConcurrentMap;
@@ -715,6 +758,7 @@ mod tests {
 
         let guard = &mut crossbeam_pebr::pin();
         let handle = &mut HHSList::handle(guard);
+        assert_eq!(map.pop(handle, guard), None);
         map.insert(handle, 1, "1".to_string(), guard);
         map.insert(handle, 2, "2".to_string(), guard);
         map.insert(handle, 3, "3".to_string(), guard);
@@ -1,6 +1,8 @@
 use crossbeam_pebr::{unprotected, Atomic, Guard, Owned, Pointer, Shared, Shield, ShieldError};
 
 use super::concurrent_map::{ConcurrentMap, OutputHolder};
+use crate::ds_impl::cas::{Cas, Classify};
+use crate::ds_impl::helping::InsertConflict;
 use std::cmp;
 use std::mem;
 use std::sync::atomic::Ordering;
@@ -87,7 +89,7 @@ where
 }
 
 #[derive(Debug)]
-struct Node<K, V> {
+pub(crate) struct Node<K, V> {
     key: Key<K>,
     // TODO(@jeehoonkang): how about having another type that is either (1) value, or (2) left and
     // right.
@@ -321,6 +323,18 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire, guard);
         let leaf_flag = Marks::from_bits_truncate(leaf_marked.tag()).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        debug_assert!(
+            leaf_flag
+                || Marks::from_bits_truncate(
+                    record
+                        .leaf_sibling_addr()
+                        .load(Ordering::Acquire, guard)
+                        .tag()
+                )
+                .flag(),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -336,7 +350,18 @@ where
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load(Ordering::Acquire, guard);
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(target_sibling.tag()).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(target_sibling.tag()).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            record.successor.shared().tag(),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         let is_unlinked = record
             .successor_addr()
             .compare_and_set(
@@ -345,7 +370,8 @@ where
                 Ordering::AcqRel,
                 &guard,
             )
-            .is_ok();
+            .classify(&record.successor.shared())
+            .is_success();
 
         if is_unlinked {
             unsafe {
@@ -458,15 +484,16 @@ where
             match record
                 .leaf_addr()
                 .compare_and_set(leaf, new_internal, Ordering::AcqRel, &guard)
+                .classify(&leaf)
             {
-                Ok(_) => return Ok(()),
-                Err(e) => {
-                    // Insertion failed. Help the conflicting remove operation if needed.
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if e.current.with_tag(Marks::empty().bits()) == leaf {
-                        self.cleanup(&record, guard);
-                    }
+                Cas::Success(_) => return Ok(()),
+                // Insertion failed. Help the conflicting remove operation if needed.
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    InsertConflict::Help.count();
+                    self.cleanup(&record, guard);
                 }
+                Cas::CurrentDiffers(_) => InsertConflict::Retry.count(),
             }
         }
     }
@@ -518,13 +545,18 @@ where
             let value = leaf_node.value.as_ref().unwrap().clone();
 
             // Try injecting the deletion flag.
-            match record.leaf_addr().compare_and_set(
-                leaf,
-                leaf.with_tag(Marks::new(true, false).bits()),
-                Ordering::AcqRel,
-                &guard,
-            ) {
-                Ok(_) => {
+            debug_assert_eq!(leaf.tag(), 0, "the leaf edge is expected unmarked");
+            match record
+                .leaf_addr()
+                .compare_and_set(
+                    leaf,
+                    leaf.with_tag(Marks::new(true, false).bits()),
+                    Ordering::AcqRel,
+                    &guard,
+                )
+                .classify(&leaf)
+            {
+                Cas::Success(_) => {
                     // Finalize the node to be removed
                     if self.cleanup(&record, guard) {
                         return Ok(DeleteResult::CleanedUp(value));
@@ -533,15 +565,14 @@ where
                     // In-place cleanup failed. Enter the cleanup phase.
                     return Ok(DeleteResult::Injected(value, leaf.into_usize()));
                 }
-                Err(e) => {
-                    // Flagging failed.
-                    // case 1. record.leaf_addr(e.current) points to another node: restart.
-                    // case 2. Another thread flagged/tagged the edge to leaf: help and restart
-                    // NOTE: The paper version checks if any of the mark is set, which is redundant.
-                    if leaf == e.current.with_tag(Marks::empty().bits()) {
-                        self.cleanup(&record, guard);
-                    }
+                // Flagging failed.
+                // case 1. record.leaf_addr(e.current) points to another node: restart.
+                // case 2. Another thread flagged/tagged the edge to leaf: help and restart
+                // NOTE: The paper version checks if any of the mark is set, which is redundant.
+                Cas::TagDiffers(_) => {
+                    self.cleanup(&record, guard);
                 }
+                Cas::CurrentDiffers(_) => {}
             }
         }
     }
@@ -630,4 +661,9 @@ mod tests {
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<_, NMTreeMap<i32, String>, _>(&i32::to_string);
+    }
 }
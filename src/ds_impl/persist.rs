@@ -0,0 +1,135 @@
+//! The write-backs and fences of the persistent structures, counted per thread.
+//!
+//! A structure on persistent memory writes a cache line back (`clwb`) after it changes a node or a
+//! link that must survive a crash, and orders the write-backs with a fence (`sfence`) before the
+//! change becomes the state that an operation returns. The persistent structures call [`flush`]
+//! and [`fence`] at those points, and both are counted per thread with
+//! [`Counters`](super::counters::Counters), so that the benchmark reports how many of each a
+//! workload issues with each scheme. They are only executed after [`set_execute`], e.g., on a
+//! machine with real persistent memory, since on DRAM a write-back only slows the structure down
+//! without making anything durable. A CPU without `clwb` writes back with `clflush`, which also
+//! evicts the line.
+
+use super::counters::{Counters, LocalCounts};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static EXECUTE: AtomicBool = AtomicBool::new(false);
+
+/// Executes the write-backs and the fences from now on, instead of only counting them.
+pub fn set_execute(execute: bool) {
+    EXECUTE.store(execute, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Point {
+    Flush,
+    Fence,
+}
+
+static COUNTERS: Counters<2> = Counters::new(&LOCAL);
+
+thread_local! {
+    static LOCAL: LocalCounts<2> = LocalCounts::new(&COUNTERS);
+}
+
+impl Point {
+    #[inline]
+    fn count(self) {
+        COUNTERS.count(self as usize);
+    }
+}
+
+/// Writes the cache line of `addr` back to the memory.
+#[inline]
+pub fn flush<T>(addr: *const T) {
+    Point::Flush.count();
+    if EXECUTE.load(Ordering::Relaxed) {
+        write_back(addr.cast());
+    }
+}
+
+/// Orders the previous write-backs before the following stores.
+#[inline]
+pub fn fence() {
+    Point::Fence.count();
+    if EXECUTE.load(Ordering::Relaxed) {
+        store_fence();
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn write_back(addr: *const u8) {
+    use std::arch::x86_64::{__cpuid_count, _mm_clflush};
+    use std::sync::OnceLock;
+
+    static CLWB: OnceLock<bool> = OnceLock::new();
+    // CPUID.(EAX=07H, ECX=0):EBX[bit 24] is `clwb`.
+    let clwb = *CLWB.get_or_init(|| unsafe { __cpuid_count(7, 0) }.ebx & (1 << 24) != 0);
+    unsafe {
+        if clwb {
+            std::arch::asm!("clwb [{}]", in(reg) addr, options(nostack, preserves_flags));
+        } else {
+            _mm_clflush(addr);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn write_back(_addr: *const u8) {}
+
+#[cfg(target_arch = "x86_64")]
+fn store_fence() {
+    unsafe { std::arch::x86_64::_mm_sfence() };
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn store_fence() {
+    std::sync::atomic::fence(Ordering::SeqCst);
+}
+
+/// The numbers of write-backs and fences, of the exited threads and the calling thread.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Persists {
+    pub flushes: usize,
+    pub fences: usize,
+}
+
+/// Returns the numbers of write-backs and fences since the start of the process, including those
+/// of the calling thread, but not those of the other running threads.
+pub fn persists() -> Persists {
+    let [flushes, fences] = COUNTERS.get();
+    Persists { flushes, fences }
+}
+
+impl fmt::Display for Persists {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} write-backs, {} fences", self.flushes, self.fences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fence, flush, persists, set_execute};
+    use std::thread;
+
+    #[test]
+    fn count_and_execute() {
+        let before = persists();
+        thread::spawn(|| {
+            let line = Box::new([0u64; 8]);
+            flush(&*line);
+            fence();
+            set_execute(true);
+            flush(&line[4]);
+            fence();
+            set_execute(false);
+        })
+        .join()
+        .unwrap();
+        let after = persists();
+        // Other tests may persist their own structures concurrently.
+        assert!(after.flushes >= before.flushes + 2);
+        assert!(after.fences >= before.fences + 2);
+    }
+}
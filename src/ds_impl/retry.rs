@@ -0,0 +1,116 @@
+//! The causes for which an operation of a structure restarts its traversal.
+//!
+//! A cursor operation that cannot proceed returns one of them instead of `Err(())`, and the
+//! operation counts it when it retries. The counts are kept per thread with
+//! [`Counters`](super::counters::Counters), so that counting does not add contention to a retry
+//! storm.
+
+use super::counters::{Counters, LocalCounts};
+use std::fmt;
+use std::ops::Div;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retry {
+    /// A CAS that unlinks logically removed nodes failed, so the cursor may not be adjacent.
+    Unlink,
+    /// The CAS that links or marks a node failed, because another thread changed the link.
+    CasFail,
+    /// A node could not be protected, because it was changed or removed after it was read.
+    Validation,
+}
+
+static COUNTERS: Counters<3> = Counters::new(&LOCAL);
+
+thread_local! {
+    static LOCAL: LocalCounts<3> = LocalCounts::new(&COUNTERS);
+}
+
+impl Retry {
+    /// Counts a retry for this cause.
+    #[inline]
+    pub fn count(self) {
+        COUNTERS.count(self as usize);
+    }
+}
+
+/// The numbers of retries for each cause, of the exited threads and the calling thread.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Retries([usize; 3]);
+
+impl Retries {
+    pub fn get(&self, cause: Retry) -> usize {
+        self.0[cause as usize]
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+/// Returns the numbers of retries since the start of the process, including those of the calling
+/// thread, but not those of the other running threads.
+pub fn retries() -> Retries {
+    Retries(COUNTERS.get())
+}
+
+/// Marks the start of the measured window of the calling thread.
+pub fn arrive() {
+    COUNTERS.arrive();
+}
+
+/// Adds the retries of the calling thread since it arrived to those of the measured windows.
+pub fn depart() {
+    COUNTERS.depart();
+}
+
+/// Returns the numbers of retries in the measured windows of the threads that have departed.
+pub fn measured_retries() -> Retries {
+    Retries(COUNTERS.measured())
+}
+
+/// The average over `reps` repetitions.
+impl Div<usize> for Retries {
+    type Output = Retries;
+
+    fn div(self, reps: usize) -> Retries {
+        Retries(self.0.map(|count| count / reps))
+    }
+}
+
+impl fmt::Display for Retries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unlink {}, cas fail {}, validation {}",
+            self.get(Retry::Unlink),
+            self.get(Retry::CasFail),
+            self.get(Retry::Validation)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{retries, Retry};
+    use std::thread;
+
+    const CAUSES: [Retry; 3] = [Retry::Unlink, Retry::CasFail, Retry::Validation];
+
+    #[test]
+    fn retries_of_exited_threads() {
+        let before = retries();
+        thread::spawn(|| {
+            for cause in CAUSES {
+                cause.count();
+            }
+            Retry::Unlink.count();
+        })
+        .join()
+        .unwrap();
+        let after = retries();
+        // Other tests may count retries concurrently.
+        assert!(after.get(Retry::Unlink) >= before.get(Retry::Unlink) + 2);
+        assert!(after.get(Retry::CasFail) > before.get(Retry::CasFail));
+        assert!(after.get(Retry::Validation) > before.get(Retry::Validation));
+    }
+}
@@ -8,6 +8,24 @@ pub trait ConcurrentMap<K, V> {
     fn get(&self, key: &K, local: &Self::Local) -> Option<V>;
     fn insert(&self, key: K, value: V, local: &Self::Local) -> bool;
     fn remove(&self, key: &K, local: &Self::Local) -> Option<V>;
+    /// Returns the value of `key` if it is present, or inserts `value` for it and returns `None`.
+    ///
+    /// The structures of this scheme use this default, which looks up the key and then inserts it,
+    /// retrying if another thread inserts the key in between.
+    fn get_or_insert(&self, key: K, value: V, local: &Self::Local) -> Option<V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        loop {
+            if let Some(found) = self.get(&key, local) {
+                return Some(found);
+            }
+            if self.insert(key.clone(), value.clone(), local) {
+                return None;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -73,4 +91,45 @@ pub mod tests {
         })
         .unwrap();
     }
+
+    /// Runs the single-threaded edge cases of a map, which the smoke test rarely hits
+    /// deterministically: the operations on an empty map, a duplicate insert, the removal of the
+    /// only key, and the removal of each of three keys, which promotes the sibling of the removed
+    /// leaf in the external trees.
+    pub fn edge_cases<M: ConcurrentMap<i32, i32>>() {
+        let global = &M::global(16);
+        let local = &M::local(global);
+        let map = &M::new(local);
+
+        // An empty map.
+        assert!(map.get(&0, local).is_none());
+        assert!(map.remove(&0, local).is_none());
+
+        // A duplicate insert keeps the old value.
+        assert!(map.insert(0, 0, local));
+        assert!(!map.insert(0, 1, local));
+        assert_eq!(Some(0), map.get(&0, local));
+
+        // Removing the only key empties the map, which replaces the root of some trees.
+        assert_eq!(Some(0), map.remove(&0, local));
+        assert!(map.get(&0, local).is_none());
+        assert!(map.remove(&0, local).is_none());
+
+        // Removing the smallest, the middle, and the largest of three keys.
+        for removed in 1..=3 {
+            for i in 1..=3 {
+                assert!(map.insert(i, i, local));
+            }
+            assert_eq!(Some(removed), map.remove(&removed, local));
+            for i in 1..=3 {
+                assert_eq!(i != removed, map.get(&i, local).is_some());
+            }
+            for i in (1..=3).filter(|&i| i != removed) {
+                assert_eq!(Some(i), map.remove(&i, local));
+            }
+        }
+        for i in 0..=3 {
+            assert!(map.get(&i, local).is_none());
+        }
+    }
 }
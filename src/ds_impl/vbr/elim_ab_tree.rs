@@ -1635,4 +1635,9 @@ mod tests {
     fn smoke_elim_ab_tree() {
         concurrent_map::tests::smoke::<ElimABTree<i32, i32>>();
     }
+
+    #[test]
+    fn edge_cases_elim_ab_tree() {
+        concurrent_map::tests::edge_cases::<ElimABTree<i32, i32>>();
+    }
 }
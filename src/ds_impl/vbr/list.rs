@@ -1,4 +1,6 @@
 use super::concurrent_map::ConcurrentMap;
+use crate::ds_impl::retry::Retry;
+use crate::ds_impl::wait_free;
 use vbr::CompareExchangeError::Success;
 use vbr::{Entry, Global, Guard, ImmAtomic, Local, MutAtomic, Shared};
 
@@ -59,12 +61,13 @@ where
     pub fn head(
         head: Shared<'g, Node<K, V>>,
         guard: &'g Guard<Node<K, V>>,
-    ) -> Result<Cursor<'g, K, V>, ()> {
+    ) -> Result<Cursor<'g, K, V>, Retry> {
         Ok(Self {
             prev: head,
             curr: unsafe { head.deref() }
                 .next
-                .load(Ordering::Acquire, guard)?,
+                .load(Ordering::Acquire, guard)
+                .map_err(|_| Retry::Validation)?,
         })
     }
 }
@@ -97,16 +100,20 @@ where
         &'g self,
         key: &K,
         guard: &'g Guard<Node<K, V>>,
-    ) -> Result<(bool, Cursor<'g, K, V>), ()> {
+    ) -> Result<(bool, Cursor<'g, K, V>), Retry> {
         // Finding phase
         // - cursor.curr: first unmarked node w/ key >= search key (4)
         // - cursor.prev: the ref of .next in previous unmarked node (1 -> 2)
         // 1 -> 2 -x-> 3 -x-> 4 -> 5 -> ∅  (search key: 4)
-        let mut cursor = Cursor::head(self.head.load(guard)?, guard)?;
+        let head = self.head.load(guard).map_err(|_| Retry::Validation)?;
+        let mut cursor = Cursor::head(head, guard)?;
         let mut prev_next = cursor.curr;
         let found = loop {
             let curr_node = some_or!(cursor.curr.as_ref(), break false);
-            let next = curr_node.next.load(Ordering::Acquire, guard)?;
+            let next = curr_node
+                .next
+                .load(Ordering::Acquire, guard)
+                .map_err(|_| Retry::Validation)?;
 
             // - finding stage is done if cursor.curr advancement stops
             // - advance cursor.curr if (.next is marked) || (cursor.curr < key)
@@ -119,7 +126,8 @@ where
                 continue;
             }
 
-            match curr_node.key.get(guard)?.cmp(key) {
+            let curr_key = curr_node.key.get(guard).map_err(|_| Retry::Validation)?;
+            match curr_key.cmp(key) {
                 Less => {
                     cursor.prev = cursor.curr;
                     cursor.curr = next;
@@ -146,7 +154,8 @@ where
                 Ordering::Relaxed,
                 guard,
             )
-            .success()?;
+            .success()
+            .map_err(|_| Retry::Unlink)?;
 
         // retire from cursor.prev.load() to cursor.curr (exclusive)
         let mut node = prev_next;
@@ -165,13 +174,17 @@ where
         &'g self,
         key: &K,
         guard: &'g Guard<Node<K, V>>,
-    ) -> Result<(bool, Cursor<'g, K, V>), ()> {
-        let mut cursor = Cursor::head(self.head.load(guard)?, guard)?;
+    ) -> Result<(bool, Cursor<'g, K, V>), Retry> {
+        let head = self.head.load(guard).map_err(|_| Retry::Validation)?;
+        let mut cursor = Cursor::head(head, guard)?;
         loop {
             debug_assert_eq!(cursor.curr.tag(), 0);
 
             let curr_node = some_or!(cursor.curr.as_ref(), return Ok((false, cursor)));
-            let mut next = curr_node.next.load(Ordering::Acquire, guard)?;
+            let mut next = curr_node
+                .next
+                .load(Ordering::Acquire, guard)
+                .map_err(|_| Retry::Validation)?;
 
             // NOTE: original version aborts here if self.prev is tagged
 
@@ -187,13 +200,15 @@ where
                         Ordering::Relaxed,
                         guard,
                     )
-                    .success()?;
+                    .success()
+                    .map_err(|_| Retry::Unlink)?;
                 unsafe { guard.retire(cursor.curr) };
                 cursor.curr = next;
                 continue;
             }
 
-            match curr_node.key.get(guard)?.cmp(key) {
+            let curr_key = curr_node.key.get(guard).map_err(|_| Retry::Validation)?;
+            match curr_key.cmp(key) {
                 Less => {
                     cursor.prev = cursor.curr;
                     cursor.curr = next;
@@ -210,12 +225,19 @@ where
         &'g self,
         key: &K,
         guard: &'g Guard<Node<K, V>>,
-    ) -> Result<(bool, Cursor<'g, K, V>), ()> {
-        let mut cursor = Cursor::head(self.head.load(guard)?, guard)?;
+    ) -> Result<(bool, Cursor<'g, K, V>), Retry> {
+        wait_free::traversal();
+        let head = self.head.load(guard).map_err(|_| Retry::Validation)?;
+        let mut cursor = Cursor::head(head, guard)?;
         Ok(loop {
             let curr_node = some_or!(cursor.curr.as_ref(), break (false, cursor));
-            let next = curr_node.next.load(Ordering::Acquire, guard)?;
-            match curr_node.key.get(guard)?.cmp(key) {
+            wait_free::visit();
+            let next = curr_node
+                .next
+                .load(Ordering::Acquire, guard)
+                .map_err(|_| Retry::Validation)?;
+            let curr_key = curr_node.key.get(guard).map_err(|_| Retry::Validation)?;
+            match curr_key.cmp(key) {
                 Less => {
                     cursor.curr = next;
                     continue;
@@ -229,11 +251,15 @@ where
     #[inline]
     fn get<F>(&self, key: &K, find: F, local: &Local<Node<K, V>>) -> Option<V>
     where
-        F: for<'g> Fn(&'g Self, &K, &'g Guard<Node<K, V>>) -> Result<(bool, Cursor<'g, K, V>), ()>,
+        F: for<'g> Fn(
+            &'g Self,
+            &K,
+            &'g Guard<Node<K, V>>,
+        ) -> Result<(bool, Cursor<'g, K, V>), Retry>,
     {
         loop {
             let guard = &local.guard();
-            let (found, cursor) = ok_or!(find(self, key, guard), continue);
+            let (found, cursor) = ok_or_retry!(find(self, key, guard), continue);
             if found {
                 if let Some(curr_ref) = cursor.curr.as_ref() {
                     let value = ok_or!(curr_ref.value.get(guard), continue);
@@ -249,11 +275,15 @@ where
     #[inline]
     fn insert<F>(&self, key: K, value: V, find: F, local: &Local<Node<K, V>>) -> bool
     where
-        F: for<'g> Fn(&'g Self, &K, &'g Guard<Node<K, V>>) -> Result<(bool, Cursor<'g, K, V>), ()>,
+        F: for<'g> Fn(
+            &'g Self,
+            &K,
+            &'g Guard<Node<K, V>>,
+        ) -> Result<(bool, Cursor<'g, K, V>), Retry>,
     {
         loop {
             let guard = &local.guard();
-            let (found, cursor) = ok_or!(find(self, &key, guard), continue);
+            let (found, cursor) = ok_or_retry!(find(self, &key, guard), continue);
             if found {
                 return false;
             }
@@ -275,7 +305,10 @@ where
                 guard,
             ) {
                 Success(_) => return true,
-                _ => unsafe { guard.retire(node) },
+                _ => {
+                    Retry::CasFail.count();
+                    unsafe { guard.retire(node) };
+                }
             }
         }
     }
@@ -283,11 +316,15 @@ where
     #[inline]
     fn remove<F>(&self, key: &K, find: F, local: &Local<Node<K, V>>) -> Option<V>
     where
-        F: for<'g> Fn(&'g Self, &K, &'g Guard<Node<K, V>>) -> Result<(bool, Cursor<'g, K, V>), ()>,
+        F: for<'g> Fn(
+            &'g Self,
+            &K,
+            &'g Guard<Node<K, V>>,
+        ) -> Result<(bool, Cursor<'g, K, V>), Retry>,
     {
         loop {
             let guard = &mut local.guard();
-            let (found, cursor) = ok_or!(find(self, key, guard), continue);
+            let (found, cursor) = ok_or_retry!(find(self, key, guard), continue);
             if !found {
                 return None;
             }
@@ -297,6 +334,7 @@ where
 
             let next = ok_or!(curr_node.next.load(Ordering::Acquire, guard), continue);
             if next.tag() == 1 {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -313,6 +351,7 @@ where
                 .success()
                 .is_err()
             {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -341,7 +380,7 @@ where
         loop {
             let guard = &local.guard();
             let head = ok_or!(self.head.load(guard), continue);
-            let cursor = ok_or!(Cursor::head(head, guard), continue);
+            let cursor = ok_or_retry!(Cursor::head(head, guard), continue);
             if cursor.curr.is_null() {
                 return None;
             }
@@ -352,6 +391,7 @@ where
 
             let next = ok_or!(curr_node.next.load(Ordering::Acquire, guard), continue);
             if next.tag() == 1 {
+                Retry::CasFail.count();
                 continue;
             }
             if curr_node
@@ -367,6 +407,7 @@ where
                 .success()
                 .is_err()
             {
+                Retry::CasFail.count();
                 continue;
             }
 
@@ -583,16 +624,46 @@ mod tests {
         concurrent_map::tests::smoke::<HList<i32, i32>>();
     }
 
+    #[test]
+    fn edge_cases_h_list() {
+        concurrent_map::tests::edge_cases::<HList<i32, i32>>();
+    }
+
     #[test]
     fn smoke_hm_list() {
         concurrent_map::tests::smoke::<HMList<i32, i32>>();
     }
 
+    #[test]
+    fn edge_cases_hm_list() {
+        concurrent_map::tests::edge_cases::<HMList<i32, i32>>();
+    }
+
     #[test]
     fn smoke_hhs_list() {
         concurrent_map::tests::smoke::<HHSList<i32, i32>>();
     }
 
+    #[test]
+    fn edge_cases_hhs_list() {
+        concurrent_map::tests::edge_cases::<HHSList<i32, i32>>();
+    }
+
+    #[test]
+    fn wait_free_get_hhs_list() {
+        use crate::ds_impl::wait_free::{check_get, Op};
+        use concurrent_map::ConcurrentMap;
+        let global = &HHSList::<i32, i32>::global(1000);
+        let local = &HHSList::<i32, i32>::local(global);
+        let map = HHSList::<i32, i32>::new(local);
+
+        check_get(|op, key| match op {
+            Op::Insert => map.insert(key, key, local),
+            Op::Remove => map.remove(&key, local).is_some(),
+            Op::Get => map.get(&key, local).is_some(),
+        });
+    }
+
     #[test]
     fn litmus_hhs_pop() {
         use concurrent_map::ConcurrentMap;
@@ -100,4 +100,9 @@ mod tests {
     fn smoke_hashmap() {
         concurrent_map::tests::smoke::<HashMap<i32, i32>>();
     }
+
+    #[test]
+    fn edge_cases_hashmap() {
+        concurrent_map::tests::edge_cases::<HashMap<i32, i32>>();
+    }
 }
@@ -3,6 +3,7 @@ use vbr::CompareExchangeError::*;
 use vbr::{ptr_with_tag, Entry, Global, Guard, ImmAtomic, Local, MutAtomic, Shared};
 
 use super::concurrent_map::ConcurrentMap;
+use crate::ds_impl::helping::InsertConflict;
 use std::cmp;
 use std::mem::zeroed;
 use std::sync::atomic::Ordering;
@@ -280,6 +281,17 @@ where
         // Identify the node(subtree) that will replace `successor`.
         let leaf_marked = record.leaf_addr().load(Ordering::Acquire, guard)?;
         let leaf_flag = Marks::from_bits_truncate(leaf_marked.tag()).flag();
+        // A cleanup is only started for a parent with a flagged edge, and a flag is never cleared.
+        // A failed validation means that the parent has been reclaimed, which the CASes detect.
+        debug_assert!(
+            leaf_flag
+                || record
+                    .leaf_sibling_addr()
+                    .load(Ordering::Acquire, guard)
+                    .map_or(true, |sibling| Marks::from_bits_truncate(sibling.tag())
+                        .flag()),
+            "cleanup of a parent without a flagged edge"
+        );
         let target_sibling_addr = if leaf_flag {
             record.leaf_sibling_addr()
         } else {
@@ -310,7 +322,18 @@ where
         // Since (parent, sibling) might have been concurrently flagged, copy
         // the flag to the new edge (ancestor, sibling).
         let target_sibling = target_sibling_addr.load(Ordering::Acquire, guard)?;
+        // A tagged edge is never the target of a CAS, so its tag cannot have been cleared.
+        debug_assert!(
+            Marks::from_bits_truncate(target_sibling.tag()).tag(),
+            "the tag of the (parent, sibling) edge was cleared"
+        );
         let flag = Marks::from_bits_truncate(target_sibling.tag()).flag();
+        // The (ancestor, successor) edge is the last untagged edge on the access path.
+        debug_assert_eq!(
+            record.successor.tag(),
+            0,
+            "the (ancestor, successor) edge is expected unmarked"
+        );
         let is_unlinked = record
             .successor_addr()
             .compare_exchange(
@@ -416,7 +439,10 @@ where
                     // Insertion failed. Help the conflicting remove operation if needed.
                     // NOTE: The paper version checks if any of the mark is set, which is redundant.
                     if e.1 == record.leaf.as_raw() {
+                        InsertConflict::Help.count();
                         self.cleanup(&record, guard)?;
+                    } else {
+                        InsertConflict::Retry.count();
                     }
                     unsafe {
                         guard.retire(new_leaf);
@@ -424,6 +450,7 @@ where
                     }
                 }
                 Reallocated => unsafe {
+                    InsertConflict::Retry.count();
                     guard.retire(new_leaf);
                     guard.retire(new_internal);
                 },
@@ -457,6 +484,7 @@ where
             let value = leaf_node.value.get(guard)?;
 
             // Try injecting the deletion flag.
+            debug_assert_eq!(record.leaf.tag(), 0, "the leaf edge is expected unmarked");
             match record.leaf_addr().compare_exchange(
                 record.parent,
                 record.leaf,
@@ -557,4 +585,9 @@ mod tests {
     fn smoke_nm_tree() {
         concurrent_map::tests::smoke::<NMTreeMap<i32, i32>>();
     }
+
+    #[test]
+    fn edge_cases_nm_tree() {
+        concurrent_map::tests::edge_cases::<NMTreeMap<i32, i32>>();
+    }
 }
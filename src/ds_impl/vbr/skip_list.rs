@@ -460,4 +460,9 @@ mod tests {
     fn smoke_skip_list() {
         concurrent_map::tests::smoke::<SkipList<i32, i32>>();
     }
+
+    #[test]
+    fn edge_cases_skip_list() {
+        concurrent_map::tests::edge_cases::<SkipList<i32, i32>>();
+    }
 }
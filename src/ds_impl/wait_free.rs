@@ -0,0 +1,77 @@
+//! Instrumentation of the wait-free `get` of `hhs-list`.
+//!
+//! The get of the Harris-Herlihy-Shavit list traverses the list once, without a CAS or a restart,
+//! and visits only the nodes up to the first one whose key is not smaller than its own. Each port
+//! counts its traversals and the iterations of its traversal loop, so that its tests check the
+//! claim against the position of the key in the list, instead of trusting the comment on the
+//! traversal. A port that has to validate its protection may still restart under contention, but
+//! not in a list that no other thread changes. The counts are kept only in the test builds.
+
+#[cfg(test)]
+use std::cell::Cell;
+
+#[cfg(test)]
+thread_local! {
+    static COUNTS: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+/// Counts the start of a traversal of a get.
+#[inline(always)]
+pub fn traversal() {
+    #[cfg(test)]
+    COUNTS.with(|counts| {
+        let (traversals, visits) = counts.get();
+        counts.set((traversals + 1, visits));
+    });
+}
+
+/// Counts an iteration of the traversal loop of a get, at a node.
+#[inline(always)]
+pub fn visit() {
+    #[cfg(test)]
+    COUNTS.with(|counts| {
+        let (traversals, visits) = counts.get();
+        counts.set((traversals, visits + 1));
+    });
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Insert,
+    Remove,
+    Get,
+}
+
+/// Fills a list with the even keys through `op`, which runs an operation on the list and returns
+/// whether it succeeded, removes every third of them, and checks that the get of each key, present
+/// or not, runs a single traversal that visits exactly the nodes up to its key.
+#[cfg(test)]
+pub fn check_get<F: FnMut(Op, i32) -> bool>(mut op: F) {
+    const KEYS: i32 = 64;
+
+    for key in (1..=KEYS).rev() {
+        assert!(op(Op::Insert, 2 * key));
+    }
+    for key in (1..=KEYS).step_by(3) {
+        assert!(op(Op::Remove, 2 * key));
+    }
+    let live = (1..=KEYS)
+        .filter(|key| (key - 1) % 3 != 0)
+        .map(|key| 2 * key)
+        .collect::<Vec<_>>();
+
+    for key in 0..=2 * KEYS + 1 {
+        let smaller = live.partition_point(|&live| live < key);
+        COUNTS.with(|counts| counts.set((0, 0)));
+        let found = op(Op::Get, key);
+        let (traversals, visits) = COUNTS.with(Cell::get);
+        assert_eq!(found, live.binary_search(&key).is_ok(), "get of {key}");
+        assert_eq!(traversals, 1, "get of {key} restarted");
+        assert_eq!(
+            visits,
+            smaller + usize::from(smaller < live.len()),
+            "get of {key} visited a wrong number of nodes"
+        );
+    }
+}
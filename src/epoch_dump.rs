@@ -0,0 +1,164 @@
+//! A coarse timeline of the epochs of EBR.
+//!
+//! `crossbeam-ebr` exposes neither its global epoch nor the epochs that the threads are pinned at,
+//! so the benchmark keeps a shadow of them. A thread publishes the shadow epoch that it observes
+//! whenever it pins, and the sampler advances the shadow epoch once every pinned thread has
+//! observed the current one, which is the condition on which EBR advances its global epoch. The
+//! shadow epoch lags behind the real one, but it stagnates whenever the real one does, and the
+//! thread that is pinned at an old epoch tells which thread stalls the reclamation.
+//!
+//! Each sample also records the number of operations done so far and the garbage of EBR, so that
+//! the stagnation can be correlated with the throughput and the memory usage over time.
+
+use crossbeam_utils::CachePadded;
+use csv::Writer;
+use std::fs::File;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The pinned word of a thread that is not pinned. A pinned thread stores its epoch shifted left
+/// by one, with the lowest bit set.
+const UNPINNED: u64 = 0;
+
+#[derive(Default)]
+struct Local {
+    pinned: AtomicU64,
+    ops: AtomicU64,
+}
+
+pub struct EpochDump {
+    epoch: CachePadded<AtomicU64>,
+    locals: Box<[CachePadded<Local>]>,
+}
+
+impl EpochDump {
+    /// Creates a dump of `threads` threads, which are unpinned.
+    pub fn new(threads: usize) -> Self {
+        Self {
+            epoch: CachePadded::new(AtomicU64::new(0)),
+            locals: (0..threads).map(|_| CachePadded::default()).collect(),
+        }
+    }
+
+    /// Publishes that the thread `index` has pinned after `ops` operations.
+    #[inline]
+    pub fn pin(&self, index: usize, ops: u64) {
+        let local = &self.locals[index];
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        local.pinned.store((epoch << 1) | 1, Ordering::Relaxed);
+        local.ops.store(ops, Ordering::Relaxed);
+    }
+
+    /// Publishes that the thread `index` has unpinned.
+    #[inline]
+    pub fn unpin(&self, index: usize) {
+        self.locals[index].pinned.store(UNPINNED, Ordering::Relaxed);
+    }
+
+    /// The epochs that the threads are pinned at, or `None` for the unpinned ones.
+    fn pinned(&self) -> impl Iterator<Item = Option<u64>> + '_ {
+        self.locals.iter().map(|local| {
+            let pinned = local.pinned.load(Ordering::Relaxed);
+            (pinned != UNPINNED).then_some(pinned >> 1)
+        })
+    }
+
+    /// Advances the shadow epoch if every pinned thread has observed it, and returns whether it
+    /// has advanced.
+    fn try_advance(&self) -> bool {
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        if self.pinned().flatten().any(|pinned| pinned != epoch) {
+            return false;
+        }
+        self.epoch.store(epoch + 1, Ordering::Relaxed);
+        true
+    }
+}
+
+/// Writes the samples of an [`EpochDump`] to a CSV file, one row per sample.
+///
+/// A row has the time since the start of the measurement, the shadow epoch, the time since the
+/// shadow epoch last advanced, the total number of operations, the garbage of EBR, and a column
+/// per thread with the epoch that the thread is pinned at, which is empty if it is unpinned.
+pub struct EpochTimeline<'d> {
+    dump: &'d EpochDump,
+    writer: Writer<File>,
+    advanced_at: Duration,
+}
+
+impl<'d> EpochTimeline<'d> {
+    /// Creates the file at `path` and writes the header. `names` are the names of the threads.
+    pub fn create<S: AsRef<str>>(dump: &'d EpochDump, path: &str, names: &[S]) -> Self {
+        assert_eq!(names.len(), dump.locals.len());
+        let mut writer = Writer::from_path(path).unwrap();
+        let header = ["time_ms", "epoch", "stalled_ms", "ops", "garbage"];
+        writer
+            .write_record(
+                header
+                    .iter()
+                    .copied()
+                    .chain(names.iter().map(AsRef::as_ref)),
+            )
+            .unwrap();
+        Self {
+            dump,
+            writer,
+            advanced_at: Duration::ZERO,
+        }
+    }
+
+    /// Advances the shadow epoch if possible, and writes a row for the time `now` since the
+    /// start of the measurement.
+    pub fn sample(&mut self, now: Duration, garbage: usize) {
+        if self.dump.try_advance() {
+            self.advanced_at = now;
+        }
+        let ops: u64 = self
+            .dump
+            .locals
+            .iter()
+            .map(|local| local.ops.load(Ordering::Relaxed))
+            .sum();
+        let row = [
+            now.as_millis().to_string(),
+            self.dump.epoch.load(Ordering::Relaxed).to_string(),
+            (now - self.advanced_at).as_millis().to_string(),
+            ops.to_string(),
+            garbage.to_string(),
+        ];
+        let pinned = self
+            .dump
+            .pinned()
+            .map(|pinned| pinned.map(|epoch| epoch.to_string()).unwrap_or_default());
+        self.writer
+            .write_record(row.into_iter().chain(pinned))
+            .unwrap();
+    }
+
+    /// Flushes the rows to the file.
+    pub fn finish(mut self) {
+        self.writer.flush().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EpochDump;
+
+    #[test]
+    fn epoch_advances_past_pinned_threads() {
+        let dump = EpochDump::new(2);
+        assert!(dump.try_advance());
+        dump.pin(0, 0);
+        dump.pin(1, 0);
+        assert!(dump.try_advance());
+        // Both threads are pinned at the previous epoch.
+        assert!(!dump.try_advance());
+
+        dump.pin(0, 10);
+        assert!(!dump.try_advance());
+        dump.unpin(1);
+        assert!(dump.try_advance());
+        assert_eq!(dump.pinned().collect::<Vec<_>>(), [Some(2), None]);
+    }
+}
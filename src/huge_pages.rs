@@ -0,0 +1,113 @@
+//! Transparent huge pages (THP) for the memory of the benchmark.
+//!
+//! Whether the kernel backs the nodes with huge pages changes the cost of the TLB misses of every
+//! traversal, and without `--thp` it silently follows the policy of the machine in
+//! `/sys/kernel/mm/transparent_hugepage/enabled`. With `never`, the process opts out of THP
+//! altogether. With `always`, an arena that a structure reserves for itself is advised with
+//! `MADV_HUGEPAGE` before it is touched, and the anonymous mappings of the heap are advised between
+//! the phases of a benchmark, i.e., after the prefill. The pages of the heap that are populated by
+//! then only become huge once `khugepaged` collapses them, and the mappings that the heap creates
+//! during a measurement are not advised.
+
+use clap::ValueEnum;
+use std::fs;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum HugePages {
+    /// Follows the policy of the machine.
+    Default,
+    /// Advises the memory of the structures to be backed by huge pages.
+    Always,
+    /// Disables the huge pages for the whole process.
+    Never,
+}
+
+/// The mode that the process runs with, for the arenas that are reserved after the setup.
+static MODE: AtomicU8 = AtomicU8::new(HugePages::Default as u8);
+
+#[cfg(target_os = "linux")]
+fn mode() -> HugePages {
+    match MODE.load(Ordering::Relaxed) {
+        m if m == HugePages::Always as u8 => HugePages::Always,
+        m if m == HugePages::Never as u8 => HugePages::Never,
+        _ => HugePages::Default,
+    }
+}
+
+impl HugePages {
+    /// Makes the process run with this mode.
+    pub fn apply(self) {
+        MODE.store(self as u8, Ordering::Relaxed);
+        #[cfg(target_os = "linux")]
+        if self == HugePages::Never {
+            let ret = unsafe { libc::prctl(libc::PR_SET_THP_DISABLE, 1, 0, 0, 0) };
+            assert_eq!(ret, 0, "Failed to disable the transparent huge pages");
+        }
+    }
+
+    /// The mode for the results, which is the policy of the machine for `default`.
+    pub fn describe(self) -> String {
+        match self {
+            HugePages::Default => {
+                format!(
+                    "default:{}",
+                    system_policy().unwrap_or_else(|| "unknown".to_string())
+                )
+            }
+            HugePages::Always => "always".to_string(),
+            HugePages::Never => "never".to_string(),
+        }
+    }
+}
+
+/// Reads the THP policy of the machine, which is the bracketed one of, e.g.,
+/// `always [madvise] never`.
+pub fn system_policy() -> Option<String> {
+    let enabled = fs::read_to_string("/sys/kernel/mm/transparent_hugepage/enabled").ok()?;
+    let begin = enabled.find('[')? + 1;
+    let end = begin + enabled[begin..].find(']')?;
+    Some(enabled[begin..end].to_string())
+}
+
+/// Advises the page-aligned region of `len` bytes at `addr`, which a structure has reserved for
+/// itself, with the mode of the process.
+#[cfg(target_os = "linux")]
+pub fn advise_region(addr: *mut libc::c_void, len: usize) {
+    if mode() == HugePages::Always {
+        // The advice is only a hint, so a kernel without THP is not an error.
+        unsafe { libc::madvise(addr, len, libc::MADV_HUGEPAGE) };
+    }
+}
+
+/// Advises the anonymous mappings of the process, which hold the heap, with the `always` mode.
+pub fn advise_heap() {
+    #[cfg(target_os = "linux")]
+    if mode() == HugePages::Always {
+        let Ok(maps) = fs::read_to_string("/proc/self/maps") else {
+            return;
+        };
+        for line in maps.lines() {
+            // E.g., `7f3a00000000-7f3a00200000 rw-p 00000000 00:00 0`, where an anonymous mapping
+            // has no path after the inode, except for the `brk` heap.
+            let mut fields = line.split_whitespace();
+            let (Some(range), Some(perms)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let path = fields.nth(3);
+            if perms != "rw-p" || path.is_some_and(|path| path != "[heap]") {
+                continue;
+            }
+            let Some((begin, end)) = range.split_once('-') else {
+                continue;
+            };
+            let (Ok(begin), Ok(end)) = (
+                usize::from_str_radix(begin, 16),
+                usize::from_str_radix(end, 16),
+            ) else {
+                continue;
+            };
+            unsafe { libc::madvise(begin as *mut _, end - begin, libc::MADV_HUGEPAGE) };
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! A histogram of the latencies of operations.
+//!
+//! A latency is counted in a bucket of its power of two, which is split into 16 linear
+//! sub-buckets, so that a percentile is within 1/16 of the exact latency from a few kilobytes,
+//! however long the run is. Recording a latency is an increment without allocation once the
+//! histogram has seen its first latency, so that a thread records each of its operations.
+
+use std::time::Duration;
+
+const SUB_BITS: u32 = 4;
+const SUBS: usize = 1 << SUB_BITS;
+const BUCKETS: usize = (u64::BITS - SUB_BITS + 1) as usize * SUBS;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    counts: Vec<u64>,
+    total: u64,
+}
+
+/// The bucket of a latency of `nanos`.
+fn bucket(nanos: u64) -> usize {
+    if nanos < SUBS as u64 {
+        return nanos as usize;
+    }
+    let shift = u64::BITS - 1 - nanos.leading_zeros() - SUB_BITS;
+    ((shift as usize + 1) << SUB_BITS) + ((nanos >> shift) as usize & (SUBS - 1))
+}
+
+/// The smallest latency of a bucket, in nanoseconds.
+fn lower_bound(bucket: usize) -> u64 {
+    if bucket < SUBS {
+        return bucket as u64;
+    }
+    let shift = (bucket >> SUB_BITS) - 1;
+    ((SUBS + (bucket & (SUBS - 1))) as u64) << shift
+}
+
+impl Histogram {
+    pub const fn new() -> Self {
+        Self {
+            counts: Vec::new(),
+            total: 0,
+        }
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        if self.counts.is_empty() {
+            self.counts = vec![0; BUCKETS];
+        }
+        self.counts[bucket(latency.as_nanos().min(u64::MAX as u128) as u64)] += 1;
+        self.total += 1;
+    }
+
+    /// Adds the latencies of `other`.
+    pub fn merge(&mut self, other: &Histogram) {
+        if other.total == 0 {
+            return;
+        }
+        if self.counts.is_empty() {
+            self.counts = vec![0; BUCKETS];
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.total += other.total;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    /// The latency under which a `q` fraction of the latencies are, rounded up to the end of its
+    /// bucket, or zero if there is none.
+    pub fn percentile(&self, q: f64) -> Duration {
+        if self.total == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((q * self.total as f64).ceil() as u64).clamp(1, self.total);
+        let mut seen = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let end = if bucket + 1 < BUCKETS {
+                    lower_bound(bucket + 1) - 1
+                } else {
+                    u64::MAX
+                };
+                return Duration::from_nanos(end);
+            }
+        }
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bucket, lower_bound, Histogram, BUCKETS};
+    use std::time::Duration;
+
+    #[test]
+    fn buckets() {
+        for nanos in (0..100_000).chain([u64::MAX / 3, u64::MAX]) {
+            let b = bucket(nanos);
+            assert!(b < BUCKETS);
+            assert!(lower_bound(b) <= nanos, "{nanos} below bucket {b}");
+            if b + 1 < BUCKETS {
+                assert!(nanos < lower_bound(b + 1), "{nanos} above bucket {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn percentiles() {
+        let mut histogram = Histogram::default();
+        assert_eq!(histogram.percentile(0.99), Duration::ZERO);
+        for micros in 1..=1000 {
+            histogram.record(Duration::from_micros(micros));
+        }
+        let mut other = Histogram::default();
+        other.record(Duration::from_secs(1));
+        histogram.merge(&other);
+        assert_eq!(histogram.count(), 1001);
+
+        let close = |latency: Duration, micros: f64| {
+            let ratio = latency.as_secs_f64() * 1e6 / micros;
+            (1.0..1.0 + 1.0 / 16.0).contains(&ratio)
+        };
+        assert!(close(histogram.percentile(0.5), 501.0));
+        assert!(close(histogram.percentile(0.99), 991.0));
+        assert!(close(histogram.percentile(1.0), 1e6));
+    }
+}
@@ -0,0 +1,262 @@
+//! The sizes and alignments of the nodes of each backend, as they are allocated.
+//!
+//! A node is allocated together with the header of its scheme: `circ` and `cdrc` put their
+//! reference counts next to the node, and VBR its birth and retire epochs, while the other schemes
+//! allocate the bare node in a `Box`. jemalloc rounds each allocation up to its size class, so a
+//! node whose class is not a divisor or a multiple of the cache line lands across two lines at
+//! some addresses, and every traversal that touches it misses twice. VBR boxes its nodes too, but
+//! allocates them in bags in advance, so that the nodes of a bag lie next to each other.
+//!
+//! `--print-layout` prints the table, and the test below fails once a node grows beyond the budget
+//! of its data structure, or once a node that is meant to fit in a line may straddle two.
+
+use std::fmt;
+use std::mem::{align_of, size_of};
+
+use ::cdrc::{Counted, CsEBR as CdrcEBR};
+use ::vbr::Inner;
+use circ::RcInner;
+
+use crate::ds_impl::{self, circ_ebr, circ_hp, ebr, hp, hp_brcu, hp_pp, nbr, nr, pebr};
+
+const CACHE_LINE: usize = 64;
+
+#[derive(Debug, Clone, Copy)]
+pub struct NodeLayout {
+    pub backend: &'static str,
+    pub structure: &'static str,
+    /// The size and the alignment of the allocation, including the header of the scheme.
+    pub size: usize,
+    pub align: usize,
+    /// The size class of the allocation, which is the distance between two neighbouring nodes.
+    pub stride: usize,
+    /// Whether the node is meant to fit in a single cache line.
+    pub fits_line: bool,
+}
+
+/// The jemalloc size class that an allocation of `size` bytes is rounded up to.
+fn size_class(size: usize) -> usize {
+    if size <= 16 {
+        size.next_multiple_of(8).max(8)
+    } else if size <= 128 {
+        size.next_multiple_of(16)
+    } else {
+        size.next_multiple_of(size.next_power_of_two() / 8)
+    }
+}
+
+impl NodeLayout {
+    fn boxed<T>(backend: &'static str, structure: &'static str, fits_line: bool) -> Self {
+        Self {
+            backend,
+            structure,
+            size: size_of::<T>(),
+            align: align_of::<T>(),
+            stride: size_class(size_of::<T>()),
+            fits_line,
+        }
+    }
+
+    /// The most cache lines that a node touches, over the positions of a node in a run of nodes.
+    pub fn worst_lines(&self) -> usize {
+        (0..CACHE_LINE)
+            .map(|i| {
+                let start = i * self.stride;
+                (start + self.size - 1) / CACHE_LINE - start / CACHE_LINE + 1
+            })
+            .max()
+            .unwrap()
+    }
+
+    /// Whether some node touches more cache lines than its size needs.
+    pub fn straddles(&self) -> bool {
+        self.worst_lines() > self.size.div_ceil(CACHE_LINE)
+    }
+
+    /// The largest size that a node of the data structure may have.
+    pub fn budget(&self) -> usize {
+        match self.structure {
+            "list" => 64,
+            "skip-list" | "elim-ab-tree" => 1024,
+            _ => 128,
+        }
+    }
+}
+
+impl fmt::Display for NodeLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<9} {:<13} size {:>4}, align {:>2}, stride {:>4}, {} line(s) at worst, budget {:>4}{}",
+            self.backend,
+            self.structure,
+            self.size,
+            self.align,
+            self.stride,
+            self.worst_lines(),
+            self.budget(),
+            if self.straddles() { ", straddles" } else { "" }
+        )
+    }
+}
+
+type K = usize;
+type V = usize;
+
+/// Returns the layouts of the nodes of every backend, keyed and valued by `usize`.
+pub fn node_layouts() -> Vec<NodeLayout> {
+    vec![
+        NodeLayout::boxed::<nr::list::Node<K, V>>("nr", "list", true),
+        NodeLayout::boxed::<nr::natarajan_mittal_tree::Node<K, V>>("nr", "nm-tree", false),
+        NodeLayout::boxed::<nr::skip_list::Node<K, V>>("nr", "skip-list", false),
+        NodeLayout::boxed::<nr::bonsai_tree::Node<K, V>>("nr", "bonsai-tree", false),
+        NodeLayout::boxed::<nr::ellen_tree::Node<K, V>>("nr", "efrb-tree", false),
+        NodeLayout::boxed::<nr::elim_ab_tree::Node<K, V>>("nr", "elim-ab-tree", false),
+        NodeLayout::boxed::<nr::double_link::Node<V>>("nr", "double-link", false),
+        NodeLayout::boxed::<ebr::list::Node<K, V>>("ebr", "list", true),
+        NodeLayout::boxed::<ebr::natarajan_mittal_tree::Node<K, V>>("ebr", "nm-tree", false),
+        NodeLayout::boxed::<ebr::skip_list::Node<K, V>>("ebr", "skip-list", false),
+        NodeLayout::boxed::<ebr::bonsai_tree::Node<K, V>>("ebr", "bonsai-tree", false),
+        NodeLayout::boxed::<ebr::ellen_tree::Node<K, V>>("ebr", "efrb-tree", false),
+        NodeLayout::boxed::<ebr::elim_ab_tree::Node<K, V>>("ebr", "elim-ab-tree", false),
+        NodeLayout::boxed::<ebr::wait_free_treap::Node<K, V>>("ebr", "wf-treap", false),
+        NodeLayout::boxed::<ebr::double_link::Node<V>>("ebr", "double-link", false),
+        NodeLayout::boxed::<pebr::list::Node<K, V>>("pebr", "list", true),
+        NodeLayout::boxed::<pebr::natarajan_mittal_tree::Node<K, V>>("pebr", "nm-tree", false),
+        NodeLayout::boxed::<pebr::skip_list::Node<K, V>>("pebr", "skip-list", false),
+        NodeLayout::boxed::<pebr::bonsai_tree::Node<K, V>>("pebr", "bonsai-tree", false),
+        NodeLayout::boxed::<pebr::ellen_tree::Node<K, V>>("pebr", "efrb-tree", false),
+        NodeLayout::boxed::<pebr::elim_ab_tree::Node<K, V>>("pebr", "elim-ab-tree", false),
+        NodeLayout::boxed::<hp::list::Node<K, V>>("hp", "list", true),
+        NodeLayout::boxed::<hp::natarajan_mittal_tree::Node<K, V>>("hp", "nm-tree", false),
+        NodeLayout::boxed::<hp::skip_list::Node<K, V>>("hp", "skip-list", false),
+        NodeLayout::boxed::<hp::bonsai_tree::Node<K, V>>("hp", "bonsai-tree", false),
+        NodeLayout::boxed::<hp::ellen_tree::Node<K, V>>("hp", "efrb-tree", false),
+        NodeLayout::boxed::<hp::elim_ab_tree::Node<K, V>>("hp", "elim-ab-tree", false),
+        NodeLayout::boxed::<hp::double_link::Node<V>>("hp", "double-link", false),
+        NodeLayout::boxed::<hp_pp::list::Node<K, V>>("hp-pp", "list", true),
+        NodeLayout::boxed::<hp_pp::natarajan_mittal_tree::Node<K, V>>("hp-pp", "nm-tree", false),
+        NodeLayout::boxed::<hp_pp::skip_list::Node<K, V>>("hp-pp", "skip-list", false),
+        NodeLayout::boxed::<hp_pp::bonsai_tree::Node<K, V>>("hp-pp", "bonsai-tree", false),
+        NodeLayout::boxed::<hp_pp::ellen_tree::Node<K, V>>("hp-pp", "efrb-tree", false),
+        NodeLayout::boxed::<hp_brcu::list::Node<K, V>>("hp-brcu", "list", true),
+        NodeLayout::boxed::<hp_brcu::natarajan_mittal_tree::Node<K, V>>(
+            "hp-brcu", "nm-tree", false,
+        ),
+        NodeLayout::boxed::<hp_brcu::skip_list::Node<K, V>>("hp-brcu", "skip-list", false),
+        NodeLayout::boxed::<hp_brcu::bonsai_tree::Node<K, V>>("hp-brcu", "bonsai-tree", false),
+        NodeLayout::boxed::<hp_brcu::elim_ab_tree::Node<K, V>>("hp-brcu", "elim-ab-tree", false),
+        NodeLayout::boxed::<nbr::list::Node<K, V>>("nbr", "list", true),
+        NodeLayout::boxed::<nbr::natarajan_mittal_tree::Node<K, V>>("nbr", "nm-tree", false),
+        // The header of VBR makes a list node 48 bytes long, which is a size class of its own and
+        // straddles two lines at every other address.
+        NodeLayout::boxed::<Inner<ds_impl::vbr::list::Node<K, V>>>("vbr", "list", false),
+        NodeLayout::boxed::<Inner<ds_impl::vbr::natarajan_mittal_tree::Node<K, V>>>(
+            "vbr", "nm-tree", false,
+        ),
+        NodeLayout::boxed::<Inner<ds_impl::vbr::skip_list::Node<K, V>>>("vbr", "skip-list", false),
+        NodeLayout::boxed::<Inner<ds_impl::vbr::elim_ab_tree::Node<K, V>>>(
+            "vbr",
+            "elim-ab-tree",
+            false,
+        ),
+        NodeLayout::boxed::<Counted<ds_impl::cdrc::list::Node<K, V, CdrcEBR>>>(
+            "cdrc", "list", true,
+        ),
+        NodeLayout::boxed::<Counted<ds_impl::cdrc::natarajan_mittal_tree::Node<K, V, CdrcEBR>>>(
+            "cdrc", "nm-tree", false,
+        ),
+        NodeLayout::boxed::<Counted<ds_impl::cdrc::skip_list::Node<K, V, CdrcEBR>>>(
+            "cdrc",
+            "skip-list",
+            false,
+        ),
+        NodeLayout::boxed::<Counted<ds_impl::cdrc::bonsai_tree::Node<K, V, CdrcEBR>>>(
+            "cdrc",
+            "bonsai-tree",
+            false,
+        ),
+        NodeLayout::boxed::<Counted<ds_impl::cdrc::elim_ab_tree::Node<K, V, CdrcEBR>>>(
+            "cdrc",
+            "elim-ab-tree",
+            false,
+        ),
+        NodeLayout::boxed::<Counted<ds_impl::cdrc::double_link::Node<V, CdrcEBR>>>(
+            "cdrc",
+            "double-link",
+            false,
+        ),
+        NodeLayout::boxed::<RcInner<circ_ebr::list::Node<K, V>>>("circ-ebr", "list", true),
+        NodeLayout::boxed::<RcInner<circ_ebr::natarajan_mittal_tree::Node<K, V>>>(
+            "circ-ebr", "nm-tree", false,
+        ),
+        NodeLayout::boxed::<RcInner<circ_ebr::skip_list::Node<K, V>>>(
+            "circ-ebr",
+            "skip-list",
+            false,
+        ),
+        NodeLayout::boxed::<RcInner<circ_ebr::bonsai_tree::Node<K, V>>>(
+            "circ-ebr",
+            "bonsai-tree",
+            false,
+        ),
+        NodeLayout::boxed::<RcInner<circ_ebr::double_link::Node<V>>>(
+            "circ-ebr",
+            "double-link",
+            false,
+        ),
+        NodeLayout::boxed::<RcInner<circ_hp::list::Node<K, V>>>("circ-hp", "list", true),
+        NodeLayout::boxed::<RcInner<circ_hp::natarajan_mittal_tree::Node<K, V>>>(
+            "circ-hp", "nm-tree", false,
+        ),
+        NodeLayout::boxed::<RcInner<circ_hp::skip_list::Node<K, V>>>("circ-hp", "skip-list", false),
+        NodeLayout::boxed::<RcInner<circ_hp::bonsai_tree::Node<K, V>>>(
+            "circ-hp",
+            "bonsai-tree",
+            false,
+        ),
+        NodeLayout::boxed::<RcInner<circ_hp::elim_ab_tree::Node<K, V>>>(
+            "circ-hp",
+            "elim-ab-tree",
+            false,
+        ),
+        NodeLayout::boxed::<RcInner<circ_hp::double_link::Node<V>>>(
+            "circ-hp",
+            "double-link",
+            false,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{node_layouts, size_class, NodeLayout};
+
+    #[test]
+    fn size_classes() {
+        assert_eq!(size_class(1), 8);
+        assert_eq!(size_class(24), 32);
+        assert_eq!(size_class(40), 48);
+        assert_eq!(size_class(129), 160);
+        assert_eq!(size_class(200), 224);
+        assert_eq!(size_class(300), 320);
+        let layout = NodeLayout::boxed::<[u8; 40]>("", "", true);
+        assert_eq!(layout.worst_lines(), 2);
+        assert!(layout.straddles());
+    }
+
+    #[test]
+    fn node_layouts_fit_their_budgets() {
+        let mut failures = Vec::new();
+        for layout in node_layouts() {
+            println!("{layout}");
+            if layout.size > layout.budget() {
+                failures.push(format!("{layout}: over the budget"));
+            }
+            if layout.fits_line && layout.straddles() {
+                failures.push(format!("{layout}: crosses a cache line"));
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,183 @@
+//! A leaky but counting mode for every scheme.
+//!
+//! With `--leak`, the global allocator skips every free and only counts it, so that a scheme still
+//! does all of its protection work, i.e., the fences, the announcements, the epochs, and the scans
+//! that decide what to reclaim, but none of the frees and the allocator work that they cause. The
+//! throughput difference between a run with `--leak` and one without tells the cost of the frees
+//! of the scheme apart from the cost of its protection, and NR gives the cost of neither.
+//!
+//! The mode is global to the process because the external schemes free their nodes without a hook,
+//! so the counts also include the few frees of the benchmark itself, e.g., of a node whose insertion
+//! failed. Only the frees of the benchmark threads during their measurement are reported. A
+//! reallocation is not a free of a node, and goes to the allocator as usual. The allocator also reports
+//! each free to the checker of `reclamation`, whether it is skipped or not, and with the `alloc-tags`
+//! feature, each allocation and free to the counts of `alloc_tags`.
+
+#[cfg(feature = "alloc-tags")]
+use crate::alloc_tags;
+use crate::reclamation;
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::fmt;
+use std::ops::Sub;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// The frees of the benchmark threads that have recorded their results.
+static TOTAL_COUNT: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Without a destructor and with a constant initializer, the slot needs no allocation to be
+    // registered, so the allocator may count with it at any time.
+    static LOCAL: Cell<Frees> = const { Cell::new(Frees { count: 0, bytes: 0 }) };
+}
+
+/// A global allocator that skips the frees to `A` while its leaky mode is enabled.
+pub struct Leaky<A> {
+    alloc: A,
+    leak: AtomicBool,
+}
+
+impl<A> Leaky<A> {
+    pub const fn new(alloc: A) -> Self {
+        Self {
+            alloc,
+            leak: AtomicBool::new(false),
+        }
+    }
+
+    /// Makes this allocator skip the frees from now on.
+    pub fn enable(&self) {
+        self.leak.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether this allocator skips the frees.
+    pub fn enabled(&self) -> bool {
+        self.leak.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Leaky<A> {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        #[cfg(feature = "alloc-tags")]
+        alloc_tags::allocated(layout);
+        self.alloc.alloc(layout)
+    }
+
+    #[inline]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        #[cfg(feature = "alloc-tags")]
+        alloc_tags::allocated(layout);
+        self.alloc.alloc_zeroed(layout)
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        reclamation::freed(ptr, layout.size());
+        // A skipped free is counted as a free of the node, which the scheme has reclaimed.
+        #[cfg(feature = "alloc-tags")]
+        alloc_tags::freed(layout);
+        if !self.enabled() {
+            return self.alloc.dealloc(ptr, layout);
+        }
+        let _ = LOCAL.try_with(|local| {
+            let frees = local.get();
+            local.set(Frees {
+                count: frees.count + 1,
+                bytes: frees.bytes + layout.size(),
+            });
+        });
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = self.alloc.realloc(ptr, layout, new_size);
+        #[cfg(feature = "alloc-tags")]
+        if !new.is_null() {
+            alloc_tags::freed(layout);
+            alloc_tags::allocated(Layout::from_size_align_unchecked(new_size, layout.align()));
+        }
+        new
+    }
+}
+
+/// Makes the global allocator skip the frees from now on.
+pub fn enable() {
+    crate::ALLOC.enable();
+}
+
+/// Whether the global allocator skips the frees.
+pub fn enabled() -> bool {
+    crate::ALLOC.enabled()
+}
+
+/// A number of skipped frees and their total size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Frees {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+impl Sub for Frees {
+    type Output = Frees;
+
+    fn sub(self, rhs: Frees) -> Frees {
+        Frees {
+            count: self.count - rhs.count,
+            bytes: self.bytes - rhs.bytes,
+        }
+    }
+}
+
+impl fmt::Display for Frees {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} frees of {} bytes", self.count, self.bytes)
+    }
+}
+
+/// The frees that the calling thread has skipped so far.
+pub fn local_frees() -> Frees {
+    LOCAL.with(Cell::get)
+}
+
+/// Adds the frees that a benchmark thread has skipped during its measurement to the totals.
+pub fn record(frees: Frees) {
+    TOTAL_COUNT.fetch_add(frees.count, Ordering::Relaxed);
+    TOTAL_BYTES.fetch_add(frees.bytes, Ordering::Relaxed);
+}
+
+/// The frees that the benchmark threads have recorded since the start of the process.
+pub fn recorded_frees() -> Frees {
+    Frees {
+        count: TOTAL_COUNT.load(Ordering::Relaxed),
+        bytes: TOTAL_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{local_frees, Frees, Leaky};
+    use std::alloc::{GlobalAlloc, Layout, System};
+
+    #[test]
+    fn leaky_allocator_counts_frees() {
+        let leaky = Leaky::new(System);
+        let layout = Layout::new::<[u64; 4]>();
+        // Only this allocator leaks its frees, not the global one of the other tests.
+        leaky.enable();
+        let before = local_frees();
+        unsafe {
+            let ptr = leaky.alloc(layout);
+            assert!(!ptr.is_null());
+            leaky.dealloc(ptr, layout);
+        }
+        assert_eq!(
+            local_frees() - before,
+            Frees {
+                count: 1,
+                bytes: 32
+            }
+        );
+    }
+}
@@ -1,6 +1,7 @@
 #![doc = include_str!("../README.md")]
 #![feature(strict_provenance_atomic_ptr)]
 #![feature(cfg_sanitize)]
+#![debugger_visualizer(gdb_script_file = "../debugger/smr_benchmark_gdb.py")]
 
 #[macro_use]
 extern crate cfg_if;
@@ -9,12 +10,15 @@ cfg_if! {
     if #[cfg(all(not(feature = "sanitize"), target_os = "linux"))] {
         extern crate tikv_jemallocator;
         #[global_allocator]
-        static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+        static ALLOC: leak::Leaky<tikv_jemallocator::Jemalloc> =
+            leak::Leaky::new(tikv_jemallocator::Jemalloc);
 
         extern crate tikv_jemalloc_ctl;
         pub struct MemSampler {
             epoch_mib: tikv_jemalloc_ctl::epoch_mib,
             allocated_mib: tikv_jemalloc_ctl::stats::allocated_mib,
+            /// The resident set sizes at the samples.
+            rss: rss::RssSamples,
         }
 
         impl Default for MemSampler {
@@ -28,14 +32,46 @@ cfg_if! {
                 MemSampler {
                     epoch_mib: tikv_jemalloc_ctl::epoch::mib().unwrap(),
                     allocated_mib: tikv_jemalloc_ctl::stats::allocated::mib().unwrap(),
+                    rss: rss::RssSamples::new(),
                 }
             }
+            /// Returns the allocated bytes, and also samples the resident set size.
             pub fn sample(&self) -> usize {
+                self.rss.sample();
                 self.epoch_mib.advance().unwrap();
                 self.allocated_mib.read().unwrap()
             }
+            /// The peak and the average of the resident set sizes over all the samples so far.
+            pub fn rss(&self) -> Option<rss::Rss> {
+                self.rss.summary()
+            }
+        }
+
+        extern crate tikv_jemalloc_sys;
+        /// Flushes the calling thread's cache and purges the dirty pages of every arena,
+        /// so that the next phase of a benchmark does not reuse the heap state of the previous one.
+        pub fn release_allocator_caches() {
+            fn mallctl_void(name: &str) {
+                let name = std::ffi::CString::new(name).unwrap();
+                let ret = unsafe {
+                    tikv_jemalloc_sys::mallctl(
+                        name.as_ptr(),
+                        std::ptr::null_mut(),
+                        std::ptr::null_mut(),
+                        std::ptr::null_mut(),
+                        0,
+                    )
+                };
+                assert_eq!(ret, 0, "mallctl({:?}) failed", name);
+            }
+            mallctl_void("thread.tcache.flush");
+            // 4096 is `MALLCTL_ARENAS_ALL`, which addresses all arenas at once.
+            mallctl_void("arena.4096.purge");
         }
     } else {
+        #[global_allocator]
+        static ALLOC: leak::Leaky<std::alloc::System> = leak::Leaky::new(std::alloc::System);
+
         pub struct MemSampler {}
 
         impl Default for MemSampler {
@@ -52,7 +88,63 @@ cfg_if! {
             pub fn sample(&self) -> usize {
                 0
             }
+            pub fn rss(&self) -> Option<rss::Rss> {
+                None
+            }
+        }
+
+        /// Returns the free memory at the top of the heap to the system, if the platform supports it.
+        pub fn release_allocator_caches() {
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            unsafe {
+                libc::malloc_trim(0);
+            }
+        }
+    }
+}
+
+// The drains of the EBR flavours, which `--hygiene` runs before it releases the allocator caches.
+// Each one stops after 128 flushes, in case a thread that is still pinned holds the epoch back.
+
+/// Repeatedly advances the epoch of `collector` until its deferred garbage is destroyed.
+pub fn drain_ebr(collector: &crossbeam_ebr::Collector) {
+    let handle = collector.register();
+    for _ in 0..128 {
+        if crossbeam_ebr::GLOBAL_GARBAGE_COUNT.load(std::sync::atomic::Ordering::Acquire) == 0 {
+            break;
+        }
+        handle.pin().flush();
+    }
+}
+
+/// Repeatedly advances the epoch of `collector` of PEBR until its deferred garbage is destroyed.
+pub fn drain_pebr(collector: &crossbeam_pebr::Collector) {
+    let handle = collector.register();
+    for _ in 0..128 {
+        if crossbeam_pebr::GLOBAL_GARBAGE_COUNT.load(std::sync::atomic::Ordering::Acquire) == 0 {
+            break;
+        }
+        handle.pin().flush();
+    }
+}
+
+/// Repeatedly advances the epoch of the EBR of CDRC until the deferred garbage is destroyed.
+pub fn drain_cdrc_ebr() {
+    for _ in 0..128 {
+        if cdrc::ebr_impl::GLOBAL_GARBAGE_COUNT.load(std::sync::atomic::Ordering::Acquire) == 0 {
+            break;
+        }
+        cdrc::ebr_impl::pin().flush();
+    }
+}
+
+/// Repeatedly advances the epoch of the EBR of CIRC until the deferred garbage is destroyed.
+pub fn drain_circ_ebr() {
+    for _ in 0..128 {
+        if circ::ebr_impl::GLOBAL_GARBAGE_COUNT.load(std::sync::atomic::Ordering::Acquire) == 0 {
+            break;
         }
+        circ::ebr_impl::pin().flush();
     }
 }
 
@@ -65,5 +157,32 @@ extern crate typenum;
 
 #[macro_use]
 mod utils;
+pub mod affinity;
+pub mod alloc_tags;
+pub mod app_cache;
+pub mod baseline;
+pub mod cache_thrasher;
+pub mod clock;
+pub mod compare;
 pub mod config;
+pub mod conflicts;
+pub mod cpu_freq;
 pub mod ds_impl;
+pub mod epoch_dump;
+pub mod huge_pages;
+pub mod latency;
+pub mod layout;
+pub mod leak;
+pub mod limits;
+pub mod log;
+pub mod reclaim_timeline;
+pub mod reclamation;
+pub mod results;
+pub mod rss;
+pub mod sched;
+pub mod shadow;
+pub mod signals;
+pub mod stale_cache;
+pub mod start;
+pub mod topology;
+pub mod trace;
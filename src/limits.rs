@@ -0,0 +1,115 @@
+//! The largest numbers of threads that a benchmark can run.
+//!
+//! A structure or a scheme with a fixed number of thread slots panics when one thread too many
+//! takes one, and the system refuses to spawn a thread beyond the limits of the user or the
+//! cgroup, which the benchmark only learns from a panic in the middle of a run, or of a sweep. The
+//! limits are instead read at the setup: the slots of the structures that have a fixed number of
+//! them, and the `pids` limit of the cgroup, `kernel.threads-max` and `RLIMIT_NPROC`, less a few
+//! threads for the auxiliary, the history and the sampling threads. A number of threads beyond the
+//! limit then fails at once with the limit and where it comes from, `-t auto` runs as many threads
+//! as the limit allows, and a sweep skips its points beyond it.
+
+use crate::config::map::DS;
+use crate::ds_impl::ebr::wait_free_treap;
+use std::fmt;
+use std::fs;
+
+/// The threads that a benchmark runs besides its benchmark threads.
+const SPARE_THREADS: usize = 8;
+
+/// The largest number of benchmark threads, and where it comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadLimit {
+    pub max: usize,
+    pub source: String,
+}
+
+impl fmt::Display for ThreadLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} threads ({})", self.max, self.source)
+    }
+}
+
+/// The limit of the thread slots of `ds` on `mm`, if it has a fixed number of them.
+fn slots(mm: &str, ds: DS) -> Option<ThreadLimit> {
+    match (mm, ds) {
+        ("ebr", DS::WfTreap) => Some(ThreadLimit {
+            max: wait_free_treap::MAX_THREADS,
+            source: "the announcement slots of the wait-free treap".to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// Parses a limit of the number of tasks, which is `max` if there is none.
+fn parse_tasks(s: &str) -> Option<usize> {
+    let s = s.trim();
+    if s == "max" {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// The limits of the number of tasks of the system, less the tasks that the cgroup runs already.
+fn system() -> Vec<ThreadLimit> {
+    let mut limits = Vec::new();
+    let read = |path: &str| fs::read_to_string(path).ok();
+    if let Some(max) = read("/sys/fs/cgroup/pids.max").and_then(|s| parse_tasks(&s)) {
+        let current = read("/sys/fs/cgroup/pids.current")
+            .and_then(|s| parse_tasks(&s))
+            .unwrap_or(0);
+        limits.push(ThreadLimit {
+            max: max.saturating_sub(current),
+            source: format!("pids.max of the cgroup, {current} of {max} in use"),
+        });
+    }
+    if let Some(max) = read("/proc/sys/kernel/threads-max").and_then(|s| parse_tasks(&s)) {
+        limits.push(ThreadLimit {
+            max,
+            source: "kernel.threads-max".to_string(),
+        });
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let mut rlimit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let ret = unsafe { libc::getrlimit(libc::RLIMIT_NPROC, &mut rlimit) };
+        if ret == 0 && rlimit.rlim_cur != libc::RLIM_INFINITY {
+            limits.push(ThreadLimit {
+                max: rlimit.rlim_cur as usize,
+                source: "RLIMIT_NPROC".to_string(),
+            });
+        }
+    }
+    for limit in &mut limits {
+        limit.max = limit.max.saturating_sub(SPARE_THREADS);
+    }
+    limits
+}
+
+/// The largest number of benchmark threads that `ds` on `mm` can run on this system, or `None`
+/// if nothing limits it.
+pub fn max_threads(mm: &str, ds: DS) -> Option<ThreadLimit> {
+    slots(mm, ds)
+        .into_iter()
+        .chain(system())
+        .min_by_key(|limit| limit.max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{max_threads, parse_tasks, slots};
+    use crate::config::map::DS;
+
+    #[test]
+    fn thread_limits() {
+        assert_eq!(parse_tasks("max\n"), None);
+        assert_eq!(parse_tasks("4096\n"), Some(4096));
+        assert_eq!(slots("ebr", DS::WfTreap).unwrap().max, 1024);
+        assert_eq!(slots("hp", DS::HashMap), None);
+        // The system may lower the limit of the slots, but never raise it.
+        assert!(max_threads("ebr", DS::WfTreap).unwrap().max <= 1024);
+    }
+}
@@ -0,0 +1,193 @@
+//! Structured logging of the phases of a benchmark.
+//!
+//! The harness prints its progress to stdout as a single line per configuration, which tells little
+//! about where a long sweep is stuck. The events of the harness are instead logged to stderr with
+//! the time since the setup, their level and their phase, and their fields as `key=value` pairs:
+//!
+//! ```text
+//! [   0.412s INFO  prefill] prefilled keys=500000
+//! ```
+//!
+//! `--log-level` sets the level of every phase, and may override that of each phase, e.g.,
+//! `--log-level info,run=debug`. Only the warnings are logged by default, so that the output of
+//! the sweeps does not change. The level is checked before the fields are formatted, so that a
+//! disabled event costs a relaxed load.
+
+use clap::ValueEnum;
+use std::fmt::{self, Write as _};
+use std::io::{stderr, Write as _};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, ValueEnum, Clone, Copy)]
+#[repr(u8)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(PartialEq, Eq, Debug, ValueEnum, Clone, Copy)]
+pub enum Phase {
+    /// The parsing of the configuration and the initialization of the scheme.
+    Setup,
+    /// The insertion of the initial keys.
+    Prefill,
+    /// The measurement, with the arrivals of the threads and the repetitions.
+    Run,
+    /// The checks of the structures after a run.
+    Validate,
+}
+
+const PHASES: usize = 4;
+
+static LEVELS: [AtomicU8; PHASES] = [const { AtomicU8::new(Level::Warn as u8) }; PHASES];
+
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// The levels of the phases, as given with `--log-level`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct LogLevels([Level; PHASES]);
+
+impl Default for LogLevels {
+    fn default() -> Self {
+        Self([Level::Warn; PHASES])
+    }
+}
+
+impl LogLevels {
+    pub fn get(&self, phase: Phase) -> Level {
+        self.0[phase as usize]
+    }
+
+    /// Makes the events of each phase up to its level logged from now on.
+    pub fn apply(&self) {
+        START.get_or_init(Instant::now);
+        for (level, &new) in LEVELS.iter().zip(self.0.iter()) {
+            level.store(new as u8, Ordering::Relaxed);
+        }
+    }
+}
+
+impl FromStr for LogLevels {
+    type Err = String;
+
+    /// Parses a comma-separated list of a level for every phase and of `<phase>=<level>` overrides.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut levels = Self::default();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((phase, level)) => {
+                    let phase = Phase::from_str(phase, true)?;
+                    levels.0[phase as usize] = Level::from_str(level, true)?;
+                }
+                None => levels.0 = [Level::from_str(directive, true)?; PHASES],
+            }
+        }
+        Ok(levels)
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Level::Off => "OFF",
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        })
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.to_possible_value().unwrap().get_name())
+    }
+}
+
+/// Whether the events of `phase` at `level` are logged.
+#[inline(always)]
+pub fn enabled(level: Level, phase: Phase) -> bool {
+    level != Level::Off && level as u8 <= LEVELS[phase as usize].load(Ordering::Relaxed)
+}
+
+/// Formats an event, without its time.
+fn format_event(
+    level: Level,
+    phase: Phase,
+    message: &str,
+    fields: &[(&str, &dyn fmt::Display)],
+) -> String {
+    let mut line = format!("{level:<5} {phase}] {message}");
+    for (key, value) in fields {
+        write!(line, " {key}={value}").unwrap();
+    }
+    line
+}
+
+/// Logs an event with its fields. Use [`log_event!`](crate::log_event) instead, which checks the
+/// level before formatting the fields.
+pub fn write(level: Level, phase: Phase, message: &str, fields: &[(&str, &dyn fmt::Display)]) {
+    let elapsed = START.get_or_init(Instant::now).elapsed();
+    let line = format_event(level, phase, message, fields);
+    // A single write keeps the events of the threads from interleaving.
+    let _ = writeln!(stderr(), "[{:>8.3}s {line}", elapsed.as_secs_f64());
+}
+
+#[macro_export]
+/// Logs an event of a phase at a level, with its fields as `key = value`, if it is enabled.
+///
+/// ```ignore
+/// log_event!(Info, Prefill, "prefilled", keys = config.prefill);
+/// ```
+macro_rules! log_event {
+    ($level:ident, $phase:ident, $message:expr $(, $key:ident = $value:expr)* $(,)?) => {{
+        let level = $crate::log::Level::$level;
+        let phase = $crate::log::Phase::$phase;
+        if $crate::log::enabled(level, phase) {
+            $crate::log::write(
+                level,
+                phase,
+                $message,
+                &[$((stringify!($key), &$value as &dyn ::std::fmt::Display)),*],
+            );
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_event, Level, LogLevels, Phase};
+
+    #[test]
+    fn log_levels() {
+        assert_eq!("".parse::<LogLevels>().unwrap(), LogLevels::default());
+        let levels = "info,run=debug,Validate=off".parse::<LogLevels>().unwrap();
+        assert_eq!(levels.get(Phase::Setup), Level::Info);
+        assert_eq!(levels.get(Phase::Prefill), Level::Info);
+        assert_eq!(levels.get(Phase::Run), Level::Debug);
+        assert_eq!(levels.get(Phase::Validate), Level::Off);
+        assert!("loud".parse::<LogLevels>().is_err());
+        assert!("warmup=info".parse::<LogLevels>().is_err());
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(
+            format_event(
+                Level::Info,
+                Phase::Run,
+                "arrived",
+                &[("thread", &3), ("scheme", &"ebr")]
+            ),
+            "INFO  run] arrived thread=3 scheme=ebr"
+        );
+    }
+}
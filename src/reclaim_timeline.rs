@@ -0,0 +1,271 @@
+//! A timeline of the reclamations of HP and CIRC, on the clock of the throughput.
+//!
+//! A dip of the throughput for a few milliseconds is lost in the averages of a run, and the
+//! samples of the garbage tell that the reclamation lagged but not what it was doing. With the
+//! `reclaim-timeline` feature, the backends call a hook at the events of their reclamations: the
+//! start and the end of a scan of the hazard pointers of `hp_pp` and CIRC, an advance of the epoch
+//! of CIRC, and the start and the end of the destruction of a bag of CIRC. The hook stamps the
+//! event with the time and the thread into a lock-free ring, and the auxiliary thread stamps the
+//! total number of operations into the same ring every `--reclaim-timeline-period`, so that the
+//! throughput and the reclamations are on one clock. The ring keeps the last `CAPACITY` events,
+//! and is written to a CSV file at the end of a run.
+
+use crossbeam_utils::CachePadded;
+use csv::Writer;
+use std::sync::atomic::{fence, AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// The number of events that the ring keeps.
+const CAPACITY: usize = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Kind {
+    /// The total number of operations so far.
+    Ops,
+    /// A reclamation starts scanning the hazard pointers, with the number of retired objects.
+    ScanStart,
+    /// A reclamation has scanned the hazard pointers, with the number of freed objects.
+    ScanEnd,
+    /// The epoch has advanced, with the new epoch.
+    EpochFlip,
+    /// The destruction of a bag starts, with the number of its objects.
+    BatchStart,
+    /// The destruction of a bag has ended, with the number of its objects.
+    BatchEnd,
+}
+
+impl Kind {
+    const ALL: [Kind; 6] = [
+        Kind::Ops,
+        Kind::ScanStart,
+        Kind::ScanEnd,
+        Kind::EpochFlip,
+        Kind::BatchStart,
+        Kind::BatchEnd,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Ops => "ops",
+            Kind::ScanStart => "scan_start",
+            Kind::ScanEnd => "scan_end",
+            Kind::EpochFlip => "epoch_flip",
+            Kind::BatchStart => "batch_start",
+            Kind::BatchEnd => "batch_end",
+        }
+    }
+}
+
+/// A slot of the ring. `seq` is the position of the event in the ring plus one, or zero while the
+/// event is written, so that a reader can tell a torn slot.
+struct Slot {
+    seq: AtomicU64,
+    nanos: AtomicU64,
+    /// The kind of the event in the lowest byte, and the thread above it.
+    word: AtomicU64,
+    value: AtomicU64,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            seq: AtomicU64::new(0),
+            nanos: AtomicU64::new(0),
+            word: AtomicU64::new(0),
+            value: AtomicU64::new(0),
+        }
+    }
+}
+
+static HEAD: CachePadded<AtomicUsize> = CachePadded::new(AtomicUsize::new(0));
+static RING: [Slot; CAPACITY] = [const { Slot::new() }; CAPACITY];
+
+/// The origin of the times of the events.
+static ORIGIN: OnceLock<Instant> = OnceLock::new();
+
+static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// The number of the calling thread in the timeline, in the order of their first event.
+    static THREAD: usize = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+fn nanos_since_origin(instant: Instant) -> u64 {
+    instant
+        .saturating_duration_since(*ORIGIN.get_or_init(Instant::now))
+        .as_nanos() as u64
+}
+
+/// Stamps an event into the ring, overwriting the oldest one if it is full.
+#[inline]
+pub fn record(kind: Kind, value: u64) {
+    let nanos = nanos_since_origin(Instant::now());
+    let thread = THREAD.with(|thread| *thread) as u64;
+    let pos = HEAD.fetch_add(1, Ordering::Relaxed);
+    let slot = &RING[pos % CAPACITY];
+    slot.seq.store(0, Ordering::Relaxed);
+    fence(Ordering::Release);
+    slot.nanos.store(nanos, Ordering::Relaxed);
+    slot.word
+        .store((thread << 8) | kind as u64, Ordering::Relaxed);
+    slot.value.store(value, Ordering::Relaxed);
+    slot.seq.store(pos as u64 + 1, Ordering::Release);
+}
+
+/// Reads the event at `pos`, unless it has been overwritten or is being written.
+fn read(pos: usize) -> Option<(u64, u64, Kind, u64)> {
+    let slot = &RING[pos % CAPACITY];
+    if slot.seq.load(Ordering::Acquire) != pos as u64 + 1 {
+        return None;
+    }
+    let nanos = slot.nanos.load(Ordering::Relaxed);
+    let word = slot.word.load(Ordering::Relaxed);
+    let value = slot.value.load(Ordering::Relaxed);
+    fence(Ordering::Acquire);
+    if slot.seq.load(Ordering::Relaxed) != pos as u64 + 1 {
+        return None;
+    }
+    Some((nanos, word >> 8, Kind::ALL[(word & 0xff) as usize], value))
+}
+
+/// Installs the hooks of the backends, which have no effect unless they are built with their
+/// `timeline` feature.
+fn install() {
+    hp_pp::timeline::set_hook(|event, value| {
+        use hp_pp::timeline::Event;
+        let kind = match event {
+            Event::ScanStart => Kind::ScanStart,
+            Event::ScanEnd => Kind::ScanEnd,
+        };
+        record(kind, value);
+    });
+    circ::timeline::set_hook(|event, value| {
+        use circ::timeline::Event;
+        let kind = match event {
+            Event::ScanStart => Kind::ScanStart,
+            Event::ScanEnd => Kind::ScanEnd,
+            Event::EpochFlip => Kind::EpochFlip,
+            Event::BatchStart => Kind::BatchStart,
+            Event::BatchEnd => Kind::BatchEnd,
+        };
+        record(kind, value);
+    });
+}
+
+/// The timeline of a run: the events stamped since it was created, and the operations of the
+/// threads.
+pub struct ReclaimTimeline {
+    first: usize,
+    ops: Box<[CachePadded<AtomicU64>]>,
+    start: OnceLock<Instant>,
+}
+
+impl ReclaimTimeline {
+    /// Creates a timeline of `threads` threads from the next event on.
+    pub fn new(threads: usize) -> Self {
+        install();
+        Self {
+            first: HEAD.load(Ordering::Relaxed),
+            ops: (0..threads).map(|_| CachePadded::default()).collect(),
+            start: OnceLock::new(),
+        }
+    }
+
+    /// Publishes that the thread `index` has done `ops` operations.
+    #[inline]
+    pub fn publish(&self, index: usize, ops: u64) {
+        self.ops[index].store(ops, Ordering::Relaxed);
+    }
+
+    /// Marks the start of the measurement, which is the origin of the times of the CSV file.
+    pub fn start(&self) {
+        let _ = self.start.set(Instant::now());
+    }
+
+    /// Stamps the total number of operations of the threads.
+    pub fn sample(&self) {
+        let ops = self.ops.iter().map(|ops| ops.load(Ordering::Relaxed)).sum();
+        record(Kind::Ops, ops);
+    }
+
+    /// Writes the events of the timeline to a CSV file at `path`, and returns the number of the
+    /// events written and of those that were overwritten before.
+    ///
+    /// A row has the time since the start of the measurement in nanoseconds, which is negative
+    /// for the events of the warm-up, the thread, the kind of the event, and its value.
+    pub fn dump(&self, path: &str) -> (usize, usize) {
+        let head = HEAD.load(Ordering::Relaxed);
+        let start = self
+            .start
+            .get()
+            .map_or(0, |start| nanos_since_origin(*start)) as i64;
+        let mut writer = Writer::from_path(path).unwrap();
+        writer
+            .write_record(["time_ns", "thread", "event", "value"])
+            .unwrap();
+        let mut written = 0;
+        for pos in self.first.max(head.saturating_sub(CAPACITY))..head {
+            if let Some((nanos, thread, kind, value)) = read(pos) {
+                writer
+                    .write_record([
+                        (nanos as i64 - start).to_string(),
+                        thread.to_string(),
+                        kind.name().to_string(),
+                        value.to_string(),
+                    ])
+                    .unwrap();
+                written += 1;
+            }
+        }
+        writer.flush().unwrap();
+        (written, head - self.first - written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record, Kind, ReclaimTimeline};
+    use std::fs;
+    use std::thread;
+
+    #[test]
+    fn reclaim_timeline() {
+        const THREADS: usize = 4;
+        const FLIPS: usize = 100;
+        let timeline = &ReclaimTimeline::new(THREADS);
+        timeline.start();
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move || {
+                    for epoch in 0..FLIPS {
+                        record(Kind::EpochFlip, epoch as u64);
+                    }
+                    timeline.publish(t, 10);
+                });
+            }
+        });
+        timeline.sample();
+
+        let path =
+            std::env::temp_dir().join(format!("reclaim-timeline-{}.csv", std::process::id()));
+        let (written, overwritten) = timeline.dump(path.to_str().unwrap());
+        let csv = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        // The reclamations of the other tests may stamp their events meanwhile, if the backends
+        // are built with their `timeline` feature.
+        assert!(written + overwritten > THREADS * FLIPS);
+        let rows = csv.lines().skip(1).collect::<Vec<_>>();
+        assert_eq!(rows.len(), written);
+        if overwritten == 0 {
+            let flips = rows
+                .iter()
+                .filter(|row| row.contains(",epoch_flip,"))
+                .count();
+            assert!(flips >= THREADS * FLIPS);
+        }
+        let ops = rows.iter().filter(|row| row.ends_with(",ops,40")).count();
+        assert_eq!(ops, 1);
+    }
+}
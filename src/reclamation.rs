@@ -0,0 +1,255 @@
+//! A checker of a bound on the latency of the reclamation of the retired objects.
+//!
+//! A scheme with bounded garbage, e.g., HP or HP-BRCU, claims that an object is freed soon after it
+//! is retired, and the sampled garbage counts only show that the garbage stays small on average. The
+//! checker instead tracks each retired object: the thread that retires a node registers the address
+//! of its value, and the global allocator, which sees every free of the process, looks the freed
+//! block up in the registry and measures the time since the retirement. A free later than the bound
+//! is a violation, and the stacks of the first ones are kept, which show the path of the scheme that
+//! freed them late. An object that is still registered after the bound at the end of a run is an
+//! overdue violation, e.g., each node that NR retires.
+//!
+//! The registry is a table of a fixed size that the allocator reads without allocating. A retirement
+//! that finds no free slot near its address is not tracked, and is counted apart, so that a scheme
+//! whose garbage outgrows the table does not pass the check silently.
+
+use std::backtrace::Backtrace;
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// The number of slots of the registry.
+const SLOTS: usize = 1 << 20;
+/// The slots that a retirement probes for a free one.
+const PROBES: usize = 4;
+/// The largest freed block that is looked up, which is larger than the node of any structure.
+const MAX_BLOCK: usize = 256;
+/// The violations whose stack is kept.
+const MAX_CONTEXTS: usize = 4;
+
+struct Slot {
+    /// The address of the registered object, or 0.
+    addr: AtomicUsize,
+    /// The time of its retirement, in nanoseconds since the start of the checker.
+    retired: AtomicU64,
+}
+
+static REGISTRY: [Slot; SLOTS] = [const {
+    Slot {
+        addr: AtomicUsize::new(0),
+        retired: AtomicU64::new(0),
+    }
+}; SLOTS];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static BOUND: AtomicU64 = AtomicU64::new(0);
+static START: OnceLock<Instant> = OnceLock::new();
+
+static RETIRED: AtomicUsize = AtomicUsize::new(0);
+static UNTRACKED: AtomicUsize = AtomicUsize::new(0);
+static RECLAIMED: AtomicUsize = AtomicUsize::new(0);
+static LATE: AtomicUsize = AtomicUsize::new(0);
+static MAX_LATENCY: AtomicU64 = AtomicU64::new(0);
+static CONTEXTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+thread_local! {
+    // Set while a free takes a stack, so that the frees of the capture itself are not looked up.
+    static CAPTURING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Checks from now on that each retired object is freed within `bound` after its retirement.
+pub fn enable(bound: Duration) {
+    START.get_or_init(Instant::now);
+    BOUND.store(bound.as_nanos() as u64, Ordering::Relaxed);
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Whether the reclamations are checked.
+#[inline(always)]
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn now() -> u64 {
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+fn index(addr: usize) -> usize {
+    ((addr >> 3).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (usize::BITS - SLOTS.trailing_zeros()))
+        as usize
+}
+
+/// Registers `object`, which lies in a node that the calling thread has just retired.
+///
+/// The caller must still protect the node, so that it is not freed before it is registered.
+pub fn retired<T>(object: &T) {
+    if !enabled() {
+        return;
+    }
+    RETIRED.fetch_add(1, Ordering::Relaxed);
+    let addr = object as *const T as usize;
+    let start = index(addr);
+    for probe in 0..PROBES {
+        let slot = &REGISTRY[(start + probe) % SLOTS];
+        // The time is stored before the address, so that a free that finds the address reads it.
+        if slot.addr.load(Ordering::Relaxed) == 0 {
+            slot.retired.store(now(), Ordering::Relaxed);
+            if slot
+                .addr
+                .compare_exchange(0, addr, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                return;
+            }
+        }
+    }
+    UNTRACKED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Looks up the objects in the block of `size` bytes at `ptr`, which is being freed, and checks
+/// the latency of their reclamation. Called by the global allocator.
+#[inline]
+pub(crate) fn freed(ptr: *mut u8, size: usize) {
+    if !enabled() || size > MAX_BLOCK || CAPTURING.try_with(Cell::get).unwrap_or(true) {
+        return;
+    }
+    let base = ptr as usize;
+    for addr in (base..base + size).step_by(8) {
+        let start = index(addr);
+        for probe in 0..PROBES {
+            let slot = &REGISTRY[(start + probe) % SLOTS];
+            if slot.addr.load(Ordering::Acquire) != addr {
+                continue;
+            }
+            let latency = now().saturating_sub(slot.retired.load(Ordering::Relaxed));
+            if slot
+                .addr
+                .compare_exchange(addr, 0, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+            {
+                reclaimed(latency);
+            }
+        }
+    }
+}
+
+fn reclaimed(latency: u64) {
+    RECLAIMED.fetch_add(1, Ordering::Relaxed);
+    MAX_LATENCY.fetch_max(latency, Ordering::Relaxed);
+    if latency <= BOUND.load(Ordering::Relaxed) {
+        return;
+    }
+    if LATE.fetch_add(1, Ordering::Relaxed) >= MAX_CONTEXTS {
+        return;
+    }
+    CAPTURING.with(|capturing| capturing.set(true));
+    let context = format!(
+        "freed {:?} after its retirement, on {}:\n{}",
+        Duration::from_nanos(latency),
+        std::thread::current().name().unwrap_or("<unnamed>"),
+        Backtrace::force_capture()
+    );
+    CONTEXTS.lock().unwrap().push(context);
+    CAPTURING.with(|capturing| capturing.set(false));
+}
+
+/// The reclamations of the retired objects since the checker was enabled.
+#[derive(Debug, Default, Clone)]
+pub struct Report {
+    pub bound: Duration,
+    pub retired: usize,
+    pub untracked: usize,
+    pub reclaimed: usize,
+    pub max_latency: Duration,
+    /// The objects that were freed later than the bound.
+    pub late: usize,
+    /// The objects that are not freed yet, and were retired earlier than the bound.
+    pub overdue: usize,
+    /// The stacks of the first late frees.
+    pub contexts: Vec<String>,
+}
+
+impl Report {
+    pub fn violations(&self) -> usize {
+        self.late + self.overdue
+    }
+}
+
+/// Reports the reclamations so far, and counts the registered objects that are overdue.
+pub fn report() -> Report {
+    let bound = BOUND.load(Ordering::Relaxed);
+    let now = now();
+    let overdue = REGISTRY
+        .iter()
+        .filter(|slot| {
+            slot.addr.load(Ordering::Acquire) != 0
+                && now.saturating_sub(slot.retired.load(Ordering::Relaxed)) > bound
+        })
+        .count();
+    Report {
+        bound: Duration::from_nanos(bound),
+        retired: RETIRED.load(Ordering::Relaxed),
+        untracked: UNTRACKED.load(Ordering::Relaxed),
+        reclaimed: RECLAIMED.load(Ordering::Relaxed),
+        max_latency: Duration::from_nanos(MAX_LATENCY.load(Ordering::Relaxed)),
+        late: LATE.load(Ordering::Relaxed),
+        overdue,
+        contexts: CONTEXTS.lock().unwrap().clone(),
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "reclamation bound {:?}: {} retired ({} untracked), {} reclaimed, max latency {:?}, \
+             {} late, {} overdue",
+            self.bound,
+            self.retired,
+            self.untracked,
+            self.reclaimed,
+            self.max_latency,
+            self.late,
+            self.overdue
+        )?;
+        for context in &self.contexts {
+            write!(f, "\n{context}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enable, report, retired};
+    use std::time::Duration;
+
+    #[test]
+    fn late_and_overdue() {
+        enable(Duration::from_millis(10));
+        let before = report();
+
+        let fresh = Box::new([0u64; 4]);
+        retired(&fresh[1]);
+        drop(fresh);
+
+        let late = Box::new([0u64; 4]);
+        retired(&late[2]);
+        std::thread::sleep(Duration::from_millis(20));
+        drop(late);
+
+        let kept = Box::new(0u64);
+        retired(&*kept);
+        std::thread::sleep(Duration::from_millis(20));
+        let after = report();
+        // Other tests may free late objects of their own at the same time.
+        assert_eq!(after.retired - before.retired, 3);
+        assert!(after.reclaimed - before.reclaimed >= 2);
+        assert!(after.late > before.late);
+        assert!(after.overdue >= 1);
+        assert!(after.max_latency >= Duration::from_millis(20));
+        drop(kept);
+    }
+}
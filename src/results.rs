@@ -0,0 +1,233 @@
+//! CSV result files that survive a crash of the benchmark.
+//!
+//! A sweep runs one benchmark process per configuration, and each process appends a row to a
+//! shared CSV file. A process that is killed while it writes could leave a torn row behind, which
+//! breaks the parsing of the whole file by the plotting scripts. Instead, a row is appended by
+//! writing the file with the new row to a temporary file next to it, syncing it, and renaming it
+//! over the file, so that the file always holds the complete rows of the finished configurations.
+//! The processes of a sweep must not append to the same file concurrently.
+//!
+//! With [`Format::Json`], a row is a JSON object on a line of its own instead, keyed by the columns
+//! of the header, so that an analysis script reads a column by its name rather than its position.
+
+use clap::ValueEnum;
+use std::fs::{self, create_dir_all, File};
+use std::io::{ErrorKind, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+
+/// The format of the rows of a results file.
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum Format {
+    /// Comma-separated values, after a header row.
+    Csv,
+    /// A JSON object per line, with a member for each column of the header. An empty field is
+    /// `null`, and a field that is a number or a boolean is not quoted.
+    Json,
+}
+
+pub struct ResultsFile {
+    path: PathBuf,
+    format: Format,
+    header: Vec<String>,
+}
+
+impl ResultsFile {
+    /// Opens the results at `path`, and creates the file with the `header` row if it does not
+    /// exist yet.
+    pub fn open<P: AsRef<Path>>(path: P, header: &[&str]) -> Self {
+        Self::open_with(path, header, Format::Csv)
+    }
+
+    /// Opens the results at `path` in `format`. A JSON file has no header row, and is created with
+    /// its first row.
+    pub fn open_with<P: AsRef<Path>>(path: P, header: &[&str], format: Format) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir).unwrap();
+        }
+        let results = Self {
+            path,
+            format,
+            header: header.iter().map(|column| column.to_string()).collect(),
+        };
+        match fs::metadata(&results.path) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if format == Format::Csv {
+                    results.append(header)
+                }
+            }
+            Err(e) => panic!("Failed to open {}: {e}", results.path.display()),
+        }
+        results
+    }
+
+    /// Appends `record` to the file, which is replaced at once.
+    pub fn append<I, T>(&self, record: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        self.append_all(iter::once(record));
+    }
+
+    /// Appends all of `records` to the file, which is replaced at once.
+    pub fn append_all<R, I, T>(&self, records: R)
+    where
+        R: IntoIterator<Item = I>,
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let mut contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => panic!("Failed to read {}: {e}", self.path.display()),
+        };
+        match self.format {
+            Format::Csv => {
+                let mut writer = csv::Writer::from_writer(&mut contents);
+                for record in records {
+                    writer.write_record(record).unwrap();
+                }
+                writer.flush().unwrap();
+            }
+            Format::Json => {
+                for record in records {
+                    contents.extend_from_slice(json_object(&self.header, record).as_bytes());
+                    contents.push(b'\n');
+                }
+            }
+        }
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(format!(".{}.tmp", std::process::id()));
+        let mut file = File::create(&tmp).unwrap();
+        file.write_all(&contents).unwrap();
+        file.sync_all().unwrap();
+        fs::rename(&tmp, &self.path)
+            .unwrap_or_else(|e| panic!("Failed to replace {}: {e}", self.path.display()));
+    }
+}
+
+/// The JSON object of `record`, with the fields as the values of the columns of `header`.
+fn json_object<I, T>(header: &[String], record: I) -> String
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let members = header
+        .iter()
+        .zip(record)
+        .map(|(column, field)| {
+            let field = String::from_utf8_lossy(field.as_ref());
+            format!("{}:{}", json_string(column), json_value(&field))
+        })
+        .collect::<Vec<_>>();
+    format!("{{{}}}", members.join(","))
+}
+
+/// The JSON value of a CSV field.
+fn json_value(field: &str) -> String {
+    match field {
+        "" => "null".to_string(),
+        "true" | "false" => field.to_string(),
+        _ if is_json_number(field) => field.to_string(),
+        _ => json_string(field),
+    }
+}
+
+/// Whether `field` is a number in the grammar of JSON, which is stricter than `f64::from_str`,
+/// e.g., about `inf`, a leading `+` and a trailing `.`.
+fn is_json_number(field: &str) -> bool {
+    let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let mut rest = field.strip_prefix('-').unwrap_or(field);
+    match digits(rest) {
+        0 => return false,
+        n if n > 1 && rest.starts_with('0') => return false,
+        n => rest = &rest[n..],
+    }
+    if let Some(fraction) = rest.strip_prefix('.') {
+        match digits(fraction) {
+            0 => return false,
+            n => rest = &fraction[n..],
+        }
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        match digits(exponent) {
+            0 => return false,
+            n => rest = &exponent[n..],
+        }
+    }
+    rest.is_empty()
+}
+
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_json_number, Format, ResultsFile};
+    use std::fs;
+
+    #[test]
+    fn append_rows_atomically() {
+        let dir = std::env::temp_dir().join(format!("results-{}", std::process::id()));
+        let path = dir.join("results.csv");
+        let results = ResultsFile::open(&path, &["mm", "throughput"]);
+        results.append(["ebr", "100"]);
+        // Reopening keeps the rows and does not repeat the header.
+        let results = ResultsFile::open(&path, &["mm", "throughput"]);
+        results.append(["hp", "90"]);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "mm,throughput\nebr,100\nhp,90\n"
+        );
+        // No temporary file is left behind.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn append_json_rows() {
+        let dir = std::env::temp_dir().join(format!("results-json-{}", std::process::id()));
+        let path = dir.join("results.json");
+        let header = ["mm", "throughput", "ci", "leak", "trace"];
+        let results = ResultsFile::open_with(&path, &header, Format::Json);
+        results.append(["ebr", "100", "1.5", "false", "a \"b\".txt"]);
+        let results = ResultsFile::open_with(&path, &header, Format::Json);
+        results.append(["hp", "90", "", "true", "C:\\t\n"]);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"mm\":\"ebr\",\"throughput\":100,\"ci\":1.5,\"leak\":false,\"trace\":\"a \\\"b\\\".txt\"}\n\
+             {\"mm\":\"hp\",\"throughput\":90,\"ci\":null,\"leak\":true,\"trace\":\"C:\\\\t\\n\"}\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn json_numbers() {
+        for number in ["0", "-7", "12.50", "1e-3", "6.02E+23"] {
+            assert!(is_json_number(number), "{number}");
+        }
+        for other in ["", "-", "007", "+1", "1.", ".5", "1e", "inf", "NaN", "1 2"] {
+            assert!(!is_json_number(other), "{other}");
+        }
+    }
+}
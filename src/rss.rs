@@ -0,0 +1,83 @@
+//! The resident set size of the process, which the memory sampler reads with the statistics of the
+//! allocator.
+//!
+//! `stats.allocated` of jemalloc counts the bytes of the live allocations, while the resident set
+//! also counts the pages that the allocator keeps without allocations in them, its metadata, and
+//! the stacks of the threads. A scheme that frees in bursts may return to the same allocated bytes
+//! with a larger resident set, which is the footprint that the system sees.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The peak and the average of the sampled resident set sizes in bytes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rss {
+    pub peak: usize,
+    pub avg: usize,
+}
+
+/// The resident set sizes sampled over the repetitions of a benchmark.
+#[derive(Default)]
+pub struct RssSamples {
+    peak: AtomicUsize,
+    sum: AtomicUsize,
+    samples: AtomicUsize,
+}
+
+impl RssSamples {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the resident set size and adds it to the samples, unless the platform does not expose
+    /// it.
+    pub fn sample(&self) {
+        if let Some(rss) = resident() {
+            self.peak.fetch_max(rss, Ordering::Relaxed);
+            self.sum.fetch_add(rss, Ordering::Relaxed);
+            self.samples.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The peak and the average of the samples, or `None` if there are none.
+    pub fn summary(&self) -> Option<Rss> {
+        let samples = self.samples.load(Ordering::Relaxed);
+        (samples > 0).then(|| Rss {
+            peak: self.peak.load(Ordering::Relaxed),
+            avg: self.sum.load(Ordering::Relaxed) / samples,
+        })
+    }
+}
+
+/// The resident set size of the process in bytes, from `/proc/self/statm`, or `None` if the
+/// platform does not expose it.
+pub fn resident() -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+        let pages = statm.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        Some(pages * page_size as usize)
+    }
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::{resident, RssSamples};
+
+    #[test]
+    fn samples_resident_set() {
+        assert!(resident().unwrap() > 0);
+
+        let samples = RssSamples::new();
+        assert_eq!(samples.summary(), None);
+        samples.sample();
+        // Touches a few megabytes, which stay resident until the next sample.
+        let block = vec![1u8; 16 << 20];
+        samples.sample();
+        assert_eq!(block.iter().map(|&b| b as usize).sum::<usize>(), 16 << 20);
+        let rss = samples.summary().unwrap();
+        assert!(rss.peak >= rss.avg && rss.avg > 0);
+    }
+}
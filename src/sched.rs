@@ -0,0 +1,38 @@
+//! Scheduling points for the `shuttle` tests.
+//!
+//! `shuttle` only switches between its threads at its own synchronization primitives, while the
+//! data structures use std atomics. [`yield_point`] marks the steps of an operation between which
+//! the interleavings with other operations are subtle, so that a `shuttle` test explores them.
+//! It is a no-op unless the `shuttle` feature is enabled and the current thread runs a `shuttle`
+//! test that has called [`enable_yield_points`].
+
+#[cfg(feature = "shuttle")]
+use std::cell::Cell;
+
+#[cfg(feature = "shuttle")]
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Makes the yield points on the current thread switch to another `shuttle` thread.
+///
+/// `shuttle` runs all threads of a test on the thread that calls it, so this must be called
+/// before running the test.
+#[cfg(feature = "shuttle")]
+pub fn enable_yield_points() {
+    ENABLED.with(|enabled| enabled.set(true));
+}
+
+/// Lets `shuttle` switch to another thread here.
+#[cfg(feature = "shuttle")]
+#[inline]
+pub fn yield_point() {
+    if ENABLED.with(Cell::get) {
+        shuttle::thread::yield_now();
+    }
+}
+
+/// Lets `shuttle` switch to another thread here.
+#[cfg(not(feature = "shuttle"))]
+#[inline(always)]
+pub fn yield_point() {}
@@ -0,0 +1,204 @@
+//! A reference map that mirrors the operations of a structure and checks their outcomes, for
+//! `--shadow-check`.
+//!
+//! Each operation of a benchmark thread runs with the lock of the stripe of its key held, and is
+//! mirrored into the `BTreeMap` of the stripe, which checks its outcome against the outcome of
+//! the previous operations on the key right away. The operations on a key are thus linearized by
+//! the lock, so that the outcomes are exact, while the operations on the keys of the other stripes
+//! still run concurrently on the structure. The shadow does not mirror the prefill: a key that it
+//! has not seen yet takes the outcome of its first operation as the truth, and is checked from its
+//! second operation on.
+
+use crate::config::map::Op;
+use crossbeam_utils::CachePadded;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// The number of stripes, which bounds how often the threads contend on a stripe.
+const STRIPES: usize = 1024;
+
+/// What the shadow knows of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Absent,
+    /// A key whose value is not known is one that an `insert` failed to insert before the shadow
+    /// saw its value.
+    Present(Option<usize>),
+}
+
+#[derive(Default)]
+struct Stripe {
+    entries: BTreeMap<usize, Entry>,
+    checked: u64,
+}
+
+/// An outcome of an operation that contradicts the previous operations on its key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub op: Op,
+    pub index: usize,
+    /// The value that a `get` or a `remove` returned, or the value of an `insert` if it inserted
+    /// it.
+    pub outcome: Option<usize>,
+    /// What the shadow knew of the key before the operation.
+    expected: Entry,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected = match self.expected {
+            Entry::Absent => "absent".to_string(),
+            Entry::Present(Some(value)) => format!("present with {value}"),
+            Entry::Present(None) => "present".to_string(),
+        };
+        match (self.op, self.outcome) {
+            (Op::Insert, Some(_)) => write!(f, "inserted the key of index {}", self.index)?,
+            (Op::Insert, None) => write!(f, "did not insert the key of index {}", self.index)?,
+            (op, outcome) => write!(
+                f,
+                "{op:?} of the key of index {} returned {outcome:?}",
+                self.index
+            )?,
+        }
+        write!(f, ", but the key was {expected}")
+    }
+}
+
+/// The shadow of the structure of one run, which is empty and checks nothing without
+/// `--shadow-check`.
+pub struct Shadow {
+    stripes: Box<[CachePadded<Mutex<Stripe>>]>,
+}
+
+impl Shadow {
+    pub fn new(enabled: bool) -> Self {
+        let stripes = if enabled { STRIPES } else { 0 };
+        Self {
+            stripes: (0..stripes).map(|_| Default::default()).collect(),
+        }
+    }
+
+    /// Runs `op` on the key of `index` with `run`, and checks its outcome if the shadow is
+    /// enabled. `run` returns the value that a `get` or a `remove` returned, or for an `insert`,
+    /// the value that it inserted if it did.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the divergence if the outcome contradicts the previous operations on the key.
+    #[inline]
+    pub fn run<F: FnOnce() -> Option<usize>>(&self, op: Op, index: usize, run: F) {
+        if self.stripes.is_empty() {
+            run();
+            return;
+        }
+        if let Err(divergence) = self.check(op, index, run) {
+            panic!("shadow check: {divergence}");
+        }
+    }
+
+    fn check<F: FnOnce() -> Option<usize>>(
+        &self,
+        op: Op,
+        index: usize,
+        run: F,
+    ) -> Result<(), Divergence> {
+        let mut stripe = self.stripes[index % self.stripes.len()].lock().unwrap();
+        let outcome = run();
+        stripe.checked += 1;
+        let known = stripe.entries.get(&index).copied();
+        let consistent = match (op, known) {
+            (_, None) => true,
+            (Op::Insert, Some(entry)) => outcome.is_some() == (entry == Entry::Absent),
+            (_, Some(Entry::Absent)) => outcome.is_none(),
+            (_, Some(Entry::Present(value))) => {
+                outcome.is_some() && (value.is_none() || value == outcome)
+            }
+        };
+        if !consistent {
+            return Err(Divergence {
+                op,
+                index,
+                outcome,
+                expected: known.unwrap(),
+            });
+        }
+        let entry = match (op, outcome) {
+            (Op::Remove, _) => Entry::Absent,
+            (_, Some(value)) => Entry::Present(Some(value)),
+            (Op::Get, None) => Entry::Absent,
+            (Op::Insert, None) => match known {
+                Some(Entry::Present(value)) => Entry::Present(value),
+                _ => Entry::Present(None),
+            },
+        };
+        stripe.entries.insert(index, entry);
+        Ok(())
+    }
+
+    /// Prints how many operations on how many keys were checked, if the shadow is enabled.
+    pub fn report(&self) {
+        if self.stripes.is_empty() {
+            return;
+        }
+        let (mut checked, mut keys) = (0, 0);
+        for stripe in self.stripes.iter() {
+            let stripe = stripe.lock().unwrap();
+            checked += stripe.checked;
+            keys += stripe.entries.len();
+        }
+        println!("shadow check: {checked} operations on {keys} keys matched the shadow");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, Shadow};
+    use crate::config::map::Op;
+
+    #[test]
+    fn adopts_and_checks() {
+        let shadow = Shadow::new(true);
+        // The first outcome of a key is adopted.
+        assert!(shadow.check(Op::Insert, 1, || None).is_ok());
+        assert!(shadow.check(Op::Get, 1, || Some(1)).is_ok());
+        assert!(shadow.check(Op::Insert, 1, || None).is_ok());
+        assert!(shadow.check(Op::Remove, 1, || Some(1)).is_ok());
+        assert!(shadow.check(Op::Get, 1, || None).is_ok());
+        assert!(shadow.check(Op::Remove, 1, || None).is_ok());
+        assert!(shadow.check(Op::Insert, 1, || Some(1)).is_ok());
+
+        let divergence = shadow.check(Op::Get, 1, || Some(2)).unwrap_err();
+        assert_eq!(divergence.expected, Entry::Present(Some(1)));
+        assert_eq!(
+            divergence.to_string(),
+            "Get of the key of index 1 returned Some(2), but the key was present with 1"
+        );
+        assert!(shadow.check(Op::Insert, 1, || Some(1)).is_err());
+        assert!(shadow.check(Op::Remove, 1, || None).is_err());
+    }
+
+    #[test]
+    fn unknown_value() {
+        let shadow = Shadow::new(true);
+        assert!(shadow.check(Op::Insert, 7, || None).is_ok());
+        assert!(shadow.check(Op::Get, 7, || None).is_err());
+        assert!(shadow.check(Op::Get, 7, || Some(3)).is_ok());
+        assert!(shadow.check(Op::Remove, 7, || Some(4)).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "shadow check: did not insert")]
+    fn panics_on_divergence() {
+        let shadow = Shadow::new(true);
+        shadow.run(Op::Remove, 3, || None);
+        shadow.run(Op::Insert, 3, || None);
+    }
+
+    #[test]
+    fn disabled() {
+        let shadow = Shadow::new(false);
+        shadow.run(Op::Insert, 1, || None);
+        shadow.run(Op::Get, 1, || None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Pausing the measurement and dumping its progress with signals, for long interactive runs.
+//!
+//! With `--signals`, `SIGUSR1` toggles the recording: the benchmark threads stop their
+//! operations at their next operation and wait until the next `SIGUSR1`, and the paused time
+//! neither counts towards their throughput nor shortens their window, so that a run can be paused
+//! to inspect the process without restarting a long prefill. `SIGUSR2` prints the operations of
+//! the threads since the previous dump and the memory usage, without stopping anything. The
+//! handlers only flip atomics. The threads publish their operations into slots of their own, and a
+//! watcher thread prints the dumps. The signals are supported only on linux.
+
+use crate::MemSampler;
+use crossbeam_utils::CachePadded;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static DUMPS: AtomicUsize = AtomicUsize::new(0);
+
+/// The time of the start of the current pause, and the time of the finished pauses, in
+/// nanoseconds of the monotonic clock.
+static PAUSE_START: AtomicU64 = AtomicU64::new(0);
+static PAUSED_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// The operations of each benchmark thread so far.
+static PROGRESS: OnceLock<Box<[CachePadded<AtomicU64>]>> = OnceLock::new();
+
+/// How often a paused thread and the watcher look at the flags.
+const POLL_PERIOD: Duration = Duration::from_millis(1);
+const WATCH_PERIOD: Duration = Duration::from_millis(100);
+
+/// The monotonic time in nanoseconds, which the handlers may read.
+#[cfg(target_os = "linux")]
+fn now_nanos() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
+
+#[cfg(not(target_os = "linux"))]
+fn now_nanos() -> u64 {
+    0
+}
+
+#[cfg(target_os = "linux")]
+extern "C" fn toggle(_: libc::c_int) {
+    let now = now_nanos();
+    if PAUSED.load(Ordering::Relaxed) {
+        let start = PAUSE_START.load(Ordering::Relaxed);
+        PAUSED_NANOS.fetch_add(now - start, Ordering::Relaxed);
+        PAUSED.store(false, Ordering::Release);
+    } else {
+        PAUSE_START.store(now, Ordering::Relaxed);
+        PAUSED.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(target_os = "linux")]
+extern "C" fn dump(_: libc::c_int) {
+    DUMPS.fetch_add(1, Ordering::Release);
+}
+
+/// Installs the handlers of `SIGUSR1` and `SIGUSR2` for `threads` benchmark threads, and starts
+/// the watcher that prints the dumps.
+pub fn install(threads: usize) {
+    let progress = (0..threads).map(|_| CachePadded::new(AtomicU64::new(0)));
+    assert!(
+        PROGRESS.set(progress.collect()).is_ok(),
+        "The signal handlers are already installed"
+    );
+    #[cfg(target_os = "linux")]
+    unsafe {
+        let toggle: extern "C" fn(libc::c_int) = toggle;
+        let dump: extern "C" fn(libc::c_int) = dump;
+        for (signal, handler) in [
+            (libc::SIGUSR1, toggle as libc::sighandler_t),
+            (libc::SIGUSR2, dump as libc::sighandler_t),
+        ] {
+            let mut action = std::mem::zeroed::<libc::sigaction>();
+            action.sa_sigaction = handler;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            let ret = libc::sigaction(signal, &action, std::ptr::null_mut());
+            assert_eq!(ret, 0, "Failed to install the handler of signal {signal}");
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        println!("NOTE: Pausing with signals is supported only for linux.");
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    thread::spawn(watch);
+    println!(
+        "signals: kill -USR1 {0} pauses or resumes, kill -USR2 {0} dumps the progress",
+        std::process::id()
+    );
+}
+
+/// Whether the recording is paused.
+#[inline]
+pub fn paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// Waits until the recording is resumed, and returns how long the calling thread waited.
+pub fn wait_while_paused() -> Duration {
+    let start = Instant::now();
+    while PAUSED.load(Ordering::Acquire) {
+        thread::sleep(POLL_PERIOD);
+    }
+    start.elapsed()
+}
+
+/// The time that the recording has been paused since the handlers were installed, including the
+/// current pause.
+pub fn paused_time() -> Duration {
+    let mut nanos = PAUSED_NANOS.load(Ordering::Relaxed);
+    if PAUSED.load(Ordering::Acquire) {
+        nanos += now_nanos().saturating_sub(PAUSE_START.load(Ordering::Relaxed));
+    }
+    Duration::from_nanos(nanos)
+}
+
+/// Publishes that the benchmark thread of `index` has run `ops` operations in its current run.
+#[inline]
+pub fn publish(index: usize, ops: u64) {
+    if let Some(slot) = PROGRESS.get().and_then(|progress| progress.get(index)) {
+        slot.store(ops, Ordering::Relaxed);
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn watch() {
+    let mem_sampler = MemSampler::new();
+    let progress = PROGRESS.get().unwrap();
+    let mut dumps = 0;
+    let mut last = (Instant::now(), vec![0; progress.len()]);
+    loop {
+        thread::sleep(WATCH_PERIOD);
+        let requested = DUMPS.load(Ordering::Acquire);
+        if requested == dumps {
+            continue;
+        }
+        dumps = requested;
+        let now = Instant::now();
+        let ops = progress
+            .iter()
+            .map(|slot| slot.load(Ordering::Relaxed))
+            .collect::<Vec<_>>();
+        // A thread whose count went down has started another run.
+        let delta = ops
+            .iter()
+            .zip(&last.1)
+            .map(|(&ops, &last)| ops.checked_sub(last).unwrap_or(ops))
+            .sum::<u64>();
+        let elapsed = now - last.0;
+        println!(
+            "dump {dumps}: {delta} ops in {elapsed:.1?} ({:.0} ops/s), {} ops in the runs so far, \
+             mem: {}, paused for {:.1?}{}",
+            delta as f64 / elapsed.as_secs_f64(),
+            ops.iter().sum::<u64>(),
+            mem_sampler.sample(),
+            paused_time(),
+            if paused() { " (paused now)" } else { "" }
+        );
+        last = (now, ops);
+    }
+}
@@ -0,0 +1,77 @@
+//! A per-thread cache of recent `get` results.
+//!
+//! A benchmark thread with a cache serves a `get` from the cache if it has looked up the same key
+//! within the TTL, without traversing (and protecting) the data structure. This models an
+//! application that tolerates reads that are stale by a bounded time, and shows how much of the
+//! reclamation overhead disappears with it. The thread invalidates the entry of a key whenever it
+//! inserts or removes the key, so that it always reads its own writes.
+
+use std::time::{Duration, Instant};
+
+struct Entry<V> {
+    key: usize,
+    value: Option<V>,
+    expiry: Instant,
+}
+
+/// A direct-mapped cache from keys to the results of `get`.
+pub struct StaleCache<V> {
+    entries: Box<[Option<Entry<V>>]>,
+    ttl: Duration,
+    hits: u64,
+}
+
+impl<V: Clone> StaleCache<V> {
+    /// Creates a cache with `capacity` entries (rounded up to a power of two).
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: (0..capacity.next_power_of_two()).map(|_| None).collect(),
+            ttl,
+            hits: 0,
+        }
+    }
+
+    #[inline]
+    fn slot(&self, key: usize) -> usize {
+        // Fibonacci hashing, so that consecutive keys do not collide in the same entries.
+        key.wrapping_mul(0x9E37_79B9_7F4A_7C15_u64 as usize) & (self.entries.len() - 1)
+    }
+
+    /// Returns the cached result of `get` for `key` if it has not expired at `now`.
+    #[inline]
+    pub fn get(&mut self, key: usize, now: Instant) -> Option<Option<V>> {
+        let slot = self.slot(key);
+        match &self.entries[slot] {
+            Some(entry) if entry.key == key && now < entry.expiry => {
+                self.hits += 1;
+                Some(entry.value.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Caches the result of `get` for `key`, looked up at `now`.
+    #[inline]
+    pub fn put(&mut self, key: usize, value: Option<V>, now: Instant) {
+        let slot = self.slot(key);
+        self.entries[slot] = Some(Entry {
+            key,
+            value,
+            expiry: now + self.ttl,
+        });
+    }
+
+    /// Drops the cached result for `key`, if any.
+    #[inline]
+    pub fn invalidate(&mut self, key: usize) {
+        let slot = self.slot(key);
+        if matches!(&self.entries[slot], Some(entry) if entry.key == key) {
+            self.entries[slot] = None;
+        }
+    }
+
+    /// The number of `get`s served from the cache so far.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+}
@@ -0,0 +1,104 @@
+//! The simultaneous start of the threads of a benchmark.
+//!
+//! With a `std` barrier, the last thread to arrive wakes the others one futex at a time, so with
+//! 100+ threads the first ones start operating well before the last ones are even scheduled, and
+//! a short run measures the ramp as much as the steady state. A [`StartGate`] starts the threads
+//! in two phases instead. Each thread takes a ticket when it has registered with the scheme, and
+//! once every ticket is taken, warms its caches with a few unrecorded operations and takes a
+//! second ticket. The thread that takes the last one raises a flag, on which the others have been
+//! spinning rather than sleeping, so that they all observe it within a few cache misses. If there
+//! are more threads than CPUs, the waiting threads yield their CPUs instead, or the thread that
+//! raises the flag may never be scheduled. Each thread measures its start skew, from the raise of
+//! the flag until it observed it.
+
+use crossbeam_utils::{Backoff, CachePadded};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::thread::available_parallelism;
+use std::time::{Duration, Instant};
+
+/// A two-phase barrier for the threads of a benchmark, which releases all of them at once.
+pub struct StartGate {
+    parties: usize,
+    spin: bool,
+    registered: CachePadded<AtomicUsize>,
+    ready: CachePadded<AtomicUsize>,
+    go: CachePadded<AtomicBool>,
+    released: OnceLock<Instant>,
+}
+
+impl StartGate {
+    pub fn new(parties: usize) -> Self {
+        Self {
+            parties,
+            spin: parties <= available_parallelism().map_or(1, |n| n.get()),
+            registered: CachePadded::new(AtomicUsize::new(0)),
+            ready: CachePadded::new(AtomicUsize::new(0)),
+            go: CachePadded::new(AtomicBool::new(false)),
+            released: OnceLock::new(),
+        }
+    }
+
+    /// The first phase: takes a ticket, and waits until every party has taken one.
+    pub fn register(&self) {
+        self.registered.fetch_add(1, Ordering::SeqCst);
+        let backoff = Backoff::new();
+        while self.registered.load(Ordering::SeqCst) < self.parties {
+            backoff.snooze();
+        }
+    }
+
+    /// The second phase: takes a ticket, and waits until every party has taken one. Returns how
+    /// long after the release the calling thread observed it.
+    pub fn start(&self) -> Duration {
+        if self.ready.fetch_add(1, Ordering::SeqCst) + 1 == self.parties {
+            self.released.set(Instant::now()).unwrap();
+            self.go.store(true, Ordering::SeqCst);
+        } else {
+            let backoff = Backoff::new();
+            while !self.go.load(Ordering::SeqCst) {
+                if self.spin {
+                    std::hint::spin_loop();
+                } else {
+                    backoff.snooze();
+                }
+            }
+        }
+        self.released.get().unwrap().elapsed()
+    }
+
+    /// Both phases at once, for a thread that has nothing to warm up.
+    pub fn wait(&self) {
+        self.register();
+        self.start();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StartGate;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn start_gate() {
+        const THREADS: usize = 8;
+        let gate = &StartGate::new(THREADS);
+        let registered = &AtomicUsize::new(0);
+        let warmed = &AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move || {
+                    registered.fetch_add(1, Ordering::SeqCst);
+                    gate.register();
+                    // No thread warms up before every thread has registered.
+                    assert_eq!(registered.load(Ordering::SeqCst), THREADS);
+                    warmed.fetch_add(1, Ordering::SeqCst);
+                    gate.start();
+                    // No thread starts before every thread has warmed up.
+                    assert_eq!(warmed.load(Ordering::SeqCst), THREADS);
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,209 @@
+//! The sockets of the machine, discovered from sysfs.
+//!
+//! A scheme whose throughput drops once the threads span more than one socket may pay for the
+//! cross-socket traffic of its shared state (e.g., a global epoch) rather than for the structure.
+//! The benchmarks record the socket that each thread runs on, and can be restricted to a single
+//! socket to compare against.
+//!
+//! The CPUs of a physical core share its execution units with SMT, so the number of threads that
+//! `-t auto:physical` detects counts the cores instead of the CPUs.
+
+use std::fs;
+use std::sync::OnceLock;
+
+/// The CPUs of each socket, in the order of the socket ids.
+pub struct Topology {
+    sockets: Vec<(usize, Vec<usize>)>,
+    /// The `(socket, core id)` of the physical core of each CPU, in the order of the CPUs.
+    cores: Vec<(usize, (usize, usize))>,
+}
+
+impl Topology {
+    /// Reads the topology from sysfs. Every CPU is put on socket 0 if it is not available.
+    fn discover() -> Self {
+        let mut cores = Vec::new();
+        let cpus = fs::read_dir("/sys/devices/system/cpu")
+            .into_iter()
+            .flatten();
+        for entry in cpus.flatten() {
+            let name = entry.file_name();
+            let Some(cpu) = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("cpu"))
+                .and_then(|id| id.parse::<usize>().ok())
+            else {
+                continue;
+            };
+            let socket = fs::read_to_string(entry.path().join("topology/physical_package_id"))
+                .ok()
+                .and_then(|id| id.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            // A CPU without a core id is a core of its own.
+            let core = fs::read_to_string(entry.path().join("topology/core_id"))
+                .ok()
+                .and_then(|id| id.trim().parse::<usize>().ok())
+                .unwrap_or(usize::MAX - cpu);
+            cores.push((cpu, (socket, core)));
+        }
+        if cores.is_empty() {
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            cores = (0..threads).map(|cpu| (cpu, (0, cpu))).collect();
+        }
+        Self::from_cores(cores)
+    }
+
+    /// The topology of the CPUs of `cores`, with the `(socket, core id)` of each.
+    pub(crate) fn from_cores(mut cores: Vec<(usize, (usize, usize))>) -> Self {
+        let mut sockets: Vec<(usize, Vec<usize>)> = Vec::new();
+        for &(cpu, (socket, _)) in &cores {
+            match sockets.iter_mut().find(|(id, _)| *id == socket) {
+                Some((_, cpus)) => cpus.push(cpu),
+                None => sockets.push((socket, vec![cpu])),
+            }
+        }
+        sockets.sort();
+        for (_, cpus) in sockets.iter_mut() {
+            cpus.sort();
+        }
+        cores.sort();
+        Self { sockets, cores }
+    }
+
+    /// The ids of the sockets.
+    pub fn sockets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.sockets.iter().map(|(id, _)| *id)
+    }
+
+    /// The CPUs of the socket `socket`, if it exists.
+    pub fn cpus(&self, socket: usize) -> Option<&[usize]> {
+        self.sockets
+            .iter()
+            .find(|(id, _)| *id == socket)
+            .map(|(_, cpus)| cpus.as_slice())
+    }
+
+    /// The socket of the CPU `cpu`.
+    pub fn socket_of(&self, cpu: usize) -> Option<usize> {
+        self.sockets
+            .iter()
+            .find(|(_, cpus)| cpus.binary_search(&cpu).is_ok())
+            .map(|(id, _)| *id)
+    }
+
+    /// The `(socket, core id)` of the physical core of the CPU `cpu`.
+    pub fn core_of(&self, cpu: usize) -> Option<(usize, usize)> {
+        self.cores
+            .binary_search_by_key(&cpu, |&(cpu, _)| cpu)
+            .ok()
+            .map(|i| self.cores[i].1)
+    }
+
+    /// The CPUs of the socket `socket`, or of every socket if `None`. With `physical`, only the
+    /// first CPU of each physical core is included, so that no two of them are SMT siblings.
+    pub fn available_cpus(&self, socket: Option<usize>, physical: bool) -> Vec<usize> {
+        let mut seen = Vec::new();
+        let mut cpus = Vec::new();
+        for &(cpu, core) in &self.cores {
+            if socket.is_some_and(|socket| core.0 != socket) || (physical && seen.contains(&core)) {
+                continue;
+            }
+            seen.push(core);
+            cpus.push(cpu);
+        }
+        cpus
+    }
+
+    /// Restricts the calling thread, and the threads that it spawns afterwards, to `cpus`.
+    pub fn restrict_to_cpus(&self, cpus: &[usize]) {
+        set_affinity(cpus);
+    }
+
+    /// Returns the socket that the calling thread is running on.
+    pub fn current_socket(&self) -> Option<usize> {
+        self.socket_of(current_cpu()?)
+    }
+
+    /// Restricts the calling thread, and the threads that it spawns afterwards, to the CPUs of
+    /// the socket `socket`.
+    pub fn restrict_to(&self, socket: usize) {
+        let cpus = self
+            .cpus(socket)
+            .unwrap_or_else(|| panic!("There is no socket {socket}"));
+        set_affinity(cpus);
+    }
+}
+
+/// Returns the topology of the machine, discovered on the first call.
+pub fn topology() -> &'static Topology {
+    static TOPOLOGY: OnceLock<Topology> = OnceLock::new();
+    TOPOLOGY.get_or_init(Topology::discover)
+}
+
+#[cfg(target_os = "linux")]
+fn current_cpu() -> Option<usize> {
+    let cpu = unsafe { libc::sched_getcpu() };
+    (cpu >= 0).then_some(cpu as usize)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_cpu() -> Option<usize> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn set_affinity(cpus: &[usize]) {
+    unsafe {
+        let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+        for &cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        let ret = libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set);
+        assert_eq!(ret, 0, "sched_setaffinity failed");
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_cpus: &[usize]) {
+    panic!("Restricting the threads to CPUs is supported only for linux");
+}
+
+/// Formats `cpus`, which are in an increasing order, as a list of ranges, e.g., `0-3,8-11`.
+pub fn cpu_list(cpus: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == cpu => *last = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(first, last)| {
+            if first == last {
+                first.to_string()
+            } else {
+                format!("{first}-{last}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cpu_list, Topology};
+
+    #[test]
+    fn physical_cpus_skip_smt_siblings() {
+        // Two sockets of two cores with two CPUs each, where the siblings are numbered apart.
+        let cores = (0..8).map(|cpu| (cpu, ((cpu / 2) % 2, cpu % 2))).collect();
+        let topology = Topology::from_cores(cores);
+        assert_eq!(topology.cpus(1), Some(&[2, 3, 6, 7][..]));
+        assert_eq!(topology.core_of(6), Some((1, 0)));
+        assert_eq!(topology.available_cpus(None, false).len(), 8);
+        assert_eq!(topology.available_cpus(None, true), [0, 1, 2, 3]);
+        assert_eq!(topology.available_cpus(Some(1), true), [2, 3]);
+        assert_eq!(cpu_list(&topology.available_cpus(None, true)), "0-3");
+        assert_eq!(cpu_list(&[0, 2, 3, 4, 7]), "0,2-4,7");
+    }
+}
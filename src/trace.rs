@@ -0,0 +1,246 @@
+//! Workloads replayed from trace files.
+//!
+//! With `--trace`, the threads replay the operations of a trace instead of sampling them from the
+//! get rate and the key range, so that the key popularity and the mix of the operations follow a
+//! real workload. Each distinct key of the trace is numbered before the prefill, in a random order
+//! since the order of the keys of a trace means nothing for the structure, and the numbers are
+//! folded into the key range if the trace has more distinct keys than the range. A thread that
+//! reaches the end of the trace starts over, so the trace only needs to be long enough to be
+//! representative.
+
+use crate::config::map::Op;
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of operations that a thread takes at once from a shared trace.
+const CHUNK_LEN: usize = 64;
+
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum TraceFormat {
+    /// The operations that the `basic` binding of the YCSB client prints, e.g.,
+    /// `READ usertable user6284781860667377211 [ <all fields>]`.
+    Ycsb,
+    /// `<op>,<key>` lines, where `<op>` is `get`, `insert`, or `remove`.
+    Csv,
+    /// The cache traces of Twitter, whose lines are
+    /// `timestamp,key,key size,value size,client id,operation,TTL`.
+    Twitter,
+}
+
+impl TraceFormat {
+    /// Parses the operation and the key of a line, or returns `None` for a line without an
+    /// operation, such as a header or a status line of the YCSB client.
+    fn parse(self, line: &str) -> Option<(Op, &str)> {
+        match self {
+            TraceFormat::Ycsb => {
+                let mut fields = line.split_whitespace();
+                let op = match fields.next()? {
+                    "READ" | "SCAN" => Op::Get,
+                    "INSERT" | "UPDATE" => Op::Insert,
+                    "DELETE" => Op::Remove,
+                    _ => return None,
+                };
+                // The table, and then the key.
+                Some((op, fields.nth(1)?))
+            }
+            TraceFormat::Csv => {
+                let (op, key) = line.split_once(',')?;
+                let op = match op.trim() {
+                    "get" => Op::Get,
+                    "insert" => Op::Insert,
+                    "remove" => Op::Remove,
+                    _ => return None,
+                };
+                Some((op, key.trim()))
+            }
+            TraceFormat::Twitter => {
+                let mut fields = line.split(',');
+                let key = fields.nth(1)?;
+                let op = match fields.nth(3)? {
+                    "get" | "gets" => Op::Get,
+                    "set" | "add" | "replace" | "cas" | "append" | "prepend" | "incr" | "decr" => {
+                        Op::Insert
+                    }
+                    "delete" => Op::Remove,
+                    _ => return None,
+                };
+                Some((op, key))
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, ValueEnum, Clone, Copy)]
+pub enum TraceSplit {
+    /// The threads take the next operations of the whole trace in turn.
+    Shared,
+    /// Each thread replays its own contiguous part of the trace.
+    Partitioned,
+}
+
+/// The operations of a trace, with the keys numbered in the key range.
+pub struct Trace {
+    ops: Box<[(Op, usize)]>,
+    keys: usize,
+    split: TraceSplit,
+    threads: usize,
+    /// The position of the next chunk of operations to take with `TraceSplit::Shared`.
+    next: AtomicUsize,
+}
+
+impl Trace {
+    /// Loads the trace at `path` to be replayed by `threads` threads over `key_range` keys.
+    pub fn load(
+        path: &str,
+        format: TraceFormat,
+        split: TraceSplit,
+        key_range: usize,
+        threads: usize,
+    ) -> Self {
+        let file = File::open(path).unwrap_or_else(|e| panic!("Failed to open {path}: {e}"));
+        let mut ids = HashMap::<String, usize>::new();
+        let mut ops = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.unwrap_or_else(|e| panic!("Failed to read {path}: {e}"));
+            let Some((op, key)) = format.parse(&line) else {
+                continue;
+            };
+            let id = match ids.get(key) {
+                Some(id) => *id,
+                None => {
+                    let id = ids.len();
+                    ids.insert(key.to_string(), id);
+                    id
+                }
+            };
+            ops.push((op, id));
+        }
+        assert!(
+            ops.len() >= threads,
+            "{path} has {} operations, fewer than the {threads} threads",
+            ops.len()
+        );
+
+        let mut indices = (0..ids.len()).collect::<Vec<_>>();
+        indices.shuffle(&mut StdRng::seed_from_u64(0));
+        for (_, id) in ops.iter_mut() {
+            *id = indices[*id] % key_range;
+        }
+        Self {
+            ops: ops.into(),
+            keys: ids.len(),
+            split,
+            threads,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Creates the cursor of the thread `index`.
+    pub fn cursor(&self, index: usize) -> TraceCursor<'_> {
+        let (begin, end) = match self.split {
+            TraceSplit::Shared => (0, 0),
+            TraceSplit::Partitioned => (
+                index * self.ops.len() / self.threads,
+                (index + 1) * self.ops.len() / self.threads,
+            ),
+        };
+        TraceCursor {
+            trace: self,
+            pos: begin,
+            end,
+            begin,
+        }
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = |op: Op| self.ops.iter().filter(|(o, _)| *o == op).count();
+        write!(
+            f,
+            "{} operations ({} gets, {} inserts, {} removes) on {} keys",
+            self.ops.len(),
+            count(Op::Get),
+            count(Op::Insert),
+            count(Op::Remove),
+            self.keys,
+        )
+    }
+}
+
+/// The position of a thread in a [`Trace`].
+pub struct TraceCursor<'t> {
+    trace: &'t Trace,
+    pos: usize,
+    end: usize,
+    /// The start of the part of the thread with `TraceSplit::Partitioned`.
+    begin: usize,
+}
+
+impl TraceCursor<'_> {
+    /// Returns the next operation and key to run.
+    #[inline]
+    pub fn next_op(&mut self) -> (Op, usize) {
+        if self.pos == self.end {
+            match self.trace.split {
+                TraceSplit::Shared => {
+                    let len = self.trace.ops.len();
+                    self.pos = self.trace.next.fetch_add(CHUNK_LEN, Ordering::Relaxed) % len;
+                    self.end = (self.pos + CHUNK_LEN).min(len);
+                }
+                TraceSplit::Partitioned => self.pos = self.begin,
+            }
+        }
+        let op = self.trace.ops[self.pos];
+        self.pos += 1;
+        op
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Trace, TraceFormat, TraceSplit};
+    use crate::config::map::Op;
+    use std::fs;
+
+    #[test]
+    fn replay_partitioned_ycsb() {
+        let path = std::env::temp_dir().join(format!("trace-{}.txt", std::process::id()));
+        let lines = [
+            "Loading workload...",
+            "READ usertable user1 [ <all fields>]",
+            "UPDATE usertable user2 [ field0=x ]",
+            "READ usertable user1 [ <all fields>]",
+            "DELETE usertable user3",
+            "[OVERALL], RunTime(ms), 10",
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+        let trace = Trace::load(
+            path.to_str().unwrap(),
+            TraceFormat::Ycsb,
+            TraceSplit::Partitioned,
+            100,
+            2,
+        );
+        fs::remove_file(&path).unwrap();
+        assert_eq!(trace.keys, 3);
+
+        let mut first = trace.cursor(0);
+        let (op, user1) = first.next_op();
+        assert_eq!(op, Op::Get);
+        assert_eq!(first.next_op().0, Op::Insert);
+        // The part of the thread starts over.
+        assert_eq!(first.next_op(), (Op::Get, user1));
+
+        let mut second = trace.cursor(1);
+        assert_eq!(second.next_op(), (Op::Get, user1));
+        assert_eq!(second.next_op().0, Op::Remove);
+    }
+}
@@ -9,6 +9,20 @@ macro_rules! ok_or {
     }};
 }
 
+#[macro_export]
+/// Ok or counting the `Retry` of the error and executing the given expression.
+macro_rules! ok_or_retry {
+    ($e:expr, $err:expr) => {{
+        match $e {
+            Ok(r) => r,
+            Err(retry) => {
+                $crate::ds_impl::retry::Retry::count(retry);
+                $err
+            }
+        }
+    }};
+}
+
 #[macro_export]
 /// Some or executing the given expression.
 macro_rules! some_or {